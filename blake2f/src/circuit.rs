@@ -0,0 +1,496 @@
+use crate::{
+    blake2f_arith::{Blake2fArith, CompressionTrace},
+    common::*,
+    gates::{
+        compression::CompressionConfig,
+        gate_helpers::*,
+        layout::LayoutConfig,
+        round::{RoundConfig, RoundWitness},
+    },
+    plain::Blake2fInput,
+};
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression},
+    poly::Rotation,
+};
+
+/// The number of columns of the BLAKE2F table.
+pub const BLAKE2F_TABLE_WIDTH: usize = 3;
+
+/// The number of rows before the first round, which is queried by it as the
+/// last row of a previous compression.
+const NUM_LEADING_ROWS: usize = 1;
+
+/// The number of rows after the last round, which is queried by it as the
+/// first row of a next compression.
+const NUM_TRAILING_ROWS: usize = 1;
+
+/// The compression of an input, which is the witness of [`ROWS_PER_ROUND`]
+/// rows per round.
+#[derive(Clone, Debug)]
+struct CompressionWitness {
+    input: Blake2fInput,
+    trace: CompressionTrace,
+    is_padding: bool,
+}
+
+impl CompressionWitness {
+    fn new(input: &Blake2fInput, is_padding: bool) -> Self {
+        Self {
+            input: input.clone(),
+            trace: Blake2fArith::compress(input),
+            is_padding,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Blake2fConfig<F> {
+    r: F,
+    minimum_rows: usize,
+    layout: LayoutConfig<F>,
+    round: RoundConfig<F>,
+    compression: CompressionConfig<F>,
+    /// The BLAKE2F table, whose columns are the number of rounds, the RLC of
+    /// the words of the input and the RLC of the words of the output.  Only
+    /// the last row of the last round of a compression isn't all zero.
+    pub blake2f_table: [Column<Advice>; BLAKE2F_TABLE_WIDTH],
+}
+
+impl<F: Field> Blake2fConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>, r: F) -> Self {
+        let layout = LayoutConfig::configure(meta);
+        let round = RoundConfig::configure(meta, &layout);
+        let compression = CompressionConfig::configure(meta, &layout, &round);
+        let blake2f_table = [(); BLAKE2F_TABLE_WIDTH].map(|_| meta.advice_column());
+        let q_last = layout.q_output[NUM_OUTPUT_ROWS - 1];
+
+        meta.create_gate("blake2f table", |meta| {
+            let q_enable = meta.query_selector(layout.q_enable);
+            let q_last = meta.query_selector(q_last);
+            blake2f_table
+                .iter()
+                .map(|column| {
+                    (
+                        "table value = 0 when not in the last row of a round",
+                        q_enable.clone()
+                            * (constant(1) - q_last.clone())
+                            * meta.query_advice(*column, Rotation::cur()),
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("blake2f output", |meta| {
+            let q_last = meta.query_selector(q_last);
+            let is_last = meta.query_advice(round.is_last, Rotation::cur());
+            let is_padding = meta.query_advice(round.is_padding, Rotation::cur());
+            let rounds = meta.query_advice(round.rounds, Rotation::cur());
+            let f = meta.query_advice(round.f, Rotation::cur());
+
+            let mut input_words = vec![rounds.clone()];
+            input_words.extend(query(meta, &round.h, 0));
+            input_words.extend(query(meta, &round.m, 0));
+            input_words.extend(query(meta, &round.t, 0));
+            input_words.push(f);
+            // The last row of a round holds the last word of the output,
+            // after the other words at the rotations -7 to -1
+            let output_words = (0..NUM_OUTPUT_ROWS)
+                .map(|idx| {
+                    meta.query_advice(
+                        compression.output,
+                        Rotation(idx as i32 + 1 - NUM_OUTPUT_ROWS as i32),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let is_output = is_last * (constant(1) - is_padding);
+            [rounds, rlc(&input_words, r), rlc(&output_words, r)]
+                .iter()
+                .zip(blake2f_table.iter())
+                .map(|(value, column)| {
+                    (
+                        "table value = is_last && !is_padding ? value : 0",
+                        q_last.clone()
+                            * (meta.query_advice(*column, Rotation::cur())
+                                - is_output.clone() * value.clone()),
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+
+        Self {
+            r,
+            minimum_rows: meta.minimum_rows(),
+            layout,
+            round,
+            compression,
+            blake2f_table,
+        }
+    }
+
+    /// Assign the compressions of `inputs` into a circuit of `size` rows,
+    /// where the rounds left are filled with padding compressions of 0
+    /// rounds.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        size: usize,
+        inputs: &[Blake2fInput],
+    ) -> Result<(), Error> {
+        self.assign_compressions(
+            layouter,
+            size,
+            inputs
+                .iter()
+                .map(|input| CompressionWitness::new(input, false))
+                .collect(),
+        )
+    }
+
+    fn assign_compressions(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        size: usize,
+        mut compressions: Vec<CompressionWitness>,
+    ) -> Result<(), Error> {
+        // Subtract the unusable rows from the size
+        let num_rows = size - self.minimum_rows + 1;
+        let num_rounds = (num_rows - NUM_LEADING_ROWS - NUM_TRAILING_ROWS) / ROWS_PER_ROUND;
+        let num_used_rounds = compressions
+            .iter()
+            .map(|compression| compression.trace.rounds.len())
+            .sum::<usize>();
+        if num_used_rounds > num_rounds {
+            return Err(Error::Synthesis);
+        }
+        let padding = CompressionWitness::new(&Blake2fInput::default(), true);
+        compressions.resize(compressions.len() + num_rounds - num_used_rounds, padding);
+
+        layouter.assign_region(
+            || "blake2f",
+            |mut region| {
+                // The rows out of the rounds are both the first and the last
+                // row of a compression
+                let empty_row = RoundWitness {
+                    is_first: true,
+                    is_last: true,
+                    ..Default::default()
+                };
+                for offset in 0..NUM_LEADING_ROWS {
+                    self.assign_empty_row(&mut region, offset, &empty_row)?;
+                }
+
+                let mut offset = NUM_LEADING_ROWS;
+                for compression in compressions.iter() {
+                    for round_idx in 0..compression.trace.rounds.len() {
+                        self.assign_round(&mut region, offset, compression, round_idx)?;
+                        offset += ROWS_PER_ROUND;
+                    }
+                }
+
+                for offset in offset..num_rows {
+                    self.assign_empty_row(&mut region, offset, &empty_row)?;
+                }
+
+                Ok(())
+            },
+        )
+    }
+
+    fn assign_round(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        compression: &CompressionWitness,
+        round_idx: usize,
+    ) -> Result<(), Error> {
+        let CompressionWitness {
+            input,
+            trace,
+            is_padding,
+        } = compression;
+        let round = &trace.rounds[round_idx];
+        let round_witness = RoundWitness {
+            is_first: round_idx == 0,
+            is_last: round_idx == trace.rounds.len() - 1,
+            is_dummy: input.rounds == 0,
+            is_padding: *is_padding,
+            round_idx,
+            input: input.clone(),
+        };
+        self.layout.assign_round(region, offset)?;
+
+        for row in 0..ROWS_PER_ROUND {
+            let offset = offset + row;
+            self.round.assign_row(region, offset, &round_witness)?;
+
+            if row < NUM_HALF_G {
+                let half_g = &round.half_gs[row];
+                let [_, b, _, d] = G_INDICES[row / 2];
+                self.compression.assign_row(
+                    region,
+                    offset,
+                    &half_g.v,
+                    [half_g.v[d], half_g.v[b], half_g.a, half_g.c],
+                    half_g.a_carry,
+                    half_g.c_carry,
+                    0,
+                )?;
+            } else {
+                // The output is only computed in the last round
+                let idx = row - NUM_HALF_G;
+                let (words, output) = if round_witness.is_last {
+                    (
+                        [
+                            input.h[idx],
+                            round.v[idx],
+                            round.v[idx + NUM_STATE_WORDS],
+                            0,
+                        ],
+                        trace.output[idx],
+                    )
+                } else {
+                    ([0; 4], 0)
+                };
+                self.compression
+                    .assign_row(region, offset, &round.v, words, 0, 0, output)?;
+            }
+
+            let table_row = if row == ROWS_PER_ROUND - 1
+                && round_witness.is_last
+                && !round_witness.is_padding
+            {
+                [
+                    F::from(input.rounds as u64),
+                    input_rlc(input, self.r),
+                    output_rlc(&trace.output, self.r),
+                ]
+            } else {
+                [F::zero(); BLAKE2F_TABLE_WIDTH]
+            };
+            self.assign_table(region, offset, table_row)?;
+        }
+
+        Ok(())
+    }
+
+    /// Assign a row out of the rounds, where only the constraints of the
+    /// table are enabled so that its values are all zero.
+    fn assign_empty_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        round: &RoundWitness,
+    ) -> Result<(), Error> {
+        self.layout.q_enable.enable(region, offset)?;
+        self.round.assign_row(region, offset, round)?;
+        self.compression
+            .assign_row(region, offset, &[0; NUM_WORK_WORDS], [0; 4], 0, 0, 0)?;
+        self.assign_table(region, offset, [F::zero(); BLAKE2F_TABLE_WIDTH])
+    }
+
+    fn assign_table(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        table_row: [F; BLAKE2F_TABLE_WIDTH],
+    ) -> Result<(), Error> {
+        for (idx, (column, value)) in self.blake2f_table.iter().zip(table_row).enumerate() {
+            region.assign_advice(
+                || format!("assign blake2f table {} {}", idx, offset),
+                *column,
+                offset,
+                || Ok(value),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the RLC of `values`, where the first value has the highest power
+/// of `r`.
+fn rlc<F: Field>(values: &[Expression<F>], r: F) -> Expression<F> {
+    values.iter().fold(constant(0), |acc, value| {
+        acc * Expression::Constant(r) + value.clone()
+    })
+}
+
+/// Returns the RLC of the words of `input`, which are the number of rounds,
+/// `h`, `m`, `t` and `f` in this order, where the first word has the highest
+/// power of `r`.
+pub fn input_rlc<F: Field>(input: &Blake2fInput, r: F) -> F {
+    std::iter::once(input.rounds as u64)
+        .chain(input.h.iter().copied())
+        .chain(input.m.iter().copied())
+        .chain(input.t.iter().copied())
+        .chain(std::iter::once(input.f as u64))
+        .fold(F::zero(), |acc, word| acc * r + F::from(word))
+}
+
+/// Returns the RLC of the words of `output`, where the first word has the
+/// highest power of `r`.
+pub fn output_rlc<F: Field>(output: &[u64], r: F) -> F {
+    output
+        .iter()
+        .fold(F::zero(), |acc, word| acc * r + F::from(*word))
+}
+
+/// Returns the row of the BLAKE2F table of `input`.
+pub fn blake2f_table_row<F: Field>(input: &Blake2fInput, r: F) -> [F; BLAKE2F_TABLE_WIDTH] {
+    [
+        F::from(input.rounds as u64),
+        input_rlc(input, r),
+        output_rlc(&input.compress(), r),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use pairing::bn256::Fr;
+
+    #[derive(Default)]
+    struct MyCircuit<F> {
+        compressions: Vec<CompressionWitness>,
+        size: usize,
+        _marker: std::marker::PhantomData<F>,
+    }
+
+    impl<F: Field> MyCircuit<F> {
+        fn r() -> F {
+            F::from(123456)
+        }
+    }
+
+    impl<F: Field> Circuit<F> for MyCircuit<F> {
+        type Config = Blake2fConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            Blake2fConfig::configure(meta, MyCircuit::r())
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.assign_compressions(&mut layouter, self.size, self.compressions.clone())
+        }
+    }
+
+    fn verify<F: Field>(k: u32, compressions: Vec<CompressionWitness>, success: bool) {
+        let circuit = MyCircuit::<F> {
+            compressions,
+            size: 2usize.pow(k),
+            _marker: std::marker::PhantomData,
+        };
+
+        let prover = MockProver::<F>::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify().is_ok(), success);
+    }
+
+    /// Returns the input of the test vectors of EIP-152, which compress the
+    /// block of "abc".
+    fn abc(rounds: u32, f: bool) -> Blake2fInput {
+        let mut input = Blake2fInput {
+            rounds,
+            h: IV,
+            t: [3, 0],
+            f,
+            ..Default::default()
+        };
+        input.h[0] ^= 0x01010040;
+        input.m[0] = 0x636261;
+        input
+    }
+
+    fn compressions(inputs: &[Blake2fInput]) -> Vec<CompressionWitness> {
+        inputs
+            .iter()
+            .map(|input| CompressionWitness::new(input, false))
+            .collect()
+    }
+
+    #[test]
+    fn blake2f_circuit_valid() {
+        let mut input = abc(2, false);
+        input.m = [u64::MAX; NUM_MESSAGE_WORDS];
+        input.t = [u64::MAX; 2];
+        let inputs = vec![
+            abc(0, true),
+            abc(1, true),
+            abc(12, true),
+            abc(12, false),
+            input,
+        ];
+        verify::<Fr>(10, compressions(&inputs), true);
+    }
+
+    #[test]
+    fn blake2f_circuit_empty() {
+        verify::<Fr>(8, vec![], true);
+    }
+
+    #[test]
+    fn blake2f_circuit_invalid_output() {
+        let mut compressions = compressions(&[abc(1, true)]);
+        compressions[0].trace.output[3] ^= 1;
+        verify::<Fr>(8, compressions, false);
+    }
+
+    #[test]
+    fn blake2f_circuit_invalid_rounds() {
+        let mut compressions = compressions(&[abc(2, true)]);
+        compressions[0].input.rounds = 1;
+        verify::<Fr>(8, compressions, false);
+    }
+
+    #[test]
+    fn blake2f_circuit_invalid_flag() {
+        let mut compressions = compressions(&[abc(1, true)]);
+        compressions[0].input.f = false;
+        verify::<Fr>(8, compressions, false);
+    }
+
+    #[test]
+    fn blake2f_circuit_invalid_dummy_round() {
+        // The dummy round of an input of 0 rounds can't skip a round
+        let mut compressions = compressions(&[abc(1, true)]);
+        compressions[0].input.rounds = 0;
+        verify::<Fr>(8, compressions, false);
+    }
+
+    #[test]
+    fn blake2f_table_row_test_vector() {
+        let r = MyCircuit::<Fr>::r();
+        let output = [
+            0x0d4d1c983fa580ba,
+            0xe9f6129fb697276a,
+            0xb7c45a68142f214c,
+            0xd1a2ffdb6fbb124b,
+            0x2d79ab2a39c5877d,
+            0x95cc3345ded552c2,
+            0x5a92f1dba88ad318,
+            0x239900d4ed8623b9,
+        ];
+        let input = abc(12, true);
+        assert_eq!(
+            blake2f_table_row(&input, r),
+            [Fr::from(12), input_rlc(&input, r), output_rlc(&output, r)]
+        );
+    }
+}
@@ -0,0 +1,77 @@
+//! Constants of the compression function F of BLAKE2b, which can be found in
+//! [RFC 7693](https://datatracker.ietf.org/doc/html/rfc7693) and
+//! [EIP-152](https://eips.ethereum.org/EIPS/eip-152), and the layout of a
+//! round in the circuit.
+
+/// The number of bits of a word.
+pub const WORD_SIZE: usize = 64;
+
+/// The number of bytes of the input of the BLAKE2F precompile.
+pub const INPUT_SIZE: usize = 213;
+
+/// The number of words of the state vector `h`.
+pub const NUM_STATE_WORDS: usize = 8;
+
+/// The number of words of the message block vector `m`.
+pub const NUM_MESSAGE_WORDS: usize = 16;
+
+/// The number of words of the local work vector `v`.
+pub const NUM_WORK_WORDS: usize = 16;
+
+/// The initialization vector (section 2.6 of RFC 7693)
+pub static IV: [u64; NUM_STATE_WORDS] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// The permutations of the message words, where the round `i` uses the
+/// permutation `i % 10` (section 2.7 of RFC 7693)
+pub static SIGMA: [[usize; NUM_MESSAGE_WORDS]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// The indices of the words `a`, `b`, `c` and `d` of the local work vector
+/// mixed by the 8 applications of the function G in a round (section 3.2 of
+/// RFC 7693)
+pub static G_INDICES: [[usize; 4]; 8] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+/// The right rotations of the words `d` and `b` in the two halves of the
+/// function G (section 3.1 of RFC 7693)
+pub static G_ROTATIONS: [[usize; 2]; 2] = [[32, 24], [16, 63]];
+
+/// The number of halves of the function G in a round, where the half `k`
+/// belongs to the application `k / 2` of the function G and mixes in the
+/// message word `m[SIGMA[round % 10][k]]`.
+pub const NUM_HALF_G: usize = 16;
+
+/// The number of rows at the end of a round, which hold the words of the
+/// output once the last round is done.
+pub const NUM_OUTPUT_ROWS: usize = NUM_STATE_WORDS;
+
+/// The number of rows of a round, where each half of the function G takes one
+/// row.
+pub const ROWS_PER_ROUND: usize = NUM_HALF_G + NUM_OUTPUT_ROWS;
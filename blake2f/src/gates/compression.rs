@@ -0,0 +1,318 @@
+use super::{
+    gate_helpers::*,
+    layout::LayoutConfig,
+    round::{message_word, RoundConfig},
+};
+use crate::common::*;
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::Region,
+    plonk::{Advice, Column, ConstraintSystem, Error},
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+/// The compression function F, where each of the first [`NUM_HALF_G`] rows
+/// of a round holds the local work vector `v` before a half of the function
+/// G, the bits of its words `d` and `b`, and the bits of its new words `a`
+/// and `c`.  The new words `d` and `b` are rotations of XORs of these bits,
+/// so the local work vector of the next row is fully determined.  The output
+/// rows hold the local work vector after the round, and once the last round
+/// is done, the bits of the words of the state vector and of both halves of
+/// the local work vector which are XORed into the output.
+#[derive(Clone, Debug)]
+pub struct CompressionConfig<F> {
+    pub v: [Column<Advice>; NUM_WORK_WORDS],
+    pub d: [Column<Advice>; WORD_SIZE],
+    pub b: [Column<Advice>; WORD_SIZE],
+    pub a: [Column<Advice>; WORD_SIZE],
+    pub c: [Column<Advice>; WORD_SIZE],
+    /// The carry of the addition of the new word `a`, which adds up 3 words
+    pub a_carry: Column<Advice>,
+    /// The carry of the addition of the new word `c`, which adds up 2 words
+    pub c_carry: Column<Advice>,
+    /// The word of the output computed by an output row
+    pub output: Column<Advice>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> CompressionConfig<F> {
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        layout: &LayoutConfig<F>,
+        round: &RoundConfig<F>,
+    ) -> Self {
+        let v = [(); NUM_WORK_WORDS].map(|_| meta.advice_column());
+        let d = [(); WORD_SIZE].map(|_| meta.advice_column());
+        let b = [(); WORD_SIZE].map(|_| meta.advice_column());
+        let a = [(); WORD_SIZE].map(|_| meta.advice_column());
+        let c = [(); WORD_SIZE].map(|_| meta.advice_column());
+        let a_carry = meta.advice_column();
+        let c_carry = meta.advice_column();
+        let output = meta.advice_column();
+
+        meta.create_gate("blake2f boolean", |meta| {
+            let q_round = meta.query_selector(layout.q_round);
+            let mut constraints = d
+                .iter()
+                .chain(b.iter())
+                .chain(a.iter())
+                .chain(c.iter())
+                .chain(std::iter::once(&c_carry))
+                .map(|column| {
+                    let bit = meta.query_advice(*column, Rotation::cur());
+                    (
+                        "bit is boolean",
+                        q_round.clone() * bit.clone() * (constant(1) - bit),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let a_carry = meta.query_advice(a_carry, Rotation::cur());
+            constraints.push((
+                "a_carry is 0, 1 or 2",
+                q_round
+                    * a_carry.clone()
+                    * (a_carry.clone() - constant(1))
+                    * (a_carry - constant(2)),
+            ));
+            constraints
+        });
+
+        meta.create_gate("blake2f init", |meta| {
+            let q_start = meta.query_selector(layout.q_half_g[0]);
+            // The word `d` of the third row is `v[13]`, which isn't changed
+            // by the first two rows
+            let q_third = meta.query_selector(layout.q_half_g[2]);
+            let is_first = meta.query_advice(round.is_first, Rotation::cur());
+            let v = query(meta, &v, 0);
+            let h = query(meta, &round.h, 0);
+            let t = query(meta, &round.t, 0);
+            let f = meta.query_advice(round.f, Rotation::cur());
+            let d = query(meta, &d, 0);
+
+            let mut constraints = Vec::new();
+            for idx in 0..NUM_STATE_WORDS {
+                constraints.push((
+                    "v[i] = h[i]",
+                    q_start.clone() * (v[idx].clone() - h[idx].clone()),
+                ));
+            }
+            for idx in [0, 1, 2, 3, 7] {
+                constraints.push((
+                    "v[i + 8] = IV[i]",
+                    q_start.clone() * (v[idx + NUM_STATE_WORDS].clone() - constant(IV[idx])),
+                ));
+            }
+            // The word `d` of the first row is `v[12]`
+            constraints.push((
+                "v[12] = IV[4] ^ t[0]",
+                q_start.clone() * (compose(&xor_constant(&d, IV[4])) - t[0].clone()),
+            ));
+            constraints.push((
+                "v[13] = IV[5] ^ t[1]",
+                q_third * (compose(&xor_constant(&d, IV[5])) - t[1].clone()),
+            ));
+            constraints.push((
+                "v[14] = f ? !IV[6] : IV[6]",
+                q_start
+                    * (v[14].clone() - constant(IV[6]) - f * (constant(!IV[6]) - constant(IV[6]))),
+            ));
+
+            // Only before the first round of a compression
+            constraints
+                .into_iter()
+                .map(|(name, constraint)| (name, is_first.clone() * constraint))
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("blake2f half g", |meta| {
+            let is_dummy = meta.query_advice(round.is_dummy, Rotation::cur());
+            let sigma = query(meta, &round.sigma, 0);
+            let m = query(meta, &round.m, 0);
+            let v_next = query(meta, &v, 1);
+            let v = query(meta, &v, 0);
+            let d = query(meta, &d, 0);
+            let b = query(meta, &b, 0);
+            let a = query(meta, &a, 0);
+            let c = query(meta, &c, 0);
+            let a_carry = meta.query_advice(a_carry, Rotation::cur());
+            let c_carry = meta.query_advice(c_carry, Rotation::cur());
+
+            let mut constraints = Vec::new();
+            for k in 0..NUM_HALF_G {
+                let q_half_g = meta.query_selector(layout.q_half_g[k]);
+                let [idx_a, idx_b, idx_c, idx_d] = G_INDICES[k / 2];
+                let [rotation_d, rotation_b] = G_ROTATIONS[k % 2];
+                let x = message_word(&sigma, &m, k);
+
+                let new_a = compose(&a);
+                let new_d = compose(&rotate_right(&xor2(&d, &a), rotation_d));
+                let new_c = compose(&c);
+                let new_b = compose(&rotate_right(&xor2(&b, &c), rotation_b));
+
+                let mut half_g_constraints = vec![
+                    ("bits of d", compose(&d) - v[idx_d].clone()),
+                    ("bits of b", compose(&b) - v[idx_b].clone()),
+                    (
+                        "a' = a + b + x",
+                        new_a.clone() + a_carry.clone() * word_modulus()
+                            - v[idx_a].clone()
+                            - v[idx_b].clone()
+                            - x,
+                    ),
+                    (
+                        "c' = c + d'",
+                        new_c.clone() + c_carry.clone() * word_modulus()
+                            - v[idx_c].clone()
+                            - new_d.clone(),
+                    ),
+                ];
+                // A dummy round leaves the local work vector unchanged
+                for (idx, new_value) in [
+                    (idx_a, new_a),
+                    (idx_b, new_b),
+                    (idx_c, new_c),
+                    (idx_d, new_d),
+                ] {
+                    half_g_constraints.push((
+                        "v'[i] = dummy ? v[i] : new word",
+                        v_next[idx].clone()
+                            - v[idx].clone()
+                            - (constant(1) - is_dummy.clone()) * (new_value - v[idx].clone()),
+                    ));
+                }
+                for idx in (0..NUM_WORK_WORDS).filter(|idx| !G_INDICES[k / 2].contains(idx)) {
+                    half_g_constraints.push(("v'[i] = v[i]", v_next[idx].clone() - v[idx].clone()));
+                }
+
+                constraints.extend(
+                    half_g_constraints
+                        .into_iter()
+                        .map(|(name, constraint)| (name, q_half_g.clone() * constraint)),
+                );
+            }
+            constraints
+        });
+
+        meta.create_gate("blake2f output words", |meta| {
+            let is_last = meta.query_advice(round.is_last, Rotation::cur());
+            let is_first_next = meta.query_advice(round.is_first, Rotation::next());
+            let h = query(meta, &round.h, 0);
+            let v_next = query(meta, &v, 1);
+            let v = query(meta, &v, 0);
+            let d = query(meta, &d, 0);
+            let b = query(meta, &b, 0);
+            let a = query(meta, &a, 0);
+            let output = meta.query_advice(output, Rotation::cur());
+
+            let mut constraints = Vec::new();
+            for idx in 0..NUM_OUTPUT_ROWS {
+                let q_output = meta.query_selector(layout.q_output[idx]);
+                // The output row `i` XORs `h[i]`, `v[i]` and `v[i + 8]` in the
+                // bits of `d`, `b` and `a`
+                let mut output_constraints = vec![
+                    (
+                        "output = d ^ b ^ a",
+                        output.clone() - compose(&xor3(&d, &b, &a)),
+                    ),
+                    (
+                        "d = h[i] in the last round",
+                        is_last.clone() * (compose(&d) - h[idx].clone()),
+                    ),
+                    (
+                        "b = v[i] in the last round",
+                        is_last.clone() * (compose(&b) - v[idx].clone()),
+                    ),
+                    (
+                        "a = v[i + 8] in the last round",
+                        is_last.clone() * (compose(&a) - v[idx + NUM_STATE_WORDS].clone()),
+                    ),
+                ];
+                // The next round of the same compression starts from the local
+                // work vector after this round
+                let is_same_compression = if idx == NUM_OUTPUT_ROWS - 1 {
+                    constant(1) - is_first_next.clone()
+                } else {
+                    constant(1)
+                };
+                for (value_next, value) in v_next.iter().zip(v.iter()) {
+                    output_constraints.push((
+                        "v'[i] = v[i]",
+                        is_same_compression.clone() * (value_next.clone() - value.clone()),
+                    ));
+                }
+
+                constraints.extend(
+                    output_constraints
+                        .into_iter()
+                        .map(|(name, constraint)| (name, q_output.clone() * constraint)),
+                );
+            }
+            constraints
+        });
+
+        Self {
+            v,
+            d,
+            b,
+            a,
+            c,
+            a_carry,
+            c_carry,
+            output,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assign the local work vector, the bits of the words and the carries
+    /// of a row.
+    #[allow(clippy::too_many_arguments)]
+    pub fn assign_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        v: &[u64; NUM_WORK_WORDS],
+        [d, b, a, c]: [u64; 4],
+        a_carry: u64,
+        c_carry: u64,
+        output: u64,
+    ) -> Result<(), Error> {
+        for (idx, column) in self.v.iter().enumerate() {
+            region.assign_advice(
+                || format!("assign v[{}] {}", idx, offset),
+                *column,
+                offset,
+                || Ok(F::from(v[idx])),
+            )?;
+        }
+        for (name, columns, value) in [
+            ("d", &self.d, d),
+            ("b", &self.b, b),
+            ("a", &self.a, a),
+            ("c", &self.c, c),
+        ] {
+            for (idx, column) in columns.iter().enumerate() {
+                region.assign_advice(
+                    || format!("assign {} bit {} {}", name, idx, offset),
+                    *column,
+                    offset,
+                    || Ok(F::from((value >> idx) & 1)),
+                )?;
+            }
+        }
+        for (name, column, value) in [
+            ("a_carry", self.a_carry, a_carry),
+            ("c_carry", self.c_carry, c_carry),
+            ("output", self.output, output),
+        ] {
+            region.assign_advice(
+                || format!("assign {} {}", name, offset),
+                column,
+                offset,
+                || Ok(F::from(value)),
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use crate::common::WORD_SIZE;
+use eth_types::Field;
+use halo2_proofs::{
+    plonk::{Advice, Column, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+pub fn constant<F: Field>(value: u64) -> Expression<F> {
+    Expression::Constant(F::from(value))
+}
+
+/// Returns 2^64, which is the modulus of the additions of words.
+pub fn word_modulus<F: Field>() -> Expression<F> {
+    Expression::Constant(F::from(1 << 32) * F::from(1 << 32))
+}
+
+/// Query the cells of `columns` at `rotation`.
+pub fn query<F: Field>(
+    meta: &mut VirtualCells<'_, F>,
+    columns: &[Column<Advice>],
+    rotation: i32,
+) -> Vec<Expression<F>> {
+    columns
+        .iter()
+        .map(|column| meta.query_advice(*column, Rotation(rotation)))
+        .collect()
+}
+
+/// Returns the value of `bits`, least significant first.
+pub fn compose<F: Field>(bits: &[Expression<F>]) -> Expression<F> {
+    bits.iter()
+        .enumerate()
+        .fold(constant(0), |acc, (idx, bit)| {
+            acc + bit.clone() * constant(1 << idx)
+        })
+}
+
+pub fn rotate_right<F: Field>(bits: &[Expression<F>], n: usize) -> Vec<Expression<F>> {
+    (0..WORD_SIZE)
+        .map(|idx| bits[(idx + n) % WORD_SIZE].clone())
+        .collect()
+}
+
+fn xor<F: Field>(x: Expression<F>, y: Expression<F>) -> Expression<F> {
+    x.clone() + y.clone() - constant(2) * x * y
+}
+
+/// Bitwise `x ^ y`
+pub fn xor2<F: Field>(x: &[Expression<F>], y: &[Expression<F>]) -> Vec<Expression<F>> {
+    (0..WORD_SIZE)
+        .map(|idx| xor(x[idx].clone(), y[idx].clone()))
+        .collect()
+}
+
+/// Bitwise `x ^ y ^ z`
+pub fn xor3<F: Field>(
+    x: &[Expression<F>],
+    y: &[Expression<F>],
+    z: &[Expression<F>],
+) -> Vec<Expression<F>> {
+    xor2(&xor2(x, y), z)
+}
+
+/// Bitwise `x ^ value`, which flips the bits of `x` set in the constant
+/// `value`.
+pub fn xor_constant<F: Field>(x: &[Expression<F>], value: u64) -> Vec<Expression<F>> {
+    (0..WORD_SIZE)
+        .map(|idx| {
+            if (value >> idx) & 1 == 1 {
+                constant(1) - x[idx].clone()
+            } else {
+                x[idx].clone()
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,61 @@
+use crate::common::*;
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::Region,
+    plonk::{ConstraintSystem, Error, Selector},
+};
+use std::marker::PhantomData;
+
+/// Selectors of the layout of the rounds, where a round takes
+/// [`ROWS_PER_ROUND`] rows:
+///
+/// | rows     | content                                                    |
+/// |----------|------------------------------------------------------------|
+/// | `0..16`  | halves of the function G, each mixing in a message word    |
+/// | `16..24` | words of the output, computed from the last round          |
+///
+/// Since every row of a round mixes different words of the local work
+/// vector, each row has its own selector.
+#[derive(Clone, Debug)]
+pub struct LayoutConfig<F> {
+    /// Enabled on all the usable rows, including the ones out of the rounds
+    pub q_enable: Selector,
+    /// Enabled on all the rows of the rounds
+    pub q_round: Selector,
+    /// Enabled on the row `k` of every round, where `q_half_g[0]` is the
+    /// first row of a round
+    pub q_half_g: [Selector; NUM_HALF_G],
+    /// Enabled on the row `NUM_HALF_G + i` of every round, where
+    /// `q_output[NUM_OUTPUT_ROWS - 1]` is the last row of a round
+    pub q_output: [Selector; NUM_OUTPUT_ROWS],
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> LayoutConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        // Complex selectors, since some gates combine several of them
+        Self {
+            q_enable: meta.complex_selector(),
+            q_round: meta.complex_selector(),
+            q_half_g: [(); NUM_HALF_G].map(|_| meta.complex_selector()),
+            q_output: [(); NUM_OUTPUT_ROWS].map(|_| meta.complex_selector()),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Enable the selectors of the round starting at `offset`.
+    pub fn assign_round(&self, region: &mut Region<'_, F>, offset: usize) -> Result<(), Error> {
+        for row in 0..ROWS_PER_ROUND {
+            let offset = offset + row;
+            self.q_enable.enable(region, offset)?;
+            self.q_round.enable(region, offset)?;
+            if row < NUM_HALF_G {
+                self.q_half_g[row].enable(region, offset)?;
+            } else {
+                self.q_output[row - NUM_HALF_G].enable(region, offset)?;
+            }
+        }
+
+        Ok(())
+    }
+}
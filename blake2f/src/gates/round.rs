@@ -0,0 +1,259 @@
+use super::{gate_helpers::*, layout::LayoutConfig};
+use crate::{common::*, plain::Blake2fInput};
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::Region,
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression},
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+/// The number of permutations of the message words.
+pub const NUM_PERMUTATIONS: usize = 10;
+
+/// Witness of the columns which are the same in all the rows of a round.
+#[derive(Clone, Debug, Default)]
+pub struct RoundWitness {
+    pub is_first: bool,
+    pub is_last: bool,
+    pub is_dummy: bool,
+    pub is_padding: bool,
+    pub round_idx: usize,
+    pub input: Blake2fInput,
+}
+
+/// The input of the compression and the flags of a round, which are the same
+/// in all the rows of the round.  The rounds of a compression follow each
+/// other, starting from a round with `is_first` and ending in a round with
+/// `is_last`.  An input of 0 rounds takes a single dummy round, which is both
+/// the first and the last.  The permutation of the message words used by a
+/// round is selected by the one-hot `sigma`.
+#[derive(Clone, Debug)]
+pub struct RoundConfig<F> {
+    pub is_first: Column<Advice>,
+    pub is_last: Column<Advice>,
+    pub is_dummy: Column<Advice>,
+    /// Set in the rounds which aren't the compression of an input, but fill
+    /// the rows left in the circuit
+    pub is_padding: Column<Advice>,
+    pub round_idx: Column<Advice>,
+    pub sigma: [Column<Advice>; NUM_PERMUTATIONS],
+    pub rounds: Column<Advice>,
+    pub h: [Column<Advice>; NUM_STATE_WORDS],
+    pub m: [Column<Advice>; NUM_MESSAGE_WORDS],
+    pub t: [Column<Advice>; 2],
+    pub f: Column<Advice>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> RoundConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>, layout: &LayoutConfig<F>) -> Self {
+        let is_first = meta.advice_column();
+        let is_last = meta.advice_column();
+        let is_dummy = meta.advice_column();
+        let is_padding = meta.advice_column();
+        let round_idx = meta.advice_column();
+        let sigma = [(); NUM_PERMUTATIONS].map(|_| meta.advice_column());
+        let rounds = meta.advice_column();
+        let h = [(); NUM_STATE_WORDS].map(|_| meta.advice_column());
+        let m = [(); NUM_MESSAGE_WORDS].map(|_| meta.advice_column());
+        let t = [(); 2].map(|_| meta.advice_column());
+        let f = meta.advice_column();
+
+        // The columns of the input of the compression, which are the same in
+        // all its rounds
+        let input_columns = [is_padding, rounds, f]
+            .iter()
+            .chain(h.iter())
+            .chain(m.iter())
+            .chain(t.iter())
+            .copied()
+            .collect::<Vec<_>>();
+
+        meta.create_gate("blake2f round boolean", |meta| {
+            let q_round = meta.query_selector(layout.q_round);
+            [is_first, is_last, is_dummy, is_padding, f]
+                .iter()
+                .chain(sigma.iter())
+                .map(|column| {
+                    let flag = meta.query_advice(*column, Rotation::cur());
+                    (
+                        "flag is boolean",
+                        q_round.clone() * flag.clone() * (constant(1) - flag),
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("blake2f round constants", |meta| {
+            // Enabled on all the rows of a round but the first one
+            let q_round_not_first =
+                meta.query_selector(layout.q_round) - meta.query_selector(layout.q_half_g[0]);
+            [is_first, is_last, is_dummy, round_idx]
+                .iter()
+                .chain(sigma.iter())
+                .chain(input_columns.iter())
+                .map(|column| {
+                    (
+                        "value is the same in all the rows of a round",
+                        q_round_not_first.clone()
+                            * (meta.query_advice(*column, Rotation::cur())
+                                - meta.query_advice(*column, Rotation::prev())),
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("blake2f round start", |meta| {
+            let q_start = meta.query_selector(layout.q_half_g[0]);
+            let is_last_prev = meta.query_advice(is_last, Rotation::prev());
+            let is_first = meta.query_advice(is_first, Rotation::cur());
+            let is_last = meta.query_advice(is_last, Rotation::cur());
+            let is_dummy = meta.query_advice(is_dummy, Rotation::cur());
+            let round_idx_prev = meta.query_advice(round_idx, Rotation::prev());
+            let round_idx = meta.query_advice(round_idx, Rotation::cur());
+            let sigma_prev = sigma.map(|column| meta.query_advice(column, Rotation::prev()));
+            let sigma = sigma.map(|column| meta.query_advice(column, Rotation::cur()));
+            let rounds = meta.query_advice(rounds, Rotation::cur());
+
+            let mut constraints = vec![
+                (
+                    "a round is the first one after the last round of a compression",
+                    is_first.clone() - is_last_prev,
+                ),
+                (
+                    "sigma is one-hot",
+                    sigma
+                        .iter()
+                        .fold(constant(0), |acc, flag| acc + flag.clone())
+                        - constant(1),
+                ),
+                (
+                    "round_idx = 0 in the first round",
+                    is_first.clone() * round_idx.clone(),
+                ),
+                (
+                    "the first round uses the first permutation",
+                    is_first.clone() * (constant(1) - sigma[0].clone()),
+                ),
+                (
+                    "round_idx increases by 1 in the other rounds",
+                    (constant(1) - is_first.clone())
+                        * (round_idx.clone() - round_idx_prev - constant(1)),
+                ),
+                (
+                    "a dummy round has 0 rounds",
+                    is_dummy.clone() * rounds.clone(),
+                ),
+                (
+                    "a dummy round is the first round",
+                    is_dummy.clone() * (constant(1) - is_first.clone()),
+                ),
+                (
+                    "a dummy round is the last round",
+                    is_dummy.clone() * (constant(1) - is_last.clone()),
+                ),
+                (
+                    "the last round is the round rounds - 1",
+                    is_last * (constant(1) - is_dummy) * (round_idx + constant(1) - rounds),
+                ),
+            ];
+            for idx in 0..NUM_PERMUTATIONS {
+                constraints.push((
+                    "the other rounds use the next permutation",
+                    (constant(1) - is_first.clone())
+                        * (sigma[idx].clone()
+                            - sigma_prev[(idx + NUM_PERMUTATIONS - 1) % NUM_PERMUTATIONS].clone()),
+                ));
+            }
+            for column in input_columns.iter() {
+                constraints.push((
+                    "the input is the same in all the rounds of a compression",
+                    (constant(1) - is_first.clone())
+                        * (meta.query_advice(*column, Rotation::cur())
+                            - meta.query_advice(*column, Rotation::prev())),
+                ));
+            }
+
+            constraints
+                .into_iter()
+                .map(|(name, constraint)| (name, q_start.clone() * constraint))
+                .collect::<Vec<_>>()
+        });
+
+        Self {
+            is_first,
+            is_last,
+            is_dummy,
+            is_padding,
+            round_idx,
+            sigma,
+            rounds,
+            h,
+            m,
+            t,
+            f,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn assign_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        round: &RoundWitness,
+    ) -> Result<(), Error> {
+        let input = &round.input;
+        let mut values = vec![
+            ("is_first", self.is_first, round.is_first as u64),
+            ("is_last", self.is_last, round.is_last as u64),
+            ("is_dummy", self.is_dummy, round.is_dummy as u64),
+            ("is_padding", self.is_padding, round.is_padding as u64),
+            ("round_idx", self.round_idx, round.round_idx as u64),
+            ("rounds", self.rounds, input.rounds as u64),
+            ("f", self.f, input.f as u64),
+        ];
+        for (idx, column) in self.sigma.iter().enumerate() {
+            values.push((
+                "sigma",
+                *column,
+                (round.round_idx % NUM_PERMUTATIONS == idx) as u64,
+            ));
+        }
+        for (columns, words) in [
+            (&self.h[..], &input.h[..]),
+            (&self.m[..], &input.m[..]),
+            (&self.t[..], &input.t[..]),
+        ] {
+            for (column, word) in columns.iter().zip(words.iter()) {
+                values.push(("input word", *column, *word));
+            }
+        }
+
+        for (name, column, value) in values {
+            region.assign_advice(
+                || format!("assign {} {}", name, offset),
+                column,
+                offset,
+                || Ok(F::from(value)),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the message word mixed in by the half `k` of the function G, which
+/// is selected by the permutation `sigma` of the round.
+pub fn message_word<F: Field>(
+    sigma: &[Expression<F>],
+    m: &[Expression<F>],
+    k: usize,
+) -> Expression<F> {
+    sigma
+        .iter()
+        .zip(SIGMA.iter())
+        .fold(constant(0), |acc, (flag, permutation)| {
+            acc + flag.clone() * m[permutation[k]].clone()
+        })
+}
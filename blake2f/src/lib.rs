@@ -0,0 +1,11 @@
+//! BLAKE2F circuit, which proves the outputs of the compression function F of
+//! BLAKE2b called by the BLAKE2F precompile and exposes them in a lookup
+//! table.
+
+pub mod circuit;
+pub mod common;
+pub mod gates;
+// We build plain module for the purpose of reviewing the circuit
+pub mod plain;
+// We build arith module to get the witness of the circuit
+pub mod blake2f_arith;
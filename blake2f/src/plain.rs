@@ -0,0 +1,170 @@
+use crate::common::*;
+use std::convert::TryInto;
+
+/// Input of the compression function F, as encoded in the 213 bytes of the
+/// input of the BLAKE2F precompile.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Blake2fInput {
+    /// Number of rounds
+    pub rounds: u32,
+    /// State vector
+    pub h: [u64; NUM_STATE_WORDS],
+    /// Message block vector
+    pub m: [u64; NUM_MESSAGE_WORDS],
+    /// Offset counters
+    pub t: [u64; 2],
+    /// Final block indicator flag
+    pub f: bool,
+}
+
+impl Blake2fInput {
+    /// Returns the input encoded in `bytes`, or `None` if they aren't 213
+    /// bytes or the final block indicator flag isn't 0 or 1.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != INPUT_SIZE || bytes[INPUT_SIZE - 1] > 1 {
+            return None;
+        }
+        // The words are encoded in little-endian, unlike the rounds
+        let mut words = bytes[4..INPUT_SIZE - 1]
+            .chunks(8)
+            .map(|word| u64::from_le_bytes(word.try_into().unwrap()));
+        let mut input = Self {
+            rounds: u32::from_be_bytes(bytes[..4].try_into().unwrap()),
+            f: bytes[INPUT_SIZE - 1] == 1,
+            ..Default::default()
+        };
+        for word in input
+            .h
+            .iter_mut()
+            .chain(input.m.iter_mut())
+            .chain(input.t.iter_mut())
+        {
+            *word = words.next().unwrap();
+        }
+        Some(input)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.rounds.to_be_bytes().to_vec();
+        for word in self.h.iter().chain(self.m.iter()).chain(self.t.iter()) {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes.push(self.f as u8);
+        bytes
+    }
+
+    /// Returns the local work vector before the first round.
+    pub fn init(&self) -> [u64; NUM_WORK_WORDS] {
+        let mut v = [0; NUM_WORK_WORDS];
+        v[..NUM_STATE_WORDS].copy_from_slice(&self.h);
+        v[NUM_STATE_WORDS..].copy_from_slice(&IV);
+        v[12] ^= self.t[0];
+        v[13] ^= self.t[1];
+        if self.f {
+            v[14] = !v[14];
+        }
+        v
+    }
+
+    /// Returns the new state vector after the rounds of the compression
+    /// function F.
+    pub fn compress(&self) -> [u64; NUM_STATE_WORDS] {
+        let mut v = self.init();
+        for round in 0..self.rounds as usize {
+            for k in 0..NUM_HALF_G {
+                let x = self.m[SIGMA[round % 10][k]];
+                half_g(&mut v, k, x);
+            }
+        }
+        output(&self.h, &v)
+    }
+}
+
+/// Apply the half `k` of the function G of a round to the local work vector
+/// `v`, which mixes in the message word `x`.
+pub fn half_g(v: &mut [u64; NUM_WORK_WORDS], k: usize, x: u64) {
+    let [a, b, c, d] = G_INDICES[k / 2];
+    let [rotation_d, rotation_b] = G_ROTATIONS[k % 2];
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(rotation_d as u32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(rotation_b as u32);
+}
+
+/// Returns the new state vector, which XORs the state vector `h` with both
+/// halves of the local work vector `v`.
+pub fn output(h: &[u64; NUM_STATE_WORDS], v: &[u64; NUM_WORK_WORDS]) -> [u64; NUM_STATE_WORDS] {
+    let mut output = *h;
+    for (idx, word) in output.iter_mut().enumerate() {
+        *word ^= v[idx] ^ v[idx + NUM_STATE_WORDS];
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|idx| u8::from_str_radix(&hex[idx..idx + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn blake2f(input: &str) -> Vec<u8> {
+        Blake2fInput::from_bytes(&from_hex(input))
+            .unwrap()
+            .compress()
+            .iter()
+            .flat_map(|word| word.to_le_bytes().to_vec())
+            .collect()
+    }
+
+    // The inputs of the test vectors 4 to 7 of EIP-152, which compress the
+    // block of "abc" with 0, 12, 12 and 1 rounds, where the third isn't the
+    // final block
+    const INPUTS: [&str; 4] = [
+        "0000000048c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+        "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+        "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000000",
+        "0000000148c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+    ];
+
+    #[test]
+    fn blake2f_test_vectors() {
+        let outputs = [
+            "08c9bcf367e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d282e6ad7f520e511f6c3e2b8c68059b9442be0454267ce079217e1319cde05b",
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+            "75ab69d3190a562c51aef8d88f1c2775876944407270c42c9844252c26d2875298743e7f6d5ea2f2d3e8d226039cd31b4e426ac4f2d3d666a610c2116fde4735",
+            "b63a380cb2897d521994a85234ee2c181b5f844d2c624c002677e9703449d2fba551b3a8333bcdf5f2f7e08993d53923de3d64fcc68c034e717b9293fed7a421",
+        ];
+        for (input, output) in INPUTS.iter().zip(outputs.iter()) {
+            assert_eq!(blake2f(input), from_hex(output));
+        }
+    }
+
+    #[test]
+    fn blake2f_bytes() {
+        let bytes = from_hex(INPUTS[1]);
+        let input = Blake2fInput::from_bytes(&bytes).unwrap();
+        assert_eq!(input.rounds, 12);
+        assert_eq!(input.m[0], 0x636261);
+        assert_eq!(input.t, [3, 0]);
+        assert!(input.f);
+        assert_eq!(input.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn blake2f_invalid_bytes() {
+        // Test vectors 1 to 3 of EIP-152, whose input is one byte too short,
+        // one byte too long, or whose final block indicator flag is 2
+        let mut bytes = from_hex(INPUTS[1]);
+        assert_eq!(Blake2fInput::from_bytes(&bytes[1..]), None);
+        bytes.insert(0, 0);
+        assert_eq!(Blake2fInput::from_bytes(&bytes), None);
+        bytes.remove(0);
+        bytes[INPUT_SIZE - 1] = 2;
+        assert_eq!(Blake2fInput::from_bytes(&bytes), None);
+    }
+}
@@ -1,19 +1,27 @@
 //! This module contains the CircuitInputBuilder, which is an object that takes
 //! types from geth / web3 and outputs the circuit inputs.
-use crate::evm::opcodes::gen_associated_ops;
+use crate::evm::opcodes::{
+    gen_associated_ops, gen_begin_tx_ops, gen_end_tx_ops, gen_precompile_tx_ops,
+};
 use crate::exec_trace::OperationRef;
 use crate::geth_errors::*;
+use crate::mpt::{MptKey, MptUpdate, StateTrie, EMPTY_ROOT};
 use crate::operation::container::OperationContainer;
 use crate::operation::{
-    AccountField, CallContextField, MemoryOp, Op, OpEnum, Operation, RWCounter, StackOp, Target, RW,
+    AccountField, CallContextField, CallContextOp, MemoryOp, Op, OpEnum, Operation, RWCounter,
+    StackOp, Target, RW,
 };
+use crate::precompile::{is_precompiled, PrecompileCalls};
 use crate::state_db::{self, CodeDB, StateDB};
 use crate::Error;
 use core::fmt::Debug;
 use eth_types::evm_types::{Gas, GasCost, MemoryAddress, OpcodeId, ProgramCounter, StackAddress};
-use eth_types::{self, Address, GethExecStep, GethExecTrace, Hash, ToAddress, ToBigEndian, Word};
-use ethers_core::utils::{get_contract_address, get_create2_address};
-use std::collections::{hash_map::Entry, BTreeMap, HashMap, HashSet};
+use eth_types::{
+    self, AccessList, Address, GethExecStep, GethExecTrace, Hash, ToAddress, ToBigEndian, ToWord,
+    Word,
+};
+use ethers_core::utils::{get_contract_address, get_create2_address, keccak256};
+use std::collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet};
 
 use crate::rpc::GethClient;
 use ethers_providers::JsonRpcClient;
@@ -89,10 +97,47 @@ pub enum ExecError {
     MaxCodeSizeExceeded,
 }
 
+/// Execution state of an [`ExecStep`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExecState {
+    /// Step executing an EVM opcode
+    Op(OpcodeId),
+    /// Internal step beginning a transaction, generated before the steps of
+    /// its execution trace.
+    BeginTx,
+    /// Internal step executing a precompiled contract, generated after a
+    /// *CALL* to it.
+    Precompile(PrecompileCalls),
+    /// Internal step ending a transaction, generated after the steps of its
+    /// execution trace.
+    EndTx,
+}
+
+/// Auxiliary data of an internal [`ExecStep`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepAuxiliaryData {
+    /// Auxiliary data of a [`ExecState::Precompile`] step.
+    Precompile {
+        /// Input of the precompiled contract, read from the memory of the
+        /// caller
+        input: Vec<u8>,
+        /// Output of the precompiled contract, which is empty when it fails
+        output: Vec<u8>,
+    },
+    /// Auxiliary data of a CREATE or CREATE2 step.
+    Create {
+        /// Init code of the contract, read from the memory of the creator
+        init_code: Vec<u8>,
+    },
+}
+
 /// An execution step of the EVM.
 #[derive(Debug)]
 pub struct ExecStep {
-    /// The opcode ID
+    /// Execution state
+    pub exec_state: ExecState,
+    /// The opcode ID, which is STOP in a [`ExecState::BeginTx`] or a
+    /// [`ExecState::EndTx`] step
     pub op: OpcodeId,
     /// Program Counter
     pub pc: ProgramCounter,
@@ -117,9 +162,31 @@ pub struct ExecStep {
     pub bus_mapping_instance: Vec<OperationRef>,
     /// Error generated by this step
     pub error: Option<ExecError>,
+    /// Auxiliary data of internal steps
+    pub aux_data: Option<StepAuxiliaryData>,
 }
 
 impl ExecStep {
+    /// Create the [`ExecState::BeginTx`] step of a transaction, which starts
+    /// with `gas_left` and costs the intrinsic gas `gas_cost`.
+    pub fn begin_tx(call_index: usize, rwc: RWCounter, gas_left: Gas, gas_cost: GasCost) -> Self {
+        ExecStep {
+            exec_state: ExecState::BeginTx,
+            op: OpcodeId::STOP,
+            pc: ProgramCounter(0),
+            stack_size: 0,
+            memory_size: 0,
+            gas_left,
+            gas_cost,
+            call_index,
+            rwc,
+            swc: 0,
+            bus_mapping_instance: Vec::new(),
+            error: None,
+            aux_data: None,
+        }
+    }
+
     /// Create a new Self from a [`GethExecStep`].
     pub fn new(
         step: &GethExecStep,
@@ -128,6 +195,7 @@ impl ExecStep {
         swc: usize, // State Write Counter
     ) -> Self {
         ExecStep {
+            exec_state: ExecState::Op(step.op),
             op: step.op,
             pc: step.pc,
             stack_size: step.stack.0.len(),
@@ -139,10 +207,89 @@ impl ExecStep {
             swc,
             bus_mapping_instance: Vec::new(),
             error: None,
+            aux_data: None,
         }
     }
 }
 
+/// Type of the source or the destination of a [`CopyEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyDataType {
+    /// Memory of a call, which is also where the return data of a call is
+    /// found.
+    Memory = 1,
+    /// Calldata of a transaction.
+    TxCalldata,
+    /// Bytecode of a contract.
+    Bytecode,
+    /// Random linear combination of the copied bytes, which isn't a buffer
+    /// but is looked up by the EVM circuit, like the input and the output of
+    /// a precompile.  Its bytes are neither read nor written with a
+    /// [`MemoryOp`].
+    RlcAcc,
+}
+
+/// Identifier of the source or the destination of a [`CopyEvent`], which is
+/// a transaction or call id, or the hash of a bytecode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberOrHash {
+    /// Id of a transaction or of a call
+    Number(usize),
+    /// Hash of a bytecode
+    Hash(Hash),
+}
+
+/// Copy of `bytes` from a source buffer into the memory of a call or into a
+/// random linear combination, proved by the copy circuit.  Each byte read from
+/// memory and each byte written into memory is a [`MemoryOp`], the first of
+/// them at `rw_counter_start`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CopyEvent {
+    /// Type of the source
+    pub src_type: CopyDataType,
+    /// Id of the source
+    pub src_id: NumberOrHash,
+    /// Address in the source of the first copied byte, which is at most
+    /// `src_addr_end`
+    pub src_addr: u64,
+    /// End of the source buffer, the bytes at or after it are read as 0
+    pub src_addr_end: u64,
+    /// Type of the destination
+    pub dst_type: CopyDataType,
+    /// Id of the destination
+    pub dst_id: NumberOrHash,
+    /// Address in the destination of the first copied byte
+    pub dst_addr: u64,
+    /// Read/write counter of the first operation of the copy
+    pub rw_counter_start: RWCounter,
+    /// Copied bytes
+    pub bytes: Vec<u8>,
+}
+
+impl CopyEvent {
+    /// Return the number of bytes read from the source buffer, the other ones
+    /// are read as 0.
+    pub fn src_bytes(&self) -> u64 {
+        (self.src_addr_end - self.src_addr).min(self.bytes.len() as u64)
+    }
+
+    /// Return the number of read/write operations done by the copy, which
+    /// reads the source and writes the destination when they're memory.
+    pub fn rw_counter_increase(&self) -> u64 {
+        let reads = if self.src_type == CopyDataType::Memory {
+            self.src_bytes()
+        } else {
+            0
+        };
+        let writes = if self.dst_type == CopyDataType::Memory {
+            self.bytes.len() as u64
+        } else {
+            0
+        };
+        reads + writes
+    }
+}
+
 /// Context of a [`Block`] which can mutate in a [`Transaction`].
 #[derive(Debug)]
 pub struct BlockContext {
@@ -153,6 +300,9 @@ pub struct BlockContext {
     /// in Block.txs and call_index is the index used in Transaction.
     /// calls).
     call_map: HashMap<usize, (usize, usize)>,
+    /// Accounts destructed in the block, whose storage is removed from the
+    /// state trie.
+    destructed_accounts: BTreeSet<Address>,
 }
 
 impl Default for BlockContext {
@@ -167,6 +317,7 @@ impl BlockContext {
         Self {
             rwc: RWCounter::new(),
             call_map: HashMap::new(),
+            destructed_accounts: BTreeSet::new(),
         }
     }
 }
@@ -193,6 +344,18 @@ pub struct Block {
     pub base_fee: Word,
     /// Container of operations done in this block.
     pub container: OperationContainer,
+    /// Copies done in this block, proved by the copy circuit.
+    pub copy_events: Vec<CopyEvent>,
+    /// Inputs hashed by KECCAK256 in this block, such as the preimages of
+    /// contract addresses, proved by the Keccak circuit.
+    pub sha3_inputs: Vec<Vec<u8>>,
+    /// State root before this block
+    pub prev_state_root: Hash,
+    /// State root after this block
+    pub state_root: Hash,
+    /// Updates of the state trie for the accounts and storage accessed in
+    /// this block, proved by the MPT circuit.
+    pub mpt_updates: Vec<MptUpdate>,
     txs: Vec<Transaction>,
     code: HashMap<Hash, Vec<u8>>,
 }
@@ -225,6 +388,11 @@ impl Block {
             difficulty: eth_block.difficulty,
             base_fee: eth_block.base_fee_per_gas.unwrap_or_default(),
             container: OperationContainer::new(),
+            copy_events: Vec::new(),
+            sha3_inputs: Vec::new(),
+            prev_state_root: Hash::zero(),
+            state_root: Hash::zero(),
+            mpt_updates: Vec::new(),
             txs: Vec::new(),
             code: HashMap::new(),
         })
@@ -239,6 +407,15 @@ impl Block {
     pub fn txs_mut(&mut self) -> &mut Vec<Transaction> {
         &mut self.txs
     }
+    /// Push a copy done in this block.
+    pub fn add_copy_event(&mut self, event: CopyEvent) {
+        self.copy_events.push(event);
+    }
+
+    /// Push an input hashed by KECCAK256 in this block.
+    pub fn add_sha3_input(&mut self, input: Vec<u8>) {
+        self.sha3_inputs.push(input);
+    }
 }
 
 /// Type of a *CALL*/CREATE* Function.
@@ -319,6 +496,12 @@ pub struct Call {
     pub return_data_offset: u64,
     /// Return data length
     pub return_data_length: u64,
+    /// Id of the last callee, or 0 if no call has been made from this call
+    pub last_callee_id: usize,
+    /// Offset of the return data of the last callee in its memory
+    pub last_callee_return_data_offset: u64,
+    /// Length of the return data of the last callee
+    pub last_callee_return_data_length: u64,
 }
 
 impl Call {
@@ -370,6 +553,12 @@ pub struct TransactionContext {
     /// in the inner most revert (which we track with the last element in
     /// the reversion groups stack), and skip it in the outer revert.
     reversion_groups: Vec<ReversionGroup>,
+    /// Internal steps generated by the step being handled, which are
+    /// appended to the transaction right after it.
+    internal_steps: Vec<ExecStep>,
+    /// Gas left after the intrinsic gas, and then after the return of the
+    /// root call, which is refunded at the end of the transaction.
+    gas_left: u64,
 }
 
 impl TransactionContext {
@@ -407,6 +596,8 @@ impl TransactionContext {
             call_is_success,
             calls: Vec::new(),
             reversion_groups: Vec::new(),
+            internal_steps: Vec::new(),
+            gas_left: 0,
         };
         tx_ctx.push_call_ctx(0);
 
@@ -468,6 +659,14 @@ impl TransactionContext {
     }
 }
 
+/// Maximum number of addresses in the access list of a transaction supported
+/// by the circuits, counting the repeated ones.
+pub const MAX_ACCESS_LIST_ADDRESSES: usize = 4;
+
+/// Maximum number of storage keys in the access list of a transaction
+/// supported by the circuits, counting the repeated ones.
+pub const MAX_ACCESS_LIST_STORAGE_KEYS: usize = 4;
+
 #[derive(Debug)]
 /// Result of the parsing of an Ethereum Transaction.
 pub struct Transaction {
@@ -477,14 +676,21 @@ pub struct Transaction {
     pub gas: u64,
     /// Gas price
     pub gas_price: Word,
+    /// Gas fee cap, which equals the gas price for legacy transactions
+    pub gas_fee_cap: Word,
+    /// Gas tip cap, which equals the gas price for legacy transactions
+    pub gas_tip_cap: Word,
     /// From / Caller Address
     pub from: Address, // caller_address
-    /// To / Callee Address
+    /// To / Callee Address, which is the address of the created contract in
+    /// a creation transaction
     pub to: Address, // callee_address
     /// Value
     pub value: Word,
     /// Input / Call Data
     pub input: Vec<u8>, // call_data
+    /// Access list (EIP-2930)
+    pub access_list: AccessList,
     calls: Vec<Call>,
     steps: Vec<ExecStep>,
 }
@@ -503,6 +709,21 @@ impl Transaction {
             return Err(Error::AccountNotFound(eth_tx.from));
         }
 
+        let access_list = eth_tx.access_list.clone().unwrap_or_default();
+        let storage_keys_len = access_list
+            .0
+            .iter()
+            .map(|item| item.storage_keys.len())
+            .sum();
+        if access_list.0.len() > MAX_ACCESS_LIST_ADDRESSES
+            || storage_keys_len > MAX_ACCESS_LIST_STORAGE_KEYS
+        {
+            return Err(Error::AccessListTooLarge(
+                access_list.0.len(),
+                storage_keys_len,
+            ));
+        }
+
         let call = if let Some(address) = eth_tx.to {
             // Contract Call / Transfer
             let (found, account) = sdb.get_account(&address);
@@ -529,6 +750,9 @@ impl Transaction {
                 call_data_length: eth_tx.input.as_ref().len() as u64,
                 return_data_offset: 0,
                 return_data_length: 0,
+                last_callee_id: 0,
+                last_callee_return_data_offset: 0,
+                last_callee_return_data_length: 0,
             }
         } else {
             // Contract creation
@@ -552,17 +776,24 @@ impl Transaction {
                 call_data_length: 0,
                 return_data_offset: 0,
                 return_data_length: 0,
+                last_callee_id: 0,
+                last_callee_return_data_offset: 0,
+                last_callee_return_data_length: 0,
             }
         };
 
+        let gas_price = eth_tx.gas_price.unwrap_or_default();
         Ok(Self {
             nonce: eth_tx.nonce.as_u64(),
             gas: eth_tx.gas.as_u64(),
-            gas_price: eth_tx.gas_price.unwrap_or_default(),
+            gas_price,
+            gas_fee_cap: eth_tx.max_fee_per_gas.unwrap_or(gas_price),
+            gas_tip_cap: eth_tx.max_priority_fee_per_gas.unwrap_or(gas_price),
             from: eth_tx.from,
-            to: eth_tx.to.unwrap_or_default(),
+            to: call.address,
             value: eth_tx.value,
             input: eth_tx.input.to_vec(),
+            access_list,
             calls: vec![call],
             steps: Vec::new(),
         })
@@ -655,6 +886,18 @@ impl<'a> CircuitInputStateRef<'a> {
         }
     }
 
+    /// Push an [`Operation`] into the [`OperationContainer`] with the next
+    /// [`RWCounter`] and then adds a reference to the stored operation
+    /// ([`OperationRef`]) inside the bus-mapping instance of the given
+    /// internal `step`.  Then increase the block_ctx [`RWCounter`] by one.
+    pub fn push_op_to_step<T: Op>(&mut self, step: &mut ExecStep, rw: RW, op: T) {
+        let op_ref =
+            self.block
+                .container
+                .insert(Operation::new(self.block_ctx.rwc.inc_pre(), rw, op));
+        step.bus_mapping_instance.push(op_ref);
+    }
+
     /// Push a [`MemoryOp`] into the [`OperationContainer`] with the next
     /// [`RWCounter`] and `call_id`, and then adds a reference to
     /// the stored operation ([`OperationRef`]) inside the bus-mapping
@@ -689,6 +932,29 @@ impl<'a> CircuitInputStateRef<'a> {
         );
     }
 
+    /// Create a new internal [`ExecStep`] in the current call, which is
+    /// executed after the current step and before `next_step`.  The internal
+    /// step shares the program counter, stack, memory and gas left of
+    /// `next_step` and doesn't cost any gas.
+    pub fn new_internal_step(&self, exec_state: ExecState, next_step: &GethExecStep) -> ExecStep {
+        let mut step = ExecStep::new(
+            next_step,
+            self.tx_ctx.call_index(),
+            self.block_ctx.rwc,
+            self.call_ctx().swc,
+        );
+        step.exec_state = exec_state;
+        step.op = self.step.op;
+        step.gas_cost = GasCost::ZERO;
+        step
+    }
+
+    /// Push an internal [`ExecStep`] which will be placed in the
+    /// [`Transaction`] right after the current step.
+    pub fn push_internal_step(&mut self, step: ExecStep) {
+        self.tx_ctx.internal_steps.push(step);
+    }
+
     /// Reference to the current Call
     pub fn call(&self) -> &Call {
         &self.tx.calls[self.tx_ctx.call_index()]
@@ -724,7 +990,7 @@ impl<'a> CircuitInputStateRef<'a> {
 
     /// Return the contract address of a CREATE step.  This is calculated by
     /// inspecting the current address and its nonce from the StateDB.
-    fn create_address(&self) -> Result<Address, Error> {
+    pub(crate) fn create_address(&self) -> Result<Address, Error> {
         let sender = self.call().address;
         let (found, account) = self.sdb.get_account(&sender);
         if !found {
@@ -735,7 +1001,7 @@ impl<'a> CircuitInputStateRef<'a> {
 
     /// Return the contract address of a CREATE2 step.  This is calculated
     /// deterministically from the arguments in the stack.
-    fn create2_address(&self, step: &GethExecStep) -> Result<Address, Error> {
+    pub(crate) fn create2_address(&self, step: &GethExecStep) -> Result<Address, Error> {
         let salt = step.stack.nth_last(3)?;
         let init_code = get_create_init_code(step)?;
         Ok(get_create2_address(
@@ -796,8 +1062,10 @@ impl<'a> CircuitInputStateRef<'a> {
                     }
                     _ => address,
                 };
+                // Precompiled contracts can be called without existing in
+                // the state, in which case they have no code.
                 let (found, account) = self.sdb.get_account(&code_address);
-                if !found {
+                if !found && !is_precompiled(&code_address) {
                     return Err(Error::AccountNotFound(code_address));
                 }
                 (CodeSource::Address(code_address), account.code_hash)
@@ -847,11 +1115,32 @@ impl<'a> CircuitInputStateRef<'a> {
             call_data_length,
             return_data_offset,
             return_data_length,
+            last_callee_id: 0,
+            last_callee_return_data_offset: 0,
+            last_callee_return_data_length: 0,
         };
 
         Ok(call)
     }
 
+    /// Parse the [`Call`] of a *CALL*/CREATE* `step` to a precompiled
+    /// contract or an account without code, which ends with `is_success`, and
+    /// push it into the [`Transaction`].  Geth doesn't step into these calls,
+    /// so their result is not known in advance by the [`TransactionContext`]
+    /// and it's inserted here.
+    pub fn push_untraced_call(
+        &mut self,
+        step: &GethExecStep,
+        is_success: bool,
+    ) -> Result<Call, Error> {
+        self.tx_ctx
+            .call_is_success
+            .insert(self.tx.calls().len(), is_success);
+        let call = self.parse_call(step)?;
+        self.push_call(call.clone());
+        Ok(call)
+    }
+
     /// Return the reverted version of an op by op_ref only if the original op
     /// was reversible.
     fn get_rev_op_by_ref(&self, op_ref: &OperationRef) -> Option<OpEnum> {
@@ -956,8 +1245,24 @@ impl<'a> CircuitInputStateRef<'a> {
                     op,
                 ))
             }
-            OpEnum::TxRefund(_) => unimplemented!(),
-            OpEnum::AccountDestructed(_) => unimplemented!(),
+            OpEnum::AccountDestructed(op) => {
+                if !op.value {
+                    self.sdb.remove_destructed_account(&op.address);
+                }
+                self.block.container.insert(Operation::new(
+                    self.block_ctx.rwc.inc_pre(),
+                    RW::WRITE,
+                    op,
+                ))
+            }
+            OpEnum::TxRefund(op) => {
+                self.sdb.set_refund(op.value.as_u64());
+                self.block.container.insert(Operation::new(
+                    self.block_ctx.rwc.inc_pre(),
+                    RW::WRITE,
+                    op,
+                ))
+            }
             _ => unreachable!(),
         }
     }
@@ -988,16 +1293,199 @@ impl<'a> CircuitInputStateRef<'a> {
     }
 
     /// Handle a return step caused by any opcode that causes a return to the
-    /// previous call context.
-    pub fn handle_return(&mut self) -> Result<(), Error> {
+    /// previous call context.  `steps` starts with the return step, which is
+    /// followed by the step where the caller resumes, if there's any caller.
+    pub fn handle_return(&mut self, steps: &[GethExecStep]) -> Result<(), Error> {
+        let step = &steps[0];
+        let next_step = steps.get(1);
+        if !self.call().is_root && next_step.is_none() {
+            return Err(Error::InvalidGethExecStep(
+                "return to the caller without next step",
+                step.clone(),
+            ));
+        }
+
+        // The return data is only set by RETURN or REVERT, except for a
+        // successful CREATE*, where RETURN provides the deployed code instead.
+        let call = self.call();
+        let (return_data_offset, return_data_length) = match step.op {
+            OpcodeId::RETURN if call.is_create() => (0, 0),
+            OpcodeId::RETURN | OpcodeId::REVERT => {
+                let offset = step.stack.nth_last(0)?;
+                let length = step.stack.nth_last(1)?;
+                if length.is_zero() {
+                    (0, 0)
+                } else {
+                    (offset.low_u64(), length.low_u64())
+                }
+            }
+            _ => (0, 0),
+        };
+
+        // The gas left after the step is returned to the caller, unless the
+        // call fails with an error other than REVERT, which consumes it all.
+        // A successful CREATE* also pays for every byte of the deployed code.
+        let code_deposit_cost = match step.op {
+            OpcodeId::RETURN if call.is_create() && call.is_success => {
+                GasCost::CODE_DEPOSIT_BYTE_COST.as_u64() * get_return_code(step)?.len() as u64
+            }
+            _ => 0,
+        };
+        let gas_returned = match self.step.error {
+            None | Some(ExecError::Reverted) => {
+                step.gas.0 - step.gas_cost.as_u64() - code_deposit_cost
+            }
+            Some(_) => 0,
+        };
+
+        self.return_to_caller(
+            None,
+            next_step,
+            return_data_offset,
+            return_data_length,
+            gas_returned,
+        );
+
+        Ok(())
+    }
+
+    /// Handle the return of a call pushed by [`Self::push_untraced_call`], or
+    /// of a root call without execution trace, whose `output_length` bytes of
+    /// output are its return data, placed at the start of its memory.  The
+    /// call context of the caller, which resumes at `next_step` with
+    /// `gas_returned` more gas, is restored by `exec_step` if given, or by
+    /// the current step otherwise.  A root call has no `next_step`.
+    pub fn handle_untraced_return(
+        &mut self,
+        exec_step: Option<&mut ExecStep>,
+        next_step: Option<&GethExecStep>,
+        output_length: u64,
+        gas_returned: u64,
+    ) {
+        self.return_to_caller(exec_step, next_step, 0, output_length, gas_returned);
+    }
+
+    /// Apply the reversion of the current call if it fails, and return to the
+    /// previous call context with the given return data.
+    fn return_to_caller(
+        &mut self,
+        exec_step: Option<&mut ExecStep>,
+        next_step: Option<&GethExecStep>,
+        return_data_offset: u64,
+        return_data_length: u64,
+        gas_returned: u64,
+    ) {
         // Handle reversion if this call doens't end successfully
         if !self.call().is_success {
             self.handle_reversion();
         }
 
+        let callee_id = self.call().call_id;
+        if self.call().is_root {
+            self.tx_ctx.gas_left = gas_returned;
+        }
+
+        // The caller resumes at the next step with its step state, which is
+        // read from its call context, and the gas returned by the callee.
+        if let (false, Some(next_step)) = (self.call().is_root, next_step) {
+            self.gen_restore_context_ops(
+                exec_step,
+                next_step,
+                return_data_offset,
+                return_data_length,
+                gas_returned,
+            );
+        }
+
         self.tx_ctx.pop_call_ctx();
 
-        Ok(())
+        // Update the last callee of the caller, if there's any
+        if !self.tx_ctx.calls.is_empty() {
+            let caller = self.call_mut();
+            caller.last_callee_id = callee_id;
+            caller.last_callee_return_data_offset = return_data_offset;
+            caller.last_callee_return_data_length = return_data_length;
+        }
+    }
+
+    /// Generate the [`CallContextOp`]s which restore the call context of the
+    /// caller of the current call: the read of the caller id, the reads of
+    /// the step state the caller resumes with at `next_step`, and the writes
+    /// of its last callee.
+    fn gen_restore_context_ops(
+        &mut self,
+        mut exec_step: Option<&mut ExecStep>,
+        next_step: &GethExecStep,
+        return_data_offset: u64,
+        return_data_length: u64,
+        gas_returned: u64,
+    ) {
+        let callee_id = self.call().call_id;
+        let caller_ctx = &self.tx_ctx.calls[self.tx_ctx.calls.len() - 2];
+        let caller_swc = caller_ctx.swc;
+        let caller = self.tx.calls[caller_ctx.index].clone();
+
+        let ops = std::iter::once((
+            RW::READ,
+            callee_id,
+            CallContextField::CallerId,
+            caller.call_id.into(),
+        ))
+        .chain(
+            [
+                (CallContextField::IsRoot, (caller.is_root as u64).into()),
+                (
+                    CallContextField::IsCreate,
+                    (caller.is_create() as u64).into(),
+                ),
+                (CallContextField::CodeSource, caller.code_hash.to_word()),
+                (
+                    CallContextField::ProgramCounter,
+                    usize::from(next_step.pc).into(),
+                ),
+                (
+                    CallContextField::StackPointer,
+                    (1024 - next_step.stack.0.len()).into(),
+                ),
+                (
+                    CallContextField::GasLeft,
+                    (next_step.gas.0 - gas_returned).into(),
+                ),
+                (
+                    CallContextField::MemorySize,
+                    (next_step.memory.0.len() / 32).into(),
+                ),
+                (CallContextField::StateWriteCounter, caller_swc.into()),
+            ]
+            .map(|(field, value)| (RW::READ, caller.call_id, field, value)),
+        )
+        .chain(
+            [
+                (CallContextField::LastCalleeId, callee_id.into()),
+                (
+                    CallContextField::LastCalleeReturnDataOffset,
+                    return_data_offset.into(),
+                ),
+                (
+                    CallContextField::LastCalleeReturnDataLength,
+                    return_data_length.into(),
+                ),
+            ]
+            .map(|(field, value)| (RW::WRITE, caller.call_id, field, value)),
+        )
+        .collect::<Vec<(RW, usize, CallContextField, Word)>>();
+
+        for (rw, call_id, field, value) in ops {
+            let op = CallContextOp {
+                call_id,
+                field,
+                value,
+            };
+            match exec_step.as_deref_mut() {
+                Some(exec_step) => self.push_op_to_step(exec_step, rw, op),
+                None => self.push_op(rw, op),
+            }
+        }
     }
 
     fn get_step_err(
@@ -1013,8 +1501,18 @@ impl<'a> CircuitInputStateRef<'a> {
             return Ok(Some(ExecError::InvalidOpcode));
         }
 
-        // When last step is RETURN or STOP there's no error.
-        if matches!(next_step, None) && matches!(step.op, OpcodeId::RETURN | OpcodeId::STOP) {
+        // When last step is RETURN or STOP there's no error, unless the RETURN
+        // of a creation transaction fails to deploy its code.
+        if matches!(next_step, None)
+            && matches!(step.op, OpcodeId::RETURN | OpcodeId::STOP)
+            && (self.call().is_success || !self.call().is_create())
+        {
+            return Ok(None);
+        }
+
+        // When last step of a successful transaction is not RETURN or STOP,
+        // the execution runs out of the code, which is an implicit STOP.
+        if matches!(next_step, None) && self.call().is_success {
             return Ok(None);
         }
 
@@ -1023,8 +1521,11 @@ impl<'a> CircuitInputStateRef<'a> {
             .map(|s| s.stack.last().unwrap_or_else(|_| Word::zero()))
             .unwrap_or_else(Word::zero);
 
+        // The next step is in the caller, unless this step enters a callee.
+        let is_return = step.depth == next_depth + 1;
+
         // Return from a call with a failure
-        if step.depth != next_depth && next_result.is_zero() {
+        if is_return && next_result.is_zero() {
             if !matches!(step.op, OpcodeId::RETURN) {
                 // Without calling RETURN
                 return Ok(Some(match step.op {
@@ -1057,8 +1558,9 @@ impl<'a> CircuitInputStateRef<'a> {
                 // Calling RETURN
                 let call = self.call();
 
-                // Return from a {CREATE, CREATE2} with a failure, via RETURN
-                if !call.is_root && call.is_create() {
+                // Return from a {CREATE, CREATE2} or a creation transaction
+                // with a failure, via RETURN
+                if call.is_create() {
                     let offset = step.stack.nth_last(0)?;
                     let length = step.stack.nth_last(1)?;
                     if length > Word::from(0x6000u64) {
@@ -1090,7 +1592,7 @@ impl<'a> CircuitInputStateRef<'a> {
 
         // Return from a call without calling RETURN or STOP and having success
         // is unexpected.
-        if step.depth != next_depth
+        if is_return
             && next_result != Word::zero()
             && !matches!(step.op, OpcodeId::RETURN | OpcodeId::STOP)
         {
@@ -1138,6 +1640,14 @@ impl<'a> CircuitInputStateRef<'a> {
                 return Ok(Some(ExecError::InsufficientBalance));
             }
 
+            // A precompiled contract is not executed as code, so its failure
+            // is not an error of the *CALL*
+            if !matches!(step.op, OpcodeId::CREATE | OpcodeId::CREATE2)
+                && is_precompiled(&step.stack.nth_last(1)?.to_address())
+            {
+                return Ok(None);
+            }
+
             // Address collision
             if matches!(step.op, OpcodeId::CREATE | OpcodeId::CREATE2) {
                 let address = match step.op {
@@ -1189,17 +1699,23 @@ pub struct CircuitInputBuilder {
     pub block: Block,
     /// Block Context
     pub block_ctx: BlockContext,
+    /// Tries of the state before the block, which are updated by
+    /// [`Self::gen_mpt_updates`].  They're built from the whole `sdb`, so the
+    /// ones built from proofs replace them when `sdb` is partial.
+    pub state_trie: StateTrie,
 }
 
 impl<'a> CircuitInputBuilder {
     /// Create a new CircuitInputBuilder from the given `eth_block` and
     /// `constants`.
     pub fn new(sdb: StateDB, code_db: CodeDB, block: Block) -> Self {
+        let state_trie = StateTrie::from_state_db(&sdb);
         Self {
             sdb,
             code_db,
             block,
             block_ctx: BlockContext::new(),
+            state_trie,
         }
     }
 
@@ -1266,6 +1782,175 @@ impl<'a> CircuitInputBuilder {
         }
     }
 
+    /// Update the state trie with the final values of the accounts and storage
+    /// slots accessed in the block, and register each of the updates with
+    /// the state roots before and after the block.  The accesses that are
+    /// only reads give updates that keep the root, proving the value read.
+    pub fn gen_mpt_updates(&mut self) -> Result<(), Error> {
+        // The storage of a destructed account is removed before the writes of
+        // its storage slots, which follow its recreation if there's any.
+        let keys: BTreeSet<(Address, MptKey)> = self
+            .block_ctx
+            .destructed_accounts
+            .iter()
+            .map(|address| (*address, MptKey::StorageRoot))
+            .chain(
+                self.block
+                    .container
+                    .storage
+                    .iter()
+                    .map(|op| (op.op().address, MptKey::Storage(op.op().key))),
+            )
+            .chain(
+                self.block
+                    .container
+                    .account
+                    .iter()
+                    .map(|op| (op.op().address, MptKey::Account(op.op().field.clone()))),
+            )
+            .collect();
+
+        self.block.prev_state_root = self.state_trie.root();
+        for (address, key) in keys {
+            let (_, account) = self.sdb.get_account(&address);
+            let value = match &key {
+                MptKey::StorageRoot => EMPTY_ROOT.to_word(),
+                MptKey::Storage(key) => *self.sdb.get_storage(&address, key).1,
+                MptKey::Account(AccountField::Nonce) => account.nonce,
+                MptKey::Account(AccountField::Balance) => account.balance,
+                MptKey::Account(AccountField::CodeHash) => account.code_hash.to_word(),
+            };
+            let update = self.state_trie.update(address, key, value)?;
+            self.block.mpt_updates.push(update);
+        }
+        self.block.state_root = self.state_trie.root();
+        Ok(())
+    }
+
+    /// Generate the [`ExecState::BeginTx`] step of `tx`, which buys its gas,
+    /// pre-warms its access list, transfers its value and sets up the context
+    /// of its root call, before the steps of its execution trace.  It's
+    /// followed by the [`ExecState::Precompile`] step of the root call when
+    /// the callee is a precompiled contract, and it ends the root call of a
+    /// creation transaction on the address collision.
+    pub fn begin_tx(
+        &mut self,
+        tx: &mut Transaction,
+        tx_ctx: &mut TransactionContext,
+    ) -> Result<(), Error> {
+        // The access list and the gas refund counter are scoped to the
+        // transaction.
+        self.sdb.clear_access_list();
+        self.sdb.clear_refund();
+
+        let call_data_gas_cost = tx
+            .input
+            .iter()
+            .fold(0, |acc, byte| acc + if *byte == 0 { 4 } else { 16 });
+        let access_list_gas_cost = tx.access_list.0.iter().fold(0, |acc, item| {
+            acc + GasCost::ACCESS_LIST_PER_ADDRESS.as_u64()
+                + item.storage_keys.len() as u64 * GasCost::ACCESS_LIST_PER_STORAGE_KEY.as_u64()
+        });
+        let intrinsic_gas_cost = if tx.is_create() {
+            GasCost::CREATION_TX
+        } else {
+            GasCost::TX
+        }
+        .as_u64()
+            + call_data_gas_cost
+            + access_list_gas_cost;
+
+        let mut step = ExecStep::begin_tx(
+            tx_ctx.call_index(),
+            self.block_ctx.rwc,
+            Gas(tx.gas),
+            GasCost::from(intrinsic_gas_cost),
+        );
+        let mut state_ref = self.state_ref(tx, tx_ctx, &mut step);
+        gen_begin_tx_ops(&mut state_ref)?;
+        tx.steps.push(step);
+        tx_ctx.gas_left = tx.gas - intrinsic_gas_cost;
+
+        // A creation transaction fails on the address collision, where the
+        // created contract already has a nonce or code, without execution
+        // trace.  All its gas is consumed, and the transfer of its value is
+        // reverted in the BeginTx step.
+        let call = tx.calls[tx_ctx.call_index()].clone();
+        let (_, callee_account) = self.sdb.get_account(&call.address);
+        let is_collision = !callee_account.nonce.is_zero()
+            || callee_account.code_hash != Hash::from(keccak256(&[]));
+        if call.is_create() && is_collision {
+            // The root call has no caller context to restore, so its return
+            // needs no step of its own
+            let mut step = ExecStep::begin_tx(
+                tx_ctx.call_index(),
+                self.block_ctx.rwc,
+                Gas(0),
+                GasCost::ZERO,
+            );
+            let mut state_ref = self.state_ref(tx, tx_ctx, &mut step);
+            state_ref.handle_untraced_return(None, None, 0, 0);
+            return Ok(());
+        }
+
+        // A transaction to a precompiled contract has no execution trace, so
+        // its call is executed by a Precompile step, with the gas left after
+        // the intrinsic gas
+        if let (false, Some(precompile)) = (
+            call.is_create(),
+            PrecompileCalls::from_address(&call.address),
+        ) {
+            let mut step = ExecStep {
+                exec_state: ExecState::Precompile(precompile),
+                swc: tx_ctx.call_ctx().swc,
+                ..ExecStep::begin_tx(
+                    tx_ctx.call_index(),
+                    self.block_ctx.rwc,
+                    Gas(tx.gas - intrinsic_gas_cost),
+                    GasCost::ZERO,
+                )
+            };
+            let mut state_ref = self.state_ref(tx, tx_ctx, &mut step);
+            gen_precompile_tx_ops(&mut state_ref, precompile)?;
+            tx.steps.push(step);
+        }
+
+        Ok(())
+    }
+
+    /// Generate the [`ExecState::EndTx`] step of `tx`, which refunds the gas
+    /// left after its root call to the caller and rewards the coinbase, after
+    /// the steps of its execution trace.
+    pub fn end_tx(
+        &mut self,
+        tx: &mut Transaction,
+        tx_ctx: &mut TransactionContext,
+    ) -> Result<(), Error> {
+        let mut step = ExecStep {
+            exec_state: ExecState::EndTx,
+            ..ExecStep::begin_tx(0, self.block_ctx.rwc, Gas(tx_ctx.gas_left), GasCost::ZERO)
+        };
+        let mut state_ref = self.state_ref(tx, tx_ctx, &mut step);
+        gen_end_tx_ops(&mut state_ref)?;
+        tx.steps.push(step);
+
+        Ok(())
+    }
+
+    /// Generate the [`ExecState::EndTx`] step of `tx` as if its root call
+    /// returned with `gas_left`, for the tests which generate the steps of a
+    /// transaction one by one.
+    #[cfg(test)]
+    pub(crate) fn end_tx_with_gas_left(
+        &mut self,
+        tx: &mut Transaction,
+        tx_ctx: &mut TransactionContext,
+        gas_left: Gas,
+    ) -> Result<(), Error> {
+        tx_ctx.gas_left = gas_left.0;
+        self.end_tx(tx, tx_ctx)
+    }
+
     /// Handle a block by handling each transaction to generate all the
     /// associated operations.
     pub fn handle_block(
@@ -1278,6 +1963,7 @@ impl<'a> CircuitInputBuilder {
             self.handle_tx(tx, geth_trace)?;
         }
         self.set_value_ops_call_context_rwc_eor();
+        self.gen_mpt_updates()?;
         Ok(())
     }
 
@@ -1290,9 +1976,34 @@ impl<'a> CircuitInputBuilder {
         eth_tx: &eth_types::Transaction,
         geth_trace: &GethExecTrace,
     ) -> Result<(), Error> {
+        // The EndTx step of the previous transaction sets the tx_id of this
+        // one in the call context of its root call, whose call_id is the
+        // rw_counter of its BeginTx step.
+        let tx_id = self.block.txs.len() + 1;
+        if let Some(prev_tx) = self.block.txs.last_mut() {
+            let rwc = self.block_ctx.rwc.inc_pre();
+            let op_ref = self.block.container.insert(Operation::new(
+                rwc,
+                RW::WRITE,
+                CallContextOp {
+                    call_id: rwc.0 + 1,
+                    field: CallContextField::TxId,
+                    value: tx_id.into(),
+                },
+            ));
+            prev_tx
+                .steps
+                .last_mut()
+                .expect("EndTx step not found")
+                .bus_mapping_instance
+                .push(op_ref);
+        }
+
         let mut tx = self.new_tx(eth_tx, !geth_trace.failed)?;
         let mut tx_ctx = TransactionContext::new(eth_tx, geth_trace)?;
 
+        self.begin_tx(&mut tx, &mut tx_ctx)?;
+
         for (index, geth_step) in geth_trace.struct_logs.iter().enumerate() {
             let mut step = ExecStep::new(
                 geth_step,
@@ -1302,6 +2013,11 @@ impl<'a> CircuitInputBuilder {
             );
             let mut state_ref = self.state_ref(&mut tx, &mut tx_ctx, &mut step);
 
+            // Error steps are dispatched by their error instead of their
+            // opcode in `gen_associated_ops`
+            let next_geth_step = geth_trace.struct_logs.get(index + 1);
+            state_ref.step.error = state_ref.get_step_err(geth_step, next_geth_step)?;
+
             gen_associated_ops(
                 &geth_step.op,
                 &mut state_ref,
@@ -1309,8 +2025,17 @@ impl<'a> CircuitInputBuilder {
             )?;
 
             tx.steps.push(step);
+            tx.steps.append(&mut tx_ctx.internal_steps);
         }
 
+        self.end_tx(&mut tx, &mut tx_ctx)?;
+
+        // Accounts destructed by SELFDESTRUCT are deleted at the end of the
+        // transaction.
+        self.block_ctx
+            .destructed_accounts
+            .extend(self.sdb.clear_destructed_accounts());
+
         self.block.txs.push(tx);
 
         Ok(())
@@ -1326,7 +2051,9 @@ fn get_step_reported_error(op: &OpcodeId, error: &str) -> ExecError {
             OpcodeId::CODECOPY => OogError::CodeCopy,
             OpcodeId::EXTCODECOPY => OogError::ExtCodeCopy,
             OpcodeId::RETURNDATACOPY => OogError::ReturnDataCopy,
-            OpcodeId::LOG0 | OpcodeId::LOG2 | OpcodeId::LOG3 | OpcodeId::LOG4 => OogError::Log,
+            OpcodeId::LOG0 | OpcodeId::LOG1 | OpcodeId::LOG2 | OpcodeId::LOG3 | OpcodeId::LOG4 => {
+                OogError::Log
+            }
             OpcodeId::CALL => OogError::Call,
             OpcodeId::CALLCODE => OogError::CallCode,
             OpcodeId::DELEGATECALL => OogError::DelegateCall,
@@ -1357,6 +2084,40 @@ pub fn get_create_init_code(step: &GethExecStep) -> Result<&[u8], Error> {
     Ok(&step.memory.0[offset.low_u64() as usize..(offset.low_u64() + length.low_u64()) as usize])
 }
 
+/// Return the preimage of the address of the contract created by CREATE from
+/// the `sender` with its `nonce`, which is `rlp([sender, nonce])`.
+pub fn get_create_address_preimage(sender: Address, nonce: Word) -> Vec<u8> {
+    let mut stream = rlp::RlpStream::new_list(2);
+    stream.append(&sender);
+    stream.append(&nonce);
+    stream.out().to_vec()
+}
+
+/// Return the preimage of the address of the contract created by CREATE2 from
+/// the `sender`, which is `0xff ++ sender ++ salt ++ keccak256(init_code)`.
+pub fn get_create2_address_preimage(sender: Address, salt: Word, init_code: &[u8]) -> Vec<u8> {
+    std::iter::once(0xff)
+        .chain(sender.to_fixed_bytes())
+        .chain(salt.to_be_bytes())
+        .chain(keccak256(init_code))
+        .collect()
+}
+
+/// Retrieve the bytes returned by RETURN or REVERT in a `step`, which are the
+/// deployed code when RETURN ends a CREATE* or creation transaction.  The
+/// bytes beyond the current memory are zeros.
+pub fn get_return_code(step: &GethExecStep) -> Result<Vec<u8>, Error> {
+    let offset = step.stack.nth_last(0)?;
+    let length = step.stack.nth_last(1)?;
+    if length.is_zero() {
+        return Ok(Vec::new());
+    }
+    let offset = offset.low_u64() as usize;
+    Ok((offset..offset + length.low_u64() as usize)
+        .map(|addr| step.memory.0.get(addr).copied().unwrap_or(0))
+        .collect())
+}
+
 /// State and Code Access with "keys/index" used in the access operation.
 #[derive(Debug, PartialEq)]
 pub enum AccessValue {
@@ -1601,13 +2362,20 @@ pub fn gen_state_access_trace<TX>(
 
 type EthBlock = eth_types::Block<eth_types::Transaction>;
 
+/// Number of the parent of the block `block_num`, whose state the block is
+/// executed on.  The genesis block has no parent.
+fn parent_block_num(block_num: u64) -> Result<u64, Error> {
+    block_num
+        .checked_sub(1)
+        .ok_or(Error::NoParentBlock(block_num))
+}
+
 /// Struct that wraps a GethClient and contains methods to perform all the steps
 /// necessary to generate the circuit inputs for a block by querying geth for
 /// the necessary information and using the CircuitInputBuilder.
 pub struct BuilderClient<P: JsonRpcClient> {
     cli: GethClient<P>,
     chain_id: Word,
-    history_hashes: Vec<Word>,
 }
 
 impl<P: JsonRpcClient> BuilderClient<P> {
@@ -1618,8 +2386,6 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         Ok(Self {
             cli: client,
             chain_id: chain_id.into(),
-            // TODO: Get history hashes
-            history_hashes: Vec::new(),
         })
     }
 
@@ -1633,6 +2399,21 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         Ok((eth_block, geth_traces))
     }
 
+    /// Step 1.1. Query geth for the hashes of the most recent 256 blocks
+    /// before `block_num`, where the lastest one is at the end of the
+    /// returned vector.
+    pub async fn get_history_hashes(&self, block_num: u64) -> Result<Vec<Word>, Error> {
+        let mut history_hashes = Vec::new();
+        for number in block_num.saturating_sub(256)..block_num {
+            let block = self.cli.get_block_by_number(number.into()).await?;
+            let hash = block
+                .hash
+                .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?;
+            history_hashes.push(hash.to_word());
+        }
+        Ok(history_hashes)
+    }
+
     /// Step 2. Get State Accesses from TxExecTraces
     pub fn get_state_accesses(
         &self,
@@ -1662,13 +2443,14 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         ),
         Error,
     > {
+        let parent_block_num = parent_block_num(block_num)?;
         let mut proofs = Vec::new();
         for (address, key_set) in access_set.state {
             let mut keys: Vec<Word> = key_set.iter().cloned().collect();
             keys.sort();
             let proof = self
                 .cli
-                .get_proof(address, keys, (block_num - 1).into())
+                .get_proof(address, keys, parent_block_num.into())
                 .await
                 .unwrap();
             proofs.push(proof);
@@ -1677,7 +2459,7 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         for address in access_set.code {
             let code = self
                 .cli
-                .get_code(address, (block_num - 1).into())
+                .get_code(address, parent_block_num.into())
                 .await
                 .unwrap();
             codes.insert(address, code);
@@ -1685,6 +2467,16 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         Ok((proofs, codes))
     }
 
+    /// Step 3.1. Query geth for the state root of the parent block, against
+    /// which the proofs from step 3 are.
+    pub async fn get_prev_state_root(&self, block_num: u64) -> Result<Hash, Error> {
+        let parent = self
+            .cli
+            .get_block_by_number(parent_block_num(block_num)?.into())
+            .await?;
+        Ok(parent.state_root)
+    }
+
     /// Step 4. Build a partial StateDB from step 3
     pub fn build_state_code_db(
         &self,
@@ -1715,28 +2507,60 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         (sdb, code_db)
     }
 
+    /// Step 4.1. Build the partial state tries from the proofs of step 3,
+    /// verifying them against the state root of step 3.1
+    pub fn build_state_trie(
+        &self,
+        prev_state_root: Hash,
+        proofs: &[eth_types::EIP1186ProofResponse],
+    ) -> Result<StateTrie, Error> {
+        StateTrie::from_proofs(prev_state_root, proofs)
+    }
+
     /// Step 5. For each step in TxExecTraces, gen the associated ops and state
     /// circuit inputs
     pub fn gen_inputs_from_state(
         &self,
         sdb: StateDB,
         code_db: CodeDB,
+        state_trie: StateTrie,
+        history_hashes: Vec<Word>,
         eth_block: &EthBlock,
         geth_traces: &[eth_types::GethExecTrace],
     ) -> Result<CircuitInputBuilder, Error> {
-        let block = Block::new(self.chain_id, self.history_hashes.clone(), eth_block)?;
+        let block = Block::new(self.chain_id, history_hashes, eth_block)?;
         let mut builder = CircuitInputBuilder::new(sdb, code_db, block);
+        builder.state_trie = state_trie;
         builder.handle_block(eth_block, geth_traces)?;
+        if builder.block.state_root != eth_block.state_root {
+            // FIXME: resolve this once the block reward is part of the state
+            // ops
+            log::warn!(
+                "state root {:?} differs from the one of the block {:?}",
+                builder.block.state_root,
+                eth_block.state_root
+            );
+        }
         Ok(builder)
     }
 
     /// Perform all the steps to generate the circuit inputs
     pub async fn gen_inputs(&self, block_num: u64) -> Result<CircuitInputBuilder, Error> {
         let (eth_block, geth_traces) = self.get_block(block_num).await?;
+        let history_hashes = self.get_history_hashes(block_num).await?;
         let access_set = self.get_state_accesses(&eth_block, &geth_traces)?;
         let (proofs, codes) = self.get_state(block_num, access_set).await?;
+        let prev_state_root = self.get_prev_state_root(block_num).await?;
+        let state_trie = self.build_state_trie(prev_state_root, &proofs)?;
         let (state_db, code_db) = self.build_state_code_db(proofs, codes);
-        let builder = self.gen_inputs_from_state(state_db, code_db, &eth_block, &geth_traces)?;
+        let builder = self.gen_inputs_from_state(
+            state_db,
+            code_db,
+            state_trie,
+            history_hashes,
+            &eth_block,
+            &geth_traces,
+        )?;
         Ok(builder)
     }
 }
@@ -1819,6 +2643,9 @@ mod tracer_tests {
             call_data_length: 0,
             return_data_offset: 0,
             return_data_length: 0,
+            last_callee_id: 0,
+            last_callee_return_data_offset: 0,
+            last_callee_return_data_length: 0,
         }
     }
 
@@ -2712,6 +3539,9 @@ mod tracer_tests {
             call_data_length: 0,
             return_data_offset: 0,
             return_data_length: 0,
+            last_callee_id: 0,
+            last_callee_return_data_offset: 0,
+            last_callee_return_data_length: 0,
         });
 
         assert_eq!(
@@ -3060,3 +3890,405 @@ mod tracer_tests {
         )
     }
 }
+
+#[cfg(test)]
+mod reversion_tests {
+    use super::*;
+    use crate::operation::{AccountDestructedOp, TxRefundOp};
+    use eth_types::{address, bytecode};
+    use pretty_assertions::assert_eq;
+
+    fn failed_sub_call(call_id: usize, caller_id: usize, depth: usize) -> Call {
+        Call {
+            call_id,
+            caller_id,
+            kind: CallKind::Call,
+            is_static: false,
+            is_root: false,
+            is_persistent: false,
+            is_success: false,
+            rw_counter_end_of_reversion: 0,
+            caller_address: Address::zero(),
+            address: Address::zero(),
+            code_source: CodeSource::Address(Address::zero()),
+            code_hash: Hash::zero(),
+            depth,
+            value: Word::zero(),
+            call_data_offset: 0,
+            call_data_length: 0,
+            return_data_offset: 0,
+            return_data_length: 0,
+            last_callee_id: 0,
+            last_callee_return_data_offset: 0,
+            last_callee_return_data_length: 0,
+        }
+    }
+
+    #[test]
+    fn nested_call_reversion() {
+        let code = bytecode! {
+            STOP
+        };
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_code(&code).unwrap(),
+        );
+        let geth_step = &block.geth_trace.struct_logs[0];
+        // Each failing call returns to its caller, which resumes at the next
+        // step
+        let return_steps = [geth_step.clone(), geth_step.clone()];
+        let destructed_address = address!("0x00000000000000000000000000000000000000de");
+
+        let mut builder = block.new_circuit_input_builder();
+        let mut tx = builder.new_tx(&block.eth_tx, true).unwrap();
+        let mut tx_ctx = TransactionContext::new(&block.eth_tx, &block.geth_trace).unwrap();
+        // The root call succeeds, while both the sub-call and its sub-call fail
+        tx_ctx.call_is_success = vec![true, false, false];
+        let rwc = builder.block_ctx.rwc.0;
+        let root_call_id = tx.calls()[0].call_id;
+
+        // The root call calls the sub-call
+        let mut step = ExecStep::new(geth_step, 0, builder.block_ctx.rwc, 0);
+        let mut state_ref = builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+        state_ref.push_call(failed_sub_call(root_call_id + 1, root_call_id, 2));
+        tx.steps.push(step);
+
+        // The sub-call adds refund and then calls its sub-call
+        let mut step = ExecStep::new(geth_step, 1, builder.block_ctx.rwc, 0);
+        let mut state_ref = builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+        state_ref.sdb.set_refund(100);
+        state_ref.push_op_reversible(
+            RW::WRITE,
+            TxRefundOp {
+                tx_id: 1,
+                value: Word::from(100),
+                value_prev: Word::zero(),
+            },
+        );
+        state_ref.push_call(failed_sub_call(root_call_id + 2, root_call_id + 1, 3));
+        tx.steps.push(step);
+
+        // The innermost call adds more refund and destructs an account
+        let mut step = ExecStep::new(geth_step, 2, builder.block_ctx.rwc, 0);
+        let mut state_ref = builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+        state_ref.sdb.set_refund(150);
+        state_ref.push_op_reversible(
+            RW::WRITE,
+            TxRefundOp {
+                tx_id: 1,
+                value: Word::from(150),
+                value_prev: Word::from(100),
+            },
+        );
+        state_ref.sdb.destruct_account(destructed_address);
+        state_ref.push_op_reversible(
+            RW::WRITE,
+            AccountDestructedOp {
+                tx_id: 1,
+                address: destructed_address,
+                value: true,
+                value_prev: false,
+            },
+        );
+        tx.steps.push(step);
+
+        // The innermost call fails, and only its own writes are reverted
+        let mut step = ExecStep::new(geth_step, 2, builder.block_ctx.rwc, 2);
+        let mut state_ref = builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+        state_ref.handle_return(&return_steps).unwrap();
+        assert_eq!(state_ref.sdb.refund(), 100);
+        // The account is not destructed anymore, so it can be destructed again
+        assert!(state_ref.sdb.destruct_account(destructed_address));
+        state_ref.sdb.remove_destructed_account(&destructed_address);
+        tx.steps.push(step);
+
+        // The sub-call fails, and its write is reverted
+        let mut step = ExecStep::new(geth_step, 1, builder.block_ctx.rwc, 1);
+        let mut state_ref = builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+        state_ref.handle_return(&return_steps).unwrap();
+        assert_eq!(state_ref.sdb.refund(), 0);
+        tx.steps.push(step);
+
+        // The reverted writes are applied in reverse order right after the
+        // failing step of each call
+        let container = &builder.block.container;
+        assert_eq!(
+            container
+                .tx_refund
+                .iter()
+                .map(|op| (op.rwc().0, op.op().value_prev, op.op().value))
+                .collect::<Vec<_>>(),
+            vec![
+                (rwc, Word::zero(), Word::from(100)),
+                (rwc + 1, Word::from(100), Word::from(150)),
+                (rwc + 4, Word::from(150), Word::from(100)),
+                (rwc + 17, Word::from(100), Word::zero()),
+            ]
+        );
+        assert_eq!(
+            container
+                .account_destructed
+                .iter()
+                .map(|op| (op.rwc().0, op.op().value_prev, op.op().value))
+                .collect::<Vec<_>>(),
+            vec![(rwc + 2, false, true), (rwc + 3, true, false)]
+        );
+
+        // The reverted writes are attached to the steps doing the writes
+        assert_eq!(tx.steps[1].bus_mapping_instance.len(), 2);
+        assert_eq!(tx.steps[2].bus_mapping_instance.len(), 4);
+        // The returning steps restore the call context of the caller after
+        // the reversion
+        assert_eq!(tx.steps[3].bus_mapping_instance.len(), 12);
+        assert_eq!(tx.steps[4].bus_mapping_instance.len(), 12);
+
+        // Each failing call ends its reversion right after its last reverted
+        // write
+        assert_eq!(tx.calls()[1].rw_counter_end_of_reversion, rwc + 17);
+        assert_eq!(tx.calls()[2].rw_counter_end_of_reversion, rwc + 4);
+    }
+}
+
+#[cfg(test)]
+mod access_list_tests {
+    use super::*;
+    use eth_types::{address, bytecode, AccessListItem, H256};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn access_list_pre_warmed() {
+        let code = bytecode! {
+            STOP
+        };
+        let mut geth_data = mock::new_single_tx_trace_code(&code).unwrap();
+        let callee = geth_data.eth_tx.to.unwrap();
+        let account = address!("0x00000000000000000000000000000000000000ac");
+        geth_data.eth_tx.access_list = Some(AccessList(vec![
+            AccessListItem {
+                address: callee,
+                storage_keys: vec![],
+            },
+            AccessListItem {
+                address: account,
+                storage_keys: vec![H256::from_low_u64_be(1), H256::from_low_u64_be(1)],
+            },
+        ]));
+        let block = crate::mock::BlockData::new_from_geth_data(geth_data);
+
+        let mut builder = block.new_circuit_input_builder();
+        let rwc = builder.block_ctx.rwc.0;
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        // Every entry is written in BeginTx after the caller and the callee,
+        // and the ones already warm keep warm
+        let container = &builder.block.container;
+        let caller = block.eth_tx.from;
+        assert_eq!(
+            container
+                .tx_access_list_account
+                .iter()
+                .map(|op| (op.rwc().0, op.op().address, op.op().value_prev))
+                .collect::<Vec<_>>(),
+            vec![
+                (rwc + 4, caller, false),
+                (rwc + 5, callee, false),
+                (rwc + 6, callee, true),
+                (rwc + 7, account, false)
+            ]
+        );
+        assert_eq!(
+            container
+                .tx_access_list_account_storage
+                .iter()
+                .map(|op| (op.rwc().0, op.op().key, op.op().value_prev))
+                .collect::<Vec<_>>(),
+            vec![(rwc + 8, Word::one(), false), (rwc + 9, Word::one(), true)]
+        );
+
+        // The pre-warming is part of the BeginTx step, and the first step of
+        // the execution follows the 24 operations of BeginTx
+        let steps = builder.block.txs()[0].steps();
+        assert_eq!(steps[0].exec_state, ExecState::BeginTx);
+        assert_eq!(steps[0].bus_mapping_instance.len(), 24);
+        assert_eq!(steps[1].rwc.0, rwc + 24);
+        assert!(builder.sdb.check_account_in_access_list(&account));
+    }
+
+    #[test]
+    fn access_list_too_large() {
+        let code = bytecode! {
+            STOP
+        };
+        // The storage keys are all in the first item
+        let access_list = |addresses_len: u64, storage_keys_len: u64| {
+            AccessList(
+                (1..=addresses_len)
+                    .map(|n| AccessListItem {
+                        address: Address::from_low_u64_be(n),
+                        storage_keys: if n == 1 {
+                            (0..storage_keys_len).map(H256::from_low_u64_be).collect()
+                        } else {
+                            vec![]
+                        },
+                    })
+                    .collect(),
+            )
+        };
+        let max_addresses_len = MAX_ACCESS_LIST_ADDRESSES as u64;
+        let max_storage_keys_len = MAX_ACCESS_LIST_STORAGE_KEYS as u64;
+
+        for (addresses_len, storage_keys_len, is_ok) in [
+            (max_addresses_len, max_storage_keys_len, true),
+            (max_addresses_len + 1, 0, false),
+            (1, max_storage_keys_len + 1, false),
+        ] {
+            let mut geth_data = mock::new_single_tx_trace_code(&code).unwrap();
+            geth_data.eth_tx.access_list = Some(access_list(addresses_len, storage_keys_len));
+            let block = crate::mock::BlockData::new_from_geth_data(geth_data);
+
+            let mut builder = block.new_circuit_input_builder();
+            let result = builder.handle_tx(&block.eth_tx, &block.geth_trace);
+            if is_ok {
+                assert!(result.is_ok());
+            } else {
+                assert!(matches!(
+                    result,
+                    Err(Error::AccessListTooLarge(len, keys_len))
+                        if len == addresses_len as usize && keys_len == storage_keys_len as usize
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod creation_tx_tests {
+    use super::*;
+    use crate::operation::AccountOp;
+    use eth_types::{bytecode, geth_types::Account, Bytes, H256};
+    use ethers_core::utils::keccak256;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn creation_tx_deploys_code() {
+        // Return 0x2a as the deployed code
+        let init_code = bytecode! {
+            PUSH1(0x2a)
+            PUSH1(0)
+            MSTORE8
+            PUSH1(1)
+            PUSH1(0)
+            RETURN
+        };
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_create(&init_code).unwrap(),
+        );
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        // The init code is executed from the tx calldata at the address
+        // derived from the caller and its nonce
+        let tx = &builder.block.txs()[0];
+        let call = &tx.calls()[0];
+        let address = get_contract_address(block.eth_tx.from, block.eth_tx.nonce);
+        assert!(tx.is_create());
+        assert_eq!(tx.to, address);
+        assert!(matches!(call.code_source, CodeSource::Tx));
+        assert_eq!(call.code_hash, H256(keccak256(init_code.to_vec())));
+
+        // The returned code is deployed at the created address, with a write
+        // of the code hash in the RETURN step
+        let code_hash = H256(keccak256(&[0x2a]));
+        assert_eq!(builder.sdb.get_account(&address).1.code_hash, code_hash);
+        assert_eq!(builder.code_db.0.get(&code_hash), Some(&vec![0x2a]));
+        let return_step = tx
+            .steps()
+            .iter()
+            .find(|step| step.op == OpcodeId::RETURN)
+            .unwrap();
+        assert_eq!(return_step.op, OpcodeId::RETURN);
+        let (code_hash_op_index, code_hash_op) = builder
+            .block
+            .container
+            .account
+            .iter()
+            .enumerate()
+            .find(|(_, op)| op.op().field == AccountField::CodeHash && op.rw().is_write())
+            .unwrap();
+        assert_eq!(
+            code_hash_op.op(),
+            &AccountOp {
+                address,
+                field: AccountField::CodeHash,
+                value: code_hash.to_word(),
+                value_prev: H256(keccak256(&[])).to_word(),
+            }
+        );
+        assert!(return_step
+            .bus_mapping_instance
+            .contains(&OperationRef::from((Target::Account, code_hash_op_index))));
+    }
+
+    #[test]
+    fn creation_tx_fails_on_address_collision() {
+        // The created address already has a nonce and code
+        let caller = mock::new_tx(&mock::new_block()).from;
+        let address = get_contract_address(caller, Word::zero());
+        let account = Account {
+            address,
+            nonce: Word::one(),
+            code: Bytes::from(bytecode! { STOP }.to_vec()),
+            ..Default::default()
+        };
+        let init_code = bytecode! {
+            PUSH1(0x2a)
+            STOP
+        };
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_create_accounts(&init_code, vec![account]).unwrap(),
+        );
+        assert!(block.geth_trace.failed);
+        assert!(block.geth_trace.struct_logs.is_empty());
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        // The transaction ends right after BeginTx, with all its gas consumed
+        let tx = &builder.block.txs()[0];
+        let steps = tx.steps();
+        assert_eq!(
+            steps.iter().map(|step| step.exec_state).collect::<Vec<_>>(),
+            vec![ExecState::BeginTx, ExecState::EndTx]
+        );
+        assert_eq!(steps[1].gas_left, Gas(0));
+
+        // BeginTx reads the nonce of the created address, and reverts the
+        // transfer of the value in its last 2 operations
+        let begin_tx = &steps[0];
+        let account_ops = begin_tx
+            .bus_mapping_instance
+            .iter()
+            .filter(|op_ref| op_ref.target() == Target::Account)
+            .map(|op_ref| {
+                builder.block.container.account[op_ref.as_usize()]
+                    .op()
+                    .clone()
+            })
+            .collect::<Vec<_>>();
+        assert!(account_ops.contains(&AccountOp {
+            address,
+            field: AccountField::Nonce,
+            value: Word::one(),
+            value_prev: Word::one(),
+        }));
+        assert_eq!(begin_tx.bus_mapping_instance.len(), 23);
+        assert_eq!(
+            tx.calls()[0].rw_counter_end_of_reversion,
+            begin_tx.rwc.0 + 22
+        );
+        assert_eq!(steps[1].rwc.0, begin_tx.rwc.0 + 23);
+
+        // The nonce of the caller is still increased
+        assert_eq!(builder.sdb.get_account(&caller).1.nonce, Word::one());
+    }
+}
@@ -1,7 +1,8 @@
 //! Error module for the bus-mapping crate
 
+use crate::precompile::PrecompileCalls;
 use core::fmt::{Display, Formatter, Result as FmtResult};
-use eth_types::{Address, GethExecStep, Word};
+use eth_types::{Address, GethExecStep, Hash, Word};
 use ethers_providers::ProviderError;
 use std::error::Error as StdError;
 
@@ -16,6 +17,9 @@ pub enum Error {
     OpcodeIdNotCallType,
     /// Account not found in the StateDB
     AccountNotFound(Address),
+    /// Balance of the caller of a transaction not covering its value and the
+    /// gas it buys
+    InsufficientBalanceForTx(Address),
     /// Storage key not found in the StateDB
     StorageKeyNotFound(Address, Word),
     /// Unable to figure out error at a [`GethExecStep`]
@@ -24,6 +28,26 @@ pub enum Error {
     InvalidGethExecStep(&'static str, GethExecStep),
     /// Eth type related error.
     EthTypeError(eth_types::Error),
+    /// Input of a call to a precompiled contract larger than supported by
+    /// the circuits.
+    PrecompileInputTooLarge(PrecompileCalls, Vec<u8>),
+    /// Call to a precompiled contract whose execution isn't proved by the
+    /// circuits.
+    PrecompileNotSupported(PrecompileCalls),
+    /// Access list of a transaction with more addresses or storage keys than
+    /// supported by the circuits, which are the numbers it has.
+    AccessListTooLarge(usize, usize),
+    /// Node of a Merkle Patricia Trie missing from the proofs it's built
+    /// from.
+    MptNodeNotFound(Hash),
+    /// Invalid RLP encoding of a Merkle Patricia Trie node.
+    InvalidMptNode(Vec<u8>),
+    /// Proof of an account, or of one of its storage slots, not proving
+    /// the value it returns.
+    InvalidMptProof(Address, Option<Word>),
+    /// Block without a parent block, whose state the block is executed on,
+    /// which is the case of the genesis block.
+    NoParentBlock(u64),
 }
 
 impl From<eth_types::Error> for Error {
@@ -1,25 +1,54 @@
 //! Definition of each opcode of the EVM.
-use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecError, OogError};
 use crate::Error;
 use core::fmt::Debug;
 use eth_types::GethExecStep;
 
+mod address;
+mod balance;
+mod begin_tx;
+mod calldatacopy;
+mod calldataload;
 mod calldatasize;
 mod caller;
+mod callop;
 mod callvalue;
+mod codecopy;
 mod coinbase;
 mod dup;
+mod end_tx;
+mod error_call_failure;
+mod error_contract_address_collision;
+mod error_depth;
+mod error_insufficient_balance;
+mod error_invalid_creation_code;
+mod error_invalid_jump;
+mod error_oog_call;
+mod error_oog_memory_copy;
+mod error_return_data_out_of_bound;
+mod error_write_protection;
+mod extcodecopy;
+mod extcodehash;
+mod extcodesize;
 mod gas;
+mod gasprice;
 mod jump;
 mod jumpdest;
 mod jumpi;
+mod memory_copy;
 mod mload;
 mod msize;
 mod mstore;
+mod origin;
 mod pc;
 mod pop;
 mod push;
+mod returndatacopy;
+mod returndatasize;
+mod returnop;
 mod selfbalance;
+mod selfdestruct;
+mod sha3;
 mod sload;
 mod stackonlyop;
 mod stop;
@@ -29,20 +58,49 @@ use crate::evm::OpcodeId;
 use log::warn;
 
 use self::push::Push;
+use address::Address;
+use balance::Balance;
+pub use begin_tx::gen_begin_tx_ops;
+use calldatacopy::Calldatacopy;
+use calldataload::Calldataload;
 use calldatasize::Calldatasize;
 use caller::Caller;
+pub use callop::gen_precompile_tx_ops;
+use callop::CallOp;
 use callvalue::Callvalue;
+use codecopy::Codecopy;
 use dup::Dup;
+pub use end_tx::gen_end_tx_ops;
+use error_call_failure::ErrorCallFailure;
+use error_contract_address_collision::ErrorContractAddressCollision;
+use error_depth::ErrorDepth;
+use error_insufficient_balance::ErrorInsufficientBalance;
+use error_invalid_creation_code::ErrorInvalidCreationCode;
+use error_invalid_jump::ErrorInvalidJump;
+use error_oog_call::ErrorOOGCall;
+use error_oog_memory_copy::ErrorOOGMemoryCopy;
+use error_return_data_out_of_bound::ErrorReturnDataOutOfBound;
+use error_write_protection::ErrorWriteProtection;
+use extcodecopy::Extcodecopy;
+use extcodehash::Extcodehash;
+use extcodesize::Extcodesize;
 use gas::Gas;
+use gasprice::GasPrice;
 use jump::Jump;
 use jumpdest::Jumpdest;
 use jumpi::Jumpi;
 use mload::Mload;
 use msize::Msize;
 use mstore::Mstore;
+use origin::Origin;
 use pc::Pc;
 use pop::Pop;
+use returndatacopy::Returndatacopy;
+use returndatasize::Returndatasize;
+use returnop::ReturnOp;
 use selfbalance::Selfbalance;
+use selfdestruct::Selfdestruct;
+use sha3::Sha3;
 use sload::Sload;
 use stackonlyop::StackOnlyOpcode;
 use stop::Stop;
@@ -101,32 +159,32 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::SHL => StackOnlyOpcode::<2>::gen_associated_ops,
         OpcodeId::SHR => StackOnlyOpcode::<2>::gen_associated_ops,
         OpcodeId::SAR => StackOnlyOpcode::<2>::gen_associated_ops,
-        // OpcodeId::SHA3 => {},
-        // OpcodeId::ADDRESS => {},
-        // OpcodeId::BALANCE => {},
-        // OpcodeId::ORIGIN => {},
+        OpcodeId::SHA3 => Sha3::gen_associated_ops,
+        OpcodeId::ADDRESS => Address::gen_associated_ops,
+        OpcodeId::BALANCE => Balance::gen_associated_ops,
+        OpcodeId::ORIGIN => Origin::gen_associated_ops,
         OpcodeId::CALLER => Caller::gen_associated_ops,
         OpcodeId::CALLVALUE => Callvalue::gen_associated_ops,
-        // OpcodeId::CALLDATALOAD => {},
+        OpcodeId::CALLDATALOAD => Calldataload::gen_associated_ops,
         OpcodeId::CALLDATASIZE => Calldatasize::gen_associated_ops,
-        // OpcodeId::CALLDATACOPY => {},
-        // OpcodeId::CODESIZE => {},
-        // OpcodeId::CODECOPY => {},
-        // OpcodeId::GASPRICE => {},
-        // OpcodeId::EXTCODESIZE => {},
-        // OpcodeId::EXTCODECOPY => {},
-        // OpcodeId::RETURNDATASIZE => {},
-        // OpcodeId::RETURNDATACOPY => {},
-        // OpcodeId::EXTCODEHASH => {},
-        // OpcodeId::BLOCKHASH => {},
+        OpcodeId::CALLDATACOPY => Calldatacopy::gen_associated_ops,
+        OpcodeId::CODESIZE => StackOnlyOpcode::<0>::gen_associated_ops,
+        OpcodeId::CODECOPY => Codecopy::gen_associated_ops,
+        OpcodeId::GASPRICE => GasPrice::gen_associated_ops,
+        OpcodeId::EXTCODESIZE => Extcodesize::gen_associated_ops,
+        OpcodeId::EXTCODECOPY => Extcodecopy::gen_associated_ops,
+        OpcodeId::RETURNDATASIZE => Returndatasize::gen_associated_ops,
+        OpcodeId::RETURNDATACOPY => Returndatacopy::gen_associated_ops,
+        OpcodeId::EXTCODEHASH => Extcodehash::gen_associated_ops,
+        OpcodeId::BLOCKHASH => StackOnlyOpcode::<1>::gen_associated_ops,
         OpcodeId::COINBASE => StackOnlyOpcode::<0>::gen_associated_ops,
         OpcodeId::TIMESTAMP => StackOnlyOpcode::<0>::gen_associated_ops,
-        // OpcodeId::NUMBER => {},
-        // OpcodeId::DIFFICULTY => {},
-        // OpcodeId::GASLIMIT => {},
-        // OpcodeId::CHAINID => {},
+        OpcodeId::NUMBER => StackOnlyOpcode::<0>::gen_associated_ops,
+        OpcodeId::DIFFICULTY => StackOnlyOpcode::<0>::gen_associated_ops,
+        OpcodeId::GASLIMIT => StackOnlyOpcode::<0>::gen_associated_ops,
+        OpcodeId::CHAINID => StackOnlyOpcode::<0>::gen_associated_ops,
         OpcodeId::SELFBALANCE => Selfbalance::gen_associated_ops,
-        // OpcodeId::BASEFEE => {},
+        OpcodeId::BASEFEE => StackOnlyOpcode::<0>::gen_associated_ops,
         OpcodeId::POP => Pop::gen_associated_ops,
         OpcodeId::MLOAD => Mload::gen_associated_ops,
         OpcodeId::MSTORE => Mstore::<false>::gen_associated_ops,
@@ -208,15 +266,15 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         // OpcodeId::LOG2 => {},
         // OpcodeId::LOG3 => {},
         // OpcodeId::LOG4 => {},
-        // OpcodeId::CREATE => {},
-        // OpcodeId::CALL => {},
-        // OpcodeId::CALLCODE => {},
-        // OpcodeId::RETURN => {},
-        // OpcodeId::DELEGATECALL => {},
-        // OpcodeId::CREATE2 => {},
-        // OpcodeId::STATICCALL => {},
-        // OpcodeId::REVERT => {},
-        // OpcodeId::SELFDESTRUCT => {},
+        OpcodeId::CREATE => CallOp::gen_associated_ops,
+        OpcodeId::CALL => CallOp::gen_associated_ops,
+        OpcodeId::CALLCODE => CallOp::gen_associated_ops,
+        OpcodeId::RETURN => ReturnOp::gen_associated_ops,
+        OpcodeId::DELEGATECALL => CallOp::gen_associated_ops,
+        OpcodeId::CREATE2 => CallOp::gen_associated_ops,
+        OpcodeId::STATICCALL => CallOp::gen_associated_ops,
+        OpcodeId::REVERT => ReturnOp::gen_associated_ops,
+        OpcodeId::SELFDESTRUCT => Selfdestruct::gen_associated_ops,
         // _ => panic!("Opcode {:?} gen_associated_ops not implemented",
         // self),
         _ => {
@@ -226,13 +284,47 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
     }
 }
 
+fn fn_gen_error_associated_ops(error: &ExecError) -> FnGenAssociatedOps {
+    match error {
+        ExecError::InvalidOpcode
+        | ExecError::StackOverflow
+        | ExecError::StackUnderflow
+        | ExecError::OutOfGas(OogError::Constant) => ErrorCallFailure::<0>::gen_associated_ops,
+        ExecError::OutOfGas(OogError::PureMemory) => ErrorCallFailure::<1>::gen_associated_ops,
+        ExecError::OutOfGas(OogError::Sha3 | OogError::Log)
+        | ExecError::CodeStoreOutOfGas
+        | ExecError::MaxCodeSizeExceeded => ErrorCallFailure::<2>::gen_associated_ops,
+        ExecError::OutOfGas(OogError::Create2) => ErrorCallFailure::<3>::gen_associated_ops,
+        ExecError::OutOfGas(
+            OogError::CallDataCopy
+            | OogError::CodeCopy
+            | OogError::ExtCodeCopy
+            | OogError::ReturnDataCopy,
+        ) => ErrorOOGMemoryCopy::gen_associated_ops,
+        ExecError::OutOfGas(
+            OogError::Call | OogError::CallCode | OogError::DelegateCall | OogError::StaticCall,
+        ) => ErrorOOGCall::gen_associated_ops,
+        ExecError::WriteProtection => ErrorWriteProtection::gen_associated_ops,
+        ExecError::Depth => ErrorDepth::gen_associated_ops,
+        ExecError::InsufficientBalance => ErrorInsufficientBalance::gen_associated_ops,
+        ExecError::ContractAddressCollision => ErrorContractAddressCollision::gen_associated_ops,
+        ExecError::InvalidCreationCode => ErrorInvalidCreationCode::gen_associated_ops,
+        ExecError::InvalidJump => ErrorInvalidJump::gen_associated_ops,
+        ExecError::ReturnDataOutOfBounds => ErrorReturnDataOutOfBound::gen_associated_ops,
+        ExecError::Reverted => ReturnOp::gen_associated_ops,
+    }
+}
+
 /// Generate the associated operations according to the particular
-/// [`OpcodeId`].
+/// [`OpcodeId`], or to the [`ExecError`] of the current step when it fails.
 pub fn gen_associated_ops(
     opcode_id: &OpcodeId,
     state: &mut CircuitInputStateRef,
     next_steps: &[GethExecStep],
 ) -> Result<(), Error> {
-    let fn_gen_associated_ops = fn_gen_associated_ops(opcode_id);
+    let fn_gen_associated_ops = match &state.step.error {
+        Some(error) => fn_gen_error_associated_ops(error),
+        None => fn_gen_associated_ops(opcode_id),
+    };
     fn_gen_associated_ops(state, next_steps)
 }
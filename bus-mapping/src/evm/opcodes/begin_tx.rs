@@ -0,0 +1,211 @@
+use crate::circuit_input_builder::{get_create_address_preimage, CircuitInputStateRef};
+use crate::operation::{
+    AccountField, AccountOp, CallContextField, CallContextOp, TxAccessListAccountOp,
+    TxAccessListAccountStorageOp, RW,
+};
+use crate::Error;
+use eth_types::{ToWord, Word};
+
+/// Generate the associated operations of the
+/// [`ExecState::BeginTx`](crate::circuit_input_builder::ExecState::BeginTx)
+/// step of a transaction.  The nonce of the caller is increased, and the
+/// access list of the transaction is pre-warmed with the caller and the
+/// callee (EIP-2929), and then with the addresses and the storage keys of the
+/// access list of the transaction (EIP-2930).  The gas is bought at the
+/// effective gas price, and the value is transferred to the callee, which is
+/// reverted if the transaction fails.  Finally the code hash of the callee,
+/// and its nonce in a creation transaction, are read and the context of the
+/// root call is set up.
+pub fn gen_begin_tx_ops(state: &mut CircuitInputStateRef) -> Result<(), Error> {
+    let call = state.call().clone();
+    let tx_id = state.tx_ctx.id();
+
+    for (field, value) in [
+        (CallContextField::TxId, tx_id.into()),
+        (
+            CallContextField::RwCounterEndOfReversion,
+            call.rw_counter_end_of_reversion.into(),
+        ),
+        (
+            CallContextField::IsPersistent,
+            Word::from(call.is_persistent as u64),
+        ),
+    ] {
+        state.push_op(
+            RW::READ,
+            CallContextOp {
+                call_id: call.call_id,
+                field,
+                value,
+            },
+        );
+    }
+
+    // The nonce of the caller increases even when the transaction fails
+    let (_, caller_account) = state.sdb.get_account_mut(&call.caller_address);
+    let nonce_prev = caller_account.nonce;
+    caller_account.nonce = nonce_prev + 1;
+    state.push_op(
+        RW::WRITE,
+        AccountOp {
+            address: call.caller_address,
+            field: AccountField::Nonce,
+            value: nonce_prev + 1,
+            value_prev: nonce_prev,
+        },
+    );
+
+    // The addresses are pre-warmed before the storage keys, and an address or
+    // a storage key repeated in the access list is already warm.
+    let access_list = state.tx.access_list.clone();
+    for address in [call.caller_address, call.address]
+        .into_iter()
+        .chain(access_list.0.iter().map(|item| item.address))
+    {
+        let is_warm = !state.sdb.add_account_to_access_list(address);
+        state.push_op(
+            RW::WRITE,
+            TxAccessListAccountOp {
+                tx_id,
+                address,
+                value: true,
+                value_prev: is_warm,
+            },
+        );
+    }
+    for item in access_list.0.iter() {
+        for key in item.storage_keys.iter() {
+            let key = key.to_word();
+            let is_warm = !state
+                .sdb
+                .add_account_storage_to_access_list((item.address, key));
+            state.push_op(
+                RW::WRITE,
+                TxAccessListAccountStorageOp {
+                    tx_id,
+                    address: item.address,
+                    key,
+                    value: true,
+                    value_prev: is_warm,
+                },
+            );
+        }
+    }
+
+    // Buy the gas, which is kept even when the transaction fails, and then
+    // transfer the value to the callee
+    let gas_fee = state.tx.gas_price * Word::from(state.tx.gas);
+    let (_, caller_account) = state.sdb.get_account_mut(&call.caller_address);
+    let caller_balance_prev = caller_account.balance;
+    let caller_balance_after_fee = caller_balance_prev
+        .checked_sub(gas_fee)
+        .ok_or(Error::InsufficientBalanceForTx(call.caller_address))?;
+    let caller_balance = caller_balance_after_fee
+        .checked_sub(call.value)
+        .ok_or(Error::InsufficientBalanceForTx(call.caller_address))?;
+    caller_account.balance = caller_balance;
+    state.push_op(
+        RW::WRITE,
+        AccountOp {
+            address: call.caller_address,
+            field: AccountField::Balance,
+            value: caller_balance_after_fee,
+            value_prev: caller_balance_prev,
+        },
+    );
+    state.push_op_reversible(
+        RW::WRITE,
+        AccountOp {
+            address: call.caller_address,
+            field: AccountField::Balance,
+            value: caller_balance,
+            value_prev: caller_balance_after_fee,
+        },
+    );
+    let (_, callee_account) = state.sdb.get_account_mut(&call.address);
+    let callee_balance_prev = callee_account.balance;
+    let callee_balance = callee_balance_prev + call.value;
+    callee_account.balance = callee_balance;
+    state.push_op_reversible(
+        RW::WRITE,
+        AccountOp {
+            address: call.address,
+            field: AccountField::Balance,
+            value: callee_balance,
+            value_prev: callee_balance_prev,
+        },
+    );
+
+    // The created contract of a creation transaction runs the init code from
+    // the calldata.  Its address is derived from the caller and its nonce
+    // before the increase, and its nonce is read to check the address
+    // collision, where it already has a nonce or code.
+    let callee_account = state.sdb.get_account(&call.address).1.clone();
+    if call.is_create() {
+        state.block.add_sha3_input(state.tx.input.clone());
+        state
+            .block
+            .add_sha3_input(get_create_address_preimage(call.caller_address, nonce_prev));
+        state.push_op(
+            RW::READ,
+            AccountOp {
+                address: call.address,
+                field: AccountField::Nonce,
+                value: callee_account.nonce,
+                value_prev: callee_account.nonce,
+            },
+        );
+    }
+    let code_hash = callee_account.code_hash.to_word();
+    state.push_op(
+        RW::READ,
+        AccountOp {
+            address: call.address,
+            field: AccountField::CodeHash,
+            value: code_hash,
+            value_prev: code_hash,
+        },
+    );
+
+    for (field, value) in [
+        (CallContextField::Depth, call.depth.into()),
+        (
+            CallContextField::CallerAddress,
+            call.caller_address.to_word(),
+        ),
+        (CallContextField::CalleeAddress, call.address.to_word()),
+        (
+            CallContextField::CallDataOffset,
+            call.call_data_offset.into(),
+        ),
+        (
+            CallContextField::CallDataLength,
+            call.call_data_length.into(),
+        ),
+        (CallContextField::Value, call.value),
+        (
+            CallContextField::IsStatic,
+            Word::from(call.is_static as u64),
+        ),
+        (CallContextField::LastCalleeId, call.last_callee_id.into()),
+        (
+            CallContextField::LastCalleeReturnDataOffset,
+            call.last_callee_return_data_offset.into(),
+        ),
+        (
+            CallContextField::LastCalleeReturnDataLength,
+            call.last_callee_return_data_length.into(),
+        ),
+    ] {
+        state.push_op(
+            RW::READ,
+            CallContextOp {
+                call_id: call.call_id,
+                field,
+                value,
+            },
+        );
+    }
+
+    Ok(())
+}
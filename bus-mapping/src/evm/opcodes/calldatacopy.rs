@@ -0,0 +1,210 @@
+use super::memory_copy::gen_copy_to_memory;
+use super::Opcode;
+use crate::circuit_input_builder::{CircuitInputStateRef, CopyDataType, NumberOrHash};
+use crate::operation::{CallContextField, CallContextOp, RW};
+use crate::Error;
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::CALLDATACOPY`](crate::evm::OpcodeId::CALLDATACOPY) `OpcodeId`.
+/// Besides the [`crate::operation::StackOp`]s and
+/// [`crate::operation::CallContextOp`]s of the step itself, this generates the
+/// [`crate::circuit_input_builder::CopyEvent`] of the copy of the calldata into
+/// memory.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Calldatacopy;
+
+impl Opcode for Calldatacopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        let memory_offset = step.stack.nth_last(0)?;
+        let data_offset = step.stack.nth_last(1)?;
+        let length = step.stack.nth_last(2)?;
+
+        state.push_stack_op(RW::READ, step.stack.nth_last_filled(0), memory_offset);
+        state.push_stack_op(RW::READ, step.stack.nth_last_filled(1), data_offset);
+        state.push_stack_op(RW::READ, step.stack.nth_last_filled(2), length);
+
+        let call = state.call().clone();
+        let tx_id = state.tx_ctx.id();
+        state.push_op(
+            RW::READ,
+            CallContextOp {
+                call_id: call.call_id,
+                field: CallContextField::TxId,
+                value: tx_id.into(),
+            },
+        );
+        if !call.is_root {
+            for (field, value) in [
+                (
+                    CallContextField::CallDataLength,
+                    call.call_data_length.into(),
+                ),
+                (
+                    CallContextField::CallDataOffset,
+                    call.call_data_offset.into(),
+                ),
+                (CallContextField::CallerId, call.caller_id.into()),
+            ] {
+                state.push_op(
+                    RW::READ,
+                    CallContextOp {
+                        call_id: call.call_id,
+                        field,
+                        value,
+                    },
+                );
+            }
+        }
+
+        if !length.is_zero() {
+            // In the root call the calldata comes from the transaction,
+            // otherwise it's a slice of the caller's memory.
+            let (src_type, src_id, src_addr, src_addr_end) = if call.is_root {
+                (
+                    CopyDataType::TxCalldata,
+                    tx_id,
+                    data_offset.low_u64(),
+                    state.tx.input.len() as u64,
+                )
+            } else {
+                (
+                    CopyDataType::Memory,
+                    call.caller_id,
+                    call.call_data_offset + data_offset.low_u64(),
+                    call.call_data_offset + call.call_data_length,
+                )
+            };
+            gen_copy_to_memory(
+                state,
+                &steps[1],
+                src_type,
+                NumberOrHash::Number(src_id),
+                src_addr,
+                src_addr_end,
+                memory_offset.low_u64(),
+                length.low_u64(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod calldatacopy_tests {
+    use super::*;
+    use crate::circuit_input_builder::{CopyEvent, ExecState, ExecStep, TransactionContext};
+    use crate::operation::MemoryOp;
+    use crate::operation::RWCounter;
+    use eth_types::evm_types::StackAddress;
+    use eth_types::{bytecode, Word};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn calldatacopy_opcode_impl() -> Result<(), Error> {
+        let code = bytecode! {
+            PUSH1(0x50u64) // length
+            PUSH1(0x00u64) // data_offset
+            PUSH1(0x40u64) // memory_offset
+            #[start]
+            CALLDATACOPY
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_code_at_start(&code).unwrap(),
+        );
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let mut test_builder = block.new_circuit_input_builder();
+        let mut tx = test_builder
+            .new_tx(&block.eth_tx, !block.geth_trace.failed)
+            .unwrap();
+        let mut tx_ctx = TransactionContext::new(&block.eth_tx, &block.geth_trace).unwrap();
+        test_builder.begin_tx(&mut tx, &mut tx_ctx).unwrap();
+
+        // Generate step corresponding to CALLDATACOPY
+        let mut step = ExecStep::new(
+            &block.geth_trace.struct_logs[0],
+            0,
+            test_builder.block_ctx.rwc,
+            0,
+        );
+        let mut state_ref = test_builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+
+        // Add the 3 StackOp reads
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1021), Word::from(0x40));
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1022), Word::from(0x00));
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1023), Word::from(0x50));
+
+        // Add the TxId read of the root call
+        let call_id = state_ref.call().call_id;
+        state_ref.push_op(
+            RW::READ,
+            CallContextOp {
+                call_id,
+                field: CallContextField::TxId,
+                value: Word::one(),
+            },
+        );
+
+        // The calldata of the mock tx is empty, so 0x50 zero bytes are written
+        // to memory, without any memory read.
+        for idx in 0..0x50 {
+            state_ref.push_op(RW::WRITE, MemoryOp::new(call_id, (0x40 + idx).into(), 0));
+        }
+
+        tx.steps_mut().push(step);
+        let gas_left = builder.block.txs()[0].steps().last().unwrap().gas_left;
+        test_builder
+            .end_tx_with_gas_left(&mut tx, &mut tx_ctx, gas_left)
+            .unwrap();
+        test_builder.block.txs_mut().push(tx);
+
+        // Compare first step bus mapping instance
+        assert_eq!(
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance,
+        );
+
+        // Compare containers
+        assert_eq!(builder.block.container, test_builder.block.container);
+
+        // Check the copy event, which follows the 4 reads of the step
+        assert_eq!(
+            builder.block.copy_events,
+            vec![CopyEvent {
+                src_type: CopyDataType::TxCalldata,
+                src_id: NumberOrHash::Number(1),
+                src_addr: 0,
+                src_addr_end: 0,
+                dst_type: CopyDataType::Memory,
+                dst_id: NumberOrHash::Number(call_id),
+                dst_addr: 0x40,
+                rw_counter_start: RWCounter(builder.block.txs()[0].steps()[1].rwc.0 + 4),
+                bytes: vec![0; 0x50],
+            }]
+        );
+        let steps = builder.block.txs()[0].steps();
+        assert_eq!(
+            steps.iter().map(|step| step.exec_state).collect::<Vec<_>>(),
+            vec![
+                ExecState::BeginTx,
+                ExecState::Op(crate::evm::OpcodeId::CALLDATACOPY),
+                ExecState::Op(crate::evm::OpcodeId::STOP),
+                ExecState::EndTx,
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::operation::{CallContextField, CallContextOp, MemoryOp, RW};
+use crate::Error;
+use eth_types::{GethExecStep, ToBigEndian, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::CALLDATALOAD`](crate::evm::OpcodeId::CALLDATALOAD) `OpcodeId`.
+/// In an internal call the calldata is read from the caller's memory, which
+/// generates a [`MemoryOp`] read for each byte inside the calldata.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Calldataload;
+
+impl Opcode for Calldataload {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        let data_offset = step.stack.last()?;
+        let value = steps[1].stack.last()?;
+
+        state.push_stack_op(RW::READ, step.stack.last_filled(), data_offset);
+
+        let call = state.call().clone();
+        state.push_op(
+            RW::READ,
+            CallContextOp {
+                call_id: call.call_id,
+                field: CallContextField::TxId,
+                value: state.tx_ctx.id().into(),
+            },
+        );
+        if !call.is_root {
+            for (field, value) in [
+                (
+                    CallContextField::CallDataLength,
+                    call.call_data_length.into(),
+                ),
+                (
+                    CallContextField::CallDataOffset,
+                    call.call_data_offset.into(),
+                ),
+                (CallContextField::CallerId, call.caller_id.into()),
+            ] {
+                state.push_op(
+                    RW::READ,
+                    CallContextOp {
+                        call_id: call.call_id,
+                        field,
+                        value,
+                    },
+                );
+            }
+
+            // Read the bytes inside the calldata from the caller's memory.
+            if data_offset < Word::from(call.call_data_length) {
+                let data_offset = data_offset.as_u64();
+                let bytes = value.to_be_bytes();
+                for (idx, byte) in bytes
+                    .iter()
+                    .take((call.call_data_length - data_offset) as usize)
+                    .enumerate()
+                {
+                    let address = call.call_data_offset + data_offset + idx as u64;
+                    state.push_op(
+                        RW::READ,
+                        MemoryOp::new(call.caller_id, (address as usize).into(), *byte),
+                    );
+                }
+            }
+        }
+
+        state.push_stack_op(RW::WRITE, step.stack.last_filled(), value);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod calldataload_tests {
+    use super::*;
+    use crate::circuit_input_builder::{ExecStep, TransactionContext};
+    use eth_types::bytecode;
+    use eth_types::evm_types::StackAddress;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn calldataload_opcode_impl() -> Result<(), Error> {
+        let code = bytecode! {
+            PUSH1(0x00u64)
+            #[start]
+            CALLDATALOAD
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_code_at_start(&code).unwrap(),
+        );
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let mut test_builder = block.new_circuit_input_builder();
+        let mut tx = test_builder
+            .new_tx(&block.eth_tx, !block.geth_trace.failed)
+            .unwrap();
+        let mut tx_ctx = TransactionContext::new(&block.eth_tx, &block.geth_trace).unwrap();
+        test_builder.begin_tx(&mut tx, &mut tx_ctx).unwrap();
+
+        // Generate step corresponding to CALLDATALOAD
+        let mut step = ExecStep::new(
+            &block.geth_trace.struct_logs[0],
+            0,
+            test_builder.block_ctx.rwc,
+            0,
+        );
+        let mut state_ref = test_builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+
+        // Add the StackOp read of the data offset
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1023), Word::zero());
+
+        // Add the TxId read of the root call
+        state_ref.push_op(
+            RW::READ,
+            CallContextOp {
+                call_id: state_ref.call().call_id,
+                field: CallContextField::TxId,
+                value: Word::one(),
+            },
+        );
+
+        // The calldata of the mock tx is empty, so the loaded word is 0
+        state_ref.push_stack_op(RW::WRITE, StackAddress::from(1023), Word::zero());
+
+        tx.steps_mut().push(step);
+        let gas_left = builder.block.txs()[0].steps().last().unwrap().gas_left;
+        test_builder
+            .end_tx_with_gas_left(&mut tx, &mut tx_ctx, gas_left)
+            .unwrap();
+        test_builder.block.txs_mut().push(tx);
+
+        // Compare first step bus mapping instance
+        assert_eq!(
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance,
+        );
+
+        // Compare containers
+        assert_eq!(builder.block.container, test_builder.block.container);
+
+        Ok(())
+    }
+}
@@ -64,6 +64,7 @@ mod calldatasize_tests {
             .new_tx(&block.eth_tx, !block.geth_trace.failed)
             .unwrap();
         let mut tx_ctx = TransactionContext::new(&block.eth_tx, &block.geth_trace).unwrap();
+        test_builder.begin_tx(&mut tx, &mut tx_ctx).unwrap();
 
         // Generate step corresponding to CALLDATASIZE
         let mut step = ExecStep::new(
@@ -95,12 +96,16 @@ mod calldatasize_tests {
         );
 
         tx.steps_mut().push(step);
+        let gas_left = builder.block.txs()[0].steps().last().unwrap().gas_left;
+        test_builder
+            .end_tx_with_gas_left(&mut tx, &mut tx_ctx, gas_left)
+            .unwrap();
         test_builder.block.txs_mut().push(tx);
 
         // Compare first step bus mapping instance
         assert_eq!(
-            builder.block.txs()[0].steps()[0].bus_mapping_instance,
-            test_builder.block.txs()[0].steps()[0].bus_mapping_instance,
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance,
         );
 
         // Compare containers
@@ -0,0 +1,929 @@
+use super::memory_copy::gen_copy_event;
+use super::Opcode;
+use crate::circuit_input_builder::{
+    Call, CallKind, CircuitInputStateRef, CopyDataType, ExecState, ExecStep, NumberOrHash,
+    StepAuxiliaryData,
+};
+use crate::evm::OpcodeId;
+use crate::operation::{
+    AccountField, AccountOp, CallContextField, CallContextOp, TxAccessListAccountOp, RW,
+};
+use crate::precompile::PrecompileCalls;
+use crate::Error;
+use eth_types::evm_types::{Gas, GasCost};
+use eth_types::{GethExecStep, ToAddress, ToWord, Word};
+use log::warn;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the *CALL* and CREATE* `OpcodeId`s, which push the
+/// [`Call`] of the callee.  Calls to precompiled contracts are executed by an
+/// internal [`ExecState::Precompile`] step in the context of the callee.  The
+/// other calls are not proved yet.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct CallOp;
+
+impl Opcode for CallOp {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        let next_step = steps.get(1).ok_or_else(|| {
+            Error::InvalidGethExecStep("CallOp: call without next step", step.clone())
+        })?;
+
+        if matches!(
+            step.op,
+            OpcodeId::CALL | OpcodeId::CALLCODE | OpcodeId::DELEGATECALL | OpcodeId::STATICCALL
+        ) {
+            if let Some(precompile) =
+                PrecompileCalls::from_address(&step.stack.nth_last(1)?.to_address())
+            {
+                return gen_precompile_call_ops(state, step, next_step, precompile);
+            }
+        }
+
+        warn!("Using dummy gen_associated_ops for opcode {:?}", step.op);
+        if next_step.depth == step.depth + 1 {
+            // The code of the callee is executed by the next steps, which
+            // return to the caller.
+            let call = state.parse_call(step)?;
+            state.push_call(call);
+        } else {
+            // The callee has no code, so it returns right away without
+            // return data, and with all the gas passed to it, which is
+            // included in the gas cost of the step.
+            let is_success = !next_step.stack.last()?.is_zero();
+            state.push_untraced_call(step, is_success)?;
+            let gas_returned = next_step.gas.0 - (step.gas.0 - step.gas_cost.as_u64());
+            state.handle_untraced_return(None, Some(next_step), 0, gas_returned);
+        }
+
+        Ok(())
+    }
+}
+
+/// Generate the internal [`ExecState::Precompile`] step of a call to a
+/// precompiled contract by a *CALL* `step`, whose input is read from the
+/// memory of the caller.
+fn gen_precompile_call_ops(
+    state: &mut CircuitInputStateRef,
+    step: &GethExecStep,
+    next_step: &GethExecStep,
+    precompile: PrecompileCalls,
+) -> Result<(), Error> {
+    let is_success = !next_step.stack.last()?.is_zero();
+    let call = state.push_untraced_call(step, is_success)?;
+
+    let input = (call.call_data_offset..call.call_data_offset + call.call_data_length)
+        .map(|addr| step.memory.0.get(addr as usize).copied().unwrap_or(0))
+        .collect::<Vec<_>>();
+
+    // The callee starts with an empty stack and memory, and the gas passed by
+    // the caller.
+    let mut exec_step = state.new_internal_step(ExecState::Precompile(precompile), next_step);
+    exec_step.pc = 0.into();
+    exec_step.stack_size = 0;
+    exec_step.memory_size = 0;
+    exec_step.gas_left = Gas(callee_gas(state, step, &call)?);
+    exec_step.gas_cost = GasCost::from(precompile.gas_cost(&input));
+
+    gen_precompile_ops(
+        state,
+        Some(&mut exec_step),
+        Some(next_step),
+        precompile,
+        input,
+    )?;
+    state.push_internal_step(exec_step);
+
+    Ok(())
+}
+
+/// Generate the operations of the [`ExecState::Precompile`] step of a
+/// transaction to a precompiled contract, which is the current step and
+/// follows the [`ExecState::BeginTx`] step.  The input is the calldata of the
+/// transaction, and there's no caller to return to.
+pub fn gen_precompile_tx_ops(
+    state: &mut CircuitInputStateRef,
+    precompile: PrecompileCalls,
+) -> Result<(), Error> {
+    let input = state.tx.input.clone();
+    state.step.gas_cost = GasCost::from(precompile.gas_cost(&input));
+
+    gen_precompile_ops(state, None, None, precompile, input)
+}
+
+/// Generate the operations of the [`ExecState::Precompile`] step of the
+/// current call, to a precompiled contract, in `exec_step` if given, or in
+/// the current step otherwise.  The step reads the call context of the
+/// callee, writes the output into the memory of the callee, where the return
+/// data is, and copies it into the memory of the caller at `next_step`, if
+/// there's any caller.  The identity precompile copies its input instead of
+/// writing its output.
+fn gen_precompile_ops(
+    state: &mut CircuitInputStateRef,
+    mut exec_step: Option<&mut ExecStep>,
+    next_step: Option<&GethExecStep>,
+    precompile: PrecompileCalls,
+    input: Vec<u8>,
+) -> Result<(), Error> {
+    // A call to an unsupported precompile is rejected even when it runs out
+    // of gas, since the circuits never select it.
+    if !precompile.is_supported() {
+        return Err(Error::PrecompileNotSupported(precompile));
+    }
+    let call = state.call().clone();
+    let tx_id = state.tx_ctx.id();
+    // The gas cost of a call to MODEXP is only proved for supported lengths,
+    // so the larger ones are rejected even when the call runs out of gas.
+    let output = match precompile.output(&input) {
+        None if call.is_success || precompile == PrecompileCalls::Modexp => {
+            return Err(Error::PrecompileInputTooLarge(precompile, input));
+        }
+        Some(output) if call.is_success => output,
+        _ => Vec::new(),
+    };
+
+    let step = match exec_step.as_deref_mut() {
+        Some(step) => step,
+        None => &mut *state.step,
+    };
+    step.aux_data = Some(StepAuxiliaryData::Precompile {
+        input: input.clone(),
+        output: output.clone(),
+    });
+    let (gas_left, gas_cost) = (step.gas_left.0, step.gas_cost.as_u64());
+
+    for (field, value) in [
+        (CallContextField::CalleeAddress, call.address.to_word()),
+        (CallContextField::CallerId, call.caller_id.into()),
+        (CallContextField::TxId, tx_id.into()),
+        (
+            CallContextField::CallDataOffset,
+            call.call_data_offset.into(),
+        ),
+        (
+            CallContextField::CallDataLength,
+            call.call_data_length.into(),
+        ),
+        (
+            CallContextField::ReturnDataOffset,
+            call.return_data_offset.into(),
+        ),
+        (
+            CallContextField::ReturnDataLength,
+            call.return_data_length.into(),
+        ),
+        (CallContextField::IsSuccess, (call.is_success as u64).into()),
+    ] {
+        let op = CallContextOp {
+            call_id: call.call_id,
+            field,
+            value,
+        };
+        match exec_step.as_deref_mut() {
+            Some(step) => state.push_op_to_step(step, RW::READ, op),
+            None => state.push_op(RW::READ, op),
+        }
+    }
+
+    // The input is read from the memory of the caller, or from the calldata
+    // of the transaction for a root call.  The output of the identity
+    // precompile is copied from there into the memory of the callee, the
+    // input of the other ones is copied into its RLC, even when the call
+    // fails, and their output is copied from its RLC into the memory of the
+    // callee.
+    let (src_type, src_id) = if call.is_root {
+        (CopyDataType::TxCalldata, NumberOrHash::Number(tx_id))
+    } else {
+        (CopyDataType::Memory, NumberOrHash::Number(call.caller_id))
+    };
+    let (src_addr, src_addr_end) = (
+        call.call_data_offset,
+        call.call_data_offset + call.call_data_length,
+    );
+    if precompile == PrecompileCalls::Identity {
+        gen_copy_event(
+            state,
+            exec_step.as_deref_mut(),
+            src_type,
+            src_id,
+            src_addr,
+            src_addr_end,
+            CopyDataType::Memory,
+            NumberOrHash::Number(call.call_id),
+            0,
+            output.clone(),
+        );
+    } else {
+        let input_bytes = (0..precompile.input_length(&input))
+            .map(|idx| input.get(idx).copied().unwrap_or_default())
+            .collect();
+        gen_copy_event(
+            state,
+            exec_step.as_deref_mut(),
+            src_type,
+            src_id,
+            src_addr,
+            src_addr_end,
+            CopyDataType::RlcAcc,
+            NumberOrHash::Number(0),
+            0,
+            input_bytes,
+        );
+        gen_copy_event(
+            state,
+            exec_step.as_deref_mut(),
+            CopyDataType::RlcAcc,
+            NumberOrHash::Number(0),
+            0,
+            output.len() as u64,
+            CopyDataType::Memory,
+            NumberOrHash::Number(call.call_id),
+            0,
+            output.clone(),
+        );
+    }
+
+    let length = call.return_data_length.min(output.len() as u64) as usize;
+    gen_copy_event(
+        state,
+        exec_step.as_deref_mut(),
+        CopyDataType::Memory,
+        NumberOrHash::Number(call.call_id),
+        0,
+        output.len() as u64,
+        CopyDataType::Memory,
+        NumberOrHash::Number(call.caller_id),
+        call.return_data_offset,
+        output[..length].to_vec(),
+    );
+
+    // The gas left after the precompile is returned to the caller, unless it
+    // fails, which consumes all the gas passed to it.
+    let gas_returned = if call.is_success {
+        gas_left - gas_cost
+    } else {
+        0
+    };
+    state.handle_untraced_return(exec_step, next_step, output.len() as u64, gas_returned);
+
+    Ok(())
+}
+
+/// Return the gas passed to the callee of a *CALL* `step` to a precompiled
+/// contract, which is all but one 64th of the gas left after paying the cost
+/// of the *CALL* itself, capped by the requested gas, plus the stipend for
+/// calls with value.  Precompiled contracts are always warm (EIP-2929).
+fn callee_gas(
+    state: &CircuitInputStateRef,
+    step: &GethExecStep,
+    call: &Call,
+) -> Result<u64, Error> {
+    let memory_cost = |word_size: u64| {
+        GasCost::MEMORY_EXPANSION_LINEAR_COEFF.as_u64() * word_size
+            + word_size * word_size / GasCost::MEMORY_EXPANSION_QUAD_DENOMINATOR.as_u64()
+    };
+    let curr_memory_word_size = step.memory.0.len() as u64 / 32;
+    let next_memory_word_size = [
+        (call.call_data_offset, call.call_data_length),
+        (call.return_data_offset, call.return_data_length),
+    ]
+    .iter()
+    .map(|(offset, length)| {
+        if *length == 0 {
+            0
+        } else {
+            (offset + length + 31) / 32
+        }
+    })
+    .fold(curr_memory_word_size, u64::max);
+
+    let has_value =
+        !call.value.is_zero() && matches!(call.kind, CallKind::Call | CallKind::CallCode);
+    let mut gas_cost = GasCost::WARM_STORAGE_READ_COST.as_u64()
+        + memory_cost(next_memory_word_size)
+        - memory_cost(curr_memory_word_size);
+    if has_value {
+        gas_cost += GasCost::CALL_WITH_VALUE.as_u64();
+        if call.kind == CallKind::Call && state.sdb.get_account(&call.address).1.is_empty() {
+            gas_cost += GasCost::NEW_ACCOUNT.as_u64();
+        }
+    }
+
+    let gas_available = step.gas.0 - gas_cost;
+    let requested_gas = step.stack.last()?;
+    let all_but_one_64th_gas = gas_available - gas_available / 64;
+    let gas = if requested_gas > Word::from(all_but_one_64th_gas) {
+        all_but_one_64th_gas
+    } else {
+        requested_gas.as_u64()
+    };
+
+    Ok(if has_value {
+        gas + GasCost::CALL_STIPEND.as_u64()
+    } else {
+        gas
+    })
+}
+
+/// Generate the operations of a *CALL*/CREATE* `step` which doesn't create
+/// its sub-call, because a precheck of the sub-call fails: the operands are
+/// popped, 0 is pushed as the result of the step, and the current call has no
+/// last callee anymore, since there's no return data.  A *CALL* still adds
+/// the callee into the access list when its gas cost is computed, and reads
+/// the callee when CALL transfers value, to check whether it's empty.  This
+/// must be the last thing done in such a step.
+pub(crate) fn gen_call_precheck_failure_ops(
+    state: &mut CircuitInputStateRef,
+    step: &GethExecStep,
+) -> Result<(), Error> {
+    let (operand_count, _) = step.op.stack_pop_push();
+    for i in 0..operand_count as usize {
+        state.push_stack_op(
+            RW::READ,
+            step.stack.nth_last_filled(i),
+            step.stack.nth_last(i)?,
+        );
+    }
+    state.push_stack_op(
+        RW::WRITE,
+        step.stack.nth_last_filled(operand_count as usize - 1),
+        Word::zero(),
+    );
+
+    if !matches!(step.op, OpcodeId::CREATE | OpcodeId::CREATE2) {
+        let call = state.call().clone();
+        let tx_id = state.tx_ctx.id();
+        for (field, value) in [
+            (CallContextField::TxId, tx_id.into()),
+            (
+                CallContextField::RwCounterEndOfReversion,
+                call.rw_counter_end_of_reversion.into(),
+            ),
+            (
+                CallContextField::IsPersistent,
+                Word::from(call.is_persistent as u64),
+            ),
+        ] {
+            state.push_op(
+                RW::READ,
+                CallContextOp {
+                    call_id: call.call_id,
+                    field,
+                    value,
+                },
+            );
+        }
+
+        let callee_address = step.stack.nth_last(1)?.to_address();
+        let is_warm = !state.sdb.add_account_to_access_list(callee_address);
+        state.push_op_reversible(
+            RW::WRITE,
+            TxAccessListAccountOp {
+                tx_id,
+                address: callee_address,
+                value: true,
+                value_prev: is_warm,
+            },
+        );
+
+        if step.op == OpcodeId::CALL && !step.stack.nth_last(2)?.is_zero() {
+            let callee = state.sdb.get_account(&callee_address).1.clone();
+            for (field, value) in [
+                (AccountField::Nonce, callee.nonce),
+                (AccountField::Balance, callee.balance),
+                (AccountField::CodeHash, callee.code_hash.to_word()),
+            ] {
+                state.push_op(
+                    RW::READ,
+                    AccountOp {
+                        address: callee_address,
+                        field,
+                        value,
+                        value_prev: value,
+                    },
+                );
+            }
+        }
+    }
+
+    let call_id = state.call().call_id;
+    for field in [
+        CallContextField::LastCalleeId,
+        CallContextField::LastCalleeReturnDataOffset,
+        CallContextField::LastCalleeReturnDataLength,
+    ] {
+        state.push_op(
+            RW::WRITE,
+            CallContextOp {
+                call_id,
+                field,
+                value: Word::zero(),
+            },
+        );
+    }
+    let call = state.call_mut();
+    call.last_callee_id = 0;
+    call.last_callee_return_data_offset = 0;
+    call.last_callee_return_data_length = 0;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod callop_tests {
+    use super::*;
+    use crate::circuit_input_builder::CopyEvent;
+    use crate::operation::RWCounter;
+    use eth_types::{address, bytecode};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn call_identity_precompile() {
+        let code = bytecode! {
+            PUSH32(Word::from(0x1234))
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x20) // retLength
+            PUSH1(0x20) // retOffset
+            PUSH1(0x20) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH1(0x04) // address
+            PUSH2(0xffff) // gas
+            #[start]
+            CALL
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_code_at_start(&code).unwrap(),
+        );
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let tx = &builder.block.txs()[0];
+        let [caller, callee] = [&tx.calls()[0], &tx.calls()[1]];
+        assert_eq!(
+            callee.address,
+            address!("0x0000000000000000000000000000000000000004")
+        );
+        assert_eq!(callee.caller_id, caller.call_id);
+        assert!(callee.is_success);
+        assert_eq!(caller.last_callee_id, callee.call_id);
+        assert_eq!(caller.last_callee_return_data_offset, 0);
+        assert_eq!(caller.last_callee_return_data_length, 0x20);
+
+        let steps = tx.steps();
+        assert_eq!(
+            steps.iter().map(|step| step.exec_state).collect::<Vec<_>>(),
+            vec![
+                ExecState::BeginTx,
+                ExecState::Op(crate::evm::OpcodeId::CALL),
+                ExecState::Precompile(PrecompileCalls::Identity),
+                ExecState::Op(crate::evm::OpcodeId::STOP),
+                ExecState::EndTx,
+            ]
+        );
+
+        let step = &steps[2];
+        assert_eq!(step.gas_cost, GasCost::from(18u64));
+        let mut input = vec![0; 0x20];
+        input[30..].copy_from_slice(&[0x12, 0x34]);
+        assert_eq!(
+            step.aux_data,
+            Some(StepAuxiliaryData::Precompile {
+                input: input.clone(),
+                output: input.clone(),
+            })
+        );
+        assert_eq!(
+            step.bus_mapping_instance[..8]
+                .iter()
+                .map(|op_ref| {
+                    let op = &builder.block.container.call_context[op_ref.as_usize()];
+                    (op.rw(), op.op().clone())
+                })
+                .collect::<Vec<_>>(),
+            [
+                (CallContextField::CalleeAddress, callee.address.to_word()),
+                (CallContextField::CallerId, caller.call_id.into()),
+                (CallContextField::TxId, Word::one()),
+                (CallContextField::CallDataOffset, Word::zero()),
+                (CallContextField::CallDataLength, Word::from(0x20)),
+                (CallContextField::ReturnDataOffset, Word::from(0x20)),
+                (CallContextField::ReturnDataLength, Word::from(0x20)),
+                (CallContextField::IsSuccess, Word::one()),
+            ]
+            .map(|(field, value)| (
+                RW::READ,
+                CallContextOp {
+                    call_id: callee.call_id,
+                    field,
+                    value,
+                }
+            ))
+            .to_vec()
+        );
+
+        // The input is copied into the memory of the callee, and then into
+        // the memory of the caller as return data, with a read and a write
+        // per byte
+        assert_eq!(step.bus_mapping_instance.len(), 8 + 4 * 0x20 + 12);
+        let rw_counter_start = step.rwc.0 + 8;
+        assert_eq!(
+            builder.block.copy_events,
+            vec![
+                CopyEvent {
+                    src_type: CopyDataType::Memory,
+                    src_id: NumberOrHash::Number(caller.call_id),
+                    src_addr: 0,
+                    src_addr_end: 0x20,
+                    dst_type: CopyDataType::Memory,
+                    dst_id: NumberOrHash::Number(callee.call_id),
+                    dst_addr: 0,
+                    rw_counter_start: RWCounter(rw_counter_start),
+                    bytes: input.clone(),
+                },
+                CopyEvent {
+                    src_type: CopyDataType::Memory,
+                    src_id: NumberOrHash::Number(callee.call_id),
+                    src_addr: 0,
+                    src_addr_end: 0x20,
+                    dst_type: CopyDataType::Memory,
+                    dst_id: NumberOrHash::Number(caller.call_id),
+                    dst_addr: 0x20,
+                    rw_counter_start: RWCounter(rw_counter_start + 2 * 0x20),
+                    bytes: input,
+                },
+            ]
+        );
+
+        // Then the call context of the caller is restored, which resumes at
+        // the next step with the gas left by the precompile
+        let next_step = &block.geth_trace.struct_logs[1];
+        let gas_returned = step.gas_left.0 - step.gas_cost.as_u64();
+        assert_eq!(
+            step.bus_mapping_instance[8 + 4 * 0x20..]
+                .iter()
+                .map(|op_ref| {
+                    let op = &builder.block.container.call_context[op_ref.as_usize()];
+                    (op.rw(), op.op().clone())
+                })
+                .collect::<Vec<_>>(),
+            [
+                (
+                    RW::READ,
+                    callee.call_id,
+                    CallContextField::CallerId,
+                    caller.call_id.into()
+                ),
+                (
+                    RW::READ,
+                    caller.call_id,
+                    CallContextField::IsRoot,
+                    Word::one()
+                ),
+                (
+                    RW::READ,
+                    caller.call_id,
+                    CallContextField::IsCreate,
+                    Word::zero()
+                ),
+                (
+                    RW::READ,
+                    caller.call_id,
+                    CallContextField::CodeSource,
+                    caller.code_hash.to_word()
+                ),
+                (
+                    RW::READ,
+                    caller.call_id,
+                    CallContextField::ProgramCounter,
+                    usize::from(next_step.pc).into()
+                ),
+                (
+                    RW::READ,
+                    caller.call_id,
+                    CallContextField::StackPointer,
+                    (1024 - next_step.stack.0.len()).into()
+                ),
+                (
+                    RW::READ,
+                    caller.call_id,
+                    CallContextField::GasLeft,
+                    (next_step.gas.0 - gas_returned).into()
+                ),
+                (
+                    RW::READ,
+                    caller.call_id,
+                    CallContextField::MemorySize,
+                    Word::from(2)
+                ),
+                (
+                    RW::READ,
+                    caller.call_id,
+                    CallContextField::StateWriteCounter,
+                    Word::zero()
+                ),
+                (
+                    RW::WRITE,
+                    caller.call_id,
+                    CallContextField::LastCalleeId,
+                    callee.call_id.into()
+                ),
+                (
+                    RW::WRITE,
+                    caller.call_id,
+                    CallContextField::LastCalleeReturnDataOffset,
+                    Word::zero()
+                ),
+                (
+                    RW::WRITE,
+                    caller.call_id,
+                    CallContextField::LastCalleeReturnDataLength,
+                    Word::from(0x20)
+                ),
+            ]
+            .map(|(rw, call_id, field, value)| (
+                rw,
+                CallContextOp {
+                    call_id,
+                    field,
+                    value,
+                }
+            ))
+            .to_vec()
+        );
+    }
+
+    #[test]
+    fn tx_to_identity_precompile() {
+        let input = vec![0x12, 0x00, 0x34];
+
+        // Get the execution steps from the external tracer
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_precompile(
+                address!("0x0000000000000000000000000000000000000004"),
+                input.clone(),
+            )
+            .unwrap(),
+        );
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let tx = &builder.block.txs()[0];
+        let call = &tx.calls()[0];
+        assert!(call.is_root);
+        assert!(call.is_success);
+
+        let steps = tx.steps();
+        assert_eq!(
+            steps.iter().map(|step| step.exec_state).collect::<Vec<_>>(),
+            vec![
+                ExecState::BeginTx,
+                ExecState::Precompile(PrecompileCalls::Identity),
+                ExecState::EndTx,
+            ]
+        );
+
+        // The precompile runs with the gas left after the intrinsic gas
+        let step = &steps[1];
+        assert_eq!(
+            step.gas_left.0,
+            steps[0].gas_left.0 - steps[0].gas_cost.as_u64()
+        );
+        assert_eq!(step.gas_cost, GasCost::from(18u64));
+        assert_eq!(
+            step.aux_data,
+            Some(StepAuxiliaryData::Precompile {
+                input: input.clone(),
+                output: input.clone(),
+            })
+        );
+
+        // The input is copied from the calldata of the transaction into the
+        // memory of the callee, with a write per byte, and there's neither
+        // return data to copy nor caller to return to
+        assert_eq!(step.bus_mapping_instance.len(), 8 + input.len());
+        assert_eq!(
+            builder.block.copy_events,
+            vec![CopyEvent {
+                src_type: CopyDataType::TxCalldata,
+                src_id: NumberOrHash::Number(1),
+                src_addr: 0,
+                src_addr_end: input.len() as u64,
+                dst_type: CopyDataType::Memory,
+                dst_id: NumberOrHash::Number(call.call_id),
+                dst_addr: 0,
+                rw_counter_start: RWCounter(step.rwc.0 + 8),
+                bytes: input,
+            }]
+        );
+    }
+
+    #[test]
+    fn tx_to_sha256_precompile() {
+        let input = b"abc".to_vec();
+
+        // Get the execution steps from the external tracer
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_precompile(
+                address!("0x0000000000000000000000000000000000000002"),
+                input.clone(),
+            )
+            .unwrap(),
+        );
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let tx = &builder.block.txs()[0];
+        let call = &tx.calls()[0];
+        assert!(call.is_success);
+
+        let step = &tx.steps()[1];
+        assert_eq!(
+            step.exec_state,
+            ExecState::Precompile(PrecompileCalls::Sha256)
+        );
+        let output = PrecompileCalls::Sha256.output(&input).unwrap();
+
+        // The input is copied from the calldata of the transaction into its
+        // RLC, and the output from its RLC into the memory of the callee,
+        // with a write per byte
+        assert_eq!(step.bus_mapping_instance.len(), 8 + output.len());
+        assert_eq!(
+            builder.block.copy_events,
+            vec![
+                CopyEvent {
+                    src_type: CopyDataType::TxCalldata,
+                    src_id: NumberOrHash::Number(1),
+                    src_addr: 0,
+                    src_addr_end: input.len() as u64,
+                    dst_type: CopyDataType::RlcAcc,
+                    dst_id: NumberOrHash::Number(0),
+                    dst_addr: 0,
+                    rw_counter_start: RWCounter(step.rwc.0 + 8),
+                    bytes: input,
+                },
+                CopyEvent {
+                    src_type: CopyDataType::RlcAcc,
+                    src_id: NumberOrHash::Number(0),
+                    src_addr: 0,
+                    src_addr_end: output.len() as u64,
+                    dst_type: CopyDataType::Memory,
+                    dst_id: NumberOrHash::Number(call.call_id),
+                    dst_addr: 0,
+                    rw_counter_start: RWCounter(step.rwc.0 + 8),
+                    bytes: output,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ec_recover_precompile_not_supported() {
+        // Neither a transaction to ECRecover nor a call to it are supported,
+        // even when the call runs out of gas
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_precompile(
+                PrecompileCalls::ECRecover.address(),
+                vec![0; 128],
+            )
+            .unwrap(),
+        );
+        let mut builder = block.new_circuit_input_builder();
+        assert!(matches!(
+            builder.handle_tx(&block.eth_tx, &block.geth_trace),
+            Err(Error::PrecompileNotSupported(PrecompileCalls::ECRecover))
+        ));
+
+        for gas in [0xffffu64, 0x10] {
+            let code = bytecode! {
+                PUSH1(0x20) // retLength
+                PUSH1(0x00) // retOffset
+                PUSH1(0x80) // argsLength
+                PUSH1(0x00) // argsOffset
+                PUSH1(0x00) // value
+                PUSH1(0x01) // address
+                PUSH2(gas) // gas
+                CALL
+                STOP
+            };
+            let block = crate::mock::BlockData::new_from_geth_data(
+                mock::new_single_tx_trace_code(&code).unwrap(),
+            );
+            let mut builder = block.new_circuit_input_builder();
+            assert!(matches!(
+                builder.handle_tx(&block.eth_tx, &block.geth_trace),
+                Err(Error::PrecompileNotSupported(PrecompileCalls::ECRecover))
+            ));
+        }
+    }
+
+    #[test]
+    fn bn128_precompiles_not_supported() {
+        for precompile in [
+            PrecompileCalls::Bn128Add,
+            PrecompileCalls::Bn128Mul,
+            PrecompileCalls::Bn128Pairing,
+        ] {
+            let block = crate::mock::BlockData::new_from_geth_data(
+                mock::new_single_tx_trace_precompile(precompile.address(), vec![0; 128]).unwrap(),
+            );
+            let mut builder = block.new_circuit_input_builder();
+            assert!(matches!(
+                builder.handle_tx(&block.eth_tx, &block.geth_trace),
+                Err(Error::PrecompileNotSupported(callee)) if callee == precompile
+            ));
+        }
+    }
+
+    #[test]
+    fn call_contract_with_code() {
+        let code_a = bytecode! {
+            PUSH1(0x20) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH2(0x123) // address
+            PUSH2(0xffff) // gas
+            CALL
+            STOP
+        };
+        let code_b = bytecode! {
+            PUSH1(0x42)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x20) // length
+            PUSH1(0x00) // offset
+            RETURN
+        };
+
+        // Get the execution steps from the external tracer
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_code_2(&code_a, &code_b).unwrap(),
+        );
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let tx = &builder.block.txs()[0];
+        let [caller, callee] = [&tx.calls()[0], &tx.calls()[1]];
+        assert_eq!(
+            callee.address,
+            address!("0x0000000000000000000000000000000000000123")
+        );
+        assert_eq!(callee.caller_id, caller.call_id);
+        assert_eq!(callee.depth, 2);
+        assert!(callee.is_success);
+        assert_eq!(caller.last_callee_id, callee.call_id);
+        assert_eq!(caller.last_callee_return_data_offset, 0);
+        assert_eq!(caller.last_callee_return_data_length, 0x20);
+
+        // The code of the callee is executed in its call, and the execution
+        // returns to the caller after RETURN, without any error
+        let steps = tx.steps();
+        assert!(steps.iter().all(|step| step.error.is_none()));
+        assert_eq!(steps[0].exec_state, ExecState::BeginTx);
+        assert_eq!(
+            steps[1..]
+                .iter()
+                .map(|step| (step.op, step.call_index))
+                .collect::<Vec<_>>(),
+            [
+                OpcodeId::PUSH1,
+                OpcodeId::PUSH1,
+                OpcodeId::PUSH1,
+                OpcodeId::PUSH1,
+                OpcodeId::PUSH1,
+                OpcodeId::PUSH2,
+                OpcodeId::PUSH2,
+                OpcodeId::CALL,
+            ]
+            .iter()
+            .map(|op| (*op, 0))
+            .chain(
+                [
+                    OpcodeId::PUSH1,
+                    OpcodeId::PUSH1,
+                    OpcodeId::MSTORE,
+                    OpcodeId::PUSH1,
+                    OpcodeId::PUSH1,
+                    OpcodeId::RETURN,
+                ]
+                .iter()
+                .map(|op| (*op, 1))
+            )
+            .chain(std::iter::once((OpcodeId::STOP, 0)))
+            .collect::<Vec<_>>()
+        );
+    }
+}
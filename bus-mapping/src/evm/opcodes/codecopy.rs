@@ -0,0 +1,145 @@
+use super::memory_copy::gen_copy_to_memory;
+use super::Opcode;
+use crate::circuit_input_builder::{CircuitInputStateRef, CopyDataType, NumberOrHash};
+use crate::operation::RW;
+use crate::Error;
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::CODECOPY`](crate::evm::OpcodeId::CODECOPY)
+/// `OpcodeId`.
+/// Besides the [`crate::operation::StackOp`]s of the step itself, this
+/// generates the [`crate::circuit_input_builder::CopyEvent`] of the copy of the
+/// bytecode of the current call into memory.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Codecopy;
+
+impl Opcode for Codecopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        let memory_offset = step.stack.nth_last(0)?;
+        let code_offset = step.stack.nth_last(1)?;
+        let length = step.stack.nth_last(2)?;
+
+        state.push_stack_op(RW::READ, step.stack.nth_last_filled(0), memory_offset);
+        state.push_stack_op(RW::READ, step.stack.nth_last_filled(1), code_offset);
+        state.push_stack_op(RW::READ, step.stack.nth_last_filled(2), length);
+
+        if !length.is_zero() {
+            let code_hash = state.call().code_hash;
+            let code_size = state.code_db.0.get(&code_hash).map_or(0, |code| code.len());
+            gen_copy_to_memory(
+                state,
+                &steps[1],
+                CopyDataType::Bytecode,
+                NumberOrHash::Hash(code_hash),
+                code_offset.low_u64(),
+                code_size as u64,
+                memory_offset.low_u64(),
+                length.low_u64(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod codecopy_tests {
+    use super::*;
+    use crate::circuit_input_builder::{CopyEvent, ExecStep, TransactionContext};
+    use crate::operation::MemoryOp;
+    use crate::operation::RWCounter;
+    use eth_types::evm_types::StackAddress;
+    use eth_types::{bytecode, Word};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn codecopy_opcode_impl() -> Result<(), Error> {
+        let code = bytecode! {
+            PUSH1(0x20u64) // length
+            PUSH1(0x00u64) // code_offset
+            PUSH1(0x40u64) // memory_offset
+            #[start]
+            CODECOPY
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_code_at_start(&code).unwrap(),
+        );
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let mut test_builder = block.new_circuit_input_builder();
+        let mut tx = test_builder
+            .new_tx(&block.eth_tx, !block.geth_trace.failed)
+            .unwrap();
+        let mut tx_ctx = TransactionContext::new(&block.eth_tx, &block.geth_trace).unwrap();
+        test_builder.begin_tx(&mut tx, &mut tx_ctx).unwrap();
+
+        // Generate step corresponding to CODECOPY
+        let mut step = ExecStep::new(
+            &block.geth_trace.struct_logs[0],
+            0,
+            test_builder.block_ctx.rwc,
+            0,
+        );
+        let mut state_ref = test_builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+
+        // Add the 3 StackOp reads
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1021), Word::from(0x40));
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1022), Word::from(0x00));
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1023), Word::from(0x20));
+
+        // The code is shorter than 0x20 bytes, so it's padded with zeros
+        let call_id = state_ref.call().call_id;
+        let code = code.to_vec();
+        for idx in 0..0x20 {
+            let byte = code.get(idx).cloned().unwrap_or(0);
+            state_ref.push_op(RW::WRITE, MemoryOp::new(call_id, (0x40 + idx).into(), byte));
+        }
+
+        tx.steps_mut().push(step);
+        let gas_left = builder.block.txs()[0].steps().last().unwrap().gas_left;
+        test_builder
+            .end_tx_with_gas_left(&mut tx, &mut tx_ctx, gas_left)
+            .unwrap();
+        test_builder.block.txs_mut().push(tx);
+
+        // Compare first step bus mapping instance
+        assert_eq!(
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance,
+        );
+
+        // Compare containers
+        assert_eq!(builder.block.container, test_builder.block.container);
+
+        // Check the copy event, which follows the 3 reads of the step
+        assert_eq!(builder.block.txs()[0].steps().len(), 4);
+        assert_eq!(
+            builder.block.copy_events,
+            vec![CopyEvent {
+                src_type: CopyDataType::Bytecode,
+                src_id: NumberOrHash::Hash(builder.block.txs()[0].calls()[0].code_hash),
+                src_addr: 0,
+                src_addr_end: code.len() as u64,
+                dst_type: CopyDataType::Memory,
+                dst_id: NumberOrHash::Number(call_id),
+                dst_addr: 0x40,
+                rw_counter_start: RWCounter(builder.block.txs()[0].steps()[1].rwc.0 + 3),
+                bytes: (0..0x20)
+                    .map(|idx| code.get(idx).cloned().unwrap_or(0))
+                    .collect(),
+            }]
+        );
+
+        Ok(())
+    }
+}
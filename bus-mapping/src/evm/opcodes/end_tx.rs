@@ -0,0 +1,137 @@
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::operation::{AccountField, AccountOp, CallContextField, CallContextOp, TxRefundOp, RW};
+use crate::Error;
+use eth_types::evm_types::GasCost;
+use eth_types::Word;
+
+/// Generate the associated operations of the
+/// [`ExecState::EndTx`](crate::circuit_input_builder::ExecState::EndTx) step
+/// of a transaction, which starts with the gas left after its root call.  The
+/// gas left and the effective refund, capped by a fifth of the gas used
+/// (EIP-3529), are refunded to the caller at the effective gas price, and the
+/// coinbase is credited with the tip of the rest of the gas used, since the
+/// base fee is burnt (EIP-1559).  The tx_id of the next transaction is set by
+/// the step as well, when the next transaction is handled.
+pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<(), Error> {
+    let call = state.tx.calls()[0].clone();
+    let tx_id = state.tx_ctx.id();
+
+    state.push_op(
+        RW::READ,
+        CallContextOp {
+            call_id: call.call_id,
+            field: CallContextField::TxId,
+            value: tx_id.into(),
+        },
+    );
+
+    let refund = state.sdb.refund();
+    state.push_op(
+        RW::READ,
+        TxRefundOp {
+            tx_id,
+            value: refund.into(),
+            value_prev: refund.into(),
+        },
+    );
+
+    let gas_left = state.step.gas_left.0;
+    let gas_used = state.tx.gas - gas_left;
+    let effective_refund = std::cmp::min(
+        gas_used / GasCost::MAX_REFUND_QUOTIENT_OF_GAS_USED.as_u64(),
+        refund,
+    );
+
+    let (_, caller_account) = state.sdb.get_account_mut(&call.caller_address);
+    let caller_balance_prev = caller_account.balance;
+    let caller_balance =
+        caller_balance_prev + state.tx.gas_price * Word::from(gas_left + effective_refund);
+    caller_account.balance = caller_balance;
+    state.push_op(
+        RW::WRITE,
+        AccountOp {
+            address: call.caller_address,
+            field: AccountField::Balance,
+            value: caller_balance,
+            value_prev: caller_balance_prev,
+        },
+    );
+
+    let coinbase = state.block.coinbase;
+    let effective_tip = state.tx.gas_price - state.block.base_fee;
+    let (_, coinbase_account) = state.sdb.get_account_mut(&coinbase);
+    let coinbase_balance_prev = coinbase_account.balance;
+    let coinbase_balance =
+        coinbase_balance_prev + effective_tip * Word::from(gas_used - effective_refund);
+    coinbase_account.balance = coinbase_balance;
+    state.push_op(
+        RW::WRITE,
+        AccountOp {
+            address: coinbase,
+            field: AccountField::Balance,
+            value: coinbase_balance,
+            value_prev: coinbase_balance_prev,
+        },
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod end_tx_tests {
+    use crate::circuit_input_builder::ExecState;
+    use crate::operation::AccountField;
+    use eth_types::{bytecode, Word};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn end_tx_refunds_caller_and_rewards_coinbase() {
+        let code = bytecode! {
+            PUSH1(0x2a)
+            POP
+            STOP
+        };
+        let mut block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_code(&code).unwrap(),
+        );
+        // The caller buys the gas at 3 wei above the base fee
+        block.eth_block.base_fee_per_gas = Some(Word::from(7));
+        block.eth_tx.gas_price = Some(Word::from(10));
+        block.sdb.get_account_mut(&block.eth_tx.from).1.balance = Word::from(10) * block.eth_tx.gas;
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        // The transaction ends with the gas left after its STOP
+        let tx = &builder.block.txs()[0];
+        let steps = tx.steps();
+        let step = &steps[steps.len() - 1];
+        assert_eq!(step.exec_state, ExecState::EndTx);
+        assert_eq!(step.gas_left, steps[steps.len() - 2].gas_left);
+
+        // The gas left is refunded to the caller, and the tip of the gas used
+        // goes to the coinbase
+        let gas_used = tx.gas - step.gas_left.0;
+        let balance_changes = step.bus_mapping_instance[2..]
+            .iter()
+            .map(|op_ref| {
+                let op = builder.block.container.account[op_ref.as_usize()].op();
+                assert_eq!(op.field, AccountField::Balance);
+                (op.address, op.value - op.value_prev)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            balance_changes,
+            vec![
+                (
+                    block.eth_tx.from,
+                    tx.gas_price * Word::from(step.gas_left.0)
+                ),
+                (
+                    builder.block.coinbase,
+                    (tx.gas_price - builder.block.base_fee) * Word::from(gas_used)
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::operation::{CallContextField, CallContextOp, RW};
+use crate::Error;
+use eth_types::{GethExecStep, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the error steps which fail the current call after reading
+/// the `N` topmost stack items, which are the operands needed to prove the
+/// error:
+/// - N = 0: InvalidOpcode, StackOverflow, StackUnderflow and OutOfGas due to
+///   constant gas cost
+/// - N = 1: OutOfGas due to pure memory expansion
+/// - N = 2: OutOfGas in SHA3, LOG* and the code store of CREATE*, and
+///   MaxCodeSizeExceeded
+/// - N = 3: OutOfGas in CREATE2
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorCallFailure<const N: usize>;
+
+impl<const N: usize> Opcode for ErrorCallFailure<N> {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        // N stack reads
+        for i in 0..N {
+            state.push_stack_op(
+                RW::READ,
+                step.stack.nth_last_filled(i),
+                step.stack.nth_last(i)?,
+            );
+        }
+
+        gen_call_failure_ops(state, steps)
+    }
+}
+
+/// Generate the [`CallContextOp`]s which prove the current call fails and
+/// where the reversion of its state writes ends, and then return to the
+/// caller, which applies the reversion and restores the call context of the
+/// caller.  This must be the last thing done in an error step which
+/// terminates the current call.
+pub(crate) fn gen_call_failure_ops(
+    state: &mut CircuitInputStateRef,
+    steps: &[GethExecStep],
+) -> Result<(), Error> {
+    let call = state.call().clone();
+    for (field, value) in [
+        (
+            CallContextField::IsSuccess,
+            Word::from(call.is_success as u64),
+        ),
+        (
+            CallContextField::RwCounterEndOfReversion,
+            call.rw_counter_end_of_reversion.into(),
+        ),
+    ] {
+        state.push_op(
+            RW::READ,
+            CallContextOp {
+                call_id: call.call_id,
+                field,
+                value,
+            },
+        );
+    }
+
+    state.handle_return(steps)
+}
+
+#[cfg(test)]
+mod error_call_failure_tests {
+    use super::*;
+    use crate::circuit_input_builder::ExecError;
+    use eth_types::bytecode;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn error_stack_underflow_ops() {
+        let code = bytecode! {
+            #[start]
+            POP
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_code_at_start(&code).unwrap(),
+        );
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+        builder.set_value_ops_call_context_rwc_eor();
+
+        let step = &builder.block.txs()[0].steps()[1];
+        assert_eq!(step.error, Some(ExecError::StackUnderflow));
+
+        // Only the call failure is proved, since there is no stack item to
+        // read.  The only state writes to revert are the balance writes of
+        // BeginTx, which are reverted right after the step and attached to
+        // the BeginTx step
+        let rwc = step.rwc.0;
+        let call_id = builder.block.txs()[0].calls()[0].call_id;
+        assert_eq!(
+            step.bus_mapping_instance
+                .iter()
+                .map(|op_ref| {
+                    let op = &builder.block.container.call_context[op_ref.as_usize()];
+                    (op.rwc().0, op.rw(), op.op().clone())
+                })
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    rwc,
+                    RW::READ,
+                    CallContextOp {
+                        call_id,
+                        field: CallContextField::IsSuccess,
+                        value: Word::zero(),
+                    }
+                ),
+                (
+                    rwc + 1,
+                    RW::READ,
+                    CallContextOp {
+                        call_id,
+                        field: CallContextField::RwCounterEndOfReversion,
+                        value: Word::from(rwc + 3),
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            builder.block.txs()[0].calls()[0].rw_counter_end_of_reversion,
+            rwc + 3
+        );
+        assert_eq!(
+            builder.block.txs()[0].steps()[0].bus_mapping_instance.len(),
+            19 + 2
+        );
+    }
+}
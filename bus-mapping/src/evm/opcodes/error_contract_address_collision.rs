@@ -0,0 +1,106 @@
+use super::callop::gen_call_precheck_failure_ops;
+use super::memory_copy::gen_copy_event;
+use super::Opcode;
+use crate::circuit_input_builder::{
+    get_create2_address_preimage, get_create_address_preimage, get_create_init_code,
+    CircuitInputStateRef, CopyDataType, NumberOrHash, StepAuxiliaryData,
+};
+use crate::evm::OpcodeId;
+use crate::operation::{AccountField, AccountOp, CallContextField, CallContextOp, RW};
+use crate::Error;
+use eth_types::{GethExecStep, ToWord};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`ExecError::ContractAddressCollision`](crate::circuit_input_builder::
+/// ExecError::ContractAddressCollision) error of a CREATE or CREATE2 step.  The
+/// address and nonce of the creator are read to derive the contract address,
+/// and the nonce and code hash of the account at the contract address are read
+/// to prove that it already exists.  The init code of CREATE2 is then read
+/// from memory.  The creation is not executed: the operands are popped, 0 is
+/// pushed as the result, and the current call continues.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorContractAddressCollision;
+
+impl Opcode for ErrorContractAddressCollision {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        let call = state.call().clone();
+        state.push_op(
+            RW::READ,
+            CallContextOp {
+                call_id: call.call_id,
+                field: CallContextField::CalleeAddress,
+                value: call.address.to_word(),
+            },
+        );
+        let creator_nonce = state.sdb.get_account(&call.address).1.nonce;
+        state.push_op(
+            RW::READ,
+            AccountOp {
+                address: call.address,
+                field: AccountField::Nonce,
+                value: creator_nonce,
+                value_prev: creator_nonce,
+            },
+        );
+
+        // The contract address is the last 20 bytes of the hash of its
+        // preimage, where the preimage of CREATE2 includes the hash of the
+        // init code
+        let address = if step.op == OpcodeId::CREATE2 {
+            let init_code = get_create_init_code(step)?.to_vec();
+            let preimage =
+                get_create2_address_preimage(call.address, step.stack.nth_last(3)?, &init_code);
+            state.block.add_sha3_input(init_code.clone());
+            state.block.add_sha3_input(preimage);
+            state.step.aux_data = Some(StepAuxiliaryData::Create { init_code });
+            state.create2_address(step)?
+        } else {
+            state
+                .block
+                .add_sha3_input(get_create_address_preimage(call.address, creator_nonce));
+            state.create_address()?
+        };
+
+        let account = state.sdb.get_account(&address).1.clone();
+        for (field, value) in [
+            (AccountField::Nonce, account.nonce),
+            (AccountField::CodeHash, account.code_hash.to_word()),
+        ] {
+            state.push_op(
+                RW::READ,
+                AccountOp {
+                    address,
+                    field,
+                    value,
+                    value_prev: value,
+                },
+            );
+        }
+
+        // The init code of CREATE2 is read from memory into the RLC which is
+        // hashed
+        if step.op == OpcodeId::CREATE2 {
+            let offset = step.stack.nth_last(1)?.low_u64();
+            let init_code = get_create_init_code(step)?.to_vec();
+            gen_copy_event(
+                state,
+                None,
+                CopyDataType::Memory,
+                NumberOrHash::Number(call.call_id),
+                offset,
+                offset + init_code.len() as u64,
+                CopyDataType::RlcAcc,
+                NumberOrHash::Number(0),
+                0,
+                init_code,
+            );
+        }
+
+        gen_call_precheck_failure_ops(state, step)
+    }
+}
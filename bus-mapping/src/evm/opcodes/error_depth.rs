@@ -0,0 +1,34 @@
+use super::callop::gen_call_precheck_failure_ops;
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::operation::{CallContextField, CallContextOp, RW};
+use crate::Error;
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`ExecError::Depth`](crate::circuit_input_builder::ExecError::Depth) error
+/// of a *CALL*/CREATE* step.  The depth of the current call is read to prove
+/// that the call stack is full.  The sub-call is not executed: the operands are
+/// popped, 0 is pushed as the result, and the current call continues.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorDepth;
+
+impl Opcode for ErrorDepth {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let call = state.call().clone();
+        state.push_op(
+            RW::READ,
+            CallContextOp {
+                call_id: call.call_id,
+                field: CallContextField::Depth,
+                value: call.depth.into(),
+            },
+        );
+
+        gen_call_precheck_failure_ops(state, &steps[0])
+    }
+}
@@ -0,0 +1,47 @@
+use super::callop::gen_call_precheck_failure_ops;
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::operation::{AccountField, AccountOp, CallContextField, CallContextOp, RW};
+use crate::Error;
+use eth_types::{GethExecStep, ToWord};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`ExecError::InsufficientBalance`](crate::circuit_input_builder::ExecError::
+/// InsufficientBalance) error of a CALL, CALLCODE, CREATE or CREATE2 step.  The
+/// balance of the current account and the value among the operands are read
+/// to prove that the value can't be transferred.  The sub-call is not
+/// executed: the operands are popped, 0 is pushed as the result, and the
+/// current call continues.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorInsufficientBalance;
+
+impl Opcode for ErrorInsufficientBalance {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let call = state.call().clone();
+        state.push_op(
+            RW::READ,
+            CallContextOp {
+                call_id: call.call_id,
+                field: CallContextField::CalleeAddress,
+                value: call.address.to_word(),
+            },
+        );
+
+        let balance = state.sdb.get_account(&call.address).1.balance;
+        state.push_op(
+            RW::READ,
+            AccountOp {
+                address: call.address,
+                field: AccountField::Balance,
+                value: balance,
+                value_prev: balance,
+            },
+        );
+
+        gen_call_precheck_failure_ops(state, &steps[0])
+    }
+}
@@ -0,0 +1,37 @@
+use super::error_call_failure::gen_call_failure_ops;
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::operation::RW;
+use crate::Error;
+use core::convert::TryInto;
+use eth_types::evm_types::MemoryAddress;
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`ExecError::InvalidCreationCode`](crate::circuit_input_builder::ExecError::
+/// InvalidCreationCode) error of a RETURN step in a CREATE*.  Besides the
+/// offset and length of the returned code, its first byte is read to prove that
+/// it's 0xef (EIP-3541).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorInvalidCreationCode;
+
+impl Opcode for ErrorInvalidCreationCode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        let offset = step.stack.nth_last(0)?;
+        let length = step.stack.nth_last(1)?;
+
+        state.push_stack_op(RW::READ, step.stack.nth_last_filled(0), offset);
+        state.push_stack_op(RW::READ, step.stack.nth_last_filled(1), length);
+
+        let address: MemoryAddress = offset.try_into()?;
+        let byte = step.memory.0[address.0];
+        state.push_memory_op(RW::READ, address, byte);
+
+        gen_call_failure_ops(state, steps)
+    }
+}
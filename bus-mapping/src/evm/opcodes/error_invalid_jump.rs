@@ -0,0 +1,35 @@
+use super::error_call_failure::gen_call_failure_ops;
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::evm::OpcodeId;
+use crate::operation::RW;
+use crate::Error;
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`ExecError::InvalidJump`](crate::circuit_input_builder::ExecError::
+/// InvalidJump) error of a JUMP or JUMPI step.  The destination, and the
+/// condition of a JUMPI, are read to prove that the jump is taken to an invalid
+/// destination.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorInvalidJump;
+
+impl Opcode for ErrorInvalidJump {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        let n_pop = if step.op == OpcodeId::JUMPI { 2 } else { 1 };
+        for i in 0..n_pop {
+            state.push_stack_op(
+                RW::READ,
+                step.stack.nth_last_filled(i),
+                step.stack.nth_last(i)?,
+            );
+        }
+
+        gen_call_failure_ops(state, steps)
+    }
+}
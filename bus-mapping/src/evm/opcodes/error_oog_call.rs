@@ -0,0 +1,80 @@
+use super::error_call_failure::gen_call_failure_ops;
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::evm::OpcodeId;
+use crate::operation::{
+    AccountField, AccountOp, CallContextField, CallContextOp, TxAccessListAccountOp, RW,
+};
+use crate::Error;
+use eth_types::{GethExecStep, ToAddress, ToWord};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the out of gas error of a CALL, CALLCODE, DELEGATECALL or
+/// STATICCALL step.  The operands are read to compute the memory expansion
+/// gas cost, and the access list is read, without adding the callee into it,
+/// to compute the cold account access cost.  When CALL transfers non-zero
+/// value, the callee account is also read to check whether it's empty, which
+/// costs more gas to be created.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorOOGCall;
+
+impl Opcode for ErrorOOGCall {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        let has_value_operand = matches!(step.op, OpcodeId::CALL | OpcodeId::CALLCODE);
+        let n_pop = if has_value_operand { 7 } else { 6 };
+        for i in 0..n_pop {
+            state.push_stack_op(
+                RW::READ,
+                step.stack.nth_last_filled(i),
+                step.stack.nth_last(i)?,
+            );
+        }
+
+        let callee_address = step.stack.nth_last(1)?.to_address();
+        let tx_id = state.tx_ctx.id();
+        state.push_op(
+            RW::READ,
+            CallContextOp {
+                call_id: state.call().call_id,
+                field: CallContextField::TxId,
+                value: tx_id.into(),
+            },
+        );
+
+        let is_warm = state.sdb.check_account_in_access_list(&callee_address);
+        state.push_op(
+            RW::READ,
+            TxAccessListAccountOp {
+                tx_id,
+                address: callee_address,
+                value: is_warm,
+                value_prev: is_warm,
+            },
+        );
+
+        if step.op == OpcodeId::CALL && !step.stack.nth_last(2)?.is_zero() {
+            let callee = state.sdb.get_account(&callee_address).1.clone();
+            for (field, value) in [
+                (AccountField::Nonce, callee.nonce),
+                (AccountField::Balance, callee.balance),
+                (AccountField::CodeHash, callee.code_hash.to_word()),
+            ] {
+                state.push_op(
+                    RW::READ,
+                    AccountOp {
+                        address: callee_address,
+                        field,
+                        value,
+                        value_prev: value,
+                    },
+                );
+            }
+        }
+
+        gen_call_failure_ops(state, steps)
+    }
+}
@@ -0,0 +1,60 @@
+use super::error_call_failure::gen_call_failure_ops;
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::evm::OpcodeId;
+use crate::operation::{CallContextField, CallContextOp, TxAccessListAccountOp, RW};
+use crate::Error;
+use eth_types::{GethExecStep, ToAddress};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the out of gas error of a CALLDATACOPY, CODECOPY,
+/// EXTCODECOPY or RETURNDATACOPY step.  The operands are read to compute the
+/// copy and memory expansion gas cost.  For EXTCODECOPY, the access list is
+/// also read, without adding the external account into it, to compute the
+/// cold account access cost.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorOOGMemoryCopy;
+
+impl Opcode for ErrorOOGMemoryCopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        let is_extcodecopy = step.op == OpcodeId::EXTCODECOPY;
+        let n_pop = if is_extcodecopy { 4 } else { 3 };
+        for i in 0..n_pop {
+            state.push_stack_op(
+                RW::READ,
+                step.stack.nth_last_filled(i),
+                step.stack.nth_last(i)?,
+            );
+        }
+
+        if is_extcodecopy {
+            let address = step.stack.nth_last(0)?.to_address();
+            let tx_id = state.tx_ctx.id();
+            state.push_op(
+                RW::READ,
+                CallContextOp {
+                    call_id: state.call().call_id,
+                    field: CallContextField::TxId,
+                    value: tx_id.into(),
+                },
+            );
+
+            let is_warm = state.sdb.check_account_in_access_list(&address);
+            state.push_op(
+                RW::READ,
+                TxAccessListAccountOp {
+                    tx_id,
+                    address,
+                    value: is_warm,
+                    value_prev: is_warm,
+                },
+            );
+        }
+
+        gen_call_failure_ops(state, steps)
+    }
+}
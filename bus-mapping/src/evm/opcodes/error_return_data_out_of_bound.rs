@@ -0,0 +1,27 @@
+use super::error_call_failure::gen_call_failure_ops;
+use super::returndatacopy::gen_returndatacopy_read_ops;
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::Error;
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`ExecError::ReturnDataOutOfBounds`](crate::circuit_input_builder::
+/// ExecError::ReturnDataOutOfBounds) error of a RETURNDATACOPY step.  The
+/// operands and the return data of the last callee are read just like in a
+/// successful RETURNDATACOPY, but nothing is copied.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorReturnDataOutOfBound;
+
+impl Opcode for ErrorReturnDataOutOfBound {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        gen_returndatacopy_read_ops(state, step)?;
+
+        gen_call_failure_ops(state, steps)
+    }
+}
@@ -0,0 +1,43 @@
+use super::error_call_failure::gen_call_failure_ops;
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::evm::OpcodeId;
+use crate::operation::{CallContextField, CallContextOp, RW};
+use crate::Error;
+use eth_types::{GethExecStep, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`ExecError::WriteProtection`](crate::circuit_input_builder::ExecError::
+/// WriteProtection) error of a state modifying step in a static call.  For
+/// CALL, the value is read to prove that it's non-zero.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorWriteProtection;
+
+impl Opcode for ErrorWriteProtection {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        if step.op == OpcodeId::CALL {
+            state.push_stack_op(
+                RW::READ,
+                step.stack.nth_last_filled(2),
+                step.stack.nth_last(2)?,
+            );
+        }
+
+        let call = state.call().clone();
+        state.push_op(
+            RW::READ,
+            CallContextOp {
+                call_id: call.call_id,
+                field: CallContextField::IsStatic,
+                value: Word::from(call.is_static as u64),
+            },
+        );
+
+        gen_call_failure_ops(state, steps)
+    }
+}
@@ -0,0 +1,243 @@
+use super::memory_copy::gen_copy_to_memory;
+use super::Opcode;
+use crate::circuit_input_builder::{CircuitInputStateRef, CopyDataType, NumberOrHash};
+use crate::operation::{
+    AccountField, AccountOp, CallContextField, CallContextOp, TxAccessListAccountOp, RW,
+};
+use crate::Error;
+use eth_types::{GethExecStep, ToAddress, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::EXTCODECOPY`](crate::evm::OpcodeId::EXTCODECOPY) `OpcodeId`.
+/// The accessed account is added into the access list (EIP-2929), and its
+/// code hash is read to generate the
+/// [`crate::circuit_input_builder::CopyEvent`] of the copy of its bytecode
+/// into memory.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Extcodecopy;
+
+impl Opcode for Extcodecopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        let address_word = step.stack.nth_last(0)?;
+        let memory_offset = step.stack.nth_last(1)?;
+        let code_offset = step.stack.nth_last(2)?;
+        let length = step.stack.nth_last(3)?;
+        let address = address_word.to_address();
+
+        state.push_stack_op(RW::READ, step.stack.nth_last_filled(0), address_word);
+        state.push_stack_op(RW::READ, step.stack.nth_last_filled(1), memory_offset);
+        state.push_stack_op(RW::READ, step.stack.nth_last_filled(2), code_offset);
+        state.push_stack_op(RW::READ, step.stack.nth_last_filled(3), length);
+
+        let call = state.call().clone();
+        for (field, value) in [
+            (CallContextField::TxId, state.tx_ctx.id().into()),
+            (
+                CallContextField::RwCounterEndOfReversion,
+                call.rw_counter_end_of_reversion.into(),
+            ),
+            (
+                CallContextField::IsPersistent,
+                Word::from(call.is_persistent as u64),
+            ),
+        ] {
+            state.push_op(
+                RW::READ,
+                CallContextOp {
+                    call_id: call.call_id,
+                    field,
+                    value,
+                },
+            );
+        }
+
+        let is_warm = !state.sdb.add_account_to_access_list(address);
+        state.push_op_reversible(
+            RW::WRITE,
+            TxAccessListAccountOp {
+                tx_id: state.tx_ctx.id(),
+                address,
+                value: true,
+                value_prev: is_warm,
+            },
+        );
+
+        let code_hash = state.sdb.get_account(&address).1.code_hash;
+        state.push_op(
+            RW::READ,
+            AccountOp {
+                address,
+                field: AccountField::CodeHash,
+                value: code_hash.to_word(),
+                value_prev: code_hash.to_word(),
+            },
+        );
+
+        if !length.is_zero() {
+            let code_size = state.code_db.0.get(&code_hash).map_or(0, |code| code.len());
+            gen_copy_to_memory(
+                state,
+                &steps[1],
+                CopyDataType::Bytecode,
+                NumberOrHash::Hash(code_hash),
+                code_offset.low_u64(),
+                code_size as u64,
+                memory_offset.low_u64(),
+                length.low_u64(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod extcodecopy_tests {
+    use super::*;
+    use crate::circuit_input_builder::{CopyEvent, ExecStep, TransactionContext};
+    use crate::operation::MemoryOp;
+    use crate::operation::RWCounter;
+    use eth_types::evm_types::StackAddress;
+    use eth_types::{address, bytecode, Word};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn extcodecopy_opcode_impl() -> Result<(), Error> {
+        let code_ext = bytecode! {
+            PUSH1(0x01u64)
+            PUSH1(0x02u64)
+            ADD
+            STOP
+        };
+        let code = bytecode! {
+            PUSH1(0x20u64) // length
+            PUSH1(0x02u64) // code_offset
+            PUSH1(0x00u64) // memory_offset
+            PUSH2(0x123u64) // address
+            #[start]
+            EXTCODECOPY
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let mut geth_data = mock::new_single_tx_trace_code_2(&code, &code_ext).unwrap();
+        geth_data.geth_trace.struct_logs =
+            geth_data.geth_trace.struct_logs[code.get_pos("start")..].to_vec();
+        let block = crate::mock::BlockData::new_from_geth_data(geth_data);
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let mut test_builder = block.new_circuit_input_builder();
+        let mut tx = test_builder
+            .new_tx(&block.eth_tx, !block.geth_trace.failed)
+            .unwrap();
+        let mut tx_ctx = TransactionContext::new(&block.eth_tx, &block.geth_trace).unwrap();
+        test_builder.begin_tx(&mut tx, &mut tx_ctx).unwrap();
+
+        // Generate step corresponding to EXTCODECOPY
+        let mut step = ExecStep::new(
+            &block.geth_trace.struct_logs[0],
+            0,
+            test_builder.block_ctx.rwc,
+            0,
+        );
+        let mut state_ref = test_builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+
+        // Add the 4 StackOp reads
+        let address = address!("0x0000000000000000000000000000000000000123");
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1020), address.to_word());
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1021), Word::from(0x00));
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1022), Word::from(0x02));
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1023), Word::from(0x20));
+
+        // Add the CallContextOp reads of the root call
+        let call_id = state_ref.call().call_id;
+        for (field, value) in [
+            (CallContextField::TxId, Word::one()),
+            (CallContextField::RwCounterEndOfReversion, Word::zero()),
+            (CallContextField::IsPersistent, Word::one()),
+        ] {
+            state_ref.push_op(
+                RW::READ,
+                CallContextOp {
+                    call_id,
+                    field,
+                    value,
+                },
+            );
+        }
+
+        // Add the access list write of the cold account
+        state_ref.push_op_reversible(
+            RW::WRITE,
+            TxAccessListAccountOp {
+                tx_id: 1,
+                address,
+                value: true,
+                value_prev: false,
+            },
+        );
+
+        // Add the code hash read
+        let code_hash = state_ref.sdb.get_account(&address).1.code_hash;
+        state_ref.push_op(
+            RW::READ,
+            AccountOp {
+                address,
+                field: AccountField::CodeHash,
+                value: code_hash.to_word(),
+                value_prev: code_hash.to_word(),
+            },
+        );
+
+        // Add the memory writes of the code from offset 2, padded with zeros
+        let code_ext = code_ext.to_vec();
+        for idx in 0..0x20 {
+            let byte = code_ext.get(idx + 2).cloned().unwrap_or(0);
+            state_ref.push_op(RW::WRITE, MemoryOp::new(call_id, idx.into(), byte));
+        }
+
+        tx.steps_mut().push(step);
+        let gas_left = builder.block.txs()[0].steps().last().unwrap().gas_left;
+        test_builder
+            .end_tx_with_gas_left(&mut tx, &mut tx_ctx, gas_left)
+            .unwrap();
+        test_builder.block.txs_mut().push(tx);
+
+        // Compare first step bus mapping instance
+        assert_eq!(
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance,
+        );
+
+        // Compare containers
+        assert_eq!(builder.block.container, test_builder.block.container);
+
+        // Check the copy event, which follows the 9 operations of the step
+        assert_eq!(builder.block.txs()[0].steps().len(), 4);
+        assert_eq!(
+            builder.block.copy_events,
+            vec![CopyEvent {
+                src_type: CopyDataType::Bytecode,
+                src_id: NumberOrHash::Hash(code_hash),
+                src_addr: 2,
+                src_addr_end: code_ext.len() as u64,
+                dst_type: CopyDataType::Memory,
+                dst_id: NumberOrHash::Number(call_id),
+                dst_addr: 0,
+                rw_counter_start: RWCounter(builder.block.txs()[0].steps()[1].rwc.0 + 9),
+                bytes: (0..0x20)
+                    .map(|idx| code_ext.get(idx + 2).cloned().unwrap_or(0))
+                    .collect(),
+            }]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,198 @@
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::operation::{
+    AccountField, AccountOp, CallContextField, CallContextOp, TxAccessListAccountOp, RW,
+};
+use crate::Error;
+use eth_types::{GethExecStep, ToAddress, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::EXTCODESIZE`](crate::evm::OpcodeId::EXTCODESIZE) `OpcodeId`.
+/// The accessed account is added into the access list (EIP-2929), and its
+/// code hash is read to look up the bytecode length.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Extcodesize;
+
+impl Opcode for Extcodesize {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        let address_word = step.stack.last()?;
+        let address = address_word.to_address();
+
+        state.push_stack_op(RW::READ, step.stack.last_filled(), address_word);
+
+        let call = state.call().clone();
+        for (field, value) in [
+            (CallContextField::TxId, state.tx_ctx.id().into()),
+            (
+                CallContextField::RwCounterEndOfReversion,
+                call.rw_counter_end_of_reversion.into(),
+            ),
+            (
+                CallContextField::IsPersistent,
+                Word::from(call.is_persistent as u64),
+            ),
+        ] {
+            state.push_op(
+                RW::READ,
+                CallContextOp {
+                    call_id: call.call_id,
+                    field,
+                    value,
+                },
+            );
+        }
+
+        let is_warm = !state.sdb.add_account_to_access_list(address);
+        state.push_op_reversible(
+            RW::WRITE,
+            TxAccessListAccountOp {
+                tx_id: state.tx_ctx.id(),
+                address,
+                value: true,
+                value_prev: is_warm,
+            },
+        );
+
+        let code_hash = state.sdb.get_account(&address).1.code_hash.to_word();
+        state.push_op(
+            RW::READ,
+            AccountOp {
+                address,
+                field: AccountField::CodeHash,
+                value: code_hash,
+                value_prev: code_hash,
+            },
+        );
+
+        state.push_stack_op(RW::WRITE, step.stack.last_filled(), steps[1].stack.last()?);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod extcodesize_tests {
+    use super::*;
+    use crate::circuit_input_builder::{ExecStep, TransactionContext};
+    use eth_types::evm_types::StackAddress;
+    use eth_types::{address, bytecode, Address, Word};
+    use pretty_assertions::assert_eq;
+
+    fn test_ok(address: Address, is_warm: bool) -> Result<(), Error> {
+        let code = bytecode! {
+            PUSH20(address.to_word())
+            #[start]
+            EXTCODESIZE
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_code_at_start(&code).unwrap(),
+        );
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let mut test_builder = block.new_circuit_input_builder();
+        let mut tx = test_builder
+            .new_tx(&block.eth_tx, !block.geth_trace.failed)
+            .unwrap();
+        let mut tx_ctx = TransactionContext::new(&block.eth_tx, &block.geth_trace).unwrap();
+        test_builder.begin_tx(&mut tx, &mut tx_ctx).unwrap();
+
+        // Generate step corresponding to EXTCODESIZE
+        let mut step = ExecStep::new(
+            &block.geth_trace.struct_logs[0],
+            0,
+            test_builder.block_ctx.rwc,
+            0,
+        );
+        let mut state_ref = test_builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+
+        // Add the StackOp read of the address
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1023), address.to_word());
+
+        // Add the CallContextOp reads of the root call
+        let call_id = state_ref.call().call_id;
+        for (field, value) in [
+            (CallContextField::TxId, Word::one()),
+            (CallContextField::RwCounterEndOfReversion, Word::zero()),
+            (CallContextField::IsPersistent, Word::one()),
+        ] {
+            state_ref.push_op(
+                RW::READ,
+                CallContextOp {
+                    call_id,
+                    field,
+                    value,
+                },
+            );
+        }
+
+        // Add the access list write
+        state_ref.push_op_reversible(
+            RW::WRITE,
+            TxAccessListAccountOp {
+                tx_id: 1,
+                address,
+                value: true,
+                value_prev: is_warm,
+            },
+        );
+
+        // Add the code hash read
+        let code_hash = state_ref.sdb.get_account(&address).1.code_hash.to_word();
+        state_ref.push_op(
+            RW::READ,
+            AccountOp {
+                address,
+                field: AccountField::CodeHash,
+                value: code_hash,
+                value_prev: code_hash,
+            },
+        );
+
+        // Add the StackOp write of the code size
+        let code_size = if address == Address::zero() {
+            code.to_vec().len()
+        } else {
+            0
+        };
+        state_ref.push_stack_op(RW::WRITE, StackAddress::from(1023), Word::from(code_size));
+
+        tx.steps_mut().push(step);
+        let gas_left = builder.block.txs()[0].steps().last().unwrap().gas_left;
+        test_builder
+            .end_tx_with_gas_left(&mut tx, &mut tx_ctx, gas_left)
+            .unwrap();
+        test_builder.block.txs_mut().push(tx);
+
+        // Compare first step bus mapping instance
+        assert_eq!(
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance,
+        );
+
+        // Compare containers
+        assert_eq!(builder.block.container, test_builder.block.container);
+
+        Ok(())
+    }
+
+    #[test]
+    fn extcodesize_opcode_impl() -> Result<(), Error> {
+        // The callee is warm and has code
+        test_ok(Address::zero(), true)?;
+        // A cold account without code
+        test_ok(
+            address!("0x0000000000000000000000000000000000000123"),
+            false,
+        )
+    }
+}
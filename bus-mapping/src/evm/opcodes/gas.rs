@@ -41,6 +41,7 @@ mod gas_tests {
         let mut test_builder = block.new_circuit_input_builder();
         let mut tx = test_builder.new_tx(&block.eth_tx, !block.geth_trace.failed)?;
         let mut tx_ctx = TransactionContext::new(&block.eth_tx, &block.geth_trace)?;
+        test_builder.begin_tx(&mut tx, &mut tx_ctx)?;
 
         let mut step = ExecStep::new(
             &block.geth_trace.struct_logs[0],
@@ -53,11 +54,15 @@ mod gas_tests {
         state_ref.push_stack_op(RW::WRITE, StackAddress::from(1022), Word::from(gas_left));
 
         tx.steps_mut().push(step);
+        let gas_left = builder.block.txs()[0].steps().last().unwrap().gas_left;
+        test_builder
+            .end_tx_with_gas_left(&mut tx, &mut tx_ctx, gas_left)
+            .unwrap();
         test_builder.block.txs_mut().push(tx);
 
         assert_eq!(
-            builder.block.txs()[0].steps()[0].bus_mapping_instance,
-            test_builder.block.txs()[0].steps()[0].bus_mapping_instance,
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance,
         );
 
         assert_eq!(builder.block.container, test_builder.block.container);
@@ -0,0 +1,119 @@
+use crate::circuit_input_builder::{
+    CircuitInputStateRef, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+};
+use crate::operation::{MemoryOp, RW};
+use crate::Error;
+use eth_types::GethExecStep;
+
+/// Generate the [`CopyEvent`] of an opcode which copies `length` bytes
+/// starting at `src_addr` of the source into the memory of the current call
+/// starting at `dst_addr`, like CALLDATACOPY.  The source buffer is bounded
+/// by `src_addr_end`, after which bytes are read as 0.  `next_step` is the
+/// [`GethExecStep`] following the copying opcode, whose memory already
+/// contains the copied bytes.  The [`MemoryOp`]s of the copy are pushed into
+/// the current step.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn gen_copy_to_memory(
+    state: &mut CircuitInputStateRef,
+    next_step: &GethExecStep,
+    src_type: CopyDataType,
+    src_id: NumberOrHash,
+    src_addr: u64,
+    src_addr_end: u64,
+    dst_addr: u64,
+    length: u64,
+) -> Result<(), Error> {
+    let bytes = (dst_addr..dst_addr + length)
+        .map(|addr| {
+            next_step
+                .memory
+                .0
+                .get(addr as usize)
+                .copied()
+                .ok_or_else(|| {
+                    Error::InvalidGethExecStep(
+                        "gen_copy_to_memory: memory not expanded",
+                        next_step.clone(),
+                    )
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let dst_id = state.call().call_id;
+    gen_copy_event(
+        state,
+        None,
+        src_type,
+        src_id,
+        src_addr,
+        src_addr_end,
+        CopyDataType::Memory,
+        NumberOrHash::Number(dst_id),
+        dst_addr,
+        bytes,
+    );
+
+    Ok(())
+}
+
+/// Generate the [`CopyEvent`] of a copy of `bytes`, found starting at
+/// `src_addr` of the source, into the destination starting at `dst_addr`,
+/// and push it into the block.  The bytes of a memory source before
+/// `src_addr_end` are read with a [`MemoryOp`], and every byte of a memory
+/// destination is written with a [`MemoryOp`].  These are pushed into
+/// `exec_step`, or into the current step if it's `None`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn gen_copy_event(
+    state: &mut CircuitInputStateRef,
+    mut exec_step: Option<&mut ExecStep>,
+    src_type: CopyDataType,
+    src_id: NumberOrHash,
+    src_addr: u64,
+    src_addr_end: u64,
+    dst_type: CopyDataType,
+    dst_id: NumberOrHash,
+    dst_addr: u64,
+    bytes: Vec<u8>,
+) {
+    if bytes.is_empty() {
+        return;
+    }
+
+    // Copying from past the end of the source only reads zeros, so the
+    // address is bounded by the end
+    let event = CopyEvent {
+        src_type,
+        src_id,
+        src_addr: src_addr.min(src_addr_end),
+        src_addr_end,
+        dst_type,
+        dst_id,
+        dst_addr,
+        rw_counter_start: state.block_ctx.rwc,
+        bytes,
+    };
+
+    let src_bytes = event.src_bytes() as usize;
+    for (idx, byte) in event.bytes.iter().enumerate() {
+        let mut ops = Vec::with_capacity(2);
+        if let (CopyDataType::Memory, NumberOrHash::Number(call_id), true) =
+            (src_type, src_id, idx < src_bytes)
+        {
+            let addr = event.src_addr as usize + idx;
+            ops.push((RW::READ, MemoryOp::new(call_id, addr.into(), *byte)));
+        }
+        if let (CopyDataType::Memory, NumberOrHash::Number(call_id)) = (dst_type, dst_id) {
+            let addr = dst_addr as usize + idx;
+            ops.push((RW::WRITE, MemoryOp::new(call_id, addr.into(), *byte)));
+        }
+
+        for (rw, op) in ops {
+            match exec_step.as_deref_mut() {
+                Some(step) => state.push_op_to_step(step, rw, op),
+                None => state.push_op(rw, op),
+            }
+        }
+    }
+
+    state.block.add_copy_event(event);
+}
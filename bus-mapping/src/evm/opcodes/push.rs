@@ -60,6 +60,7 @@ mod push_tests {
             .new_tx(&block.eth_tx, !block.geth_trace.failed)
             .unwrap();
         let mut tx_ctx = TransactionContext::new(&block.eth_tx, &block.geth_trace).unwrap();
+        test_builder.begin_tx(&mut tx, &mut tx_ctx).unwrap();
 
         // Generate steps corresponding to PUSH1 80, PUSH2 1234,
         // PUSH16 0x00112233445566778899aabbccddeeff
@@ -84,10 +85,14 @@ mod push_tests {
             tx.steps_mut().push(step);
         }
 
+        let gas_left = builder.block.txs()[0].steps().last().unwrap().gas_left;
+        test_builder
+            .end_tx_with_gas_left(&mut tx, &mut tx_ctx, gas_left)
+            .unwrap();
         test_builder.block.txs_mut().push(tx);
 
-        // Compare first 3 steps bus mapping instance
-        for i in 0..3 {
+        // Compare the 3 steps after BeginTx bus mapping instance
+        for i in 1..4 {
             assert_eq!(
                 builder.block.txs()[0].steps()[i].bus_mapping_instance,
                 test_builder.block.txs()[0].steps()[i].bus_mapping_instance
@@ -0,0 +1,252 @@
+use super::memory_copy::gen_copy_to_memory;
+use super::Opcode;
+use crate::circuit_input_builder::{CircuitInputStateRef, CopyDataType, NumberOrHash};
+use crate::operation::{CallContextField, CallContextOp, RW};
+use crate::Error;
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::RETURNDATACOPY`](crate::evm::OpcodeId::RETURNDATACOPY)
+/// `OpcodeId`.
+/// Besides the [`crate::operation::StackOp`]s and
+/// [`crate::operation::CallContextOp`]s of the step itself, this generates the
+/// [`crate::circuit_input_builder::CopyEvent`] of the copy of the return data
+/// of the last callee from its memory.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Returndatacopy;
+
+impl Opcode for Returndatacopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        let memory_offset = step.stack.nth_last(0)?;
+        let data_offset = step.stack.nth_last(1)?;
+        let length = step.stack.nth_last(2)?;
+
+        gen_returndatacopy_read_ops(state, step)?;
+
+        if !length.is_zero() {
+            let call = state.call().clone();
+            gen_copy_to_memory(
+                state,
+                &steps[1],
+                CopyDataType::Memory,
+                NumberOrHash::Number(call.last_callee_id),
+                call.last_callee_return_data_offset + data_offset.low_u64(),
+                call.last_callee_return_data_offset + call.last_callee_return_data_length,
+                memory_offset.low_u64(),
+                length.low_u64(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Generate the [`crate::operation::StackOp`]s of the operands of a
+/// RETURNDATACOPY step, and the [`CallContextOp`]s of the last callee whose
+/// return data is copied.  These are also needed to prove that the copied
+/// range is out of the bound of the return data, when the step fails.
+pub(crate) fn gen_returndatacopy_read_ops(
+    state: &mut CircuitInputStateRef,
+    step: &GethExecStep,
+) -> Result<(), Error> {
+    for i in 0..3 {
+        state.push_stack_op(
+            RW::READ,
+            step.stack.nth_last_filled(i),
+            step.stack.nth_last(i)?,
+        );
+    }
+
+    let call = state.call().clone();
+    for (field, value) in [
+        (CallContextField::LastCalleeId, call.last_callee_id.into()),
+        (
+            CallContextField::LastCalleeReturnDataOffset,
+            call.last_callee_return_data_offset.into(),
+        ),
+        (
+            CallContextField::LastCalleeReturnDataLength,
+            call.last_callee_return_data_length.into(),
+        ),
+    ] {
+        state.push_op(
+            RW::READ,
+            CallContextOp {
+                call_id: call.call_id,
+                field,
+                value,
+            },
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod returndatacopy_tests {
+    use super::*;
+    use crate::circuit_input_builder::{ExecStep, TransactionContext};
+    use eth_types::evm_types::{OpcodeId, StackAddress};
+    use eth_types::{bytecode, Word};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn returndatacopy_opcode_impl() -> Result<(), Error> {
+        // No call has been made, so only an empty return data can be copied
+        let code = bytecode! {
+            PUSH1(0x00u64) // length
+            PUSH1(0x00u64) // data_offset
+            PUSH1(0x40u64) // memory_offset
+            #[start]
+            RETURNDATACOPY
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_code_at_start(&code).unwrap(),
+        );
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let mut test_builder = block.new_circuit_input_builder();
+        let mut tx = test_builder
+            .new_tx(&block.eth_tx, !block.geth_trace.failed)
+            .unwrap();
+        let mut tx_ctx = TransactionContext::new(&block.eth_tx, &block.geth_trace).unwrap();
+        test_builder.begin_tx(&mut tx, &mut tx_ctx).unwrap();
+
+        // Generate step corresponding to RETURNDATACOPY
+        let mut step = ExecStep::new(
+            &block.geth_trace.struct_logs[0],
+            0,
+            test_builder.block_ctx.rwc,
+            0,
+        );
+        let mut state_ref = test_builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+
+        // Add the 3 StackOp reads
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1021), Word::from(0x40));
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1022), Word::zero());
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1023), Word::zero());
+
+        // Add the reads of the last callee, which doesn't exist
+        let call_id = state_ref.call().call_id;
+        for field in [
+            CallContextField::LastCalleeId,
+            CallContextField::LastCalleeReturnDataOffset,
+            CallContextField::LastCalleeReturnDataLength,
+        ] {
+            state_ref.push_op(
+                RW::READ,
+                CallContextOp {
+                    call_id,
+                    field,
+                    value: Word::zero(),
+                },
+            );
+        }
+
+        tx.steps_mut().push(step);
+        let gas_left = builder.block.txs()[0].steps().last().unwrap().gas_left;
+        test_builder
+            .end_tx_with_gas_left(&mut tx, &mut tx_ctx, gas_left)
+            .unwrap();
+        test_builder.block.txs_mut().push(tx);
+
+        // Compare first step bus mapping instance
+        assert_eq!(
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance,
+        );
+
+        // Compare containers
+        assert_eq!(builder.block.container, test_builder.block.container);
+
+        // Nothing is copied
+        assert!(builder.block.copy_events.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn returndatacopy_after_call_with_return_data() {
+        let code_a = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH2(0x123) // address
+            PUSH2(0xffff) // gas
+            CALL
+            POP
+            PUSH1(0x10) // length
+            PUSH1(0x08) // data_offset
+            PUSH1(0x40) // memory_offset
+            RETURNDATACOPY
+            STOP
+        };
+        // Return the 32 bytes at memory offset 0x20
+        let code_b = bytecode! {
+            PUSH32(Word::from_big_endian(&[0xab; 32]))
+            PUSH1(0x20)
+            MSTORE
+            PUSH1(0x20) // length
+            PUSH1(0x20) // offset
+            RETURN
+        };
+
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_code_2(&code_a, &code_b).unwrap(),
+        );
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let tx = &builder.block.txs()[0];
+        let callee = &tx.calls()[1];
+        let step = tx
+            .steps()
+            .iter()
+            .find(|step| step.op == OpcodeId::RETURNDATACOPY)
+            .unwrap();
+        let container = &builder.block.container;
+        assert_eq!(
+            step.bus_mapping_instance[3..6]
+                .iter()
+                .map(|op_ref| {
+                    let op = container.call_context[op_ref.as_usize()].op();
+                    (op.field.clone(), op.value)
+                })
+                .collect::<Vec<_>>(),
+            vec![
+                (CallContextField::LastCalleeId, Word::from(callee.call_id)),
+                (
+                    CallContextField::LastCalleeReturnDataOffset,
+                    Word::from(0x20)
+                ),
+                (
+                    CallContextField::LastCalleeReturnDataLength,
+                    Word::from(0x20)
+                ),
+            ]
+        );
+
+        // The return data is copied from the memory of the callee
+        let copy_event = builder.block.copy_events.last().unwrap();
+        assert_eq!(copy_event.src_type, CopyDataType::Memory);
+        assert_eq!(copy_event.src_id, NumberOrHash::Number(callee.call_id));
+        assert_eq!(copy_event.src_addr, 0x28);
+        assert_eq!(copy_event.src_addr_end, 0x40);
+        assert_eq!(copy_event.dst_type, CopyDataType::Memory);
+        assert_eq!(copy_event.dst_addr, 0x40);
+        assert_eq!(copy_event.bytes, vec![0xab; 0x10]);
+    }
+}
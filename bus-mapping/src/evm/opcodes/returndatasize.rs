@@ -0,0 +1,186 @@
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::operation::{CallContextField, CallContextOp, RW};
+use crate::Error;
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::RETURNDATASIZE`](crate::evm::OpcodeId::RETURNDATASIZE)
+/// `OpcodeId`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Returndatasize;
+
+impl Opcode for Returndatasize {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        // Get the length of the last callee's return data from next step
+        let value = steps[1].stack.last()?;
+        state.push_op(
+            RW::READ,
+            CallContextOp {
+                call_id: state.call().call_id,
+                field: CallContextField::LastCalleeReturnDataLength,
+                value,
+            },
+        );
+        state.push_stack_op(RW::WRITE, step.stack.last_filled().map(|a| a - 1), value);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod returndatasize_tests {
+    use super::*;
+    use crate::circuit_input_builder::{ExecStep, TransactionContext};
+    use eth_types::evm_types::{OpcodeId, StackAddress};
+    use eth_types::{bytecode, Word};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn returndatasize_opcode_impl() -> Result<(), Error> {
+        let code = bytecode! {
+            #[start]
+            RETURNDATASIZE
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_code_at_start(&code).unwrap(),
+        );
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let mut test_builder = block.new_circuit_input_builder();
+        let mut tx = test_builder
+            .new_tx(&block.eth_tx, !block.geth_trace.failed)
+            .unwrap();
+        let mut tx_ctx = TransactionContext::new(&block.eth_tx, &block.geth_trace).unwrap();
+        test_builder.begin_tx(&mut tx, &mut tx_ctx).unwrap();
+
+        // Generate step corresponding to RETURNDATASIZE
+        let mut step = ExecStep::new(
+            &block.geth_trace.struct_logs[0],
+            0,
+            test_builder.block_ctx.rwc,
+            0,
+        );
+        let mut state_ref = test_builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+
+        // No call has been made yet, so the return data is empty
+        state_ref.push_op(
+            RW::READ,
+            CallContextOp {
+                call_id: state_ref.call().call_id,
+                field: CallContextField::LastCalleeReturnDataLength,
+                value: Word::zero(),
+            },
+        );
+        state_ref.push_stack_op(RW::WRITE, StackAddress::from(1024 - 1), Word::zero());
+
+        tx.steps_mut().push(step);
+        let gas_left = builder.block.txs()[0].steps().last().unwrap().gas_left;
+        test_builder
+            .end_tx_with_gas_left(&mut tx, &mut tx_ctx, gas_left)
+            .unwrap();
+        test_builder.block.txs_mut().push(tx);
+
+        // Compare first step bus mapping instance
+        assert_eq!(
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance,
+        );
+
+        // Compare containers
+        assert_eq!(builder.block.container, test_builder.block.container);
+
+        Ok(())
+    }
+
+    /// Callee code that returns 32 bytes of data when called with calldata,
+    /// and stops without return data otherwise.
+    fn callee_code() -> eth_types::Bytecode {
+        bytecode! {
+            CALLDATASIZE
+            PUSH1(0x05) // dest
+            JUMPI
+            STOP
+            JUMPDEST
+            PUSH1(0x42)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x20) // length
+            PUSH1(0x00) // offset
+            RETURN
+        }
+    }
+
+    /// Returns the last callee return data length read by RETURNDATASIZE and
+    /// the value it pushes to the stack, after the calls to the callee at
+    /// `0x123` with the given calldata lengths.
+    fn returndatasize_after_calls(args_lengths: &[u64]) -> (Word, Word) {
+        let mut code = eth_types::Bytecode::default();
+        for &args_length in args_lengths {
+            code.append(&bytecode! {
+                PUSH1(0x00) // retLength
+                PUSH1(0x00) // retOffset
+                PUSH1(args_length) // argsLength
+                PUSH1(0x00) // argsOffset
+                PUSH1(0x00) // value
+                PUSH2(0x123) // address
+                PUSH2(0xffff) // gas
+                CALL
+                POP
+            });
+        }
+        code.append(&bytecode! {
+            RETURNDATASIZE
+            STOP
+        });
+
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_code_2(&code, &callee_code()).unwrap(),
+        );
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.op == OpcodeId::RETURNDATASIZE)
+            .unwrap();
+        let container = &builder.block.container;
+        let call_context_op = &container.call_context[step.bus_mapping_instance[0].as_usize()];
+        let stack_op = &container.stack[step.bus_mapping_instance[1].as_usize()];
+        assert_eq!(
+            call_context_op.op().field,
+            CallContextField::LastCalleeReturnDataLength
+        );
+        (call_context_op.op().value, stack_op.op().value)
+    }
+
+    #[test]
+    fn returndatasize_after_call_with_return_data() {
+        assert_eq!(
+            returndatasize_after_calls(&[0x20]),
+            (Word::from(0x20), Word::from(0x20))
+        );
+    }
+
+    #[test]
+    fn returndatasize_after_call_without_return_data() {
+        // The return data of the first call is discarded by the second one,
+        // which stops without return data.
+        assert_eq!(
+            returndatasize_after_calls(&[0x20, 0x00]),
+            (Word::zero(), Word::zero())
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use super::error_call_failure::gen_call_failure_ops;
+use super::memory_copy::gen_copy_event;
+use super::Opcode;
+use crate::circuit_input_builder::{
+    get_return_code, CircuitInputStateRef, CopyDataType, NumberOrHash,
+};
+use crate::operation::{AccountField, AccountOp, CallContextField, CallContextOp, RW};
+use crate::Error;
+use eth_types::{
+    evm_types::{MemoryAddress, OpcodeId},
+    GethExecStep, ToWord, Word,
+};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::RETURN`](crate::evm::OpcodeId::RETURN)
+/// and [`OpcodeId::REVERT`](crate::evm::OpcodeId::REVERT) `OpcodeId`s, which
+/// return to the caller with the return data, or deploy the code of a
+/// successful CREATE* or creation transaction in the case of RETURN.  REVERT
+/// fails the current call, whose state writes are reverted.
+/// Besides the [`crate::operation::StackOp`]s of the step itself, and the read
+/// of the first byte of a deployed code, this generates the
+/// [`crate::circuit_input_builder::CopyEvent`] of the copy of the deployed code
+/// into its RLC, which is hashed, or of the copy of the return data into the
+/// memory of the caller.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ReturnOp;
+
+impl Opcode for ReturnOp {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        let offset = step.stack.nth_last(0)?;
+        let length = step.stack.nth_last(1)?;
+
+        state.push_stack_op(RW::READ, step.stack.nth_last_filled(0), offset);
+        state.push_stack_op(RW::READ, step.stack.nth_last_filled(1), length);
+
+        let call = state.call().clone();
+        let data = get_return_code(step)?;
+        let offset = if data.is_empty() { 0 } else { offset.low_u64() };
+        let push_call_context_reads = |state: &mut CircuitInputStateRef, fields: [_; 3]| {
+            for (field, value) in fields {
+                state.push_op(
+                    RW::READ,
+                    CallContextOp {
+                        call_id: call.call_id,
+                        field,
+                        value,
+                    },
+                );
+            }
+        };
+
+        let is_revert = step.op == OpcodeId::REVERT;
+        if call.is_create() && !is_revert {
+            // The deployed code is read from memory into its RLC, which is
+            // hashed into the code hash written at the created address.  The
+            // write is reverted if any of the callers fails later.
+            push_call_context_reads(
+                state,
+                [
+                    (CallContextField::CalleeAddress, call.address.to_word()),
+                    (
+                        CallContextField::RwCounterEndOfReversion,
+                        call.rw_counter_end_of_reversion.into(),
+                    ),
+                    (
+                        CallContextField::IsPersistent,
+                        Word::from(call.is_persistent as u64),
+                    ),
+                ],
+            );
+            // The first byte is read to prove that it isn't 0xef (EIP-3541)
+            if let Some(byte) = data.first() {
+                state.push_memory_op(RW::READ, MemoryAddress(offset as usize), *byte);
+            }
+            gen_copy_event(
+                state,
+                None,
+                CopyDataType::Memory,
+                NumberOrHash::Number(call.call_id),
+                offset,
+                offset + data.len() as u64,
+                CopyDataType::RlcAcc,
+                NumberOrHash::Number(0),
+                0,
+                data.clone(),
+            );
+            state.block.add_sha3_input(data.clone());
+            let code_hash = state.code_db.insert(data);
+            let (_, account) = state.sdb.get_account_mut(&call.address);
+            let code_hash_prev = account.code_hash;
+            account.code_hash = code_hash;
+            state.push_op_reversible(
+                RW::WRITE,
+                AccountOp {
+                    address: call.address,
+                    field: AccountField::CodeHash,
+                    value: code_hash.to_word(),
+                    value_prev: code_hash_prev.to_word(),
+                },
+            );
+        } else if !call.is_create() && !call.is_root {
+            // The return data is copied into the memory of the caller, up to
+            // the length it expects.  A creation doesn't copy it, even when it
+            // reverts.
+            push_call_context_reads(
+                state,
+                [
+                    (CallContextField::CallerId, call.caller_id.into()),
+                    (
+                        CallContextField::ReturnDataOffset,
+                        call.return_data_offset.into(),
+                    ),
+                    (
+                        CallContextField::ReturnDataLength,
+                        call.return_data_length.into(),
+                    ),
+                ],
+            );
+            let length = data.len().min(call.return_data_length as usize);
+            gen_copy_event(
+                state,
+                None,
+                CopyDataType::Memory,
+                NumberOrHash::Number(call.call_id),
+                offset,
+                offset + data.len() as u64,
+                CopyDataType::Memory,
+                NumberOrHash::Number(call.caller_id),
+                call.return_data_offset,
+                data[..length].to_vec(),
+            );
+        }
+
+        if is_revert {
+            gen_call_failure_ops(state, steps)
+        } else {
+            state.handle_return(steps)
+        }
+    }
+}
@@ -0,0 +1,335 @@
+use super::Opcode;
+use crate::circuit_input_builder::CircuitInputStateRef;
+use crate::operation::{
+    AccountDestructedOp, AccountField, AccountOp, CallContextField, CallContextOp,
+    TxAccessListAccountOp, RW,
+};
+use crate::Error;
+use eth_types::{GethExecStep, ToAddress, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::SELFDESTRUCT`](crate::evm::OpcodeId::SELFDESTRUCT) `OpcodeId`.
+/// The beneficiary is added into the access list (EIP-2929), and its nonce and
+/// code hash are read to check whether it's empty.  The whole balance of the
+/// callee is then transferred to the beneficiary, and the callee is marked as
+/// destructed in the current transaction.  All the writes are reverted if the
+/// call fails.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Selfdestruct;
+
+impl Opcode for Selfdestruct {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        let beneficiary_word = step.stack.last()?;
+        let beneficiary = beneficiary_word.to_address();
+
+        state.push_stack_op(RW::READ, step.stack.last_filled(), beneficiary_word);
+
+        let call = state.call().clone();
+        for (field, value) in [
+            (CallContextField::TxId, state.tx_ctx.id().into()),
+            (CallContextField::CalleeAddress, call.address.to_word()),
+            (
+                CallContextField::RwCounterEndOfReversion,
+                call.rw_counter_end_of_reversion.into(),
+            ),
+            (
+                CallContextField::IsPersistent,
+                Word::from(call.is_persistent as u64),
+            ),
+        ] {
+            state.push_op(
+                RW::READ,
+                CallContextOp {
+                    call_id: call.call_id,
+                    field,
+                    value,
+                },
+            );
+        }
+
+        let is_warm = !state.sdb.add_account_to_access_list(beneficiary);
+        state.push_op_reversible(
+            RW::WRITE,
+            TxAccessListAccountOp {
+                tx_id: state.tx_ctx.id(),
+                address: beneficiary,
+                value: true,
+                value_prev: is_warm,
+            },
+        );
+
+        // Read nonce and code hash of the beneficiary to check whether it's
+        // empty, which costs extra gas when receiving a non-zero value
+        let beneficiary_account = state.sdb.get_account(&beneficiary).1.clone();
+        for (field, value) in [
+            (AccountField::Nonce, beneficiary_account.nonce),
+            (
+                AccountField::CodeHash,
+                beneficiary_account.code_hash.to_word(),
+            ),
+        ] {
+            state.push_op(
+                RW::READ,
+                AccountOp {
+                    address: beneficiary,
+                    field,
+                    value,
+                    value_prev: value,
+                },
+            );
+        }
+
+        // Read the balance of the callee, which is the value to transfer
+        let value = state.sdb.get_account(&call.address).1.balance;
+        state.push_op(
+            RW::READ,
+            AccountOp {
+                address: call.address,
+                field: AccountField::Balance,
+                value,
+                value_prev: value,
+            },
+        );
+
+        // Add the value to the beneficiary first and then clear the balance of
+        // the callee, so the value is burnt when the beneficiary is the callee
+        // itself.
+        for (address, add_value) in [(beneficiary, true), (call.address, false)] {
+            let (_, account) = state.sdb.get_account_mut(&address);
+            let balance_prev = account.balance;
+            let balance = if add_value {
+                balance_prev + value
+            } else {
+                Word::zero()
+            };
+            account.balance = balance;
+            state.push_op_reversible(
+                RW::WRITE,
+                AccountOp {
+                    address,
+                    field: AccountField::Balance,
+                    value: balance,
+                    value_prev: balance_prev,
+                },
+            );
+        }
+
+        let is_destructed = !state.sdb.destruct_account(call.address);
+        state.push_op_reversible(
+            RW::WRITE,
+            AccountDestructedOp {
+                tx_id: state.tx_ctx.id(),
+                address: call.address,
+                value: true,
+                value_prev: is_destructed,
+            },
+        );
+
+        state.handle_return(steps)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod selfdestruct_tests {
+    use super::*;
+    use crate::circuit_input_builder::{ExecStep, TransactionContext};
+    use crate::mpt::StateTrie;
+    use eth_types::evm_types::StackAddress;
+    use eth_types::{address, bytecode, Address};
+    use pretty_assertions::assert_eq;
+
+    fn test_ok(beneficiary: Address, is_warm: bool) -> Result<(), Error> {
+        let code = bytecode! {
+            PUSH20(beneficiary.to_word())
+            #[start]
+            SELFDESTRUCT
+        };
+
+        // Get the execution steps from the external tracer
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_code_at_start(&code).unwrap(),
+        );
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let mut test_builder = block.new_circuit_input_builder();
+        let mut tx = test_builder
+            .new_tx(&block.eth_tx, !block.geth_trace.failed)
+            .unwrap();
+        let mut tx_ctx = TransactionContext::new(&block.eth_tx, &block.geth_trace).unwrap();
+        test_builder.begin_tx(&mut tx, &mut tx_ctx).unwrap();
+
+        // Generate step corresponding to SELFDESTRUCT
+        let mut step = ExecStep::new(
+            &block.geth_trace.struct_logs[0],
+            0,
+            test_builder.block_ctx.rwc,
+            0,
+        );
+        let mut state_ref = test_builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+
+        // Add the StackOp read of the beneficiary
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1023), beneficiary.to_word());
+
+        // Add the CallContextOp reads of the root call
+        let call_id = state_ref.call().call_id;
+        let callee = state_ref.call().address;
+        for (field, value) in [
+            (CallContextField::TxId, Word::one()),
+            (CallContextField::CalleeAddress, callee.to_word()),
+            (CallContextField::RwCounterEndOfReversion, Word::zero()),
+            (CallContextField::IsPersistent, Word::one()),
+        ] {
+            state_ref.push_op(
+                RW::READ,
+                CallContextOp {
+                    call_id,
+                    field,
+                    value,
+                },
+            );
+        }
+
+        // Add the access list write
+        state_ref.push_op_reversible(
+            RW::WRITE,
+            TxAccessListAccountOp {
+                tx_id: 1,
+                address: beneficiary,
+                value: true,
+                value_prev: is_warm,
+            },
+        );
+
+        // Add the nonce and code hash reads of the beneficiary
+        let beneficiary_account = block.sdb.get_account(&beneficiary).1.clone();
+        for (field, value) in [
+            (AccountField::Nonce, beneficiary_account.nonce),
+            (
+                AccountField::CodeHash,
+                beneficiary_account.code_hash.to_word(),
+            ),
+        ] {
+            state_ref.push_op(
+                RW::READ,
+                AccountOp {
+                    address: beneficiary,
+                    field,
+                    value,
+                    value_prev: value,
+                },
+            );
+        }
+
+        // Add the balance read of the callee and the transfer
+        let value = block.sdb.get_account(&callee).1.balance;
+        state_ref.push_op(
+            RW::READ,
+            AccountOp {
+                address: callee,
+                field: AccountField::Balance,
+                value,
+                value_prev: value,
+            },
+        );
+        let beneficiary_balance_prev = beneficiary_account.balance;
+        let beneficiary_balance = beneficiary_balance_prev + value;
+        state_ref.push_op_reversible(
+            RW::WRITE,
+            AccountOp {
+                address: beneficiary,
+                field: AccountField::Balance,
+                value: beneficiary_balance,
+                value_prev: beneficiary_balance_prev,
+            },
+        );
+        state_ref.push_op_reversible(
+            RW::WRITE,
+            AccountOp {
+                address: callee,
+                field: AccountField::Balance,
+                value: Word::zero(),
+                value_prev: if beneficiary == callee {
+                    beneficiary_balance
+                } else {
+                    value
+                },
+            },
+        );
+
+        // Add the account destruction
+        state_ref.push_op_reversible(
+            RW::WRITE,
+            AccountDestructedOp {
+                tx_id: 1,
+                address: callee,
+                value: true,
+                value_prev: false,
+            },
+        );
+
+        tx.steps_mut().push(step);
+        let gas_left = builder.block.txs()[0].steps().last().unwrap().gas_left;
+        test_builder
+            .end_tx_with_gas_left(&mut tx, &mut tx_ctx, gas_left)
+            .unwrap();
+        test_builder.block.txs_mut().push(tx);
+
+        // Compare first step bus mapping instance
+        assert_eq!(
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance,
+        );
+
+        // Compare containers
+        assert_eq!(builder.block.container, test_builder.block.container);
+
+        // The callee is deleted at the end of the transaction
+        assert!(!builder.sdb.get_account(&callee).0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn selfdestruct_opcode_impl() -> Result<(), Error> {
+        // The callee is the beneficiary, and the value is burnt
+        test_ok(Address::zero(), true)?;
+        // A cold beneficiary which doesn't exist
+        test_ok(
+            address!("0x0000000000000000000000000000000000000123"),
+            false,
+        )
+    }
+
+    #[test]
+    fn selfdestruct_removes_storage_from_state_trie() {
+        let code = bytecode! {
+            PUSH20(0x123)
+            SELFDESTRUCT
+        };
+        let mut geth_data = mock::new_single_tx_trace_code(&code).unwrap();
+        // The storage of the callee isn't accessed by the transaction
+        geth_data.accounts[0]
+            .storage
+            .insert(Word::one(), Word::from(2));
+        let block = crate::mock::BlockData::new_from_geth_data(geth_data);
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+        builder.gen_mpt_updates().unwrap();
+
+        // The callee is removed from the state trie along with its storage
+        assert_eq!(
+            builder.block.state_root,
+            StateTrie::from_state_db(&builder.sdb).root()
+        );
+    }
+}
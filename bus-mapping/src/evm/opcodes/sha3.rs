@@ -0,0 +1,166 @@
+use super::memory_copy::gen_copy_event;
+use super::Opcode;
+use crate::circuit_input_builder::{CircuitInputStateRef, CopyDataType, NumberOrHash};
+use crate::operation::RW;
+use crate::Error;
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::SHA3`](crate::evm::OpcodeId::SHA3)
+/// `OpcodeId`.
+/// Besides the [`crate::operation::StackOp`]s of the step itself, this
+/// generates the [`crate::circuit_input_builder::CopyEvent`] of the copy of the
+/// hashed memory into its RLC, and pushes the hashed bytes into the inputs of
+/// the Keccak circuit.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Sha3;
+
+impl Opcode for Sha3 {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let step = &steps[0];
+        let offset = step.stack.nth_last(0)?;
+        let length = step.stack.nth_last(1)?;
+
+        state.push_stack_op(RW::READ, step.stack.nth_last_filled(0), offset);
+        state.push_stack_op(RW::READ, step.stack.nth_last_filled(1), length);
+
+        // The memory isn't expanded yet, so the bytes past its end are 0
+        let (offset, length) = if length.is_zero() {
+            (0, 0)
+        } else {
+            (offset.low_u64(), length.low_u64())
+        };
+        let bytes = (offset..offset + length)
+            .map(|addr| step.memory.0.get(addr as usize).copied().unwrap_or(0))
+            .collect::<Vec<_>>();
+        let call_id = state.call().call_id;
+        gen_copy_event(
+            state,
+            None,
+            CopyDataType::Memory,
+            NumberOrHash::Number(call_id),
+            offset,
+            offset + length,
+            CopyDataType::RlcAcc,
+            NumberOrHash::Number(0),
+            0,
+            bytes.clone(),
+        );
+        state.block.add_sha3_input(bytes);
+
+        state.push_stack_op(
+            RW::WRITE,
+            step.stack.nth_last_filled(1),
+            steps[1].stack.last()?,
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod sha3_tests {
+    use super::*;
+    use crate::circuit_input_builder::{CopyEvent, ExecStep, TransactionContext};
+    use crate::operation::{MemoryOp, RWCounter};
+    use eth_types::evm_types::StackAddress;
+    use eth_types::{bytecode, ToBigEndian, Word};
+    use ethers_core::utils::keccak256;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn sha3_opcode_impl() -> Result<(), Error> {
+        let code = bytecode! {
+            PUSH32(Word::from(0x1234))
+            PUSH1(0x00u64)
+            MSTORE
+            PUSH1(0x28u64) // length
+            PUSH1(0x10u64) // offset
+            #[start]
+            SHA3
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block = crate::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_code_at_start(&code).unwrap(),
+        );
+
+        let mut builder = block.new_circuit_input_builder();
+        builder.handle_tx(&block.eth_tx, &block.geth_trace).unwrap();
+
+        let mut test_builder = block.new_circuit_input_builder();
+        let mut tx = test_builder
+            .new_tx(&block.eth_tx, !block.geth_trace.failed)
+            .unwrap();
+        let mut tx_ctx = TransactionContext::new(&block.eth_tx, &block.geth_trace).unwrap();
+        test_builder.begin_tx(&mut tx, &mut tx_ctx).unwrap();
+
+        // Generate step corresponding to SHA3
+        let mut step = ExecStep::new(
+            &block.geth_trace.struct_logs[0],
+            0,
+            test_builder.block_ctx.rwc,
+            0,
+        );
+        let mut state_ref = test_builder.state_ref(&mut tx, &mut tx_ctx, &mut step);
+
+        // The hashed bytes are the low 16 bytes of the stored word, followed
+        // by 24 bytes past the end of the memory, which are 0
+        let bytes = Word::from(0x1234).to_be_bytes()[0x10..]
+            .iter()
+            .copied()
+            .chain(std::iter::repeat(0).take(0x18))
+            .collect::<Vec<_>>();
+        let hash = Word::from_big_endian(&keccak256(&bytes));
+
+        // Add the 2 StackOp reads, the reads of every hashed byte from memory
+        // and the StackOp write of the hash
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1022), Word::from(0x10));
+        state_ref.push_stack_op(RW::READ, StackAddress::from(1023), Word::from(0x28));
+        let call_id = state_ref.call().call_id;
+        for (idx, byte) in bytes.iter().enumerate() {
+            state_ref.push_op(RW::READ, MemoryOp::new(call_id, (0x10 + idx).into(), *byte));
+        }
+        state_ref.push_stack_op(RW::WRITE, StackAddress::from(1023), hash);
+
+        tx.steps_mut().push(step);
+        let gas_left = builder.block.txs()[0].steps().last().unwrap().gas_left;
+        test_builder
+            .end_tx_with_gas_left(&mut tx, &mut tx_ctx, gas_left)
+            .unwrap();
+        test_builder.block.txs_mut().push(tx);
+
+        // Compare first step bus mapping instance
+        assert_eq!(
+            builder.block.txs()[0].steps()[1].bus_mapping_instance,
+            test_builder.block.txs()[0].steps()[1].bus_mapping_instance,
+        );
+
+        // Compare containers
+        assert_eq!(builder.block.container, test_builder.block.container);
+
+        // Check the copy event, which follows the 2 reads of the step, and the
+        // input of the Keccak circuit
+        assert_eq!(
+            builder.block.copy_events,
+            vec![CopyEvent {
+                src_type: CopyDataType::Memory,
+                src_id: NumberOrHash::Number(call_id),
+                src_addr: 0x10,
+                src_addr_end: 0x38,
+                dst_type: CopyDataType::RlcAcc,
+                dst_id: NumberOrHash::Number(0),
+                dst_addr: 0,
+                rw_counter_start: RWCounter(builder.block.txs()[0].steps()[1].rwc.0 + 2),
+                bytes: bytes.clone(),
+            }]
+        );
+        assert_eq!(builder.block.sha3_inputs, vec![bytes]);
+
+        Ok(())
+    }
+}
@@ -15,9 +15,9 @@ pub(crate) struct Stop;
 impl Opcode for Stop {
     fn gen_associated_ops(
         state: &mut CircuitInputStateRef,
-        _steps: &[GethExecStep],
+        steps: &[GethExecStep],
     ) -> Result<(), Error> {
-        state.handle_return()?;
+        state.handle_return(steps)?;
 
         Ok(())
     }
@@ -214,7 +214,9 @@ pub mod evm;
 pub mod exec_trace;
 pub(crate) mod geth_errors;
 pub mod mock;
+pub mod mpt;
 pub mod operation;
+pub mod precompile;
 pub mod rpc;
 pub mod state_db;
 pub use error::Error;
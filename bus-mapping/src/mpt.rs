@@ -0,0 +1,1182 @@
+//! Merkle Patricia Tries of the Ethereum state, built from the proofs of the
+//! accounts and storage accessed in a block, which prove the values at the
+//! start of the block and give the state root after its writes.
+
+use crate::{
+    operation::AccountField,
+    state_db::{Account, StateDB},
+    Error,
+};
+use eth_types::{Address, EIP1186ProofResponse, Hash, ToBigEndian, ToWord, Word, H256};
+use ethers_core::utils::keccak256;
+use lazy_static::lazy_static;
+use rlp::{Rlp, RlpStream};
+use std::{collections::HashMap, ops::Range};
+
+lazy_static! {
+    /// The root of an empty trie, which is the hash of the RLP encoding of
+    /// an empty string.
+    pub static ref EMPTY_ROOT: Hash = H256(keccak256(&EMPTY_NODE));
+    static ref EMPTY_CODE_HASH: Hash = H256(keccak256(&[]));
+}
+
+/// The RLP encoding of an empty node.
+const EMPTY_NODE: [u8; 1] = [0x80];
+
+/// A node of a [`Trie`], whose path is in nibbles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Empty,
+    Leaf {
+        path: Vec<u8>,
+        value: Vec<u8>,
+    },
+    Extension {
+        path: Vec<u8>,
+        child: Box<Node>,
+    },
+    Branch {
+        children: Vec<Node>,
+        value: Vec<u8>,
+    },
+    /// A node referenced by the hash of its encoding, which is in the
+    /// database of the trie when known.
+    Hash(Hash),
+}
+
+impl Node {
+    fn empty_branch() -> Self {
+        Node::Branch {
+            children: vec![Node::Empty; 16],
+            value: Vec::new(),
+        }
+    }
+
+    /// Return `child` preceded by `path`, which is `child` itself when `path`
+    /// is empty.
+    fn extension(path: Vec<u8>, child: Node) -> Self {
+        if path.is_empty() {
+            child
+        } else {
+            Node::Extension {
+                path,
+                child: Box::new(child),
+            }
+        }
+    }
+
+    /// Return the RLP encoding of the node, whose children need to be either
+    /// referenced by their hashes or shorter than 32 bytes.
+    fn encode(&self) -> Vec<u8> {
+        match self {
+            Node::Empty => EMPTY_NODE.to_vec(),
+            Node::Leaf { path, value } => {
+                let mut stream = RlpStream::new_list(2);
+                stream.append(&hex_prefix(path, true));
+                stream.append(value);
+                stream.out().to_vec()
+            }
+            Node::Extension { path, child } => {
+                let mut stream = RlpStream::new_list(2);
+                stream.append(&hex_prefix(path, false));
+                child.append_reference(&mut stream);
+                stream.out().to_vec()
+            }
+            Node::Branch { children, value } => {
+                let mut stream = RlpStream::new_list(17);
+                for child in children {
+                    child.append_reference(&mut stream);
+                }
+                stream.append(value);
+                stream.out().to_vec()
+            }
+            Node::Hash(_) => unreachable!("the encoding of a hashed node is in the database"),
+        }
+    }
+
+    /// Append the reference of the node in its parent to `stream`.
+    fn append_reference(&self, stream: &mut RlpStream) {
+        match self {
+            Node::Empty => {
+                stream.append_empty_data();
+            }
+            Node::Hash(hash) => {
+                stream.append(&hash.as_bytes().to_vec());
+            }
+            node => {
+                stream.append_raw(&node.encode(), 1);
+            }
+        }
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        Self::decode_rlp(&Rlp::new(bytes)).ok_or_else(|| Error::InvalidMptNode(bytes.to_vec()))
+    }
+
+    fn decode_rlp(rlp: &Rlp) -> Option<Self> {
+        if rlp.is_empty() {
+            return Some(Node::Empty);
+        }
+        match rlp.item_count().ok()? {
+            2 => {
+                let (path, is_leaf) = decode_hex_prefix(rlp.at(0).ok()?.data().ok()?)?;
+                if is_leaf {
+                    Some(Node::Leaf {
+                        path,
+                        value: rlp.val_at(1).ok()?,
+                    })
+                } else {
+                    Some(Node::Extension {
+                        path,
+                        child: Box::new(Self::decode_reference(&rlp.at(1).ok()?)?),
+                    })
+                }
+            }
+            17 => Some(Node::Branch {
+                children: (0..16)
+                    .map(|idx| Self::decode_reference(&rlp.at(idx).ok()?))
+                    .collect::<Option<_>>()?,
+                value: rlp.val_at(16).ok()?,
+            }),
+            _ => None,
+        }
+    }
+
+    fn decode_reference(rlp: &Rlp) -> Option<Self> {
+        if rlp.is_list() {
+            return Self::decode_rlp(rlp);
+        }
+        match rlp.data().ok()? {
+            [] => Some(Node::Empty),
+            hash if hash.len() == 32 => Some(Node::Hash(H256::from_slice(hash))),
+            _ => None,
+        }
+    }
+}
+
+/// Return the nibbles of `bytes`.
+fn nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0xf])
+        .collect()
+}
+
+/// Return the hex-prefix encoding of the nibbles of `path`, whose flag tells
+/// whether it's the path of a leaf.
+fn hex_prefix(path: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 2 } else { 0 };
+    let (first, rest) = if path.len() % 2 == 1 {
+        (((flag + 1) << 4) | path[0], &path[1..])
+    } else {
+        (flag << 4, path)
+    };
+    std::iter::once(first)
+        .chain(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]))
+        .collect()
+}
+
+/// Return the nibbles of a hex-prefix encoded path, and whether it's the path
+/// of a leaf.
+fn decode_hex_prefix(bytes: &[u8]) -> Option<(Vec<u8>, bool)> {
+    let (first, rest) = bytes.split_first()?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return None;
+    }
+    let mut path = if flag & 1 == 1 {
+        vec![first & 0xf]
+    } else {
+        Vec::new()
+    };
+    path.extend(nibbles(rest));
+    Some((path, flag & 2 == 2))
+}
+
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+/// A Merkle Patricia Trie, of which only the nodes in its database are known,
+/// which are the nodes of the proofs it's built from and the ones it creates.
+#[derive(Debug, Clone)]
+pub struct Trie {
+    root: Node,
+    db: HashMap<Hash, Vec<u8>>,
+}
+
+impl Default for Trie {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Trie {
+    /// Create an empty trie.
+    pub fn new() -> Self {
+        Self {
+            root: Node::Empty,
+            db: HashMap::new(),
+        }
+    }
+
+    /// Create a trie with the root `root`, whose nodes are yet unknown.
+    pub fn from_root(root: Hash) -> Self {
+        Self {
+            root: if root == *EMPTY_ROOT {
+                Node::Empty
+            } else {
+                Node::Hash(root)
+            },
+            db: HashMap::new(),
+        }
+    }
+
+    /// Add the RLP encoded nodes of a proof into the known nodes.
+    pub fn add_proof<B: AsRef<[u8]>>(&mut self, proof: &[B]) {
+        for node in proof {
+            let node = node.as_ref();
+            self.db.insert(H256(keccak256(node)), node.to_vec());
+        }
+    }
+
+    /// Return the root of the trie.
+    pub fn root(&self) -> Hash {
+        match &self.root {
+            Node::Empty => *EMPTY_ROOT,
+            Node::Hash(hash) => *hash,
+            _ => unreachable!("the root is always hashed"),
+        }
+    }
+
+    /// Return the node referenced by `node`.
+    fn resolve(&self, node: &Node) -> Result<Node, Error> {
+        match node {
+            Node::Hash(hash) => {
+                Node::decode(self.db.get(hash).ok_or(Error::MptNodeNotFound(*hash))?)
+            }
+            node => Ok(node.clone()),
+        }
+    }
+
+    /// Return the value at `key`, or `None` if it's not in the trie.
+    pub fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        let path = nibbles(key);
+        let mut path = path.as_slice();
+        let mut node = self.root.clone();
+        loop {
+            node = match self.resolve(&node)? {
+                Node::Leaf {
+                    path: leaf_path,
+                    value,
+                } => return Ok((leaf_path == path).then(|| value)),
+                Node::Extension {
+                    path: extension_path,
+                    child,
+                } if path.starts_with(&extension_path) => {
+                    path = &path[extension_path.len()..];
+                    *child
+                }
+                Node::Branch { children, value } => match path.split_first() {
+                    Some((nibble, rest)) => {
+                        path = rest;
+                        children[*nibble as usize].clone()
+                    }
+                    None => return Ok(Some(value).filter(|value| !value.is_empty())),
+                },
+                _ => return Ok(None),
+            }
+        }
+    }
+
+    /// Set the value at `key` to `value`, or remove `key` when `value` is
+    /// empty.  The removal of a key can fail if it requires a node that's
+    /// not known, which is the case when a branch is left with a single
+    /// child that's not in the proofs.
+    pub fn update(&mut self, key: &[u8], value: Vec<u8>) -> Result<(), Error> {
+        let path = nibbles(key);
+        let root = if value.is_empty() {
+            self.remove_at(self.root.clone(), &path)?
+        } else {
+            self.insert_at(self.root.clone(), &path, value)?
+        };
+
+        self.root = match self.commit(root) {
+            Node::Empty => Node::Empty,
+            Node::Hash(hash) => Node::Hash(hash),
+            root => {
+                let encoding = root.encode();
+                let hash = H256(keccak256(&encoding));
+                self.db.insert(hash, encoding);
+                Node::Hash(hash)
+            }
+        };
+        Ok(())
+    }
+
+    fn insert_at(&self, node: Node, path: &[u8], value: Vec<u8>) -> Result<Node, Error> {
+        Ok(match self.resolve(&node)? {
+            Node::Empty => Node::Leaf {
+                path: path.to_vec(),
+                value,
+            },
+            Node::Leaf {
+                path: leaf_path,
+                value: leaf_value,
+            } => {
+                if leaf_path == path {
+                    Node::Leaf {
+                        path: leaf_path,
+                        value,
+                    }
+                } else {
+                    let common = common_prefix_len(&leaf_path, path);
+                    let branch =
+                        self.insert_at(Node::empty_branch(), &leaf_path[common..], leaf_value)?;
+                    let branch = self.insert_at(branch, &path[common..], value)?;
+                    Node::extension(path[..common].to_vec(), branch)
+                }
+            }
+            Node::Extension {
+                path: extension_path,
+                child,
+            } => {
+                let common = common_prefix_len(&extension_path, path);
+                if common == extension_path.len() {
+                    Node::Extension {
+                        path: extension_path,
+                        child: Box::new(self.insert_at(*child, &path[common..], value)?),
+                    }
+                } else {
+                    // Split the extension where the paths diverge
+                    let mut branch = Node::empty_branch();
+                    if let Node::Branch { children, .. } = &mut branch {
+                        children[extension_path[common] as usize] =
+                            Node::extension(extension_path[common + 1..].to_vec(), *child);
+                    }
+                    let branch = self.insert_at(branch, &path[common..], value)?;
+                    Node::extension(path[..common].to_vec(), branch)
+                }
+            }
+            Node::Branch {
+                mut children,
+                value: branch_value,
+            } => match path.split_first() {
+                Some((nibble, rest)) => {
+                    let child = std::mem::replace(&mut children[*nibble as usize], Node::Empty);
+                    children[*nibble as usize] = self.insert_at(child, rest, value)?;
+                    Node::Branch {
+                        children,
+                        value: branch_value,
+                    }
+                }
+                None => Node::Branch { children, value },
+            },
+            Node::Hash(_) => unreachable!("resolved node"),
+        })
+    }
+
+    fn remove_at(&self, node: Node, path: &[u8]) -> Result<Node, Error> {
+        Ok(match self.resolve(&node)? {
+            Node::Leaf {
+                path: leaf_path, ..
+            } if leaf_path == path => Node::Empty,
+            Node::Extension {
+                path: extension_path,
+                child,
+            } if path.starts_with(&extension_path) => {
+                let child = self.remove_at(*child, &path[extension_path.len()..])?;
+                self.join(extension_path, child)?
+            }
+            Node::Branch {
+                mut children,
+                mut value,
+            } => {
+                match path.split_first() {
+                    Some((nibble, rest)) => {
+                        let child = std::mem::replace(&mut children[*nibble as usize], Node::Empty);
+                        children[*nibble as usize] = self.remove_at(child, rest)?;
+                    }
+                    None => value = Vec::new(),
+                }
+
+                let mut non_empty = children
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, child)| **child != Node::Empty)
+                    .map(|(nibble, _)| nibble);
+                match (non_empty.next(), non_empty.next(), value.is_empty()) {
+                    (None, _, true) => Node::Empty,
+                    (None, _, false) => Node::Leaf {
+                        path: Vec::new(),
+                        value,
+                    },
+                    (Some(nibble), None, true) => {
+                        // A branch with a single child is merged into it
+                        let child = std::mem::replace(&mut children[nibble], Node::Empty);
+                        self.join(vec![nibble as u8], child)?
+                    }
+                    _ => Node::Branch { children, value },
+                }
+            }
+            node => node,
+        })
+    }
+
+    /// Return the node of `child` preceded by `path`, merging the paths when
+    /// `child` is a leaf or an extension.
+    fn join(&self, path: Vec<u8>, child: Node) -> Result<Node, Error> {
+        Ok(match self.resolve(&child)? {
+            Node::Empty => Node::Empty,
+            Node::Leaf {
+                path: child_path,
+                value,
+            } => Node::Leaf {
+                path: [path, child_path].concat(),
+                value,
+            },
+            Node::Extension {
+                path: child_path,
+                child,
+            } => Node::Extension {
+                path: [path, child_path].concat(),
+                child,
+            },
+            Node::Branch { .. } => Node::extension(path, child),
+            Node::Hash(_) => unreachable!("resolved node"),
+        })
+    }
+
+    /// Store the children of `node` whose encodings are 32 bytes or longer
+    /// into the database, and return `node` with them replaced by their
+    /// hashes.
+    fn commit(&mut self, node: Node) -> Node {
+        match node {
+            Node::Extension { path, child } => Node::Extension {
+                path,
+                child: Box::new(self.commit_child(*child)),
+            },
+            Node::Branch { children, value } => Node::Branch {
+                children: children
+                    .into_iter()
+                    .map(|child| self.commit_child(child))
+                    .collect(),
+                value,
+            },
+            node => node,
+        }
+    }
+
+    fn commit_child(&mut self, node: Node) -> Node {
+        if matches!(node, Node::Empty | Node::Hash(_)) {
+            return node;
+        }
+        let node = self.commit(node);
+        let encoding = node.encode();
+        if encoding.len() < 32 {
+            node
+        } else {
+            let hash = H256(keccak256(&encoding));
+            self.db.insert(hash, encoding);
+            Node::Hash(hash)
+        }
+    }
+
+    /// Return the RLP encoded nodes on the path of `key` that are referenced
+    /// by their hashes, starting from the root, as in `eth_getProof`.
+    pub fn prove(&self, key: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+        let path = nibbles(key);
+        let mut path = path.as_slice();
+        let mut node = self.root.clone();
+        let mut proof = Vec::new();
+        loop {
+            if let Node::Hash(hash) = node {
+                proof.push(
+                    self.db
+                        .get(&hash)
+                        .ok_or(Error::MptNodeNotFound(hash))?
+                        .clone(),
+                );
+            }
+            node = match self.resolve(&node)? {
+                Node::Extension {
+                    path: extension_path,
+                    child,
+                } if path.starts_with(&extension_path) => {
+                    path = &path[extension_path.len()..];
+                    *child
+                }
+                Node::Branch { children, .. } if !path.is_empty() => {
+                    let child = children[path[0] as usize].clone();
+                    path = &path[1..];
+                    child
+                }
+                _ => return Ok(proof),
+            }
+        }
+    }
+}
+
+/// The key of a value of the state, which is a field of an account or a
+/// storage slot.  The storage root of an account comes first when sorted,
+/// followed by its storage slots, which update it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MptKey {
+    /// Storage root, which is reset to the empty root when the account is
+    /// destructed
+    StorageRoot,
+    /// Storage slot
+    Storage(Word),
+    /// Account field
+    Account(AccountField),
+}
+
+/// A node of the path of a key in the proof of an [`MptUpdate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MptProofNode {
+    /// RLP encoding of the node
+    pub bytes: Vec<u8>,
+    /// Range of `bytes` holding the hash of the next node of the path
+    pub child: Option<Range<usize>>,
+    /// Range of `bytes` holding the value at the key, in the last node of
+    /// the path when the key is in the trie
+    pub value: Option<Range<usize>>,
+}
+
+/// An update of a value of the state, which moves the state root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MptUpdate {
+    /// Account address
+    pub address: Address,
+    /// Key of the value in the account
+    pub key: MptKey,
+    /// Value before the update
+    pub old_value: Word,
+    /// Value after the update
+    pub new_value: Word,
+    /// State root before the update
+    pub old_root: Hash,
+    /// State root after the update
+    pub new_root: Hash,
+    /// Nodes of the path of the key before the update, which are the nodes
+    /// of the account followed by the ones of the storage slot
+    pub old_path: Vec<MptProofNode>,
+    /// Nodes of the path of the key after the update
+    pub new_path: Vec<MptProofNode>,
+}
+
+/// The fields of an account as encoded in the state trie.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AccountState {
+    nonce: Word,
+    balance: Word,
+    storage_root: Hash,
+    code_hash: Hash,
+}
+
+impl Default for AccountState {
+    fn default() -> Self {
+        Self {
+            nonce: Word::zero(),
+            balance: Word::zero(),
+            storage_root: *EMPTY_ROOT,
+            code_hash: *EMPTY_CODE_HASH,
+        }
+    }
+}
+
+impl AccountState {
+    fn encode(&self) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(4);
+        stream.append(&word_bytes(self.nonce));
+        stream.append(&word_bytes(self.balance));
+        stream.append(&self.storage_root.as_bytes().to_vec());
+        stream.append(&self.code_hash.as_bytes().to_vec());
+        stream.out().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let rlp = Rlp::new(bytes);
+        let field = |idx| -> Option<Vec<u8>> { rlp.val_at(idx).ok() };
+        (|| {
+            let [nonce, balance, storage_root, code_hash] =
+                [field(0)?, field(1)?, field(2)?, field(3)?];
+            (nonce.len() <= 32
+                && balance.len() <= 32
+                && storage_root.len() == 32
+                && code_hash.len() == 32)
+                .then(|| Self {
+                    nonce: Word::from_big_endian(&nonce),
+                    balance: Word::from_big_endian(&balance),
+                    storage_root: H256::from_slice(&storage_root),
+                    code_hash: H256::from_slice(&code_hash),
+                })
+        })()
+        .ok_or_else(|| Error::InvalidMptNode(bytes.to_vec()))
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Return the big-endian bytes of `word` without the leading zeros, as it's
+/// encoded in the tries.
+fn word_bytes(word: Word) -> Vec<u8> {
+    let bytes = word.to_be_bytes();
+    let zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+    bytes[zeros..].to_vec()
+}
+
+fn encode_storage_value(value: Word) -> Vec<u8> {
+    if value.is_zero() {
+        Vec::new()
+    } else {
+        rlp::encode(&word_bytes(value)).to_vec()
+    }
+}
+
+fn decode_storage_value(bytes: &[u8]) -> Result<Word, Error> {
+    Rlp::new(bytes)
+        .data()
+        .ok()
+        .filter(|data| data.len() <= 32)
+        .map(Word::from_big_endian)
+        .ok_or_else(|| Error::InvalidMptNode(bytes.to_vec()))
+}
+
+/// The tries of the accounts and their storage, of which only the nodes on
+/// the paths of the accessed keys are known when built from proofs.
+#[derive(Debug, Clone, Default)]
+pub struct StateTrie {
+    accounts: Trie,
+    storages: HashMap<Address, Trie>,
+}
+
+impl StateTrie {
+    /// Build the tries of the whole state in `sdb`.
+    pub fn from_state_db(sdb: &StateDB) -> Self {
+        let mut state_trie = Self::default();
+        for (address, account) in sdb.accounts() {
+            let mut storage = Trie::new();
+            for (key, value) in account.storage.iter() {
+                storage
+                    .update(&keccak256(key.to_be_bytes()), encode_storage_value(*value))
+                    .expect("all the nodes are known");
+            }
+            let state = AccountState {
+                nonce: account.nonce,
+                balance: account.balance,
+                storage_root: storage.root(),
+                code_hash: account.code_hash,
+            };
+            if !state.is_empty() {
+                state_trie
+                    .accounts
+                    .update(&keccak256(address), state.encode())
+                    .expect("all the nodes are known");
+            }
+            state_trie.storages.insert(*address, storage);
+        }
+        state_trie
+    }
+
+    /// Build the tries from the proofs of `eth_getProof` against the state
+    /// root `root`, verifying that they prove the values they return.
+    pub fn from_proofs(root: Hash, proofs: &[EIP1186ProofResponse]) -> Result<Self, Error> {
+        let mut state_trie = Self {
+            accounts: Trie::from_root(root),
+            storages: HashMap::new(),
+        };
+        for proof in proofs {
+            state_trie.accounts.add_proof(&proof.account_proof);
+            let state = state_trie.account(&proof.address)?;
+            let expected = AccountState {
+                nonce: proof.nonce,
+                balance: proof.balance,
+                storage_root: state.storage_root,
+                code_hash: state.code_hash,
+            };
+            let account_exists = state_trie
+                .accounts
+                .get(&keccak256(proof.address))?
+                .is_some();
+            if (account_exists
+                && (state.storage_root != proof.storage_hash || state.code_hash != proof.code_hash))
+                || state != expected
+            {
+                return Err(Error::InvalidMptProof(proof.address, None));
+            }
+
+            let mut storage = Trie::from_root(state.storage_root);
+            for storage_proof in proof.storage_proof.iter() {
+                storage.add_proof(&storage_proof.proof);
+                let value = match storage.get(&keccak256(storage_proof.key.to_be_bytes()))? {
+                    Some(value) => decode_storage_value(&value)?,
+                    None => Word::zero(),
+                };
+                if value != storage_proof.value {
+                    return Err(Error::InvalidMptProof(
+                        proof.address,
+                        Some(storage_proof.key),
+                    ));
+                }
+            }
+            state_trie.storages.insert(proof.address, storage);
+        }
+        Ok(state_trie)
+    }
+
+    /// Return the state root.
+    pub fn root(&self) -> Hash {
+        self.accounts.root()
+    }
+
+    fn account(&self, address: &Address) -> Result<AccountState, Error> {
+        match self.accounts.get(&keccak256(address))? {
+            Some(value) => AccountState::decode(&value),
+            None => Ok(AccountState::default()),
+        }
+    }
+
+    /// Return the value at `key` of the account at `address`.
+    pub fn get(&self, address: &Address, key: &MptKey) -> Result<Word, Error> {
+        let state = self.account(address)?;
+        Ok(match key {
+            MptKey::Account(AccountField::Nonce) => state.nonce,
+            MptKey::Account(AccountField::Balance) => state.balance,
+            MptKey::Account(AccountField::CodeHash) => state.code_hash.to_word(),
+            MptKey::StorageRoot => state.storage_root.to_word(),
+            MptKey::Storage(key) => match self
+                .storages
+                .get(address)
+                .map(|storage| storage.get(&keccak256(key.to_be_bytes())))
+                .transpose()?
+                .flatten()
+            {
+                Some(value) => decode_storage_value(&value)?,
+                None => Word::zero(),
+            },
+        })
+    }
+
+    /// Set the value at `key` of the account at `address` to `value`, and
+    /// return the update with the paths of `key` before and after it.  The
+    /// accounts left empty are removed.
+    pub fn update(
+        &mut self,
+        address: Address,
+        key: MptKey,
+        value: Word,
+    ) -> Result<MptUpdate, Error> {
+        let old_value = self.get(&address, &key)?;
+        let old_root = self.root();
+        let old_path = self.path(&address, &key)?;
+
+        let mut state = self.account(&address)?;
+        match &key {
+            MptKey::Account(AccountField::Nonce) => state.nonce = value,
+            MptKey::Account(AccountField::Balance) => state.balance = value,
+            MptKey::Account(AccountField::CodeHash) => {
+                state.code_hash = H256::from(value.to_be_bytes())
+            }
+            MptKey::StorageRoot => {
+                state.storage_root = H256::from(value.to_be_bytes());
+                self.storages
+                    .insert(address, Trie::from_root(state.storage_root));
+            }
+            MptKey::Storage(key) => {
+                let storage = self
+                    .storages
+                    .entry(address)
+                    .or_insert_with(|| Trie::from_root(state.storage_root));
+                storage.update(&keccak256(key.to_be_bytes()), encode_storage_value(value))?;
+                state.storage_root = storage.root();
+            }
+        }
+        let encoding = if state.is_empty() {
+            Vec::new()
+        } else {
+            state.encode()
+        };
+        self.accounts.update(&keccak256(address), encoding)?;
+
+        Ok(MptUpdate {
+            address,
+            key: key.clone(),
+            old_value,
+            new_value: value,
+            old_root,
+            new_root: self.root(),
+            old_path,
+            new_path: self.path(&address, &key)?,
+        })
+    }
+
+    /// Return the nodes of the path of `key` of the account at `address`.
+    fn path(&self, address: &Address, key: &MptKey) -> Result<Vec<MptProofNode>, Error> {
+        let account_key = keccak256(address);
+        let mut path = walk_proof(&self.accounts.prove(&account_key)?, &account_key)?;
+        let account_exists = self.accounts.get(&account_key)?.is_some();
+
+        match key {
+            MptKey::Storage(key) if account_exists => {
+                let state = self.account(address)?;
+                let storage = self
+                    .storages
+                    .get(address)
+                    .cloned()
+                    .unwrap_or_else(|| Trie::from_root(state.storage_root));
+                let storage_key = keccak256(key.to_be_bytes());
+
+                // The storage root in the account leaf is the reference to the
+                // storage trie
+                let leaf = path.last_mut().expect("account leaf");
+                leaf.child = leaf
+                    .value
+                    .take()
+                    .map(|range| account_field(&leaf.bytes, range, 2));
+                let mut storage_path = walk_proof(&storage.prove(&storage_key)?, &storage_key)?;
+
+                // The values are RLP encoded in the storage leaves
+                let leaf = storage_path.last_mut().expect("storage node");
+                leaf.value = leaf.value.take().map(|range| {
+                    let value = Rlp::new(&leaf.bytes[range.clone()])
+                        .data()
+                        .expect("valid storage value");
+                    subslice_range(&leaf.bytes, value)
+                });
+                path.extend(storage_path);
+            }
+            MptKey::Storage(_) => {}
+            MptKey::StorageRoot | MptKey::Account(_) => {
+                let leaf = path.last_mut().expect("account leaf");
+                let idx = match key {
+                    MptKey::Account(AccountField::Nonce) => 0,
+                    MptKey::Account(AccountField::Balance) => 1,
+                    MptKey::StorageRoot => 2,
+                    MptKey::Account(AccountField::CodeHash) => 3,
+                    MptKey::Storage(_) => unreachable!("storage slot"),
+                };
+                leaf.value = leaf
+                    .value
+                    .take()
+                    .map(|range| account_field(&leaf.bytes, range, idx));
+            }
+        }
+        Ok(path)
+    }
+}
+
+/// Return the range of the field `idx` of the account encoded in
+/// `bytes[range]`.
+fn account_field(bytes: &[u8], range: Range<usize>, idx: usize) -> Range<usize> {
+    let data = Rlp::new(&bytes[range.clone()])
+        .at(idx)
+        .and_then(|field| field.data())
+        .expect("valid account");
+    subslice_range(bytes, data)
+}
+
+/// Return the range of `subslice` in `bytes`, which contains it.
+fn subslice_range(bytes: &[u8], subslice: &[u8]) -> Range<usize> {
+    let start = subslice.as_ptr() as usize - bytes.as_ptr() as usize;
+    start..start + subslice.len()
+}
+
+/// Walk the nodes of a proof along the path of `key`, and return them with
+/// the ranges of the references to the next nodes and of the value in the
+/// last one.  An empty proof is the one of an empty trie.
+fn walk_proof(proof: &[Vec<u8>], key: &[u8]) -> Result<Vec<MptProofNode>, Error> {
+    if proof.is_empty() {
+        return Ok(vec![MptProofNode {
+            bytes: EMPTY_NODE.to_vec(),
+            child: None,
+            value: None,
+        }]);
+    }
+
+    let path = nibbles(key);
+    let mut path = path.as_slice();
+    let mut nodes = Vec::new();
+    for (idx, bytes) in proof.iter().enumerate() {
+        let invalid = || Error::InvalidMptNode(bytes.clone());
+        let mut node = MptProofNode {
+            bytes: bytes.clone(),
+            child: None,
+            value: None,
+        };
+
+        // Walk through the nodes embedded in this one until a reference to the
+        // next node or the end of the path
+        let mut rlp = Rlp::new(bytes);
+        loop {
+            let next = match rlp.item_count().map_err(|_| invalid())? {
+                2 => {
+                    let (node_path, is_leaf) = rlp
+                        .at(0)
+                        .and_then(|path| path.data())
+                        .ok()
+                        .and_then(decode_hex_prefix)
+                        .ok_or_else(invalid)?;
+                    if is_leaf {
+                        if node_path == path {
+                            let value = rlp.at(1).and_then(|value| value.data());
+                            node.value = Some(subslice_range(bytes, value.map_err(|_| invalid())?));
+                        }
+                        None
+                    } else if path.starts_with(&node_path) {
+                        path = &path[node_path.len()..];
+                        Some(rlp.at(1).map_err(|_| invalid())?)
+                    } else {
+                        None
+                    }
+                }
+                17 => match path.split_first() {
+                    Some((nibble, rest)) => {
+                        path = rest;
+                        Some(rlp.at(*nibble as usize).map_err(|_| invalid())?)
+                    }
+                    None => {
+                        let value = rlp.at(16).and_then(|value| value.data());
+                        let value = subslice_range(bytes, value.map_err(|_| invalid())?);
+                        node.value = Some(value).filter(|value| !value.is_empty());
+                        None
+                    }
+                },
+                _ => return Err(invalid()),
+            };
+
+            match next {
+                Some(child) if child.is_list() => rlp = child,
+                Some(child) => {
+                    let reference = child.data().map_err(|_| invalid())?;
+                    if reference.len() == 32 {
+                        node.child = Some(subslice_range(bytes, reference));
+                    }
+                    break;
+                }
+                None => break,
+            }
+        }
+
+        // Only the last node of the proof has no reference to a next one
+        if node.child.is_some() != (idx + 1 < proof.len()) {
+            return Err(invalid());
+        }
+        nodes.push(node);
+    }
+    Ok(nodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{address, StorageProof};
+    use std::str::FromStr;
+
+    fn trie(entries: &[(&[u8], &[u8])]) -> Trie {
+        let mut trie = Trie::new();
+        for (key, value) in entries {
+            trie.update(key, value.to_vec()).unwrap();
+        }
+        trie
+    }
+
+    #[test]
+    fn trie_root() {
+        assert_eq!(
+            Trie::new().root(),
+            H256::from_str("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421")
+                .unwrap()
+        );
+
+        let entries: [(&[u8], &[u8]); 3] = [
+            (b"doe", b"reindeer"),
+            (b"dog", b"puppy"),
+            (b"dogglesworth", b"cat"),
+        ];
+        let root =
+            H256::from_str("8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3")
+                .unwrap();
+        assert_eq!(trie(&entries).root(), root);
+        // The root doesn't depend on the order of the insertions
+        let mut reversed = entries;
+        reversed.reverse();
+        assert_eq!(trie(&reversed).root(), root);
+    }
+
+    #[test]
+    fn trie_get_and_remove() {
+        let mut trie = trie(&[
+            (b"doe", b"reindeer"),
+            (b"dog", b"puppy"),
+            (b"dogglesworth", b"cat"),
+        ]);
+        assert_eq!(trie.get(b"dog").unwrap(), Some(b"puppy".to_vec()));
+        assert_eq!(trie.get(b"do").unwrap(), None);
+
+        trie.update(b"dogglesworth", Vec::new()).unwrap();
+        trie.update(b"doe", Vec::new()).unwrap();
+        assert_eq!(trie.root(), self::trie(&[(b"dog", b"puppy")]).root());
+        trie.update(b"dog", Vec::new()).unwrap();
+        assert_eq!(trie.root(), *EMPTY_ROOT);
+    }
+
+    #[test]
+    fn trie_from_proof() {
+        let keys: Vec<_> = (0u8..32).map(|idx| keccak256([idx])).collect();
+        let mut full = Trie::new();
+        for key in keys.iter() {
+            full.update(key, key.to_vec()).unwrap();
+        }
+
+        // A trie built from the proof of a key gives the same root as the full
+        // one after updating it
+        let mut partial = Trie::from_root(full.root());
+        partial.add_proof(&full.prove(&keys[3]).unwrap());
+        assert_eq!(partial.get(&keys[3]).unwrap(), Some(keys[3].to_vec()));
+        assert!(matches!(
+            partial.get(&keys[4]),
+            Err(Error::MptNodeNotFound(_))
+        ));
+
+        full.update(&keys[3], vec![1]).unwrap();
+        partial.update(&keys[3], vec![1]).unwrap();
+        assert_eq!(partial.root(), full.root());
+    }
+
+    #[test]
+    fn state_trie_update() {
+        let address = address!("0x00000000000000000000000000000000000000aa");
+        let slots = [Word::from(1), Word::from(2)];
+        let mut sdb = StateDB::new();
+        sdb.set_account(
+            &address,
+            Account {
+                nonce: Word::from(1),
+                balance: Word::from(100),
+                storage: slots.iter().map(|slot| (*slot, *slot + 1)).collect(),
+                code_hash: *EMPTY_CODE_HASH,
+            },
+        );
+        let mut full = StateTrie::from_state_db(&sdb);
+
+        // Build the tries from the proofs of the full ones
+        let account_key = keccak256(address);
+        let storage = &full.storages[&address];
+        let proof = EIP1186ProofResponse {
+            address,
+            balance: Word::from(100),
+            code_hash: *EMPTY_CODE_HASH,
+            nonce: Word::from(1),
+            storage_hash: storage.root(),
+            account_proof: full
+                .accounts
+                .prove(&account_key)
+                .unwrap()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            storage_proof: slots
+                .iter()
+                .map(|slot| StorageProof {
+                    key: *slot,
+                    value: *slot + 1,
+                    proof: storage
+                        .prove(&keccak256(slot.to_be_bytes()))
+                        .unwrap()
+                        .into_iter()
+                        .map(Into::into)
+                        .collect(),
+                })
+                .collect(),
+        };
+        let mut partial = StateTrie::from_proofs(full.root(), &[proof.clone()]).unwrap();
+
+        for (key, value) in [
+            (MptKey::Storage(slots[0]), Word::from(7)),
+            (MptKey::Storage(slots[1]), Word::zero()),
+            (MptKey::Account(AccountField::Balance), Word::from(50)),
+        ] {
+            let update = partial.update(address, key.clone(), value).unwrap();
+            assert_eq!(update, full.update(address, key, value).unwrap());
+            assert_eq!(update.new_value, value);
+
+            // The paths end with the value when it's not 0
+            for (path, value) in [
+                (&update.old_path, update.old_value),
+                (&update.new_path, update.new_value),
+            ] {
+                let last = path.last().unwrap();
+                let bytes = last.value.clone().map(|range| last.bytes[range].to_vec());
+                assert_eq!(
+                    bytes.filter(|bytes| !bytes.is_empty()),
+                    Some(word_bytes(value)).filter(|bytes| !bytes.is_empty())
+                );
+            }
+        }
+
+        // A proof with a wrong value is rejected
+        let mut invalid = proof;
+        invalid.storage_proof[0].value = Word::from(3);
+        assert!(matches!(
+            StateTrie::from_proofs(full.root(), &[invalid]),
+            Err(Error::InvalidMptProof(_, Some(_)))
+        ));
+    }
+
+    #[test]
+    fn state_trie_reset_storage_root() {
+        let address = address!("0x00000000000000000000000000000000000000aa");
+        let mut sdb = StateDB::new();
+        sdb.set_account(
+            &address,
+            Account {
+                nonce: Word::from(1),
+                balance: Word::from(100),
+                storage: [(Word::from(1), Word::from(2))].into_iter().collect(),
+                code_hash: *EMPTY_CODE_HASH,
+            },
+        );
+        let full = StateTrie::from_state_db(&sdb);
+        let storage_root = full.storages[&address].root();
+
+        // The storage slots aren't in the proof of the destructed account
+        let proof = EIP1186ProofResponse {
+            address,
+            balance: Word::from(100),
+            code_hash: *EMPTY_CODE_HASH,
+            nonce: Word::from(1),
+            storage_hash: storage_root,
+            account_proof: full
+                .accounts
+                .prove(&keccak256(address))
+                .unwrap()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            storage_proof: Vec::new(),
+        };
+        let mut partial = StateTrie::from_proofs(full.root(), &[proof]).unwrap();
+
+        // The storage root is in the account leaf until it's reset
+        let update = partial
+            .update(address, MptKey::StorageRoot, EMPTY_ROOT.to_word())
+            .unwrap();
+        assert_eq!(update.old_value, storage_root.to_word());
+        let last = update.old_path.last().unwrap();
+        assert_eq!(
+            last.value.clone().map(|range| last.bytes[range].to_vec()),
+            Some(storage_root.as_bytes().to_vec())
+        );
+
+        // The account is removed once its fields are reset as well
+        for field in [AccountField::Nonce, AccountField::Balance] {
+            partial
+                .update(address, MptKey::Account(field), Word::zero())
+                .unwrap();
+        }
+        assert_eq!(partial.root(), *EMPTY_ROOT);
+    }
+}
@@ -0,0 +1,905 @@
+//! Precompiled contracts, which live at the addresses 0x01 to 0x09 and are
+//! executed natively instead of running EVM bytecode.
+
+use eth_types::evm_types::GasCost;
+use eth_types::{Address, ToBigEndian, ToLittleEndian, Word, H256};
+use ethers_core::types::{Signature, U512};
+use pairing::arithmetic::{Coordinates, CurveAffine, FieldExt};
+use pairing::bn256::{pairing, Fq, Fq2, Fr, G1Affine, G2Affine, Gt};
+use pairing::group::{ff::Field, prime::PrimeCurveAffine, Curve, Group};
+use ripemd160::Ripemd160;
+use sha2::{Digest, Sha256};
+
+/// Precompiled contracts, whose value is their address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrecompileCalls {
+    /// ECDSA public key recovery
+    ECRecover = 0x01,
+    /// SHA256 hash
+    Sha256 = 0x02,
+    /// RIPEMD160 hash
+    Ripemd160 = 0x03,
+    /// Identity, which returns its input
+    Identity = 0x04,
+    /// Modular exponentiation (EIP-198)
+    Modexp = 0x05,
+    /// Point addition on the BN254 curve (EIP-196)
+    Bn128Add = 0x06,
+    /// Scalar multiplication on the BN254 curve (EIP-196)
+    Bn128Mul = 0x07,
+    /// Pairing check on the BN254 curve (EIP-197)
+    Bn128Pairing = 0x08,
+    /// BLAKE2 compression function F (EIP-152)
+    Blake2F = 0x09,
+}
+
+impl PrecompileCalls {
+    /// Iterate over all the precompiled contracts, ordered by address.
+    pub fn iter() -> impl Iterator<Item = Self> {
+        [
+            Self::ECRecover,
+            Self::Sha256,
+            Self::Ripemd160,
+            Self::Identity,
+            Self::Modexp,
+            Self::Bn128Add,
+            Self::Bn128Mul,
+            Self::Bn128Pairing,
+            Self::Blake2F,
+        ]
+        .into_iter()
+    }
+
+    /// Return the address of the precompiled contract.
+    pub fn address(&self) -> Address {
+        Address::from_low_u64_be(*self as u64)
+    }
+
+    /// Return the precompiled contract at `address`, if any.
+    pub fn from_address(address: &Address) -> Option<Self> {
+        Self::iter().find(|precompile| precompile.address() == *address)
+    }
+
+    /// Return whether the calls to the precompiled contract are proved by
+    /// the circuits.  ECRecover isn't, since the ECDSA circuit doesn't prove
+    /// the recovery of the signer, nor are the BN254 precompiles, since the
+    /// ECC circuit doesn't prove the curve arithmetic or the validity of the
+    /// points.
+    pub fn is_supported(&self) -> bool {
+        !matches!(
+            self,
+            Self::ECRecover | Self::Bn128Add | Self::Bn128Mul | Self::Bn128Pairing
+        )
+    }
+
+    /// Return the gas cost of calling the precompiled contract with `input`.
+    pub fn gas_cost(&self, input: &[u8]) -> u64 {
+        let words = (input.len() as u64 + 31) / 32;
+        match self {
+            Self::ECRecover => GasCost::PRECOMPILE_EC_RECOVER.as_u64(),
+            Self::Sha256 => {
+                GasCost::PRECOMPILE_SHA256_BASE.as_u64()
+                    + GasCost::PRECOMPILE_SHA256_PER_WORD.as_u64() * words
+            }
+            Self::Ripemd160 => {
+                GasCost::PRECOMPILE_RIPEMD160_BASE.as_u64()
+                    + GasCost::PRECOMPILE_RIPEMD160_PER_WORD.as_u64() * words
+            }
+            Self::Identity => {
+                GasCost::PRECOMPILE_IDENTITY_BASE.as_u64()
+                    + GasCost::PRECOMPILE_IDENTITY_PER_WORD.as_u64() * words
+            }
+            Self::Modexp => {
+                let [base_len, exp_len, mod_len] =
+                    [0, 32, 64].map(|offset| input_word(input, offset, 32));
+                // The exponent is only read when the lengths can be paid
+                let exp_head = if base_len.max(exp_len) > Word::from(u32::MAX) {
+                    Word::zero()
+                } else {
+                    input_word(input, 96 + base_len.as_usize(), exp_len.as_usize().min(32))
+                };
+                GasCost::precompile_modexp(base_len, exp_len, mod_len, exp_head).as_u64()
+            }
+            Self::Bn128Add => GasCost::PRECOMPILE_BN256_ADD.as_u64(),
+            Self::Bn128Mul => GasCost::PRECOMPILE_BN256_MUL.as_u64(),
+            Self::Bn128Pairing => {
+                GasCost::PRECOMPILE_BN256_PAIRING_BASE.as_u64()
+                    + GasCost::PRECOMPILE_BN256_PAIRING_PER_PAIR.as_u64()
+                        * (input.len() as u64 / 192)
+            }
+            Self::Blake2F => {
+                // An input of invalid length costs nothing, and makes the
+                // call fail.
+                if input.len() != 213 {
+                    return 0;
+                }
+                let rounds = u32::from_be_bytes([input[0], input[1], input[2], input[3]]);
+                GasCost::precompile_blake2f(rounds).as_u64()
+            }
+        }
+    }
+
+    /// Return the number of bytes of the call data `input` read by the
+    /// precompiled contract, which are right padded with zeros past its end.
+    pub fn input_length(&self, input: &[u8]) -> usize {
+        match self {
+            Self::ECRecover => 128,
+            Self::Modexp => ModexpInput::new(input).map_or(input.len(), |input| {
+                96 + input.base_len + input.exp_len + input.mod_len
+            }),
+            // An input of invalid length isn't read, since it makes the call
+            // fail.
+            Self::Blake2F if input.len() != 213 => 0,
+            _ => input.len(),
+        }
+    }
+
+    /// Return the output of the precompiled contract called with `input`, or
+    /// `None` if the input is larger than supported by the circuits.
+    pub fn output(&self, input: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Self::ECRecover => Some(
+                EcRecoverInput::new(input)
+                    .recover()
+                    .map(|address| {
+                        // The address is left padded to 32 bytes
+                        let mut output = vec![0; 32];
+                        output[12..].copy_from_slice(address.as_bytes());
+                        output
+                    })
+                    .unwrap_or_default(),
+            ),
+            Self::Sha256 => Some(Sha256::digest(input).to_vec()),
+            Self::Ripemd160 => {
+                // The digest is left padded to 32 bytes
+                let mut output = vec![0; 32];
+                output[12..].copy_from_slice(&Ripemd160::digest(input));
+                Some(output)
+            }
+            Self::Identity => Some(input.to_vec()),
+            // The output of a failed call is never read, since it returns no
+            // data
+            Self::Bn128Add => Some(
+                Bn128AddInput::new(input)
+                    .add()
+                    .map(g1_output)
+                    .unwrap_or_default(),
+            ),
+            Self::Bn128Mul => Some(
+                Bn128MulInput::new(input)
+                    .mul()
+                    .map(g1_output)
+                    .unwrap_or_default(),
+            ),
+            Self::Bn128Pairing => Some(
+                Bn128PairingInput::new(input)
+                    .and_then(|input| input.check())
+                    .map(|is_one| Word::from(is_one as u64).to_be_bytes().to_vec())
+                    .unwrap_or_default(),
+            ),
+            Self::Modexp => ModexpInput::new(input).map(|input| input.output()),
+            Self::Blake2F => match Blake2fInput::new(input) {
+                Some(input) if input.rounds > BLAKE2F_MAX_ROUNDS => None,
+                Some(input) => Some(
+                    input
+                        .compress()
+                        .iter()
+                        .flat_map(|word| word.to_le_bytes())
+                        .collect(),
+                ),
+                None => Some(Vec::new()),
+            },
+        }
+    }
+}
+
+/// Maximum length in bytes of the base, the exponent and the modulus of the
+/// MODEXP precompile supported by the circuits.
+pub const MODEXP_MAX_INPUT_SIZE: usize = 32;
+
+/// Maximum number of rounds of the BLAKE2F precompile supported by the
+/// circuits.
+pub const BLAKE2F_MAX_ROUNDS: u32 = 1 << 10;
+
+/// Input of the ecRecover precompile, which is read from its call data right
+/// padded with zeros to 128 bytes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EcRecoverInput {
+    /// Hash of the signed message
+    pub msg_hash: Word,
+    /// Recovery id of the signature, which is 27 or 28 when valid
+    pub v: Word,
+    /// `r` of the signature
+    pub r: Word,
+    /// `s` of the signature
+    pub s: Word,
+}
+
+impl EcRecoverInput {
+    /// Parse the input of the ecRecover precompile from its call data.
+    pub fn new(input: &[u8]) -> Self {
+        let [msg_hash, v, r, s] = [0, 32, 64, 96].map(|offset| input_word(input, offset, 32));
+        Self { msg_hash, v, r, s }
+    }
+
+    /// Return the address of the signer, or `None` if the signature is
+    /// invalid, in which case the precompile returns no data.  Unlike
+    /// transaction signatures, the upper half of the `s` values is accepted.
+    pub fn recover(&self) -> Option<Address> {
+        let secp256k1_n = Word::from_str_radix(
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+            16,
+        )
+        .unwrap();
+        if (self.v != Word::from(27) && self.v != Word::from(28))
+            || [self.r, self.s]
+                .iter()
+                .any(|value| value.is_zero() || *value >= secp256k1_n)
+        {
+            return None;
+        }
+
+        let signature = Signature {
+            r: self.r,
+            s: self.s,
+            v: self.v.as_u64(),
+        };
+        let mut msg_hash = [0u8; 32];
+        self.msg_hash.to_big_endian(&mut msg_hash);
+        signature.recover(H256(msg_hash)).ok()
+    }
+}
+
+/// Input of the ecAdd precompile, which is read from its call data right
+/// padded with zeros to 128 bytes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bn128AddInput {
+    /// Coordinates of the first point
+    pub p: [Word; 2],
+    /// Coordinates of the second point
+    pub q: [Word; 2],
+}
+
+impl Bn128AddInput {
+    /// Parse the input of the ecAdd precompile from its call data.
+    pub fn new(input: &[u8]) -> Self {
+        let [p_x, p_y, q_x, q_y] = [0, 32, 64, 96].map(|offset| input_word(input, offset, 32));
+        Self {
+            p: [p_x, p_y],
+            q: [q_x, q_y],
+        }
+    }
+
+    /// Return the coordinates of the sum of the points, or `None` if either
+    /// of them is not a point of BN254, in which case the call fails.
+    pub fn add(&self) -> Option<[Word; 2]> {
+        let sum = g1_point(self.p)?.to_curve() + g1_point(self.q)?.to_curve();
+        Some(g1_words(sum.to_affine()))
+    }
+}
+
+/// Input of the ecMul precompile, which is read from its call data right
+/// padded with zeros to 96 bytes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bn128MulInput {
+    /// Coordinates of the point
+    pub p: [Word; 2],
+    /// Scalar, which isn't reduced modulo the order of the group
+    pub s: Word,
+}
+
+impl Bn128MulInput {
+    /// Parse the input of the ecMul precompile from its call data.
+    pub fn new(input: &[u8]) -> Self {
+        let [p_x, p_y, s] = [0, 32, 64].map(|offset| input_word(input, offset, 32));
+        Self { p: [p_x, p_y], s }
+    }
+
+    /// Return the coordinates of the point multiplied by the scalar, or
+    /// `None` if it's not a point of BN254, in which case the call fails.
+    pub fn mul(&self) -> Option<[Word; 2]> {
+        let p = g1_point(self.p)?;
+        let mut s = [0u8; 64];
+        s[..32].copy_from_slice(&self.s.to_le_bytes());
+        let product = p.to_curve() * Fr::from_bytes_wide(&s);
+        Some(g1_words(product.to_affine()))
+    }
+}
+
+/// Input of the ecPairing precompile, which is a list of pairs of points of
+/// G1 and G2 taking 192 bytes each.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bn128PairingInput {
+    /// Coordinates of the points of G1 and G2, where the coordinates in
+    /// `Fq2` are encoded with their imaginary part first
+    pub pairs: Vec<([Word; 2], [Word; 4])>,
+}
+
+impl Bn128PairingInput {
+    /// Parse the input of the ecPairing precompile from its call data, or
+    /// return `None` if its length isn't a multiple of 192, in which case the
+    /// call fails.
+    pub fn new(input: &[u8]) -> Option<Self> {
+        if input.len() % 192 != 0 {
+            return None;
+        }
+        let pairs = input
+            .chunks(192)
+            .map(|pair| {
+                let [g1_x, g1_y, g2_x_c1, g2_x_c0, g2_y_c1, g2_y_c0] =
+                    [0, 32, 64, 96, 128, 160].map(|offset| input_word(pair, offset, 32));
+                ([g1_x, g1_y], [g2_x_c1, g2_x_c0, g2_y_c1, g2_y_c0])
+            })
+            .collect();
+        Some(Self { pairs })
+    }
+
+    /// Return whether the product of the pairings of the pairs is 1, or
+    /// `None` if any of them is not a point of G1 or G2, in which case the
+    /// call fails.
+    pub fn check(&self) -> Option<bool> {
+        let mut product = Gt::identity();
+        for (g1, g2) in self.pairs.iter() {
+            let (g1, g2) = (g1_point(*g1)?, g2_point(*g2)?);
+            if bool::from(g1.is_identity()) || bool::from(g2.is_identity()) {
+                continue;
+            }
+            // The group operation of `Gt` is written additively
+            product += pairing(&g1, &g2);
+        }
+        Some(product == Gt::identity())
+    }
+}
+
+/// Return the element of the base field of BN254 encoded by `word`, or `None`
+/// if it's not less than the modulus.
+fn fq(word: Word) -> Option<Fq> {
+    Fq::from_bytes(&word.to_le_bytes()).into()
+}
+
+/// Return the point of G1 with coordinates `x` and `y`, where `(0, 0)` encodes
+/// the point at infinity.
+fn g1_point([x, y]: [Word; 2]) -> Option<G1Affine> {
+    if x.is_zero() && y.is_zero() {
+        return Some(G1Affine::identity());
+    }
+    G1Affine::from_xy(fq(x)?, fq(y)?).into()
+}
+
+/// Return the point of G2 with coordinates `x` and `y` in `Fq2` encoded with
+/// their imaginary part first, where `(0, 0)` encodes the point at infinity.
+fn g2_point(words: [Word; 4]) -> Option<G2Affine> {
+    if words.iter().all(|word| word.is_zero()) {
+        return Some(G2Affine::identity());
+    }
+    let [x_c1, x_c0, y_c1, y_c0] = words;
+    let x = Fq2 {
+        c0: fq(x_c0)?,
+        c1: fq(x_c1)?,
+    };
+    let y = Fq2 {
+        c0: fq(y_c0)?,
+        c1: fq(y_c1)?,
+    };
+    let point: G2Affine = Option::from(G2Affine::from_xy(x, y))?;
+
+    // Unlike G1, the curve of G2 has points outside of the subgroup of order
+    // `r`, which are rejected by checking that `r * point` is infinity
+    let point_curve = point.to_curve();
+    if bool::from((point_curve * -Fr::one() + point_curve).is_identity()) {
+        Some(point)
+    } else {
+        None
+    }
+}
+
+/// Return the coordinates of `point`, which are `(0, 0)` for the point at
+/// infinity.
+fn g1_words(point: G1Affine) -> [Word; 2] {
+    let coordinates: Option<Coordinates<G1Affine>> = point.coordinates().into();
+    coordinates.map_or([Word::zero(); 2], |coordinates| {
+        [*coordinates.x(), *coordinates.y()]
+            .map(|value| Word::from_little_endian(&value.to_bytes()))
+    })
+}
+
+/// Return the output of the ecAdd and ecMul precompiles, which is the
+/// coordinates of the resulting point.
+fn g1_output(point: [Word; 2]) -> Vec<u8> {
+    point
+        .iter()
+        .flat_map(|coordinate| coordinate.to_be_bytes())
+        .collect()
+}
+
+/// Input of the MODEXP precompile, whose base, exponent and modulus are read
+/// from its call data after their lengths, right padded with zeros.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModexpInput {
+    /// Base
+    pub base: Word,
+    /// Exponent
+    pub exp: Word,
+    /// Modulus
+    pub modulus: Word,
+    /// Length of the base in bytes
+    pub base_len: usize,
+    /// Length of the exponent in bytes
+    pub exp_len: usize,
+    /// Length of the modulus in bytes, which is the length of the output
+    pub mod_len: usize,
+}
+
+impl ModexpInput {
+    /// Parse the input of the MODEXP precompile from its call data, or return
+    /// `None` if any of its lengths is greater than
+    /// [`MODEXP_MAX_INPUT_SIZE`].
+    pub fn new(input: &[u8]) -> Option<Self> {
+        let lengths = [0, 32, 64].map(|offset| input_word(input, offset, 32));
+        if lengths
+            .iter()
+            .any(|len| *len > Word::from(MODEXP_MAX_INPUT_SIZE))
+        {
+            return None;
+        }
+        let [base_len, exp_len, mod_len] = lengths.map(|len| len.as_usize());
+        Some(Self {
+            base: input_word(input, 96, base_len),
+            exp: input_word(input, 96 + base_len, exp_len),
+            modulus: input_word(input, 96 + base_len + exp_len, mod_len),
+            base_len,
+            exp_len,
+            mod_len,
+        })
+    }
+
+    /// Return `base ^ exp % modulus`, which is 0 when the modulus is 0.
+    pub fn modexp(&self) -> Word {
+        if self.modulus.is_zero() {
+            return Word::zero();
+        }
+        let mul_mod = |lhs: Word, rhs: Word| {
+            Word::try_from(lhs.full_mul(rhs) % U512::from(self.modulus)).unwrap()
+        };
+        let mut result = Word::one() % self.modulus;
+        for idx in (0..self.exp.bits()).rev() {
+            result = mul_mod(result, result);
+            if self.exp.bit(idx) {
+                result = mul_mod(result, self.base);
+            }
+        }
+        result
+    }
+
+    /// Return the output of the MODEXP precompile, which is the result
+    /// encoded in big-endian in as many bytes as the modulus.
+    pub fn output(&self) -> Vec<u8> {
+        self.modexp().to_be_bytes()[32 - self.mod_len..].to_vec()
+    }
+}
+
+/// Input of the BLAKE2F precompile, which takes exactly 213 bytes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Blake2fInput {
+    /// Number of rounds
+    pub rounds: u32,
+    /// State vector
+    pub h: [u64; 8],
+    /// Message block vector
+    pub m: [u64; 16],
+    /// Offset counters
+    pub t: [u64; 2],
+    /// Final block indicator flag
+    pub f: bool,
+}
+
+impl Blake2fInput {
+    /// Parse the input of the BLAKE2F precompile from its call data, or return
+    /// `None` if its length isn't 213 or its final block indicator flag isn't
+    /// 0 or 1, in which case the call fails.
+    pub fn new(input: &[u8]) -> Option<Self> {
+        if input.len() != 213 || input[212] > 1 {
+            return None;
+        }
+        // The words are encoded in little-endian, unlike the rounds
+        let mut words = input[4..212]
+            .chunks(8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()));
+        let mut blake2f_input = Self {
+            rounds: u32::from_be_bytes(input[..4].try_into().unwrap()),
+            f: input[212] == 1,
+            ..Default::default()
+        };
+        for word in blake2f_input
+            .h
+            .iter_mut()
+            .chain(blake2f_input.m.iter_mut())
+            .chain(blake2f_input.t.iter_mut())
+        {
+            *word = words.next().unwrap();
+        }
+        Some(blake2f_input)
+    }
+
+    /// Return the state vector after the rounds of the compression function F
+    /// of BLAKE2b.
+    pub fn compress(&self) -> [u64; 8] {
+        let mut v = [0; 16];
+        v[..8].copy_from_slice(&self.h);
+        v[8..].copy_from_slice(&BLAKE2B_IV);
+        v[12] ^= self.t[0];
+        v[13] ^= self.t[1];
+        if self.f {
+            v[14] = !v[14];
+        }
+
+        for round in 0..self.rounds as usize {
+            let s = &BLAKE2B_SIGMA[round % 10];
+            for (idx, [a, b, c, d]) in BLAKE2B_G_INDICES.iter().enumerate() {
+                for (half, [rotation_d, rotation_b]) in [[32, 24], [16, 63]].iter().enumerate() {
+                    let x = self.m[s[2 * idx + half]];
+                    v[*a] = v[*a].wrapping_add(v[*b]).wrapping_add(x);
+                    v[*d] = (v[*d] ^ v[*a]).rotate_right(*rotation_d);
+                    v[*c] = v[*c].wrapping_add(v[*d]);
+                    v[*b] = (v[*b] ^ v[*c]).rotate_right(*rotation_b);
+                }
+            }
+        }
+
+        let mut h = self.h;
+        for (idx, word) in h.iter_mut().enumerate() {
+            *word ^= v[idx] ^ v[idx + 8];
+        }
+        h
+    }
+}
+
+/// The initialization vector of BLAKE2b.
+const BLAKE2B_IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// The permutations of the message words of BLAKE2b, where the round `i` uses
+/// the permutation `i % 10`.
+const BLAKE2B_SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// The indices of the words `a`, `b`, `c` and `d` of the state vector mixed
+/// by the 8 applications of the function G in a round of BLAKE2b.
+const BLAKE2B_G_INDICES: [[usize; 4]; 8] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+/// Return whether `address` is the address of a precompiled contract.
+pub fn is_precompiled(address: &Address) -> bool {
+    PrecompileCalls::from_address(address).is_some()
+}
+
+/// Return the `length` bytes of `input` starting at `offset` as a big-endian
+/// [`Word`], where the bytes beyond the input are 0.
+fn input_word(input: &[u8], offset: usize, length: usize) -> Word {
+    let mut bytes = [0u8; 32];
+    for (idx, byte) in bytes[32 - length..].iter_mut().enumerate() {
+        *byte = input.get(offset + idx).copied().unwrap_or(0);
+    }
+    Word::from_big_endian(&bytes)
+}
+
+#[cfg(test)]
+mod precompile_tests {
+    use super::*;
+    use eth_types::{address, word};
+
+    #[test]
+    fn precompile_addresses() {
+        assert_eq!(
+            PrecompileCalls::from_address(&address!("0x0000000000000000000000000000000000000004")),
+            Some(PrecompileCalls::Identity)
+        );
+        assert!(is_precompiled(&address!(
+            "0x0000000000000000000000000000000000000009"
+        )));
+        assert!(!is_precompiled(&address!(
+            "0x0000000000000000000000000000000000000000"
+        )));
+        assert!(!is_precompiled(&address!(
+            "0x000000000000000000000000000000000000000a"
+        )));
+    }
+
+    #[test]
+    fn precompile_gas_costs() {
+        assert_eq!(PrecompileCalls::Identity.gas_cost(&[]), 15);
+        assert_eq!(PrecompileCalls::Identity.gas_cost(&[0; 33]), 21);
+        assert_eq!(PrecompileCalls::Sha256.gas_cost(&[0; 64]), 84);
+        assert_eq!(PrecompileCalls::Ripemd160.gas_cost(&[0; 1]), 720);
+        assert_eq!(PrecompileCalls::Bn128Pairing.gas_cost(&[0; 384]), 113000);
+        assert_eq!(PrecompileCalls::Blake2F.gas_cost(&[0; 212]), 0);
+
+        let mut blake2f_input = vec![0; 213];
+        blake2f_input[3] = 12;
+        assert_eq!(PrecompileCalls::Blake2F.gas_cost(&blake2f_input), 12);
+
+        // 1 byte base, 2 bytes exponent 0xffff and 32 bytes modulus cost
+        // 4 * 4 * 15 / 3, which is below the minimum
+        let mut modexp_input = vec![0; 96];
+        modexp_input[31] = 1;
+        modexp_input[63] = 2;
+        modexp_input[95] = 32;
+        modexp_input.extend_from_slice(&[3, 0xff, 0xff]);
+        modexp_input.extend_from_slice(&[0xff; 32]);
+        assert_eq!(PrecompileCalls::Modexp.gas_cost(&modexp_input), 200);
+    }
+
+    #[test]
+    fn precompile_input_lengths() {
+        assert_eq!(PrecompileCalls::ECRecover.input_length(&[0; 3]), 128);
+        assert_eq!(PrecompileCalls::Sha256.input_length(&[0; 3]), 3);
+        assert_eq!(PrecompileCalls::Blake2F.input_length(&[0; 212]), 0);
+        assert_eq!(PrecompileCalls::Blake2F.input_length(&[0; 213]), 213);
+        // The base, the exponent and the modulus are read past the end of a
+        // short input
+        let input = modexp_input([1, 1, 2], &[2]);
+        assert_eq!(PrecompileCalls::Modexp.input_length(&input), 100);
+    }
+
+    #[test]
+    fn precompile_ec_recover() {
+        let input = [
+            word!("18c547e4f7b0f325ad1e56f57e26c745b09a3e503d86e00e5255ff7f715d3d1c"),
+            word!("1c"),
+            word!("73b1693892219d736caba55bdb67216e485557ea6b6af75f37096c9aa6a5a75f"),
+            word!("eeb940b1d03b21e36b0e47e79769f095fe2ab855bd91e3a38756b7d75a9c4549"),
+        ]
+        .iter()
+        .flat_map(|word| {
+            let mut bytes = [0u8; 32];
+            word.to_big_endian(&mut bytes);
+            bytes
+        })
+        .collect::<Vec<_>>();
+        let address = address!("0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+        assert_eq!(EcRecoverInput::new(&input).recover(), Some(address));
+        assert_eq!(
+            PrecompileCalls::ECRecover.output(&input),
+            Some([[0; 12].as_slice(), address.as_bytes()].concat())
+        );
+
+        // Invalid recovery id
+        let mut invalid_input = input.clone();
+        invalid_input[63] = 0x1d;
+        assert_eq!(EcRecoverInput::new(&invalid_input).recover(), None);
+        assert_eq!(
+            PrecompileCalls::ECRecover.output(&invalid_input),
+            Some(Vec::new())
+        );
+
+        // Zero `r`, where the input is zero padded
+        assert_eq!(EcRecoverInput::new(&input[..64]).recover(), None);
+    }
+
+    #[test]
+    fn precompile_hashes() {
+        assert_eq!(
+            PrecompileCalls::Sha256.output(b"abc"),
+            Some(
+                word!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+                    .to_be_bytes()
+                    .to_vec()
+            )
+        );
+        assert_eq!(
+            PrecompileCalls::Ripemd160.output(b"abc"),
+            Some(
+                word!("8eb208f7e05d987a9b044a8e98c6b087f15a0bfc")
+                    .to_be_bytes()
+                    .to_vec()
+            )
+        );
+    }
+
+    fn words_to_bytes(words: &[Word]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_be_bytes()).collect()
+    }
+
+    #[test]
+    fn precompile_bn128_add_and_mul() {
+        let g = [Word::one(), Word::from(2)];
+        let double_g = [
+            word!("030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3"),
+            word!("15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4"),
+        ];
+        let neg_g = [
+            Word::one(),
+            word!("30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45"),
+        ];
+
+        let add_input = words_to_bytes(&[g, g].concat());
+        assert_eq!(Bn128AddInput::new(&add_input).add(), Some(double_g));
+        assert_eq!(
+            PrecompileCalls::Bn128Add.output(&add_input),
+            Some(words_to_bytes(&double_g))
+        );
+        // Infinity is the identity, and is encoded by a short input
+        assert_eq!(Bn128AddInput::new(&add_input[..64]).add(), Some(g));
+        assert_eq!(
+            Bn128AddInput::new(&words_to_bytes(&[g, neg_g].concat())).add(),
+            Some([Word::zero(); 2])
+        );
+        // Point not on the curve
+        let invalid_input = words_to_bytes(&[Word::one(), Word::from(3)]);
+        assert_eq!(Bn128AddInput::new(&invalid_input).add(), None);
+        assert_eq!(
+            PrecompileCalls::Bn128Add.output(&invalid_input),
+            Some(Vec::new())
+        );
+
+        let mul_input = words_to_bytes(&[g[0], g[1], Word::from(2)]);
+        assert_eq!(Bn128MulInput::new(&mul_input).mul(), Some(double_g));
+        // The scalar isn't reduced before the multiplication
+        let bn254_r = word!("30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001");
+        let mul_input = words_to_bytes(&[g[0], g[1], bn254_r + 2]);
+        assert_eq!(Bn128MulInput::new(&mul_input).mul(), Some(double_g));
+        let mul_input = words_to_bytes(&[Word::one(), Word::from(3), Word::from(2)]);
+        assert_eq!(Bn128MulInput::new(&mul_input).mul(), None);
+    }
+
+    #[test]
+    fn precompile_bn128_pairing() {
+        let g1 = [Word::one(), Word::from(2)];
+        let neg_g1 = [
+            Word::one(),
+            word!("30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45"),
+        ];
+        let g2 = [
+            word!("198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2"),
+            word!("1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed"),
+            word!("090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b"),
+            word!("12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa"),
+        ];
+        let check = |pairs: &[[Word; 2]]| {
+            let input = words_to_bytes(
+                &pairs
+                    .iter()
+                    .flat_map(|g1| [g1.as_slice(), g2.as_slice()].concat())
+                    .collect::<Vec<_>>(),
+            );
+            Bn128PairingInput::new(&input).and_then(|input| input.check())
+        };
+
+        assert_eq!(check(&[]), Some(true));
+        assert_eq!(check(&[g1]), Some(false));
+        assert_eq!(check(&[g1, neg_g1]), Some(true));
+        assert_eq!(check(&[[Word::zero(); 2]]), Some(true));
+        assert_eq!(check(&[[Word::one(), Word::from(3)]]), None);
+        assert_eq!(Bn128PairingInput::new(&[0; 191]), None);
+
+        assert_eq!(
+            PrecompileCalls::Bn128Pairing.output(&[]),
+            Some(Word::one().to_be_bytes().to_vec())
+        );
+        assert_eq!(
+            PrecompileCalls::Bn128Pairing.output(&[0; 100]),
+            Some(Vec::new())
+        );
+    }
+
+    fn modexp_input(lengths: [usize; 3], values: &[u8]) -> Vec<u8> {
+        let mut input = words_to_bytes(&lengths.map(Word::from));
+        input.extend_from_slice(values);
+        input
+    }
+
+    #[test]
+    fn precompile_modexp() {
+        // 3 ^ 0xffff % 2 ^ 256 - 1
+        let input = modexp_input(
+            [1, 2, 32],
+            &[[3, 0xff, 0xff].as_slice(), &[0xff; 32]].concat(),
+        );
+        let output = PrecompileCalls::Modexp.output(&input).unwrap();
+        let modulus = Word::MAX;
+        let mut expected = Word::one();
+        for _ in 0..0xffff {
+            expected =
+                Word::try_from(expected.full_mul(Word::from(3)) % U512::from(modulus)).unwrap();
+        }
+        assert_eq!(output, expected.to_be_bytes().to_vec());
+        // 4 ^ 2 words squared times 15 iterations is less than the minimum
+        assert_eq!(PrecompileCalls::Modexp.gas_cost(&input), 200);
+
+        // 2 ^ 10 % 1000, where the output takes as many bytes as the modulus
+        let input = modexp_input([1, 1, 2], &[2, 10, 0x03, 0xe8]);
+        assert_eq!(PrecompileCalls::Modexp.output(&input), Some(vec![0, 24]));
+        // The short input is right padded with zeros, so the modulus is 0x0300
+        assert_eq!(
+            PrecompileCalls::Modexp.output(&input[..99]),
+            Some(vec![0x01, 0x00])
+        );
+        // Zero modulus
+        let input = modexp_input([1, 1, 1], &[2, 10, 0]);
+        assert_eq!(PrecompileCalls::Modexp.output(&input), Some(vec![0]));
+        // Empty exponent and modulus
+        let input = modexp_input([1, 0, 0], &[2]);
+        assert_eq!(PrecompileCalls::Modexp.output(&input), Some(Vec::new()));
+
+        // Inputs larger than supported
+        let input = modexp_input([MODEXP_MAX_INPUT_SIZE + 1, 1, 1], &[]);
+        assert_eq!(ModexpInput::new(&input), None);
+        assert_eq!(PrecompileCalls::Modexp.output(&input), None);
+    }
+
+    #[test]
+    fn precompile_blake2f() {
+        // Test vector 5 of EIP-152, which is the BLAKE2b hash of "abc"
+        let mut input = Vec::new();
+        input.extend_from_slice(&12u32.to_be_bytes());
+        for word in [
+            0x6a09e667f2bdc948u64,
+            0xbb67ae8584caa73b,
+            0x3c6ef372fe94f82b,
+            0xa54ff53a5f1d36f1,
+            0x510e527fade682d1,
+            0x9b05688c2b3e6c1f,
+            0x1f83d9abfb41bd6b,
+            0x5be0cd19137e2179,
+        ] {
+            input.extend_from_slice(&word.to_le_bytes());
+        }
+        input.extend_from_slice(b"abc");
+        input.extend_from_slice(&[0; 125]);
+        input.extend_from_slice(&3u64.to_le_bytes());
+        input.extend_from_slice(&0u64.to_le_bytes());
+        input.push(1);
+        assert_eq!(
+            PrecompileCalls::Blake2F.output(&input),
+            Some(
+                [
+                    word!("ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1"),
+                    word!("7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"),
+                ]
+                .iter()
+                .flat_map(|word| word.to_be_bytes())
+                .collect()
+            )
+        );
+        assert_eq!(PrecompileCalls::Blake2F.gas_cost(&input), 12);
+
+        // Invalid final block indicator flag
+        let mut invalid_input = input.clone();
+        invalid_input[212] = 2;
+        assert_eq!(Blake2fInput::new(&invalid_input), None);
+        assert_eq!(
+            PrecompileCalls::Blake2F.output(&invalid_input),
+            Some(Vec::new())
+        );
+
+        // More rounds than supported
+        input[..4].copy_from_slice(&(BLAKE2F_MAX_ROUNDS + 1).to_be_bytes());
+        assert_eq!(PrecompileCalls::Blake2F.output(&input), None);
+    }
+}
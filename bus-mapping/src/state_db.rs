@@ -75,6 +75,8 @@ pub struct StateDB {
     state: HashMap<Address, Account>,
     access_list_account: HashSet<Address>,
     access_list_account_storage: HashSet<(Address, U256)>,
+    destructed_account: HashSet<Address>,
+    refund: u64,
 }
 
 impl Default for StateDB {
@@ -90,6 +92,8 @@ impl StateDB {
             state: HashMap::new(),
             access_list_account: HashSet::new(),
             access_list_account_storage: HashSet::new(),
+            destructed_account: HashSet::new(),
+            refund: 0,
         }
     }
 
@@ -107,6 +111,11 @@ impl StateDB {
         }
     }
 
+    /// Iterate over the [`Account`]s in the StateDB.
+    pub fn accounts(&self) -> impl Iterator<Item = (&Address, &Account)> {
+        self.state.iter()
+    }
+
     /// Get a mutable reference to the [`Account`] at `addr`.  If the
     /// [`Account`] is not found in the state, a zero one will be inserted
     /// and returned along with false.
@@ -154,11 +163,23 @@ impl StateDB {
         self.access_list_account.insert(addr)
     }
 
+    /// Check whether `addr` is in account access list.
+    pub fn check_account_in_access_list(&self, addr: &Address) -> bool {
+        self.access_list_account.contains(addr)
+    }
+
     /// Remove `addr` from account access list.
     pub fn remove_account_from_access_list(&mut self, addr: &Address) {
         assert!(self.access_list_account.remove(addr));
     }
 
+    /// Clear the account and account storage access lists, which must be
+    /// done at the beginning of each transaction.
+    pub fn clear_access_list(&mut self) {
+        self.access_list_account.clear();
+        self.access_list_account_storage.clear();
+    }
+
     /// Add `(addr, key)` into account storage access list. Returns `true` if
     /// it's not in the access list before.
     pub fn add_account_storage_to_access_list(&mut self, (addr, key): (Address, Word)) -> bool {
@@ -169,6 +190,44 @@ impl StateDB {
     pub fn remove_account_storage_from_access_list(&mut self, pair: &(Address, Word)) {
         assert!(self.access_list_account_storage.remove(pair));
     }
+
+    /// Mark the account at `addr` as destructed in the current transaction.
+    /// Returns `true` if it's not destructed before.
+    pub fn destruct_account(&mut self, addr: Address) -> bool {
+        self.destructed_account.insert(addr)
+    }
+
+    /// Unmark the account at `addr` as destructed.
+    pub fn remove_destructed_account(&mut self, addr: &Address) {
+        assert!(self.destructed_account.remove(addr));
+    }
+
+    /// Remove the accounts destructed in the current transaction from the
+    /// state, which must be done at the end of each transaction, and return
+    /// their addresses.
+    pub fn clear_destructed_accounts(&mut self) -> Vec<Address> {
+        let addrs = self.destructed_account.drain().collect::<Vec<_>>();
+        for addr in addrs.iter() {
+            self.state.remove(addr);
+        }
+        addrs
+    }
+
+    /// Return the gas refund counter of the current transaction.
+    pub fn refund(&self) -> u64 {
+        self.refund
+    }
+
+    /// Set the gas refund counter of the current transaction.
+    pub fn set_refund(&mut self, value: u64) {
+        self.refund = value;
+    }
+
+    /// Clear the gas refund counter, which must be done at the beginning of
+    /// each transaction.
+    pub fn clear_refund(&mut self) {
+        self.refund = 0;
+    }
 }
 
 #[cfg(test)]
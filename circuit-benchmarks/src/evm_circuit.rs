@@ -25,6 +25,14 @@ impl<F: Field> Circuit<F> for TestCircuit<F> {
         let rw_table = [(); 10].map(|_| meta.advice_column());
         let bytecode_table = [(); 4].map(|_| meta.advice_column());
         let block_table = [(); 3].map(|_| meta.advice_column());
+        let keccak_table = [(); 3].map(|_| meta.advice_column());
+        let ecrecover_table = [(); 6].map(|_| meta.advice_column());
+        let sha256_table = [(); 3].map(|_| meta.advice_column());
+        let ripemd160_table = [(); 3].map(|_| meta.advice_column());
+        let ecc_table = [(); 5].map(|_| meta.advice_column());
+        let modexp_table = [(); 4].map(|_| meta.advice_column());
+        let blake2f_table = [(); 3].map(|_| meta.advice_column());
+        let copy_table = [(); 11].map(|_| meta.advice_column());
         // Use constant expression to mock constant instance column for a more
         // reasonable benchmark.
         let power_of_randomness = [(); 31].map(|_| Expression::Constant(F::one()));
@@ -36,6 +44,14 @@ impl<F: Field> Circuit<F> for TestCircuit<F> {
             rw_table,
             bytecode_table,
             block_table,
+            keccak_table,
+            ecrecover_table,
+            sha256_table,
+            ripemd160_table,
+            ecc_table,
+            modexp_table,
+            blake2f_table,
+            copy_table,
         )
     }
 
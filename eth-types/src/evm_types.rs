@@ -5,6 +5,7 @@ pub mod opcode_ids;
 pub mod stack;
 pub mod storage;
 
+use crate::Word;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 pub use {
@@ -89,8 +90,21 @@ impl GasCost {
     pub const EXT: Self = Self(20);
     /// Constant cost for SHA3
     pub const SHA3: Self = Self(30);
+    /// Constant cost for every word hashed by SHA3 and CREATE2
+    pub const COPY_SHA3: Self = Self(6);
+    /// Constant cost for LOG and every topic of it
+    pub const LOG: Self = Self(375);
+    /// Constant cost for every byte of data in LOG
+    pub const LOG_DATA: Self = Self(8);
+    /// Constant cost for CALL and CALLCODE with non-zero value
+    pub const CALL_WITH_VALUE: Self = Self(9000);
+    /// Constant cost for every byte of code stored by a creation
+    pub const CODE_DEPOSIT_BYTE_COST: Self = Self(200);
     /// Constant cost for SELFDESTRUCT
     pub const SELFDESTRUCT: Self = Self(5000);
+    /// Constant cost for a *CALL* or SELFDESTRUCT sending value to an empty
+    /// account
+    pub const NEW_ACCOUNT: Self = Self(25000);
     /// Constant cost for CREATE
     pub const CREATE: Self = Self(32000);
     /// Constant cost for every additional word when expanding memory
@@ -107,10 +121,47 @@ impl GasCost {
     pub const TX: Self = Self(21000);
     /// Constant cost for creation transaction
     pub const CREATION_TX: Self = Self(53000);
+    /// Constant cost for every address in the access list of a transaction
+    pub const ACCESS_LIST_PER_ADDRESS: Self = Self(2400);
+    /// Constant cost for every storage key in the access list of a
+    /// transaction
+    pub const ACCESS_LIST_PER_STORAGE_KEY: Self = Self(1900);
+    /// Denominator of the maximum gas refund of a transaction over its used
+    /// gas (EIP-3529)
+    pub const MAX_REFUND_QUOTIENT_OF_GAS_USED: Self = Self(5);
     /// Denominator of quadratic part of memory expansion gas cost
     pub const MEMORY_EXPANSION_QUAD_DENOMINATOR: Self = Self(512);
     /// Coefficient of linear part of memory expansion gas cost
     pub const MEMORY_EXPANSION_LINEAR_COEFF: Self = Self(3);
+    /// Gas stipend given to the callee of a CALL or CALLCODE with non-zero
+    /// value
+    pub const CALL_STIPEND: Self = Self(2300);
+    /// Constant cost for the ecRecover precompile
+    pub const PRECOMPILE_EC_RECOVER: Self = Self(3000);
+    /// Base cost for the SHA256 precompile
+    pub const PRECOMPILE_SHA256_BASE: Self = Self(60);
+    /// Cost for every word of input of the SHA256 precompile
+    pub const PRECOMPILE_SHA256_PER_WORD: Self = Self(12);
+    /// Base cost for the RIPEMD160 precompile
+    pub const PRECOMPILE_RIPEMD160_BASE: Self = Self(600);
+    /// Cost for every word of input of the RIPEMD160 precompile
+    pub const PRECOMPILE_RIPEMD160_PER_WORD: Self = Self(120);
+    /// Base cost for the identity precompile
+    pub const PRECOMPILE_IDENTITY_BASE: Self = Self(15);
+    /// Cost for every word of input of the identity precompile
+    pub const PRECOMPILE_IDENTITY_PER_WORD: Self = Self(3);
+    /// Minimum cost for the MODEXP precompile
+    pub const PRECOMPILE_MODEXP_MIN: Self = Self(200);
+    /// Constant cost for the BN254 point addition precompile
+    pub const PRECOMPILE_BN256_ADD: Self = Self(150);
+    /// Constant cost for the BN254 scalar multiplication precompile
+    pub const PRECOMPILE_BN256_MUL: Self = Self(6000);
+    /// Base cost for the BN254 pairing check precompile
+    pub const PRECOMPILE_BN256_PAIRING_BASE: Self = Self(45000);
+    /// Cost for every pair of points of the BN254 pairing check precompile
+    pub const PRECOMPILE_BN256_PAIRING_PER_PAIR: Self = Self(34000);
+    /// Cost for every round of the BLAKE2F precompile
+    pub const PRECOMPILE_BLAKE2F_PER_ROUND: Self = Self(1);
 }
 
 impl GasCost {
@@ -125,6 +176,47 @@ impl GasCost {
     pub const fn as_usize(&self) -> usize {
         self.0 as usize
     }
+
+    /// Returns the gas cost of the MODEXP precompile as defined in EIP-2565,
+    /// given the lengths in bytes of the base, the exponent and the modulus,
+    /// and the first 32 bytes of the exponent.  The cost is saturated to
+    /// [`u64::MAX`].
+    pub fn precompile_modexp(base_len: Word, exp_len: Word, mod_len: Word, exp_head: Word) -> Self {
+        // Lengths this large can't be paid with any gas limit
+        if [base_len, exp_len, mod_len]
+            .iter()
+            .any(|len| *len > Word::from(u32::MAX))
+        {
+            return Self(u64::MAX);
+        }
+        let [base_len, exp_len, mod_len] = [base_len, exp_len, mod_len].map(|len| len.as_u64());
+
+        let words = (base_len.max(mod_len) + 7) / 8;
+        let multiplication_complexity = Word::from(words * words);
+
+        // Only the first 32 bytes of the exponent are used to compute the
+        // number of iterations
+        let exp_head_bits = exp_head.bits() as u64;
+        let iteration_count = if exp_len <= 32 {
+            exp_head_bits.saturating_sub(1)
+        } else {
+            8 * (exp_len - 32) + exp_head_bits.saturating_sub(1)
+        }
+        .max(1);
+
+        let gas_cost = multiplication_complexity * iteration_count / 3;
+        if gas_cost > Word::from(u64::MAX) {
+            Self(u64::MAX)
+        } else {
+            Self(gas_cost.as_u64().max(Self::PRECOMPILE_MODEXP_MIN.0))
+        }
+    }
+
+    /// Returns the gas cost of the BLAKE2F precompile as defined in EIP-152,
+    /// given its number of rounds.
+    pub const fn precompile_blake2f(rounds: u32) -> Self {
+        Self(Self::PRECOMPILE_BLAKE2F_PER_ROUND.0 * rounds as u64)
+    }
 }
 
 impl From<u8> for GasCost {
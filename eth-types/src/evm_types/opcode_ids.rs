@@ -633,6 +633,317 @@ impl OpcodeId {
             OpcodeId::SELFDESTRUCT => GasCost::SELFDESTRUCT,
         }
     }
+
+    /// Returns the number of stack items popped and pushed by `OpcodeId`,
+    /// where `DUPn` and `SWAPn` are regarded as popping and pushing back all
+    /// the stack items they access.
+    pub const fn stack_pop_push(&self) -> (u32, u32) {
+        match self {
+            OpcodeId::STOP => (0, 0),
+            OpcodeId::ADD => (2, 1),
+            OpcodeId::MUL => (2, 1),
+            OpcodeId::SUB => (2, 1),
+            OpcodeId::DIV => (2, 1),
+            OpcodeId::SDIV => (2, 1),
+            OpcodeId::MOD => (2, 1),
+            OpcodeId::SMOD => (2, 1),
+            OpcodeId::ADDMOD => (3, 1),
+            OpcodeId::MULMOD => (3, 1),
+            OpcodeId::EXP => (2, 1),
+            OpcodeId::SIGNEXTEND => (2, 1),
+            OpcodeId::LT => (2, 1),
+            OpcodeId::GT => (2, 1),
+            OpcodeId::SLT => (2, 1),
+            OpcodeId::SGT => (2, 1),
+            OpcodeId::EQ => (2, 1),
+            OpcodeId::ISZERO => (1, 1),
+            OpcodeId::AND => (2, 1),
+            OpcodeId::OR => (2, 1),
+            OpcodeId::XOR => (2, 1),
+            OpcodeId::NOT => (1, 1),
+            OpcodeId::BYTE => (2, 1),
+            OpcodeId::CALLDATALOAD => (1, 1),
+            OpcodeId::CALLDATASIZE => (0, 1),
+            OpcodeId::CALLDATACOPY => (3, 0),
+            OpcodeId::CODESIZE => (0, 1),
+            OpcodeId::CODECOPY => (3, 0),
+            OpcodeId::SHL => (2, 1),
+            OpcodeId::SHR => (2, 1),
+            OpcodeId::SAR => (2, 1),
+            OpcodeId::POP => (1, 0),
+            OpcodeId::MLOAD => (1, 1),
+            OpcodeId::MSTORE => (2, 0),
+            OpcodeId::MSTORE8 => (2, 0),
+            OpcodeId::JUMP => (1, 0),
+            OpcodeId::JUMPI => (2, 0),
+            OpcodeId::PC => (0, 1),
+            OpcodeId::MSIZE => (0, 1),
+            OpcodeId::JUMPDEST => (0, 0),
+            OpcodeId::PUSH1 => (0, 1),
+            OpcodeId::PUSH2 => (0, 1),
+            OpcodeId::PUSH3 => (0, 1),
+            OpcodeId::PUSH4 => (0, 1),
+            OpcodeId::PUSH5 => (0, 1),
+            OpcodeId::PUSH6 => (0, 1),
+            OpcodeId::PUSH7 => (0, 1),
+            OpcodeId::PUSH8 => (0, 1),
+            OpcodeId::PUSH9 => (0, 1),
+            OpcodeId::PUSH10 => (0, 1),
+            OpcodeId::PUSH11 => (0, 1),
+            OpcodeId::PUSH12 => (0, 1),
+            OpcodeId::PUSH13 => (0, 1),
+            OpcodeId::PUSH14 => (0, 1),
+            OpcodeId::PUSH15 => (0, 1),
+            OpcodeId::PUSH16 => (0, 1),
+            OpcodeId::PUSH17 => (0, 1),
+            OpcodeId::PUSH18 => (0, 1),
+            OpcodeId::PUSH19 => (0, 1),
+            OpcodeId::PUSH20 => (0, 1),
+            OpcodeId::PUSH21 => (0, 1),
+            OpcodeId::PUSH22 => (0, 1),
+            OpcodeId::PUSH23 => (0, 1),
+            OpcodeId::PUSH24 => (0, 1),
+            OpcodeId::PUSH25 => (0, 1),
+            OpcodeId::PUSH26 => (0, 1),
+            OpcodeId::PUSH27 => (0, 1),
+            OpcodeId::PUSH28 => (0, 1),
+            OpcodeId::PUSH29 => (0, 1),
+            OpcodeId::PUSH30 => (0, 1),
+            OpcodeId::PUSH31 => (0, 1),
+            OpcodeId::PUSH32 => (0, 1),
+            OpcodeId::DUP1 => (1, 2),
+            OpcodeId::DUP2 => (2, 3),
+            OpcodeId::DUP3 => (3, 4),
+            OpcodeId::DUP4 => (4, 5),
+            OpcodeId::DUP5 => (5, 6),
+            OpcodeId::DUP6 => (6, 7),
+            OpcodeId::DUP7 => (7, 8),
+            OpcodeId::DUP8 => (8, 9),
+            OpcodeId::DUP9 => (9, 10),
+            OpcodeId::DUP10 => (10, 11),
+            OpcodeId::DUP11 => (11, 12),
+            OpcodeId::DUP12 => (12, 13),
+            OpcodeId::DUP13 => (13, 14),
+            OpcodeId::DUP14 => (14, 15),
+            OpcodeId::DUP15 => (15, 16),
+            OpcodeId::DUP16 => (16, 17),
+            OpcodeId::SWAP1 => (2, 2),
+            OpcodeId::SWAP2 => (3, 3),
+            OpcodeId::SWAP3 => (4, 4),
+            OpcodeId::SWAP4 => (5, 5),
+            OpcodeId::SWAP5 => (6, 6),
+            OpcodeId::SWAP6 => (7, 7),
+            OpcodeId::SWAP7 => (8, 8),
+            OpcodeId::SWAP8 => (9, 9),
+            OpcodeId::SWAP9 => (10, 10),
+            OpcodeId::SWAP10 => (11, 11),
+            OpcodeId::SWAP11 => (12, 12),
+            OpcodeId::SWAP12 => (13, 13),
+            OpcodeId::SWAP13 => (14, 14),
+            OpcodeId::SWAP14 => (15, 15),
+            OpcodeId::SWAP15 => (16, 16),
+            OpcodeId::SWAP16 => (17, 17),
+            OpcodeId::RETURN => (2, 0),
+            OpcodeId::REVERT => (2, 0),
+            OpcodeId::SHA3 => (2, 1),
+            OpcodeId::ADDRESS => (0, 1),
+            OpcodeId::BALANCE => (1, 1),
+            OpcodeId::ORIGIN => (0, 1),
+            OpcodeId::CALLER => (0, 1),
+            OpcodeId::CALLVALUE => (0, 1),
+            OpcodeId::GASPRICE => (0, 1),
+            OpcodeId::EXTCODESIZE => (1, 1),
+            OpcodeId::EXTCODECOPY => (4, 0),
+            OpcodeId::EXTCODEHASH => (1, 1),
+            OpcodeId::RETURNDATASIZE => (0, 1),
+            OpcodeId::RETURNDATACOPY => (3, 0),
+            OpcodeId::BLOCKHASH => (1, 1),
+            OpcodeId::COINBASE => (0, 1),
+            OpcodeId::TIMESTAMP => (0, 1),
+            OpcodeId::NUMBER => (0, 1),
+            OpcodeId::DIFFICULTY => (0, 1),
+            OpcodeId::GASLIMIT => (0, 1),
+            OpcodeId::CHAINID => (0, 1),
+            OpcodeId::SELFBALANCE => (0, 1),
+            OpcodeId::BASEFEE => (0, 1),
+            OpcodeId::SLOAD => (1, 1),
+            OpcodeId::SSTORE => (2, 0),
+            OpcodeId::GAS => (0, 1),
+            OpcodeId::LOG0 => (2, 0),
+            OpcodeId::LOG1 => (3, 0),
+            OpcodeId::LOG2 => (4, 0),
+            OpcodeId::LOG3 => (5, 0),
+            OpcodeId::LOG4 => (6, 0),
+            OpcodeId::CREATE => (3, 1),
+            OpcodeId::CREATE2 => (4, 1),
+            OpcodeId::CALL => (7, 1),
+            OpcodeId::CALLCODE => (7, 1),
+            OpcodeId::DELEGATECALL => (6, 1),
+            OpcodeId::STATICCALL => (6, 1),
+            OpcodeId::SELFDESTRUCT => (1, 0),
+            OpcodeId::INVALID(_) => (0, 0),
+        }
+    }
+
+    /// Returns the range `(min, max)` of the stack pointer within which
+    /// `OpcodeId` can be executed without a stack overflow (below `min`) or a
+    /// stack underflow (above `max`).  The stack pointer is 1024 when the
+    /// stack is empty, and decreases when an item is pushed.
+    pub const fn valid_stack_ptr_range(&self) -> (u32, u32) {
+        let (pop, push) = self.stack_pop_push();
+        let min = if push > pop { push - pop } else { 0 };
+        (min, 1024 - pop)
+    }
+}
+
+impl From<u8> for OpcodeId {
+    fn from(value: u8) -> Self {
+        match value {
+            0x00u8 => OpcodeId::STOP,
+            0x01u8 => OpcodeId::ADD,
+            0x02u8 => OpcodeId::MUL,
+            0x03u8 => OpcodeId::SUB,
+            0x04u8 => OpcodeId::DIV,
+            0x05u8 => OpcodeId::SDIV,
+            0x06u8 => OpcodeId::MOD,
+            0x07u8 => OpcodeId::SMOD,
+            0x08u8 => OpcodeId::ADDMOD,
+            0x09u8 => OpcodeId::MULMOD,
+            0x0au8 => OpcodeId::EXP,
+            0x0bu8 => OpcodeId::SIGNEXTEND,
+            0x10u8 => OpcodeId::LT,
+            0x11u8 => OpcodeId::GT,
+            0x12u8 => OpcodeId::SLT,
+            0x13u8 => OpcodeId::SGT,
+            0x14u8 => OpcodeId::EQ,
+            0x15u8 => OpcodeId::ISZERO,
+            0x16u8 => OpcodeId::AND,
+            0x17u8 => OpcodeId::OR,
+            0x18u8 => OpcodeId::XOR,
+            0x19u8 => OpcodeId::NOT,
+            0x1au8 => OpcodeId::BYTE,
+            0x35u8 => OpcodeId::CALLDATALOAD,
+            0x36u8 => OpcodeId::CALLDATASIZE,
+            0x37u8 => OpcodeId::CALLDATACOPY,
+            0x38u8 => OpcodeId::CODESIZE,
+            0x39u8 => OpcodeId::CODECOPY,
+            0x1bu8 => OpcodeId::SHL,
+            0x1cu8 => OpcodeId::SHR,
+            0x1du8 => OpcodeId::SAR,
+            0x50u8 => OpcodeId::POP,
+            0x51u8 => OpcodeId::MLOAD,
+            0x52u8 => OpcodeId::MSTORE,
+            0x53u8 => OpcodeId::MSTORE8,
+            0x56u8 => OpcodeId::JUMP,
+            0x57u8 => OpcodeId::JUMPI,
+            0x58u8 => OpcodeId::PC,
+            0x59u8 => OpcodeId::MSIZE,
+            0x5bu8 => OpcodeId::JUMPDEST,
+            0x60u8 => OpcodeId::PUSH1,
+            0x61u8 => OpcodeId::PUSH2,
+            0x62u8 => OpcodeId::PUSH3,
+            0x63u8 => OpcodeId::PUSH4,
+            0x64u8 => OpcodeId::PUSH5,
+            0x65u8 => OpcodeId::PUSH6,
+            0x66u8 => OpcodeId::PUSH7,
+            0x67u8 => OpcodeId::PUSH8,
+            0x68u8 => OpcodeId::PUSH9,
+            0x69u8 => OpcodeId::PUSH10,
+            0x6au8 => OpcodeId::PUSH11,
+            0x6bu8 => OpcodeId::PUSH12,
+            0x6cu8 => OpcodeId::PUSH13,
+            0x6du8 => OpcodeId::PUSH14,
+            0x6eu8 => OpcodeId::PUSH15,
+            0x6fu8 => OpcodeId::PUSH16,
+            0x70u8 => OpcodeId::PUSH17,
+            0x71u8 => OpcodeId::PUSH18,
+            0x72u8 => OpcodeId::PUSH19,
+            0x73u8 => OpcodeId::PUSH20,
+            0x74u8 => OpcodeId::PUSH21,
+            0x75u8 => OpcodeId::PUSH22,
+            0x76u8 => OpcodeId::PUSH23,
+            0x77u8 => OpcodeId::PUSH24,
+            0x78u8 => OpcodeId::PUSH25,
+            0x79u8 => OpcodeId::PUSH26,
+            0x7au8 => OpcodeId::PUSH27,
+            0x7bu8 => OpcodeId::PUSH28,
+            0x7cu8 => OpcodeId::PUSH29,
+            0x7du8 => OpcodeId::PUSH30,
+            0x7eu8 => OpcodeId::PUSH31,
+            0x7fu8 => OpcodeId::PUSH32,
+            0x80u8 => OpcodeId::DUP1,
+            0x81u8 => OpcodeId::DUP2,
+            0x82u8 => OpcodeId::DUP3,
+            0x83u8 => OpcodeId::DUP4,
+            0x84u8 => OpcodeId::DUP5,
+            0x85u8 => OpcodeId::DUP6,
+            0x86u8 => OpcodeId::DUP7,
+            0x87u8 => OpcodeId::DUP8,
+            0x88u8 => OpcodeId::DUP9,
+            0x89u8 => OpcodeId::DUP10,
+            0x8au8 => OpcodeId::DUP11,
+            0x8bu8 => OpcodeId::DUP12,
+            0x8cu8 => OpcodeId::DUP13,
+            0x8du8 => OpcodeId::DUP14,
+            0x8eu8 => OpcodeId::DUP15,
+            0x8fu8 => OpcodeId::DUP16,
+            0x90u8 => OpcodeId::SWAP1,
+            0x91u8 => OpcodeId::SWAP2,
+            0x92u8 => OpcodeId::SWAP3,
+            0x93u8 => OpcodeId::SWAP4,
+            0x94u8 => OpcodeId::SWAP5,
+            0x95u8 => OpcodeId::SWAP6,
+            0x96u8 => OpcodeId::SWAP7,
+            0x97u8 => OpcodeId::SWAP8,
+            0x98u8 => OpcodeId::SWAP9,
+            0x99u8 => OpcodeId::SWAP10,
+            0x9au8 => OpcodeId::SWAP11,
+            0x9bu8 => OpcodeId::SWAP12,
+            0x9cu8 => OpcodeId::SWAP13,
+            0x9du8 => OpcodeId::SWAP14,
+            0x9eu8 => OpcodeId::SWAP15,
+            0x9fu8 => OpcodeId::SWAP16,
+            0xf3u8 => OpcodeId::RETURN,
+            0xfdu8 => OpcodeId::REVERT,
+            0x20u8 => OpcodeId::SHA3,
+            0x30u8 => OpcodeId::ADDRESS,
+            0x31u8 => OpcodeId::BALANCE,
+            0x32u8 => OpcodeId::ORIGIN,
+            0x33u8 => OpcodeId::CALLER,
+            0x34u8 => OpcodeId::CALLVALUE,
+            0x3au8 => OpcodeId::GASPRICE,
+            0x3bu8 => OpcodeId::EXTCODESIZE,
+            0x3cu8 => OpcodeId::EXTCODECOPY,
+            0x3fu8 => OpcodeId::EXTCODEHASH,
+            0x3du8 => OpcodeId::RETURNDATASIZE,
+            0x3eu8 => OpcodeId::RETURNDATACOPY,
+            0x40u8 => OpcodeId::BLOCKHASH,
+            0x41u8 => OpcodeId::COINBASE,
+            0x42u8 => OpcodeId::TIMESTAMP,
+            0x43u8 => OpcodeId::NUMBER,
+            0x44u8 => OpcodeId::DIFFICULTY,
+            0x45u8 => OpcodeId::GASLIMIT,
+            0x46u8 => OpcodeId::CHAINID,
+            0x47u8 => OpcodeId::SELFBALANCE,
+            0x48u8 => OpcodeId::BASEFEE,
+            0x54u8 => OpcodeId::SLOAD,
+            0x55u8 => OpcodeId::SSTORE,
+            0x5au8 => OpcodeId::GAS,
+            0xa0u8 => OpcodeId::LOG0,
+            0xa1u8 => OpcodeId::LOG1,
+            0xa2u8 => OpcodeId::LOG2,
+            0xa3u8 => OpcodeId::LOG3,
+            0xa4u8 => OpcodeId::LOG4,
+            0xf0u8 => OpcodeId::CREATE,
+            0xf5u8 => OpcodeId::CREATE2,
+            0xf1u8 => OpcodeId::CALL,
+            0xf2u8 => OpcodeId::CALLCODE,
+            0xf4u8 => OpcodeId::DELEGATECALL,
+            0xfau8 => OpcodeId::STATICCALL,
+            0xffu8 => OpcodeId::SELFDESTRUCT,
+            b => OpcodeId::INVALID(b),
+        }
+    }
 }
 
 impl FromStr for OpcodeId {
@@ -29,7 +29,10 @@ use crate::evm_types::{memory::Memory, stack::Stack, storage::Storage};
 use crate::evm_types::{Gas, GasCost, OpcodeId, ProgramCounter};
 use ethers_core::types;
 pub use ethers_core::types::{
-    transaction::{eip2930::AccessList, response::Transaction},
+    transaction::{
+        eip2930::{AccessList, AccessListItem},
+        response::Transaction,
+    },
     Address, Block, Bytes, H160, H256, U256, U64,
 };
 use pairing::arithmetic::FieldExt;
@@ -15,6 +15,7 @@ async fn test_circuit_input_builder_block(block_num: u64) {
 
     // 1. Query geth for Block, Txs and TxExecTraces
     let (eth_block, geth_trace) = cli.get_block(block_num).await.unwrap();
+    let history_hashes = cli.get_history_hashes(block_num).await.unwrap();
 
     // 2. Get State Accesses from TxExecTraces
     let access_set = cli.get_state_accesses(&eth_block, &geth_trace).unwrap();
@@ -22,16 +23,26 @@ async fn test_circuit_input_builder_block(block_num: u64) {
 
     // 3. Query geth for all accounts, storage keys, and codes from Accesses
     let (proofs, codes) = cli.get_state(block_num, access_set).await.unwrap();
+    let prev_state_root = cli.get_prev_state_root(block_num).await.unwrap();
 
-    // 4. Build a partial StateDB from step 3
+    // 4. Build a partial StateDB and the partial state tries from step 3
+    let state_trie = cli.build_state_trie(prev_state_root, &proofs).unwrap();
     let (state_db, code_db) = cli.build_state_code_db(proofs, codes);
     trace!("StateDB: {:#?}", state_db);
 
     // 5. For each step in TxExecTraces, gen the associated ops and state
     // circuit inputs
     let builder = cli
-        .gen_inputs_from_state(state_db, code_db, &eth_block, &geth_trace)
+        .gen_inputs_from_state(
+            state_db,
+            code_db,
+            state_trie,
+            history_hashes,
+            &eth_block,
+            &geth_trace,
+        )
         .unwrap();
+    assert_eq!(builder.block.prev_state_root, prev_state_root);
 
     trace!("CircuitInputBuilder: {:#?}", builder);
 }
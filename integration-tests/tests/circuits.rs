@@ -77,7 +77,13 @@ async fn test_state_circuit_block(block_num: u64) {
         STACK_ROWS_MAX,
         STACK_ADDRESS_MAX,
         STORAGE_ROWS_MAX,
-    >::new(Fr::rand(), memory_ops, stack_ops, storage_ops);
+    >::new(
+        Fr::rand(),
+        memory_ops,
+        stack_ops,
+        storage_ops,
+        &builder.block.mpt_updates,
+    );
 
     use pairing::bn256::Fr as Fp;
     let prover = MockProver::<Fp>::run(DEGREE as u32, &circuit, vec![]).unwrap();
@@ -31,11 +31,57 @@ pub fn new_single_tx_trace_accounts_gas(
     let eth_block = new_block();
     let mut eth_tx = new_tx(&eth_block);
     eth_tx.gas = Word::from(gas.0);
+    new_single_tx_trace(eth_block, eth_tx, accounts)
+}
+
+/// Create a new block with a single creation tx that executes the init code
+/// passed by argument.  The trace will be generated automatically with the
+/// external_tracer from the init code.
+pub fn new_single_tx_trace_create(init_code: &Bytecode) -> Result<GethData, Error> {
+    new_single_tx_trace_create_accounts(init_code, Vec::new())
+}
+
+/// Create a new block with a single creation tx that executes the init code
+/// passed by argument, with the given accounts in the state, which may
+/// include an account at the address of the created contract.  The trace will
+/// be generated automatically with the external_tracer from the init code.
+pub fn new_single_tx_trace_create_accounts(
+    init_code: &Bytecode,
+    accounts: Vec<Account>,
+) -> Result<GethData, Error> {
+    let eth_block = new_block();
+    let mut eth_tx = new_tx(&eth_block);
+    eth_tx.to = None;
+    eth_tx.input = Bytes::from(init_code.to_vec());
+    new_single_tx_trace(eth_block, eth_tx, accounts)
+}
 
+/// Create a new block with a single tx that calls the precompiled contract at
+/// `address` with the given input.  The trace will be generated automatically
+/// with the external_tracer, and has no steps since a precompiled contract
+/// has no code.
+pub fn new_single_tx_trace_precompile(address: Address, input: Vec<u8>) -> Result<GethData, Error> {
+    let eth_block = new_block();
+    let mut eth_tx = new_tx(&eth_block);
+    eth_tx.to = Some(address);
+    eth_tx.input = Bytes::from(input);
+    let precompile_account = Account {
+        address,
+        ..Default::default()
+    };
+    new_single_tx_trace(eth_block, eth_tx, vec![precompile_account])
+}
+
+/// Generate the trace of the single tx `eth_tx` in `eth_block`, executed with
+/// the given accounts in the state.
+fn new_single_tx_trace(
+    eth_block: Block<()>,
+    eth_tx: eth_types::Transaction,
+    accounts: Vec<Account>,
+) -> Result<GethData, Error> {
     let trace_config = TraceConfig {
         chain_id: MOCK_CHAIN_ID.into(),
-        // TODO: Add mocking history_hashes when nedded.
-        history_hashes: Vec::new(),
+        history_hashes: new_history_hashes(&eth_block),
         block_constants: BlockConstants::try_from(&eth_block)?,
         accounts: accounts
             .iter()
@@ -113,7 +159,9 @@ pub fn new_block() -> Block<()> {
         number: Some(U64([123456u64])),
         gas_used: Word::from(15_000_000u64),
         gas_limit: Word::from(15_000_000u64),
-        base_fee_per_gas: Some(Word::from(97u64)),
+        // The tracer runs the mock transactions, which set a gas price, with
+        // no base fee as legacy ones
+        base_fee_per_gas: Some(Word::zero()),
         extra_data: Bytes::default(),
         logs_bloom: None,
         timestamp: Word::from(1633398551u64),
@@ -128,6 +176,15 @@ pub fn new_block() -> Block<()> {
     }
 }
 
+/// Generate mock hashes of the most recent 256 blocks before the given block,
+/// where the hash of a block is derived from its number.
+pub fn new_history_hashes<TX>(block: &Block<TX>) -> Vec<Word> {
+    let number = block.number.unwrap_or_default().as_u64();
+    (number.saturating_sub(256)..number)
+        .map(|n| (Word::from(n) << 128) | Word::from(0xb10c_u64))
+        .collect()
+}
+
 /// Generate a new mock transaction with preloaded data, useful for tests.
 pub fn new_tx<TX>(block: &Block<TX>) -> eth_types::Transaction {
     eth_types::Transaction {
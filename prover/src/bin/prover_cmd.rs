@@ -111,7 +111,7 @@ async fn main() {
             STACK_ROWS_MAX,
             STACK_ADDRESS_MAX,
             STORAGE_ROWS_MAX,
-        >::new_from_rw_map(block.randomness, &block.rws);
+        >::new_from_rw_map(block.randomness, &block.rws, &block.mpt_updates);
 
         // TODO: same quest like in the first scope
         let vk = keygen_vk(&params, &circuit).expect("keygen_vk for params, state_circuit");
@@ -0,0 +1,71 @@
+use crate::common::*;
+
+/// Returns the bits of `value`, least significant first.
+pub fn into_bits(value: u32) -> [u8; WORD_SIZE] {
+    let mut bits = [0; WORD_SIZE];
+    for (idx, bit) in bits.iter_mut().enumerate() {
+        *bit = ((value >> idx) & 1) as u8;
+    }
+    bits
+}
+
+/// The boolean function of the rounds of the left line in `group`, which is
+/// used in the reverse order by the right line.
+pub fn f(group: usize, x: u32, y: u32, z: u32) -> u32 {
+    match group {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        _ => x ^ (y | !z),
+    }
+}
+
+/// Returns the words `t` of the five rounds which result in the state
+/// `[a, b, c, d, e]`, where `b` and `c` are the last two words, and `d`, `e`
+/// and `a` are the three words before them rotated to the left by 10.
+pub fn state_to_words(state: &[u32; NUM_STATE_WORDS]) -> [u32; NUM_STATE_WORDS] {
+    let [a, b, c, d, e] = *state;
+    let rotation = STATE_ROTATION as u32;
+    [
+        a.rotate_right(rotation),
+        e.rotate_right(rotation),
+        d.rotate_right(rotation),
+        c,
+        b,
+    ]
+}
+
+/// Returns `input` padded to a multiple of the block size, followed by the
+/// padding flags of its bytes.
+pub fn pad(input: &[u8]) -> (Vec<u8>, Vec<bool>) {
+    let mut padded = input.to_vec();
+    padded.push(0x80);
+    while padded.len() % BLOCK_SIZE != BLOCK_SIZE - 8 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&(input.len() as u64 * 8).to_le_bytes());
+
+    let is_paddings = (0..padded.len()).map(|idx| idx >= input.len()).collect();
+    (padded, is_paddings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryInto;
+
+    #[test]
+    fn padding_lengths() {
+        for (length, padded_length) in [(0, 64), (55, 64), (56, 128), (64, 128), (120, 192)] {
+            let (padded, is_paddings) = pad(&vec![0xff; length]);
+            assert_eq!(padded.len(), padded_length);
+            assert_eq!(padded[length], 0x80);
+            assert_eq!(is_paddings.iter().filter(|p| !**p).count(), length);
+            assert_eq!(
+                u64::from_le_bytes(padded[padded_length - 8..].try_into().unwrap()),
+                length as u64 * 8
+            );
+        }
+    }
+}
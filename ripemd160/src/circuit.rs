@@ -0,0 +1,538 @@
+use crate::{
+    arith_helpers::{pad, state_to_words},
+    common::*,
+    gates::{
+        compression::CompressionConfig,
+        gate_helpers::*,
+        layout::LayoutConfig,
+        padding::{PaddingConfig, NUM_BYTES_PER_WORD},
+    },
+    plain::Ripemd160,
+    ripemd160_arith::{LineRoundTrace, Ripemd160Arith},
+};
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression},
+    poly::Rotation,
+};
+
+/// The number of columns of the RIPEMD-160 table.
+pub const HASH_TABLE_WIDTH: usize = 3;
+
+/// The number of rows before the first block, which are queried by it as the
+/// end rows of a previous block.
+const NUM_LEADING_ROWS: usize = NUM_END_ROWS;
+
+/// A block of a padded input, which is the witness of [`ROWS_PER_BLOCK`]
+/// rows.
+#[derive(Clone, Debug)]
+struct BlockWitness {
+    bytes: Vec<u8>,
+    is_paddings: Vec<bool>,
+    is_final: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Ripemd160Config<F> {
+    r: F,
+    minimum_rows: usize,
+    layout: LayoutConfig<F>,
+    compression: CompressionConfig<F>,
+    padding: PaddingConfig<F>,
+    is_final: Column<Advice>,
+    is_new_hash: Column<Advice>,
+    /// The RIPEMD-160 table, whose columns are the RLC of the input, the length
+    /// of the input and the RLC of the digest.  Only the last row of the last
+    /// block of a hash isn't all zero.
+    pub hash_table: [Column<Advice>; HASH_TABLE_WIDTH],
+}
+
+impl<F: Field> Ripemd160Config<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>, r: F) -> Self {
+        let layout = LayoutConfig::configure(meta);
+        let is_final = meta.advice_column();
+        let is_new_hash = meta.advice_column();
+        let compression = CompressionConfig::configure(meta, &layout, is_new_hash);
+        let padding =
+            PaddingConfig::configure(meta, r, &layout, compression.x, is_final, is_new_hash);
+        let hash_table = [(); HASH_TABLE_WIDTH].map(|_| meta.advice_column());
+
+        meta.create_gate("ripemd160 flags", |meta| {
+            let q_block = meta.query_selector(layout.q_block);
+            let q_block_start = meta.query_selector(layout.q_block_start);
+            let q_first = meta.query_selector(layout.q_first);
+            let is_final_prev = meta.query_advice(is_final, Rotation::prev());
+            let is_final = meta.query_advice(is_final, Rotation::cur());
+            let is_new_hash_prev = meta.query_advice(is_new_hash, Rotation::prev());
+            let is_new_hash = meta.query_advice(is_new_hash, Rotation::cur());
+
+            vec![
+                (
+                    "is_final is boolean",
+                    q_block.clone() * is_final.clone() * (constant(1) - is_final.clone()),
+                ),
+                (
+                    "is_new_hash is boolean",
+                    q_block.clone() * is_new_hash.clone() * (constant(1) - is_new_hash.clone()),
+                ),
+                (
+                    "is_final is the same in all the rows of a block",
+                    (q_block.clone() - q_block_start.clone()) * (is_final - is_final_prev.clone()),
+                ),
+                (
+                    "is_new_hash is the same in all the rows of a block",
+                    (q_block - q_block_start.clone()) * (is_new_hash.clone() - is_new_hash_prev),
+                ),
+                (
+                    "a block starts a new hash after the final block of a hash",
+                    q_block_start
+                        * (constant(1) - q_first.clone())
+                        * (is_new_hash.clone() - is_final_prev),
+                ),
+                (
+                    "the first block starts a new hash",
+                    q_first * (constant(1) - is_new_hash),
+                ),
+            ]
+        });
+
+        meta.create_gate("ripemd160 table", |meta| {
+            let q_enable = meta.query_selector(layout.q_enable);
+            let q_last = meta.query_selector(layout.q_last);
+            hash_table
+                .iter()
+                .map(|column| {
+                    (
+                        "table value = 0 when not in the last row of a block",
+                        q_enable.clone()
+                            * (constant(1) - q_last.clone())
+                            * meta.query_advice(*column, Rotation::cur()),
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("ripemd160 output", |meta| {
+            let q_last = meta.query_selector(layout.q_last);
+            let is_final = meta.query_advice(is_final, Rotation::cur());
+            let data_rlc =
+                meta.query_advice(padding.data_rlcs[NUM_BYTES_PER_WORD - 1], Rotation::cur());
+            let length = meta.query_advice(padding.length, Rotation::cur());
+
+            // The last row of a block holds the hash value as the state
+            // before the next row
+            let mut digest_rlc = constant(0);
+            for word in query_state(meta, &compression.t_left, 1) {
+                for byte in word_to_bytes(&word) {
+                    digest_rlc = digest_rlc * Expression::Constant(r) + byte;
+                }
+            }
+
+            [data_rlc, length, digest_rlc]
+                .iter()
+                .zip(hash_table.iter())
+                .map(|(value, column)| {
+                    (
+                        "table value = is_final ? value : 0",
+                        q_last.clone()
+                            * (meta.query_advice(*column, Rotation::cur())
+                                - is_final.clone() * value.clone()),
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+
+        Self {
+            r,
+            minimum_rows: meta.minimum_rows(),
+            layout,
+            compression,
+            padding,
+            is_final,
+            is_new_hash,
+            hash_table,
+        }
+    }
+
+    /// Assign the hashes of `inputs` into a circuit of `size` rows, where the
+    /// blocks left are filled with zeros which are never finalized.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        size: usize,
+        inputs: &[Vec<u8>],
+    ) -> Result<(), Error> {
+        self.assign_blocks(layouter, size, blocks(inputs))
+    }
+
+    fn assign_blocks(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        size: usize,
+        mut blocks: Vec<BlockWitness>,
+    ) -> Result<(), Error> {
+        // Subtract the unusable rows from the size
+        let num_rows = size - self.minimum_rows + 1;
+        let num_blocks = (num_rows - NUM_LEADING_ROWS) / ROWS_PER_BLOCK;
+        if blocks.len() > num_blocks {
+            return Err(Error::Synthesis);
+        }
+        blocks.resize(
+            num_blocks,
+            BlockWitness {
+                bytes: vec![0; BLOCK_SIZE],
+                is_paddings: vec![false; BLOCK_SIZE],
+                is_final: false,
+            },
+        );
+
+        layouter.assign_region(
+            || "ripemd160",
+            |mut region| {
+                let mut state = IV;
+                let mut is_new_hash = true;
+                let mut is_padding = false;
+                let mut data_rlc = F::zero();
+                let mut length = 0;
+
+                for offset in 0..NUM_LEADING_ROWS {
+                    self.assign_empty_row(&mut region, offset)?;
+                }
+
+                for (idx, block) in blocks.iter().enumerate() {
+                    let offset = NUM_LEADING_ROWS + idx * ROWS_PER_BLOCK;
+                    self.layout.assign_block(&mut region, offset, idx == 0)?;
+
+                    if is_new_hash {
+                        state = IV;
+                        is_padding = false;
+                        data_rlc = F::zero();
+                        length = 0;
+                    }
+                    let trace = Ripemd160Arith::compress(&state, &block.bytes);
+                    let start_words = state_to_words(&state);
+                    let end_words = state_to_words(&trace.state);
+                    // The carries in the order of the words of the end rows
+                    let end_carries = [0, 4, 3, 2, 1].map(|idx| trace.state_carries[idx]);
+                    let mut x_words = Vec::with_capacity(NUM_WORDS_TO_ABSORB);
+                    let mut x_lines = Vec::with_capacity(NUM_ROUNDS);
+
+                    for row in 0..ROWS_PER_BLOCK {
+                        let offset = offset + row;
+
+                        if row < NUM_START_ROWS {
+                            let line = LineRoundTrace {
+                                t: start_words[row],
+                                ..Default::default()
+                            };
+                            self.compression
+                                .assign_row(&mut region, offset, &line, &line, 0)?;
+                        } else if row < NUM_START_ROWS + NUM_ROUNDS {
+                            let round = row - NUM_START_ROWS;
+                            let x = trace.words.get(round).copied().unwrap_or_default();
+                            let [x_word, x_left, x_right] = self.compression.assign_row(
+                                &mut region,
+                                offset,
+                                &trace.rounds[round].left,
+                                &trace.rounds[round].right,
+                                x,
+                            )?;
+                            if round < NUM_WORDS_TO_ABSORB {
+                                x_words.push(x_word);
+                            }
+                            x_lines.push((x_left, x_right));
+                        } else {
+                            let idx = row - NUM_START_ROWS - NUM_ROUNDS;
+                            let line = LineRoundTrace {
+                                t: end_words[idx],
+                                t_carry: end_carries[idx],
+                                ..Default::default()
+                            };
+                            self.compression.assign_row(
+                                &mut region,
+                                offset,
+                                &line,
+                                &LineRoundTrace::default(),
+                                0,
+                            )?;
+                        }
+
+                        let mut is_paddings = [is_padding; NUM_BYTES_PER_WORD];
+                        let mut data_rlcs = [data_rlc; NUM_BYTES_PER_WORD];
+                        if (NUM_START_ROWS..NUM_START_ROWS + NUM_WORDS_TO_ABSORB).contains(&row) {
+                            let byte_offset = (row - NUM_START_ROWS) * NUM_BYTES_PER_WORD;
+                            for idx in 0..NUM_BYTES_PER_WORD {
+                                is_padding = block.is_paddings[byte_offset + idx];
+                                if !is_padding {
+                                    data_rlc = data_rlc * self.r
+                                        + F::from(block.bytes[byte_offset + idx] as u64);
+                                    length += 1;
+                                }
+                                is_paddings[idx] = is_padding;
+                                data_rlcs[idx] = data_rlc;
+                            }
+                        }
+                        self.padding.assign_row(
+                            &mut region,
+                            offset,
+                            is_paddings,
+                            data_rlcs,
+                            length,
+                        )?;
+
+                        let hash_table_row = if row == ROWS_PER_BLOCK - 1 && block.is_final {
+                            let digest = trace
+                                .state
+                                .iter()
+                                .flat_map(|word| word.to_le_bytes().to_vec())
+                                .collect::<Vec<_>>();
+                            [data_rlc, F::from(length), digest_rlc(&digest, self.r)]
+                        } else {
+                            [F::zero(); HASH_TABLE_WIDTH]
+                        };
+                        self.assign_flags_and_table(
+                            &mut region,
+                            offset,
+                            block.is_final,
+                            is_new_hash,
+                            hash_table_row,
+                        )?;
+                    }
+
+                    // Every round copies the words of the block it selects
+                    for (round, (x_left, x_right)) in x_lines.iter().enumerate() {
+                        region.constrain_equal(x_left.cell(), x_words[LEFT_WORDS[round]].cell())?;
+                        region
+                            .constrain_equal(x_right.cell(), x_words[RIGHT_WORDS[round]].cell())?;
+                    }
+
+                    state = trace.state;
+                    is_new_hash = block.is_final;
+                }
+
+                for offset in NUM_LEADING_ROWS + num_blocks * ROWS_PER_BLOCK..num_rows {
+                    self.assign_empty_row(&mut region, offset)?;
+                }
+
+                Ok(())
+            },
+        )
+    }
+
+    /// Assign zeros to a row outside of the blocks, where only the
+    /// constraints of the table are enabled so that its values are all zero.
+    fn assign_empty_row(&self, region: &mut Region<'_, F>, offset: usize) -> Result<(), Error> {
+        self.layout.q_enable.enable(region, offset)?;
+        let line = LineRoundTrace::default();
+        self.compression
+            .assign_row(region, offset, &line, &line, 0)?;
+        self.padding.assign_row(
+            region,
+            offset,
+            [false; NUM_BYTES_PER_WORD],
+            [F::zero(); NUM_BYTES_PER_WORD],
+            0,
+        )?;
+        self.assign_flags_and_table(region, offset, false, false, [F::zero(); HASH_TABLE_WIDTH])
+    }
+
+    fn assign_flags_and_table(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        is_final: bool,
+        is_new_hash: bool,
+        hash_table_row: [F; HASH_TABLE_WIDTH],
+    ) -> Result<(), Error> {
+        for (name, column, value) in [
+            ("is_final", self.is_final, F::from(is_final as u64)),
+            ("is_new_hash", self.is_new_hash, F::from(is_new_hash as u64)),
+        ] {
+            region.assign_advice(
+                || format!("assign {} {}", name, offset),
+                column,
+                offset,
+                || Ok(value),
+            )?;
+        }
+        for (idx, (column, value)) in self.hash_table.iter().zip(hash_table_row).enumerate() {
+            region.assign_advice(
+                || format!("assign hash table {} {}", idx, offset),
+                *column,
+                offset,
+                || Ok(value),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the blocks of the padded `inputs`.
+fn blocks(inputs: &[Vec<u8>]) -> Vec<BlockWitness> {
+    inputs
+        .iter()
+        .flat_map(|input| {
+            let (padded, is_paddings) = pad(input);
+            let num_blocks = padded.len() / BLOCK_SIZE;
+            padded
+                .chunks(BLOCK_SIZE)
+                .zip(is_paddings.chunks(BLOCK_SIZE))
+                .enumerate()
+                .map(|(idx, (bytes, is_paddings))| BlockWitness {
+                    bytes: bytes.to_vec(),
+                    is_paddings: is_paddings.to_vec(),
+                    is_final: idx == num_blocks - 1,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Returns the RLC of `input`, where the first byte has the highest power of
+/// `r`.
+pub fn input_rlc<F: Field>(input: &[u8], r: F) -> F {
+    input
+        .iter()
+        .fold(F::zero(), |acc, byte| acc * r + F::from(*byte as u64))
+}
+
+/// Returns the RLC of `digest`, where the first byte has the highest power of
+/// `r`.
+pub fn digest_rlc<F: Field>(digest: &[u8], r: F) -> F {
+    digest
+        .iter()
+        .fold(F::zero(), |acc, byte| acc * r + F::from(*byte as u64))
+}
+
+/// Returns the row of the RIPEMD-160 table of `input`.
+pub fn hash_table_row<F: Field>(input: &[u8], r: F) -> [F; HASH_TABLE_WIDTH] {
+    let mut ripemd160 = Ripemd160::default();
+    ripemd160.update(input);
+    [
+        input_rlc(input, r),
+        F::from(input.len() as u64),
+        digest_rlc(&ripemd160.digest(), r),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use pairing::bn256::Fr;
+
+    #[derive(Default)]
+    struct MyCircuit<F> {
+        blocks: Vec<BlockWitness>,
+        size: usize,
+        _marker: std::marker::PhantomData<F>,
+    }
+
+    impl<F: Field> MyCircuit<F> {
+        fn r() -> F {
+            F::from(123456)
+        }
+    }
+
+    impl<F: Field> Circuit<F> for MyCircuit<F> {
+        type Config = Ripemd160Config<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            Ripemd160Config::configure(meta, MyCircuit::r())
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.assign_blocks(&mut layouter, self.size, self.blocks.clone())
+        }
+    }
+
+    fn verify<F: Field>(k: u32, blocks: Vec<BlockWitness>, success: bool) {
+        let circuit = MyCircuit::<F> {
+            blocks,
+            size: 2usize.pow(k),
+            _marker: std::marker::PhantomData,
+        };
+
+        let prover = MockProver::<F>::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify().is_ok(), success);
+    }
+
+    #[test]
+    fn ripemd160_circuit_valid() {
+        let inputs = vec![
+            vec![],
+            b"abc".to_vec(),
+            vec![0xff; 55],
+            vec![0x80; 56],
+            vec![0x01; 64],
+            (0..130).map(|byte| byte as u8).collect(),
+        ];
+        verify::<Fr>(10, blocks(&inputs), true);
+    }
+
+    #[test]
+    fn ripemd160_circuit_empty() {
+        verify::<Fr>(8, vec![], true);
+    }
+
+    #[test]
+    fn ripemd160_circuit_invalid_final() {
+        // The padding starts in the last 8 bytes of the first block, which
+        // doesn't leave room for the length
+        let mut blocks = blocks(&[vec![0x80; 56]]);
+        blocks[0].is_final = true;
+        verify::<Fr>(9, blocks, false);
+    }
+
+    #[test]
+    fn ripemd160_circuit_invalid_padding() {
+        // The last byte of the input isn't the first padding byte 0x80
+        let mut blocks = blocks(&[b"abc".to_vec()]);
+        blocks[0].is_paddings[2] = true;
+        verify::<Fr>(8, blocks, false);
+    }
+
+    #[test]
+    fn ripemd160_circuit_invalid_length() {
+        let mut blocks = blocks(&[b"abc".to_vec()]);
+        blocks[0].bytes[BLOCK_SIZE - 8] += 1;
+        verify::<Fr>(8, blocks, false);
+    }
+
+    #[test]
+    fn ripemd160_hash_table_row() {
+        let r = MyCircuit::<Fr>::r();
+        let digest = (0..DIGEST_SIZE)
+            .map(|idx| {
+                u8::from_str_radix(
+                    &"8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"[2 * idx..2 * idx + 2],
+                    16,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            hash_table_row(b"abc", r),
+            [
+                Fr::from(0x61) * r * r + Fr::from(0x62) * r + Fr::from(0x63),
+                Fr::from(3),
+                digest_rlc(&digest, r),
+            ]
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Constants of the RIPEMD-160 hash function, which can be found in
+//! [RIPEMD-160: A Strengthened Version of RIPEMD](https://homes.esat.kuleuven.be/~bosselae/ripemd160/pdf/AB-9601/AB-9601.pdf),
+//! and the layout of a block in the circuit.
+
+/// The number of bits of a word.
+pub const WORD_SIZE: usize = 32;
+
+/// The number of bytes of a message block.
+pub const BLOCK_SIZE: usize = 64;
+
+/// The number of words of a message block.
+pub const NUM_WORDS_TO_ABSORB: usize = 16;
+
+/// The number of bytes of the digest.
+pub const DIGEST_SIZE: usize = 20;
+
+/// The number of words of the state.
+pub const NUM_STATE_WORDS: usize = 5;
+
+/// The number of rounds of each of the two lines of the compression function.
+pub const NUM_ROUNDS: usize = 80;
+
+/// The number of rounds of a group, which share the same boolean function and
+/// the same constant.
+pub const NUM_ROUNDS_PER_GROUP: usize = 16;
+
+/// The number of groups of rounds.
+pub const NUM_GROUPS: usize = NUM_ROUNDS / NUM_ROUNDS_PER_GROUP;
+
+/// The initial hash value
+pub static IV: [u32; NUM_STATE_WORDS] =
+    [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// The constants of the groups of the left line
+pub static LEFT_CONSTANTS: [u32; NUM_GROUPS] =
+    [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+
+/// The constants of the groups of the right line
+pub static RIGHT_CONSTANTS: [u32; NUM_GROUPS] =
+    [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+
+/// The words of the message block selected by the rounds of the left line
+pub static LEFT_WORDS: [usize; NUM_ROUNDS] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, //
+    7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8, //
+    3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, //
+    1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2, //
+    4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+
+/// The words of the message block selected by the rounds of the right line
+pub static RIGHT_WORDS: [usize; NUM_ROUNDS] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, //
+    6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2, //
+    15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, //
+    8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14, //
+    12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+
+/// The left rotations of the rounds of the left line
+pub static LEFT_ROTATIONS: [usize; NUM_ROUNDS] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, //
+    7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12, //
+    11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, //
+    11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, //
+    9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+
+/// The left rotations of the rounds of the right line
+pub static RIGHT_ROTATIONS: [usize; NUM_ROUNDS] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, //
+    9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11, //
+    9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, //
+    15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8, //
+    8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+
+/// The smallest left rotation of a round.
+pub const MIN_ROTATION: usize = 5;
+
+/// The number of the different left rotations of the rounds, which are 5 to
+/// 15.
+pub const NUM_ROTATIONS: usize = 11;
+
+/// The left rotation of the words `c` of the state, which become `d` in the
+/// next round.
+pub const STATE_ROTATION: usize = 10;
+
+/// The number of rows at the start of a block, which hold the initial state
+/// of the compression function, so that `a`, `b`, `c`, `d` and `e` can be
+/// queried at the rotations -1 to -5 of the first round.
+pub const NUM_START_ROWS: usize = NUM_STATE_WORDS;
+
+/// The number of rows at the end of a block, which hold the hash value after
+/// the block is absorbed, in the layout of the start rows.
+pub const NUM_END_ROWS: usize = NUM_STATE_WORDS;
+
+/// The number of rows of a block, where each round of both lines of the
+/// compression function takes one row.
+pub const ROWS_PER_BLOCK: usize = NUM_START_ROWS + NUM_ROUNDS + NUM_END_ROWS;
@@ -0,0 +1,299 @@
+use super::{gate_helpers::*, layout::LayoutConfig};
+use crate::common::*;
+use crate::ripemd160_arith::LineRoundTrace;
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{AssignedCell, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error},
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+/// The number of bits of the carry of `u`, which adds up 4 words.
+pub const NUM_BITS_U_CARRY: usize = 2;
+
+/// The number of bits of the carry of `t`, which adds up 2 words in the
+/// rounds and 3 words in the end rows.
+pub const NUM_BITS_T_CARRY: usize = 2;
+
+/// The compression function, where each row holds the bits of the new words
+/// `t` of the left and the right lines after a round, and of the sums `u`
+/// which are rotated into them.  The state of a line is made of the previous
+/// words `t`, so they are queried at the rotations -1 to -5.
+///
+/// The input rows hold the bits of the words `x` of the block, and every
+/// round copies the words it selects into `x_left` and `x_right`.
+#[derive(Clone, Debug)]
+pub struct CompressionConfig<F> {
+    pub t_left: [Column<Advice>; WORD_SIZE],
+    pub t_right: [Column<Advice>; WORD_SIZE],
+    pub u_left: [Column<Advice>; WORD_SIZE],
+    pub u_right: [Column<Advice>; WORD_SIZE],
+    pub t_left_carry: [Column<Advice>; NUM_BITS_T_CARRY],
+    pub t_right_carry: [Column<Advice>; NUM_BITS_T_CARRY],
+    pub u_left_carry: [Column<Advice>; NUM_BITS_U_CARRY],
+    pub u_right_carry: [Column<Advice>; NUM_BITS_U_CARRY],
+    pub x: [Column<Advice>; WORD_SIZE],
+    pub x_word: Column<Advice>,
+    pub x_left: Column<Advice>,
+    pub x_right: Column<Advice>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> CompressionConfig<F> {
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        layout: &LayoutConfig<F>,
+        is_new_hash: Column<Advice>,
+    ) -> Self {
+        let t_left = [(); WORD_SIZE].map(|_| meta.advice_column());
+        let t_right = [(); WORD_SIZE].map(|_| meta.advice_column());
+        let u_left = [(); WORD_SIZE].map(|_| meta.advice_column());
+        let u_right = [(); WORD_SIZE].map(|_| meta.advice_column());
+        let t_left_carry = [(); NUM_BITS_T_CARRY].map(|_| meta.advice_column());
+        let t_right_carry = [(); NUM_BITS_T_CARRY].map(|_| meta.advice_column());
+        let u_left_carry = [(); NUM_BITS_U_CARRY].map(|_| meta.advice_column());
+        let u_right_carry = [(); NUM_BITS_U_CARRY].map(|_| meta.advice_column());
+        let x = [(); WORD_SIZE].map(|_| meta.advice_column());
+        let x_word = meta.advice_column();
+        let x_left = meta.advice_column();
+        let x_right = meta.advice_column();
+        for column in [x_word, x_left, x_right] {
+            meta.enable_equality(column);
+        }
+        let word_modulus = constant::<F>(1 << WORD_SIZE);
+
+        meta.create_gate("ripemd160 boolean", |meta| {
+            let q_block = meta.query_selector(layout.q_block);
+            t_left
+                .iter()
+                .chain(t_right.iter())
+                .chain(u_left.iter())
+                .chain(u_right.iter())
+                .chain(t_left_carry.iter())
+                .chain(t_right_carry.iter())
+                .chain(u_left_carry.iter())
+                .chain(u_right_carry.iter())
+                .chain(x.iter())
+                .map(|column| {
+                    let bit = meta.query_advice(*column, Rotation::cur());
+                    (
+                        "bit is boolean",
+                        q_block.clone() * bit.clone() * (constant(1) - bit),
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("ripemd160 start", |meta| {
+            let q_start = meta.query_selector(layout.q_start);
+            let is_new_hash = meta.query_advice(is_new_hash, Rotation::cur());
+            let iv = meta.query_fixed(layout.iv, Rotation::cur());
+            // The hash value after the previous block is in its end rows
+            let prev = compose(&query_word(meta, &t_left, -(NUM_END_ROWS as i32)));
+            let t_left_value = compose(&query_word(meta, &t_left, 0));
+            let t_right_value = compose(&query_word(meta, &t_right, 0));
+            vec![
+                (
+                    "t_left = is_new_hash ? iv : previous hash value",
+                    q_start.clone() * (t_left_value.clone() - select(is_new_hash, iv, prev)),
+                ),
+                (
+                    "both lines start from the same state",
+                    q_start * (t_right_value - t_left_value),
+                ),
+            ]
+        });
+
+        meta.create_gate("ripemd160 message", |meta| {
+            let q_input = meta.query_selector(layout.q_input);
+            let x = compose(&query_word(meta, &x, 0));
+            let x_word = meta.query_advice(x_word, Rotation::cur());
+            vec![("x_word = x", q_input * (x_word - x))]
+        });
+
+        meta.create_gate("ripemd160 round", |meta| {
+            let q_round = meta.query_selector(layout.q_round);
+            let q_groups = layout
+                .q_groups
+                .map(|selector| meta.query_selector(selector));
+
+            let mut constraints = Vec::new();
+            for (t, u, t_carry, u_carry, x, constants, rotations, is_left) in [
+                (
+                    t_left,
+                    u_left,
+                    t_left_carry,
+                    u_left_carry,
+                    x_left,
+                    LEFT_CONSTANTS,
+                    layout.q_left_rotations,
+                    true,
+                ),
+                (
+                    t_right,
+                    u_right,
+                    t_right_carry,
+                    u_right_carry,
+                    x_right,
+                    RIGHT_CONSTANTS,
+                    layout.q_right_rotations,
+                    false,
+                ),
+            ] {
+                let [a, b, c, d, e] = query_state(meta, &t, 0);
+                let new_t = compose(&query_word(meta, &t, 0));
+                let u_bits = query_word(meta, &u, 0);
+                let u = compose(&u_bits);
+                let t_carry = compose(&query_word(meta, &t_carry, 0));
+                let u_carry = compose(&query_word(meta, &u_carry, 0));
+                let x = meta.query_advice(x, Rotation::cur());
+
+                // The selectors of the groups choose the boolean function,
+                // which the right line uses in the reverse order, and the
+                // constant
+                let f_and_k =
+                    q_groups
+                        .iter()
+                        .enumerate()
+                        .fold(constant(0), |acc, (group, q_group)| {
+                            let f_group = if is_left {
+                                group
+                            } else {
+                                NUM_GROUPS - 1 - group
+                            };
+                            acc + q_group.clone()
+                                * (compose(&f(f_group, &b, &c, &d))
+                                    + constant(constants[group] as u64))
+                        });
+                // The selectors of the rotations choose the rotation of `u`
+                let rotated_u =
+                    rotations
+                        .iter()
+                        .enumerate()
+                        .fold(constant(0), |acc, (idx, q_rotation)| {
+                            acc + meta.query_selector(*q_rotation)
+                                * compose(&rotate_left(&u_bits, MIN_ROTATION + idx))
+                        });
+
+                constraints.push((
+                    "u = a + f(b, c, d) + x + k",
+                    q_round.clone() * (u + u_carry * word_modulus.clone() - compose(&a) - x)
+                        - f_and_k,
+                ));
+                constraints.push((
+                    "t = rol(u, s) + e",
+                    q_round.clone() * (new_t + t_carry * word_modulus.clone() - compose(&e))
+                        - rotated_u,
+                ));
+            }
+            constraints
+        });
+
+        meta.create_gate("ripemd160 end", |meta| {
+            let q_ends = layout.q_ends.map(|selector| meta.query_selector(selector));
+            // The end row `i` computes the word `i` of the start rows, from
+            // the initial state in the start rows and from the final states
+            // of the lines in the last round rows
+            q_ends
+                .iter()
+                .enumerate()
+                .map(|(idx, q_end)| {
+                    let idx_rotation = idx as i32;
+                    let [h0, h1, h2, h3, h4] =
+                        query_state(meta, &t_left, -(NUM_ROUNDS as i32) - idx_rotation)
+                            .map(|word| compose(&word));
+                    let [al, bl, cl, dl, el] =
+                        query_state(meta, &t_left, -idx_rotation).map(|word| compose(&word));
+                    let [ar, br, cr, dr, er] =
+                        query_state(meta, &t_right, -idx_rotation).map(|word| compose(&word));
+                    let bits = query_word(meta, &t_left, 0);
+                    let carry = compose(&query_word(meta, &t_left_carry, 0));
+                    // The first three words are rotated to the right by 10
+                    let (new, sum) = match idx {
+                        0 => (rotate_left(&bits, STATE_ROTATION), h1 + cl + dr),
+                        1 => (rotate_left(&bits, STATE_ROTATION), h0 + bl + cr),
+                        2 => (rotate_left(&bits, STATE_ROTATION), h4 + al + br),
+                        3 => (bits, h3 + el + ar),
+                        _ => (bits, h2 + dl + er),
+                    };
+                    (
+                        "new hash value = hash value + states of the lines",
+                        q_end.clone() * (compose(&new) + carry * word_modulus.clone() - sum),
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+
+        Self {
+            t_left,
+            t_right,
+            u_left,
+            u_right,
+            t_left_carry,
+            t_right_carry,
+            u_left_carry,
+            u_right_carry,
+            x,
+            x_word,
+            x_left,
+            x_right,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assign the bits of the words and of the carries of a row, and the
+    /// words of the block.  Returns the cells of `x_word`, `x_left` and
+    /// `x_right`.
+    pub fn assign_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        left: &LineRoundTrace,
+        right: &LineRoundTrace,
+        x: u32,
+    ) -> Result<[AssignedCell<F, F>; 3], Error> {
+        for (name, columns, value) in [
+            ("t_left", &self.t_left[..], left.t as u64),
+            ("t_right", &self.t_right[..], right.t as u64),
+            ("u_left", &self.u_left[..], left.u as u64),
+            ("u_right", &self.u_right[..], right.u as u64),
+            ("t_left_carry", &self.t_left_carry[..], left.t_carry),
+            ("t_right_carry", &self.t_right_carry[..], right.t_carry),
+            ("u_left_carry", &self.u_left_carry[..], left.u_carry),
+            ("u_right_carry", &self.u_right_carry[..], right.u_carry),
+            ("x", &self.x[..], x as u64),
+        ] {
+            for (idx, column) in columns.iter().enumerate() {
+                region.assign_advice(
+                    || format!("assign {} bit {} {}", name, idx, offset),
+                    *column,
+                    offset,
+                    || Ok(F::from((value >> idx) & 1)),
+                )?;
+            }
+        }
+
+        Ok([
+            self.assign_word(region, offset, "x_word", self.x_word, x)?,
+            self.assign_word(region, offset, "x_left", self.x_left, left.x)?,
+            self.assign_word(region, offset, "x_right", self.x_right, right.x)?,
+        ])
+    }
+
+    fn assign_word(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        name: &str,
+        column: Column<Advice>,
+        value: u32,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        region.assign_advice(
+            || format!("assign {} {}", name, offset),
+            column,
+            offset,
+            || Ok(F::from(value as u64)),
+        )
+    }
+}
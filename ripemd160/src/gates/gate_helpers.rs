@@ -0,0 +1,108 @@
+use crate::common::*;
+use eth_types::Field;
+use halo2_proofs::{
+    plonk::{Advice, Column, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+pub fn constant<F: Field>(value: u64) -> Expression<F> {
+    Expression::Constant(F::from(value))
+}
+
+/// Query the bits of a word, least significant first, at `rotation`.
+pub fn query_word<F: Field>(
+    meta: &mut VirtualCells<'_, F>,
+    columns: &[Column<Advice>],
+    rotation: i32,
+) -> Vec<Expression<F>> {
+    columns
+        .iter()
+        .map(|column| meta.query_advice(*column, Rotation(rotation)))
+        .collect()
+}
+
+/// Query the state `[a, b, c, d, e]` of a line before the row at `rotation`,
+/// where the previous rows hold the new words `t` of the previous rounds:
+/// `b` and `c` are the last two words, and `d`, `e` and `a` are the three
+/// words before them rotated to the left by 10.
+pub fn query_state<F: Field>(
+    meta: &mut VirtualCells<'_, F>,
+    columns: &[Column<Advice>],
+    rotation: i32,
+) -> [Vec<Expression<F>>; NUM_STATE_WORDS] {
+    let [t1, t2, t3, t4, t5] = [1, 2, 3, 4, 5].map(|idx| query_word(meta, columns, rotation - idx));
+    [
+        rotate_left(&t5, STATE_ROTATION),
+        t1,
+        t2,
+        rotate_left(&t3, STATE_ROTATION),
+        rotate_left(&t4, STATE_ROTATION),
+    ]
+}
+
+/// Returns the value of `bits`, least significant first.
+pub fn compose<F: Field>(bits: &[Expression<F>]) -> Expression<F> {
+    bits.iter()
+        .enumerate()
+        .fold(constant(0), |acc, (idx, bit)| {
+            acc + bit.clone() * constant(1 << idx)
+        })
+}
+
+/// Returns the little-endian bytes of the word `bits`.
+pub fn word_to_bytes<F: Field>(bits: &[Expression<F>]) -> Vec<Expression<F>> {
+    bits.chunks(8).map(compose).collect()
+}
+
+/// Returns `when_true` if `condition` is 1, or `when_false` if it's 0.
+pub fn select<F: Field>(
+    condition: Expression<F>,
+    when_true: Expression<F>,
+    when_false: Expression<F>,
+) -> Expression<F> {
+    condition.clone() * when_true + (constant(1) - condition) * when_false
+}
+
+pub fn rotate_left<F: Field>(bits: &[Expression<F>], n: usize) -> Vec<Expression<F>> {
+    (0..WORD_SIZE)
+        .map(|idx| bits[(idx + WORD_SIZE - n) % WORD_SIZE].clone())
+        .collect()
+}
+
+fn xor<F: Field>(x: Expression<F>, y: Expression<F>) -> Expression<F> {
+    x.clone() + y.clone() - constant(2) * x * y
+}
+
+fn or<F: Field>(x: Expression<F>, y: Expression<F>) -> Expression<F> {
+    x.clone() + y.clone() - x * y
+}
+
+fn not<F: Field>(x: Expression<F>) -> Expression<F> {
+    constant(1) - x
+}
+
+/// Bitwise boolean function of the rounds of the left line in `group`:
+/// - `x ^ y ^ z`
+/// - `(x & y) | (!x & z)`
+/// - `(x | !y) ^ z`
+/// - `(x & z) | (y & !z)`
+/// - `x ^ (y | !z)`
+pub fn f<F: Field>(
+    group: usize,
+    x: &[Expression<F>],
+    y: &[Expression<F>],
+    z: &[Expression<F>],
+) -> Vec<Expression<F>> {
+    (0..WORD_SIZE)
+        .map(|idx| {
+            let (x, y, z) = (x[idx].clone(), y[idx].clone(), z[idx].clone());
+            match group {
+                0 => xor(xor(x, y), z),
+                1 => select(x, y, z),
+                2 => xor(or(x, not(y)), z),
+                3 => select(z, x, y),
+                _ => xor(x, or(y, not(z))),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,135 @@
+use crate::{arith_helpers::state_to_words, common::*};
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::Region,
+    plonk::{Column, ConstraintSystem, Error, Fixed, Selector},
+};
+use std::marker::PhantomData;
+
+/// Selectors and fixed columns of the layout of the blocks, where a block
+/// takes [`ROWS_PER_BLOCK`] rows:
+///
+/// | rows        | content                                                 |
+/// |-------------|---------------------------------------------------------|
+/// | `0..5`      | the hash value before the block, as the initial state   |
+/// | `5..21`     | rounds of both lines, and the words of the block        |
+/// | `21..85`    | rounds of both lines                                    |
+/// | `85..90`    | the hash value after the block, in the same layout      |
+#[derive(Clone, Debug)]
+pub struct LayoutConfig<F> {
+    /// Enabled on all the usable rows, including the ones after the blocks
+    pub q_enable: Selector,
+    /// Enabled on all the rows of the blocks
+    pub q_block: Selector,
+    /// Enabled on the first row of every block
+    pub q_block_start: Selector,
+    /// Enabled on the first row of the first block
+    pub q_first: Selector,
+    pub q_start: Selector,
+    pub q_round: Selector,
+    /// Enabled on the rounds of a group, which choose the boolean functions
+    /// and the constants of the lines
+    pub q_groups: [Selector; NUM_GROUPS],
+    /// Enabled on the rounds of the left line which rotate by
+    /// `MIN_ROTATION + idx`
+    pub q_left_rotations: [Selector; NUM_ROTATIONS],
+    /// Enabled on the rounds of the right line which rotate by
+    /// `MIN_ROTATION + idx`
+    pub q_right_rotations: [Selector; NUM_ROTATIONS],
+    pub q_input: Selector,
+    /// Enabled on the last two input rows, which hold the length of the
+    /// message in the last block of a hash
+    pub q_length: Selector,
+    /// Enabled on the last input row
+    pub q_input_last: Selector,
+    /// Enabled on each of the end rows, which compute different words of the
+    /// hash value
+    pub q_ends: [Selector; NUM_END_ROWS],
+    /// Enabled on the last row of every block
+    pub q_last: Selector,
+    pub iv: Column<Fixed>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> LayoutConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        // Some gates combine several of these selectors, which simple
+        // selectors don't allow
+        Self {
+            q_enable: meta.complex_selector(),
+            q_block: meta.complex_selector(),
+            q_block_start: meta.complex_selector(),
+            q_first: meta.complex_selector(),
+            q_start: meta.complex_selector(),
+            q_round: meta.complex_selector(),
+            q_groups: [(); NUM_GROUPS].map(|_| meta.complex_selector()),
+            q_left_rotations: [(); NUM_ROTATIONS].map(|_| meta.complex_selector()),
+            q_right_rotations: [(); NUM_ROTATIONS].map(|_| meta.complex_selector()),
+            q_input: meta.complex_selector(),
+            q_length: meta.complex_selector(),
+            q_input_last: meta.complex_selector(),
+            q_ends: [(); NUM_END_ROWS].map(|_| meta.complex_selector()),
+            q_last: meta.complex_selector(),
+            iv: meta.fixed_column(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Enable the selectors of the block starting at `offset`.
+    pub fn assign_block(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        is_first: bool,
+    ) -> Result<(), Error> {
+        let iv = state_to_words(&IV);
+
+        for row in 0..ROWS_PER_BLOCK {
+            let offset = offset + row;
+            self.q_enable.enable(region, offset)?;
+            self.q_block.enable(region, offset)?;
+            if row == 0 {
+                self.q_block_start.enable(region, offset)?;
+                if is_first {
+                    self.q_first.enable(region, offset)?;
+                }
+            }
+            if row == ROWS_PER_BLOCK - 1 {
+                self.q_last.enable(region, offset)?;
+            }
+
+            if row < NUM_START_ROWS {
+                self.q_start.enable(region, offset)?;
+                // The start rows hold the initial hash value as the words `t`
+                // of the previous rounds
+                region.assign_fixed(
+                    || format!("assign iv {}", offset),
+                    self.iv,
+                    offset,
+                    || Ok(F::from(iv[row] as u64)),
+                )?;
+            } else if row < NUM_START_ROWS + NUM_ROUNDS {
+                let round = row - NUM_START_ROWS;
+                self.q_round.enable(region, offset)?;
+                self.q_groups[round / NUM_ROUNDS_PER_GROUP].enable(region, offset)?;
+                self.q_left_rotations[LEFT_ROTATIONS[round] - MIN_ROTATION]
+                    .enable(region, offset)?;
+                self.q_right_rotations[RIGHT_ROTATIONS[round] - MIN_ROTATION]
+                    .enable(region, offset)?;
+                if round < NUM_WORDS_TO_ABSORB {
+                    self.q_input.enable(region, offset)?;
+                    if round >= NUM_WORDS_TO_ABSORB - 2 {
+                        self.q_length.enable(region, offset)?;
+                    }
+                    if round == NUM_WORDS_TO_ABSORB - 1 {
+                        self.q_input_last.enable(region, offset)?;
+                    }
+                }
+            } else {
+                self.q_ends[row - NUM_START_ROWS - NUM_ROUNDS].enable(region, offset)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use super::{gate_helpers::*, layout::LayoutConfig};
+use crate::common::*;
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::Region,
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression},
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+/// The number of bytes of a word.
+pub const NUM_BYTES_PER_WORD: usize = WORD_SIZE / 8;
+
+/// The absorption of the input into the little-endian words `x` of the input
+/// rows, where the bytes of the input are followed by the padding bytes:
+/// `0x80`, zeros, and the little-endian length of the input in bits in the
+/// last 8 bytes of the last block.
+/// The flags and the RLC of the input bytes and the length of the input are
+/// carried over the other rows.
+#[derive(Clone, Debug)]
+pub struct PaddingConfig<F> {
+    pub is_paddings: [Column<Advice>; NUM_BYTES_PER_WORD],
+    pub data_rlcs: [Column<Advice>; NUM_BYTES_PER_WORD],
+    pub length: Column<Advice>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> PaddingConfig<F> {
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        r: F,
+        layout: &LayoutConfig<F>,
+        x: [Column<Advice>; WORD_SIZE],
+        is_final: Column<Advice>,
+        is_new_hash: Column<Advice>,
+    ) -> Self {
+        let is_paddings = [(); NUM_BYTES_PER_WORD].map(|_| meta.advice_column());
+        let data_rlcs = [(); NUM_BYTES_PER_WORD].map(|_| meta.advice_column());
+        let length = meta.advice_column();
+        let last = NUM_BYTES_PER_WORD - 1;
+
+        meta.create_gate("ripemd160 input", |meta| {
+            let q_input = meta.query_selector(layout.q_input);
+            let q_length = meta.query_selector(layout.q_length);
+            let is_final = meta.query_advice(is_final, Rotation::cur());
+            let bytes = word_to_bytes(&query_word(meta, &x, 0));
+            let is_padding_prev_row = meta.query_advice(is_paddings[last], Rotation::prev());
+            let data_rlc_prev_row = meta.query_advice(data_rlcs[last], Rotation::prev());
+            let length_prev = meta.query_advice(length, Rotation::prev());
+            let length = meta.query_advice(length, Rotation::cur());
+            let is_paddings = is_paddings.map(|column| meta.query_advice(column, Rotation::cur()));
+            let data_rlcs = data_rlcs.map(|column| meta.query_advice(column, Rotation::cur()));
+
+            let mut constraints = Vec::new();
+            for idx in 0..NUM_BYTES_PER_WORD {
+                let (is_padding_prev, data_rlc_prev) = if idx == 0 {
+                    (is_padding_prev_row.clone(), data_rlc_prev_row.clone())
+                } else {
+                    (is_paddings[idx - 1].clone(), data_rlcs[idx - 1].clone())
+                };
+                let is_padding = is_paddings[idx].clone();
+
+                constraints.push((
+                    "is_padding is boolean",
+                    is_padding.clone() * (constant(1) - is_padding.clone()),
+                ));
+                constraints.push((
+                    "is_padding can only go from 0 to 1",
+                    is_padding_prev.clone() * (constant(1) - is_padding.clone()),
+                ));
+                // The last 8 bytes of the last block hold the length instead
+                constraints.push((
+                    "padding byte = is_first_padding ? 0x80 : 0",
+                    (constant(1) - q_length.clone() * is_final.clone())
+                        * is_padding.clone()
+                        * (bytes[idx].clone() - constant(0x80) * (constant(1) - is_padding_prev)),
+                ));
+                constraints.push((
+                    "data_rlc = is_padding ? data_rlc_prev : data_rlc_prev * r + byte",
+                    data_rlcs[idx].clone()
+                        - select(
+                            is_padding,
+                            data_rlc_prev.clone(),
+                            data_rlc_prev * Expression::Constant(r) + bytes[idx].clone(),
+                        ),
+                ));
+            }
+            constraints.push((
+                "length = length_prev + number of input bytes",
+                length
+                    - length_prev
+                    - is_paddings.iter().fold(constant(0), |acc, is_padding| {
+                        acc + constant(1) - is_padding.clone()
+                    }),
+            ));
+            // Padding needs to fit into the last block, so the blocks before
+            // can only start the padding in their last 8 bytes
+            constraints.push((
+                "no padding before the length when not final",
+                (constant(1) - is_final) * (constant(1) - q_length) * is_paddings[last].clone(),
+            ));
+
+            constraints
+                .into_iter()
+                .map(move |(name, constraint)| (name, q_input.clone() * constraint))
+        });
+
+        meta.create_gate("ripemd160 length", |meta| {
+            let q_input_last = meta.query_selector(layout.q_input_last);
+            let is_final = meta.query_advice(is_final, Rotation::cur());
+            let is_padding_length_start = meta.query_advice(is_paddings[0], Rotation::prev());
+            let length = meta.query_advice(length, Rotation::cur());
+            let length_low = compose(&query_word(meta, &x, -1));
+            let length_high = compose(&query_word(meta, &x, 0));
+            vec![
+                (
+                    "the last 8 bytes are padding when final",
+                    q_input_last.clone()
+                        * is_final.clone()
+                        * (constant(1) - is_padding_length_start),
+                ),
+                (
+                    "the last 8 bytes are the length in bits when final",
+                    q_input_last
+                        * is_final
+                        * (length_high * constant(1 << WORD_SIZE) + length_low
+                            - length * constant(8)),
+                ),
+            ]
+        });
+
+        meta.create_gate("ripemd160 carry over", |meta| {
+            // The rows other than the input rows carry over the values of the
+            // previous row, which are reset at the start of a hash
+            let q_carry_over =
+                meta.query_selector(layout.q_block) - meta.query_selector(layout.q_input);
+            let is_reset = meta.query_selector(layout.q_block_start)
+                * meta.query_advice(is_new_hash, Rotation::cur());
+            [is_paddings[last], data_rlcs[last], length]
+                .iter()
+                .map(|column| {
+                    let value = meta.query_advice(*column, Rotation::cur());
+                    let value_prev = meta.query_advice(*column, Rotation::prev());
+                    (
+                        "value = is_reset ? 0 : value_prev",
+                        q_carry_over.clone()
+                            * (value - (constant(1) - is_reset.clone()) * value_prev),
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+
+        Self {
+            is_paddings,
+            data_rlcs,
+            length,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn assign_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        is_paddings: [bool; NUM_BYTES_PER_WORD],
+        data_rlcs: [F; NUM_BYTES_PER_WORD],
+        length: u64,
+    ) -> Result<(), Error> {
+        for (idx, (is_padding, data_rlc)) in is_paddings.iter().zip(data_rlcs.iter()).enumerate() {
+            region.assign_advice(
+                || format!("assign is_padding {} {}", idx, offset),
+                self.is_paddings[idx],
+                offset,
+                || Ok(F::from(*is_padding as u64)),
+            )?;
+            region.assign_advice(
+                || format!("assign data_rlc {} {}", idx, offset),
+                self.data_rlcs[idx],
+                offset,
+                || Ok(*data_rlc),
+            )?;
+        }
+        region.assign_advice(
+            || format!("assign length {}", offset),
+            self.length,
+            offset,
+            || Ok(F::from(length)),
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,11 @@
+//! RIPEMD-160 circuit, which proves the digests of the inputs of the
+//! RIPEMD-160 precompile and exposes them in a lookup table.
+
+pub mod arith_helpers;
+pub mod circuit;
+pub mod common;
+pub mod gates;
+// We build plain module for the purpose of reviewing the circuit
+pub mod plain;
+// We build arith module to get the witness of the circuit
+pub mod ripemd160_arith;
@@ -0,0 +1,162 @@
+use crate::common::*;
+use std::convert::TryInto;
+
+pub struct Ripemd160 {
+    state: [u32; NUM_STATE_WORDS],
+    buffer: Vec<u8>,
+    length: u64,
+}
+
+impl Default for Ripemd160 {
+    fn default() -> Self {
+        Self {
+            state: IV,
+            buffer: Vec::with_capacity(BLOCK_SIZE),
+            length: 0,
+        }
+    }
+}
+
+impl Ripemd160 {
+    pub fn update(&mut self, input: &[u8]) {
+        self.length += input.len() as u64;
+        self.buffer.extend_from_slice(input);
+
+        let num_blocks = self.buffer.len() / BLOCK_SIZE;
+        for block in self.buffer.chunks_exact(BLOCK_SIZE).take(num_blocks) {
+            Ripemd160::compress(&mut self.state, block);
+        }
+        self.buffer.drain(..num_blocks * BLOCK_SIZE);
+    }
+
+    /// Returns ripemd160 hash based on current state
+    pub fn digest(&mut self) -> Vec<u8> {
+        // Append the bit 1, the bits 0 and the length in bits, so that the
+        // padded message is a multiple of the block size
+        let mut padding = vec![0x80];
+        padding.resize(
+            (BLOCK_SIZE * 2 - 8 - 1 - self.buffer.len()) % BLOCK_SIZE + 1,
+            0,
+        );
+        padding.extend_from_slice(&(self.length * 8).to_le_bytes());
+        self.buffer.extend_from_slice(&padding);
+
+        for block in self.buffer.chunks_exact(BLOCK_SIZE) {
+            Ripemd160::compress(&mut self.state, block);
+        }
+        self.buffer.clear();
+
+        self.state
+            .iter()
+            .flat_map(|word| word.to_le_bytes().to_vec())
+            .collect()
+    }
+
+    pub fn compress(state: &mut [u32; NUM_STATE_WORDS], block: &[u8]) {
+        let mut x = [0u32; NUM_WORDS_TO_ABSORB];
+        for (idx, word) in block.chunks_exact(4).enumerate() {
+            x[idx] = u32::from_le_bytes(word.try_into().unwrap());
+        }
+
+        let [mut al, mut bl, mut cl, mut dl, mut el] = *state;
+        let [mut ar, mut br, mut cr, mut dr, mut er] = *state;
+        for j in 0..NUM_ROUNDS {
+            let group = j / NUM_ROUNDS_PER_GROUP;
+
+            let t = al
+                .wrapping_add(Ripemd160::f(group, bl, cl, dl))
+                .wrapping_add(x[LEFT_WORDS[j]])
+                .wrapping_add(LEFT_CONSTANTS[group])
+                .rotate_left(LEFT_ROTATIONS[j] as u32)
+                .wrapping_add(el);
+            al = el;
+            el = dl;
+            dl = cl.rotate_left(10);
+            cl = bl;
+            bl = t;
+
+            let t = ar
+                .wrapping_add(Ripemd160::f(NUM_GROUPS - 1 - group, br, cr, dr))
+                .wrapping_add(x[RIGHT_WORDS[j]])
+                .wrapping_add(RIGHT_CONSTANTS[group])
+                .rotate_left(RIGHT_ROTATIONS[j] as u32)
+                .wrapping_add(er);
+            ar = er;
+            er = dr;
+            dr = cr.rotate_left(10);
+            cr = br;
+            br = t;
+        }
+
+        let [h0, h1, h2, h3, h4] = *state;
+        *state = [
+            h1.wrapping_add(cl).wrapping_add(dr),
+            h2.wrapping_add(dl).wrapping_add(er),
+            h3.wrapping_add(el).wrapping_add(ar),
+            h4.wrapping_add(al).wrapping_add(br),
+            h0.wrapping_add(bl).wrapping_add(cr),
+        ];
+    }
+
+    /// The boolean function of a group of rounds
+    fn f(group: usize, x: u32, y: u32, z: u32) -> u32 {
+        match group {
+            0 => x ^ y ^ z,
+            1 => (x & y) | (!x & z),
+            2 => (x | !y) ^ z,
+            3 => (x & z) | (y & !z),
+            _ => x ^ (y | !z),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ripemd160(input: &[u8]) -> Vec<u8> {
+        let mut hasher = Ripemd160::default();
+        hasher.update(input);
+        hasher.digest()
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn ripemd160_empty() {
+        assert_eq!(
+            hex(&ripemd160(&[])),
+            "9c1185a5c5e9fc54612808977ee8f548b2258d31"
+        );
+    }
+
+    #[test]
+    fn ripemd160_abc() {
+        assert_eq!(
+            hex(&ripemd160(b"abc")),
+            "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"
+        );
+    }
+
+    #[test]
+    fn ripemd160_two_blocks() {
+        assert_eq!(
+            hex(&ripemd160(
+                b"12345678901234567890123456789012345678901234567890123456789012345678901234567890"
+            )),
+            "9b752e45573d4b39f4dbd3323cab82bf63326bfb"
+        );
+    }
+
+    #[test]
+    fn ripemd160_update_in_parts() {
+        let input = [0xab; 200];
+        let mut hasher = Ripemd160::default();
+        hasher.update(&input[..10]);
+        hasher.update(&input[10..150]);
+        hasher.update(&input[150..]);
+        assert_eq!(hasher.digest(), ripemd160(&input));
+    }
+}
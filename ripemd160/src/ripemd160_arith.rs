@@ -0,0 +1,150 @@
+use crate::arith_helpers::*;
+use crate::common::*;
+use std::convert::TryInto;
+
+/// Witness of a round of one of the lines of the compression function, where
+/// `x` is the word of the message block selected by the round, `u` is the sum
+/// `a + f(b, c, d) + x + k` and `t` is the new word `rol(u, s) + e`.  The
+/// carries are the multiples of 2^32 dropped by the modular additions.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LineRoundTrace {
+    pub x: u32,
+    pub u: u32,
+    pub t: u32,
+    pub u_carry: u64,
+    pub t_carry: u64,
+}
+
+/// Witness of a round of both lines of the compression function.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RoundTrace {
+    pub left: LineRoundTrace,
+    pub right: LineRoundTrace,
+}
+
+/// Witness of the compression of a block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockTrace {
+    /// The words of the message block
+    pub words: [u32; NUM_WORDS_TO_ABSORB],
+    pub rounds: Vec<RoundTrace>,
+    /// The hash value after the block is absorbed
+    pub state: [u32; NUM_STATE_WORDS],
+    /// The carries of the additions of the hash value and the states of the
+    /// two lines
+    pub state_carries: [u64; NUM_STATE_WORDS],
+}
+
+#[derive(Default)]
+pub struct Ripemd160Arith {}
+
+impl Ripemd160Arith {
+    /// Compress `block` into the hash value `state`, keeping the witness of
+    /// every round.
+    pub fn compress(state: &[u32; NUM_STATE_WORDS], block: &[u8]) -> BlockTrace {
+        let mut words = [0; NUM_WORDS_TO_ABSORB];
+        for (idx, word) in block.chunks_exact(4).enumerate() {
+            words[idx] = u32::from_le_bytes(word.try_into().unwrap());
+        }
+
+        let mut left = *state;
+        let mut right = *state;
+        let rounds = (0..NUM_ROUNDS)
+            .map(|j| {
+                let group = j / NUM_ROUNDS_PER_GROUP;
+                RoundTrace {
+                    left: Ripemd160Arith::round(
+                        &mut left,
+                        group,
+                        words[LEFT_WORDS[j]],
+                        LEFT_CONSTANTS[group],
+                        LEFT_ROTATIONS[j],
+                    ),
+                    right: Ripemd160Arith::round(
+                        &mut right,
+                        NUM_GROUPS - 1 - group,
+                        words[RIGHT_WORDS[j]],
+                        RIGHT_CONSTANTS[group],
+                        RIGHT_ROTATIONS[j],
+                    ),
+                }
+            })
+            .collect();
+
+        let [h0, h1, h2, h3, h4] = *state;
+        let [al, bl, cl, dl, el] = left;
+        let [ar, br, cr, dr, er] = right;
+        let mut new_state = [0; NUM_STATE_WORDS];
+        let mut state_carries = [0; NUM_STATE_WORDS];
+        for (idx, [x, y, z]) in [
+            [h1, cl, dr],
+            [h2, dl, er],
+            [h3, el, ar],
+            [h4, al, br],
+            [h0, bl, cr],
+        ]
+        .iter()
+        .enumerate()
+        {
+            let sum = *x as u64 + *y as u64 + *z as u64;
+            new_state[idx] = sum as u32;
+            state_carries[idx] = sum >> 32;
+        }
+
+        BlockTrace {
+            words,
+            rounds,
+            state: new_state,
+            state_carries,
+        }
+    }
+
+    /// Apply a round to the state `[a, b, c, d, e]` of a line, where `group`
+    /// chooses the boolean function.
+    fn round(
+        state: &mut [u32; NUM_STATE_WORDS],
+        group: usize,
+        x: u32,
+        k: u32,
+        s: usize,
+    ) -> LineRoundTrace {
+        let [a, b, c, d, e] = *state;
+        let u = a as u64 + f(group, b, c, d) as u64 + x as u64 + k as u64;
+        let t = (u as u32).rotate_left(s as u32) as u64 + e as u64;
+        *state = [e, t as u32, b, c.rotate_left(STATE_ROTATION as u32), d];
+
+        LineRoundTrace {
+            x,
+            u: u as u32,
+            t: t as u32,
+            u_carry: u >> 32,
+            t_carry: t >> 32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plain::Ripemd160;
+
+    #[test]
+    fn compress_matches_plain() {
+        let (padded, _) = pad(&[0x5a; 100]);
+        let mut state = IV;
+        let mut plain_state = IV;
+        for block in padded.chunks_exact(BLOCK_SIZE) {
+            let trace = Ripemd160Arith::compress(&state, block);
+            assert_eq!(trace.rounds.len(), NUM_ROUNDS);
+            assert!(trace.rounds.iter().all(|round| {
+                [&round.left, &round.right]
+                    .iter()
+                    .all(|line| line.u_carry < 4 && line.t_carry < 2)
+            }));
+            assert!(trace.state_carries.iter().all(|carry| *carry < 4));
+            state = trace.state;
+            Ripemd160::compress(&mut plain_state, block);
+            assert_eq!(state, plain_state);
+        }
+    }
+}
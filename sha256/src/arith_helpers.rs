@@ -0,0 +1,82 @@
+use crate::common::*;
+
+/// Returns the bits of `value`, least significant first.
+pub fn into_bits(value: u32) -> [u8; WORD_SIZE] {
+    let mut bits = [0; WORD_SIZE];
+    for (idx, bit) in bits.iter_mut().enumerate() {
+        *bit = ((value >> idx) & 1) as u8;
+    }
+    bits
+}
+
+pub fn ch(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) ^ (!x & z)
+}
+
+pub fn maj(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) ^ (x & z) ^ (y & z)
+}
+
+pub fn big_sigma0(x: u32) -> u32 {
+    BIG_SIGMA0_ROTATIONS
+        .iter()
+        .fold(0, |acc, n| acc ^ x.rotate_right(*n as u32))
+}
+
+pub fn big_sigma1(x: u32) -> u32 {
+    BIG_SIGMA1_ROTATIONS
+        .iter()
+        .fold(0, |acc, n| acc ^ x.rotate_right(*n as u32))
+}
+
+pub fn small_sigma0(x: u32) -> u32 {
+    let [r0, r1, s] = SMALL_SIGMA0_ROTATIONS;
+    x.rotate_right(r0 as u32) ^ x.rotate_right(r1 as u32) ^ (x >> s)
+}
+
+pub fn small_sigma1(x: u32) -> u32 {
+    let [r0, r1, s] = SMALL_SIGMA1_ROTATIONS;
+    x.rotate_right(r0 as u32) ^ x.rotate_right(r1 as u32) ^ (x >> s)
+}
+
+/// Returns `input` padded to a multiple of the block size, followed by the
+/// padding flags of its bytes.
+pub fn pad(input: &[u8]) -> (Vec<u8>, Vec<bool>) {
+    let mut padded = input.to_vec();
+    padded.push(0x80);
+    while padded.len() % BLOCK_SIZE != BLOCK_SIZE - 8 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&(input.len() as u64 * 8).to_be_bytes());
+
+    let is_paddings = (0..padded.len()).map(|idx| idx >= input.len()).collect();
+    (padded, is_paddings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryInto;
+
+    #[test]
+    fn padding_lengths() {
+        for (length, padded_length) in [(0, 64), (55, 64), (56, 128), (64, 128), (120, 192)] {
+            let (padded, is_paddings) = pad(&vec![0xff; length]);
+            assert_eq!(padded.len(), padded_length);
+            assert_eq!(padded[length], 0x80);
+            assert_eq!(is_paddings.iter().filter(|p| !**p).count(), length);
+            assert_eq!(
+                u64::from_be_bytes(padded[padded_length - 8..].try_into().unwrap()),
+                length as u64 * 8
+            );
+        }
+    }
+
+    #[test]
+    fn bits() {
+        let bits = into_bits(0x8000_0005);
+        assert_eq!(&bits[..4], &[1, 0, 1, 0]);
+        assert_eq!(bits[31], 1);
+        assert_eq!(bits.iter().map(|bit| *bit as u32).sum::<u32>(), 3);
+    }
+}
@@ -0,0 +1,60 @@
+//! Constants of the SHA-256 hash function, which can be found in [FIPS 180-4](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf),
+//! and the layout of a block in the circuit.
+
+/// The number of bits of a word.
+pub const WORD_SIZE: usize = 32;
+
+/// The number of bytes of a message block.
+pub const BLOCK_SIZE: usize = 64;
+
+/// The number of words of a message block.
+pub const NUM_WORDS_TO_ABSORB: usize = 16;
+
+/// The number of bytes of the digest.
+pub const DIGEST_SIZE: usize = 32;
+
+/// The number of rounds of the compression function.
+pub const NUM_ROUNDS: usize = 64;
+
+/// The initial hash value (section 5.3.3)
+pub static IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// The round constants (section 4.2.2)
+pub static ROUND_CONSTANTS: [u32; NUM_ROUNDS] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// The right rotations of Σ0 (section 4.1.2)
+pub const BIG_SIGMA0_ROTATIONS: [usize; 3] = [2, 13, 22];
+
+/// The right rotations of Σ1 (section 4.1.2)
+pub const BIG_SIGMA1_ROTATIONS: [usize; 3] = [6, 11, 25];
+
+/// The two right rotations and the right shift of σ0 (section 4.1.2)
+pub const SMALL_SIGMA0_ROTATIONS: [usize; 3] = [7, 18, 3];
+
+/// The two right rotations and the right shift of σ1 (section 4.1.2)
+pub const SMALL_SIGMA1_ROTATIONS: [usize; 3] = [17, 19, 10];
+
+/// The number of rows at the start of a block, which hold the words `a` and
+/// `e` of the initial state of the compression function, so that `a`, `b`,
+/// `c`, `d` (and `e`, `f`, `g`, `h`) can be queried at the rotations -1 to -4
+/// of the first round.
+pub const NUM_START_ROWS: usize = 4;
+
+/// The number of rows at the end of a block, which hold the words `a` and
+/// `e` of the hash value after the block is absorbed.
+pub const NUM_END_ROWS: usize = 4;
+
+/// The number of rows of a block, where each round of the compression
+/// function takes one row.
+pub const ROWS_PER_BLOCK: usize = NUM_START_ROWS + NUM_ROUNDS + NUM_END_ROWS;
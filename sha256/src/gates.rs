@@ -0,0 +1,4 @@
+pub mod compression;
+pub mod gate_helpers;
+pub mod layout;
+pub mod padding;
@@ -0,0 +1,228 @@
+use super::{gate_helpers::*, layout::LayoutConfig};
+use crate::common::*;
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::Region,
+    plonk::{Advice, Column, ConstraintSystem, Error},
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+/// The number of bits of the carry of the additions of a round, which add up
+/// to 7 words.
+pub const NUM_BITS_ROUND_CARRY: usize = 3;
+
+/// The number of bits of the carry of the message schedule, which adds up 4
+/// words.
+pub const NUM_BITS_SCHEDULE_CARRY: usize = 2;
+
+/// The compression function, where each row holds the bits of the words `a`
+/// and `e` of the state after a round, and of the word `w` of the message
+/// schedule used by the round.  The other words of the state are the previous
+/// values of `a` and `e`, so they are queried at the rotations -1 to -4.
+#[derive(Clone, Debug)]
+pub struct CompressionConfig<F> {
+    pub a: [Column<Advice>; WORD_SIZE],
+    pub e: [Column<Advice>; WORD_SIZE],
+    pub w: [Column<Advice>; WORD_SIZE],
+    pub a_carry: [Column<Advice>; NUM_BITS_ROUND_CARRY],
+    pub e_carry: [Column<Advice>; NUM_BITS_ROUND_CARRY],
+    pub w_carry: [Column<Advice>; NUM_BITS_SCHEDULE_CARRY],
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> CompressionConfig<F> {
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        layout: &LayoutConfig<F>,
+        is_new_hash: Column<Advice>,
+    ) -> Self {
+        let a = [(); WORD_SIZE].map(|_| meta.advice_column());
+        let e = [(); WORD_SIZE].map(|_| meta.advice_column());
+        let w = [(); WORD_SIZE].map(|_| meta.advice_column());
+        let a_carry = [(); NUM_BITS_ROUND_CARRY].map(|_| meta.advice_column());
+        let e_carry = [(); NUM_BITS_ROUND_CARRY].map(|_| meta.advice_column());
+        let w_carry = [(); NUM_BITS_SCHEDULE_CARRY].map(|_| meta.advice_column());
+        let word_modulus = constant::<F>(1 << WORD_SIZE);
+
+        meta.create_gate("sha256 boolean", |meta| {
+            let q_block = meta.query_selector(layout.q_block);
+            a.iter()
+                .chain(e.iter())
+                .chain(w.iter())
+                .chain(a_carry.iter())
+                .chain(e_carry.iter())
+                .chain(w_carry.iter())
+                .map(|column| {
+                    let bit = meta.query_advice(*column, Rotation::cur());
+                    (
+                        "bit is boolean",
+                        q_block.clone() * bit.clone() * (constant(1) - bit),
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("sha256 start", |meta| {
+            let q_start = meta.query_selector(layout.q_start);
+            let is_new_hash = meta.query_advice(is_new_hash, Rotation::cur());
+            let iv_a = meta.query_fixed(layout.iv_a, Rotation::cur());
+            let iv_e = meta.query_fixed(layout.iv_e, Rotation::cur());
+            // The hash value after the previous block is in its end rows
+            let prev_a = compose(&query_word(meta, &a, -(NUM_END_ROWS as i32)));
+            let prev_e = compose(&query_word(meta, &e, -(NUM_END_ROWS as i32)));
+            let a_value = compose(&query_word(meta, &a, 0));
+            let e_value = compose(&query_word(meta, &e, 0));
+            vec![
+                (
+                    "a = is_new_hash ? iv : previous hash value",
+                    q_start.clone() * (a_value - select(is_new_hash.clone(), iv_a, prev_a)),
+                ),
+                (
+                    "e = is_new_hash ? iv : previous hash value",
+                    q_start * (e_value - select(is_new_hash, iv_e, prev_e)),
+                ),
+            ]
+        });
+
+        meta.create_gate("sha256 round", |meta| {
+            let q_round = meta.query_selector(layout.q_round);
+            let [prev_a, prev_b, prev_c, prev_d] =
+                [-1, -2, -3, -4].map(|rotation| query_word(meta, &a, rotation));
+            let [prev_e, prev_f, prev_g, prev_h] =
+                [-1, -2, -3, -4].map(|rotation| query_word(meta, &e, rotation));
+            let new_a = compose(&query_word(meta, &a, 0));
+            let new_e = compose(&query_word(meta, &e, 0));
+            let a_carry = compose(&query_word(meta, &a_carry, 0));
+            let e_carry = compose(&query_word(meta, &e_carry, 0));
+            let w = compose(&query_word(meta, &w, 0));
+            let round_cst = meta.query_fixed(layout.round_cst, Rotation::cur());
+
+            let [s0, s1, s2] = BIG_SIGMA0_ROTATIONS;
+            let big_sigma0 = compose(&xor3(
+                &rotate_right(&prev_a, s0),
+                &rotate_right(&prev_a, s1),
+                &rotate_right(&prev_a, s2),
+            ));
+            let [s0, s1, s2] = BIG_SIGMA1_ROTATIONS;
+            let big_sigma1 = compose(&xor3(
+                &rotate_right(&prev_e, s0),
+                &rotate_right(&prev_e, s1),
+                &rotate_right(&prev_e, s2),
+            ));
+            let ch = compose(&ch(&prev_e, &prev_f, &prev_g));
+            let maj = compose(&maj(&prev_a, &prev_b, &prev_c));
+
+            let t1 = compose(&prev_h) + big_sigma1 + ch + round_cst + w;
+            let t2 = big_sigma0 + maj;
+            vec![
+                (
+                    "new_e = d + t1",
+                    q_round.clone()
+                        * (new_e + e_carry * word_modulus.clone() - compose(&prev_d) - t1.clone()),
+                ),
+                (
+                    "new_a = t1 + t2",
+                    q_round * (new_a + a_carry * word_modulus.clone() - t1 - t2),
+                ),
+            ]
+        });
+
+        meta.create_gate("sha256 message schedule", |meta| {
+            let q_extend = meta.query_selector(layout.q_extend);
+            let [w_2, w_7, w_15, w_16] =
+                [-2, -7, -15, -16].map(|rotation| query_word(meta, &w, rotation));
+            let new_w = compose(&query_word(meta, &w, 0));
+            let w_carry = compose(&query_word(meta, &w_carry, 0));
+
+            let [s0, s1, s2] = SMALL_SIGMA0_ROTATIONS;
+            let small_sigma0 = compose(&xor3(
+                &rotate_right(&w_15, s0),
+                &rotate_right(&w_15, s1),
+                &shift_right(&w_15, s2),
+            ));
+            let [s0, s1, s2] = SMALL_SIGMA1_ROTATIONS;
+            let small_sigma1 = compose(&xor3(
+                &rotate_right(&w_2, s0),
+                &rotate_right(&w_2, s1),
+                &shift_right(&w_2, s2),
+            ));
+
+            vec![(
+                "w = σ1(w[-2]) + w[-7] + σ0(w[-15]) + w[-16]",
+                q_extend
+                    * (new_w + w_carry * word_modulus.clone()
+                        - small_sigma1
+                        - compose(&w_7)
+                        - small_sigma0
+                        - compose(&w_16)),
+            )]
+        });
+
+        meta.create_gate("sha256 end", |meta| {
+            let q_end = meta.query_selector(layout.q_end);
+            // The end row `i` adds up the start row `i` and the round row
+            // `NUM_ROUNDS - NUM_END_ROWS + i`, which hold the same word of the
+            // hash value and of the final state.
+            let initial_rotation = -((NUM_START_ROWS + NUM_ROUNDS) as i32);
+            let state_rotation = -(NUM_END_ROWS as i32);
+            [(a, a_carry), (e, e_carry)]
+                .iter()
+                .map(|(word, carry)| {
+                    let initial = compose(&query_word(meta, word, initial_rotation));
+                    let state = compose(&query_word(meta, word, state_rotation));
+                    let new = compose(&query_word(meta, word, 0));
+                    let carry = compose(&query_word(meta, carry, 0));
+                    (
+                        "new hash value = hash value + state",
+                        q_end.clone() * (new + carry * word_modulus.clone() - initial - state),
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+
+        Self {
+            a,
+            e,
+            w,
+            a_carry,
+            e_carry,
+            w_carry,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assign the bits of the words and of the carries of a row.
+    #[allow(clippy::too_many_arguments)]
+    pub fn assign_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        a: u32,
+        e: u32,
+        w: u32,
+        a_carry: u64,
+        e_carry: u64,
+        w_carry: u64,
+    ) -> Result<(), Error> {
+        for (name, columns, value) in [
+            ("a", &self.a[..], a as u64),
+            ("e", &self.e[..], e as u64),
+            ("w", &self.w[..], w as u64),
+            ("a_carry", &self.a_carry[..], a_carry),
+            ("e_carry", &self.e_carry[..], e_carry),
+            ("w_carry", &self.w_carry[..], w_carry),
+        ] {
+            for (idx, column) in columns.iter().enumerate() {
+                region.assign_advice(
+                    || format!("assign {} bit {} {}", name, idx, offset),
+                    *column,
+                    offset,
+                    || Ok(F::from((value >> idx) & 1)),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use crate::common::WORD_SIZE;
+use eth_types::Field;
+use halo2_proofs::{
+    plonk::{Advice, Column, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+pub fn constant<F: Field>(value: u64) -> Expression<F> {
+    Expression::Constant(F::from(value))
+}
+
+/// Query the bits of a word, least significant first, at `rotation`.
+pub fn query_word<F: Field>(
+    meta: &mut VirtualCells<'_, F>,
+    columns: &[Column<Advice>],
+    rotation: i32,
+) -> Vec<Expression<F>> {
+    columns
+        .iter()
+        .map(|column| meta.query_advice(*column, Rotation(rotation)))
+        .collect()
+}
+
+/// Returns the value of `bits`, least significant first.
+pub fn compose<F: Field>(bits: &[Expression<F>]) -> Expression<F> {
+    bits.iter()
+        .enumerate()
+        .fold(constant(0), |acc, (idx, bit)| {
+            acc + bit.clone() * constant(1 << idx)
+        })
+}
+
+/// Returns the big-endian bytes of the word `bits`.
+pub fn word_to_bytes<F: Field>(bits: &[Expression<F>]) -> Vec<Expression<F>> {
+    bits.chunks(8).rev().map(compose).collect()
+}
+
+/// Returns `when_true` if `condition` is 1, or `when_false` if it's 0.
+pub fn select<F: Field>(
+    condition: Expression<F>,
+    when_true: Expression<F>,
+    when_false: Expression<F>,
+) -> Expression<F> {
+    condition.clone() * when_true + (constant(1) - condition) * when_false
+}
+
+pub fn rotate_right<F: Field>(bits: &[Expression<F>], n: usize) -> Vec<Expression<F>> {
+    (0..WORD_SIZE)
+        .map(|idx| bits[(idx + n) % WORD_SIZE].clone())
+        .collect()
+}
+
+pub fn shift_right<F: Field>(bits: &[Expression<F>], n: usize) -> Vec<Expression<F>> {
+    (0..WORD_SIZE)
+        .map(|idx| bits.get(idx + n).cloned().unwrap_or_else(|| constant(0)))
+        .collect()
+}
+
+fn xor<F: Field>(x: Expression<F>, y: Expression<F>) -> Expression<F> {
+    x.clone() + y.clone() - constant(2) * x * y
+}
+
+/// Bitwise `x ^ y ^ z`
+pub fn xor3<F: Field>(
+    x: &[Expression<F>],
+    y: &[Expression<F>],
+    z: &[Expression<F>],
+) -> Vec<Expression<F>> {
+    (0..WORD_SIZE)
+        .map(|idx| xor(xor(x[idx].clone(), y[idx].clone()), z[idx].clone()))
+        .collect()
+}
+
+/// Bitwise `(x & y) ^ (!x & z)`, where either `y` or `z` is chosen by `x`.
+pub fn ch<F: Field>(
+    x: &[Expression<F>],
+    y: &[Expression<F>],
+    z: &[Expression<F>],
+) -> Vec<Expression<F>> {
+    (0..WORD_SIZE)
+        .map(|idx| select(x[idx].clone(), y[idx].clone(), z[idx].clone()))
+        .collect()
+}
+
+/// Bitwise `(x & y) ^ (x & z) ^ (y & z)`, which is the majority of the bits.
+pub fn maj<F: Field>(
+    x: &[Expression<F>],
+    y: &[Expression<F>],
+    z: &[Expression<F>],
+) -> Vec<Expression<F>> {
+    (0..WORD_SIZE)
+        .map(|idx| {
+            let (x, y, z) = (x[idx].clone(), y[idx].clone(), z[idx].clone());
+            x.clone() * y.clone() + x.clone() * z.clone() + y.clone() * z.clone()
+                - constant(2) * x * y * z
+        })
+        .collect()
+}
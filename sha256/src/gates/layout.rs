@@ -0,0 +1,129 @@
+use crate::common::*;
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::Region,
+    plonk::{Column, ConstraintSystem, Error, Fixed, Selector},
+};
+use std::marker::PhantomData;
+
+/// Selectors and fixed columns of the layout of the blocks, where a block
+/// takes [`ROWS_PER_BLOCK`] rows:
+///
+/// | rows        | content                                                   |
+/// |-------------|-----------------------------------------------------------|
+/// | `0..4`      | `a` and `e` of the hash value before the block            |
+/// | `4..20`     | rounds whose word `w` is read from the block              |
+/// | `20..68`    | rounds whose word `w` is computed by the message schedule |
+/// | `68..72`    | `a` and `e` of the hash value after the block             |
+#[derive(Clone, Debug)]
+pub struct LayoutConfig<F> {
+    /// Enabled on all the usable rows, including the ones after the blocks
+    pub q_enable: Selector,
+    /// Enabled on all the rows of the blocks
+    pub q_block: Selector,
+    /// Enabled on the first row of every block
+    pub q_block_start: Selector,
+    /// Enabled on the first row of the first block
+    pub q_first: Selector,
+    pub q_start: Selector,
+    pub q_round: Selector,
+    pub q_input: Selector,
+    /// Enabled on the last two input rows, which hold the length of the
+    /// message in the last block of a hash
+    pub q_length: Selector,
+    /// Enabled on the last input row
+    pub q_input_last: Selector,
+    pub q_extend: Selector,
+    pub q_end: Selector,
+    /// Enabled on the last row of every block
+    pub q_last: Selector,
+    pub round_cst: Column<Fixed>,
+    pub iv_a: Column<Fixed>,
+    pub iv_e: Column<Fixed>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> LayoutConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        // Complex selectors, since the gates add up and multiply some of them
+        Self {
+            q_enable: meta.complex_selector(),
+            q_block: meta.complex_selector(),
+            q_block_start: meta.complex_selector(),
+            q_first: meta.complex_selector(),
+            q_start: meta.complex_selector(),
+            q_round: meta.complex_selector(),
+            q_input: meta.complex_selector(),
+            q_length: meta.complex_selector(),
+            q_input_last: meta.complex_selector(),
+            q_extend: meta.complex_selector(),
+            q_end: meta.complex_selector(),
+            q_last: meta.complex_selector(),
+            round_cst: meta.fixed_column(),
+            iv_a: meta.fixed_column(),
+            iv_e: meta.fixed_column(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Enable the selectors of the block starting at `offset`.
+    pub fn assign_block(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        is_first: bool,
+    ) -> Result<(), Error> {
+        for row in 0..ROWS_PER_BLOCK {
+            let offset = offset + row;
+            self.q_enable.enable(region, offset)?;
+            self.q_block.enable(region, offset)?;
+            if row == 0 {
+                self.q_block_start.enable(region, offset)?;
+                if is_first {
+                    self.q_first.enable(region, offset)?;
+                }
+            }
+            if row == ROWS_PER_BLOCK - 1 {
+                self.q_last.enable(region, offset)?;
+            }
+
+            if row < NUM_START_ROWS {
+                self.q_start.enable(region, offset)?;
+                // The start rows hold `d`, `c`, `b`, `a` and `h`, `g`, `f`,
+                // `e` of the initial hash value
+                for (column, value) in [(self.iv_a, IV[3 - row]), (self.iv_e, IV[7 - row])] {
+                    region.assign_fixed(
+                        || format!("assign iv {}", offset),
+                        column,
+                        offset,
+                        || Ok(F::from(value as u64)),
+                    )?;
+                }
+            } else if row < NUM_START_ROWS + NUM_ROUNDS {
+                let round = row - NUM_START_ROWS;
+                self.q_round.enable(region, offset)?;
+                if round < NUM_WORDS_TO_ABSORB {
+                    self.q_input.enable(region, offset)?;
+                    if round >= NUM_WORDS_TO_ABSORB - 2 {
+                        self.q_length.enable(region, offset)?;
+                    }
+                    if round == NUM_WORDS_TO_ABSORB - 1 {
+                        self.q_input_last.enable(region, offset)?;
+                    }
+                } else {
+                    self.q_extend.enable(region, offset)?;
+                }
+                region.assign_fixed(
+                    || format!("assign round_cst {}", offset),
+                    self.round_cst,
+                    offset,
+                    || Ok(F::from(ROUND_CONSTANTS[round] as u64)),
+                )?;
+            } else {
+                self.q_end.enable(region, offset)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use crate::common::*;
+use std::convert::TryInto;
+
+pub struct Sha256 {
+    state: [u32; 8],
+    buffer: Vec<u8>,
+    length: u64,
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self {
+            state: IV,
+            buffer: Vec::with_capacity(BLOCK_SIZE),
+            length: 0,
+        }
+    }
+}
+
+impl Sha256 {
+    pub fn update(&mut self, input: &[u8]) {
+        self.length += input.len() as u64;
+        self.buffer.extend_from_slice(input);
+
+        let num_blocks = self.buffer.len() / BLOCK_SIZE;
+        for block in self.buffer.chunks_exact(BLOCK_SIZE).take(num_blocks) {
+            Sha256::compress(&mut self.state, block);
+        }
+        self.buffer.drain(..num_blocks * BLOCK_SIZE);
+    }
+
+    /// Returns sha256 hash based on current state
+    pub fn digest(&mut self) -> Vec<u8> {
+        // Append the bit 1, the bits 0 and the length in bits, so that the
+        // padded message is a multiple of the block size
+        let mut padding = vec![0x80];
+        padding.resize(
+            (BLOCK_SIZE * 2 - 8 - 1 - self.buffer.len()) % BLOCK_SIZE + 1,
+            0,
+        );
+        padding.extend_from_slice(&(self.length * 8).to_be_bytes());
+        self.buffer.extend_from_slice(&padding);
+
+        for block in self.buffer.chunks_exact(BLOCK_SIZE) {
+            Sha256::compress(&mut self.state, block);
+        }
+        self.buffer.clear();
+
+        self.state
+            .iter()
+            .flat_map(|word| word.to_be_bytes().to_vec())
+            .collect()
+    }
+
+    pub fn compress(state: &mut [u32; 8], block: &[u8]) {
+        let mut w = [0u32; NUM_ROUNDS];
+        for (t, word) in block.chunks_exact(4).enumerate() {
+            w[t] = u32::from_be_bytes(word.try_into().unwrap());
+        }
+        for t in NUM_WORDS_TO_ABSORB..NUM_ROUNDS {
+            w[t] = Sha256::small_sigma1(w[t - 2])
+                .wrapping_add(w[t - 7])
+                .wrapping_add(Sha256::small_sigma0(w[t - 15]))
+                .wrapping_add(w[t - 16]);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+        for t in 0..NUM_ROUNDS {
+            let t1 = h
+                .wrapping_add(Sha256::big_sigma1(e))
+                .wrapping_add(Sha256::ch(e, f, g))
+                .wrapping_add(ROUND_CONSTANTS[t])
+                .wrapping_add(w[t]);
+            let t2 = Sha256::big_sigma0(a).wrapping_add(Sha256::maj(a, b, c));
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+
+        for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+
+    fn ch(x: u32, y: u32, z: u32) -> u32 {
+        (x & y) ^ (!x & z)
+    }
+
+    fn maj(x: u32, y: u32, z: u32) -> u32 {
+        (x & y) ^ (x & z) ^ (y & z)
+    }
+
+    fn big_sigma0(x: u32) -> u32 {
+        x.rotate_right(2) ^ x.rotate_right(13) ^ x.rotate_right(22)
+    }
+
+    fn big_sigma1(x: u32) -> u32 {
+        x.rotate_right(6) ^ x.rotate_right(11) ^ x.rotate_right(25)
+    }
+
+    fn small_sigma0(x: u32) -> u32 {
+        x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
+    }
+
+    fn small_sigma1(x: u32) -> u32 {
+        x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sha256(input: &[u8]) -> Vec<u8> {
+        let mut hasher = Sha256::default();
+        hasher.update(input);
+        hasher.digest()
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn sha256_empty() {
+        assert_eq!(
+            hex(&sha256(&[])),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn sha256_abc() {
+        assert_eq!(
+            hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn sha256_two_blocks() {
+        assert_eq!(
+            hex(&sha256(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn sha256_update_in_parts() {
+        let input = [0xab; 200];
+        let mut hasher = Sha256::default();
+        hasher.update(&input[..10]);
+        hasher.update(&input[10..150]);
+        hasher.update(&input[150..]);
+        assert_eq!(hasher.digest(), sha256(&input));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::arith_helpers::*;
+use crate::common::*;
+use std::convert::TryInto;
+
+/// Witness of a round of the compression function, where `a` and `e` are the
+/// new words of the state and `w` is the word of the message schedule.  The
+/// carries are the multiples of 2^32 dropped by the modular additions.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RoundTrace {
+    pub w: u32,
+    pub a: u32,
+    pub e: u32,
+    pub w_carry: u64,
+    pub a_carry: u64,
+    pub e_carry: u64,
+}
+
+/// Witness of the compression of a block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockTrace {
+    pub rounds: Vec<RoundTrace>,
+    /// The hash value after the block is absorbed
+    pub state: [u32; 8],
+    /// The carries of the additions of the hash value and the state
+    pub state_carries: [u64; 8],
+}
+
+#[derive(Default)]
+pub struct Sha256Arith {}
+
+impl Sha256Arith {
+    /// Compress `block` into the hash value `state`, keeping the witness of
+    /// every round.
+    pub fn compress(state: &[u32; 8], block: &[u8]) -> BlockTrace {
+        let mut rounds: Vec<RoundTrace> = block
+            .chunks_exact(4)
+            .map(|word| RoundTrace {
+                w: u32::from_be_bytes(word.try_into().unwrap()),
+                ..Default::default()
+            })
+            .collect();
+        for t in NUM_WORDS_TO_ABSORB..NUM_ROUNDS {
+            let w = small_sigma1(rounds[t - 2].w) as u64
+                + rounds[t - 7].w as u64
+                + small_sigma0(rounds[t - 15].w) as u64
+                + rounds[t - 16].w as u64;
+            rounds.push(RoundTrace {
+                w: w as u32,
+                w_carry: w >> 32,
+                ..Default::default()
+            });
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+        for (t, round) in rounds.iter_mut().enumerate() {
+            let t1 = h as u64
+                + big_sigma1(e) as u64
+                + ch(e, f, g) as u64
+                + ROUND_CONSTANTS[t] as u64
+                + round.w as u64;
+            let t2 = big_sigma0(a) as u64 + maj(a, b, c) as u64;
+            let new_e = d as u64 + t1;
+            let new_a = t1 + t2;
+
+            round.a = new_a as u32;
+            round.e = new_e as u32;
+            round.a_carry = new_a >> 32;
+            round.e_carry = new_e >> 32;
+
+            h = g;
+            g = f;
+            f = e;
+            e = round.e;
+            d = c;
+            c = b;
+            b = a;
+            a = round.a;
+        }
+
+        let mut new_state = [0; 8];
+        let mut state_carries = [0; 8];
+        for (idx, value) in [a, b, c, d, e, f, g, h].iter().enumerate() {
+            let sum = state[idx] as u64 + *value as u64;
+            new_state[idx] = sum as u32;
+            state_carries[idx] = sum >> 32;
+        }
+
+        BlockTrace {
+            rounds,
+            state: new_state,
+            state_carries,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plain::Sha256;
+
+    #[test]
+    fn compress_matches_plain() {
+        let (padded, _) = pad(&[0x5a; 100]);
+        let mut state = IV;
+        let mut plain_state = IV;
+        for block in padded.chunks_exact(BLOCK_SIZE) {
+            let trace = Sha256Arith::compress(&state, block);
+            assert_eq!(trace.rounds.len(), NUM_ROUNDS);
+            assert!(trace
+                .rounds
+                .iter()
+                .all(|round| round.a_carry < 8 && round.e_carry < 8 && round.w_carry < 4));
+            state = trace.state;
+            Sha256::compress(&mut plain_state, block);
+            assert_eq!(state, plain_state);
+        }
+    }
+}
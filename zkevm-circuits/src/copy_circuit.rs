@@ -0,0 +1,6 @@
+//! The copy circuit implementation, which proves the copies of bytes between
+//! buffers done by the memory-copying opcodes and precompiles, with a row per
+//! copied byte looking up its source and destination.
+
+pub(crate) mod copy;
+pub(crate) mod param;
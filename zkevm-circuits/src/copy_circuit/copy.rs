@@ -0,0 +1,976 @@
+use super::param::COPY_TABLE_WIDTH;
+use crate::{
+    evm_circuit::{
+        table::{BytecodeFieldTag, LookupTable, RwTableTag, TxContextFieldTag},
+        util::{constraint_builder::BaseConstraintBuilder, not},
+        witness::{Block, CopyEvent},
+    },
+    util::Expr,
+};
+use bus_mapping::circuit_input_builder::{CopyDataType, NumberOrHash};
+use eth_types::{Field, ToWord};
+use halo2_proofs::{
+    circuit::{Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, VirtualCells},
+    poly::Rotation,
+};
+
+// The indices of the columns of the copy table
+const IS_FIRST: usize = 0;
+const SRC_ID: usize = 1;
+const SRC_TYPE: usize = 2;
+const DST_ID: usize = 3;
+const DST_TYPE: usize = 4;
+const SRC_ADDR: usize = 5;
+const SRC_ADDR_END: usize = 6;
+const DST_ADDR: usize = 7;
+const BYTES_LEFT: usize = 8;
+const RW_COUNTER: usize = 9;
+const RWC_INC_LEFT: usize = 10;
+const RLC_ACC: usize = 11;
+
+/// The row of a copied byte.
+#[derive(Clone, Debug)]
+struct CopyRow<F> {
+    table: [F; COPY_TABLE_WIDTH],
+    src_type: CopyDataType,
+    dst_type: CopyDataType,
+    byte: u8,
+    /// The RLC of the bytes of the copy up to this one
+    rlc: F,
+    /// Whether the byte is an opcode, when it's read from a bytecode
+    is_code: bool,
+}
+
+/// The copies of bytes from a source buffer, which is the memory of a call,
+/// the calldata of a transaction or a bytecode, into the memory of a call.
+/// Every copy takes a row per byte, where the addresses and the bytes left
+/// move along, and the byte is looked up in the source and the destination.
+/// The bytes read past the end of the source are 0.
+///
+/// Either the source or the destination can also be the RLC of the copied
+/// bytes, where the first byte has the highest power of the randomness, which
+/// is accumulated along the rows and is `rlc_acc` in every row of the copy.
+/// A copy into an RLC only reads its source, and a copy out of an RLC only
+/// writes its destination, with bytes which are only bound by the RLC.
+#[derive(Clone, Debug)]
+pub struct Config<F> {
+    minimum_rows: usize,
+    /// Enabled on all the usable rows
+    q_enable: Selector,
+    /// Enabled on the first row
+    q_first: Selector,
+    /// Enabled on the last usable row
+    q_last: Selector,
+    /// The copy table looked up by the EVM circuit, with a row per byte, of
+    /// which the first one of a copy has `is_first` set
+    copy_table: [Column<Advice>; COPY_TABLE_WIDTH],
+    byte: Column<Advice>,
+    is_code: Column<Advice>,
+    /// One-hot encoding of the type of the source, all 0 on padding rows
+    src_is_memory: Column<Advice>,
+    src_is_tx_calldata: Column<Advice>,
+    src_is_bytecode: Column<Advice>,
+    src_is_rlc_acc: Column<Advice>,
+    /// Whether the destination is an RLC, otherwise it's memory
+    dst_is_rlc_acc: Column<Advice>,
+    /// The RLC of the bytes of the copy up to the row, which is `rlc_acc` on
+    /// the last row of a copy from or into an RLC
+    rlc: Column<Advice>,
+    /// Whether the row is out of the copies
+    is_padding: Column<Advice>,
+    /// Whether the row is the last one of a copy, when `bytes_left == 1`
+    is_last: Column<Advice>,
+    bytes_left_inv: Column<Advice>,
+    /// Whether the source address reached the end of the source, after which
+    /// it doesn't move and the bytes are 0
+    is_out_of_bound: Column<Advice>,
+    src_addr_diff_inv: Column<Advice>,
+}
+
+impl<F: Field> Config<F> {
+    pub(crate) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        r: F,
+        tx_table: impl LookupTable<F, 4>,
+        rw_table: impl LookupTable<F, 10>,
+        bytecode_table: impl LookupTable<F, 5>,
+    ) -> Self {
+        // The selectors are combined with each other in the gates, which
+        // requires complex selectors
+        let q_enable = meta.complex_selector();
+        let q_first = meta.complex_selector();
+        let q_last = meta.complex_selector();
+        let copy_table = [(); COPY_TABLE_WIDTH].map(|_| meta.advice_column());
+        let byte = meta.advice_column();
+        let is_code = meta.advice_column();
+        let src_is_memory = meta.advice_column();
+        let src_is_tx_calldata = meta.advice_column();
+        let src_is_bytecode = meta.advice_column();
+        let src_is_rlc_acc = meta.advice_column();
+        let dst_is_rlc_acc = meta.advice_column();
+        let rlc = meta.advice_column();
+        let is_padding = meta.advice_column();
+        let is_last = meta.advice_column();
+        let bytes_left_inv = meta.advice_column();
+        let is_out_of_bound = meta.advice_column();
+        let src_addr_diff_inv = meta.advice_column();
+
+        // Whether the byte of a row is written into memory, which is the
+        // destination of the copies other than the ones into an RLC
+        let dst_is_memory = |meta: &mut VirtualCells<F>, rotation: Rotation| {
+            not::expr(meta.query_advice(is_padding, rotation))
+                - meta.query_advice(dst_is_rlc_acc, rotation)
+        };
+        // The number of rw counters taken by the byte of a row, which is read
+        // from a memory source before its end and written into a memory
+        // destination
+        let rwc_step = |meta: &mut VirtualCells<F>, rotation: Rotation| {
+            meta.query_advice(src_is_memory, rotation)
+                * not::expr(meta.query_advice(is_out_of_bound, rotation))
+                + dst_is_memory(meta, rotation)
+        };
+
+        meta.create_gate("copy row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let table = query(meta, &copy_table, Rotation::cur());
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let is_padding = meta.query_advice(is_padding, Rotation::cur());
+            let src_flags = [
+                (src_is_memory, CopyDataType::Memory),
+                (src_is_tx_calldata, CopyDataType::TxCalldata),
+                (src_is_bytecode, CopyDataType::Bytecode),
+                (src_is_rlc_acc, CopyDataType::RlcAcc),
+            ]
+            .map(|(column, src_type)| (meta.query_advice(column, Rotation::cur()), src_type));
+            let src_is_rlc_acc = src_flags[3].0.clone();
+            let dst_is_rlc_acc = meta.query_advice(dst_is_rlc_acc, Rotation::cur());
+
+            cb.require_boolean("is_first is boolean", table[IS_FIRST].clone());
+            cb.require_boolean("is_padding is boolean", is_padding.clone());
+            for (flag, _) in src_flags.iter() {
+                cb.require_boolean("source flag is boolean", flag.clone());
+            }
+            cb.require_equal(
+                "a source flag is set except on padding rows",
+                src_flags
+                    .iter()
+                    .fold(0.expr(), |acc, (flag, _)| acc + flag.clone()),
+                not::expr(is_padding.clone()),
+            );
+            cb.require_equal(
+                "src_type matches the source flags",
+                table[SRC_TYPE].clone(),
+                src_flags.iter().fold(0.expr(), |acc, (flag, src_type)| {
+                    acc + flag.clone() * src_type.expr()
+                }),
+            );
+            cb.require_boolean("dst_is_rlc_acc is boolean", dst_is_rlc_acc.clone());
+            cb.require_zero(
+                "dst_is_rlc_acc = 0 on padding rows",
+                is_padding.clone() * dst_is_rlc_acc.clone(),
+            );
+            cb.require_equal(
+                "dst_type is memory or an RLC except on padding rows",
+                table[DST_TYPE].clone(),
+                not::expr(is_padding.clone()) * CopyDataType::Memory.expr()
+                    + dst_is_rlc_acc.clone()
+                        * (CopyDataType::RlcAcc.expr() - CopyDataType::Memory.expr()),
+            );
+            cb.require_zero(
+                "the source and the destination aren't both an RLC",
+                src_is_rlc_acc.clone() * dst_is_rlc_acc.clone(),
+            );
+            cb.require_zero(
+                "is_first = 0 on padding rows",
+                is_padding * table[IS_FIRST].clone(),
+            );
+
+            // The RLC starts with the first byte, and it's the one of the copy
+            // on its last row
+            let is_rlc_acc = src_is_rlc_acc + dst_is_rlc_acc;
+            let rlc = meta.query_advice(rlc, Rotation::cur());
+            cb.require_zero(
+                "rlc_acc = 0 unless the source or the destination is an RLC",
+                not::expr(is_rlc_acc.clone()) * table[RLC_ACC].clone(),
+            );
+            cb.condition(table[IS_FIRST].clone(), |cb| {
+                cb.require_equal("rlc = byte on the first row", rlc.clone(), byte.clone());
+            });
+            cb.condition(
+                is_rlc_acc * meta.query_advice(is_last, Rotation::cur()),
+                |cb| {
+                    cb.require_equal("rlc_acc = rlc on the last row", table[RLC_ACC].clone(), rlc);
+                },
+            );
+
+            for (is_zero, value, value_inv, name) in [
+                (
+                    is_last,
+                    table[BYTES_LEFT].clone() - 1.expr(),
+                    bytes_left_inv,
+                    "is_last = bytes_left == 1",
+                ),
+                (
+                    is_out_of_bound,
+                    table[SRC_ADDR_END].clone() - table[SRC_ADDR].clone(),
+                    src_addr_diff_inv,
+                    "is_out_of_bound = src_addr == src_addr_end",
+                ),
+            ] {
+                let is_zero = meta.query_advice(is_zero, Rotation::cur());
+                let value_inv = meta.query_advice(value_inv, Rotation::cur());
+                cb.require_equal(name, is_zero.clone(), 1.expr() - value.clone() * value_inv);
+                cb.require_zero(name, is_zero * value);
+            }
+            cb.require_zero(
+                "byte = 0 when out of bound",
+                meta.query_advice(is_out_of_bound, Rotation::cur()) * byte,
+            );
+
+            cb.gate(meta.query_selector(q_enable))
+        });
+
+        meta.create_gate("copy first row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            cb.require_zero(
+                "the first row starts a copy or is padding",
+                not::expr(meta.query_advice(copy_table[IS_FIRST], Rotation::cur()))
+                    * not::expr(meta.query_advice(is_padding, Rotation::cur())),
+            );
+            cb.gate(meta.query_selector(q_first))
+        });
+
+        meta.create_gate("copy last row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            cb.require_zero(
+                "the last row ends a copy or is padding",
+                not::expr(meta.query_advice(is_last, Rotation::cur()))
+                    * not::expr(meta.query_advice(is_padding, Rotation::cur())),
+            );
+            cb.gate(meta.query_selector(q_last))
+        });
+
+        meta.create_gate("copy transition", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let table = query(meta, &copy_table, Rotation::cur());
+            let table_prev = query(meta, &copy_table, Rotation::prev());
+            let is_padding = meta.query_advice(is_padding, Rotation::cur());
+            let is_padding_prev = meta.query_advice(is_padding, Rotation::prev());
+            let is_last_prev = meta.query_advice(is_last, Rotation::prev());
+            let is_out_of_bound_prev = meta.query_advice(is_out_of_bound, Rotation::prev());
+            let rwc_step_prev = rwc_step(meta, Rotation::prev());
+
+            cb.require_zero(
+                "padding rows are only followed by padding rows",
+                is_padding_prev.clone() * not::expr(is_padding.clone()),
+            );
+            cb.require_zero(
+                "padding rows follow the last row of a copy",
+                is_padding.clone() * not::expr(is_padding_prev) * not::expr(is_last_prev.clone()),
+            );
+            cb.condition(not::expr(is_padding.clone()), |cb| {
+                cb.require_equal(
+                    "a copy starts after the last row of the previous one",
+                    table[IS_FIRST].clone(),
+                    is_last_prev,
+                );
+            });
+
+            // The rows of a copy other than the first one
+            cb.condition(
+                not::expr(table[IS_FIRST].clone()) * not::expr(is_padding),
+                |cb| {
+                    for idx in [SRC_ID, SRC_TYPE, DST_ID, DST_TYPE, SRC_ADDR_END, RLC_ACC] {
+                        cb.require_equal(
+                            "value is the same in all the rows of a copy",
+                            table[idx].clone(),
+                            table_prev[idx].clone(),
+                        );
+                    }
+                    cb.require_equal(
+                        "src_addr moves to the next byte until the end of the source",
+                        table[SRC_ADDR].clone(),
+                        table_prev[SRC_ADDR].clone() + not::expr(is_out_of_bound_prev),
+                    );
+                    cb.require_equal(
+                        "dst_addr moves to the next byte",
+                        table[DST_ADDR].clone(),
+                        table_prev[DST_ADDR].clone() + 1.expr(),
+                    );
+                    cb.require_equal(
+                        "bytes_left decreases by 1",
+                        table[BYTES_LEFT].clone(),
+                        table_prev[BYTES_LEFT].clone() - 1.expr(),
+                    );
+                    cb.require_equal(
+                        "rw_counter moves past the accesses of the previous byte",
+                        table[RW_COUNTER].clone(),
+                        table_prev[RW_COUNTER].clone() + rwc_step_prev.clone(),
+                    );
+                    cb.require_equal(
+                        "rwc_inc_left decreases by the accesses of the previous byte",
+                        table[RWC_INC_LEFT].clone(),
+                        table_prev[RWC_INC_LEFT].clone() - rwc_step_prev,
+                    );
+                    cb.require_equal(
+                        "rlc accumulates the byte",
+                        meta.query_advice(rlc, Rotation::cur()),
+                        meta.query_advice(rlc, Rotation::prev()) * Expression::Constant(r)
+                            + meta.query_advice(byte, Rotation::cur()),
+                    );
+                },
+            );
+
+            cb.gate(meta.query_selector(q_enable) - meta.query_selector(q_first))
+        });
+
+        meta.create_gate("copy rw counter", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_last = meta.query_advice(is_last, Rotation::cur());
+            cb.require_equal(
+                "rwc_inc_left = the accesses of the last byte",
+                is_last.clone() * meta.query_advice(copy_table[RWC_INC_LEFT], Rotation::cur()),
+                is_last * rwc_step(meta, Rotation::cur()),
+            );
+            cb.gate(meta.query_selector(q_enable))
+        });
+
+        // The byte is looked up in the source before its end, and in the
+        // destination memory
+        let in_bound = |meta: &mut VirtualCells<F>, flag: Column<Advice>| {
+            meta.query_selector(q_enable)
+                * meta.query_advice(flag, Rotation::cur())
+                * not::expr(meta.query_advice(is_out_of_bound, Rotation::cur()))
+        };
+
+        meta.lookup_any("copy source memory", |meta| {
+            let condition = in_bound(meta, src_is_memory);
+            let table = query(meta, &copy_table, Rotation::cur());
+            let input_exprs = vec![
+                table[RW_COUNTER].clone(),
+                0.expr(),
+                RwTableTag::Memory.expr(),
+                table[SRC_ID].clone(),
+                table[SRC_ADDR].clone(),
+                0.expr(),
+                meta.query_advice(byte, Rotation::cur()),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+            ];
+            conditional_lookup(condition, input_exprs, rw_table.table_exprs(meta))
+        });
+
+        meta.lookup_any("copy source tx calldata", |meta| {
+            let condition = in_bound(meta, src_is_tx_calldata);
+            let table = query(meta, &copy_table, Rotation::cur());
+            let input_exprs = vec![
+                table[SRC_ID].clone(),
+                TxContextFieldTag::CallData.expr(),
+                table[SRC_ADDR].clone(),
+                meta.query_advice(byte, Rotation::cur()),
+            ];
+            conditional_lookup(condition, input_exprs, tx_table.table_exprs(meta))
+        });
+
+        meta.lookup_any("copy source bytecode", |meta| {
+            let condition = in_bound(meta, src_is_bytecode);
+            let table = query(meta, &copy_table, Rotation::cur());
+            let input_exprs = vec![
+                table[SRC_ID].clone(),
+                BytecodeFieldTag::Byte.expr(),
+                table[SRC_ADDR].clone(),
+                meta.query_advice(byte, Rotation::cur()),
+                meta.query_advice(is_code, Rotation::cur()),
+            ];
+            conditional_lookup(condition, input_exprs, bytecode_table.table_exprs(meta))
+        });
+
+        meta.lookup_any("copy destination memory", |meta| {
+            let condition = meta.query_selector(q_enable) * dst_is_memory(meta, Rotation::cur());
+            let table = query(meta, &copy_table, Rotation::cur());
+            // The write follows the read of a memory source
+            let rw_counter = table[RW_COUNTER].clone()
+                + meta.query_advice(src_is_memory, Rotation::cur())
+                    * not::expr(meta.query_advice(is_out_of_bound, Rotation::cur()));
+            let input_exprs = vec![
+                rw_counter,
+                1.expr(),
+                RwTableTag::Memory.expr(),
+                table[DST_ID].clone(),
+                table[DST_ADDR].clone(),
+                0.expr(),
+                meta.query_advice(byte, Rotation::cur()),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+            ];
+            conditional_lookup(condition, input_exprs, rw_table.table_exprs(meta))
+        });
+
+        Config {
+            minimum_rows: meta.minimum_rows(),
+            q_enable,
+            q_first,
+            q_last,
+            copy_table,
+            byte,
+            is_code,
+            src_is_memory,
+            src_is_tx_calldata,
+            src_is_bytecode,
+            src_is_rlc_acc,
+            dst_is_rlc_acc,
+            rlc,
+            is_padding,
+            is_last,
+            bytes_left_inv,
+            is_out_of_bound,
+            src_addr_diff_inv,
+        }
+    }
+
+    /// Assign the copy events of `block` into a circuit of `size` rows, where
+    /// the rows left are padding.
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        size: usize,
+        block: &Block<F>,
+    ) -> Result<(), Error> {
+        self.assign_rows(layouter, size, copy_rows(block))
+    }
+
+    fn assign_rows(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        size: usize,
+        rows: Vec<CopyRow<F>>,
+    ) -> Result<(), Error> {
+        // Subtract the unusable rows from the size
+        let num_rows = size - self.minimum_rows + 1;
+        if rows.len() > num_rows {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || "copy",
+            |mut region| {
+                self.q_first.enable(&mut region, 0)?;
+                self.q_last.enable(&mut region, num_rows - 1)?;
+                for offset in 0..num_rows {
+                    self.q_enable.enable(&mut region, offset)?;
+                    match rows.get(offset) {
+                        Some(row) => self.assign_row(&mut region, offset, row)?,
+                        None => self.assign_padding_row(&mut region, offset)?,
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    fn assign_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: &CopyRow<F>,
+    ) -> Result<(), Error> {
+        let bytes_left_minus_one = row.table[BYTES_LEFT] - F::one();
+        let src_addr_diff = row.table[SRC_ADDR_END] - row.table[SRC_ADDR];
+        let mut values = vec![
+            (self.byte, F::from(row.byte as u64)),
+            (self.is_code, F::from(row.is_code as u64)),
+            (
+                self.src_is_memory,
+                F::from((row.src_type == CopyDataType::Memory) as u64),
+            ),
+            (
+                self.src_is_tx_calldata,
+                F::from((row.src_type == CopyDataType::TxCalldata) as u64),
+            ),
+            (
+                self.src_is_bytecode,
+                F::from((row.src_type == CopyDataType::Bytecode) as u64),
+            ),
+            (
+                self.src_is_rlc_acc,
+                F::from((row.src_type == CopyDataType::RlcAcc) as u64),
+            ),
+            (
+                self.dst_is_rlc_acc,
+                F::from((row.dst_type == CopyDataType::RlcAcc) as u64),
+            ),
+            (self.rlc, row.rlc),
+            (self.is_padding, F::zero()),
+            (
+                self.is_last,
+                F::from(bool::from(bytes_left_minus_one.is_zero()) as u64),
+            ),
+            (
+                self.bytes_left_inv,
+                bytes_left_minus_one.invert().unwrap_or(F::zero()),
+            ),
+            (
+                self.is_out_of_bound,
+                F::from(bool::from(src_addr_diff.is_zero()) as u64),
+            ),
+            (
+                self.src_addr_diff_inv,
+                src_addr_diff.invert().unwrap_or(F::zero()),
+            ),
+        ];
+        values.extend(self.copy_table.iter().copied().zip(row.table));
+
+        for (column, value) in values {
+            region.assign_advice(
+                || format!("assign copy {}", offset),
+                column,
+                offset,
+                || Ok(value),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Assign a row out of the copies, whose values in the table are all 0.
+    fn assign_padding_row(&self, region: &mut Region<'_, F>, offset: usize) -> Result<(), Error> {
+        let values = [
+            (self.byte, F::zero()),
+            (self.is_code, F::zero()),
+            (self.src_is_memory, F::zero()),
+            (self.src_is_tx_calldata, F::zero()),
+            (self.src_is_bytecode, F::zero()),
+            (self.src_is_rlc_acc, F::zero()),
+            (self.dst_is_rlc_acc, F::zero()),
+            (self.rlc, F::zero()),
+            (self.is_padding, F::one()),
+            (self.is_last, F::zero()),
+            // The inverse of bytes_left - 1 = -1
+            (self.bytes_left_inv, -F::one()),
+            (self.is_out_of_bound, F::one()),
+            (self.src_addr_diff_inv, F::zero()),
+        ];
+        for (column, value) in values
+            .iter()
+            .copied()
+            .chain(self.copy_table.iter().map(|column| (*column, F::zero())))
+        {
+            region.assign_advice(
+                || format!("assign copy padding row {}", offset),
+                column,
+                offset,
+                || Ok(value),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<F: Field> LookupTable<F, COPY_TABLE_WIDTH> for Config<F> {
+    fn table_exprs(&self, meta: &mut VirtualCells<F>) -> [Expression<F>; COPY_TABLE_WIDTH] {
+        self.copy_table
+            .map(|column| meta.query_advice(column, Rotation::cur()))
+    }
+}
+
+/// Return the rows of the copy events of `block`.
+fn copy_rows<F: Field>(block: &Block<F>) -> Vec<CopyRow<F>> {
+    block
+        .copy_events
+        .iter()
+        .flat_map(|event| {
+            let is_code = bytecode_is_code(block, event);
+            let mut rlc = F::zero();
+            event
+                .table_assignments(block.randomness)
+                .into_iter()
+                .zip(event.bytes.iter())
+                .enumerate()
+                .map(move |(idx, (table, byte))| {
+                    rlc = rlc * block.randomness + F::from(*byte as u64);
+                    CopyRow {
+                        table,
+                        src_type: event.src_type,
+                        dst_type: event.dst_type,
+                        byte: *byte,
+                        rlc,
+                        is_code: is_code
+                            .get(event.src_addr as usize + idx)
+                            .copied()
+                            .unwrap_or(false),
+                    }
+                })
+        })
+        .collect()
+}
+
+/// Return whether each byte of the source of `event` is an opcode, when it's
+/// a bytecode, or an empty list otherwise.
+fn bytecode_is_code<F: Field>(block: &Block<F>, event: &CopyEvent) -> Vec<bool> {
+    match (event.src_type, event.src_id) {
+        (CopyDataType::Bytecode, NumberOrHash::Hash(hash)) => block
+            .bytecodes
+            .iter()
+            .find(|bytecode| bytecode.hash == hash.to_word())
+            .expect("copied bytecode must be in the block")
+            .table_assignments(block.randomness)
+            // The first row is the length
+            .skip(1)
+            .map(|row| row[4] == F::one())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn query<F: Field>(
+    meta: &mut VirtualCells<F>,
+    columns: &[Column<Advice>; COPY_TABLE_WIDTH],
+    rotation: Rotation,
+) -> [Expression<F>; COPY_TABLE_WIDTH] {
+    columns.map(|column| meta.query_advice(column, rotation))
+}
+
+fn conditional_lookup<F: Field, const W: usize>(
+    condition: Expression<F>,
+    input_exprs: Vec<Expression<F>>,
+    table_exprs: [Expression<F>; W],
+) -> Vec<(Expression<F>, Expression<F>)> {
+    input_exprs
+        .into_iter()
+        .map(|expr| condition.clone() * expr)
+        .zip(table_exprs)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        evm_circuit::{
+            test::{make_copy_event, rand_bytes},
+            witness::{Bytecode, RwMap, Transaction},
+        },
+        rw_table::RwTable,
+    };
+    use eth_types::{bytecode, Hash, ToBigEndian, Word};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use pairing::bn256::Fr;
+
+    #[derive(Clone)]
+    struct MyConfig<F> {
+        tx_table: [Column<Advice>; 4],
+        rw_table: RwTable,
+        bytecode_table: [Column<Advice>; 5],
+        copy: Config<F>,
+    }
+
+    #[derive(Default)]
+    struct MyCircuit<F> {
+        block: Block<F>,
+        rows: Vec<CopyRow<F>>,
+        size: usize,
+    }
+
+    impl<F: Field> MyCircuit<F> {
+        fn r() -> F {
+            F::from(123456)
+        }
+    }
+
+    impl<F: Field> MyConfig<F> {
+        fn load_table<const W: usize>(
+            layouter: &mut impl Layouter<F>,
+            columns: [Column<Advice>; W],
+            rows: impl Iterator<Item = [F; W]>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "table",
+                |mut region| {
+                    for (offset, row) in std::iter::once([F::zero(); W]).chain(rows).enumerate() {
+                        for (column, value) in columns.iter().zip(row) {
+                            region.assign_advice(
+                                || format!("table row {}", offset),
+                                *column,
+                                offset,
+                                || Ok(value),
+                            )?;
+                        }
+                    }
+                    Ok(())
+                },
+            )
+        }
+
+        fn load_rws(&self, layouter: &mut impl Layouter<F>, block: &Block<F>) -> Result<(), Error> {
+            layouter.assign_region(
+                || "rw table",
+                |mut region| {
+                    self.rw_table.assign(&mut region, 0, &Default::default())?;
+                    for (offset, rw) in block.rws.0.values().flatten().enumerate() {
+                        self.rw_table.assign(
+                            &mut region,
+                            offset + 1,
+                            &rw.table_assignment(block.randomness),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    impl<F: Field> Circuit<F> for MyCircuit<F> {
+        type Config = MyConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let tx_table = [(); 4].map(|_| meta.advice_column());
+            let rw_table = RwTable::construct(meta);
+            let bytecode_table = [(); 5].map(|_| meta.advice_column());
+            MyConfig {
+                tx_table,
+                rw_table,
+                bytecode_table,
+                copy: Config::configure(meta, MyCircuit::r(), tx_table, rw_table, bytecode_table),
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let randomness = self.block.randomness;
+            MyConfig::load_table(
+                &mut layouter,
+                config.tx_table,
+                self.block
+                    .txs
+                    .iter()
+                    .flat_map(|tx| tx.table_assignments(randomness)),
+            )?;
+            config.load_rws(&mut layouter, &self.block)?;
+            MyConfig::load_table(
+                &mut layouter,
+                config.bytecode_table,
+                self.block
+                    .bytecodes
+                    .iter()
+                    .flat_map(|bytecode| bytecode.table_assignments(randomness)),
+            )?;
+            config
+                .copy
+                .assign_rows(&mut layouter, self.size, self.rows.clone())
+        }
+    }
+
+    /// Verify the rows of the copy events of `block`, after `tamper` changes
+    /// them.
+    fn verify<F: Field>(block: Block<F>, tamper: impl FnOnce(&mut Vec<CopyRow<F>>), success: bool) {
+        let k = 9;
+        let mut rows = copy_rows(&block);
+        tamper(&mut rows);
+        let circuit = MyCircuit::<F> {
+            block,
+            rows,
+            size: 2usize.pow(k),
+        };
+
+        let prover = MockProver::<F>::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify().is_ok(), success);
+    }
+
+    /// A block with copies from the calldata of a transaction, past its end,
+    /// from the memory of a call and from a bytecode, and copies into and out
+    /// of an RLC.
+    fn block<F: Field>() -> Block<F> {
+        let call_data = rand_bytes(8);
+        let memory = rand_bytes(64);
+        let output = rand_bytes(20);
+        let bytecode = Bytecode::new(
+            bytecode! {
+                PUSH32(Word::MAX)
+                PUSH1(0x10)
+                MSTORE
+                STOP
+            }
+            .to_vec(),
+        );
+
+        let mut rw_counter = 1;
+        let mut rws = RwMap::default();
+        let mut rw_indices = Vec::new();
+        let copy_events = vec![
+            make_copy_event(
+                CopyDataType::TxCalldata,
+                NumberOrHash::Number(1),
+                &call_data,
+                0,
+                4,
+                CopyDataType::Memory,
+                1,
+                0,
+                10,
+                &mut rw_counter,
+                &mut rws,
+                &mut rw_indices,
+            ),
+            make_copy_event(
+                CopyDataType::Memory,
+                NumberOrHash::Number(1),
+                &memory,
+                0x20,
+                0x30,
+                CopyDataType::Memory,
+                2,
+                0x40,
+                0x40,
+                &mut rw_counter,
+                &mut rws,
+                &mut rw_indices,
+            ),
+            make_copy_event(
+                CopyDataType::Bytecode,
+                NumberOrHash::Hash(Hash::from(bytecode.hash.to_be_bytes())),
+                &bytecode.bytes,
+                0,
+                30,
+                CopyDataType::Memory,
+                2,
+                0,
+                8,
+                &mut rw_counter,
+                &mut rws,
+                &mut rw_indices,
+            ),
+            make_copy_event(
+                CopyDataType::Memory,
+                NumberOrHash::Number(1),
+                &memory,
+                0x20,
+                0x58,
+                CopyDataType::RlcAcc,
+                0,
+                0,
+                0x10,
+                &mut rw_counter,
+                &mut rws,
+                &mut rw_indices,
+            ),
+            make_copy_event(
+                CopyDataType::RlcAcc,
+                NumberOrHash::Number(0),
+                &output,
+                0,
+                0,
+                CopyDataType::Memory,
+                3,
+                0,
+                output.len(),
+                &mut rw_counter,
+                &mut rws,
+                &mut rw_indices,
+            ),
+        ];
+
+        Block {
+            randomness: MyCircuit::r(),
+            txs: vec![Transaction {
+                id: 1,
+                call_data_length: call_data.len(),
+                call_data,
+                ..Default::default()
+            }],
+            rws,
+            bytecodes: vec![bytecode],
+            copy_events,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn copy_valid() {
+        verify::<Fr>(block(), |_| {}, true);
+    }
+
+    #[test]
+    fn copy_empty() {
+        verify::<Fr>(Block::default(), |_| {}, true);
+    }
+
+    #[test]
+    fn copy_invalid_byte() {
+        let mut block = block::<Fr>();
+        block.copy_events[1].bytes[3] ^= 1;
+        verify(block, |_| {}, false);
+    }
+
+    #[test]
+    fn copy_invalid_out_of_bound_byte() {
+        // The 6th byte of the first copy is past the end of the calldata
+        verify::<Fr>(block(), |rows| rows[5].byte = 1, false);
+    }
+
+    #[test]
+    fn copy_invalid_rw_counter() {
+        verify::<Fr>(
+            block(),
+            |rows| rows[12].table[RW_COUNTER] += Fr::from(1),
+            false,
+        );
+    }
+
+    #[test]
+    fn copy_invalid_src_addr() {
+        verify::<Fr>(
+            block(),
+            |rows| rows[1].table[SRC_ADDR] += Fr::from(1),
+            false,
+        );
+    }
+
+    #[test]
+    fn copy_invalid_rlc_acc() {
+        // The rows of the copy into an RLC follow the 82 rows of the copies
+        // into memory
+        verify::<Fr>(
+            block(),
+            |rows| {
+                for row in rows[82..98].iter_mut() {
+                    row.table[RLC_ACC] += Fr::from(1);
+                }
+            },
+            false,
+        );
+    }
+
+    #[test]
+    fn copy_invalid_byte_out_of_rlc_acc() {
+        let mut block = block::<Fr>();
+        block.copy_events[4].bytes[7] ^= 1;
+        verify(block, |_| {}, false);
+    }
+
+    #[test]
+    fn copy_missing_last_row() {
+        verify::<Fr>(
+            block(),
+            |rows| {
+                rows.pop();
+            },
+            false,
+        );
+    }
+}
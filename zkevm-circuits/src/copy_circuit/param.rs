@@ -0,0 +1,4 @@
+/// The number of columns of the copy table, which are `is_first`, `src_id`,
+/// `src_type`, `dst_id`, `dst_type`, `src_addr`, `src_addr_end`, `dst_addr`,
+/// `bytes_left`, `rw_counter`, `rwc_inc_left` and `rlc_acc`.
+pub const COPY_TABLE_WIDTH: usize = 12;
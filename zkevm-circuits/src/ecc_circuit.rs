@@ -0,0 +1,7 @@
+//! The ECC circuit implementation, which builds the table of the calls to the
+//! ecAdd, ecMul and ecPairing precompiles operating on the BN254 curve.  It
+//! only checks the shape of the rows of the table, and doesn't prove the
+//! curve arithmetic yet.
+
+pub(crate) mod bn128;
+pub(crate) mod param;
@@ -0,0 +1,297 @@
+use crate::{
+    evm_circuit::{
+        util::{constraint_builder::BaseConstraintBuilder, not},
+        witness::EccCall,
+    },
+    util::Expr,
+};
+use bus_mapping::precompile::PrecompileCalls;
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use super::param::ECC_TABLE_WIDTH;
+
+#[derive(Clone, Debug)]
+pub struct Config<F> {
+    r: F,
+    q_enable: Selector,
+    /// The ECC table looked up by the EVM circuit, whose columns are the
+    /// address of the precompile, `input_rlc`, `length`, `is_valid` and
+    /// `output_rlc`.
+    ecc_table: [Column<Advice>; ECC_TABLE_WIDTH],
+    /// The number of pairs of points in the input of ecPairing.
+    num_pairs: Column<Advice>,
+}
+
+impl<F: Field> Config<F> {
+    pub(crate) fn configure(meta: &mut ConstraintSystem<F>, r: F) -> Self {
+        let q_enable = meta.selector();
+        let ecc_table = array_init::array_init(|_| meta.advice_column());
+        let num_pairs = meta.advice_column();
+
+        meta.create_gate("bn128", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [precompile, _, length, is_valid, output_rlc] =
+                ecc_table.map(|column| meta.query_advice(column, Rotation::cur()));
+            let num_pairs = meta.query_advice(num_pairs, Rotation::cur());
+
+            cb.require_in_set(
+                "precompile needs to be ecAdd, ecMul or ecPairing",
+                precompile.clone(),
+                vec![
+                    (PrecompileCalls::Bn128Add as u64).expr(),
+                    (PrecompileCalls::Bn128Mul as u64).expr(),
+                    (PrecompileCalls::Bn128Pairing as u64).expr(),
+                ],
+            );
+            cb.require_boolean("is_valid needs to be boolean", is_valid.clone());
+            cb.condition(not::expr(is_valid.clone()), |cb| {
+                cb.require_zero("output needs to be empty when invalid", output_rlc.clone());
+            });
+
+            // 1 for ecPairing and 0 for the other precompiles in the set
+            let is_pairing = (precompile.clone() - (PrecompileCalls::Bn128Add as u64).expr())
+                * (precompile - (PrecompileCalls::Bn128Mul as u64).expr())
+                * Expression::Constant(F::from(2).invert().unwrap());
+            cb.condition(is_pairing * is_valid, |cb| {
+                cb.require_equal(
+                    "length needs to be a multiple of 192 when valid",
+                    length,
+                    num_pairs * 192.expr(),
+                );
+                // The RLC encoding of a small output is the output itself
+                cb.require_boolean("output needs to be 0 or 1 when valid", output_rlc);
+            });
+
+            // TODO: Prove that the points of the input are on the curve, that
+            // the outputs of ecAdd and ecMul are their sum and product, and
+            // that the output of ecPairing is the pairing check, which
+            // requires a non-native BN254 ECC chip and pairing chip.  Until
+            // then, the outputs are only computed in the witness generation.
+
+            cb.gate(meta.query_selector(q_enable))
+        });
+
+        Config {
+            r,
+            q_enable,
+            ecc_table,
+            num_pairs,
+        }
+    }
+
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        ecc_calls: &[EccCall],
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "ecc table",
+            |mut region| {
+                // The all-zero row is looked up by the disabled lookups, and
+                // isn't a call to any of the precompiles
+                for (idx, column) in self
+                    .ecc_table
+                    .iter()
+                    .chain(std::iter::once(&self.num_pairs))
+                    .enumerate()
+                {
+                    region.assign_advice(
+                        || format!("assign ecc table all-zero row {}", idx),
+                        *column,
+                        0,
+                        || Ok(F::zero()),
+                    )?;
+                }
+                for (idx, ecc_call) in ecc_calls.iter().enumerate() {
+                    self.set_row(&mut region, idx + 1, ecc_call)?;
+                }
+
+                Ok(())
+            },
+        )
+    }
+
+    fn set_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        ecc_call: &EccCall,
+    ) -> Result<(), Error> {
+        self.q_enable.enable(region, offset)?;
+
+        let row = ecc_call.table_assignment(self.r);
+        for (column, value) in self.ecc_table.iter().zip(row) {
+            region.assign_advice(
+                || format!("assign ecc table {}", offset),
+                *column,
+                offset,
+                || Ok(value),
+            )?;
+        }
+        region.assign_advice(
+            || format!("assign num_pairs {}", offset),
+            self.num_pairs,
+            offset,
+            || Ok(F::from(ecc_call.input.len() as u64 / 192)),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{word, ToBigEndian, Word};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use pairing::bn256::Fr;
+
+    #[derive(Default)]
+    struct MyCircuit<F> {
+        ecc_calls: Vec<EccCall>,
+        _marker: std::marker::PhantomData<F>,
+    }
+
+    impl<F: Field> MyCircuit<F> {
+        fn r() -> F {
+            F::from(123456)
+        }
+    }
+
+    impl<F: Field> Circuit<F> for MyCircuit<F> {
+        type Config = Config<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            Config::configure(meta, MyCircuit::r())
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.assign(&mut layouter, &self.ecc_calls)
+        }
+    }
+
+    fn verify<F: Field>(ecc_calls: Vec<EccCall>, success: bool) {
+        let circuit = MyCircuit::<F> {
+            ecc_calls,
+            _marker: std::marker::PhantomData,
+        };
+
+        let prover = MockProver::<F>::run(5, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify().is_ok(), success);
+    }
+
+    fn words_to_bytes(words: &[Word]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_be_bytes()).collect()
+    }
+
+    fn pairing_input(g1s: &[[Word; 2]]) -> Vec<u8> {
+        let g2 = [
+            word!("198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2"),
+            word!("1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed"),
+            word!("090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b"),
+            word!("12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa"),
+        ];
+        words_to_bytes(
+            &g1s.iter()
+                .flat_map(|g1| [g1.to_vec(), g2.to_vec()].concat())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn g1() -> [Word; 2] {
+        [Word::one(), Word::from(2)]
+    }
+
+    fn neg_g1() -> [Word; 2] {
+        [
+            Word::one(),
+            word!("30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45"),
+        ]
+    }
+
+    #[test]
+    fn bn128_valid_and_invalid() {
+        let ecc_calls = vec![
+            EccCall::new(
+                PrecompileCalls::Bn128Add,
+                &words_to_bytes(&[g1(), neg_g1()].concat()),
+            ),
+            EccCall::new(
+                PrecompileCalls::Bn128Mul,
+                &words_to_bytes(&[g1()[0], g1()[1], Word::from(3)]),
+            ),
+            // Point not on the curve
+            EccCall::new(
+                PrecompileCalls::Bn128Mul,
+                &words_to_bytes(&[Word::one(), Word::from(3), Word::from(3)]),
+            ),
+            EccCall::new(PrecompileCalls::Bn128Pairing, &[]),
+            EccCall::new(PrecompileCalls::Bn128Pairing, &pairing_input(&[g1()])),
+            EccCall::new(
+                PrecompileCalls::Bn128Pairing,
+                &pairing_input(&[g1(), neg_g1()]),
+            ),
+            // Length not a multiple of 192
+            EccCall::new(PrecompileCalls::Bn128Pairing, &[0; 100]),
+        ];
+        assert_eq!(
+            ecc_calls
+                .iter()
+                .map(|ecc_call| ecc_call.is_valid())
+                .collect::<Vec<_>>(),
+            vec![true, true, false, true, true, true, false]
+        );
+
+        verify::<Fr>(ecc_calls, true);
+    }
+
+    #[test]
+    fn bn128_empty() {
+        verify::<Fr>(vec![], true);
+    }
+
+    #[test]
+    fn bn128_invalid_precompile() {
+        let ecc_call = EccCall {
+            precompile: PrecompileCalls::Identity,
+            ..EccCall::new(PrecompileCalls::Bn128Pairing, &[])
+        };
+        verify::<Fr>(vec![ecc_call], false);
+    }
+
+    #[test]
+    fn bn128_invalid_pairing_output() {
+        let ecc_call = EccCall {
+            output: Word::from(2).to_be_bytes().to_vec(),
+            ..EccCall::new(PrecompileCalls::Bn128Pairing, &[])
+        };
+        verify::<Fr>(vec![ecc_call], false);
+    }
+
+    #[test]
+    fn bn128_invalid_pairing_length() {
+        let ecc_call = EccCall {
+            input: vec![0; 191],
+            ..EccCall::new(PrecompileCalls::Bn128Pairing, &pairing_input(&[g1()]))
+        };
+        verify::<Fr>(vec![ecc_call], false);
+    }
+}
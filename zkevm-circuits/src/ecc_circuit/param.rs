@@ -0,0 +1 @@
+pub const ECC_TABLE_WIDTH: usize = 5;
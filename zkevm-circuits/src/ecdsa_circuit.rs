@@ -0,0 +1,7 @@
+//! The ECDSA circuit implementation, which builds the table of the signers of
+//! secp256k1 signatures for the ecRecover precompile.  It only checks the
+//! shape of the rows of the table, and doesn't prove the recovery of the
+//! signers yet.
+
+pub(crate) mod ecrecover;
+pub(crate) mod param;
@@ -0,0 +1,264 @@
+use crate::{
+    evm_circuit::{
+        util::{constraint_builder::BaseConstraintBuilder, not},
+        witness::EcRecover,
+    },
+    util::Expr,
+};
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+use super::param::ECRECOVER_TABLE_WIDTH;
+
+#[derive(Clone, Debug)]
+pub struct Config<F> {
+    r: F,
+    q_enable: Selector,
+    /// The ecRecover table looked up by the EVM circuit, whose columns are
+    /// `msg_hash`, `v`, `r` and `s` in RLC encoding, `is_valid` and
+    /// `recovered_address`.
+    ecrecover_table: [Column<Advice>; ECRECOVER_TABLE_WIDTH],
+    sig_r_inv: Column<Advice>,
+    sig_s_inv: Column<Advice>,
+}
+
+impl<F: Field> Config<F> {
+    pub(crate) fn configure(meta: &mut ConstraintSystem<F>, r: F) -> Self {
+        let q_enable = meta.selector();
+        let ecrecover_table = array_init::array_init(|_| meta.advice_column());
+        let sig_r_inv = meta.advice_column();
+        let sig_s_inv = meta.advice_column();
+
+        meta.create_gate("ecrecover", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [_, v, sig_r, sig_s, is_valid, recovered_address] =
+                ecrecover_table.map(|column| meta.query_advice(column, Rotation::cur()));
+            let [sig_r_inv, sig_s_inv] =
+                [sig_r_inv, sig_s_inv].map(|column| meta.query_advice(column, Rotation::cur()));
+
+            cb.require_boolean("is_valid needs to be boolean", is_valid.clone());
+            cb.condition(is_valid.clone(), |cb| {
+                // The RLC encoding of a small word is the word itself
+                cb.require_in_set(
+                    "v needs to be 27 or 28 when valid",
+                    v,
+                    vec![27.expr(), 28.expr()],
+                );
+                cb.require_equal(
+                    "r needs to be non-zero when valid",
+                    sig_r * sig_r_inv,
+                    1.expr(),
+                );
+                cb.require_equal(
+                    "s needs to be non-zero when valid",
+                    sig_s * sig_s_inv,
+                    1.expr(),
+                );
+            });
+            cb.condition(not::expr(is_valid), |cb| {
+                cb.require_zero(
+                    "recovered_address needs to be 0 when invalid",
+                    recovered_address,
+                );
+            });
+
+            // TODO: Prove that `r` and `s` are less than the order of
+            // secp256k1, and that `recovered_address` is the address of the
+            // public key recovered from the signature of `msg_hash`, which
+            // requires a non-native secp256k1 ECC chip and a lookup to the
+            // keccak table.  Until then, the signers are only recovered in
+            // the witness generation.
+
+            cb.gate(meta.query_selector(q_enable))
+        });
+
+        Config {
+            r,
+            q_enable,
+            ecrecover_table,
+            sig_r_inv,
+            sig_s_inv,
+        }
+    }
+
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        ecrecovers: &[EcRecover],
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "ecrecover table",
+            |mut region| {
+                // The all-zero row is looked up by the disabled lookups
+                self.set_row(&mut region, 0, &EcRecover::default())?;
+                for (idx, ecrecover) in ecrecovers.iter().enumerate() {
+                    self.set_row(&mut region, idx + 1, ecrecover)?;
+                }
+
+                Ok(())
+            },
+        )
+    }
+
+    fn set_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        ecrecover: &EcRecover,
+    ) -> Result<(), Error> {
+        self.q_enable.enable(region, offset)?;
+
+        let row = ecrecover.table_assignment(self.r);
+        for (column, value) in self.ecrecover_table.iter().zip(row) {
+            region.assign_advice(
+                || format!("assign ecrecover table {}", offset),
+                *column,
+                offset,
+                || Ok(value),
+            )?;
+        }
+
+        let [sig_r, sig_s] = [row[2], row[3]];
+        for (name, column, value) in &[
+            ("sig_r_inv", self.sig_r_inv, sig_r),
+            ("sig_s_inv", self.sig_s_inv, sig_s),
+        ] {
+            region.assign_advice(
+                || format!("assign {} {}", name, offset),
+                *column,
+                offset,
+                || Ok(value.invert().unwrap_or(F::zero())),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{address, word, Word};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use pairing::bn256::Fr;
+
+    #[derive(Default)]
+    struct MyCircuit<F> {
+        ecrecovers: Vec<EcRecover>,
+        _marker: std::marker::PhantomData<F>,
+    }
+
+    impl<F: Field> MyCircuit<F> {
+        fn r() -> F {
+            F::from(123456)
+        }
+    }
+
+    impl<F: Field> Circuit<F> for MyCircuit<F> {
+        type Config = Config<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            Config::configure(meta, MyCircuit::r())
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.assign(&mut layouter, &self.ecrecovers)
+        }
+    }
+
+    fn verify<F: Field>(ecrecovers: Vec<EcRecover>, success: bool) {
+        let circuit = MyCircuit::<F> {
+            ecrecovers,
+            _marker: std::marker::PhantomData,
+        };
+
+        let prover = MockProver::<F>::run(5, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify().is_ok(), success);
+    }
+
+    fn ecrecover_input(words: [Word; 4]) -> Vec<u8> {
+        words
+            .iter()
+            .flat_map(|word| {
+                let mut bytes = [0u8; 32];
+                word.to_big_endian(&mut bytes);
+                bytes.to_vec()
+            })
+            .collect()
+    }
+
+    fn valid_ecrecover() -> EcRecover {
+        let ecrecover = EcRecover::new(&ecrecover_input([
+            word!("18c547e4f7b0f325ad1e56f57e26c745b09a3e503d86e00e5255ff7f715d3d1c"),
+            word!("1c"),
+            word!("73b1693892219d736caba55bdb67216e485557ea6b6af75f37096c9aa6a5a75f"),
+            word!("eeb940b1d03b21e36b0e47e79769f095fe2ab855bd91e3a38756b7d75a9c4549"),
+        ]));
+        assert_eq!(
+            ecrecover.recovered_address,
+            Some(address!("0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b"))
+        );
+        ecrecover
+    }
+
+    #[test]
+    fn ecrecover_valid_and_invalid() {
+        let valid = valid_ecrecover();
+        let invalid_v = EcRecover::new(&ecrecover_input([
+            valid.msg_hash,
+            Word::from(29),
+            valid.r,
+            valid.s,
+        ]));
+        assert_eq!(invalid_v.recovered_address, None);
+        let zero_r = EcRecover::new(&ecrecover_input([
+            valid.msg_hash,
+            valid.v,
+            Word::zero(),
+            valid.s,
+        ]));
+        assert_eq!(zero_r.recovered_address, None);
+
+        verify::<Fr>(vec![valid, invalid_v, zero_r], true);
+    }
+
+    #[test]
+    fn ecrecover_empty() {
+        verify::<Fr>(vec![], true);
+    }
+
+    #[test]
+    fn ecrecover_invalid_v() {
+        let ecrecover = EcRecover {
+            v: Word::from(29),
+            ..valid_ecrecover()
+        };
+        verify::<Fr>(vec![ecrecover], false);
+    }
+
+    #[test]
+    fn ecrecover_invalid_zero_s() {
+        let ecrecover = EcRecover {
+            s: Word::zero(),
+            ..valid_ecrecover()
+        };
+        verify::<Fr>(vec![ecrecover], false);
+    }
+}
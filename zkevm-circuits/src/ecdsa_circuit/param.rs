@@ -0,0 +1 @@
+pub const ECRECOVER_TABLE_WIDTH: usize = 6;
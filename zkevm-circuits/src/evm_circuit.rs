@@ -25,19 +25,49 @@ pub struct EvmCircuit<F> {
 
 impl<F: Field> EvmCircuit<F> {
     /// Configure EvmCircuit
-    pub fn configure<TxTable, RwTable, BytecodeTable, BlockTable>(
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure<
+        TxTable,
+        RwTable,
+        BytecodeTable,
+        BlockTable,
+        KeccakTable,
+        EcRecoverTable,
+        Sha256Table,
+        Ripemd160Table,
+        EccTable,
+        ModexpTable,
+        Blake2fTable,
+        CopyTable,
+    >(
         meta: &mut ConstraintSystem<F>,
         power_of_randomness: [Expression<F>; 31],
         tx_table: TxTable,
         rw_table: RwTable,
         bytecode_table: BytecodeTable,
         block_table: BlockTable,
+        keccak_table: KeccakTable,
+        ecrecover_table: EcRecoverTable,
+        sha256_table: Sha256Table,
+        ripemd160_table: Ripemd160Table,
+        ecc_table: EccTable,
+        modexp_table: ModexpTable,
+        blake2f_table: Blake2fTable,
+        copy_table: CopyTable,
     ) -> Self
     where
         TxTable: LookupTable<F, 4>,
         RwTable: LookupTable<F, 10>,
-        BytecodeTable: LookupTable<F, 4>,
+        BytecodeTable: LookupTable<F, 5>,
         BlockTable: LookupTable<F, 3>,
+        KeccakTable: LookupTable<F, 3>,
+        EcRecoverTable: LookupTable<F, 6>,
+        Sha256Table: LookupTable<F, 3>,
+        Ripemd160Table: LookupTable<F, 3>,
+        EccTable: LookupTable<F, 5>,
+        ModexpTable: LookupTable<F, 4>,
+        Blake2fTable: LookupTable<F, 3>,
+        CopyTable: LookupTable<F, 12>,
     {
         let fixed_table = [(); 4].map(|_| meta.fixed_column());
 
@@ -49,6 +79,14 @@ impl<F: Field> EvmCircuit<F> {
             rw_table,
             bytecode_table,
             block_table,
+            keccak_table,
+            ecrecover_table,
+            sha256_table,
+            ripemd160_table,
+            ecc_table,
+            modexp_table,
+            blake2f_table,
+            copy_table,
         );
 
         Self {
@@ -104,13 +142,17 @@ pub mod test {
     use crate::{
         evm_circuit::{
             param::STEP_HEIGHT,
-            table::FixedTableTag,
-            witness::{Block, BlockContext, Bytecode, RwMap, Transaction},
+            table::{FixedTableTag, RwTableTag},
+            witness::{
+                keccak_table_row, Block, BlockContext, Bytecode, CopyEvent, EcRecover, EccCall,
+                ModexpCall, Rw, RwMap, Transaction,
+            },
             EvmCircuit,
         },
         rw_table::RwTable,
         util::Expr,
     };
+    use bus_mapping::circuit_input_builder::{CopyDataType, NumberOrHash};
     use eth_types::{evm_types::GasCost, Field, Word};
     use halo2_proofs::{
         arithmetic::BaseExt,
@@ -149,12 +191,91 @@ pub mod test {
         Fp::rand()
     }
 
+    /// Make the [`CopyEvent`] of a copy of `length` bytes starting at
+    /// `src_addr` of the source `buffer`, which starts at `buffer_addr`, into
+    /// the memory of the call `dst_id` starting at `dst_addr`, or into an RLC.
+    /// The memory accesses of the copy are pushed into `rws` from
+    /// `rw_counter`, and their indices into `rw_indices`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn make_copy_event(
+        src_type: CopyDataType,
+        src_id: NumberOrHash,
+        buffer: &[u8],
+        buffer_addr: u64,
+        src_addr: u64,
+        dst_type: CopyDataType,
+        dst_id: usize,
+        dst_addr: u64,
+        length: usize,
+        rw_counter: &mut usize,
+        rws: &mut RwMap,
+        rw_indices: &mut Vec<(RwTableTag, usize)>,
+    ) -> CopyEvent {
+        let src_addr_end = buffer_addr + buffer.len() as u64;
+        let rw_counter_start = *rw_counter;
+        let memory_rws = rws.0.entry(RwTableTag::Memory).or_insert_with(Vec::new);
+
+        let mut bytes = Vec::with_capacity(length);
+        for idx in 0..length as u64 {
+            let addr = src_addr + idx;
+            let byte = if addr < src_addr_end {
+                let byte = buffer[(addr - buffer_addr) as usize];
+                if let (CopyDataType::Memory, NumberOrHash::Number(call_id)) = (src_type, src_id) {
+                    rw_indices.push((RwTableTag::Memory, memory_rws.len()));
+                    memory_rws.push(Rw::Memory {
+                        rw_counter: *rw_counter,
+                        is_write: false,
+                        call_id,
+                        memory_address: addr,
+                        byte,
+                    });
+                    *rw_counter += 1;
+                }
+                byte
+            } else {
+                0
+            };
+            if dst_type == CopyDataType::Memory {
+                rw_indices.push((RwTableTag::Memory, memory_rws.len()));
+                memory_rws.push(Rw::Memory {
+                    rw_counter: *rw_counter,
+                    is_write: true,
+                    call_id: dst_id,
+                    memory_address: dst_addr + idx,
+                    byte,
+                });
+                *rw_counter += 1;
+            }
+            bytes.push(byte);
+        }
+
+        CopyEvent {
+            src_type,
+            src_id,
+            src_addr: src_addr.min(src_addr_end),
+            src_addr_end,
+            dst_type,
+            dst_id: NumberOrHash::Number(dst_id),
+            dst_addr,
+            rw_counter_start,
+            bytes,
+        }
+    }
+
     #[derive(Clone)]
     pub struct TestCircuitConfig<F> {
         tx_table: [Column<Advice>; 4],
         rw_table: RwTable,
-        bytecode_table: [Column<Advice>; 4],
+        bytecode_table: [Column<Advice>; 5],
         block_table: [Column<Advice>; 3],
+        keccak_table: [Column<Advice>; 3],
+        ecrecover_table: [Column<Advice>; 6],
+        sha256_table: [Column<Advice>; 3],
+        ripemd160_table: [Column<Advice>; 3],
+        ecc_table: [Column<Advice>; 5],
+        modexp_table: [Column<Advice>; 4],
+        blake2f_table: [Column<Advice>; 3],
+        copy_table: [Column<Advice>; 12],
         evm_circuit: EvmCircuit<F>,
     }
 
@@ -298,6 +419,197 @@ pub mod test {
                 },
             )
         }
+
+        fn load_ecrecovers(
+            &self,
+            layouter: &mut impl Layouter<F>,
+            ecrecovers: &[EcRecover],
+            randomness: F,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "ecrecover table",
+                |mut region| {
+                    let mut offset = 0;
+                    for column in self.ecrecover_table {
+                        region.assign_advice(
+                            || "ecrecover table all-zero row",
+                            column,
+                            offset,
+                            || Ok(F::zero()),
+                        )?;
+                    }
+                    offset += 1;
+
+                    for ecrecover in ecrecovers.iter() {
+                        let row = ecrecover.table_assignment(randomness);
+                        for (column, value) in self.ecrecover_table.iter().zip(row) {
+                            region.assign_advice(
+                                || format!("ecrecover table row {}", offset),
+                                *column,
+                                offset,
+                                || Ok(value),
+                            )?;
+                        }
+                        offset += 1;
+                    }
+
+                    Ok(())
+                },
+            )
+        }
+
+        fn load_ecc_calls(
+            &self,
+            layouter: &mut impl Layouter<F>,
+            ecc_calls: &[EccCall],
+            randomness: F,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "ecc table",
+                |mut region| {
+                    let mut offset = 0;
+                    for column in self.ecc_table {
+                        region.assign_advice(
+                            || "ecc table all-zero row",
+                            column,
+                            offset,
+                            || Ok(F::zero()),
+                        )?;
+                    }
+                    offset += 1;
+
+                    for ecc_call in ecc_calls.iter() {
+                        let row = ecc_call.table_assignment(randomness);
+                        for (column, value) in self.ecc_table.iter().zip(row) {
+                            region.assign_advice(
+                                || format!("ecc table row {}", offset),
+                                *column,
+                                offset,
+                                || Ok(value),
+                            )?;
+                        }
+                        offset += 1;
+                    }
+
+                    Ok(())
+                },
+            )
+        }
+
+        fn load_modexp_calls(
+            &self,
+            layouter: &mut impl Layouter<F>,
+            modexp_calls: &[ModexpCall],
+            randomness: F,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "modexp table",
+                |mut region| {
+                    let mut offset = 0;
+                    for column in self.modexp_table {
+                        region.assign_advice(
+                            || "modexp table all-zero row",
+                            column,
+                            offset,
+                            || Ok(F::zero()),
+                        )?;
+                    }
+                    offset += 1;
+
+                    for modexp_call in modexp_calls.iter() {
+                        let row = modexp_call.table_assignment(randomness);
+                        for (column, value) in self.modexp_table.iter().zip(row) {
+                            region.assign_advice(
+                                || format!("modexp table row {}", offset),
+                                *column,
+                                offset,
+                                || Ok(value),
+                            )?;
+                        }
+                        offset += 1;
+                    }
+
+                    Ok(())
+                },
+            )
+        }
+
+        fn load_hashes(
+            &self,
+            layouter: &mut impl Layouter<F>,
+            name: &str,
+            hash_table: [Column<Advice>; 3],
+            rows: &[[F; 3]],
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || format!("{} table", name),
+                |mut region| {
+                    let mut offset = 0;
+                    for column in hash_table {
+                        region.assign_advice(
+                            || format!("{} table all-zero row", name),
+                            column,
+                            offset,
+                            || Ok(F::zero()),
+                        )?;
+                    }
+                    offset += 1;
+
+                    for row in rows.iter() {
+                        for (column, value) in hash_table.iter().zip(row) {
+                            region.assign_advice(
+                                || format!("{} table row {}", name, offset),
+                                *column,
+                                offset,
+                                || Ok(*value),
+                            )?;
+                        }
+                        offset += 1;
+                    }
+
+                    Ok(())
+                },
+            )
+        }
+
+        fn load_copy_events(
+            &self,
+            layouter: &mut impl Layouter<F>,
+            copy_events: &[CopyEvent],
+            randomness: F,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "copy table",
+                |mut region| {
+                    let mut offset = 0;
+                    for column in self.copy_table {
+                        region.assign_advice(
+                            || "copy table all-zero row",
+                            column,
+                            offset,
+                            || Ok(F::zero()),
+                        )?;
+                    }
+                    offset += 1;
+
+                    for copy_event in copy_events.iter() {
+                        for row in copy_event.table_assignments(randomness) {
+                            for (column, value) in self.copy_table.iter().zip(row) {
+                                region.assign_advice(
+                                    || format!("copy table row {}", offset),
+                                    *column,
+                                    offset,
+                                    || Ok(value),
+                                )?;
+                            }
+                            offset += 1;
+                        }
+                    }
+
+                    Ok(())
+                },
+            )
+        }
     }
 
     #[derive(Default)]
@@ -326,8 +638,16 @@ pub mod test {
         fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
             let tx_table = [(); 4].map(|_| meta.advice_column());
             let rw_table = RwTable::construct(meta);
-            let bytecode_table = [(); 4].map(|_| meta.advice_column());
+            let bytecode_table = [(); 5].map(|_| meta.advice_column());
             let block_table = [(); 3].map(|_| meta.advice_column());
+            let keccak_table = [(); 3].map(|_| meta.advice_column());
+            let ecrecover_table = [(); 6].map(|_| meta.advice_column());
+            let sha256_table = [(); 3].map(|_| meta.advice_column());
+            let ripemd160_table = [(); 3].map(|_| meta.advice_column());
+            let ecc_table = [(); 5].map(|_| meta.advice_column());
+            let modexp_table = [(); 4].map(|_| meta.advice_column());
+            let blake2f_table = [(); 3].map(|_| meta.advice_column());
+            let copy_table = [(); 12].map(|_| meta.advice_column());
 
             let power_of_randomness = {
                 let columns = [(); 31].map(|_| meta.instance_column());
@@ -348,6 +668,14 @@ pub mod test {
                 rw_table,
                 bytecode_table,
                 block_table,
+                keccak_table,
+                ecrecover_table,
+                sha256_table,
+                ripemd160_table,
+                ecc_table,
+                modexp_table,
+                blake2f_table,
+                copy_table,
                 evm_circuit: EvmCircuit::configure(
                     meta,
                     power_of_randomness,
@@ -355,6 +683,14 @@ pub mod test {
                     rw_table,
                     bytecode_table,
                     block_table,
+                    keccak_table,
+                    ecrecover_table,
+                    sha256_table,
+                    ripemd160_table,
+                    ecc_table,
+                    modexp_table,
+                    blake2f_table,
+                    copy_table,
                 ),
             }
         }
@@ -371,6 +707,63 @@ pub mod test {
             config.load_rws(&mut layouter, &self.block.rws, self.block.randomness)?;
             config.load_bytecodes(&mut layouter, &self.block.bytecodes, self.block.randomness)?;
             config.load_block(&mut layouter, &self.block.context, self.block.randomness)?;
+            config.load_hashes(
+                &mut layouter,
+                "keccak",
+                config.keccak_table,
+                &self
+                    .block
+                    .keccak_inputs
+                    .iter()
+                    .map(|input| keccak_table_row(input, self.block.randomness))
+                    .collect::<Vec<_>>(),
+            )?;
+            config.load_ecrecovers(&mut layouter, &self.block.ecrecovers, self.block.randomness)?;
+            config.load_hashes(
+                &mut layouter,
+                "sha256",
+                config.sha256_table,
+                &self
+                    .block
+                    .sha256_inputs
+                    .iter()
+                    .map(|input| sha256::circuit::hash_table_row(input, self.block.randomness))
+                    .collect::<Vec<_>>(),
+            )?;
+            config.load_hashes(
+                &mut layouter,
+                "ripemd160",
+                config.ripemd160_table,
+                &self
+                    .block
+                    .ripemd160_inputs
+                    .iter()
+                    .map(|input| ripemd160::circuit::hash_table_row(input, self.block.randomness))
+                    .collect::<Vec<_>>(),
+            )?;
+            config.load_ecc_calls(&mut layouter, &self.block.ecc_calls, self.block.randomness)?;
+            config.load_modexp_calls(
+                &mut layouter,
+                &self.block.modexp_calls,
+                self.block.randomness,
+            )?;
+            // The BLAKE2F table has the same width as the hash tables
+            config.load_hashes(
+                &mut layouter,
+                "blake2f",
+                config.blake2f_table,
+                &self
+                    .block
+                    .blake2f_inputs
+                    .iter()
+                    .map(|input| blake2f::circuit::blake2f_table_row(input, self.block.randomness))
+                    .collect::<Vec<_>>(),
+            )?;
+            config.load_copy_events(
+                &mut layouter,
+                &self.block.copy_events,
+                self.block.randomness,
+            )?;
             config
                 .evm_circuit
                 .assign_block_exact(&mut layouter, &self.block)
@@ -396,6 +789,16 @@ pub mod test {
                 .map(|bytecode| bytecode.bytes.len())
                 .sum::<usize>(),
         ));
+        let k = k.max(log2_ceil(
+            64 + block
+                .copy_events
+                .iter()
+                .map(|copy_event| copy_event.bytes.len())
+                .sum::<usize>(),
+        ));
+        let k = k.max(log2_ceil(
+            64 + block.txs.iter().map(|tx| tx.steps.len()).sum::<usize>() * STEP_HEIGHT,
+        ));
 
         let power_of_randomness = (1..32)
             .map(|exp| {
@@ -421,8 +824,12 @@ pub mod test {
                 FixedTableTag::Range32,
                 FixedTableTag::Range256,
                 FixedTableTag::Range512,
+                FixedTableTag::Range5,
                 FixedTableTag::SignByte,
                 FixedTableTag::ResponsibleOpcode,
+                FixedTableTag::StackPointerRange,
+                FixedTableTag::InvalidOpcode,
+                FixedTableTag::ConstantGasCost,
             ],
         )
     }
@@ -18,59 +18,139 @@ use halo2_proofs::{
 use std::collections::HashMap;
 
 mod add;
+mod address;
+mod balance;
+mod basefee;
 mod begin_tx;
 mod bitwise;
+mod blockhash;
 mod byte;
 mod calldatacopy;
+mod calldataload;
 mod calldatasize;
 mod caller;
 mod callvalue;
+mod chainid;
+mod codecopy;
+mod codesize;
 mod coinbase;
 mod comparator;
+mod difficulty;
 mod dup;
+mod end_tx;
+mod error_contract_address_collision;
+mod error_depth;
+mod error_insufficient_balance;
+mod error_invalid_creation_code;
+mod error_invalid_jump;
+mod error_invalid_opcode;
+mod error_max_code_size_exceeded;
+mod error_oog_call;
+mod error_oog_code_store;
+mod error_oog_constant;
+mod error_oog_create2;
+mod error_oog_log;
+mod error_oog_memory_copy;
 mod error_oog_pure_memory;
+mod error_oog_sha3;
+mod error_return_data_out_of_bound;
+mod error_stack_overflow;
+mod error_stack_underflow;
+mod error_write_protection;
+mod extcodecopy;
+mod extcodehash;
+mod extcodesize;
 mod gas;
+mod gaslimit;
+mod gasprice;
 mod jump;
 mod jumpdest;
 mod jumpi;
 mod memory;
-mod memory_copy;
 mod msize;
 mod mul;
+mod number;
+mod origin;
 mod pc;
 mod pop;
+mod precompile;
 mod push;
+mod returndatacopy;
+mod returndatasize;
+mod returnop;
 mod selfbalance;
+mod selfdestruct;
+mod sha3;
 mod signed_comparator;
 mod signextend;
 mod stop;
 mod swap;
 mod timestamp;
 
+use self::sha3::Sha3Gadget;
 use add::AddGadget;
+use address::AddressGadget;
+use balance::BalanceGadget;
+use basefee::BaseFeeGadget;
 use begin_tx::BeginTxGadget;
 use bitwise::BitwiseGadget;
+use blockhash::BlockHashGadget;
 use byte::ByteGadget;
 use calldatacopy::CallDataCopyGadget;
+use calldataload::CallDataLoadGadget;
 use calldatasize::CallDataSizeGadget;
 use caller::CallerGadget;
 use callvalue::CallValueGadget;
+use chainid::ChainIdGadget;
+use codecopy::CodeCopyGadget;
+use codesize::CodesizeGadget;
 use coinbase::CoinbaseGadget;
 use comparator::ComparatorGadget;
+use difficulty::DifficultyGadget;
 use dup::DupGadget;
+use end_tx::EndTxGadget;
+use error_contract_address_collision::ErrorContractAddressCollisionGadget;
+use error_depth::ErrorDepthGadget;
+use error_insufficient_balance::ErrorInsufficientBalanceGadget;
+use error_invalid_creation_code::ErrorInvalidCreationCodeGadget;
+use error_invalid_jump::ErrorInvalidJumpGadget;
+use error_invalid_opcode::ErrorInvalidOpcodeGadget;
+use error_max_code_size_exceeded::ErrorMaxCodeSizeExceededGadget;
+use error_oog_call::ErrorOOGCallGadget;
+use error_oog_code_store::ErrorOOGCodeStoreGadget;
+use error_oog_constant::ErrorOOGConstantGadget;
+use error_oog_create2::ErrorOOGCreate2Gadget;
+use error_oog_log::ErrorOOGLogGadget;
+use error_oog_memory_copy::ErrorOOGMemoryCopyGadget;
 use error_oog_pure_memory::ErrorOOGPureMemoryGadget;
+use error_oog_sha3::ErrorOOGSha3Gadget;
+use error_return_data_out_of_bound::ErrorReturnDataOutOfBoundGadget;
+use error_stack_overflow::ErrorStackOverflowGadget;
+use error_stack_underflow::ErrorStackUnderflowGadget;
+use error_write_protection::ErrorWriteProtectionGadget;
+use extcodecopy::ExtcodecopyGadget;
+use extcodehash::ExtcodehashGadget;
+use extcodesize::ExtcodesizeGadget;
 use gas::GasGadget;
+use gaslimit::GasLimitGadget;
+use gasprice::GasPriceGadget;
 use jump::JumpGadget;
 use jumpdest::JumpdestGadget;
 use jumpi::JumpiGadget;
 use memory::MemoryGadget;
-use memory_copy::CopyToMemoryGadget;
 use msize::MsizeGadget;
 use mul::MulGadget;
+use number::NumberGadget;
+use origin::OriginGadget;
 use pc::PcGadget;
 use pop::PopGadget;
+use precompile::PrecompileGadget;
 use push::PushGadget;
+use returndatacopy::ReturnDataCopyGadget;
+use returndatasize::ReturnDataSizeGadget;
+use returnop::ReturnGadget;
 use selfbalance::SelfbalanceGadget;
+use selfdestruct::SelfdestructGadget;
 use signed_comparator::SignedComparatorGadget;
 use signextend::SignextendGadget;
 use stop::StopGadget;
@@ -104,21 +184,48 @@ pub(crate) struct ExecutionConfig<F> {
     add_gadget: AddGadget<F>,
     mul_gadget: MulGadget<F>,
     bitwise_gadget: BitwiseGadget<F>,
+    balance_gadget: BalanceGadget<F>,
     begin_tx_gadget: BeginTxGadget<F>,
+    end_tx_gadget: EndTxGadget<F>,
     byte_gadget: ByteGadget<F>,
     calldatacopy_gadget: CallDataCopyGadget<F>,
+    calldataload_gadget: CallDataLoadGadget<F>,
     calldatasize_gadget: CallDataSizeGadget<F>,
+    address_gadget: AddressGadget<F>,
     caller_gadget: CallerGadget<F>,
     call_value_gadget: CallValueGadget<F>,
+    codecopy_gadget: CodeCopyGadget<F>,
+    codesize_gadget: CodesizeGadget<F>,
     comparator_gadget: ComparatorGadget<F>,
     dup_gadget: DupGadget<F>,
+    error_contract_address_collision_gadget: ErrorContractAddressCollisionGadget<F>,
+    error_depth_gadget: ErrorDepthGadget<F>,
+    error_insufficient_balance_gadget: ErrorInsufficientBalanceGadget<F>,
+    error_invalid_creation_code_gadget: ErrorInvalidCreationCodeGadget<F>,
+    error_invalid_jump_gadget: ErrorInvalidJumpGadget<F>,
+    error_invalid_opcode_gadget: ErrorInvalidOpcodeGadget<F>,
+    error_max_code_size_exceeded_gadget: ErrorMaxCodeSizeExceededGadget<F>,
+    error_oog_call_gadget: ErrorOOGCallGadget<F>,
+    error_oog_code_store_gadget: ErrorOOGCodeStoreGadget<F>,
+    error_oog_constant_gadget: ErrorOOGConstantGadget<F>,
+    error_oog_create2_gadget: ErrorOOGCreate2Gadget<F>,
+    error_oog_log_gadget: ErrorOOGLogGadget<F>,
+    error_oog_memory_copy_gadget: ErrorOOGMemoryCopyGadget<F>,
     error_oog_pure_memory_gadget: ErrorOOGPureMemoryGadget<F>,
+    error_oog_sha3_gadget: ErrorOOGSha3Gadget<F>,
+    error_return_data_out_of_bound_gadget: ErrorReturnDataOutOfBoundGadget<F>,
+    error_stack_overflow_gadget: ErrorStackOverflowGadget<F>,
+    error_stack_underflow_gadget: ErrorStackUnderflowGadget<F>,
+    error_write_protection_gadget: ErrorWriteProtectionGadget<F>,
+    extcodecopy_gadget: ExtcodecopyGadget<F>,
+    extcodehash_gadget: ExtcodehashGadget<F>,
+    extcodesize_gadget: ExtcodesizeGadget<F>,
     jump_gadget: JumpGadget<F>,
     jumpdest_gadget: JumpdestGadget<F>,
     jumpi_gadget: JumpiGadget<F>,
     gas_gadget: GasGadget<F>,
     memory_gadget: MemoryGadget<F>,
-    copy_to_memory_gadget: CopyToMemoryGadget<F>,
+    precompile_gadget: PrecompileGadget<F>,
     pc_gadget: PcGadget<F>,
     pop_gadget: PopGadget<F>,
     push_gadget: PushGadget<F>,
@@ -129,11 +236,38 @@ pub(crate) struct ExecutionConfig<F> {
     msize_gadget: MsizeGadget<F>,
     coinbase_gadget: CoinbaseGadget<F>,
     timestamp_gadget: TimestampGadget<F>,
+    blockhash_gadget: BlockHashGadget<F>,
+    number_gadget: NumberGadget<F>,
+    difficulty_gadget: DifficultyGadget<F>,
+    gaslimit_gadget: GasLimitGadget<F>,
+    chainid_gadget: ChainIdGadget<F>,
+    basefee_gadget: BaseFeeGadget<F>,
     selfbalance_gadget: SelfbalanceGadget<F>,
+    selfdestruct_gadget: SelfdestructGadget<F>,
+    sha3_gadget: Sha3Gadget<F>,
+    origin_gadget: OriginGadget<F>,
+    gasprice_gadget: GasPriceGadget<F>,
+    returndatasize_gadget: ReturnDataSizeGadget<F>,
+    returndatacopy_gadget: ReturnDataCopyGadget<F>,
+    return_gadget: ReturnGadget<F>,
 }
 
 impl<F: Field> ExecutionConfig<F> {
-    pub(crate) fn configure<TxTable, RwTable, BytecodeTable, BlockTable>(
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn configure<
+        TxTable,
+        RwTable,
+        BytecodeTable,
+        BlockTable,
+        KeccakTable,
+        EcRecoverTable,
+        Sha256Table,
+        Ripemd160Table,
+        EccTable,
+        ModexpTable,
+        Blake2fTable,
+        CopyTable,
+    >(
         meta: &mut ConstraintSystem<F>,
         power_of_randomness: [Expression<F>; 31],
         fixed_table: [Column<Fixed>; 4],
@@ -141,12 +275,28 @@ impl<F: Field> ExecutionConfig<F> {
         rw_table: RwTable,
         bytecode_table: BytecodeTable,
         block_table: BlockTable,
+        keccak_table: KeccakTable,
+        ecrecover_table: EcRecoverTable,
+        sha256_table: Sha256Table,
+        ripemd160_table: Ripemd160Table,
+        ecc_table: EccTable,
+        modexp_table: ModexpTable,
+        blake2f_table: Blake2fTable,
+        copy_table: CopyTable,
     ) -> Self
     where
         TxTable: LookupTable<F, 4>,
         RwTable: LookupTable<F, 10>,
-        BytecodeTable: LookupTable<F, 4>,
+        BytecodeTable: LookupTable<F, 5>,
         BlockTable: LookupTable<F, 3>,
+        KeccakTable: LookupTable<F, 3>,
+        EcRecoverTable: LookupTable<F, 6>,
+        Sha256Table: LookupTable<F, 3>,
+        Ripemd160Table: LookupTable<F, 3>,
+        EccTable: LookupTable<F, 5>,
+        ModexpTable: LookupTable<F, 4>,
+        Blake2fTable: LookupTable<F, 3>,
+        CopyTable: LookupTable<F, 12>,
     {
         let q_step = meta.complex_selector();
         let q_step_first = meta.complex_selector();
@@ -238,25 +388,59 @@ impl<F: Field> ExecutionConfig<F> {
             add_gadget: configure_gadget!(),
             mul_gadget: configure_gadget!(),
             bitwise_gadget: configure_gadget!(),
+            balance_gadget: configure_gadget!(),
             begin_tx_gadget: configure_gadget!(),
+            end_tx_gadget: configure_gadget!(),
             byte_gadget: configure_gadget!(),
             calldatacopy_gadget: configure_gadget!(),
+            calldataload_gadget: configure_gadget!(),
             calldatasize_gadget: configure_gadget!(),
+            address_gadget: configure_gadget!(),
             caller_gadget: configure_gadget!(),
             call_value_gadget: configure_gadget!(),
+            codecopy_gadget: configure_gadget!(),
+            codesize_gadget: configure_gadget!(),
             comparator_gadget: configure_gadget!(),
             dup_gadget: configure_gadget!(),
+            error_contract_address_collision_gadget: configure_gadget!(),
+            error_depth_gadget: configure_gadget!(),
+            error_insufficient_balance_gadget: configure_gadget!(),
+            error_invalid_creation_code_gadget: configure_gadget!(),
+            error_invalid_jump_gadget: configure_gadget!(),
+            error_invalid_opcode_gadget: configure_gadget!(),
+            error_max_code_size_exceeded_gadget: configure_gadget!(),
+            error_oog_call_gadget: configure_gadget!(),
+            error_oog_code_store_gadget: configure_gadget!(),
+            error_oog_constant_gadget: configure_gadget!(),
+            error_oog_create2_gadget: configure_gadget!(),
+            error_oog_log_gadget: configure_gadget!(),
+            error_oog_memory_copy_gadget: configure_gadget!(),
             error_oog_pure_memory_gadget: configure_gadget!(),
+            error_oog_sha3_gadget: configure_gadget!(),
+            error_return_data_out_of_bound_gadget: configure_gadget!(),
+            error_stack_overflow_gadget: configure_gadget!(),
+            error_stack_underflow_gadget: configure_gadget!(),
+            error_write_protection_gadget: configure_gadget!(),
+            extcodecopy_gadget: configure_gadget!(),
+            extcodehash_gadget: configure_gadget!(),
+            extcodesize_gadget: configure_gadget!(),
             jump_gadget: configure_gadget!(),
             jumpdest_gadget: configure_gadget!(),
             jumpi_gadget: configure_gadget!(),
             gas_gadget: configure_gadget!(),
             memory_gadget: configure_gadget!(),
-            copy_to_memory_gadget: configure_gadget!(),
+            precompile_gadget: configure_gadget!(),
             pc_gadget: configure_gadget!(),
             pop_gadget: configure_gadget!(),
             push_gadget: configure_gadget!(),
             selfbalance_gadget: configure_gadget!(),
+            selfdestruct_gadget: configure_gadget!(),
+            sha3_gadget: configure_gadget!(),
+            origin_gadget: configure_gadget!(),
+            gasprice_gadget: configure_gadget!(),
+            returndatasize_gadget: configure_gadget!(),
+            returndatacopy_gadget: configure_gadget!(),
+            return_gadget: configure_gadget!(),
             signed_comparator_gadget: configure_gadget!(),
             signextend_gadget: configure_gadget!(),
             stop_gadget: configure_gadget!(),
@@ -264,6 +448,12 @@ impl<F: Field> ExecutionConfig<F> {
             msize_gadget: configure_gadget!(),
             coinbase_gadget: configure_gadget!(),
             timestamp_gadget: configure_gadget!(),
+            blockhash_gadget: configure_gadget!(),
+            number_gadget: configure_gadget!(),
+            difficulty_gadget: configure_gadget!(),
+            gaslimit_gadget: configure_gadget!(),
+            chainid_gadget: configure_gadget!(),
+            basefee_gadget: configure_gadget!(),
             step: step_curr,
             presets_map,
         };
@@ -276,6 +466,14 @@ impl<F: Field> ExecutionConfig<F> {
             rw_table,
             bytecode_table,
             block_table,
+            keccak_table,
+            ecrecover_table,
+            sha256_table,
+            ripemd160_table,
+            ecc_table,
+            modexp_table,
+            blake2f_table,
+            copy_table,
             independent_lookups,
         );
 
@@ -331,7 +529,20 @@ impl<F: Field> ExecutionConfig<F> {
     }
 
     #[allow(clippy::too_many_arguments)]
-    fn configure_lookup<TxTable, RwTable, BytecodeTable, BlockTable>(
+    fn configure_lookup<
+        TxTable,
+        RwTable,
+        BytecodeTable,
+        BlockTable,
+        KeccakTable,
+        EcRecoverTable,
+        Sha256Table,
+        Ripemd160Table,
+        EccTable,
+        ModexpTable,
+        Blake2fTable,
+        CopyTable,
+    >(
         meta: &mut ConstraintSystem<F>,
         q_step: Selector,
         fixed_table: [Column<Fixed>; 4],
@@ -339,12 +550,28 @@ impl<F: Field> ExecutionConfig<F> {
         rw_table: RwTable,
         bytecode_table: BytecodeTable,
         block_table: BlockTable,
+        keccak_table: KeccakTable,
+        ecrecover_table: EcRecoverTable,
+        sha256_table: Sha256Table,
+        ripemd160_table: Ripemd160Table,
+        ecc_table: EccTable,
+        modexp_table: ModexpTable,
+        blake2f_table: Blake2fTable,
+        copy_table: CopyTable,
         independent_lookups: Vec<Vec<Lookup<F>>>,
     ) where
         TxTable: LookupTable<F, 4>,
         RwTable: LookupTable<F, 10>,
-        BytecodeTable: LookupTable<F, 4>,
+        BytecodeTable: LookupTable<F, 5>,
         BlockTable: LookupTable<F, 3>,
+        KeccakTable: LookupTable<F, 3>,
+        EcRecoverTable: LookupTable<F, 6>,
+        Sha256Table: LookupTable<F, 3>,
+        Ripemd160Table: LookupTable<F, 3>,
+        EccTable: LookupTable<F, 5>,
+        ModexpTable: LookupTable<F, 4>,
+        Blake2fTable: LookupTable<F, 3>,
+        CopyTable: LookupTable<F, 12>,
     {
         // Because one and only one ExecutionState is enabled at a step, we then
         // know only one of independent_lookups will be enabled at a step, so we
@@ -398,6 +625,14 @@ impl<F: Field> ExecutionConfig<F> {
         lookup!(Table::Rw, rw_table, "RW table");
         lookup!(Table::Bytecode, bytecode_table, "Bytecode table");
         lookup!(Table::Block, block_table, "Block table");
+        lookup!(Table::Keccak, keccak_table, "Keccak table");
+        lookup!(Table::EcRecover, ecrecover_table, "EcRecover table");
+        lookup!(Table::Sha256, sha256_table, "Sha256 table");
+        lookup!(Table::Ripemd160, ripemd160_table, "Ripemd160 table");
+        lookup!(Table::Ecc, ecc_table, "Ecc table");
+        lookup!(Table::Modexp, modexp_table, "Modexp table");
+        lookup!(Table::Blake2f, blake2f_table, "Blake2f table");
+        lookup!(Table::Copy, copy_table, "Copy table");
     }
 
     pub fn assign_block(
@@ -485,6 +720,7 @@ impl<F: Field> ExecutionConfig<F> {
 
         match step.execution_state {
             ExecutionState::BeginTx => assign_exec_step!(self.begin_tx_gadget),
+            ExecutionState::EndTx => assign_exec_step!(self.end_tx_gadget),
             ExecutionState::STOP => assign_exec_step!(self.stop_gadget),
             ExecutionState::ADD => assign_exec_step!(self.add_gadget),
             ExecutionState::MUL => assign_exec_step!(self.mul_gadget),
@@ -510,6 +746,7 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::PUSH => assign_exec_step!(self.push_gadget),
             ExecutionState::DUP => assign_exec_step!(self.dup_gadget),
             ExecutionState::SWAP => assign_exec_step!(self.swap_gadget),
+            ExecutionState::ADDRESS => assign_exec_step!(self.address_gadget),
             ExecutionState::CALLER => assign_exec_step!(self.caller_gadget),
             ExecutionState::CALLVALUE => {
                 assign_exec_step!(self.call_value_gadget)
@@ -518,19 +755,107 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::TIMESTAMP => {
                 assign_exec_step!(self.timestamp_gadget)
             }
+            ExecutionState::BLOCKHASH => assign_exec_step!(self.blockhash_gadget),
+            ExecutionState::NUMBER => assign_exec_step!(self.number_gadget),
+            ExecutionState::DIFFICULTY => {
+                assign_exec_step!(self.difficulty_gadget)
+            }
+            ExecutionState::GASLIMIT => assign_exec_step!(self.gaslimit_gadget),
+            ExecutionState::CHAINID => assign_exec_step!(self.chainid_gadget),
+            ExecutionState::BASEFEE => assign_exec_step!(self.basefee_gadget),
             ExecutionState::SELFBALANCE => assign_exec_step!(self.selfbalance_gadget),
+            ExecutionState::SELFDESTRUCT => {
+                assign_exec_step!(self.selfdestruct_gadget)
+            }
+            ExecutionState::SHA3 => assign_exec_step!(self.sha3_gadget),
+            ExecutionState::ORIGIN => assign_exec_step!(self.origin_gadget),
+            ExecutionState::GASPRICE => assign_exec_step!(self.gasprice_gadget),
             ExecutionState::CALLDATACOPY => {
                 assign_exec_step!(self.calldatacopy_gadget)
             }
-            ExecutionState::CopyToMemory => {
-                assign_exec_step!(self.copy_to_memory_gadget)
+            ExecutionState::CALLDATALOAD => {
+                assign_exec_step!(self.calldataload_gadget)
+            }
+            ExecutionState::CODESIZE => assign_exec_step!(self.codesize_gadget),
+            ExecutionState::CODECOPY => assign_exec_step!(self.codecopy_gadget),
+            ExecutionState::Precompile => assign_exec_step!(self.precompile_gadget),
+            ExecutionState::EXTCODESIZE => {
+                assign_exec_step!(self.extcodesize_gadget)
+            }
+            ExecutionState::EXTCODECOPY => {
+                assign_exec_step!(self.extcodecopy_gadget)
+            }
+            ExecutionState::BALANCE => assign_exec_step!(self.balance_gadget),
+            ExecutionState::EXTCODEHASH => {
+                assign_exec_step!(self.extcodehash_gadget)
             }
             ExecutionState::ErrorOutOfGasPureMemory => {
                 assign_exec_step!(self.error_oog_pure_memory_gadget)
             }
+            ExecutionState::ErrorOutOfGasConstant => {
+                assign_exec_step!(self.error_oog_constant_gadget)
+            }
+            ExecutionState::ErrorOutOfGasMemoryCopy => {
+                assign_exec_step!(self.error_oog_memory_copy_gadget)
+            }
+            ExecutionState::ErrorOutOfGasSHA3 => {
+                assign_exec_step!(self.error_oog_sha3_gadget)
+            }
+            ExecutionState::ErrorOutOfGasLOG => {
+                assign_exec_step!(self.error_oog_log_gadget)
+            }
+            ExecutionState::ErrorOutOfGasCall => {
+                assign_exec_step!(self.error_oog_call_gadget)
+            }
+            ExecutionState::ErrorOutOfGasCREATE2 => {
+                assign_exec_step!(self.error_oog_create2_gadget)
+            }
+            ExecutionState::ErrorOutOfGasCodeStore => {
+                assign_exec_step!(self.error_oog_code_store_gadget)
+            }
             ExecutionState::CALLDATASIZE => {
                 assign_exec_step!(self.calldatasize_gadget)
             }
+            ExecutionState::RETURNDATASIZE => {
+                assign_exec_step!(self.returndatasize_gadget)
+            }
+            ExecutionState::RETURNDATACOPY => {
+                assign_exec_step!(self.returndatacopy_gadget)
+            }
+            ExecutionState::RETURN => assign_exec_step!(self.return_gadget),
+            ExecutionState::ErrorReturnDataOutOfBound => {
+                assign_exec_step!(self.error_return_data_out_of_bound_gadget)
+            }
+            ExecutionState::ErrorStackOverflow => {
+                assign_exec_step!(self.error_stack_overflow_gadget)
+            }
+            ExecutionState::ErrorStackUnderflow => {
+                assign_exec_step!(self.error_stack_underflow_gadget)
+            }
+            ExecutionState::ErrorInvalidJump => {
+                assign_exec_step!(self.error_invalid_jump_gadget)
+            }
+            ExecutionState::ErrorInvalidOpcode => {
+                assign_exec_step!(self.error_invalid_opcode_gadget)
+            }
+            ExecutionState::ErrorWriteProtection => {
+                assign_exec_step!(self.error_write_protection_gadget)
+            }
+            ExecutionState::ErrorDepth => {
+                assign_exec_step!(self.error_depth_gadget)
+            }
+            ExecutionState::ErrorInsufficientBalance => {
+                assign_exec_step!(self.error_insufficient_balance_gadget)
+            }
+            ExecutionState::ErrorContractAddressCollision => {
+                assign_exec_step!(self.error_contract_address_collision_gadget)
+            }
+            ExecutionState::ErrorMaxCodeSizeExceeded => {
+                assign_exec_step!(self.error_max_code_size_exceeded_gadget)
+            }
+            ExecutionState::ErrorInvalidCreationCode => {
+                assign_exec_step!(self.error_invalid_creation_code_gadget)
+            }
             _ => unimplemented!(),
         }
 
@@ -0,0 +1,158 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_ACCOUNT_ADDRESS,
+        step::ExecutionState,
+        table::{AccountFieldTag, CallContextFieldTag},
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
+            from_bytes, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct BalanceGadget<F> {
+    same_context: SameContextGadget<F>,
+    address: Word<F>,
+    tx_id: Cell<F>,
+    rw_counter_end_of_reversion: Cell<F>,
+    is_persistent: Cell<F>,
+    is_warm: Cell<F>,
+    balance: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for BalanceGadget<F> {
+    const NAME: &'static str = "BALANCE";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::BALANCE;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        // Pop the address from stack, of which only the lower 20 bytes are used
+        let address = cb.query_word();
+        cb.stack_pop(address.expr());
+        let address_expr = from_bytes::expr(&address.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let rw_counter_end_of_reversion =
+            cb.call_context(None, CallContextFieldTag::RwCounterEndOfReversion);
+        let is_persistent = cb.call_context(None, CallContextFieldTag::IsPersistent);
+
+        // Add the account into the access list
+        let is_warm = cb.query_bool();
+        cb.account_access_list_write_with_reversion(
+            tx_id.expr(),
+            address_expr.clone(),
+            1.expr(),
+            is_warm.expr(),
+            is_persistent.expr(),
+            rw_counter_end_of_reversion.expr(),
+        );
+
+        // Read the balance of the account and push it on the stack
+        let balance = cb.query_cell();
+        cb.account_read(address_expr, AccountFieldTag::Balance, balance.expr());
+        cb.stack_push(balance.expr());
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            // 1 stack pop + 3 call context reads + 1 access list write + 1
+            // account read + 1 stack push
+            rw_counter: Delta(7.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(0.expr()),
+            state_write_counter: Delta(1.expr()),
+            ..Default::default()
+        };
+        let dynamic_gas_cost = (1.expr() - is_warm.expr())
+            * (GasCost::COLD_ACCOUNT_ACCESS_COST.expr() - GasCost::WARM_STORAGE_READ_COST.expr());
+        let same_context =
+            SameContextGadget::construct(cb, opcode, step_state_transition, Some(dynamic_gas_cost));
+
+        Self {
+            same_context,
+            address,
+            tx_id,
+            rw_counter_end_of_reversion,
+            is_persistent,
+            is_warm,
+            balance,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let address = block.rws[step.rw_indices[0]].stack_value();
+        self.address
+            .assign(region, offset, Some(address.to_le_bytes()))?;
+
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx.id as u64)))?;
+        self.rw_counter_end_of_reversion.assign(
+            region,
+            offset,
+            Some(F::from(call.rw_counter_end_of_reversion as u64)),
+        )?;
+        self.is_persistent
+            .assign(region, offset, Some(F::from(call.is_persistent as u64)))?;
+
+        let (_, is_warm) = block.rws[step.rw_indices[4]].tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Some(F::from(is_warm as u64)))?;
+
+        let (balance, _) = block.rws[step.rw_indices[5]].account_value_pair();
+        self.balance.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                balance.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, Word};
+
+    fn test_ok(address: Word) {
+        let bytecode = bytecode! {
+            PUSH32(address)
+            #[start]
+            BALANCE
+            STOP
+        };
+        assert_eq!(run_test_circuits(bytecode), Ok(()));
+    }
+
+    #[test]
+    fn balance_gadget_warm_account() {
+        // The callee of the mock tx is always in the access list
+        test_ok(Word::zero());
+    }
+
+    #[test]
+    fn balance_gadget_cold_account() {
+        test_ok(Word::from(0x123));
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        table::BlockContextFieldTag,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
+            Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{Field, ToLittleEndian};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct BaseFeeGadget<F> {
+    same_context: SameContextGadget<F>,
+    base_fee: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for BaseFeeGadget<F> {
+    const NAME: &'static str = "BASEFEE";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::BASEFEE;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let base_fee = cb.query_cell();
+        cb.stack_push(base_fee.expr());
+
+        // Lookup block table with base fee
+        cb.block_lookup(BlockContextFieldTag::BaseFee.expr(), None, base_fee.expr());
+
+        // State transition
+        let opcode = cb.query_cell();
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(1.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta((-1).expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition, None);
+
+        Self {
+            same_context,
+            base_fee,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let base_fee = block.rws[step.rw_indices[0]].stack_value();
+
+        self.base_fee.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                base_fee.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::bytecode;
+
+    fn test_ok() {
+        let bytecode = bytecode! {
+            #[start]
+            BASEFEE
+            STOP
+        };
+        assert_eq!(run_test_circuits(bytecode), Ok(()));
+    }
+    #[test]
+    fn basefee_gadget_test() {
+        test_ok();
+    }
+}
@@ -1,26 +1,224 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::{N_BYTES_GAS, STACK_CAPACITY},
+        param::{
+            EMPTY_CODE_HASH_LE, MAX_ACCESS_LIST_ADDRESSES, MAX_ACCESS_LIST_STORAGE_KEYS,
+            N_BYTES_GAS, STACK_CAPACITY,
+        },
         step::ExecutionState,
-        table::{AccountFieldTag, CallContextFieldTag, TxContextFieldTag},
+        table::{AccountFieldTag, BlockContextFieldTag, CallContextFieldTag, TxContextFieldTag},
         util::{
-            common_gadget::TransferWithGasFeeGadget,
+            common_gadget::{ContractAddressGadget, TransferWithGasFeeGadget},
             constraint_builder::{
                 ConstraintBuilder, StepStateTransition,
                 Transition::{Delta, To},
             },
-            math_gadget::{MulWordByU64Gadget, RangeCheckGadget},
-            select, Cell, RandomLinearCombination, Word,
+            math_gadget::{
+                AddWordsGadget, IsEqualGadget, IsZeroGadget, LtWordGadget, MulWordByU64Gadget,
+                RangeCheckGadget,
+            },
+            select, sum, Cell, RandomLinearCombination, Word,
         },
-        witness::{Block, Call, ExecStep, Transaction},
+        witness::{Block, Call, CodeSource, ExecStep, Transaction},
     },
     util::Expr,
 };
+use bus_mapping::precompile::PrecompileCalls;
 use eth_types::evm_types::GasCost;
 use eth_types::Field;
-use eth_types::{ToLittleEndian, ToScalar};
-use halo2_proofs::{circuit::Region, plonk::Error};
+use eth_types::{Address, ToLittleEndian, ToScalar, Word as EthWord, U256};
+use halo2_proofs::{
+    circuit::Region,
+    plonk::{Error, Expression},
+};
+
+/// Gadget of an address in the access list of the tx, which is pre-warmed
+/// when the entry is enabled.  The address is already warm when it's the
+/// caller, the callee or any previous address in the access list.
+#[derive(Clone, Debug)]
+struct AccessListAddressGadget<F> {
+    is_enabled: Cell<F>,
+    address: Cell<F>,
+    is_equal: Vec<IsEqualGadget<F>>,
+    is_cold: IsZeroGadget<F>,
+}
+
+impl<F: Field> AccessListAddressGadget<F> {
+    fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        tx_id: Expression<F>,
+        index: usize,
+        warm_addresses: &[Expression<F>],
+    ) -> Self {
+        let is_enabled = cb.query_bool();
+        let address = cb.query_cell();
+        let is_equal = warm_addresses
+            .iter()
+            .map(|warm_address| IsEqualGadget::construct(cb, address.expr(), warm_address.clone()))
+            .collect::<Vec<_>>();
+        let is_cold = IsZeroGadget::construct(
+            cb,
+            is_equal
+                .iter()
+                .fold(0.expr(), |acc, is_equal| acc + is_equal.expr()),
+        );
+
+        cb.condition(is_enabled.expr(), |cb| {
+            cb.tx_context_lookup(
+                tx_id.clone(),
+                TxContextFieldTag::AccessListAddress,
+                Some(index.expr()),
+                address.expr(),
+            );
+            cb.account_access_list_write(
+                tx_id,
+                address.expr(),
+                1.expr(),
+                1.expr() - is_cold.expr(),
+            );
+        });
+
+        Self {
+            is_enabled,
+            address,
+            is_equal,
+            is_cold,
+        }
+    }
+
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        address: Option<Address>,
+        warm_addresses: &[Address],
+    ) -> Result<(), Error> {
+        let value = address.unwrap_or_default();
+        self.is_enabled
+            .assign(region, offset, Some(F::from(address.is_some() as u64)))?;
+        self.address.assign(region, offset, value.to_scalar())?;
+        let mut num_equal = F::zero();
+        for (is_equal, warm_address) in self.is_equal.iter().zip(warm_addresses.iter()) {
+            num_equal += is_equal.assign(
+                region,
+                offset,
+                value.to_scalar().unwrap(),
+                warm_address.to_scalar().unwrap(),
+            )?;
+        }
+        self.is_cold.assign(region, offset, num_equal)?;
+        Ok(())
+    }
+}
+
+/// Gadget of a storage key in the access list of the tx, which is pre-warmed
+/// when the entry is enabled.  The storage key is already warm when any
+/// previous storage key in the access list has the same address and key.
+#[derive(Clone, Debug)]
+struct AccessListStorageKeyGadget<F> {
+    is_enabled: Cell<F>,
+    address: Cell<F>,
+    storage_key: Cell<F>,
+    is_equal_address: Vec<IsEqualGadget<F>>,
+    is_equal_storage_key: Vec<IsEqualGadget<F>>,
+    is_cold: IsZeroGadget<F>,
+}
+
+impl<F: Field> AccessListStorageKeyGadget<F> {
+    fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        tx_id: Expression<F>,
+        index: usize,
+        prev_entries: &[Self],
+    ) -> Self {
+        let is_enabled = cb.query_bool();
+        let address = cb.query_cell();
+        let storage_key = cb.query_cell();
+        let is_equal_address = prev_entries
+            .iter()
+            .map(|entry| IsEqualGadget::construct(cb, address.expr(), entry.address.expr()))
+            .collect::<Vec<_>>();
+        let is_equal_storage_key = prev_entries
+            .iter()
+            .map(|entry| IsEqualGadget::construct(cb, storage_key.expr(), entry.storage_key.expr()))
+            .collect::<Vec<_>>();
+        let is_cold = IsZeroGadget::construct(
+            cb,
+            is_equal_address
+                .iter()
+                .zip(is_equal_storage_key.iter())
+                .fold(0.expr(), |acc, (is_equal_address, is_equal_storage_key)| {
+                    acc + is_equal_address.expr() * is_equal_storage_key.expr()
+                }),
+        );
+
+        cb.condition(is_enabled.expr(), |cb| {
+            cb.tx_context_lookup(
+                tx_id.clone(),
+                TxContextFieldTag::AccessListStorageKeyAddress,
+                Some(index.expr()),
+                address.expr(),
+            );
+            cb.tx_context_lookup(
+                tx_id.clone(),
+                TxContextFieldTag::AccessListStorageKey,
+                Some(index.expr()),
+                storage_key.expr(),
+            );
+            cb.account_storage_access_list_write(
+                tx_id,
+                address.expr(),
+                storage_key.expr(),
+                1.expr(),
+                1.expr() - is_cold.expr(),
+            );
+        });
+
+        Self {
+            is_enabled,
+            address,
+            storage_key,
+            is_equal_address,
+            is_equal_storage_key,
+            is_cold,
+        }
+    }
+
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        storage_key: Option<(Address, EthWord)>,
+        prev_storage_keys: &[(Address, EthWord)],
+        randomness: F,
+    ) -> Result<(), Error> {
+        let rlc = |key: EthWord| {
+            RandomLinearCombination::random_linear_combine(key.to_le_bytes(), randomness)
+        };
+        let (address, key) = storage_key.unwrap_or_default();
+        self.is_enabled
+            .assign(region, offset, Some(F::from(storage_key.is_some() as u64)))?;
+        self.address.assign(region, offset, address.to_scalar())?;
+        self.storage_key.assign(region, offset, Some(rlc(key)))?;
+        let mut num_equal = F::zero();
+        for ((is_equal_address, is_equal_storage_key), (prev_address, prev_key)) in self
+            .is_equal_address
+            .iter()
+            .zip(self.is_equal_storage_key.iter())
+            .zip(prev_storage_keys.iter())
+        {
+            num_equal +=
+                is_equal_address.assign(
+                    region,
+                    offset,
+                    address.to_scalar().unwrap(),
+                    prev_address.to_scalar().unwrap(),
+                )? * is_equal_storage_key.assign(region, offset, rlc(key), rlc(*prev_key))?;
+        }
+        self.is_cold.assign(region, offset, num_equal)?;
+        Ok(())
+    }
+}
 
 #[derive(Clone, Debug)]
 pub(crate) struct BeginTxGadget<F> {
@@ -28,6 +226,13 @@ pub(crate) struct BeginTxGadget<F> {
     tx_nonce: Cell<F>,
     tx_gas: Cell<F>,
     tx_gas_price: Word<F>,
+    tx_gas_fee_cap: Word<F>,
+    tx_gas_tip_cap: Word<F>,
+    base_fee: Word<F>,
+    add_base_fee_and_tip: AddWordsGadget<F, 2>,
+    fee_cap_lt_base_fee: LtWordGadget<F>,
+    fee_cap_lt_tip_cap: LtWordGadget<F>,
+    fee_cap_lt_max_gas_price: LtWordGadget<F>,
     mul_gas_fee_by_gas: MulWordByU64Gadget<F>,
     tx_caller_address: Cell<F>,
     tx_callee_address: Cell<F>,
@@ -35,11 +240,25 @@ pub(crate) struct BeginTxGadget<F> {
     tx_value: Word<F>,
     tx_call_data_length: Cell<F>,
     tx_call_data_gas_cost: Cell<F>,
+    tx_call_data_rlc: Cell<F>,
+    tx_access_list_addresses_len: Cell<F>,
+    tx_access_list_storage_keys_len: Cell<F>,
+    callee_is_caller: IsEqualGadget<F>,
+    // `callee_is_precompile[i]` is 1 when the callee is the precompiled
+    // contract at address `i + 1`
+    callee_is_precompile: Vec<IsEqualGadget<F>>,
+    access_list_addresses: Vec<AccessListAddressGadget<F>>,
+    access_list_storage_keys: Vec<AccessListStorageKeyGadget<F>>,
     rw_counter_end_of_reversion: Cell<F>,
     is_persistent: Cell<F>,
     sufficient_gas_left: RangeCheckGadget<F, N_BYTES_GAS>,
     transfer_with_gas_fee: TransferWithGasFeeGadget<F>,
+    callee_nonce: Cell<F>,
+    callee_nonce_is_zero: IsZeroGadget<F>,
     code_hash: Cell<F>,
+    code_hash_is_empty: IsEqualGadget<F>,
+    init_code_hash: Cell<F>,
+    contract_address: ContractAddressGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
@@ -58,7 +277,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         ]
         .map(|field_tag| cb.call_context(Some(call_id.expr()), field_tag));
 
-        let [tx_nonce, tx_gas, tx_caller_address, tx_callee_address, tx_is_create, tx_call_data_length, tx_call_data_gas_cost] =
+        let [tx_nonce, tx_gas, tx_caller_address, tx_callee_address, tx_is_create, tx_call_data_length, tx_call_data_gas_cost, tx_call_data_rlc, tx_access_list_addresses_len, tx_access_list_storage_keys_len] =
             [
                 TxContextFieldTag::Nonce,
                 TxContextFieldTag::Gas,
@@ -67,10 +286,18 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 TxContextFieldTag::IsCreate,
                 TxContextFieldTag::CallDataLength,
                 TxContextFieldTag::CallDataGasCost,
+                TxContextFieldTag::CallDataRlc,
+                TxContextFieldTag::AccessListAddressesLen,
+                TxContextFieldTag::AccessListStorageKeysLen,
             ]
             .map(|field_tag| cb.tx_context(tx_id.expr(), field_tag, None));
-        let [tx_gas_price, tx_value] = [TxContextFieldTag::GasPrice, TxContextFieldTag::Value]
-            .map(|field_tag| cb.tx_context_as_word(tx_id.expr(), field_tag, None));
+        let [tx_gas_price, tx_gas_fee_cap, tx_gas_tip_cap, tx_value] = [
+            TxContextFieldTag::GasPrice,
+            TxContextFieldTag::GasFeeCap,
+            TxContextFieldTag::GasTipCap,
+            TxContextFieldTag::Value,
+        ]
+        .map(|field_tag| cb.tx_context_as_word(tx_id.expr(), field_tag, None));
 
         // Add first step constraint to have both rw_counter and tx_id to be 1
         cb.add_constraint_first_step(
@@ -88,29 +315,132 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             tx_nonce.expr(),
         );
 
-        // TODO: Implement EIP 1559 (currently it only supports legacy
-        // transaction format)
-        // Calculate transaction gas fee
+        // Check the fee caps are valid against the block base_fee (EIP 1559).
+        // Legacy transactions have both caps set to their gas price.
+        let base_fee = cb.query_word();
+        cb.block_lookup(BlockContextFieldTag::BaseFee.expr(), None, base_fee.expr());
+        let fee_cap_lt_base_fee = LtWordGadget::construct(cb, &tx_gas_fee_cap, &base_fee);
+        cb.require_zero("gas_fee_cap >= base_fee", fee_cap_lt_base_fee.expr());
+        let fee_cap_lt_tip_cap = LtWordGadget::construct(cb, &tx_gas_fee_cap, &tx_gas_tip_cap);
+        cb.require_zero("gas_fee_cap >= gas_tip_cap", fee_cap_lt_tip_cap.expr());
+
+        // Check gas_price is the effective gas price, which is
+        // min(gas_fee_cap, base_fee + gas_tip_cap)
+        let add_base_fee_and_tip =
+            AddWordsGadget::construct(cb, [base_fee.clone(), tx_gas_tip_cap.clone()]);
+        cb.require_zero(
+            "base_fee + gas_tip_cap doesn't overflow",
+            add_base_fee_and_tip.carry().expr(),
+        );
+        let fee_cap_lt_max_gas_price =
+            LtWordGadget::construct(cb, &tx_gas_fee_cap, add_base_fee_and_tip.sum());
+        cb.require_equal(
+            "gas_price == min(gas_fee_cap, base_fee + gas_tip_cap)",
+            tx_gas_price.expr(),
+            select::expr(
+                fee_cap_lt_max_gas_price.expr(),
+                tx_gas_fee_cap.expr(),
+                add_base_fee_and_tip.sum().expr(),
+            ),
+        );
+
+        // Calculate transaction gas fee, which is bought with the effective
+        // gas price upfront.  EndTx refunds the unused gas at the same price,
+        // and credits the coinbase with only the tip of the gas used.
         let mul_gas_fee_by_gas =
             MulWordByU64Gadget::construct(cb, tx_gas_price.clone(), tx_gas.clone(), true);
 
-        // TODO: Take gas cost of access list (EIP 2930) into consideration.
-        // Use intrinsic gas
+        // Use intrinsic gas, which includes the cost of the access list (EIP
+        // 2930).
         let intrinsic_gas_cost = select::expr(
             tx_is_create.expr(),
             GasCost::CREATION_TX.expr(),
             GasCost::TX.expr(),
-        ) + tx_call_data_gas_cost.expr();
+        ) + tx_call_data_gas_cost.expr()
+            + tx_access_list_addresses_len.expr() * GasCost::ACCESS_LIST_PER_ADDRESS.expr()
+            + tx_access_list_storage_keys_len.expr() * GasCost::ACCESS_LIST_PER_STORAGE_KEY.expr();
 
         // Check gas_left is sufficient
         let gas_left = tx_gas.expr() - intrinsic_gas_cost;
         let sufficient_gas_left = RangeCheckGadget::construct(cb, gas_left.clone());
 
-        // Prepare access list of caller and callee
+        // Prepare access list of caller and callee (EIP 2929), which starts
+        // empty in each tx, so the callee is only warm when it's the caller.
+        let callee_is_caller =
+            IsEqualGadget::construct(cb, tx_callee_address.expr(), tx_caller_address.expr());
         cb.account_access_list_write(tx_id.expr(), tx_caller_address.expr(), 1.expr(), 0.expr());
-        cb.account_access_list_write(tx_id.expr(), tx_callee_address.expr(), 1.expr(), 0.expr());
+        cb.account_access_list_write(
+            tx_id.expr(),
+            tx_callee_address.expr(),
+            1.expr(),
+            callee_is_caller.expr(),
+        );
+
+        // Pre-warm the addresses and then the storage keys in the access list
+        // of the tx (EIP 2930).  The enabled entries are the first ones, as
+        // many as the entries in the tx table.
+        let mut access_list_addresses: Vec<AccessListAddressGadget<F>> = Vec::new();
+        for index in 0..MAX_ACCESS_LIST_ADDRESSES {
+            let warm_addresses = [tx_caller_address.expr(), tx_callee_address.expr()]
+                .iter()
+                .cloned()
+                .chain(
+                    access_list_addresses
+                        .iter()
+                        .map(|entry| entry.address.expr()),
+                )
+                .collect::<Vec<_>>();
+            access_list_addresses.push(AccessListAddressGadget::construct(
+                cb,
+                tx_id.expr(),
+                index,
+                &warm_addresses,
+            ));
+        }
+        let mut access_list_storage_keys: Vec<AccessListStorageKeyGadget<F>> = Vec::new();
+        for index in 0..MAX_ACCESS_LIST_STORAGE_KEYS {
+            let entry = AccessListStorageKeyGadget::construct(
+                cb,
+                tx_id.expr(),
+                index,
+                &access_list_storage_keys,
+            );
+            access_list_storage_keys.push(entry);
+        }
+        for (is_enabled, len, name) in [
+            (
+                access_list_addresses
+                    .iter()
+                    .map(|entry| entry.is_enabled.expr())
+                    .collect::<Vec<_>>(),
+                tx_access_list_addresses_len.expr(),
+                "Enabled access list addresses are as many as in the tx",
+            ),
+            (
+                access_list_storage_keys
+                    .iter()
+                    .map(|entry| entry.is_enabled.expr())
+                    .collect::<Vec<_>>(),
+                tx_access_list_storage_keys_len.expr(),
+                "Enabled access list storage keys are as many as in the tx",
+            ),
+        ] {
+            for (prev, next) in is_enabled.iter().zip(is_enabled.iter().skip(1)) {
+                cb.require_zero(
+                    "Access list entries are enabled in order",
+                    next.clone() * (1.expr() - prev.clone()),
+                );
+            }
+            cb.require_equal(
+                name,
+                is_enabled
+                    .iter()
+                    .fold(0.expr(), |acc, is_enabled| acc + is_enabled.clone()),
+                len,
+            );
+        }
 
-        // Transfer value from caller to callee
+        // Buy the gas and transfer value from caller to callee
         let transfer_with_gas_fee = TransferWithGasFeeGadget::construct(
             cb,
             tx_caller_address.expr(),
@@ -121,10 +451,30 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             rw_counter_end_of_reversion.expr(),
         );
 
-        // TODO: Handle creation transaction
-        // TODO: Handle precompiled
+        // The call to a precompiled contract is executed by the next step,
+        // whose input is the calldata of the tx instead of the memory of the
+        // caller
+        let callee_is_precompile = PrecompileCalls::iter()
+            .map(|precompile| {
+                IsEqualGadget::construct(cb, tx_callee_address.expr(), (precompile as u64).expr())
+            })
+            .collect::<Vec<_>>();
+        cb.condition(
+            (1.expr() - tx_is_create.expr())
+                * sum::expr(callee_is_precompile.iter().map(|is_equal| is_equal.expr())),
+            |cb| cb.require_next_state(ExecutionState::Precompile),
+        );
 
-        // Read code_hash of callee
+        // Read nonce of the created contract in a creation transaction, and
+        // code_hash of callee
+        let callee_nonce = cb.query_cell();
+        cb.condition(tx_is_create.expr(), |cb| {
+            cb.account_read(
+                tx_callee_address.expr(),
+                AccountFieldTag::Nonce,
+                callee_nonce.expr(),
+            );
+        });
         let code_hash = cb.query_cell();
         cb.account_read(
             tx_callee_address.expr(),
@@ -132,6 +482,44 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             code_hash.expr(),
         );
 
+        // In a creation transaction, the callee is the created contract, whose
+        // address is derived from the caller and its nonce before the
+        // increase.  The init code is the tx calldata, whose hash is the code
+        // source of the root call.
+        let contract_address = cb.condition(tx_is_create.expr(), |cb| {
+            let contract_address = ContractAddressGadget::construct(cb, 0.expr());
+            cb.require_equal(
+                "Creator address of the created contract is the caller",
+                contract_address.creator_address(),
+                tx_caller_address.expr(),
+            );
+            cb.require_equal(
+                "Creator nonce of the created contract is the tx nonce",
+                contract_address.creator_nonce(),
+                tx_nonce.expr(),
+            );
+            cb.require_equal(
+                "Callee address is the address of the created contract",
+                contract_address.contract_address(),
+                tx_callee_address.expr(),
+            );
+            contract_address
+        });
+        let empty_code_hash = Word::random_linear_combine_expr(
+            EMPTY_CODE_HASH_LE.map(|byte| byte.expr()),
+            cb.power_of_randomness(),
+        );
+        let code_hash_is_empty = IsEqualGadget::construct(cb, code_hash.expr(), empty_code_hash);
+        let init_code_hash = cb.query_cell();
+        cb.condition(tx_is_create.expr(), |cb| {
+            cb.bytecode_length(init_code_hash.expr(), tx_call_data_length.expr());
+            cb.keccak_lookup(
+                tx_call_data_rlc.expr(),
+                tx_call_data_length.expr(),
+                init_code_hash.expr(),
+            );
+        });
+
         // Setup next call's context.
         for (field_tag, value) in [
             (CallContextFieldTag::Depth, 1.expr()),
@@ -140,7 +528,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             (CallContextFieldTag::CallDataOffset, 0.expr()),
             (
                 CallContextFieldTag::CallDataLength,
-                tx_call_data_length.expr(),
+                select::expr(tx_is_create.expr(), 0.expr(), tx_call_data_length.expr()),
             ),
             (CallContextFieldTag::Value, tx_value.expr()),
             (CallContextFieldTag::IsStatic, 0.expr()),
@@ -151,16 +539,43 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             cb.call_context_lookup(false.expr(), Some(call_id.expr()), field_tag, value);
         }
 
+        // The creation fails on the address collision, where the created
+        // contract already has a nonce or code, that is a code hash other than
+        // the empty one of any non-existing account.  The transaction is not
+        // executed, and ends with all its gas consumed, once the transfer of
+        // its value is reverted by the last 2 rws of this step.
+        let callee_nonce_is_zero = IsZeroGadget::construct(cb, callee_nonce.expr());
+        let is_collision = tx_is_create.expr()
+            * (1.expr() - callee_nonce_is_zero.expr() * code_hash_is_empty.expr());
+        let rw_counter_end_of_step = cb.curr.state.rw_counter.expr() + cb.rw_counter_offset();
+        cb.condition(is_collision.clone(), |cb| {
+            cb.require_zero(
+                "Creation with address collision is not persistent",
+                is_persistent.expr(),
+            );
+            cb.require_equal(
+                "Creation with address collision is reverted in this step",
+                rw_counter_end_of_reversion.expr(),
+                rw_counter_end_of_step + 1.expr(),
+            );
+            cb.require_next_state(ExecutionState::EndTx);
+        });
+
         cb.require_step_state_transition(StepStateTransition {
-            // 19 read/write including:
+            // 20 read/write plus one for each entry of the access list, one
+            // more in a creation transaction and the 2 reversions on the
+            // address collision, including:
             //   - Read CallContext TxId
             //   - Read CallContext RwCounterEndOfReversion
             //   - Read CallContext IsPersistent
             //   - Write Account Nonce
             //   - Write TxAccessListAccount
             //   - Write TxAccessListAccount
+            //   - Write TxAccessListAccount for each address in the access list
+            //   - Write TxAccessListAccountStorage for each storage key in the access list
             //   - Write Account Balance
             //   - Write Account Balance
+            //   - Read Account Nonce of the created contract
             //   - Read Account CodeHash
             //   - Read CallContext Depth
             //   - Read CallContext CallerAddress
@@ -172,14 +587,26 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             //   - Read CallContext LastCalleeId
             //   - Read CallContext LastCalleeReturnDataOffset
             //   - Read CallContext LastCalleeReturnDataLength
-            rw_counter: Delta(19.expr()),
+            //   - Write Account Balance of the reversion of the callee
+            //   - Write Account Balance of the reversion of the caller
+            rw_counter: Delta(
+                20.expr()
+                    + tx_access_list_addresses_len.expr()
+                    + tx_access_list_storage_keys_len.expr()
+                    + tx_is_create.expr()
+                    + 2.expr() * is_collision.clone(),
+            ),
             call_id: To(call_id.expr()),
             is_root: To(true.expr()),
-            is_create: To(false.expr()),
-            code_source: To(code_hash.expr()),
+            is_create: To(tx_is_create.expr()),
+            code_source: To(select::expr(
+                tx_is_create.expr(),
+                init_code_hash.expr(),
+                code_hash.expr(),
+            )),
             program_counter: To(0.expr()),
             stack_pointer: To(STACK_CAPACITY.expr()),
-            gas_left: To(gas_left),
+            gas_left: To(select::expr(is_collision, 0.expr(), gas_left)),
             state_write_counter: To(2.expr()),
             ..StepStateTransition::new_context()
         });
@@ -189,6 +616,13 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             tx_nonce,
             tx_gas,
             tx_gas_price,
+            tx_gas_fee_cap,
+            tx_gas_tip_cap,
+            base_fee,
+            add_base_fee_and_tip,
+            fee_cap_lt_base_fee,
+            fee_cap_lt_tip_cap,
+            fee_cap_lt_max_gas_price,
             mul_gas_fee_by_gas,
             tx_caller_address,
             tx_callee_address,
@@ -196,11 +630,23 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             tx_value,
             tx_call_data_length,
             tx_call_data_gas_cost,
+            tx_call_data_rlc,
+            tx_access_list_addresses_len,
+            tx_access_list_storage_keys_len,
+            callee_is_caller,
+            callee_is_precompile,
+            access_list_addresses,
+            access_list_storage_keys,
             rw_counter_end_of_reversion,
             is_persistent,
             sufficient_gas_left,
             transfer_with_gas_fee,
+            callee_nonce,
+            callee_nonce_is_zero,
             code_hash,
+            code_hash_is_empty,
+            init_code_hash,
+            contract_address,
         }
     }
 
@@ -214,9 +660,22 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         step: &ExecStep,
     ) -> Result<(), Error> {
         let gas_fee = tx.gas_price * tx.gas;
-        let [caller_balance_pair, callee_balance_pair, (callee_code_hash, _)] =
-            [step.rw_indices[6], step.rw_indices[7], step.rw_indices[8]]
-                .map(|idx| block.rws[idx].account_value_pair());
+        // The balances follow the writes of the access list, and the code hash
+        // follows the nonce of the created contract in a creation transaction
+        let access_list_len = tx.access_list_addresses.len() + tx.access_list_storage_keys.len();
+        let [(_, caller_balance_prev), (caller_balance, _), callee_balance_pair] = [6, 7, 8]
+            .map(|idx| block.rws[step.rw_indices[idx + access_list_len]].account_value_pair());
+        let (callee_nonce, callee_code_hash) = if tx.is_create {
+            let [(callee_nonce, _), (callee_code_hash, _)] = [9, 10]
+                .map(|idx| block.rws[step.rw_indices[idx + access_list_len]].account_value_pair());
+            (callee_nonce, callee_code_hash)
+        } else {
+            let idx = 9 + access_list_len;
+            (
+                U256::zero(),
+                block.rws[step.rw_indices[idx]].account_value_pair().0,
+            )
+        };
 
         self.tx_id
             .assign(region, offset, Some(F::from(tx.id as u64)))?;
@@ -225,6 +684,22 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         self.tx_gas.assign(region, offset, Some(F::from(tx.gas)))?;
         self.tx_gas_price
             .assign(region, offset, Some(tx.gas_price.to_le_bytes()))?;
+        self.tx_gas_fee_cap
+            .assign(region, offset, Some(tx.gas_fee_cap.to_le_bytes()))?;
+        let base_fee = block.context.base_fee;
+        let max_gas_price = base_fee + tx.gas_tip_cap;
+        self.add_base_fee_and_tip.assign(
+            region,
+            offset,
+            [base_fee, tx.gas_tip_cap],
+            max_gas_price,
+        )?;
+        self.fee_cap_lt_base_fee
+            .assign(region, offset, tx.gas_fee_cap, base_fee)?;
+        self.fee_cap_lt_tip_cap
+            .assign(region, offset, tx.gas_fee_cap, tx.gas_tip_cap)?;
+        self.fee_cap_lt_max_gas_price
+            .assign(region, offset, tx.gas_fee_cap, max_gas_price)?;
         self.mul_gas_fee_by_gas
             .assign(region, offset, tx.gas_price, tx.gas, gas_fee)?;
         self.tx_caller_address
@@ -240,6 +715,74 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         )?;
         self.tx_call_data_gas_cost
             .assign(region, offset, Some(F::from(tx.call_data_gas_cost)))?;
+        self.tx_call_data_rlc
+            .assign(region, offset, Some(tx.call_data_rlc(block.randomness)))?;
+        self.tx_access_list_addresses_len.assign(
+            region,
+            offset,
+            Some(F::from(tx.access_list_addresses.len() as u64)),
+        )?;
+        self.tx_access_list_storage_keys_len.assign(
+            region,
+            offset,
+            Some(F::from(tx.access_list_storage_keys.len() as u64)),
+        )?;
+        self.callee_is_caller.assign(
+            region,
+            offset,
+            tx.callee_address.to_scalar().unwrap(),
+            tx.caller_address.to_scalar().unwrap(),
+        )?;
+        for (is_equal, precompile) in self
+            .callee_is_precompile
+            .iter()
+            .zip(PrecompileCalls::iter())
+        {
+            is_equal.assign(
+                region,
+                offset,
+                tx.callee_address.to_scalar().unwrap(),
+                F::from(precompile as u64),
+            )?;
+        }
+        // The disabled entries are assigned with zeros, and compared as well
+        let addresses = (0..MAX_ACCESS_LIST_ADDRESSES)
+            .map(|index| tx.access_list_addresses.get(index).copied())
+            .collect::<Vec<_>>();
+        let warm_addresses = [tx.caller_address, tx.callee_address]
+            .iter()
+            .copied()
+            .chain(addresses.iter().map(|address| address.unwrap_or_default()))
+            .collect::<Vec<_>>();
+        for (index, (entry, address)) in self
+            .access_list_addresses
+            .iter()
+            .zip(addresses.iter())
+            .enumerate()
+        {
+            entry.assign(region, offset, *address, &warm_addresses[..index + 2])?;
+        }
+        let storage_keys = (0..MAX_ACCESS_LIST_STORAGE_KEYS)
+            .map(|index| tx.access_list_storage_keys.get(index).copied())
+            .collect::<Vec<_>>();
+        let prev_storage_keys = storage_keys
+            .iter()
+            .map(|storage_key| storage_key.unwrap_or_default())
+            .collect::<Vec<_>>();
+        for (index, (entry, storage_key)) in self
+            .access_list_storage_keys
+            .iter()
+            .zip(storage_keys.iter())
+            .enumerate()
+        {
+            entry.assign(
+                region,
+                offset,
+                *storage_key,
+                &prev_storage_keys[..index],
+                block.randomness,
+            )?;
+        }
         self.rw_counter_end_of_reversion.assign(
             region,
             offset,
@@ -252,18 +795,50 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         self.transfer_with_gas_fee.assign(
             region,
             offset,
-            caller_balance_pair,
+            (caller_balance, caller_balance_prev),
             callee_balance_pair,
             tx.value,
             gas_fee,
         )?;
-        self.code_hash.assign(
+        let callee_nonce = callee_nonce.to_scalar().unwrap();
+        self.callee_nonce
+            .assign(region, offset, Some(callee_nonce))?;
+        self.callee_nonce_is_zero
+            .assign(region, offset, callee_nonce)?;
+        let callee_code_hash = RandomLinearCombination::random_linear_combine(
+            callee_code_hash.to_le_bytes(),
+            block.randomness,
+        );
+        self.code_hash
+            .assign(region, offset, Some(callee_code_hash))?;
+        self.code_hash_is_empty.assign(
             region,
             offset,
-            Some(RandomLinearCombination::random_linear_combine(
-                callee_code_hash.to_le_bytes(),
+            callee_code_hash,
+            RandomLinearCombination::random_linear_combine(EMPTY_CODE_HASH_LE, block.randomness),
+        )?;
+        let init_code_hash = if tx.is_create {
+            let CodeSource::Account(code_hash) = &call.code_source;
+            RandomLinearCombination::random_linear_combine(
+                code_hash.to_le_bytes(),
                 block.randomness,
-            )),
+            )
+        } else {
+            F::zero()
+        };
+        self.init_code_hash
+            .assign(region, offset, Some(init_code_hash))?;
+        // The derivation is assigned in every transaction, but only checked
+        // in a creation one
+        self.contract_address.assign(
+            region,
+            offset,
+            false,
+            tx.caller_address,
+            tx.nonce,
+            U256::zero(),
+            &[],
+            block.randomness,
         )?;
         Ok(())
     }
@@ -276,37 +851,121 @@ mod test {
         step::ExecutionState,
         table::{AccountFieldTag, CallContextFieldTag, RwTableTag},
         test::{rand_bytes, rand_fp, rand_range, run_test_circuit_incomplete_fixed_table},
-        witness::{Block, Bytecode, Call, CodeSource, ExecStep, Rw, RwMap, Transaction},
+        witness::{
+            block_convert, Block, BlockContext, Bytecode, Call, CodeSource, ExecStep, Rw, RwMap,
+            Transaction,
+        },
     };
+    use bus_mapping::circuit_input_builder::get_create_address_preimage;
     use eth_types::{
-        self, address,
+        self, address, bytecode,
         evm_types::{GasCost, OpcodeId},
-        Address, ToWord, Word,
+        geth_types::Account,
+        AccessList, AccessListItem, Address, Bytes, ToWord, Word, H256,
     };
+    use sha3::{Digest, Keccak256};
     use std::convert::TryInto;
 
-    fn test_ok(tx: eth_types::Transaction, is_success: bool) {
-        let rw_counter_end_of_reversion = if is_success { 0 } else { 23 };
-
+    fn test_ok(tx: eth_types::Transaction, base_fee: Word, is_success: bool) {
         let gas_fee = tx.gas * tx.gas_price.unwrap_or_else(Word::zero);
         let call_data_gas_cost = tx
             .input
             .0
             .iter()
             .fold(0, |acc, byte| acc + if *byte == 0 { 4 } else { 16 });
-        let intrinsic_gas_cost = if tx.to.is_none() {
+        let access_list = tx.access_list.clone().unwrap_or_default();
+        let access_list_addresses = access_list
+            .0
+            .iter()
+            .map(|item| item.address)
+            .collect::<Vec<_>>();
+        let access_list_storage_keys = access_list
+            .0
+            .iter()
+            .flat_map(|item| {
+                item.storage_keys
+                    .iter()
+                    .map(move |key| (item.address, key.to_word()))
+            })
+            .collect::<Vec<_>>();
+        // Each entry of the access list takes one more write
+        let access_list_len = access_list_addresses.len() + access_list_storage_keys.len();
+        let is_create = tx.to.is_none();
+        // A creation transaction also reads the nonce of the created contract
+        let nonce_read_len = is_create as usize;
+        let rw_counter_end_of_reversion = if is_success {
+            0
+        } else {
+            24 + access_list_len + nonce_read_len
+        };
+        // The created contract is derived from the caller and its nonce, and
+        // its init code is hashed from the calldata
+        let mut keccak_inputs = Vec::new();
+        let callee_address = tx.to.unwrap_or_else(|| {
+            let preimage = get_create_address_preimage(tx.from, tx.nonce);
+            let contract_address = Address::from_slice(&Keccak256::digest(&preimage)[12..]);
+            keccak_inputs.push(tx.input.to_vec());
+            keccak_inputs.push(preimage);
+            contract_address
+        });
+        let intrinsic_gas_cost = if is_create {
             GasCost::CREATION_TX.as_u64()
         } else {
             GasCost::TX.as_u64()
-        } + call_data_gas_cost;
+        } + call_data_gas_cost
+            + access_list_addresses.len() as u64 * GasCost::ACCESS_LIST_PER_ADDRESS.as_u64()
+            + access_list_storage_keys.len() as u64 * GasCost::ACCESS_LIST_PER_STORAGE_KEY.as_u64();
+
+        // The caller and the callee are warm before the access list, and so
+        // is any entry repeated in the access list
+        let access_list_address_rws = [tx.from, callee_address]
+            .iter()
+            .chain(access_list_addresses.iter())
+            .enumerate()
+            .map(|(idx, address)| Rw::TxAccessListAccount {
+                rw_counter: 5 + idx,
+                is_write: true,
+                tx_id: 1,
+                account_address: *address,
+                value: true,
+                value_prev: [tx.from, callee_address]
+                    .iter()
+                    .chain(access_list_addresses.iter())
+                    .take(idx)
+                    .any(|warm_address| warm_address == address),
+            })
+            .collect::<Vec<_>>();
+        let access_list_storage_key_rws = access_list_storage_keys
+            .iter()
+            .enumerate()
+            .map(|(idx, storage_key)| Rw::TxAccessListAccountStorage {
+                rw_counter: 7 + access_list_addresses.len() + idx,
+                is_write: true,
+                tx_id: 1,
+                account_address: storage_key.0,
+                storage_key: storage_key.1,
+                value: true,
+                value_prev: access_list_storage_keys[..idx].contains(storage_key),
+            })
+            .collect::<Vec<_>>();
 
         let from_balance_prev = Word::from(10_i32).pow(20_i32.into());
         let to_balance_prev = Word::zero();
-        let from_balance = from_balance_prev - tx.value - gas_fee;
+        // The gas is bought before the value is transferred, and only the
+        // transfer is reverted when the tx fails
+        let from_balance_after_fee = from_balance_prev - gas_fee;
+        let from_balance = from_balance_after_fee - tx.value;
         let to_balance = to_balance_prev + tx.value;
 
         let randomness = rand_fp();
+        // The init code of a creation transaction is also STOP, and the created
+        // contract doesn't exist yet, so it has the empty code hash
         let bytecode = Bytecode::new(vec![OpcodeId::STOP.as_u8()]);
+        let callee_code_hash = if is_create {
+            Word::from_big_endian(Keccak256::digest(&[]).as_slice())
+        } else {
+            bytecode.hash
+        };
         let block = Block {
             randomness,
             txs: vec![Transaction {
@@ -314,17 +973,27 @@ mod test {
                 nonce: tx.nonce.try_into().unwrap(),
                 gas: tx.gas.try_into().unwrap(),
                 gas_price: tx.gas_price.unwrap_or_else(Word::zero),
+                gas_fee_cap: tx
+                    .max_fee_per_gas
+                    .or(tx.gas_price)
+                    .unwrap_or_else(Word::zero),
+                gas_tip_cap: tx
+                    .max_priority_fee_per_gas
+                    .or(tx.gas_price)
+                    .unwrap_or_else(Word::zero),
                 caller_address: tx.from,
-                callee_address: tx.to.unwrap_or_else(Address::zero),
-                is_create: tx.to.is_none(),
+                callee_address,
+                is_create,
                 value: tx.value,
                 call_data: tx.input.to_vec(),
                 call_data_length: tx.input.0.len(),
                 call_data_gas_cost,
+                access_list_addresses,
+                access_list_storage_keys,
                 calls: vec![Call {
                     id: 1,
                     is_root: true,
-                    is_create: false,
+                    is_create,
                     code_source: CodeSource::Account(bytecode.hash),
                     rw_counter_end_of_reversion,
                     is_persistent: is_success,
@@ -339,11 +1008,17 @@ mod test {
                                 (RwTableTag::CallContext, 1),
                                 (RwTableTag::CallContext, 2),
                                 (RwTableTag::Account, 0),
-                                (RwTableTag::TxAccessListAccount, 0),
-                                (RwTableTag::TxAccessListAccount, 1),
-                                (RwTableTag::Account, 1),
-                                (RwTableTag::Account, 2),
-                                (RwTableTag::Account, 3),
+                            ],
+                            (0..access_list_address_rws.len())
+                                .map(|idx| (RwTableTag::TxAccessListAccount, idx))
+                                .collect(),
+                            (0..access_list_storage_key_rws.len())
+                                .map(|idx| (RwTableTag::TxAccessListAccountStorage, idx))
+                                .collect(),
+                            (1..5 + nonce_read_len)
+                                .map(|idx| (RwTableTag::Account, idx))
+                                .collect(),
+                            vec![
                                 (RwTableTag::CallContext, 3),
                                 (RwTableTag::CallContext, 4),
                                 (RwTableTag::CallContext, 5),
@@ -355,7 +1030,10 @@ mod test {
                             if is_success {
                                 vec![]
                             } else {
-                                vec![(RwTableTag::Account, 4), (RwTableTag::Account, 5)]
+                                vec![
+                                    (RwTableTag::Account, 5 + nonce_read_len),
+                                    (RwTableTag::Account, 6 + nonce_read_len),
+                                ]
                             },
                         ]
                         .concat(),
@@ -366,7 +1044,7 @@ mod test {
                     },
                     ExecStep {
                         execution_state: ExecutionState::STOP,
-                        rw_counter: 20,
+                        rw_counter: 21 + access_list_len + nonce_read_len,
                         program_counter: 0,
                         stack_pointer: STACK_CAPACITY,
                         gas_left: 0,
@@ -378,26 +1056,10 @@ mod test {
             }],
             rws: RwMap(
                 [
+                    (RwTableTag::TxAccessListAccount, access_list_address_rws),
                     (
-                        RwTableTag::TxAccessListAccount,
-                        vec![
-                            Rw::TxAccessListAccount {
-                                rw_counter: 5,
-                                is_write: true,
-                                tx_id: 1,
-                                account_address: tx.from,
-                                value: true,
-                                value_prev: false,
-                            },
-                            Rw::TxAccessListAccount {
-                                rw_counter: 6,
-                                is_write: true,
-                                tx_id: 1,
-                                account_address: tx.to.unwrap(),
-                                value: true,
-                                value_prev: false,
-                            },
-                        ],
+                        RwTableTag::TxAccessListAccountStorage,
+                        access_list_storage_key_rws,
                     ),
                     (
                         RwTableTag::Account,
@@ -412,30 +1074,50 @@ mod test {
                                     value_prev: tx.nonce,
                                 },
                                 Rw::Account {
-                                    rw_counter: 7,
+                                    rw_counter: 7 + access_list_len,
                                     is_write: true,
                                     account_address: tx.from,
                                     field_tag: AccountFieldTag::Balance,
-                                    value: from_balance,
+                                    value: from_balance_after_fee,
                                     value_prev: from_balance_prev,
                                 },
                                 Rw::Account {
-                                    rw_counter: 8,
+                                    rw_counter: 8 + access_list_len,
                                     is_write: true,
-                                    account_address: tx.to.unwrap_or_else(Address::zero),
+                                    account_address: tx.from,
                                     field_tag: AccountFieldTag::Balance,
-                                    value: to_balance,
-                                    value_prev: to_balance_prev,
+                                    value: from_balance,
+                                    value_prev: from_balance_after_fee,
                                 },
                                 Rw::Account {
-                                    rw_counter: 9,
-                                    is_write: false,
-                                    account_address: tx.to.unwrap_or_else(Address::zero),
-                                    field_tag: AccountFieldTag::CodeHash,
-                                    value: bytecode.hash,
-                                    value_prev: bytecode.hash,
+                                    rw_counter: 9 + access_list_len,
+                                    is_write: true,
+                                    account_address: callee_address,
+                                    field_tag: AccountFieldTag::Balance,
+                                    value: to_balance,
+                                    value_prev: to_balance_prev,
                                 },
                             ],
+                            if is_create {
+                                vec![Rw::Account {
+                                    rw_counter: 10 + access_list_len,
+                                    is_write: false,
+                                    account_address: callee_address,
+                                    field_tag: AccountFieldTag::Nonce,
+                                    value: Word::zero(),
+                                    value_prev: Word::zero(),
+                                }]
+                            } else {
+                                vec![]
+                            },
+                            vec![Rw::Account {
+                                rw_counter: 10 + access_list_len + nonce_read_len,
+                                is_write: false,
+                                account_address: callee_address,
+                                field_tag: AccountFieldTag::CodeHash,
+                                value: callee_code_hash,
+                                value_prev: callee_code_hash,
+                            }],
                             if is_success {
                                 vec![]
                             } else {
@@ -443,7 +1125,7 @@ mod test {
                                     Rw::Account {
                                         rw_counter: rw_counter_end_of_reversion - 1,
                                         is_write: true,
-                                        account_address: tx.to.unwrap_or_else(Address::zero),
+                                        account_address: callee_address,
                                         field_tag: AccountFieldTag::Balance,
                                         value: to_balance_prev,
                                         value_prev: to_balance,
@@ -453,7 +1135,7 @@ mod test {
                                         is_write: true,
                                         account_address: tx.from,
                                         field_tag: AccountFieldTag::Balance,
-                                        value: from_balance_prev,
+                                        value: from_balance_after_fee,
                                         value_prev: from_balance,
                                     },
                                 ]
@@ -486,70 +1168,74 @@ mod test {
                                 value: Word::from(is_success as u64),
                             },
                             Rw::CallContext {
-                                rw_counter: 10,
+                                rw_counter: 11 + access_list_len + nonce_read_len,
                                 is_write: false,
                                 call_id: 1,
                                 field_tag: CallContextFieldTag::Depth,
                                 value: Word::one(),
                             },
                             Rw::CallContext {
-                                rw_counter: 11,
+                                rw_counter: 12 + access_list_len + nonce_read_len,
                                 is_write: false,
                                 call_id: 1,
                                 field_tag: CallContextFieldTag::CallerAddress,
                                 value: tx.from.to_word(),
                             },
                             Rw::CallContext {
-                                rw_counter: 12,
+                                rw_counter: 13 + access_list_len + nonce_read_len,
                                 is_write: false,
                                 call_id: 1,
                                 field_tag: CallContextFieldTag::CalleeAddress,
-                                value: tx.to.unwrap_or_else(Address::zero).to_word(),
+                                value: callee_address.to_word(),
                             },
                             Rw::CallContext {
-                                rw_counter: 13,
+                                rw_counter: 14 + access_list_len + nonce_read_len,
                                 is_write: false,
                                 call_id: 1,
                                 field_tag: CallContextFieldTag::CallDataOffset,
                                 value: Word::zero(),
                             },
                             Rw::CallContext {
-                                rw_counter: 14,
+                                rw_counter: 15 + access_list_len + nonce_read_len,
                                 is_write: false,
                                 call_id: 1,
                                 field_tag: CallContextFieldTag::CallDataLength,
-                                value: tx.input.0.len().into(),
+                                value: if is_create {
+                                    Word::zero()
+                                } else {
+                                    tx.input.0.len().into()
+                                },
                             },
                             Rw::CallContext {
-                                rw_counter: 15,
+                                rw_counter: 16 + access_list_len + nonce_read_len,
                                 is_write: false,
                                 call_id: 1,
                                 field_tag: CallContextFieldTag::Value,
                                 value: tx.value,
                             },
                             Rw::CallContext {
-                                rw_counter: 16,
+                                rw_counter: 17 + access_list_len + nonce_read_len,
                                 is_write: false,
                                 call_id: 1,
                                 field_tag: CallContextFieldTag::IsStatic,
                                 value: Word::zero(),
                             },
                             Rw::CallContext {
-                                rw_counter: 17,
+                                rw_counter: 18 + access_list_len + nonce_read_len,
                                 is_write: false,
                                 call_id: 1,
                                 field_tag: CallContextFieldTag::LastCalleeId,
                                 value: Word::zero(),
                             },
                             Rw::CallContext {
-                                rw_counter: 18,
+                                rw_counter: 19 + access_list_len + nonce_read_len,
                                 is_write: false,
                                 call_id: 1,
                                 field_tag: CallContextFieldTag::LastCalleeReturnDataOffset,
                                 value: Word::zero(),
                             },
                             Rw::CallContext {
-                                rw_counter: 19,
+                                rw_counter: 20 + access_list_len + nonce_read_len,
                                 is_write: false,
                                 call_id: 1,
                                 field_tag: CallContextFieldTag::LastCalleeReturnDataLength,
@@ -561,6 +1247,11 @@ mod test {
                 .into(),
             ),
             bytecodes: vec![bytecode],
+            context: BlockContext {
+                base_fee,
+                ..Default::default()
+            },
+            keccak_inputs,
             ..Default::default()
         };
         assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
@@ -588,17 +1279,120 @@ mod test {
         }
     }
 
+    fn mock_dynamic_fee_tx(
+        gas_fee_cap: Word,
+        gas_tip_cap: Word,
+        base_fee: Word,
+    ) -> eth_types::Transaction {
+        let gas_price = std::cmp::min(gas_fee_cap, base_fee + gas_tip_cap);
+        eth_types::Transaction {
+            max_fee_per_gas: Some(gas_fee_cap),
+            max_priority_fee_per_gas: Some(gas_tip_cap),
+            ..mock_tx(None, None, Some(gas_price), vec![])
+        }
+    }
+
     #[test]
     fn begin_tx_gadget_simple() {
         // Transfer 1 ether, successfully
-        test_ok(mock_tx(None, None, None, vec![]), true);
+        test_ok(mock_tx(None, None, None, vec![]), Word::zero(), true);
 
         // Transfer 1 ether, tx reverts
-        test_ok(mock_tx(None, None, None, vec![]), false);
+        test_ok(mock_tx(None, None, None, vec![]), Word::zero(), false);
 
         // Transfer nothing with some calldata
         test_ok(
             mock_tx(None, Some(21080), None, vec![1, 2, 3, 4, 0, 0, 0, 0]),
+            Word::zero(),
+            false,
+        );
+    }
+
+    #[test]
+    fn begin_tx_gadget_dynamic_fee() {
+        let gwei = |n: u64| Word::from(n) * Word::from(1_000_000_000);
+
+        // Effective gas price is base_fee + gas_tip_cap
+        test_ok(
+            mock_dynamic_fee_tx(gwei(10), gwei(2), gwei(7)),
+            gwei(7),
+            true,
+        );
+
+        // Effective gas price is capped by gas_fee_cap
+        test_ok(
+            mock_dynamic_fee_tx(gwei(10), gwei(5), gwei(7)),
+            gwei(7),
+            true,
+        );
+
+        // Effective gas price is exactly base_fee, tx reverts
+        test_ok(
+            mock_dynamic_fee_tx(gwei(7), gwei(0), gwei(7)),
+            gwei(7),
+            false,
+        );
+    }
+
+    #[test]
+    fn begin_tx_gadget_access_list() {
+        let access_list = AccessList(vec![
+            AccessListItem {
+                address: address!("0x00000000000000000000000000000000000000ac"),
+                storage_keys: vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+            },
+            AccessListItem {
+                address: address!("0x00000000000000000000000000000000000000ff"),
+                storage_keys: vec![H256::from_low_u64_be(3)],
+            },
+        ]);
+        let gas = GasCost::TX.as_u64()
+            + 2 * GasCost::ACCESS_LIST_PER_ADDRESS.as_u64()
+            + 3 * GasCost::ACCESS_LIST_PER_STORAGE_KEY.as_u64();
+
+        // Transfer 1 ether with an access list, successfully
+        test_ok(
+            eth_types::Transaction {
+                access_list: Some(access_list.clone()),
+                ..mock_tx(None, Some(gas), None, vec![])
+            },
+            Word::zero(),
+            true,
+        );
+
+        // Transfer 1 ether with an access list, tx reverts
+        test_ok(
+            eth_types::Transaction {
+                access_list: Some(access_list),
+                ..mock_tx(None, Some(gas), None, vec![])
+            },
+            Word::zero(),
+            false,
+        );
+    }
+
+    #[test]
+    fn begin_tx_gadget_create() {
+        let init_code = vec![OpcodeId::STOP.as_u8()];
+        let gas = GasCost::CREATION_TX.as_u64() + 4;
+
+        // Create a contract transferring 1 ether, successfully
+        test_ok(
+            eth_types::Transaction {
+                to: None,
+                ..mock_tx(None, Some(gas), None, init_code.clone())
+            },
+            Word::zero(),
+            true,
+        );
+
+        // Create a contract transferring 1 ether, tx reverts
+        test_ok(
+            eth_types::Transaction {
+                to: None,
+                ..mock_tx(None, Some(gas), None, init_code)
+            },
+            Word::zero(),
             false,
         );
     }
@@ -615,6 +1409,7 @@ mod test {
                 None,
                 vec![],
             ),
+            Word::zero(),
             true,
         );
 
@@ -626,6 +1421,7 @@ mod test {
                 Some(Word::from(rand_range(0..42857142857143u64))),
                 vec![],
             ),
+            Word::zero(),
             true,
         );
 
@@ -637,6 +1433,7 @@ mod test {
                 None,
                 vec![],
             ),
+            Word::zero(),
             false,
         );
 
@@ -648,7 +1445,72 @@ mod test {
                 Some(Word::from(rand_range(0..42857142857143u64))),
                 vec![],
             ),
+            Word::zero(),
             false,
         );
     }
+
+    #[test]
+    fn begin_tx_gadget_precompile() {
+        // The BeginTx step is followed by the Precompile step of the root call
+        let block_trace = bus_mapping::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_precompile(
+                address!("0x0000000000000000000000000000000000000004"),
+                rand_bytes(0x40),
+            )
+            .unwrap(),
+        );
+        let mut builder = block_trace.new_circuit_input_builder();
+        builder
+            .handle_tx(&block_trace.eth_tx, &block_trace.geth_trace)
+            .unwrap();
+        builder.set_value_ops_call_context_rwc_eor();
+        let block = block_convert(&builder.block, &builder.code_db);
+        assert_eq!(
+            block.txs[0]
+                .steps
+                .iter()
+                .map(|step| step.execution_state)
+                .collect::<Vec<_>>(),
+            vec![ExecutionState::BeginTx, ExecutionState::Precompile]
+        );
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn begin_tx_gadget_create_address_collision() {
+        // The created address already has a nonce and code, so the tx fails
+        // and ends right after the BeginTx step
+        let caller = mock::new_tx(&mock::new_block()).from;
+        let preimage = get_create_address_preimage(caller, Word::zero());
+        let account = Account {
+            address: Address::from_slice(&Keccak256::digest(&preimage)[12..]),
+            nonce: Word::one(),
+            code: Bytes::from(bytecode! { STOP }.to_vec()),
+            ..Default::default()
+        };
+        let init_code = bytecode! {
+            PUSH1(0x2a)
+            STOP
+        };
+        let block_trace = bus_mapping::mock::BlockData::new_from_geth_data(
+            mock::new_single_tx_trace_create_accounts(&init_code, vec![account]).unwrap(),
+        );
+        let mut builder = block_trace.new_circuit_input_builder();
+        builder
+            .handle_tx(&block_trace.eth_tx, &block_trace.geth_trace)
+            .unwrap();
+        builder.set_value_ops_call_context_rwc_eor();
+        let block = block_convert(&builder.block, &builder.code_db);
+        assert_eq!(
+            block.txs[0]
+                .steps
+                .iter()
+                .map(|step| step.execution_state)
+                .collect::<Vec<_>>(),
+            vec![ExecutionState::BeginTx, ExecutionState::EndTx]
+        );
+        assert_eq!(block.txs[0].steps[1].gas_left, 0);
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
 }
@@ -0,0 +1,196 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_U64,
+        step::ExecutionState,
+        table::BlockContextFieldTag,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
+            from_bytes,
+            math_gadget::{IsZeroGadget, LtGadget},
+            sum, Cell, RandomLinearCombination, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{Field, ToLittleEndian};
+use halo2_proofs::{circuit::Region, plonk::Error};
+use std::convert::TryInto;
+
+/// Number of most recent blocks whose hash is accessible by BLOCKHASH
+const NUM_HISTORY_HASHES: u64 = 256;
+
+#[derive(Clone, Debug)]
+pub(crate) struct BlockHashGadget<F> {
+    same_context: SameContextGadget<F>,
+    block_number: Word<F>,
+    current_block_number: RandomLinearCombination<F, N_BYTES_U64>,
+    block_hash: Cell<F>,
+    is_u64: IsZeroGadget<F>,
+    is_lt_current: LtGadget<F, N_BYTES_U64>,
+    is_recent: LtGadget<F, N_BYTES_U64>,
+}
+
+impl<F: Field> ExecutionGadget<F> for BlockHashGadget<F> {
+    const NAME: &'static str = "BLOCKHASH";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::BLOCKHASH;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let block_number = cb.query_word();
+        cb.stack_pop(block_number.expr());
+
+        // Lookup block table with the number of the current block
+        let current_block_number = cb.query_rlc();
+        cb.block_lookup(
+            BlockContextFieldTag::Number.expr(),
+            None,
+            current_block_number.expr(),
+        );
+
+        // The hash is only available when `current - 256 <= block_number <
+        // current`, which requires the block number to fit in 8 bytes first.
+        let is_u64 = IsZeroGadget::construct(cb, sum::expr(&block_number.cells[N_BYTES_U64..]));
+        let block_number_lo = from_bytes::expr(&block_number.cells[..N_BYTES_U64]);
+        let current_block_number_expr = from_bytes::expr(&current_block_number.cells);
+        let is_lt_current = LtGadget::construct(
+            cb,
+            block_number_lo.clone(),
+            current_block_number_expr.clone(),
+        );
+        // The distance is only meaningful when `block_number < current`, so
+        // it's zeroed otherwise to keep it within the range of `LtGadget`.
+        let is_recent = LtGadget::construct(
+            cb,
+            is_lt_current.expr() * (current_block_number_expr - block_number_lo.clone() - 1.expr()),
+            NUM_HISTORY_HASHES.expr(),
+        );
+        let is_valid = is_u64.expr() * is_lt_current.expr() * is_recent.expr();
+
+        let block_hash = cb.query_cell();
+        cb.condition(is_valid.clone(), |cb| {
+            cb.block_lookup(
+                BlockContextFieldTag::BlockHash.expr(),
+                Some(block_number_lo),
+                block_hash.expr(),
+            );
+        });
+        cb.condition(1.expr() - is_valid, |cb| {
+            cb.require_zero(
+                "Block hash is 0 when block number is out of range",
+                block_hash.expr(),
+            );
+        });
+        cb.stack_push(block_hash.expr());
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(2.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(0.expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition, None);
+
+        Self {
+            same_context,
+            block_number,
+            current_block_number,
+            block_hash,
+            is_u64,
+            is_lt_current,
+            is_recent,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [block_number, block_hash] =
+            [0, 1].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        let block_number = block_number.to_le_bytes();
+        self.block_number
+            .assign(region, offset, Some(block_number))?;
+        self.block_hash.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                block_hash.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+
+        let current_block_number = block.context.number.low_u64();
+        self.current_block_number.assign(
+            region,
+            offset,
+            Some(current_block_number.to_le_bytes()),
+        )?;
+
+        self.is_u64
+            .assign(region, offset, sum::value(&block_number[N_BYTES_U64..]))?;
+        let block_number_lo = u64::from_le_bytes(block_number[..N_BYTES_U64].try_into().unwrap());
+        let (is_lt_current, _) = self.is_lt_current.assign(
+            region,
+            offset,
+            F::from(block_number_lo),
+            F::from(current_block_number),
+        )?;
+        let distance = if is_lt_current == F::one() {
+            current_block_number - block_number_lo - 1
+        } else {
+            0
+        };
+        self.is_recent.assign(
+            region,
+            offset,
+            F::from(distance),
+            F::from(NUM_HISTORY_HASHES),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, Word};
+
+    fn test_ok(block_number: Word) {
+        let bytecode = bytecode! {
+            PUSH32(block_number)
+            #[start]
+            BLOCKHASH
+            STOP
+        };
+        assert_eq!(run_test_circuits(bytecode), Ok(()));
+    }
+
+    #[test]
+    fn blockhash_gadget_recent_block() {
+        // The mock block number is 123456
+        test_ok(Word::from(123455));
+        test_ok(Word::from(123456 - 256));
+    }
+
+    #[test]
+    fn blockhash_gadget_out_of_range() {
+        test_ok(Word::from(123456));
+        test_ok(Word::from(123456 - 257));
+        test_ok(Word::from(u64::MAX));
+        test_ok(Word::MAX);
+    }
+}
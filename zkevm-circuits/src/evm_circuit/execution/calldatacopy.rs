@@ -1,7 +1,7 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::{N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE},
+        param::{N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE, N_BYTES_U64},
         step::ExecutionState,
         table::{CallContextFieldTag, TxContextFieldTag},
         util::{
@@ -11,13 +11,15 @@ use crate::{
                 Transition::{Delta, To},
             },
             from_bytes,
+            math_gadget::MinMaxGadget,
             memory_gadget::{MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget},
-            Cell, MemoryAddress,
+            select, Cell, MemoryAddress,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
     util::Expr,
 };
+use bus_mapping::circuit_input_builder::CopyDataType;
 use eth_types::Field;
 use eth_types::ToLittleEndian;
 use halo2_proofs::{circuit::Region, plonk::Error};
@@ -31,8 +33,11 @@ pub(crate) struct CallDataCopyGadget<F> {
     tx_id: Cell<F>,
     call_data_length: Cell<F>,
     call_data_offset: Cell<F>, // Only used in the internal call
+    caller_id: Cell<F>,        // Only used in the internal call
     memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
     memory_copier_gas: MemoryCopierGasGadget<F>,
+    src_addr: MinMaxGadget<F, N_BYTES_U64>,
+    copy_rwc_inc: Cell<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
@@ -56,9 +61,10 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
         let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
         let call_data_length = cb.query_cell();
         let call_data_offset = cb.query_cell();
+        let caller_id = cb.query_cell();
 
-        // Lookup the calldata_length and caller_address in Tx context table or
-        // Call context table
+        // Lookup the calldata_length in Tx context table or the
+        // calldata_length, calldata_offset and caller_id in Call context table
         cb.condition(cb.curr.state.is_root.expr(), |cb| {
             cb.tx_context_lookup(
                 tx_id.expr(),
@@ -69,7 +75,8 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
             cb.require_zero(
                 "call_data_offset == 0 in the root call",
                 call_data_offset.expr(),
-            )
+            );
+            cb.require_zero("caller_id == 0 in the root call", caller_id.expr());
         });
         cb.condition(1.expr() - cb.curr.state.is_root.expr(), |cb| {
             cb.call_context_lookup(
@@ -83,7 +90,13 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
                 None,
                 CallContextFieldTag::CallDataOffset,
                 call_data_offset.expr(),
-            )
+            );
+            cb.call_context_lookup(
+                false.expr(),
+                None,
+                CallContextFieldTag::CallerId,
+                caller_id.expr(),
+            );
         });
 
         // Calculate the next memory size and the gas cost for this memory
@@ -99,49 +112,38 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
             memory_expansion.gas_cost(),
         );
 
-        // Constrain the next step CopyToMemory if length != 0
-        cb.constrain_next_step(
-            ExecutionState::CopyToMemory,
-            Some(memory_address.has_length()),
-            |cb| {
-                let next_src_addr = cb.query_cell();
-                let next_dst_addr = cb.query_cell();
-                let next_bytes_left = cb.query_cell();
-                let next_src_addr_end = cb.query_cell();
-                let next_from_tx = cb.query_cell();
-                let next_tx_id = cb.query_cell();
-                cb.require_equal(
-                    "next_src_addr = data_offset + call_data_offset",
-                    next_src_addr.expr(),
-                    from_bytes::expr(&data_offset.cells) + call_data_offset.expr(),
-                );
-                cb.require_equal(
-                    "next_dst_addr = memory_offset",
-                    next_dst_addr.expr(),
-                    memory_address.offset(),
-                );
-                cb.require_equal(
-                    "next_bytes_left = length",
-                    next_bytes_left.expr(),
-                    memory_address.length(),
-                );
-                cb.require_equal(
-                    "next_src_addr_end = call_data_length + call_data_offset",
-                    next_src_addr_end.expr(),
-                    call_data_length.expr() + call_data_offset.expr(),
-                );
-                cb.require_equal(
-                    "next_from_tx = is_root",
-                    next_from_tx.expr(),
-                    cb.curr.state.is_root.expr(),
-                );
-                cb.require_equal("next_tx_id = tx_id", next_tx_id.expr(), tx_id.expr());
-            },
+        // Copy the call data, which is read as 0 past its end, with a lookup
+        // to the copy table
+        let src_addr_end = call_data_offset.expr() + call_data_length.expr();
+        let src_addr = MinMaxGadget::construct(
+            cb,
+            call_data_offset.expr() + from_bytes::expr(&data_offset.cells),
+            src_addr_end.clone(),
         );
+        let copy_rwc_inc = cb.query_cell();
+        cb.condition(memory_address.has_length(), |cb| {
+            cb.copy_table_lookup(
+                select::expr(cb.curr.state.is_root.expr(), tx_id.expr(), caller_id.expr()),
+                select::expr(
+                    cb.curr.state.is_root.expr(),
+                    CopyDataType::TxCalldata.expr(),
+                    CopyDataType::Memory.expr(),
+                ),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                src_addr.min(),
+                src_addr_end,
+                memory_address.offset(),
+                memory_address.length(),
+                copy_rwc_inc.expr(),
+                0.expr(),
+            );
+        });
 
         // State transition
         let step_state_transition = StepStateTransition {
-            // 1 tx id lookup + 3 stack pop + option(calldatalength lookup)
+            // 3 stack pop + 1 tx id lookup + option(3 call context lookups) +
+            // the memory accesses of the copy
             rw_counter: Delta(cb.rw_counter_offset()),
             program_counter: Delta(1.expr()),
             stack_pointer: Delta(3.expr()),
@@ -162,8 +164,11 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
             tx_id,
             call_data_length,
             call_data_offset,
+            caller_id,
             memory_expansion,
             memory_copier_gas,
+            src_addr,
+            copy_rwc_inc,
         }
     }
 
@@ -196,16 +201,18 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
         self.tx_id
             .assign(region, offset, Some(F::from(tx.id as u64)))?;
 
-        // Call data length and call data offset
-        let (call_data_length, call_data_offset) = if call.is_root {
-            (tx.call_data_length as u64, 0_u64)
+        // Call data length, call data offset and caller id
+        let (call_data_length, call_data_offset, caller_id) = if call.is_root {
+            (tx.call_data_length as u64, 0_u64, 0)
         } else {
-            (call.call_data_length, call.call_data_offset)
+            (call.call_data_length, call.call_data_offset, call.caller_id)
         };
         self.call_data_length
             .assign(region, offset, Some(F::from(call_data_length as u64)))?;
         self.call_data_offset
             .assign(region, offset, Some(F::from(call_data_offset as u64)))?;
+        self.caller_id
+            .assign(region, offset, Some(F::from(caller_id as u64)))?;
 
         // Memory expansion
         let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
@@ -222,6 +229,21 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
             memory_expansion_gas_cost as u64,
         )?;
 
+        // The memory of the caller is read up to the end of the call data,
+        // and every byte is written
+        let src_addr = call_data_offset + data_offset.low_u64();
+        let src_addr_end = call_data_offset + call_data_length;
+        self.src_addr
+            .assign(region, offset, F::from(src_addr), F::from(src_addr_end))?;
+        let copy_rwc_inc = if call.is_root {
+            length.as_u64()
+        } else {
+            let src_bytes = src_addr_end - src_addr.min(src_addr_end);
+            length.as_u64() + src_bytes.min(length.as_u64())
+        };
+        self.copy_rwc_inc
+            .assign(region, offset, Some(F::from(copy_rwc_inc)))?;
+
         Ok(())
     }
 }
@@ -229,12 +251,15 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
 #[cfg(test)]
 mod test {
     use crate::evm_circuit::{
-        execution::memory_copy::test::make_memory_copy_steps,
         step::ExecutionState,
         table::{CallContextFieldTag, RwTableTag},
-        test::{calc_memory_copier_gas_cost, rand_bytes, run_test_circuit_incomplete_fixed_table},
+        test::{
+            calc_memory_copier_gas_cost, make_copy_event, rand_bytes,
+            run_test_circuit_incomplete_fixed_table,
+        },
         witness::{Block, Bytecode, Call, CodeSource, ExecStep, Rw, RwMap, Transaction},
     };
+    use bus_mapping::circuit_input_builder::{CopyDataType, NumberOrHash};
     use eth_types::{
         evm_types::{GasCost, OpcodeId},
         ToBigEndian, Word,
@@ -328,22 +353,22 @@ mod test {
             ..Default::default()
         }];
 
+        let mut copy_events = Vec::new();
         if !length.is_zero() {
-            make_memory_copy_steps(
-                call_id,
+            copy_events.push(make_copy_event(
+                CopyDataType::TxCalldata,
+                NumberOrHash::Number(1),
                 &call_data,
                 0,
                 data_offset.as_u64(),
+                CopyDataType::Memory,
+                call_id,
                 memory_offset.as_u64(),
                 length.as_usize(),
-                true,
-                100,
-                1024,
-                next_memory_word_size * 32,
                 &mut rw_counter,
                 &mut rws,
-                &mut steps,
-            );
+                &mut steps[0].rw_indices,
+            ));
         }
 
         steps.push(ExecStep {
@@ -374,6 +399,7 @@ mod test {
             }],
             rws,
             bytecodes: vec![bytecode],
+            copy_events,
             ..Default::default()
         };
         assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
@@ -399,7 +425,8 @@ mod test {
             ]
             .concat(),
         );
-        let call_id = 1;
+        let caller_id = 1;
+        let call_id = 2;
         let call_data = rand_bytes(call_data_length.as_usize());
 
         let mut rws = RwMap(
@@ -454,12 +481,19 @@ mod test {
                             field_tag: CallContextFieldTag::CallDataOffset,
                             value: call_data_offset,
                         },
+                        Rw::CallContext {
+                            rw_counter: 7,
+                            is_write: false,
+                            call_id,
+                            field_tag: CallContextFieldTag::CallerId,
+                            value: Word::from(caller_id),
+                        },
                     ],
                 ),
             ]
             .into(),
         );
-        let mut rw_counter = 7;
+        let mut rw_counter = 8;
 
         let curr_memory_word_size =
             (call_data_length.as_u64() + call_data_length.as_u64() + 31) / 32;
@@ -485,6 +519,7 @@ mod test {
                 (RwTableTag::CallContext, 0),
                 (RwTableTag::CallContext, 1),
                 (RwTableTag::CallContext, 2),
+                (RwTableTag::CallContext, 3),
             ],
             execution_state: ExecutionState::CALLDATACOPY,
             rw_counter: 1,
@@ -497,22 +532,22 @@ mod test {
             ..Default::default()
         }];
 
+        let mut copy_events = Vec::new();
         if !length.is_zero() {
-            make_memory_copy_steps(
-                call_id,
+            copy_events.push(make_copy_event(
+                CopyDataType::Memory,
+                NumberOrHash::Number(caller_id),
                 &call_data,
                 call_data_offset.as_u64(),
                 call_data_offset.as_u64() + data_offset.as_u64(),
+                CopyDataType::Memory,
+                call_id,
                 memory_offset.as_u64(),
                 length.as_usize(),
-                false,
-                100,
-                1024,
-                next_memory_word_size * 32,
                 &mut rw_counter,
                 &mut rws,
-                &mut steps,
-            );
+                &mut steps[0].rw_indices,
+            ));
         }
 
         steps.push(ExecStep {
@@ -533,6 +568,7 @@ mod test {
                     id: call_id,
                     is_root: false,
                     is_create: false,
+                    caller_id,
                     call_data_length: call_data_length.as_u64(),
                     call_data_offset: call_data_offset.as_u64(),
                     code_source: CodeSource::Account(bytecode.hash),
@@ -543,6 +579,7 @@ mod test {
             }],
             rws,
             bytecodes: vec![bytecode],
+            copy_events,
             ..Default::default()
         };
         assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
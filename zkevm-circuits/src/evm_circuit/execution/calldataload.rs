@@ -0,0 +1,268 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_MEMORY_ADDRESS, N_BYTES_WORD},
+        step::ExecutionState,
+        table::{CallContextFieldTag, TxContextFieldTag},
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
+            from_bytes,
+            math_gadget::IsZeroGadget,
+            memory_gadget::BufferReaderGadget,
+            select, sum, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use array_init::array_init;
+use eth_types::{Field, ToBigEndian, ToLittleEndian};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct CallDataLoadGadget<F> {
+    same_context: SameContextGadget<F>,
+    data_offset: Word<F>,
+    // Check if the data offset fits in N_BYTES_MEMORY_ADDRESS bytes
+    offset_within_range: IsZeroGadget<F>,
+    tx_id: Cell<F>,
+    call_data_length: Cell<F>,
+    call_data_offset: Cell<F>, // Only used in the internal call
+    caller_id: Cell<F>,        // Only used in the internal call
+    // The address of the calldata in the tx calldata or in the caller's memory
+    src_addr: Cell<F>,
+    // The end address of the calldata
+    src_addr_end: Cell<F>,
+    buffer_reader: BufferReaderGadget<F, N_BYTES_WORD, N_BYTES_MEMORY_ADDRESS>,
+}
+
+impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
+    const NAME: &'static str = "CALLDATALOAD";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::CALLDATALOAD;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        // Pop data_offset from stack
+        let data_offset = cb.query_word();
+        cb.stack_pop(data_offset.expr());
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let call_data_length = cb.query_cell();
+        let call_data_offset = cb.query_cell();
+        let caller_id = cb.query_cell();
+
+        // Lookup the calldata_length in Tx context table or the
+        // calldata_length, calldata_offset and caller_id in Call context table
+        cb.condition(cb.curr.state.is_root.expr(), |cb| {
+            cb.tx_context_lookup(
+                tx_id.expr(),
+                TxContextFieldTag::CallDataLength,
+                None,
+                call_data_length.expr(),
+            );
+            cb.require_zero(
+                "call_data_offset == 0 in the root call",
+                call_data_offset.expr(),
+            );
+            cb.require_zero("caller_id == 0 in the root call", caller_id.expr());
+        });
+        cb.condition(1.expr() - cb.curr.state.is_root.expr(), |cb| {
+            cb.call_context_lookup(
+                false.expr(),
+                None,
+                CallContextFieldTag::CallDataLength,
+                call_data_length.expr(),
+            );
+            cb.call_context_lookup(
+                false.expr(),
+                None,
+                CallContextFieldTag::CallDataOffset,
+                call_data_offset.expr(),
+            );
+            cb.call_context_lookup(
+                false.expr(),
+                None,
+                CallContextFieldTag::CallerId,
+                caller_id.expr(),
+            );
+        });
+
+        // When the data offset doesn't fit in N_BYTES_MEMORY_ADDRESS bytes it
+        // is beyond any calldata, so we set src_addr to src_addr_end to make
+        // the buffer reader read nothing and the loaded word 0.
+        let offset_within_range =
+            IsZeroGadget::construct(cb, sum::expr(&data_offset.cells[N_BYTES_MEMORY_ADDRESS..]));
+        let src_addr = cb.query_cell();
+        let src_addr_end = cb.query_cell();
+        cb.require_equal(
+            "src_addr_end == call_data_length + call_data_offset",
+            src_addr_end.expr(),
+            call_data_length.expr() + call_data_offset.expr(),
+        );
+        cb.require_equal(
+            "src_addr == data_offset + call_data_offset if offset is within range else src_addr_end",
+            src_addr.expr(),
+            select::expr(
+                offset_within_range.expr(),
+                from_bytes::expr(&data_offset.cells[..N_BYTES_MEMORY_ADDRESS])
+                    + call_data_offset.expr(),
+                src_addr_end.expr(),
+            ),
+        );
+
+        // CALLDATALOAD always loads a whole word
+        let buffer_reader = BufferReaderGadget::construct(cb, &src_addr, &src_addr_end);
+        cb.require_equal(
+            "all the 32 bytes are loaded",
+            buffer_reader.has_data(N_BYTES_WORD - 1),
+            1.expr(),
+        );
+
+        for i in 0..N_BYTES_WORD {
+            let read_flag = buffer_reader.read_flag(i);
+            // Read bytes[i] from Tx in the root call
+            cb.condition(cb.curr.state.is_root.expr() * read_flag.clone(), |cb| {
+                cb.tx_context_lookup(
+                    tx_id.expr(),
+                    TxContextFieldTag::CallData,
+                    Some(src_addr.expr() + i.expr()),
+                    buffer_reader.byte(i),
+                )
+            });
+            // Read bytes[i] from the caller's memory in the internal call
+            cb.condition(
+                (1.expr() - cb.curr.state.is_root.expr()) * read_flag,
+                |cb| {
+                    cb.memory_lookup(
+                        0.expr(),
+                        src_addr.expr() + i.expr(),
+                        buffer_reader.byte(i),
+                        Some(caller_id.expr()),
+                    )
+                },
+            );
+        }
+
+        // The bytes are read in big-endian order, so reverse them to compute
+        // the RLC of the loaded word
+        let value = Word::random_linear_combine_expr(
+            array_init(|i| buffer_reader.byte(N_BYTES_WORD - 1 - i)),
+            cb.power_of_randomness(),
+        );
+        cb.stack_push(value);
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            // 1 stack pop + 1 tx id lookup + option(3 call context lookups +
+            // memory reads) + 1 stack push
+            rw_counter: Delta(cb.rw_counter_offset()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(0.expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition, None);
+
+        Self {
+            same_context,
+            data_offset,
+            offset_within_range,
+            tx_id,
+            call_data_length,
+            call_data_offset,
+            caller_id,
+            src_addr,
+            src_addr_end,
+            buffer_reader,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let data_offset = block.rws[step.rw_indices[0]].stack_value();
+        let value = block.rws[*step.rw_indices.last().unwrap()].stack_value();
+        self.data_offset
+            .assign(region, offset, Some(data_offset.to_le_bytes()))?;
+        let offset_high_bytes_sum = data_offset.to_le_bytes()[N_BYTES_MEMORY_ADDRESS..]
+            .iter()
+            .fold(0, |acc, byte| acc + *byte as u64);
+        self.offset_within_range
+            .assign(region, offset, F::from(offset_high_bytes_sum))?;
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx.id as u64)))?;
+
+        // Call data length, call data offset and caller id
+        let (call_data_length, call_data_offset, caller_id) = if call.is_root {
+            (tx.call_data_length as u64, 0_u64, 0)
+        } else {
+            (call.call_data_length, call.call_data_offset, call.caller_id)
+        };
+        self.call_data_length
+            .assign(region, offset, Some(F::from(call_data_length)))?;
+        self.call_data_offset
+            .assign(region, offset, Some(F::from(call_data_offset)))?;
+        self.caller_id
+            .assign(region, offset, Some(F::from(caller_id as u64)))?;
+
+        let src_addr_end = call_data_length + call_data_offset;
+        let src_addr = if offset_high_bytes_sum == 0 {
+            data_offset.low_u64() + call_data_offset
+        } else {
+            src_addr_end
+        };
+        self.src_addr
+            .assign(region, offset, Some(F::from(src_addr)))?;
+        self.src_addr_end
+            .assign(region, offset, Some(F::from(src_addr_end)))?;
+
+        self.buffer_reader.assign(
+            region,
+            offset,
+            src_addr,
+            src_addr_end,
+            &value.to_be_bytes(),
+            &[1u8; N_BYTES_WORD],
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, Word};
+
+    fn test_ok(offset: Word) {
+        let bytecode = bytecode! {
+            PUSH32(offset)
+            #[start]
+            CALLDATALOAD
+            STOP
+        };
+        assert_eq!(run_test_circuits(bytecode), Ok(()));
+    }
+
+    #[test]
+    fn calldataload_gadget_simple() {
+        test_ok(Word::from(0x00));
+        test_ok(Word::from(0x20));
+    }
+
+    #[test]
+    fn calldataload_gadget_offset_overflow() {
+        test_ok(Word::from(0x100_0000_0000u64));
+        test_ok(Word::MAX);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        table::BlockContextFieldTag,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
+            Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{Field, ToLittleEndian};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ChainIdGadget<F> {
+    same_context: SameContextGadget<F>,
+    chain_id: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ChainIdGadget<F> {
+    const NAME: &'static str = "CHAINID";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::CHAINID;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let chain_id = cb.query_cell();
+        cb.stack_push(chain_id.expr());
+
+        // Lookup block table with chain id
+        cb.block_lookup(BlockContextFieldTag::ChainId.expr(), None, chain_id.expr());
+
+        // State transition
+        let opcode = cb.query_cell();
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(1.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta((-1).expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition, None);
+
+        Self {
+            same_context,
+            chain_id,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let chain_id = block.rws[step.rw_indices[0]].stack_value();
+
+        self.chain_id.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                chain_id.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::bytecode;
+
+    fn test_ok() {
+        let bytecode = bytecode! {
+            #[start]
+            CHAINID
+            STOP
+        };
+        assert_eq!(run_test_circuits(bytecode), Ok(()));
+    }
+    #[test]
+    fn chainid_gadget_test() {
+        test_ok();
+    }
+}
@@ -0,0 +1,220 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE, N_BYTES_U64},
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            from_bytes,
+            math_gadget::MinMaxGadget,
+            memory_gadget::{MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget},
+            Cell, MemoryAddress,
+        },
+        witness::{Block, Call, CodeSource, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::circuit_input_builder::CopyDataType;
+use eth_types::Field;
+use eth_types::ToLittleEndian;
+use halo2_proofs::{circuit::Region, plonk::Error};
+use std::convert::TryInto;
+
+#[derive(Clone, Debug)]
+pub(crate) struct CodeCopyGadget<F> {
+    same_context: SameContextGadget<F>,
+    memory_address: MemoryAddressGadget<F>,
+    code_offset: MemoryAddress<F>,
+    code_size: Cell<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_copier_gas: MemoryCopierGasGadget<F>,
+    src_addr: MinMaxGadget<F, N_BYTES_U64>,
+}
+
+impl<F: Field> ExecutionGadget<F> for CodeCopyGadget<F> {
+    const NAME: &'static str = "CODECOPY";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::CODECOPY;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let memory_offset = cb.query_cell();
+        let code_offset = cb.query_rlc();
+        let length = cb.query_rlc();
+
+        // Pop memory_offset, code_offset, length from stack
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(code_offset.expr());
+        cb.stack_pop(length.expr());
+
+        let memory_address = MemoryAddressGadget::construct(cb, memory_offset, length);
+
+        // Lookup the length of the bytecode being executed
+        let code_size = cb.query_cell();
+        cb.bytecode_length(cb.curr.state.code_source.expr(), code_size.expr());
+
+        // Calculate the next memory size and the gas cost for this memory
+        // access
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [memory_address.address()],
+        );
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            memory_address.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        // Copy the bytecode, which is read as 0 past its end, with a lookup
+        // to the copy table.  Only the memory writes take rw counters.
+        let src_addr =
+            MinMaxGadget::construct(cb, from_bytes::expr(&code_offset.cells), code_size.expr());
+        cb.condition(memory_address.has_length(), |cb| {
+            cb.copy_table_lookup(
+                cb.curr.state.code_source.expr(),
+                CopyDataType::Bytecode.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                src_addr.min(),
+                code_size.expr(),
+                memory_address.offset(),
+                memory_address.length(),
+                memory_address.length(),
+                0.expr(),
+            );
+        });
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            // 3 stack pop + the memory writes of the copy
+            rw_counter: Delta(cb.rw_counter_offset()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(3.expr()),
+            memory_word_size: To(memory_expansion.next_memory_word_size()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(
+            cb,
+            opcode,
+            step_state_transition,
+            Some(memory_copier_gas.gas_cost()),
+        );
+
+        Self {
+            same_context,
+            memory_address,
+            code_offset,
+            code_size,
+            memory_expansion,
+            memory_copier_gas,
+            src_addr,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [memory_offset, code_offset, length] =
+            [step.rw_indices[0], step.rw_indices[1], step.rw_indices[2]]
+                .map(|idx| block.rws[idx].stack_value());
+        let memory_address =
+            self.memory_address
+                .assign(region, offset, memory_offset, length, block.randomness)?;
+        self.code_offset.assign(
+            region,
+            offset,
+            Some(
+                code_offset.to_le_bytes()[..N_BYTES_MEMORY_ADDRESS]
+                    .try_into()
+                    .unwrap(),
+            ),
+        )?;
+
+        let CodeSource::Account(code_hash) = &call.code_source;
+        let code_size = block
+            .bytecodes
+            .iter()
+            .find(|bytecode| bytecode.hash == *code_hash)
+            .expect("executed bytecode must be in the block")
+            .bytes
+            .len();
+        self.code_size
+            .assign(region, offset, Some(F::from(code_size as u64)))?;
+
+        // Memory expansion
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+
+        self.memory_copier_gas.assign(
+            region,
+            offset,
+            length.as_u64(),
+            memory_expansion_gas_cost as u64,
+        )?;
+
+        self.src_addr.assign(
+            region,
+            offset,
+            F::from(code_offset.low_u64()),
+            F::from(code_size as u64),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, Word};
+
+    fn test_ok(memory_offset: Word, code_offset: Word, length: Word) {
+        let bytecode = bytecode! {
+            PUSH32(length)
+            PUSH32(code_offset)
+            PUSH32(memory_offset)
+            #[start]
+            CODECOPY
+            STOP
+        };
+        assert_eq!(run_test_circuits(bytecode), Ok(()));
+    }
+
+    #[test]
+    fn codecopy_gadget_simple() {
+        test_ok(Word::from(0x40), Word::from(0x00), Word::from(0x20));
+    }
+
+    #[test]
+    fn codecopy_gadget_multi_step() {
+        test_ok(Word::from(0x00), Word::from(0x10), Word::from(0x70));
+    }
+
+    #[test]
+    fn codecopy_gadget_out_of_bound() {
+        test_ok(Word::from(0x40), Word::from(0x60), Word::from(0x30));
+    }
+
+    #[test]
+    fn codecopy_gadget_zero_length() {
+        test_ok(Word::from(0x40), Word::from(0x00), Word::from(0x00));
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_U64,
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
+            from_bytes, RandomLinearCombination,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{Field, ToLittleEndian};
+use halo2_proofs::{circuit::Region, plonk::Error};
+use std::convert::TryInto;
+
+#[derive(Clone, Debug)]
+pub(crate) struct CodesizeGadget<F> {
+    same_context: SameContextGadget<F>,
+    codesize: RandomLinearCombination<F, N_BYTES_U64>,
+}
+
+impl<F: Field> ExecutionGadget<F> for CodesizeGadget<F> {
+    const NAME: &'static str = "CODESIZE";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::CODESIZE;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        // Lookup the length of the bytecode being executed
+        let codesize = cb.query_rlc();
+        cb.bytecode_length(
+            cb.curr.state.code_source.expr(),
+            from_bytes::expr(&codesize.cells),
+        );
+
+        // Push the value on the stack
+        cb.stack_push(codesize.expr());
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(1.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta((-1).expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition, None);
+
+        Self {
+            same_context,
+            codesize,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let codesize = block.rws[step.rw_indices[0]].stack_value();
+        self.codesize.assign(
+            region,
+            offset,
+            Some(codesize.to_le_bytes()[..N_BYTES_U64].try_into().unwrap()),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::bytecode;
+
+    #[test]
+    fn codesize_gadget_simple() {
+        let bytecode = bytecode! {
+            PUSH32(0)
+            POP
+            #[start]
+            CODESIZE
+            STOP
+        };
+        assert_eq!(run_test_circuits(bytecode), Ok(()));
+    }
+}
@@ -0,0 +1,403 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_GAS,
+        step::ExecutionState,
+        table::{AccountFieldTag, BlockContextFieldTag, CallContextFieldTag, TxContextFieldTag},
+        util::{
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
+            math_gadget::{
+                AddWordsGadget, ConstantDivisionGadget, MinMaxGadget, MulWordByU64Gadget,
+            },
+            Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct EndTxGadget<F> {
+    tx_id: Cell<F>,
+    tx_gas: Cell<F>,
+    tx_gas_price: Word<F>,
+    tx_caller_address: Cell<F>,
+    refund: Cell<F>,
+    max_refund: ConstantDivisionGadget<F, N_BYTES_GAS>,
+    effective_refund: MinMaxGadget<F, N_BYTES_GAS>,
+    mul_gas_price_by_refund: MulWordByU64Gadget<F>,
+    add_caller_balance: AddWordsGadget<F, 2>,
+    add_base_fee_and_effective_tip: AddWordsGadget<F, 2>,
+    mul_effective_tip_by_gas_used: MulWordByU64Gadget<F>,
+    coinbase: Cell<F>,
+    add_coinbase_balance: AddWordsGadget<F, 2>,
+}
+
+impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
+    const NAME: &'static str = "EndTx";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::EndTx;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let [tx_gas, tx_caller_address] =
+            [TxContextFieldTag::Gas, TxContextFieldTag::CallerAddress]
+                .map(|field_tag| cb.tx_context(tx_id.expr(), field_tag, None));
+        let tx_gas_price = cb.tx_context_as_word(tx_id.expr(), TxContextFieldTag::GasPrice, None);
+
+        // Calculate the effective refund, which is capped by a fifth of the
+        // used gas (EIP-3529)
+        let refund = cb.query_cell();
+        cb.tx_refund_read(tx_id.expr(), refund.expr());
+        let gas_used = tx_gas.expr() - cb.curr.state.gas_left.expr();
+        let max_refund = ConstantDivisionGadget::construct(
+            cb,
+            gas_used.clone(),
+            GasCost::MAX_REFUND_QUOTIENT_OF_GAS_USED.as_u64(),
+        );
+        let effective_refund = MinMaxGadget::construct(cb, max_refund.quotient(), refund.expr());
+
+        // Refund the gas left and the effective refund to the caller at the
+        // effective gas price, which is what the gas is bought for in BeginTx
+        let gas_to_refund = cb.curr.state.gas_left.expr() + effective_refund.min();
+        let gas_to_refund = cb.copy(gas_to_refund);
+        let mul_gas_price_by_refund =
+            MulWordByU64Gadget::construct(cb, tx_gas_price.clone(), gas_to_refund, true);
+        let caller_balance_prev = cb.query_word();
+        let add_caller_balance = AddWordsGadget::construct(
+            cb,
+            [
+                caller_balance_prev.clone(),
+                mul_gas_price_by_refund.product().clone(),
+            ],
+        );
+        cb.require_zero(
+            "Caller has not too much balance",
+            add_caller_balance.carry().expr(),
+        );
+        cb.account_write(
+            tx_caller_address.expr(),
+            AccountFieldTag::Balance,
+            add_caller_balance.sum().expr(),
+            caller_balance_prev.expr(),
+        );
+
+        // Credit the coinbase with only the tip of the gas used, which is
+        // gas_price - base_fee per gas, since the base fee is burnt (EIP
+        // 1559).  BeginTx checks gas_price >= base_fee.
+        let base_fee = cb.query_word();
+        cb.block_lookup(BlockContextFieldTag::BaseFee.expr(), None, base_fee.expr());
+        let effective_tip = cb.query_word();
+        let add_base_fee_and_effective_tip =
+            AddWordsGadget::construct(cb, [base_fee, effective_tip.clone()]);
+        cb.require_zero(
+            "base_fee + effective_tip doesn't overflow",
+            add_base_fee_and_effective_tip.carry().expr(),
+        );
+        cb.require_equal(
+            "gas_price == base_fee + effective_tip",
+            tx_gas_price.expr(),
+            add_base_fee_and_effective_tip.sum().expr(),
+        );
+        let gas_to_reward = cb.copy(gas_used - effective_refund.min());
+        let mul_effective_tip_by_gas_used =
+            MulWordByU64Gadget::construct(cb, effective_tip, gas_to_reward, true);
+        let coinbase = cb.query_cell();
+        cb.block_lookup(BlockContextFieldTag::Coinbase.expr(), None, coinbase.expr());
+        let coinbase_balance_prev = cb.query_word();
+        let add_coinbase_balance = AddWordsGadget::construct(
+            cb,
+            [
+                coinbase_balance_prev.clone(),
+                mul_effective_tip_by_gas_used.product().clone(),
+            ],
+        );
+        cb.require_zero(
+            "Coinbase has not too much balance",
+            add_coinbase_balance.carry().expr(),
+        );
+        cb.account_write(
+            coinbase.expr(),
+            AccountFieldTag::Balance,
+            add_coinbase_balance.sum().expr(),
+            coinbase_balance_prev.expr(),
+        );
+
+        // When the next transaction begins, set its tx_id in the call context
+        // of its call, which uses the rw_counter of its first step as call_id.
+        let is_next_begin_tx = cb.next.execution_state_selector(ExecutionState::BeginTx);
+        let next_call_id = cb.next.state.rw_counter.expr();
+        cb.condition(is_next_begin_tx, |cb| {
+            cb.call_context_lookup(
+                true.expr(),
+                Some(next_call_id),
+                CallContextFieldTag::TxId,
+                tx_id.expr() + 1.expr(),
+            );
+
+            cb.require_step_state_transition(StepStateTransition {
+                // 5 read/write including:
+                //   - Read CallContext TxId
+                //   - Read TxRefund
+                //   - Write Account Balance
+                //   - Write Account Balance
+                //   - Write CallContext TxId
+                rw_counter: Delta(5.expr()),
+                ..StepStateTransition::any()
+            });
+        });
+
+        Self {
+            tx_id,
+            tx_gas,
+            tx_gas_price,
+            tx_caller_address,
+            refund,
+            max_refund,
+            effective_refund,
+            mul_gas_price_by_refund,
+            add_caller_balance,
+            add_base_fee_and_effective_tip,
+            mul_effective_tip_by_gas_used,
+            coinbase,
+            add_coinbase_balance,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let gas_used = tx.gas - step.gas_left;
+        let refund = block.rws[step.rw_indices[1]].tx_refund_value().low_u64();
+        let [(caller_balance, caller_balance_prev), (coinbase_balance, coinbase_balance_prev)] =
+            [step.rw_indices[2], step.rw_indices[3]].map(|idx| block.rws[idx].account_value_pair());
+
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx.id as u64)))?;
+        self.tx_gas.assign(region, offset, Some(F::from(tx.gas)))?;
+        self.tx_gas_price
+            .assign(region, offset, Some(tx.gas_price.to_le_bytes()))?;
+        self.tx_caller_address
+            .assign(region, offset, tx.caller_address.to_scalar())?;
+        self.refund.assign(region, offset, Some(F::from(refund)))?;
+        let (max_refund, _) = self.max_refund.assign(region, offset, gas_used as u128)?;
+        self.effective_refund.assign(
+            region,
+            offset,
+            F::from(max_refund as u64),
+            F::from(refund),
+        )?;
+        let effective_refund = std::cmp::min(max_refund as u64, refund);
+
+        let gas_to_refund = step.gas_left + effective_refund;
+        let gas_fee_refund = tx.gas_price * gas_to_refund;
+        self.mul_gas_price_by_refund.assign(
+            region,
+            offset,
+            tx.gas_price,
+            gas_to_refund,
+            gas_fee_refund,
+        )?;
+        self.add_caller_balance.assign(
+            region,
+            offset,
+            [caller_balance_prev, gas_fee_refund],
+            caller_balance,
+        )?;
+
+        let base_fee = block.context.base_fee;
+        let effective_tip = tx.gas_price - base_fee;
+        self.add_base_fee_and_effective_tip.assign(
+            region,
+            offset,
+            [base_fee, effective_tip],
+            tx.gas_price,
+        )?;
+        let gas_to_reward = gas_used - effective_refund;
+        let coinbase_reward = effective_tip * gas_to_reward;
+        self.mul_effective_tip_by_gas_used.assign(
+            region,
+            offset,
+            effective_tip,
+            gas_to_reward,
+            coinbase_reward,
+        )?;
+        self.coinbase
+            .assign(region, offset, block.context.coinbase.to_scalar())?;
+        self.add_coinbase_balance.assign(
+            region,
+            offset,
+            [coinbase_balance_prev, coinbase_reward],
+            coinbase_balance,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::{
+        step::ExecutionState,
+        table::{AccountFieldTag, CallContextFieldTag, RwTableTag},
+        test::{rand_fp, run_test_circuit_incomplete_fixed_table},
+        witness::{
+            Block, BlockContext, Bytecode, Call, CodeSource, ExecStep, Rw, RwMap, Transaction,
+        },
+    };
+    use eth_types::{address, evm_types::OpcodeId, Word};
+
+    fn test_ok(gas_fee_cap: Word, gas_tip_cap: Word, base_fee: Word, gas_left: u64, refund: u64) {
+        let caller_address = address!("0x00000000000000000000000000000000000000fe");
+        let coinbase = address!("0x00000000000000000000000000000000000000cb");
+        let gas = 100000;
+        let gas_price = std::cmp::min(gas_fee_cap, base_fee + gas_tip_cap);
+
+        let gas_used = gas - gas_left;
+        let effective_refund = std::cmp::min(gas_used / 5, refund);
+        let caller_balance_prev = Word::from(10_i32).pow(20_i32.into());
+        let caller_balance = caller_balance_prev + gas_price * (gas_left + effective_refund);
+        let coinbase_balance_prev = Word::from(10_i32).pow(18_i32.into());
+        let coinbase_balance =
+            coinbase_balance_prev + (gas_price - base_fee) * (gas_used - effective_refund);
+
+        let randomness = rand_fp();
+        let bytecode = Bytecode::new(vec![OpcodeId::STOP.as_u8()]);
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                id: 1,
+                gas,
+                gas_price,
+                gas_fee_cap,
+                gas_tip_cap,
+                caller_address,
+                calls: vec![Call {
+                    id: 1,
+                    is_root: true,
+                    code_source: CodeSource::Account(bytecode.hash),
+                    ..Default::default()
+                }],
+                steps: vec![
+                    ExecStep {
+                        rw_indices: vec![
+                            (RwTableTag::CallContext, 0),
+                            (RwTableTag::TxRefund, 0),
+                            (RwTableTag::Account, 0),
+                            (RwTableTag::Account, 1),
+                        ],
+                        execution_state: ExecutionState::EndTx,
+                        rw_counter: 1,
+                        gas_left,
+                        ..Default::default()
+                    },
+                    // STOP serves as a terminator after the last transaction
+                    ExecStep {
+                        execution_state: ExecutionState::STOP,
+                        rw_counter: 5,
+                        opcode: Some(OpcodeId::STOP),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            rws: RwMap(
+                [
+                    (
+                        RwTableTag::CallContext,
+                        vec![Rw::CallContext {
+                            rw_counter: 1,
+                            is_write: false,
+                            call_id: 1,
+                            field_tag: CallContextFieldTag::TxId,
+                            value: Word::one(),
+                        }],
+                    ),
+                    (
+                        RwTableTag::TxRefund,
+                        vec![Rw::TxRefund {
+                            rw_counter: 2,
+                            is_write: false,
+                            tx_id: 1,
+                            value: refund.into(),
+                            value_prev: refund.into(),
+                        }],
+                    ),
+                    (
+                        RwTableTag::Account,
+                        vec![
+                            Rw::Account {
+                                rw_counter: 3,
+                                is_write: true,
+                                account_address: caller_address,
+                                field_tag: AccountFieldTag::Balance,
+                                value: caller_balance,
+                                value_prev: caller_balance_prev,
+                            },
+                            Rw::Account {
+                                rw_counter: 4,
+                                is_write: true,
+                                account_address: coinbase,
+                                field_tag: AccountFieldTag::Balance,
+                                value: coinbase_balance,
+                                value_prev: coinbase_balance_prev,
+                            },
+                        ],
+                    ),
+                ]
+                .into(),
+            ),
+            bytecodes: vec![bytecode],
+            context: BlockContext {
+                coinbase,
+                base_fee,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    fn gwei(n: u64) -> Word {
+        Word::from(n) * Word::from(1_000_000_000)
+    }
+
+    #[test]
+    fn end_tx_gadget_legacy() {
+        // Legacy transactions carry their gas price as both caps
+        test_ok(gwei(2), gwei(2), Word::zero(), 0, 0);
+        test_ok(gwei(2), gwei(2), Word::zero(), 30000, 0);
+    }
+
+    #[test]
+    fn end_tx_gadget_dynamic_fee() {
+        // Effective gas price is base_fee + gas_tip_cap, so the coinbase gets
+        // all the tip
+        test_ok(gwei(10), gwei(2), gwei(7), 30000, 0);
+
+        // Effective gas price is capped by gas_fee_cap, so the coinbase gets
+        // less than gas_tip_cap
+        test_ok(gwei(10), gwei(5), gwei(7), 30000, 0);
+
+        // Effective gas price is exactly base_fee, so the coinbase gets
+        // nothing
+        test_ok(gwei(7), gwei(2), gwei(7), 30000, 0);
+    }
+
+    #[test]
+    fn end_tx_gadget_refund() {
+        // Refund is less than a fifth of the gas used
+        test_ok(gwei(10), gwei(5), gwei(7), 30000, 4800);
+
+        // Refund is capped by a fifth of the gas used
+        test_ok(gwei(10), gwei(5), gwei(7), 30000, 20000);
+    }
+}
@@ -0,0 +1,315 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::EMPTY_CODE_HASH_LE,
+        step::ExecutionState,
+        table::{AccountFieldTag, CallContextFieldTag},
+        util::{
+            common_gadget::{CallPrecheckFailureGadget, ContractAddressGadget},
+            constraint_builder::ConstraintBuilder,
+            math_gadget::{IsEqualGadget, IsZeroGadget},
+            memory_gadget::MemoryAddressGadget,
+            Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, StepAuxiliaryData, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::circuit_input_builder::CopyDataType;
+use eth_types::{evm_types::OpcodeId, Field, ToAddress, ToLittleEndian, ToScalar, U256};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorContractAddressCollisionGadget<F> {
+    opcode: Cell<F>,
+    is_create2: IsEqualGadget<F>,
+    creator_address: Cell<F>,
+    creator_nonce: Cell<F>,
+    contract_address: ContractAddressGadget<F>,
+    init_code_address: MemoryAddressGadget<F>,
+    nonce: Cell<F>,
+    code_hash: Cell<F>,
+    nonce_is_zero: IsZeroGadget<F>,
+    code_hash_is_zero: IsZeroGadget<F>,
+    code_hash_is_empty: IsEqualGadget<F>,
+    precheck_failure: CallPrecheckFailureGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorContractAddressCollisionGadget<F> {
+    const NAME: &'static str = "ErrorContractAddressCollision";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorContractAddressCollision;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.require_in_set(
+            "ErrorContractAddressCollision only happens in CREATE and CREATE2",
+            opcode.expr(),
+            vec![OpcodeId::CREATE.expr(), OpcodeId::CREATE2.expr()],
+        );
+        let is_create2 = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::CREATE2.expr());
+
+        // Derive the contract address from the address of the current callee,
+        // which is the creator, and its nonce in CREATE, or the salt and the
+        // hash of the init code in CREATE2
+        let creator_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+        let creator_nonce = cb.query_cell();
+        cb.account_read(
+            creator_address.expr(),
+            AccountFieldTag::Nonce,
+            creator_nonce.expr(),
+        );
+        let contract_address = ContractAddressGadget::construct(cb, is_create2.expr());
+        cb.require_equal(
+            "Creator address of the contract address",
+            creator_address.expr(),
+            contract_address.creator_address(),
+        );
+        cb.require_equal(
+            "Creator nonce of the contract address",
+            creator_nonce.expr(),
+            contract_address.creator_nonce(),
+        );
+
+        // The init code of CREATE2 is read from the memory of the current
+        // call into its RLC with a lookup to the copy table
+        let init_code_offset = cb.query_cell();
+        let init_code_length = cb.query_rlc();
+        let init_code_address =
+            MemoryAddressGadget::construct(cb, init_code_offset.clone(), init_code_length.clone());
+        cb.condition(is_create2.expr(), |cb| {
+            cb.require_equal(
+                "Length of the init code of the contract address",
+                init_code_address.length(),
+                contract_address.init_code_length(),
+            );
+            cb.require_zero(
+                "init_code_rlc == 0 when the init code is empty",
+                (1.expr() - init_code_address.has_length()) * contract_address.init_code_rlc(),
+            );
+        });
+        cb.condition(is_create2.expr() * init_code_address.has_length(), |cb| {
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                0.expr(),
+                CopyDataType::RlcAcc.expr(),
+                init_code_address.offset(),
+                init_code_address.address(),
+                0.expr(),
+                init_code_address.length(),
+                init_code_address.length(),
+                contract_address.init_code_rlc(),
+            );
+        });
+
+        // Read nonce and code hash of the contract account, where the code
+        // hash of a non-existing account is 0
+        let nonce = cb.query_cell();
+        let code_hash = cb.query_cell();
+        cb.account_read(
+            contract_address.contract_address(),
+            AccountFieldTag::Nonce,
+            nonce.expr(),
+        );
+        cb.account_read(
+            contract_address.contract_address(),
+            AccountFieldTag::CodeHash,
+            code_hash.expr(),
+        );
+        let nonce_is_zero = IsZeroGadget::construct(cb, nonce.expr());
+        let code_hash_is_zero = IsZeroGadget::construct(cb, code_hash.expr());
+        let empty_code_hash = Word::random_linear_combine_expr(
+            EMPTY_CODE_HASH_LE.map(|byte| byte.expr()),
+            cb.power_of_randomness(),
+        );
+        let code_hash_is_empty = IsEqualGadget::construct(cb, code_hash.expr(), empty_code_hash);
+
+        // Check the contract account already has non-zero nonce or code
+        cb.require_zero(
+            "Contract account has non-zero nonce or non-empty code",
+            nonce_is_zero.expr() * (code_hash_is_zero.expr() + code_hash_is_empty.expr()),
+        );
+
+        // Pop the operands, push 0 as the result of the creation which is not
+        // executed, and continue the current call, where the gas passed to
+        // the creation is consumed
+        let precheck_failure = CallPrecheckFailureGadget::construct(cb, opcode.expr(), 1.expr());
+
+        // The offset and the length of the init code are the 2nd and 3rd
+        // items of stack, and the salt is the 4th item of stack in CREATE2
+        cb.condition(is_create2.expr(), |cb| {
+            for (name, value, idx) in [
+                ("Offset of the init code", init_code_offset.expr(), 1),
+                ("Length of the init code", init_code_length.expr(), 2),
+                ("Salt of the contract address", contract_address.salt(), 3),
+            ] {
+                cb.require_equal(name, value, precheck_failure.operand(idx));
+            }
+        });
+
+        Self {
+            opcode,
+            is_create2,
+            creator_address,
+            creator_nonce,
+            contract_address,
+            init_code_address,
+            nonce,
+            code_hash,
+            nonce_is_zero,
+            code_hash_is_zero,
+            code_hash_is_empty,
+            precheck_failure,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+        self.is_create2.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::CREATE2.as_u64()),
+        )?;
+
+        let creator_address = block.rws[step.rw_indices[0]].call_context_value();
+        let (creator_nonce, _) = block.rws[step.rw_indices[1]].account_value_pair();
+        self.creator_address
+            .assign(region, offset, creator_address.to_scalar())?;
+        self.creator_nonce
+            .assign(region, offset, creator_nonce.to_scalar())?;
+        // The operands are read after the bytes of the init code of CREATE2
+        let (init_code, operand_rw_offset) = match (opcode, &step.aux_data) {
+            (OpcodeId::CREATE2, Some(StepAuxiliaryData::Create { init_code })) => {
+                (init_code.as_slice(), 4 + init_code.len())
+            }
+            _ => (&[][..], 4),
+        };
+        let [init_code_offset, init_code_length, salt] = if opcode == OpcodeId::CREATE2 {
+            [1, 2, 3].map(|idx| block.rws[step.rw_indices[operand_rw_offset + idx]].stack_value())
+        } else {
+            [U256::zero(); 3]
+        };
+        self.init_code_address.assign(
+            region,
+            offset,
+            init_code_offset,
+            init_code_length,
+            block.randomness,
+        )?;
+        self.contract_address.assign(
+            region,
+            offset,
+            opcode == OpcodeId::CREATE2,
+            creator_address.to_address(),
+            creator_nonce.as_u64(),
+            salt,
+            init_code,
+            block.randomness,
+        )?;
+
+        let [nonce, code_hash] =
+            [2, 3].map(|idx| block.rws[step.rw_indices[idx]].account_value_pair().0);
+        let nonce = nonce.to_scalar().unwrap();
+        let code_hash = Word::random_linear_combine(code_hash.to_le_bytes(), block.randomness);
+        self.nonce.assign(region, offset, Some(nonce))?;
+        self.code_hash.assign(region, offset, Some(code_hash))?;
+        self.nonce_is_zero.assign(region, offset, nonce)?;
+        self.code_hash_is_zero.assign(region, offset, code_hash)?;
+        self.code_hash_is_empty.assign(
+            region,
+            offset,
+            code_hash,
+            Word::random_linear_combine(EMPTY_CODE_HASH_LE, block.randomness),
+        )?;
+
+        self.precheck_failure
+            .assign(region, offset, block, step, operand_rw_offset)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::{test_circuits_using_geth_data, BytecodeTestConfig};
+    use bus_mapping::circuit_input_builder::{
+        get_create2_address_preimage, get_create_address_preimage,
+    };
+    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::Account, Address, Bytes, Word};
+    use sha3::{Digest, Keccak256};
+
+    /// Run CREATE or CREATE2 in the tx callee with `creator_nonce`, where an
+    /// account with `nonce` and `code` already exists at the created address.
+    fn test_ok(opcode: OpcodeId, creator_nonce: u64, nonce: u64, code: Vec<u8>) {
+        // The init code is the byte at the memory offset 0
+        let salt = Word::from(0x5a17);
+        let mut creator_code = bytecode! {
+            PUSH32(salt) // only popped by CREATE2
+            PUSH1(0x01) // length
+            PUSH1(0x00) // offset
+            PUSH1(0x00) // value
+        };
+        creator_code.write_op(opcode);
+        creator_code.write_op(OpcodeId::STOP);
+
+        let creator_address = Address::zero();
+        let preimage = if opcode == OpcodeId::CREATE2 {
+            get_create2_address_preimage(creator_address, salt, &[0x00])
+        } else {
+            get_create_address_preimage(creator_address, creator_nonce.into())
+        };
+        let contract_address = Address::from_slice(&Keccak256::digest(&preimage)[12..]);
+
+        // The tx calls the creator at the address 0x0
+        let geth_data = mock::new_single_tx_trace_accounts(vec![
+            Account {
+                address: creator_address,
+                nonce: creator_nonce.into(),
+                balance: Word::from(555u64),
+                code: Bytes::from(creator_code.to_vec()),
+                ..Default::default()
+            },
+            Account {
+                address: contract_address,
+                nonce: nonce.into(),
+                code: Bytes::from(code),
+                ..Default::default()
+            },
+        ])
+        .unwrap();
+        assert_eq!(
+            test_circuits_using_geth_data(geth_data, BytecodeTestConfig::default()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn error_contract_address_collision_simple() {
+        // Account with non-zero nonce
+        test_ok(OpcodeId::CREATE, 1, 1, vec![]);
+        // Account with code
+        test_ok(OpcodeId::CREATE2, 1, 0, vec![0xc0, 0xde]);
+    }
+
+    #[test]
+    fn error_contract_address_collision_nonce_encoding() {
+        // Nonces encoded in RLP as 0x80, a single byte, and a prefixed byte
+        // and bytes
+        for creator_nonce in [0, 0x7f, 0x80, 0x1234] {
+            test_ok(OpcodeId::CREATE, creator_nonce, 1, vec![]);
+        }
+    }
+}
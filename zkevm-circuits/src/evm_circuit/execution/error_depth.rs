@@ -0,0 +1,267 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::CallPrecheckFailureGadget, constraint_builder::ConstraintBuilder, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorDepthGadget<F> {
+    opcode: Cell<F>,
+    depth: Cell<F>,
+    precheck_failure: CallPrecheckFailureGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorDepthGadget<F> {
+    const NAME: &'static str = "ErrorDepth";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorDepth;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.require_in_set(
+            "ErrorDepth only happens in opcodes which create a sub-call",
+            opcode.expr(),
+            vec![
+                OpcodeId::CALL.expr(),
+                OpcodeId::CALLCODE.expr(),
+                OpcodeId::DELEGATECALL.expr(),
+                OpcodeId::STATICCALL.expr(),
+                OpcodeId::CREATE.expr(),
+                OpcodeId::CREATE2.expr(),
+            ],
+        );
+
+        // Check the call depth has reached the limit, where the root call has
+        // depth 1 and a sub-call can only be created under depth 1025
+        let depth = cb.call_context(None, CallContextFieldTag::Depth);
+        cb.require_equal("depth == 1025", depth.expr(), 1025.expr());
+
+        // Pop the operands, push 0 as the result of the sub-call which is not
+        // executed, and continue the current call
+        let precheck_failure = CallPrecheckFailureGadget::construct(cb, opcode.expr(), 0.expr());
+
+        Self {
+            opcode,
+            depth,
+            precheck_failure,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+        self.depth
+            .assign(region, offset, Some(F::from(call.depth as u64)))?;
+        self.precheck_failure
+            .assign(region, offset, block, step, 1)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::{
+        step::ExecutionState,
+        table::{CallContextFieldTag, RwTableTag},
+        test::run_test_circuit_incomplete_fixed_table,
+        witness::{Block, Bytecode, Call, CodeSource, ExecStep, Rw, RwMap, Transaction},
+    };
+    use eth_types::{
+        address,
+        evm_types::{GasCost, OpcodeId},
+        ToWord, Word,
+    };
+    use halo2_proofs::arithmetic::BaseExt;
+    use pairing::bn256::Fr as Fp;
+
+    // The call stack can only be full in a sub-call at depth 1025, which
+    // can't be reached from bytecode without the circuit of the *CALL*s that
+    // lead there, so the step is built by hand.
+    fn test_ok(opcode: OpcodeId) {
+        let randomness = Fp::rand();
+        let bytecode = Bytecode::new(vec![opcode.as_u8(), OpcodeId::STOP.as_u8()]);
+        let call_id = 1;
+        let tx_id = 1;
+        let callee_address = address!("0x00000000000000000000000000000000000000fe");
+        let stack_pointer = 1017;
+        let gas_left = 100000;
+        let (operand_count, _) = opcode.stack_pop_push();
+        let operand_count = operand_count as usize;
+        let is_creation = matches!(opcode, OpcodeId::CREATE | OpcodeId::CREATE2);
+
+        let mut rw_counter = 1;
+        let mut call_context = vec![Rw::CallContext {
+            rw_counter,
+            is_write: false,
+            call_id,
+            field_tag: CallContextFieldTag::Depth,
+            value: Word::from(1025),
+        }];
+        rw_counter += 1;
+        // The operands are zero but the callee address of a *CALL*, which
+        // transfers no value and accesses no memory
+        let mut stack = (0..operand_count)
+            .map(|idx| {
+                rw_counter += 1;
+                Rw::Stack {
+                    rw_counter: rw_counter - 1,
+                    is_write: false,
+                    call_id,
+                    stack_pointer: stack_pointer + idx,
+                    value: if idx == 1 && !is_creation {
+                        callee_address.to_word()
+                    } else {
+                        Word::zero()
+                    },
+                }
+            })
+            .collect::<Vec<_>>();
+        stack.push(Rw::Stack {
+            rw_counter,
+            is_write: true,
+            call_id,
+            stack_pointer: stack_pointer + operand_count - 1,
+            value: Word::zero(),
+        });
+        rw_counter += 1;
+        let mut rw_indices = std::iter::once((RwTableTag::CallContext, 0))
+            .chain((0..=operand_count).map(|idx| (RwTableTag::Stack, idx)))
+            .collect::<Vec<_>>();
+
+        // A *CALL* adds the callee into the access list
+        let mut tx_access_list_account = Vec::new();
+        if !is_creation {
+            for (field_tag, value) in [
+                (CallContextFieldTag::TxId, Word::from(tx_id)),
+                (CallContextFieldTag::RwCounterEndOfReversion, Word::zero()),
+                (CallContextFieldTag::IsPersistent, Word::one()),
+            ] {
+                rw_indices.push((RwTableTag::CallContext, call_context.len()));
+                call_context.push(Rw::CallContext {
+                    rw_counter,
+                    is_write: false,
+                    call_id,
+                    field_tag,
+                    value,
+                });
+                rw_counter += 1;
+            }
+            rw_indices.push((RwTableTag::TxAccessListAccount, 0));
+            tx_access_list_account.push(Rw::TxAccessListAccount {
+                rw_counter,
+                is_write: true,
+                tx_id,
+                account_address: callee_address,
+                value: true,
+                value_prev: false,
+            });
+            rw_counter += 1;
+        }
+
+        for field_tag in [
+            CallContextFieldTag::LastCalleeId,
+            CallContextFieldTag::LastCalleeReturnDataOffset,
+            CallContextFieldTag::LastCalleeReturnDataLength,
+        ] {
+            rw_indices.push((RwTableTag::CallContext, call_context.len()));
+            call_context.push(Rw::CallContext {
+                rw_counter,
+                is_write: true,
+                call_id,
+                field_tag,
+                value: Word::zero(),
+            });
+            rw_counter += 1;
+        }
+
+        let rws = RwMap(
+            [
+                (RwTableTag::CallContext, call_context),
+                (RwTableTag::Stack, stack),
+                (RwTableTag::TxAccessListAccount, tx_access_list_account),
+            ]
+            .into(),
+        );
+
+        // The gas passed to the sub-call is all returned, so the step only
+        // costs the cold access to the callee of a *CALL*, or the constant
+        // gas of a CREATE*
+        let gas_cost = if is_creation {
+            GasCost::CREATE.as_u64()
+        } else {
+            GasCost::COLD_ACCOUNT_ACCESS_COST.as_u64()
+        };
+        let steps = vec![
+            ExecStep {
+                rw_indices,
+                execution_state: ExecutionState::ErrorDepth,
+                rw_counter: 1,
+                program_counter: 0,
+                stack_pointer,
+                gas_left,
+                opcode: Some(opcode),
+                ..Default::default()
+            },
+            ExecStep {
+                execution_state: ExecutionState::STOP,
+                rw_counter,
+                program_counter: 1,
+                stack_pointer: stack_pointer + operand_count - 1,
+                gas_left: gas_left - gas_cost,
+                state_write_counter: !is_creation as usize,
+                opcode: Some(OpcodeId::STOP),
+                ..Default::default()
+            },
+        ];
+
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                id: tx_id,
+                calls: vec![Call {
+                    id: call_id,
+                    is_root: false,
+                    is_create: false,
+                    is_persistent: true,
+                    code_source: CodeSource::Account(bytecode.hash),
+                    depth: 1025,
+                    ..Default::default()
+                }],
+                steps,
+                ..Default::default()
+            }],
+            rws,
+            bytecodes: vec![bytecode],
+            ..Default::default()
+        };
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn error_depth_simple() {
+        test_ok(OpcodeId::CALL);
+        test_ok(OpcodeId::DELEGATECALL);
+        test_ok(OpcodeId::STATICCALL);
+        test_ok(OpcodeId::CREATE2);
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        table::{AccountFieldTag, CallContextFieldTag},
+        util::{
+            common_gadget::CallPrecheckFailureGadget,
+            constraint_builder::ConstraintBuilder,
+            from_bytes,
+            math_gadget::{ComparisonGadget, LtGadget},
+            select, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian, ToScalar};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorInsufficientBalanceGadget<F> {
+    opcode: Cell<F>,
+    value: Word<F>,
+    callee_address: Cell<F>,
+    balance: Word<F>,
+    // `balance[0..16] < value[0..16]`
+    balance_lt_value_lo: LtGadget<F, 16>,
+    // `balance[16..32] <= value[16..32]`
+    balance_cmp_value_hi: ComparisonGadget<F, 16>,
+    precheck_failure: CallPrecheckFailureGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorInsufficientBalanceGadget<F> {
+    const NAME: &'static str = "ErrorInsufficientBalance";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorInsufficientBalance;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.require_in_set(
+            "ErrorInsufficientBalance only happens in opcodes which transfer value",
+            opcode.expr(),
+            vec![
+                OpcodeId::CALL.expr(),
+                OpcodeId::CALLCODE.expr(),
+                OpcodeId::CREATE.expr(),
+                OpcodeId::CREATE2.expr(),
+            ],
+        );
+
+        // Read the balance of the current callee, which is the sender of the
+        // value
+        let callee_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+        let balance = cb.query_word();
+        cb.account_read(
+            callee_address.expr(),
+            AccountFieldTag::Balance,
+            balance.expr(),
+        );
+
+        // Check balance < value, where value is checked to be the value to
+        // transfer below
+        let value = cb.query_word();
+        let balance_lt_value_lo = LtGadget::construct(
+            cb,
+            from_bytes::expr(&balance.cells[..16]),
+            from_bytes::expr(&value.cells[..16]),
+        );
+        let balance_cmp_value_hi = ComparisonGadget::construct(
+            cb,
+            from_bytes::expr(&balance.cells[16..]),
+            from_bytes::expr(&value.cells[16..]),
+        );
+        let (lt_hi, eq_hi) = balance_cmp_value_hi.expr();
+        cb.require_equal(
+            "balance < value",
+            select::expr(lt_hi, 1.expr(), eq_hi * balance_lt_value_lo.expr()),
+            1.expr(),
+        );
+
+        // Pop the operands, push 0 as the result of the sub-call which is not
+        // executed, and continue the current call
+        let precheck_failure = CallPrecheckFailureGadget::construct(cb, opcode.expr(), 0.expr());
+
+        // The value to transfer is the 3rd item of stack in CALL and
+        // CALLCODE, and the 1st item in CREATE and CREATE2
+        cb.require_equal(
+            "value is the value to transfer",
+            value.expr(),
+            precheck_failure.value(),
+        );
+
+        Self {
+            opcode,
+            value,
+            callee_address,
+            balance,
+            balance_lt_value_lo,
+            balance_cmp_value_hi,
+            precheck_failure,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        let value_index = match opcode {
+            OpcodeId::CALL | OpcodeId::CALLCODE => 2,
+            _ => 0,
+        };
+        let callee_address = block.rws[step.rw_indices[0]].call_context_value();
+        let (balance, _) = block.rws[step.rw_indices[1]].account_value_pair();
+        let value = block.rws[step.rw_indices[2 + value_index]].stack_value();
+        let [value, balance] = [value, balance].map(|word| word.to_le_bytes());
+        self.value.assign(region, offset, Some(value))?;
+        self.callee_address
+            .assign(region, offset, callee_address.to_scalar())?;
+        self.balance.assign(region, offset, Some(balance))?;
+
+        self.balance_lt_value_lo.assign(
+            region,
+            offset,
+            from_bytes::value(&balance[..16]),
+            from_bytes::value(&value[..16]),
+        )?;
+        self.balance_cmp_value_hi.assign(
+            region,
+            offset,
+            from_bytes::value(&balance[16..]),
+            from_bytes::value(&value[16..]),
+        )?;
+        self.precheck_failure
+            .assign(region, offset, block, step, 2)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, evm_types::OpcodeId, Word};
+
+    // The account running the code has a balance of 555 wei
+    fn test_ok(opcode: OpcodeId, value: Word) {
+        let mut bytecode = bytecode! {
+            PUSH1(0x2a)
+            PUSH1(0x00)
+            MSTORE
+        };
+        if matches!(opcode, OpcodeId::CALL | OpcodeId::CALLCODE) {
+            // The sub-call expands the memory to its output
+            bytecode.append(&bytecode! {
+                PUSH1(0x20) // retLength
+                PUSH1(0x40) // retOffset
+                PUSH1(0x20) // argsLength
+                PUSH1(0x00) // argsOffset
+                PUSH32(value) // value
+                PUSH1(0xfe) // address
+                PUSH2(0xffff) // gas
+            });
+        } else {
+            // The init code of CREATE2 is hashed
+            bytecode.append(&bytecode! {
+                PUSH1(0x00) // salt, only popped by CREATE2
+                PUSH1(0x40) // length
+                PUSH1(0x00) // offset
+                PUSH32(value) // value
+            });
+        }
+        bytecode.append(&bytecode! {
+            .write_op(opcode)
+            STOP
+        });
+        assert_eq!(run_test_circuits(bytecode), Ok(()));
+    }
+
+    #[test]
+    fn error_insufficient_balance_simple() {
+        test_ok(OpcodeId::CALL, Word::from(556));
+        test_ok(OpcodeId::CALLCODE, Word::MAX);
+        test_ok(OpcodeId::CREATE, Word::from(556));
+        test_ok(OpcodeId::CREATE2, Word::one() << 128);
+    }
+}
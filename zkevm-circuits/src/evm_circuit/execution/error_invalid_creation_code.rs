@@ -0,0 +1,115 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            common_gadget::RestoreContextGadget, constraint_builder::ConstraintBuilder,
+            memory_gadget::MemoryAddressGadget,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorInvalidCreationCodeGadget<F> {
+    memory_address: MemoryAddressGadget<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorInvalidCreationCodeGadget<F> {
+    const NAME: &'static str = "ErrorInvalidCreationCode";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorInvalidCreationCode;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        cb.opcode_lookup(OpcodeId::RETURN.expr(), 1.expr());
+
+        // The returned data is stored as the code of the new contract only in
+        // a creation call
+        cb.require_equal(
+            "ErrorInvalidCreationCode only happens in creation call",
+            cb.curr.state.is_create.expr(),
+            1.expr(),
+        );
+
+        // Pop offset and size from stack
+        let memory_offset = cb.query_cell();
+        let length = cb.query_rlc();
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(length.expr());
+        let memory_address = MemoryAddressGadget::construct(cb, memory_offset, length);
+
+        // Check the first byte of the returned code is 0xEF (EIP-3541)
+        cb.require_equal(
+            "Returned code is not empty",
+            memory_address.has_length(),
+            1.expr(),
+        );
+        cb.memory_lookup(false.expr(), memory_address.offset(), 0xefu8.expr(), None);
+
+        // The call fails, so all of its state writes are reverted
+        cb.require_call_failure();
+
+        // The call returns to the caller without return data, and with all of
+        // its gas consumed
+        let restore_context =
+            RestoreContextGadget::construct(cb, 0.expr(), 0.expr(), 0.expr(), 0.expr());
+
+        Self {
+            memory_address,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let [memory_offset, length] =
+            [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].stack_value());
+        self.memory_address
+            .assign(region, offset, memory_offset, length, block.randomness)?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::{test_circuits_using_geth_data, BytecodeTestConfig};
+    use eth_types::bytecode;
+
+    fn test_ok(memory_offset: u64, length: u64) {
+        // The creation tx returns code starting with 0xef
+        let init_code = bytecode! {
+            PUSH1(0xef)
+            PUSH32(memory_offset)
+            MSTORE8
+            PUSH32(length)
+            PUSH32(memory_offset)
+            RETURN
+        };
+        let geth_data = mock::new_single_tx_trace_create(&init_code).unwrap();
+        assert_eq!(
+            test_circuits_using_geth_data(geth_data, BytecodeTestConfig::default()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn error_invalid_creation_code_simple() {
+        test_ok(0, 1);
+        test_ok(0x40, 0x20);
+    }
+}
@@ -0,0 +1,247 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_PROGRAM_COUNTER,
+        step::ExecutionState,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::ConstraintBuilder,
+            from_bytes,
+            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget},
+            sum, Cell, Word,
+        },
+        witness::{Block, Call, CodeSource, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorInvalidJumpGadget<F> {
+    opcode: Cell<F>,
+    destination: Word<F>,
+    condition: Cell<F>,
+    is_jumpi: IsEqualGadget<F>,
+    is_condition_zero: IsZeroGadget<F>,
+    code_length: Cell<F>,
+    // Whether the destination fits in 8 bytes
+    is_destination_u64: IsZeroGadget<F>,
+    is_destination_lt_code_length: LtGadget<F, N_BYTES_PROGRAM_COUNTER>,
+    value: Cell<F>,
+    is_code: Cell<F>,
+    is_jumpdest: IsEqualGadget<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorInvalidJumpGadget<F> {
+    const NAME: &'static str = "ErrorInvalidJump";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorInvalidJump;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.require_in_set(
+            "ErrorInvalidJump only happens in JUMP or JUMPI",
+            opcode.expr(),
+            vec![OpcodeId::JUMP.expr(), OpcodeId::JUMPI.expr()],
+        );
+
+        // Pop the destination from stack
+        let destination = cb.query_word();
+        cb.stack_pop(destination.expr());
+
+        // Pop the condition from stack for JUMPI, which must be non-zero,
+        // otherwise it doesn't jump at all
+        let is_jumpi = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::JUMPI.expr());
+        let condition = cb.query_cell();
+        let is_condition_zero = IsZeroGadget::construct(cb, condition.expr());
+        cb.condition(is_jumpi.expr(), |cb| {
+            cb.stack_pop(condition.expr());
+            cb.require_zero("Condition of JUMPI is non-zero", is_condition_zero.expr());
+        });
+
+        // Check whether the destination is within the bytecode
+        let code_length = cb.query_cell();
+        cb.bytecode_length(cb.curr.state.code_source.expr(), code_length.expr());
+        let is_destination_u64 =
+            IsZeroGadget::construct(cb, sum::expr(&destination.cells[N_BYTES_PROGRAM_COUNTER..]));
+        let is_destination_lt_code_length = LtGadget::construct(
+            cb,
+            from_bytes::expr(&destination.cells[..N_BYTES_PROGRAM_COUNTER]),
+            code_length.expr(),
+        );
+
+        // When the destination is within the bytecode, check the byte at the
+        // destination is not a JUMPDEST which is code
+        let value = cb.query_cell();
+        let is_code = cb.query_bool();
+        let is_jumpdest = IsEqualGadget::construct(cb, value.expr(), OpcodeId::JUMPDEST.expr());
+        cb.condition(
+            is_destination_u64.expr() * is_destination_lt_code_length.expr(),
+            |cb| {
+                cb.bytecode_lookup(
+                    cb.curr.state.code_source.expr(),
+                    from_bytes::expr(&destination.cells[..N_BYTES_PROGRAM_COUNTER]),
+                    value.expr(),
+                    is_code.expr(),
+                );
+                cb.require_zero(
+                    "Destination is not a JUMPDEST which is code",
+                    is_code.expr() * is_jumpdest.expr(),
+                );
+            },
+        );
+
+        // The call fails, so all of its state writes are reverted
+        cb.require_call_failure();
+
+        // The call returns to the caller without return data, and with all of
+        // its gas consumed
+        let restore_context =
+            RestoreContextGadget::construct(cb, 0.expr(), 0.expr(), 0.expr(), 0.expr());
+
+        Self {
+            opcode,
+            destination,
+            condition,
+            is_jumpi,
+            is_condition_zero,
+            code_length,
+            is_destination_u64,
+            is_destination_lt_code_length,
+            value,
+            is_code,
+            is_jumpdest,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+        self.is_jumpi.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::JUMPI.as_u64()),
+        )?;
+
+        let destination = block.rws[step.rw_indices[0]].stack_value().to_le_bytes();
+        self.destination.assign(region, offset, Some(destination))?;
+
+        let condition = if opcode == OpcodeId::JUMPI {
+            Word::random_linear_combine(
+                block.rws[step.rw_indices[1]].stack_value().to_le_bytes(),
+                block.randomness,
+            )
+        } else {
+            F::zero()
+        };
+        self.condition.assign(region, offset, Some(condition))?;
+        self.is_condition_zero.assign(region, offset, condition)?;
+
+        let CodeSource::Account(code_hash) = &call.code_source;
+        let bytecode = block
+            .bytecodes
+            .iter()
+            .find(|bytecode| bytecode.hash == *code_hash)
+            .expect("executed bytecode must be in the block");
+        let code_length = bytecode.bytes.len() as u64;
+        self.code_length
+            .assign(region, offset, Some(F::from(code_length)))?;
+
+        self.is_destination_u64.assign(
+            region,
+            offset,
+            sum::value(&destination[N_BYTES_PROGRAM_COUNTER..]),
+        )?;
+        self.is_destination_lt_code_length.assign(
+            region,
+            offset,
+            from_bytes::value(&destination[..N_BYTES_PROGRAM_COUNTER]),
+            F::from(code_length),
+        )?;
+
+        // Take the byte and is_code at the destination from the bytecode
+        // table, where the first row is the length
+        let destination = block.rws[step.rw_indices[0]].stack_value();
+        let (value, is_code) = if destination.bits() <= 64 && destination.low_u64() < code_length {
+            let row = bytecode
+                .table_assignments(block.randomness)
+                .nth(destination.as_usize() + 1)
+                .unwrap();
+            (row[3], row[4])
+        } else {
+            (F::zero(), F::zero())
+        };
+        self.value.assign(region, offset, Some(value))?;
+        self.is_code.assign(region, offset, Some(is_code))?;
+        self.is_jumpdest
+            .assign(region, offset, value, F::from(OpcodeId::JUMPDEST.as_u64()))?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, Word};
+
+    fn test_ok(destination: Word, condition: Option<Word>) {
+        // The byte at index 1 is a JUMPDEST as push data, and the last byte is
+        // a JUMPDEST which is never jumped to
+        let mut code = bytecode! {
+            PUSH1(0x5b)
+            POP
+        };
+        match condition {
+            Some(condition) => code.append(&bytecode! {
+                PUSH32(condition)
+                PUSH32(destination)
+                JUMPI
+            }),
+            None => code.append(&bytecode! {
+                PUSH32(destination)
+                JUMP
+            }),
+        }
+        code.append(&bytecode! {
+            JUMPDEST
+        });
+        assert_eq!(run_test_circuits(code), Ok(()));
+    }
+
+    #[test]
+    fn error_invalid_jump_not_jumpdest() {
+        test_ok(Word::zero(), None);
+        test_ok(Word::from(2), Some(Word::one()));
+    }
+
+    #[test]
+    fn error_invalid_jump_push_data() {
+        test_ok(Word::one(), None);
+        test_ok(Word::one(), Some(Word::MAX));
+    }
+
+    #[test]
+    fn error_invalid_jump_out_of_range() {
+        test_ok(Word::from(0x100), None);
+        test_ok(Word::from(u64::MAX), Some(Word::one()));
+        test_ok(Word::MAX, None);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        table::{FixedTableTag, Lookup},
+        util::{common_gadget::RestoreContextGadget, constraint_builder::ConstraintBuilder, Cell},
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::Field;
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorInvalidOpcodeGadget<F> {
+    opcode: Cell<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorInvalidOpcodeGadget<F> {
+    const NAME: &'static str = "ErrorInvalidOpcode";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorInvalidOpcode;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+
+        // Check the opcode is undefined
+        cb.add_lookup(
+            "Invalid opcode lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::InvalidOpcode.expr(),
+                values: [opcode.expr(), 0.expr(), 0.expr()],
+            },
+        );
+
+        // The call fails, so all of its state writes are reverted
+        cb.require_call_failure();
+
+        // The call returns to the caller without return data, and with all of
+        // its gas consumed
+        let restore_context =
+            RestoreContextGadget::construct(cb, 0.expr(), 0.expr(), 0.expr(), 0.expr());
+
+        Self {
+            opcode,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::bytecode;
+
+    fn test_ok(byte: u8) {
+        let mut code = bytecode! {
+            PUSH1(0x2a)
+        };
+        code.write(byte);
+        assert_eq!(run_test_circuits(code), Ok(()));
+    }
+
+    #[test]
+    fn error_invalid_opcode_simple() {
+        // The designated invalid opcode
+        test_ok(0xfe);
+        // Undefined opcodes
+        test_ok(0x0c);
+        test_ok(0x21);
+        test_ok(0xef);
+    }
+}
@@ -0,0 +1,346 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{MAX_CODE_SIZE, N_BYTES_U64},
+        step::ExecutionState,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::ConstraintBuilder,
+            from_bytes,
+            math_gadget::{IsZeroGadget, LtGadget},
+            sum, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorMaxCodeSizeExceededGadget<F> {
+    memory_offset: Cell<F>,
+    length: Word<F>,
+    // Whether length fits in 8 bytes
+    is_u64: IsZeroGadget<F>,
+    is_length_gt_max: LtGadget<F, N_BYTES_U64>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorMaxCodeSizeExceededGadget<F> {
+    const NAME: &'static str = "ErrorMaxCodeSizeExceeded";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorMaxCodeSizeExceeded;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        cb.opcode_lookup(OpcodeId::RETURN.expr(), 1.expr());
+
+        // The returned data is stored as the code of the new contract only in
+        // a creation call
+        cb.require_equal(
+            "ErrorMaxCodeSizeExceeded only happens in creation call",
+            cb.curr.state.is_create.expr(),
+            1.expr(),
+        );
+
+        // Pop offset and size from stack
+        let memory_offset = cb.query_cell();
+        let length = cb.query_word();
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(length.expr());
+
+        // Check length > MAX_CODE_SIZE (EIP-170), which is always the case
+        // when it doesn't fit in 8 bytes
+        let is_u64 = IsZeroGadget::construct(cb, sum::expr(&length.cells[N_BYTES_U64..]));
+        let is_length_gt_max = LtGadget::construct(
+            cb,
+            MAX_CODE_SIZE.expr(),
+            from_bytes::expr(&length.cells[..N_BYTES_U64]),
+        );
+        cb.require_zero(
+            "length > MAX_CODE_SIZE",
+            is_u64.expr() * (1.expr() - is_length_gt_max.expr()),
+        );
+
+        // The call fails, so all of its state writes are reverted
+        cb.require_call_failure();
+
+        // The call returns to the caller without return data, and with all of
+        // its gas consumed
+        let restore_context =
+            RestoreContextGadget::construct(cb, 0.expr(), 0.expr(), 0.expr(), 0.expr());
+
+        Self {
+            memory_offset,
+            length,
+            is_u64,
+            is_length_gt_max,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let [memory_offset, length] =
+            [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].stack_value());
+        self.memory_offset.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                memory_offset.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+        let length = length.to_le_bytes();
+        self.length.assign(region, offset, Some(length))?;
+
+        self.is_u64
+            .assign(region, offset, sum::value(&length[N_BYTES_U64..]))?;
+        self.is_length_gt_max.assign(
+            region,
+            offset,
+            F::from(MAX_CODE_SIZE),
+            from_bytes::value(&length[..N_BYTES_U64]),
+        )?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        evm_circuit::{
+            param::MAX_CODE_SIZE,
+            step::ExecutionState,
+            table::{CallContextFieldTag, RwTableTag},
+            test::run_test_circuit_incomplete_fixed_table,
+            witness::{Block, Bytecode, Call, CodeSource, ExecStep, Rw, RwMap, Transaction},
+        },
+        test_util::{test_circuits_using_geth_data, BytecodeTestConfig},
+    };
+    use eth_types::{bytecode, evm_types::OpcodeId, Word};
+    use halo2_proofs::arithmetic::BaseExt;
+    use pairing::bn256::Fr as Fp;
+
+    // The circuit doesn't prove a successful CREATE yet, and geth reports a
+    // length which doesn't fit in 8 bytes as a memory expansion error, so the
+    // failure of a CREATE is tested with a hand-built witness
+    fn test_ok(length: Word) {
+        let randomness = Fp::rand();
+        let caller_bytecode = Bytecode::new(vec![OpcodeId::STOP.as_u8()]);
+        let callee_bytecode = Bytecode::new(vec![OpcodeId::RETURN.as_u8()]);
+        let (caller_id, callee_id) = (1, 2);
+        let call_context_rw_counter = 3;
+
+        let stack = vec![(1022, Word::zero()), (1023, length)]
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (stack_pointer, value))| Rw::Stack {
+                rw_counter: idx + 1,
+                is_write: false,
+                call_id: callee_id,
+                stack_pointer,
+                value,
+            })
+            .collect();
+        // The creation fails, and then restores the call context of the
+        // caller, which resumes with its own gas left
+        let call_context = vec![
+            (
+                false,
+                callee_id,
+                CallContextFieldTag::IsSuccess,
+                Word::zero(),
+            ),
+            (
+                false,
+                callee_id,
+                CallContextFieldTag::RwCounterEndOfReversion,
+                Word::from(4),
+            ),
+            (
+                false,
+                callee_id,
+                CallContextFieldTag::CallerId,
+                Word::from(caller_id),
+            ),
+            (false, caller_id, CallContextFieldTag::IsRoot, Word::one()),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::IsCreate,
+                Word::zero(),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::CodeSource,
+                caller_bytecode.hash,
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::ProgramCounter,
+                Word::zero(),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::StackPointer,
+                Word::from(1023),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::GasLeft,
+                Word::from(100),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::MemorySize,
+                Word::zero(),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::StateWriteCounter,
+                Word::zero(),
+            ),
+            (
+                true,
+                caller_id,
+                CallContextFieldTag::LastCalleeId,
+                Word::from(callee_id),
+            ),
+            (
+                true,
+                caller_id,
+                CallContextFieldTag::LastCalleeReturnDataOffset,
+                Word::zero(),
+            ),
+            (
+                true,
+                caller_id,
+                CallContextFieldTag::LastCalleeReturnDataLength,
+                Word::zero(),
+            ),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(
+            |(idx, (is_write, call_id, field_tag, value))| Rw::CallContext {
+                rw_counter: call_context_rw_counter + idx,
+                is_write,
+                call_id,
+                field_tag,
+                value,
+            },
+        )
+        .collect::<Vec<_>>();
+        let rw_indices = [(RwTableTag::Stack, 0), (RwTableTag::Stack, 1)]
+            .iter()
+            .copied()
+            .chain((0..call_context.len()).map(|idx| (RwTableTag::CallContext, idx)))
+            .collect();
+
+        let steps = vec![
+            ExecStep {
+                call_index: 1,
+                rw_indices,
+                execution_state: ExecutionState::ErrorMaxCodeSizeExceeded,
+                rw_counter: 1,
+                program_counter: 0,
+                stack_pointer: 1022,
+                gas_left: 100000,
+                opcode: Some(OpcodeId::RETURN),
+                ..Default::default()
+            },
+            ExecStep {
+                call_index: 0,
+                execution_state: ExecutionState::STOP,
+                rw_counter: call_context_rw_counter + call_context.len(),
+                program_counter: 0,
+                stack_pointer: 1023,
+                gas_left: 100,
+                opcode: Some(OpcodeId::STOP),
+                ..Default::default()
+            },
+        ];
+
+        let rws = RwMap(
+            [
+                (RwTableTag::Stack, stack),
+                (RwTableTag::CallContext, call_context),
+            ]
+            .into(),
+        );
+
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                id: 1,
+                calls: vec![
+                    Call {
+                        id: caller_id,
+                        is_root: true,
+                        is_create: false,
+                        code_source: CodeSource::Account(caller_bytecode.hash),
+                        ..Default::default()
+                    },
+                    Call {
+                        id: callee_id,
+                        is_root: false,
+                        is_create: true,
+                        code_source: CodeSource::Account(callee_bytecode.hash),
+                        caller_id,
+                        depth: 2,
+                        ..Default::default()
+                    },
+                ],
+                steps,
+                ..Default::default()
+            }],
+            rws,
+            bytecodes: vec![caller_bytecode, callee_bytecode],
+            ..Default::default()
+        };
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn error_max_code_size_exceeded_simple() {
+        test_ok(Word::from(MAX_CODE_SIZE + 1));
+        test_ok(Word::from(u64::MAX));
+    }
+
+    #[test]
+    fn error_max_code_size_exceeded_creation_tx() {
+        let init_code = bytecode! {
+            PUSH32(MAX_CODE_SIZE + 1)
+            PUSH1(0x00)
+            RETURN
+        };
+        let geth_data = mock::new_single_tx_trace_create(&init_code).unwrap();
+        assert_eq!(
+            test_circuits_using_geth_data(geth_data, BytecodeTestConfig::default()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn error_max_code_size_exceeded_overflow() {
+        test_ok(Word::MAX);
+    }
+}
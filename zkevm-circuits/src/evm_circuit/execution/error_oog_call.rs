@@ -0,0 +1,457 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{
+            EMPTY_CODE_HASH_LE, N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE,
+        },
+        step::ExecutionState,
+        table::{AccountFieldTag, CallContextFieldTag},
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::ConstraintBuilder,
+            from_bytes,
+            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget},
+            memory_gadget::{MemoryExpandedAddressGadget, MemoryExpansionGadget},
+            Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian, ToScalar, U256,
+};
+use halo2_proofs::{circuit::Region, plonk::Error};
+use std::convert::TryInto;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGCallGadget<F> {
+    opcode: Cell<F>,
+    is_call: IsEqualGadget<F>,
+    is_callcode: IsEqualGadget<F>,
+    gas: Cell<F>,
+    callee_address: Word<F>,
+    // Only used in CALL and CALLCODE
+    value: Word<F>,
+    value_is_zero: IsZeroGadget<F>,
+    cd_address: MemoryExpandedAddressGadget<F>,
+    rd_address: MemoryExpandedAddressGadget<F>,
+    tx_id: Cell<F>,
+    is_warm: Cell<F>,
+    // Only used in CALL with non-zero value
+    callee_nonce: Cell<F>,
+    callee_balance: Cell<F>,
+    callee_code_hash: Cell<F>,
+    callee_nonce_is_zero: IsZeroGadget<F>,
+    callee_balance_is_zero: IsZeroGadget<F>,
+    callee_code_hash_is_zero: IsZeroGadget<F>,
+    callee_code_hash_is_empty: IsEqualGadget<F>,
+    // Allow memory size to expand to 5 bytes as ErrorOOGPureMemoryGadget does
+    memory_expansion: MemoryExpansionGadget<F, 2, { N_BYTES_MEMORY_WORD_SIZE + 1 }>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasCall";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasCall;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.require_in_set(
+            "ErrorOutOfGasCall only happens in CALL, CALLCODE, DELEGATECALL and STATICCALL",
+            opcode.expr(),
+            vec![
+                OpcodeId::CALL.expr(),
+                OpcodeId::CALLCODE.expr(),
+                OpcodeId::DELEGATECALL.expr(),
+                OpcodeId::STATICCALL.expr(),
+            ],
+        );
+        let is_call = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::CALL.expr());
+        let is_callcode = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::CALLCODE.expr());
+        let has_value_operand = is_call.expr() + is_callcode.expr();
+
+        // Pop gas, callee address, value (only in CALL and CALLCODE), input
+        // offset, input length, output offset and output length from stack
+        let gas = cb.query_cell();
+        let callee_address = cb.query_word();
+        let value = cb.query_word();
+        let cd_address = MemoryExpandedAddressGadget::construct(cb);
+        let rd_address = MemoryExpandedAddressGadget::construct(cb);
+        cb.stack_pop(gas.expr());
+        cb.stack_pop(callee_address.expr());
+        cb.condition(has_value_operand.clone(), |cb| {
+            cb.stack_lookup(false.expr(), 2.expr(), value.expr());
+        });
+        for (idx, value) in [
+            cd_address.offset_rlc(),
+            cd_address.length_rlc(),
+            rd_address.offset_rlc(),
+            rd_address.length_rlc(),
+        ]
+        .iter()
+        .enumerate()
+        {
+            cb.stack_lookup(
+                false.expr(),
+                has_value_operand.clone() + (idx + 2).expr(),
+                value.clone(),
+            );
+        }
+        let value_is_zero = IsZeroGadget::construct(cb, value.expr());
+        let has_value = has_value_operand * (1.expr() - value_is_zero.expr());
+
+        // Check whether the callee is in the access list
+        let callee_address_expr =
+            from_bytes::expr(&callee_address.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let is_warm = cb.query_bool();
+        cb.account_access_list_read(tx_id.expr(), callee_address_expr.clone(), is_warm.expr());
+
+        // Read nonce, balance and code hash of the callee to check whether
+        // it's empty when CALL transfers non-zero value, where the code hash
+        // of a non-existing account is 0
+        let callee_nonce = cb.query_cell();
+        let callee_balance = cb.query_cell();
+        let callee_code_hash = cb.query_cell();
+        cb.condition(is_call.expr() * (1.expr() - value_is_zero.expr()), |cb| {
+            for (field_tag, value) in [
+                (AccountFieldTag::Nonce, callee_nonce.expr()),
+                (AccountFieldTag::Balance, callee_balance.expr()),
+                (AccountFieldTag::CodeHash, callee_code_hash.expr()),
+            ] {
+                cb.account_read(callee_address_expr.clone(), field_tag, value);
+            }
+        });
+        let callee_nonce_is_zero = IsZeroGadget::construct(cb, callee_nonce.expr());
+        let callee_balance_is_zero = IsZeroGadget::construct(cb, callee_balance.expr());
+        let callee_code_hash_is_zero = IsZeroGadget::construct(cb, callee_code_hash.expr());
+        let empty_code_hash = Word::random_linear_combine_expr(
+            EMPTY_CODE_HASH_LE.map(|byte| byte.expr()),
+            cb.power_of_randomness(),
+        );
+        let callee_code_hash_is_empty =
+            IsEqualGadget::construct(cb, callee_code_hash.expr(), empty_code_hash);
+        let is_empty = callee_nonce_is_zero.expr()
+            * callee_balance_is_zero.expr()
+            * (callee_code_hash_is_zero.expr() + callee_code_hash_is_empty.expr());
+
+        // Calculate the gas cost, which includes the cold account access cost
+        // (EIP-2929), the memory expansion cost of both input and output, the
+        // cost of transferring value and the cost of creating a new account
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [cd_address.address(), rd_address.address()],
+        );
+        let gas_cost = GasCost::WARM_STORAGE_READ_COST.expr()
+            + (1.expr() - is_warm.expr())
+                * (GasCost::COLD_ACCOUNT_ACCESS_COST.expr()
+                    - GasCost::WARM_STORAGE_READ_COST.expr())
+            + memory_expansion.gas_cost()
+            + has_value * GasCost::CALL_WITH_VALUE.expr()
+            + is_call.expr()
+                * (1.expr() - value_is_zero.expr())
+                * is_empty
+                * GasCost::NEW_ACCOUNT.expr();
+
+        // Check the amount of gas available is less than the gas cost, which
+        // is always the case when any memory address is out of range
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.condition(
+            cd_address.address_in_range() * rd_address.address_in_range(),
+            |cb| {
+                cb.require_equal("gas_left < gas_cost", insufficient_gas.expr(), 1.expr());
+            },
+        );
+
+        // The call fails, so all of its state writes are reverted
+        cb.require_call_failure();
+
+        // The call returns to the caller without return data, and with all of
+        // its gas consumed
+        let restore_context =
+            RestoreContextGadget::construct(cb, 0.expr(), 0.expr(), 0.expr(), 0.expr());
+
+        Self {
+            opcode,
+            is_call,
+            is_callcode,
+            gas,
+            callee_address,
+            value,
+            value_is_zero,
+            cd_address,
+            rd_address,
+            tx_id,
+            is_warm,
+            callee_nonce,
+            callee_balance,
+            callee_code_hash,
+            callee_nonce_is_zero,
+            callee_balance_is_zero,
+            callee_code_hash_is_zero,
+            callee_code_hash_is_empty,
+            memory_expansion,
+            insufficient_gas,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        let is_call = opcode == OpcodeId::CALL;
+        let has_value_operand = is_call || opcode == OpcodeId::CALLCODE;
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+        self.is_call.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::CALL.as_u64()),
+        )?;
+        self.is_callcode.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::CALLCODE.as_u64()),
+        )?;
+
+        let stack_len = if has_value_operand { 7 } else { 6 };
+        let mut stack_values = step.rw_indices[..stack_len]
+            .iter()
+            .map(|idx| block.rws[*idx].stack_value())
+            .collect::<Vec<_>>();
+        let value = if has_value_operand {
+            stack_values.remove(2)
+        } else {
+            U256::zero()
+        };
+        let [gas, callee_address, cd_offset, cd_length, rd_offset, rd_length]: [U256; 6] =
+            stack_values.try_into().unwrap();
+        self.gas.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                gas.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+        self.callee_address
+            .assign(region, offset, Some(callee_address.to_le_bytes()))?;
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.value_is_zero.assign(
+            region,
+            offset,
+            Word::random_linear_combine(value.to_le_bytes(), block.randomness),
+        )?;
+        let cd_address = self
+            .cd_address
+            .assign(region, offset, cd_offset, cd_length)?;
+        let rd_address = self
+            .rd_address
+            .assign(region, offset, rd_offset, rd_length)?;
+
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx.id as u64)))?;
+        let (_, is_warm) = block.rws[step.rw_indices[stack_len + 1]].tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Some(F::from(is_warm as u64)))?;
+
+        let is_call_with_value = is_call && !value.is_zero();
+        let [nonce, balance, code_hash] = if is_call_with_value {
+            [2, 3, 4].map(|idx| {
+                block.rws[step.rw_indices[stack_len + idx]]
+                    .account_value_pair()
+                    .0
+            })
+        } else {
+            [U256::zero(); 3]
+        };
+        let nonce = nonce.to_scalar().unwrap();
+        let balance = Word::random_linear_combine(balance.to_le_bytes(), block.randomness);
+        let code_hash = Word::random_linear_combine(code_hash.to_le_bytes(), block.randomness);
+        self.callee_nonce.assign(region, offset, Some(nonce))?;
+        self.callee_balance.assign(region, offset, Some(balance))?;
+        self.callee_code_hash
+            .assign(region, offset, Some(code_hash))?;
+        let nonce_is_zero = self.callee_nonce_is_zero.assign(region, offset, nonce)?;
+        let balance_is_zero = self
+            .callee_balance_is_zero
+            .assign(region, offset, balance)?;
+        let code_hash_is_zero = self
+            .callee_code_hash_is_zero
+            .assign(region, offset, code_hash)?;
+        let code_hash_is_empty = self.callee_code_hash_is_empty.assign(
+            region,
+            offset,
+            code_hash,
+            Word::random_linear_combine(EMPTY_CODE_HASH_LE, block.randomness),
+        )?;
+        let is_empty = nonce_is_zero == F::one()
+            && balance_is_zero == F::one()
+            && (code_hash_is_zero == F::one() || code_hash_is_empty == F::one());
+
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [cd_address, rd_address],
+        )?;
+        let mut gas_cost = GasCost::WARM_STORAGE_READ_COST.as_u64() + memory_expansion_gas_cost;
+        if !is_warm {
+            gas_cost += GasCost::COLD_ACCOUNT_ACCESS_COST.as_u64()
+                - GasCost::WARM_STORAGE_READ_COST.as_u64();
+        }
+        if has_value_operand && !value.is_zero() {
+            gas_cost += GasCost::CALL_WITH_VALUE.as_u64();
+        }
+        if is_call_with_value && is_empty {
+            gas_cost += GasCost::NEW_ACCOUNT.as_u64();
+        }
+        self.insufficient_gas
+            .assign(region, offset, F::from(step.gas_left), F::from(gas_cost))?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::{test_circuits_using_bytecode, BytecodeTestConfig};
+    use eth_types::{
+        address, bytecode,
+        evm_types::{GasCost, OpcodeId},
+        Address, Bytecode, ToWord, Word,
+    };
+
+    #[derive(Default)]
+    struct Stack {
+        value: Word,
+        cd_offset: Word,
+        cd_length: Word,
+        rd_offset: Word,
+        rd_length: Word,
+    }
+
+    /// The empty account is warmed up by EXTCODESIZE before the call, and the
+    /// non-empty account is the callee of the tx, which is always warm.
+    fn test_ok(opcode: OpcodeId, stack: Stack, gas_left: u64, is_warm: bool, is_empty: bool) {
+        assert!(is_warm || is_empty);
+        let callee_address = if is_empty {
+            address!("0x0000000000000000000000000000000000cafe01")
+        } else {
+            Address::zero()
+        };
+        let mut code = Bytecode::default();
+        let mut gas_cost = 0;
+        if is_warm && is_empty {
+            code.append(&bytecode! {
+                PUSH20(callee_address.to_word())
+                EXTCODESIZE
+                POP
+            });
+            gas_cost += GasCost::FASTEST.as_u64()
+                + GasCost::COLD_ACCOUNT_ACCESS_COST.as_u64()
+                + GasCost::QUICK.as_u64();
+        }
+
+        let mut operands = vec![
+            stack.rd_length,
+            stack.rd_offset,
+            stack.cd_length,
+            stack.cd_offset,
+        ];
+        if opcode == OpcodeId::CALL || opcode == OpcodeId::CALLCODE {
+            operands.push(stack.value);
+        }
+        operands.push(callee_address.to_word());
+        operands.push(Word::from(100000));
+        for operand in operands.iter() {
+            code.push(32, *operand);
+        }
+        code.write_op(opcode);
+        gas_cost += operands.len() as u64 * GasCost::FASTEST.as_u64();
+
+        let test_config = BytecodeTestConfig {
+            gas_limit: GasCost::TX.as_u64() + gas_cost + gas_left,
+            ..Default::default()
+        };
+        assert_eq!(test_circuits_using_bytecode(code, test_config), Ok(()));
+    }
+
+    #[test]
+    fn error_oog_call_simple() {
+        // Cold account
+        test_ok(OpcodeId::CALL, Stack::default(), 2599, false, true);
+        test_ok(OpcodeId::STATICCALL, Stack::default(), 2000, false, true);
+        // Warm account with memory expansion of 2 words
+        test_ok(
+            OpcodeId::DELEGATECALL,
+            Stack {
+                cd_length: Word::from(0x20),
+                rd_offset: Word::from(0x20),
+                rd_length: Word::from(0x20),
+                ..Default::default()
+            },
+            105,
+            true,
+            false,
+        );
+    }
+
+    #[test]
+    fn error_oog_call_with_value() {
+        let stack = || Stack {
+            value: Word::one(),
+            ..Default::default()
+        };
+        // Transfer value to an empty account
+        test_ok(OpcodeId::CALL, stack(), 34099, true, true);
+        // Transfer value to a non-empty account
+        test_ok(OpcodeId::CALL, stack(), 9099, true, false);
+        // CALLCODE transfers value to the caller itself
+        test_ok(OpcodeId::CALLCODE, stack(), 9099, true, true);
+    }
+
+    #[test]
+    fn error_oog_call_address_out_of_range() {
+        test_ok(
+            OpcodeId::CALL,
+            Stack {
+                cd_offset: Word::MAX,
+                cd_length: Word::one(),
+                ..Default::default()
+            },
+            1_000_000,
+            true,
+            false,
+        );
+        test_ok(
+            OpcodeId::STATICCALL,
+            Stack {
+                rd_length: Word::from(u64::MAX),
+                ..Default::default()
+            },
+            1_000_000,
+            true,
+            false,
+        );
+    }
+}
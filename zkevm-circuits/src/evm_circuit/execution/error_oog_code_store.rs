@@ -0,0 +1,349 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::ConstraintBuilder,
+            math_gadget::LtGadget,
+            memory_gadget::{address_low, MemoryExpandedAddressGadget, MemoryExpansionGadget},
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian,
+};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGCodeStoreGadget<F> {
+    memory_address: MemoryExpandedAddressGadget<F>,
+    // Allow memory size to expand to 5 bytes as ErrorOOGPureMemoryGadget does
+    memory_expansion: MemoryExpansionGadget<F, 1, { N_BYTES_MEMORY_WORD_SIZE + 1 }>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGCodeStoreGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasCodeStore";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasCodeStore;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        cb.opcode_lookup(OpcodeId::RETURN.expr(), 1.expr());
+
+        // The returned data is stored as the code of the new contract only in
+        // a creation call
+        cb.require_equal(
+            "ErrorOutOfGasCodeStore only happens in creation call",
+            cb.curr.state.is_create.expr(),
+            1.expr(),
+        );
+
+        // Pop offset and size from stack
+        let memory_address = MemoryExpandedAddressGadget::construct(cb);
+        cb.stack_pop(memory_address.offset_rlc());
+        cb.stack_pop(memory_address.length_rlc());
+
+        // Calculate the gas cost, which includes the memory expansion cost and
+        // the cost of storing every byte of the code
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [memory_address.address()],
+        );
+        let gas_cost = memory_expansion.gas_cost()
+            + GasCost::CODE_DEPOSIT_BYTE_COST.expr() * memory_address.length();
+
+        // Check the amount of gas available is less than the gas cost, which
+        // is always the case when the memory address is out of range
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.condition(memory_address.address_in_range(), |cb| {
+            cb.require_equal("gas_left < gas_cost", insufficient_gas.expr(), 1.expr());
+        });
+
+        // The call fails, so all of its state writes are reverted
+        cb.require_call_failure();
+
+        // The call returns to the caller without return data, and with all of
+        // its gas consumed
+        let restore_context =
+            RestoreContextGadget::construct(cb, 0.expr(), 0.expr(), 0.expr(), 0.expr());
+
+        Self {
+            memory_address,
+            memory_expansion,
+            insufficient_gas,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let [memory_offset, size] =
+            [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].stack_value());
+        let memory_address = self
+            .memory_address
+            .assign(region, offset, memory_offset, size)?;
+
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+        let gas_cost = memory_expansion_gas_cost
+            + GasCost::CODE_DEPOSIT_BYTE_COST.as_u64() * address_low::value(size.to_le_bytes());
+        self.insufficient_gas
+            .assign(region, offset, F::from(step.gas_left), F::from(gas_cost))?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        evm_circuit::{
+            step::ExecutionState,
+            table::{CallContextFieldTag, RwTableTag},
+            test::run_test_circuit_incomplete_fixed_table,
+            witness::{Block, Bytecode, Call, CodeSource, ExecStep, Rw, RwMap, Transaction},
+        },
+        test_util::{test_circuits_using_geth_data, BytecodeTestConfig},
+    };
+    use eth_types::{bytecode, evm_types::OpcodeId, Word};
+    use halo2_proofs::arithmetic::BaseExt;
+    use pairing::bn256::Fr as Fp;
+
+    // The circuit doesn't prove a successful CREATE yet, and geth reports an
+    // out of range memory address in RETURN as a memory expansion error, so
+    // these cases are tested with a hand-built witness
+    fn test_ok(memory_offset: Word, size: Word, gas_left: u64) {
+        let randomness = Fp::rand();
+        let caller_bytecode = Bytecode::new(vec![OpcodeId::STOP.as_u8()]);
+        let callee_bytecode = Bytecode::new(vec![OpcodeId::RETURN.as_u8()]);
+        let (caller_id, callee_id) = (1, 2);
+        let call_context_rw_counter = 3;
+
+        let stack = vec![(1022, memory_offset), (1023, size)]
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (stack_pointer, value))| Rw::Stack {
+                rw_counter: idx + 1,
+                is_write: false,
+                call_id: callee_id,
+                stack_pointer,
+                value,
+            })
+            .collect();
+        // The creation fails, and then restores the call context of the
+        // caller, which resumes with its own gas left
+        let call_context = vec![
+            (
+                false,
+                callee_id,
+                CallContextFieldTag::IsSuccess,
+                Word::zero(),
+            ),
+            (
+                false,
+                callee_id,
+                CallContextFieldTag::RwCounterEndOfReversion,
+                Word::from(4),
+            ),
+            (
+                false,
+                callee_id,
+                CallContextFieldTag::CallerId,
+                Word::from(caller_id),
+            ),
+            (false, caller_id, CallContextFieldTag::IsRoot, Word::one()),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::IsCreate,
+                Word::zero(),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::CodeSource,
+                caller_bytecode.hash,
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::ProgramCounter,
+                Word::zero(),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::StackPointer,
+                Word::from(1023),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::GasLeft,
+                Word::from(100),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::MemorySize,
+                Word::zero(),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::StateWriteCounter,
+                Word::zero(),
+            ),
+            (
+                true,
+                caller_id,
+                CallContextFieldTag::LastCalleeId,
+                Word::from(callee_id),
+            ),
+            (
+                true,
+                caller_id,
+                CallContextFieldTag::LastCalleeReturnDataOffset,
+                Word::zero(),
+            ),
+            (
+                true,
+                caller_id,
+                CallContextFieldTag::LastCalleeReturnDataLength,
+                Word::zero(),
+            ),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(
+            |(idx, (is_write, call_id, field_tag, value))| Rw::CallContext {
+                rw_counter: call_context_rw_counter + idx,
+                is_write,
+                call_id,
+                field_tag,
+                value,
+            },
+        )
+        .collect::<Vec<_>>();
+        let rw_indices = [(RwTableTag::Stack, 0), (RwTableTag::Stack, 1)]
+            .iter()
+            .copied()
+            .chain((0..call_context.len()).map(|idx| (RwTableTag::CallContext, idx)))
+            .collect();
+
+        let steps = vec![
+            ExecStep {
+                call_index: 1,
+                rw_indices,
+                execution_state: ExecutionState::ErrorOutOfGasCodeStore,
+                rw_counter: 1,
+                program_counter: 0,
+                stack_pointer: 1022,
+                gas_left,
+                opcode: Some(OpcodeId::RETURN),
+                ..Default::default()
+            },
+            ExecStep {
+                call_index: 0,
+                execution_state: ExecutionState::STOP,
+                rw_counter: call_context_rw_counter + call_context.len(),
+                program_counter: 0,
+                stack_pointer: 1023,
+                gas_left: 100,
+                opcode: Some(OpcodeId::STOP),
+                ..Default::default()
+            },
+        ];
+
+        let rws = RwMap(
+            [
+                (RwTableTag::Stack, stack),
+                (RwTableTag::CallContext, call_context),
+            ]
+            .into(),
+        );
+
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                id: 1,
+                calls: vec![
+                    Call {
+                        id: caller_id,
+                        is_root: true,
+                        is_create: false,
+                        code_source: CodeSource::Account(caller_bytecode.hash),
+                        ..Default::default()
+                    },
+                    Call {
+                        id: callee_id,
+                        is_root: false,
+                        is_create: true,
+                        code_source: CodeSource::Account(callee_bytecode.hash),
+                        caller_id,
+                        depth: 2,
+                        ..Default::default()
+                    },
+                ],
+                steps,
+                ..Default::default()
+            }],
+            rws,
+            bytecodes: vec![caller_bytecode, callee_bytecode],
+            ..Default::default()
+        };
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn error_oog_code_store_simple() {
+        // 3 + 200 * 32
+        test_ok(Word::zero(), Word::from(0x20), 6402);
+        // 3 * 2 + 200 * 1
+        test_ok(Word::from(0x3f), Word::one(), 205);
+    }
+
+    #[test]
+    fn error_oog_code_store_address_out_of_range() {
+        test_ok(Word::MAX, Word::one(), 1_000_000);
+        test_ok(Word::zero(), Word::MAX, 1_000_000);
+    }
+
+    #[test]
+    fn error_oog_code_store_creation_tx() {
+        // The tx has 1_000_000 gas, which doesn't pay for storing 0x2000 bytes
+        for (memory_offset, size) in [(0x00, 0x2000), (0x20, 0x5000)] {
+            let init_code = bytecode! {
+                PUSH32(size)
+                PUSH32(memory_offset)
+                RETURN
+            };
+            let geth_data = mock::new_single_tx_trace_create(&init_code).unwrap();
+            assert_eq!(
+                test_circuits_using_geth_data(geth_data, BytecodeTestConfig::default()),
+                Ok(())
+            );
+        }
+    }
+}
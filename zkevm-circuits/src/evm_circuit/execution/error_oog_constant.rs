@@ -0,0 +1,124 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_GAS,
+        step::ExecutionState,
+        util::{
+            common_gadget::RestoreContextGadget, constraint_builder::ConstraintBuilder,
+            math_gadget::LtGadget, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::Field;
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGConstantGadget<F> {
+    opcode: Cell<F>,
+    gas_cost: Cell<F>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGConstantGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasConstant";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasConstant;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+
+        // Lookup the constant gas cost of the opcode
+        let gas_cost = cb.query_cell();
+        cb.constant_gas_lookup(opcode.expr(), gas_cost.expr());
+
+        // Check the amount of gas available is less than the constant gas cost
+        let insufficient_gas =
+            LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost.expr());
+        cb.require_equal(
+            "gas_left < constant gas cost",
+            insufficient_gas.expr(),
+            1.expr(),
+        );
+
+        // The call fails, so all of its state writes are reverted
+        cb.require_call_failure();
+
+        // The call returns to the caller without return data, and with all of
+        // its gas consumed
+        let restore_context =
+            RestoreContextGadget::construct(cb, 0.expr(), 0.expr(), 0.expr(), 0.expr());
+
+        Self {
+            opcode,
+            gas_cost,
+            insufficient_gas,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        let gas_cost = opcode.constant_gas_cost().as_u64();
+        self.gas_cost
+            .assign(region, offset, Some(F::from(gas_cost)))?;
+        self.insufficient_gas
+            .assign(region, offset, F::from(step.gas_left), F::from(gas_cost))?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::{test_circuits_using_bytecode, BytecodeTestConfig};
+    use eth_types::{
+        evm_types::{GasCost, OpcodeId},
+        Bytecode, Word,
+    };
+
+    fn test_ok(opcode: OpcodeId, gas_left: u64) {
+        // Push the operands of the opcode, which then runs with `gas_left`
+        let (operand_count, _) = opcode.stack_pop_push();
+        let mut code = Bytecode::default();
+        for _ in 0..operand_count {
+            code.push(1, Word::one());
+        }
+        code.write_op(opcode);
+        if opcode == OpcodeId::PUSH1 {
+            code.write(0x2a);
+        }
+        let test_config = BytecodeTestConfig {
+            gas_limit: GasCost::TX.as_u64()
+                + GasCost::FASTEST.as_u64() * operand_count as u64
+                + gas_left,
+            ..Default::default()
+        };
+        assert_eq!(test_circuits_using_bytecode(code, test_config), Ok(()));
+    }
+
+    #[test]
+    fn error_oog_constant_simple() {
+        test_ok(OpcodeId::ADD, 2);
+        test_ok(OpcodeId::MUL, 0);
+        test_ok(OpcodeId::PUSH1, 2);
+        test_ok(OpcodeId::JUMPDEST, 0);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::ConstraintBuilder,
+            math_gadget::LtGadget,
+            memory_gadget::{address_low, MemoryExpandedAddressGadget, MemoryExpansionGadget},
+            Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian,
+};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGLogGadget<F> {
+    opcode: Cell<F>,
+    memory_address: MemoryExpandedAddressGadget<F>,
+    // Allow memory size to expand to 5 bytes as ErrorOOGPureMemoryGadget does
+    memory_expansion: MemoryExpansionGadget<F, 1, { N_BYTES_MEMORY_WORD_SIZE + 1 }>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGLogGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasLOG";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasLOG;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.require_in_set(
+            "ErrorOutOfGasLOG only happens in LOG0, LOG1, LOG2, LOG3 and LOG4",
+            opcode.expr(),
+            vec![
+                OpcodeId::LOG0.expr(),
+                OpcodeId::LOG1.expr(),
+                OpcodeId::LOG2.expr(),
+                OpcodeId::LOG3.expr(),
+                OpcodeId::LOG4.expr(),
+            ],
+        );
+
+        // Pop offset and size from stack
+        let memory_address = MemoryExpandedAddressGadget::construct(cb);
+        cb.stack_pop(memory_address.offset_rlc());
+        cb.stack_pop(memory_address.length_rlc());
+
+        // Calculate the gas cost, which includes the cost of every topic, the
+        // cost of every byte of data and the memory expansion cost
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [memory_address.address()],
+        );
+        let gas_cost = GasCost::LOG.expr()
+            + GasCost::LOG.expr() * (opcode.expr() - OpcodeId::LOG0.expr())
+            + GasCost::LOG_DATA.expr() * memory_address.length()
+            + memory_expansion.gas_cost();
+
+        // Check the amount of gas available is less than the gas cost, which
+        // is always the case when the memory address is out of range
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.condition(memory_address.address_in_range(), |cb| {
+            cb.require_equal("gas_left < gas_cost", insufficient_gas.expr(), 1.expr());
+        });
+
+        // The call fails, so all of its state writes are reverted
+        cb.require_call_failure();
+
+        // The call returns to the caller without return data, and with all of
+        // its gas consumed
+        let restore_context =
+            RestoreContextGadget::construct(cb, 0.expr(), 0.expr(), 0.expr(), 0.expr());
+
+        Self {
+            opcode,
+            memory_address,
+            memory_expansion,
+            insufficient_gas,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        let [memory_offset, size] =
+            [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].stack_value());
+        let memory_address = self
+            .memory_address
+            .assign(region, offset, memory_offset, size)?;
+
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+        let topic_count = opcode.as_u64() - OpcodeId::LOG0.as_u64();
+        let gas_cost = GasCost::LOG.as_u64() * (1 + topic_count)
+            + GasCost::LOG_DATA.as_u64() * address_low::value(size.to_le_bytes())
+            + memory_expansion_gas_cost;
+        self.insufficient_gas
+            .assign(region, offset, F::from(step.gas_left), F::from(gas_cost))?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::{test_circuits_using_bytecode, BytecodeTestConfig};
+    use eth_types::{
+        bytecode,
+        evm_types::{GasCost, OpcodeId},
+        Bytecode, Word,
+    };
+
+    fn test_ok(opcode: OpcodeId, memory_offset: Word, size: Word, gas_left: u64) {
+        // Push the topics, and then the size and the offset of the data
+        let topic_count = opcode.as_u64() - OpcodeId::LOG0.as_u64();
+        let mut code = Bytecode::default();
+        for topic in 0..topic_count {
+            code.push(1, Word::from(topic));
+        }
+        code.append(&bytecode! {
+            PUSH32(size)
+            PUSH32(memory_offset)
+        });
+        code.write_op(opcode);
+        let test_config = BytecodeTestConfig {
+            gas_limit: GasCost::TX.as_u64()
+                + (2 + topic_count) * GasCost::FASTEST.as_u64()
+                + gas_left,
+            ..Default::default()
+        };
+        assert_eq!(test_circuits_using_bytecode(code, test_config), Ok(()));
+    }
+
+    #[test]
+    fn error_oog_log_simple() {
+        // 375 + 8 * 32 + 3
+        test_ok(OpcodeId::LOG0, Word::zero(), Word::from(0x20), 633);
+        // 375 * 3 + 8 * 32 + 3 * 3
+        test_ok(OpcodeId::LOG2, Word::from(0x40), Word::from(0x20), 1389);
+        // 375 * 5
+        test_ok(OpcodeId::LOG4, Word::MAX, Word::zero(), 1874);
+    }
+
+    #[test]
+    fn error_oog_log_address_out_of_range() {
+        test_ok(OpcodeId::LOG1, Word::MAX, Word::one(), 1_000_000);
+        test_ok(OpcodeId::LOG3, Word::zero(), Word::MAX, 1_000_000);
+    }
+}
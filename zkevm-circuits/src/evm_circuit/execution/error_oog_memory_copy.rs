@@ -0,0 +1,330 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::ConstraintBuilder,
+            from_bytes,
+            math_gadget::{IsEqualGadget, LtGadget},
+            memory_gadget::{
+                address_low, MemoryExpandedAddressGadget, MemoryExpansionGadget,
+                MemoryWordSizeGadget,
+            },
+            Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian, U256,
+};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGMemoryCopyGadget<F> {
+    opcode: Cell<F>,
+    is_extcodecopy: IsEqualGadget<F>,
+    // Only used in EXTCODECOPY
+    external_address: Word<F>,
+    tx_id: Cell<F>,
+    is_warm: Cell<F>,
+    memory_address: MemoryExpandedAddressGadget<F>,
+    data_offset: Word<F>,
+    constant_gas_cost: Cell<F>,
+    // Allow memory size to expand to 5 bytes as ErrorOOGPureMemoryGadget does
+    memory_expansion: MemoryExpansionGadget<F, 1, { N_BYTES_MEMORY_WORD_SIZE + 1 }>,
+    memory_copier_word_size: MemoryWordSizeGadget<F, { N_BYTES_MEMORY_WORD_SIZE + 1 }>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasMemoryCopy";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasMemoryCopy;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.require_in_set(
+            "ErrorOutOfGasMemoryCopy only happens in CALLDATACOPY, CODECOPY, EXTCODECOPY and RETURNDATACOPY",
+            opcode.expr(),
+            vec![
+                OpcodeId::CALLDATACOPY.expr(),
+                OpcodeId::CODECOPY.expr(),
+                OpcodeId::EXTCODECOPY.expr(),
+                OpcodeId::RETURNDATACOPY.expr(),
+            ],
+        );
+        let is_extcodecopy =
+            IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::EXTCODECOPY.expr());
+
+        // Pop the address first for EXTCODECOPY
+        let external_address = cb.query_word();
+        cb.condition(is_extcodecopy.expr(), |cb| {
+            cb.stack_lookup(false.expr(), 0.expr(), external_address.expr());
+        });
+
+        // Pop memory_offset, data_offset, length from stack
+        let memory_address = MemoryExpandedAddressGadget::construct(cb);
+        let data_offset = cb.query_word();
+        for (idx, value) in [
+            memory_address.offset_rlc(),
+            data_offset.expr(),
+            memory_address.length_rlc(),
+        ]
+        .iter()
+        .enumerate()
+        {
+            cb.stack_lookup(
+                false.expr(),
+                is_extcodecopy.expr() + idx.expr(),
+                value.clone(),
+            );
+        }
+
+        // Check whether the external account is in the access list for
+        // EXTCODECOPY
+        let tx_id = cb.query_cell();
+        let is_warm = cb.query_bool();
+        cb.condition(is_extcodecopy.expr(), |cb| {
+            cb.call_context_lookup(false.expr(), None, CallContextFieldTag::TxId, tx_id.expr());
+            cb.account_access_list_read(
+                tx_id.expr(),
+                from_bytes::expr(&external_address.cells[..N_BYTES_ACCOUNT_ADDRESS]),
+                is_warm.expr(),
+            );
+        });
+
+        // Calculate the gas cost, which includes the cold account access cost
+        // for EXTCODECOPY (EIP-2929), the copy cost and the memory expansion
+        // cost
+        let constant_gas_cost = cb.query_cell();
+        cb.constant_gas_lookup(opcode.expr(), constant_gas_cost.expr());
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [memory_address.address()],
+        );
+        let memory_copier_word_size = MemoryWordSizeGadget::construct(cb, memory_address.length());
+        let gas_cost = constant_gas_cost.expr()
+            + is_extcodecopy.expr()
+                * (1.expr() - is_warm.expr())
+                * (GasCost::COLD_ACCOUNT_ACCESS_COST.expr()
+                    - GasCost::WARM_STORAGE_READ_COST.expr())
+            + GasCost::COPY.expr() * memory_copier_word_size.expr()
+            + memory_expansion.gas_cost();
+
+        // Check the amount of gas available is less than the gas cost, which
+        // is always the case when the memory address is out of range
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.condition(memory_address.address_in_range(), |cb| {
+            cb.require_equal("gas_left < gas_cost", insufficient_gas.expr(), 1.expr());
+        });
+
+        // The call fails, so all of its state writes are reverted
+        cb.require_call_failure();
+
+        // The call returns to the caller without return data, and with all of
+        // its gas consumed
+        let restore_context =
+            RestoreContextGadget::construct(cb, 0.expr(), 0.expr(), 0.expr(), 0.expr());
+
+        Self {
+            opcode,
+            is_extcodecopy,
+            external_address,
+            tx_id,
+            is_warm,
+            memory_address,
+            data_offset,
+            constant_gas_cost,
+            memory_expansion,
+            memory_copier_word_size,
+            insufficient_gas,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        let is_extcodecopy = opcode == OpcodeId::EXTCODECOPY;
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+        self.is_extcodecopy.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::EXTCODECOPY.as_u64()),
+        )?;
+
+        let stack_values = step.rw_indices[..if is_extcodecopy { 4 } else { 3 }]
+            .iter()
+            .map(|idx| block.rws[*idx].stack_value())
+            .collect::<Vec<_>>();
+        let (external_address, [memory_offset, data_offset, length]) = if is_extcodecopy {
+            (
+                stack_values[0],
+                [stack_values[1], stack_values[2], stack_values[3]],
+            )
+        } else {
+            (
+                U256::zero(),
+                [stack_values[0], stack_values[1], stack_values[2]],
+            )
+        };
+        self.external_address
+            .assign(region, offset, Some(external_address.to_le_bytes()))?;
+        self.data_offset
+            .assign(region, offset, Some(data_offset.to_le_bytes()))?;
+        let memory_address = self
+            .memory_address
+            .assign(region, offset, memory_offset, length)?;
+
+        let (tx_id, is_warm) = if is_extcodecopy {
+            let (_, is_warm) = block.rws[step.rw_indices[5]].tx_access_list_value_pair();
+            (tx.id, is_warm)
+        } else {
+            (0, false)
+        };
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx_id as u64)))?;
+        self.is_warm
+            .assign(region, offset, Some(F::from(is_warm as u64)))?;
+
+        let constant_gas_cost = opcode.constant_gas_cost().as_u64();
+        self.constant_gas_cost
+            .assign(region, offset, Some(F::from(constant_gas_cost)))?;
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+        let memory_copier_word_size = self.memory_copier_word_size.assign(
+            region,
+            offset,
+            address_low::value(length.to_le_bytes()),
+        )?;
+        let gas_cost = constant_gas_cost
+            + if is_extcodecopy && !is_warm {
+                GasCost::COLD_ACCOUNT_ACCESS_COST.as_u64()
+                    - GasCost::WARM_STORAGE_READ_COST.as_u64()
+            } else {
+                0
+            }
+            + GasCost::COPY.as_u64() * memory_copier_word_size
+            + memory_expansion_gas_cost;
+        self.insufficient_gas
+            .assign(region, offset, F::from(step.gas_left), F::from(gas_cost))?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::{test_circuits_using_bytecode, BytecodeTestConfig};
+    use eth_types::{
+        address, bytecode,
+        evm_types::{GasCost, OpcodeId},
+        Address, ToWord, Word,
+    };
+
+    fn test_ok(opcode: OpcodeId, memory_offset: Word, length: Word, gas_left: u64, is_warm: bool) {
+        let mut code = bytecode! {
+            PUSH32(length)
+            PUSH1(0x00) // data offset
+            PUSH32(memory_offset)
+        };
+        let mut push_count = 3;
+        // The callee of the tx is warm, unlike any other account
+        if opcode == OpcodeId::EXTCODECOPY {
+            let external_address = if is_warm {
+                Address::zero()
+            } else {
+                address!("0x0000000000000000000000000000000000cafe01")
+            };
+            code.push(20, external_address.to_word());
+            push_count += 1;
+        }
+        code.write_op(opcode);
+        let test_config = BytecodeTestConfig {
+            gas_limit: GasCost::TX.as_u64() + push_count * GasCost::FASTEST.as_u64() + gas_left,
+            ..Default::default()
+        };
+        assert_eq!(test_circuits_using_bytecode(code, test_config), Ok(()));
+    }
+
+    #[test]
+    fn error_oog_memory_copy_simple() {
+        test_ok(
+            OpcodeId::CALLDATACOPY,
+            Word::from(0x40),
+            Word::from(0x20),
+            10,
+            false,
+        );
+        test_ok(
+            OpcodeId::CODECOPY,
+            Word::zero(),
+            Word::from(0x100),
+            40,
+            false,
+        );
+    }
+
+    #[test]
+    fn error_oog_memory_copy_extcodecopy() {
+        // Cold account
+        test_ok(
+            OpcodeId::EXTCODECOPY,
+            Word::zero(),
+            Word::from(0x20),
+            2000,
+            false,
+        );
+        // Warm account
+        test_ok(
+            OpcodeId::EXTCODECOPY,
+            Word::zero(),
+            Word::from(0x20),
+            105,
+            true,
+        );
+    }
+
+    #[test]
+    fn error_oog_memory_copy_address_out_of_range() {
+        test_ok(
+            OpcodeId::RETURNDATACOPY,
+            Word::MAX,
+            Word::one(),
+            1_000_000,
+            false,
+        );
+        test_ok(
+            OpcodeId::CALLDATACOPY,
+            Word::zero(),
+            Word::from(u64::MAX),
+            1_000_000,
+            false,
+        );
+    }
+}
@@ -4,6 +4,7 @@ use crate::{
         param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
         step::ExecutionState,
         util::{
+            common_gadget::RestoreContextGadget,
             constraint_builder::ConstraintBuilder,
             math_gadget::{IsEqualGadget, IsZeroGadget, RangeCheckGadget},
             memory_gadget::{address_high, address_low, MemoryExpansionGadget},
@@ -33,6 +34,7 @@ pub(crate) struct ErrorOOGPureMemoryGadget<F> {
     // division by 512, which still fits in 8 bytes.
     insufficient_gas: RangeCheckGadget<F, N_BYTES_GAS>,
     is_mstore8: IsEqualGadget<F>,
+    restore_context: RestoreContextGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for ErrorOOGPureMemoryGadget<F> {
@@ -74,8 +76,13 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGPureMemoryGadget<F> {
         // We still have to do this to verify the correctness of `address`
         cb.stack_pop(address.expr());
 
-        // TODO: Use ContextSwitchGadget to switch call context to caller's and
-        // consume all gas_left.
+        // The call fails, so all of its state writes are reverted
+        cb.require_call_failure();
+
+        // The call returns to the caller without return data, and with all of
+        // its gas consumed
+        let restore_context =
+            RestoreContextGadget::construct(cb, 0.expr(), 0.expr(), 0.expr(), 0.expr());
 
         Self {
             opcode,
@@ -84,6 +91,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGPureMemoryGadget<F> {
             memory_expansion,
             insufficient_gas,
             is_mstore8,
+            restore_context,
         }
     }
 
@@ -93,7 +101,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGPureMemoryGadget<F> {
         offset: usize,
         block: &Block<F>,
         _: &Transaction,
-        _: &Call,
+        call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
         let opcode = step.opcode.unwrap();
@@ -132,6 +140,9 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGPureMemoryGadget<F> {
         self.insufficient_gas
             .assign(region, offset, F::from(step.gas_cost - step.gas_left))?;
 
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
         Ok(())
     }
 }
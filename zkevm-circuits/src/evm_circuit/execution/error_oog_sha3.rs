@@ -0,0 +1,152 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::ConstraintBuilder,
+            math_gadget::LtGadget,
+            memory_gadget::{
+                address_low, MemoryExpandedAddressGadget, MemoryExpansionGadget,
+                MemoryWordSizeGadget,
+            },
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian,
+};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGSha3Gadget<F> {
+    memory_address: MemoryExpandedAddressGadget<F>,
+    // Allow memory size to expand to 5 bytes as ErrorOOGPureMemoryGadget does
+    memory_expansion: MemoryExpansionGadget<F, 1, { N_BYTES_MEMORY_WORD_SIZE + 1 }>,
+    hash_word_size: MemoryWordSizeGadget<F, { N_BYTES_MEMORY_WORD_SIZE + 1 }>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGSha3Gadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasSHA3";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasSHA3;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        cb.opcode_lookup(OpcodeId::SHA3.expr(), 1.expr());
+
+        // Pop offset and size from stack
+        let memory_address = MemoryExpandedAddressGadget::construct(cb);
+        cb.stack_pop(memory_address.offset_rlc());
+        cb.stack_pop(memory_address.length_rlc());
+
+        // Calculate the gas cost, which includes the hashing cost of every
+        // word and the memory expansion cost
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [memory_address.address()],
+        );
+        let hash_word_size = MemoryWordSizeGadget::construct(cb, memory_address.length());
+        let gas_cost = OpcodeId::SHA3.constant_gas_cost().expr()
+            + GasCost::COPY_SHA3.expr() * hash_word_size.expr()
+            + memory_expansion.gas_cost();
+
+        // Check the amount of gas available is less than the gas cost, which
+        // is always the case when the memory address is out of range
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.condition(memory_address.address_in_range(), |cb| {
+            cb.require_equal("gas_left < gas_cost", insufficient_gas.expr(), 1.expr());
+        });
+
+        // The call fails, so all of its state writes are reverted
+        cb.require_call_failure();
+
+        // The call returns to the caller without return data, and with all of
+        // its gas consumed
+        let restore_context =
+            RestoreContextGadget::construct(cb, 0.expr(), 0.expr(), 0.expr(), 0.expr());
+
+        Self {
+            memory_address,
+            memory_expansion,
+            hash_word_size,
+            insufficient_gas,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let [memory_offset, size] =
+            [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].stack_value());
+        let memory_address = self
+            .memory_address
+            .assign(region, offset, memory_offset, size)?;
+
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+        let hash_word_size =
+            self.hash_word_size
+                .assign(region, offset, address_low::value(size.to_le_bytes()))?;
+        let gas_cost = OpcodeId::SHA3.constant_gas_cost().as_u64()
+            + GasCost::COPY_SHA3.as_u64() * hash_word_size
+            + memory_expansion_gas_cost;
+        self.insufficient_gas
+            .assign(region, offset, F::from(step.gas_left), F::from(gas_cost))?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::{test_circuits_using_bytecode, BytecodeTestConfig};
+    use eth_types::{bytecode, evm_types::GasCost, Word};
+
+    fn test_ok(memory_offset: Word, size: Word, gas_left: u64) {
+        let code = bytecode! {
+            PUSH32(size)
+            PUSH32(memory_offset)
+            SHA3
+        };
+        let test_config = BytecodeTestConfig {
+            gas_limit: GasCost::TX.as_u64() + 2 * GasCost::FASTEST.as_u64() + gas_left,
+            ..Default::default()
+        };
+        assert_eq!(test_circuits_using_bytecode(code, test_config), Ok(()));
+    }
+
+    #[test]
+    fn error_oog_sha3_simple() {
+        // 30 + 6 + 3
+        test_ok(Word::zero(), Word::from(0x20), 38);
+        // 30 + 6 * 4 + 3 * 6
+        test_ok(Word::from(0x40), Word::from(0x80), 50);
+    }
+
+    #[test]
+    fn error_oog_sha3_address_out_of_range() {
+        test_ok(Word::MAX, Word::one(), 1_000_000);
+        test_ok(Word::zero(), Word::MAX, 1_000_000);
+    }
+}
@@ -0,0 +1,301 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_U64,
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::ConstraintBuilder,
+            from_bytes,
+            math_gadget::{IsZeroGadget, LtGadget},
+            sum, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorReturnDataOutOfBoundGadget<F> {
+    memory_offset: Cell<F>,
+    data_offset: Word<F>,
+    length: Word<F>,
+    last_callee_id: Cell<F>,
+    return_data_offset: Cell<F>,
+    return_data_length: Cell<F>,
+    // Whether both data_offset and length fit in 8 bytes
+    is_u64: IsZeroGadget<F>,
+    // The sum of 2 u64 values takes at most 9 bytes
+    is_end_gt_length: LtGadget<F, { N_BYTES_U64 + 1 }>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorReturnDataOutOfBoundGadget<F> {
+    const NAME: &'static str = "ErrorReturnDataOutOfBound";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorReturnDataOutOfBound;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        cb.opcode_lookup(OpcodeId::RETURNDATACOPY.expr(), 1.expr());
+
+        let memory_offset = cb.query_cell();
+        let data_offset = cb.query_word();
+        let length = cb.query_word();
+
+        // Pop memory_offset, data_offset, length from stack
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(data_offset.expr());
+        cb.stack_pop(length.expr());
+
+        // Lookup the same call context fields as RETURNDATACOPY does
+        let last_callee_id = cb.call_context(None, CallContextFieldTag::LastCalleeId);
+        let return_data_offset =
+            cb.call_context(None, CallContextFieldTag::LastCalleeReturnDataOffset);
+        let return_data_length =
+            cb.call_context(None, CallContextFieldTag::LastCalleeReturnDataLength);
+
+        // Check data_offset + length > return_data_length, which is always the
+        // case when any of them doesn't fit in 8 bytes
+        let is_u64 = IsZeroGadget::construct(
+            cb,
+            sum::expr(
+                data_offset.cells[N_BYTES_U64..]
+                    .iter()
+                    .chain(length.cells[N_BYTES_U64..].iter()),
+            ),
+        );
+        let is_end_gt_length = LtGadget::construct(
+            cb,
+            return_data_length.expr(),
+            from_bytes::expr(&data_offset.cells[..N_BYTES_U64])
+                + from_bytes::expr(&length.cells[..N_BYTES_U64]),
+        );
+        cb.require_zero(
+            "data_offset + length > return_data_length",
+            is_u64.expr() * (1.expr() - is_end_gt_length.expr()),
+        );
+
+        // The call fails, so all of its state writes are reverted
+        cb.require_call_failure();
+
+        // The call returns to the caller without return data, and with all of
+        // its gas consumed
+        let restore_context =
+            RestoreContextGadget::construct(cb, 0.expr(), 0.expr(), 0.expr(), 0.expr());
+
+        Self {
+            memory_offset,
+            data_offset,
+            length,
+            last_callee_id,
+            return_data_offset,
+            return_data_length,
+            is_u64,
+            is_end_gt_length,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let [memory_offset, data_offset, length] =
+            [step.rw_indices[0], step.rw_indices[1], step.rw_indices[2]]
+                .map(|idx| block.rws[idx].stack_value());
+        self.memory_offset.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                memory_offset.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+        let [data_offset, length] = [data_offset, length].map(|value| value.to_le_bytes());
+        self.data_offset.assign(region, offset, Some(data_offset))?;
+        self.length.assign(region, offset, Some(length))?;
+
+        let [last_callee_id, return_data_offset, return_data_length] =
+            [step.rw_indices[3], step.rw_indices[4], step.rw_indices[5]]
+                .map(|idx| block.rws[idx].call_context_value().as_u64());
+        for (cell, value) in [
+            (&self.last_callee_id, last_callee_id),
+            (&self.return_data_offset, return_data_offset),
+            (&self.return_data_length, return_data_length),
+        ] {
+            cell.assign(region, offset, Some(F::from(value)))?;
+        }
+
+        self.is_u64.assign(
+            region,
+            offset,
+            sum::value::<F>(&data_offset[N_BYTES_U64..]) + sum::value::<F>(&length[N_BYTES_U64..]),
+        )?;
+        self.is_end_gt_length.assign(
+            region,
+            offset,
+            F::from(return_data_length),
+            from_bytes::value::<F>(&data_offset[..N_BYTES_U64])
+                + from_bytes::value::<F>(&length[..N_BYTES_U64]),
+        )?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        evm_circuit::{
+            step::ExecutionState,
+            table::{CallContextFieldTag, RwTableTag},
+            test::run_test_circuit_incomplete_fixed_table,
+            witness::{Block, Bytecode, Call, CodeSource, ExecStep, Rw, RwMap, Transaction},
+        },
+        test_util::run_test_circuits,
+    };
+    use eth_types::{bytecode, evm_types::OpcodeId, ToBigEndian, Word};
+    use halo2_proofs::arithmetic::BaseExt;
+    use pairing::bn256::Fr as Fp;
+
+    fn test_ok(return_data_length: u64, data_offset: Word, length: Word) {
+        let randomness = Fp::rand();
+        let memory_offset = Word::from(0x40);
+        let bytecode = Bytecode::new(
+            [
+                vec![OpcodeId::PUSH32.as_u8()],
+                length.to_be_bytes().to_vec(),
+                vec![OpcodeId::PUSH32.as_u8()],
+                data_offset.to_be_bytes().to_vec(),
+                vec![OpcodeId::PUSH32.as_u8()],
+                memory_offset.to_be_bytes().to_vec(),
+                vec![OpcodeId::RETURNDATACOPY.as_u8()],
+            ]
+            .concat(),
+        );
+        let call_id = 1;
+
+        let rws = RwMap(
+            [
+                (
+                    RwTableTag::Stack,
+                    vec![(1021, memory_offset), (1022, data_offset), (1023, length)]
+                        .into_iter()
+                        .enumerate()
+                        .map(|(idx, (stack_pointer, value))| Rw::Stack {
+                            rw_counter: idx + 1,
+                            is_write: false,
+                            call_id,
+                            stack_pointer,
+                            value,
+                        })
+                        .collect(),
+                ),
+                (
+                    RwTableTag::CallContext,
+                    vec![
+                        (CallContextFieldTag::LastCalleeId, Word::from(2)),
+                        (
+                            CallContextFieldTag::LastCalleeReturnDataOffset,
+                            Word::zero(),
+                        ),
+                        (
+                            CallContextFieldTag::LastCalleeReturnDataLength,
+                            Word::from(return_data_length),
+                        ),
+                        (CallContextFieldTag::IsSuccess, Word::zero()),
+                        (CallContextFieldTag::RwCounterEndOfReversion, Word::from(8)),
+                    ]
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, (field_tag, value))| Rw::CallContext {
+                        rw_counter: idx + 4,
+                        is_write: false,
+                        call_id,
+                        field_tag,
+                        value,
+                    })
+                    .collect(),
+                ),
+            ]
+            .into(),
+        );
+
+        let steps = vec![ExecStep {
+            rw_indices: vec![
+                (RwTableTag::Stack, 0),
+                (RwTableTag::Stack, 1),
+                (RwTableTag::Stack, 2),
+                (RwTableTag::CallContext, 0),
+                (RwTableTag::CallContext, 1),
+                (RwTableTag::CallContext, 2),
+                (RwTableTag::CallContext, 3),
+                (RwTableTag::CallContext, 4),
+            ],
+            execution_state: ExecutionState::ErrorReturnDataOutOfBound,
+            rw_counter: 1,
+            program_counter: 99,
+            stack_pointer: 1021,
+            opcode: Some(OpcodeId::RETURNDATACOPY),
+            ..Default::default()
+        }];
+
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                id: 1,
+                calls: vec![Call {
+                    id: call_id,
+                    is_root: true,
+                    is_create: false,
+                    code_source: CodeSource::Account(bytecode.hash),
+                    ..Default::default()
+                }],
+                steps,
+                ..Default::default()
+            }],
+            rws,
+            bytecodes: vec![bytecode],
+            ..Default::default()
+        };
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn error_return_data_out_of_bound_simple() {
+        test_ok(0x20, Word::from(0x10), Word::from(0x11));
+        test_ok(0x00, Word::zero(), Word::one());
+    }
+
+    #[test]
+    fn error_return_data_out_of_bound_overflow() {
+        test_ok(0x20, Word::MAX, Word::one());
+        test_ok(0x20, Word::from(u64::MAX), Word::from(u64::MAX));
+    }
+
+    #[test]
+    fn error_return_data_out_of_bound_root() {
+        // The root call has no last callee, so its return data is empty
+        for (data_offset, length) in [(0x00u64, 0x01u64), (0x20, 0x00)] {
+            let bytecode = bytecode! {
+                PUSH32(length)
+                PUSH32(data_offset)
+                PUSH1(0x40)
+                RETURNDATACOPY
+                STOP
+            };
+            assert_eq!(run_test_circuits(bytecode), Ok(()));
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_STACK_POINTER,
+        step::ExecutionState,
+        table::{FixedTableTag, Lookup},
+        util::{
+            common_gadget::RestoreContextGadget, constraint_builder::ConstraintBuilder,
+            math_gadget::LtGadget, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::Field;
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorStackOverflowGadget<F> {
+    opcode: Cell<F>,
+    min_stack_pointer: Cell<F>,
+    max_stack_pointer: Cell<F>,
+    is_overflow: LtGadget<F, N_BYTES_STACK_POINTER>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorStackOverflowGadget<F> {
+    const NAME: &'static str = "ErrorStackOverflow";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorStackOverflow;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+
+        // Lookup the valid range of stack pointer of the opcode
+        let min_stack_pointer = cb.query_cell();
+        let max_stack_pointer = cb.query_cell();
+        cb.add_lookup(
+            "Stack pointer range lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::StackPointerRange.expr(),
+                values: [
+                    opcode.expr(),
+                    min_stack_pointer.expr(),
+                    max_stack_pointer.expr(),
+                ],
+            },
+        );
+
+        // Check the stack doesn't have enough space to push, which means the
+        // stack pointer is less than the minimum
+        let is_overflow = LtGadget::construct(
+            cb,
+            cb.curr.state.stack_pointer.expr(),
+            min_stack_pointer.expr(),
+        );
+        cb.require_equal(
+            "stack_pointer < min_stack_pointer",
+            is_overflow.expr(),
+            1.expr(),
+        );
+
+        // The call fails, so all of its state writes are reverted
+        cb.require_call_failure();
+
+        // The call returns to the caller without return data, and with all of
+        // its gas consumed
+        let restore_context =
+            RestoreContextGadget::construct(cb, 0.expr(), 0.expr(), 0.expr(), 0.expr());
+
+        Self {
+            opcode,
+            min_stack_pointer,
+            max_stack_pointer,
+            is_overflow,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        let (min_stack_pointer, max_stack_pointer) = opcode.valid_stack_ptr_range();
+        self.min_stack_pointer
+            .assign(region, offset, Some(F::from(min_stack_pointer as u64)))?;
+        self.max_stack_pointer
+            .assign(region, offset, Some(F::from(max_stack_pointer as u64)))?;
+
+        self.is_overflow.assign(
+            region,
+            offset,
+            F::from(step.stack_pointer as u64),
+            F::from(min_stack_pointer as u64),
+        )?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        evm_circuit::{
+            step::ExecutionState,
+            table::{CallContextFieldTag, RwTableTag},
+            test::run_test_circuit_incomplete_fixed_table,
+            witness::{Block, Bytecode, Call, CodeSource, ExecStep, Rw, RwMap, Transaction},
+        },
+        test_util::{test_circuits_using_bytecode, BytecodeTestConfig},
+    };
+    use eth_types::{evm_types::OpcodeId, Word};
+    use halo2_proofs::arithmetic::BaseExt;
+    use pairing::bn256::Fr as Fp;
+
+    #[test]
+    fn error_stack_overflow_simple() {
+        for opcode in [OpcodeId::PUSH1, OpcodeId::DUP1, OpcodeId::CALLER] {
+            // Fill the stack, and then push one more item
+            let mut code = eth_types::Bytecode::default();
+            for _ in 0..1024 {
+                code.push(1, Word::one());
+            }
+            code.write_op(opcode);
+            if opcode == OpcodeId::PUSH1 {
+                code.write(0x2a);
+            }
+            // The state circuit of the tests doesn't fit 1024 stack writes
+            assert_eq!(
+                test_circuits_using_bytecode(
+                    code,
+                    BytecodeTestConfig {
+                        enable_state_circuit_test: false,
+                        ..Default::default()
+                    }
+                ),
+                Ok(())
+            );
+        }
+    }
+
+    // The circuit doesn't prove a successful CALL yet, so the failure of a
+    // callee is tested with a hand-built witness
+    fn test_ok_in_sub_call(opcode: OpcodeId) {
+        let randomness = Fp::rand();
+        let caller_bytecode = Bytecode::new(vec![OpcodeId::STOP.as_u8()]);
+        let callee_bytecode = Bytecode::new(vec![opcode.as_u8()]);
+        let (caller_id, callee_id) = (1, 2);
+
+        // The callee fails, and then restores the call context of the caller,
+        // which resumes with its own gas left
+        let call_context = |rw_counter, is_write, call_id, field_tag, value| Rw::CallContext {
+            rw_counter,
+            is_write,
+            call_id,
+            field_tag,
+            value,
+        };
+        let rws = RwMap(
+            [(
+                RwTableTag::CallContext,
+                vec![
+                    call_context(
+                        1,
+                        false,
+                        callee_id,
+                        CallContextFieldTag::IsSuccess,
+                        Word::zero(),
+                    ),
+                    call_context(
+                        2,
+                        false,
+                        callee_id,
+                        CallContextFieldTag::RwCounterEndOfReversion,
+                        Word::from(2),
+                    ),
+                    call_context(
+                        3,
+                        false,
+                        callee_id,
+                        CallContextFieldTag::CallerId,
+                        Word::from(caller_id),
+                    ),
+                    call_context(
+                        4,
+                        false,
+                        caller_id,
+                        CallContextFieldTag::IsRoot,
+                        Word::one(),
+                    ),
+                    call_context(
+                        5,
+                        false,
+                        caller_id,
+                        CallContextFieldTag::IsCreate,
+                        Word::zero(),
+                    ),
+                    call_context(
+                        6,
+                        false,
+                        caller_id,
+                        CallContextFieldTag::CodeSource,
+                        caller_bytecode.hash,
+                    ),
+                    call_context(
+                        7,
+                        false,
+                        caller_id,
+                        CallContextFieldTag::ProgramCounter,
+                        Word::zero(),
+                    ),
+                    call_context(
+                        8,
+                        false,
+                        caller_id,
+                        CallContextFieldTag::StackPointer,
+                        Word::from(1023),
+                    ),
+                    call_context(
+                        9,
+                        false,
+                        caller_id,
+                        CallContextFieldTag::GasLeft,
+                        Word::from(100),
+                    ),
+                    call_context(
+                        10,
+                        false,
+                        caller_id,
+                        CallContextFieldTag::MemorySize,
+                        Word::zero(),
+                    ),
+                    call_context(
+                        11,
+                        false,
+                        caller_id,
+                        CallContextFieldTag::StateWriteCounter,
+                        Word::zero(),
+                    ),
+                    call_context(
+                        12,
+                        true,
+                        caller_id,
+                        CallContextFieldTag::LastCalleeId,
+                        Word::from(callee_id),
+                    ),
+                    call_context(
+                        13,
+                        true,
+                        caller_id,
+                        CallContextFieldTag::LastCalleeReturnDataOffset,
+                        Word::zero(),
+                    ),
+                    call_context(
+                        14,
+                        true,
+                        caller_id,
+                        CallContextFieldTag::LastCalleeReturnDataLength,
+                        Word::zero(),
+                    ),
+                ],
+            )]
+            .into(),
+        );
+
+        let steps = vec![
+            ExecStep {
+                call_index: 1,
+                rw_indices: (0..14).map(|idx| (RwTableTag::CallContext, idx)).collect(),
+                execution_state: ExecutionState::ErrorStackOverflow,
+                rw_counter: 1,
+                program_counter: 0,
+                stack_pointer: 0,
+                gas_left: 3,
+                opcode: Some(opcode),
+                ..Default::default()
+            },
+            ExecStep {
+                call_index: 0,
+                execution_state: ExecutionState::STOP,
+                rw_counter: 15,
+                program_counter: 0,
+                stack_pointer: 1023,
+                gas_left: 100,
+                opcode: Some(OpcodeId::STOP),
+                ..Default::default()
+            },
+        ];
+
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                id: 1,
+                calls: vec![
+                    Call {
+                        id: caller_id,
+                        is_root: true,
+                        is_create: false,
+                        code_source: CodeSource::Account(caller_bytecode.hash),
+                        ..Default::default()
+                    },
+                    Call {
+                        id: callee_id,
+                        is_root: false,
+                        is_create: false,
+                        code_source: CodeSource::Account(callee_bytecode.hash),
+                        caller_id,
+                        depth: 2,
+                        ..Default::default()
+                    },
+                ],
+                steps,
+                ..Default::default()
+            }],
+            rws,
+            bytecodes: vec![caller_bytecode, callee_bytecode],
+            ..Default::default()
+        };
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn error_stack_overflow_in_sub_call() {
+        test_ok_in_sub_call(OpcodeId::PUSH1);
+    }
+}
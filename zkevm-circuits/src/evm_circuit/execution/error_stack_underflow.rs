@@ -0,0 +1,135 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_STACK_POINTER,
+        step::ExecutionState,
+        table::{FixedTableTag, Lookup},
+        util::{
+            common_gadget::RestoreContextGadget, constraint_builder::ConstraintBuilder,
+            math_gadget::LtGadget, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::Field;
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorStackUnderflowGadget<F> {
+    opcode: Cell<F>,
+    min_stack_pointer: Cell<F>,
+    max_stack_pointer: Cell<F>,
+    is_underflow: LtGadget<F, N_BYTES_STACK_POINTER>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorStackUnderflowGadget<F> {
+    const NAME: &'static str = "ErrorStackUnderflow";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorStackUnderflow;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+
+        // Lookup the valid range of stack pointer of the opcode
+        let min_stack_pointer = cb.query_cell();
+        let max_stack_pointer = cb.query_cell();
+        cb.add_lookup(
+            "Stack pointer range lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::StackPointerRange.expr(),
+                values: [
+                    opcode.expr(),
+                    min_stack_pointer.expr(),
+                    max_stack_pointer.expr(),
+                ],
+            },
+        );
+
+        // Check the stack doesn't have enough items to pop, which means the
+        // stack pointer is greater than the maximum
+        let is_underflow = LtGadget::construct(
+            cb,
+            max_stack_pointer.expr(),
+            cb.curr.state.stack_pointer.expr(),
+        );
+        cb.require_equal(
+            "stack_pointer > max_stack_pointer",
+            is_underflow.expr(),
+            1.expr(),
+        );
+
+        // The call fails, so all of its state writes are reverted
+        cb.require_call_failure();
+
+        // The call returns to the caller without return data, and with all of
+        // its gas consumed
+        let restore_context =
+            RestoreContextGadget::construct(cb, 0.expr(), 0.expr(), 0.expr(), 0.expr());
+
+        Self {
+            opcode,
+            min_stack_pointer,
+            max_stack_pointer,
+            is_underflow,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+
+        let (min_stack_pointer, max_stack_pointer) = opcode.valid_stack_ptr_range();
+        self.min_stack_pointer
+            .assign(region, offset, Some(F::from(min_stack_pointer as u64)))?;
+        self.max_stack_pointer
+            .assign(region, offset, Some(F::from(max_stack_pointer as u64)))?;
+
+        self.is_underflow.assign(
+            region,
+            offset,
+            F::from(max_stack_pointer as u64),
+            F::from(step.stack_pointer as u64),
+        )?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{evm_types::OpcodeId, Bytecode, Word};
+
+    fn test_ok(opcode: OpcodeId, stack_len: usize) {
+        let mut code = Bytecode::default();
+        for _ in 0..stack_len {
+            code.push(1, Word::one());
+        }
+        code.write_op(opcode);
+        assert_eq!(run_test_circuits(code), Ok(()));
+    }
+
+    #[test]
+    fn error_stack_underflow_simple() {
+        test_ok(OpcodeId::POP, 0);
+        test_ok(OpcodeId::ADD, 1);
+        test_ok(OpcodeId::DUP2, 1);
+        test_ok(OpcodeId::SWAP16, 16);
+    }
+}
@@ -0,0 +1,343 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::ConstraintBuilder,
+            math_gadget::{IsEqualGadget, IsZeroGadget},
+            Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian, U256};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorWriteProtectionGadget<F> {
+    opcode: Cell<F>,
+    is_call: IsEqualGadget<F>,
+    // Only used in CALL
+    value: Word<F>,
+    value_is_zero: IsZeroGadget<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorWriteProtectionGadget<F> {
+    const NAME: &'static str = "ErrorWriteProtection";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorWriteProtection;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.require_in_set(
+            "ErrorWriteProtection only happens in opcodes which modify the state",
+            opcode.expr(),
+            vec![
+                OpcodeId::SSTORE.expr(),
+                OpcodeId::CREATE.expr(),
+                OpcodeId::CREATE2.expr(),
+                OpcodeId::SELFDESTRUCT.expr(),
+                OpcodeId::LOG0.expr(),
+                OpcodeId::LOG1.expr(),
+                OpcodeId::LOG2.expr(),
+                OpcodeId::LOG3.expr(),
+                OpcodeId::LOG4.expr(),
+                OpcodeId::CALL.expr(),
+            ],
+        );
+
+        // CALL only modifies the state when it transfers non-zero value, which
+        // is the 3rd item of stack
+        let is_call = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::CALL.expr());
+        let value = cb.query_word();
+        cb.condition(is_call.expr(), |cb| {
+            cb.stack_lookup(false.expr(), 2.expr(), value.expr());
+        });
+        let value_is_zero = IsZeroGadget::construct(cb, value.expr());
+        cb.require_zero(
+            "CALL transfers non-zero value",
+            is_call.expr() * value_is_zero.expr(),
+        );
+
+        // Check the current call is static
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsStatic, 1.expr());
+
+        // The call fails, so all of its state writes are reverted
+        cb.require_call_failure();
+
+        // The call returns to the caller without return data, and with all of
+        // its gas consumed
+        let restore_context =
+            RestoreContextGadget::construct(cb, 0.expr(), 0.expr(), 0.expr(), 0.expr());
+
+        Self {
+            opcode,
+            is_call,
+            value,
+            value_is_zero,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+        self.is_call.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::CALL.as_u64()),
+        )?;
+
+        let value = if opcode == OpcodeId::CALL {
+            block.rws[step.rw_indices[0]].stack_value()
+        } else {
+            U256::zero()
+        };
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.value_is_zero.assign(
+            region,
+            offset,
+            Word::random_linear_combine(value.to_le_bytes(), block.randomness),
+        )?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::{
+        step::ExecutionState,
+        table::{CallContextFieldTag, RwTableTag},
+        test::run_test_circuit_incomplete_fixed_table,
+        witness::{Block, Bytecode, Call, CodeSource, ExecStep, Rw, RwMap, Transaction},
+    };
+    use eth_types::{evm_types::OpcodeId, Word};
+    use halo2_proofs::arithmetic::BaseExt;
+    use pairing::bn256::Fr as Fp;
+
+    // A call is only static in a sub-call of STATICCALL, which can't be
+    // reached from bytecode without the circuit of the STATICCALL, so the
+    // step is built by hand.
+    fn test_ok(opcode: OpcodeId) {
+        let randomness = Fp::rand();
+        let caller_bytecode = Bytecode::new(vec![OpcodeId::STOP.as_u8()]);
+        let callee_bytecode = Bytecode::new(vec![opcode.as_u8()]);
+        let is_call = opcode == OpcodeId::CALL;
+        let (caller_id, callee_id) = (1, 2);
+
+        // The stack of CALL is [gas, callee_address, value, ...], where only
+        // the value is read
+        let stack_pointer = if is_call { 1017 } else { 1016 };
+        let mut rw_counter = 0;
+        let mut next_rw_counter = || {
+            rw_counter += 1;
+            rw_counter
+        };
+        let mut stack_rws = Vec::new();
+        if is_call {
+            stack_rws.push(Rw::Stack {
+                rw_counter: next_rw_counter(),
+                is_write: false,
+                call_id: callee_id,
+                stack_pointer: stack_pointer + 2,
+                value: Word::one(),
+            });
+        }
+        // The callee fails, and then restores the call context of the caller,
+        // which resumes with its own gas left
+        let call_context_rws = vec![
+            (false, callee_id, CallContextFieldTag::IsStatic, Word::one()),
+            (
+                false,
+                callee_id,
+                CallContextFieldTag::IsSuccess,
+                Word::zero(),
+            ),
+            (
+                false,
+                callee_id,
+                CallContextFieldTag::RwCounterEndOfReversion,
+                Word::from(stack_rws.len() + 3),
+            ),
+            (
+                false,
+                callee_id,
+                CallContextFieldTag::CallerId,
+                Word::from(caller_id),
+            ),
+            (false, caller_id, CallContextFieldTag::IsRoot, Word::one()),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::IsCreate,
+                Word::zero(),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::CodeSource,
+                caller_bytecode.hash,
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::ProgramCounter,
+                Word::zero(),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::StackPointer,
+                Word::from(1023),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::GasLeft,
+                Word::from(100),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::MemorySize,
+                Word::zero(),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::StateWriteCounter,
+                Word::zero(),
+            ),
+            (
+                true,
+                caller_id,
+                CallContextFieldTag::LastCalleeId,
+                Word::from(callee_id),
+            ),
+            (
+                true,
+                caller_id,
+                CallContextFieldTag::LastCalleeReturnDataOffset,
+                Word::zero(),
+            ),
+            (
+                true,
+                caller_id,
+                CallContextFieldTag::LastCalleeReturnDataLength,
+                Word::zero(),
+            ),
+        ]
+        .into_iter()
+        .map(|(is_write, call_id, field_tag, value)| Rw::CallContext {
+            rw_counter: next_rw_counter(),
+            is_write,
+            call_id,
+            field_tag,
+            value,
+        })
+        .collect::<Vec<_>>();
+        let next_step_rw_counter = next_rw_counter();
+
+        let mut rw_indices = (0..stack_rws.len())
+            .map(|idx| (RwTableTag::Stack, idx))
+            .collect::<Vec<_>>();
+        rw_indices.extend((0..call_context_rws.len()).map(|idx| (RwTableTag::CallContext, idx)));
+
+        let rws = RwMap(
+            [
+                (RwTableTag::Stack, stack_rws),
+                (RwTableTag::CallContext, call_context_rws),
+            ]
+            .into(),
+        );
+
+        let steps = vec![
+            ExecStep {
+                call_index: 1,
+                rw_indices,
+                execution_state: ExecutionState::ErrorWriteProtection,
+                rw_counter: 1,
+                program_counter: 0,
+                stack_pointer,
+                gas_left: 100000,
+                opcode: Some(opcode),
+                ..Default::default()
+            },
+            ExecStep {
+                call_index: 0,
+                execution_state: ExecutionState::STOP,
+                rw_counter: next_step_rw_counter,
+                program_counter: 0,
+                stack_pointer: 1023,
+                gas_left: 100,
+                opcode: Some(OpcodeId::STOP),
+                ..Default::default()
+            },
+        ];
+
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                id: 1,
+                calls: vec![
+                    Call {
+                        id: caller_id,
+                        is_root: true,
+                        is_create: false,
+                        code_source: CodeSource::Account(caller_bytecode.hash),
+                        ..Default::default()
+                    },
+                    Call {
+                        id: callee_id,
+                        is_root: false,
+                        is_create: false,
+                        code_source: CodeSource::Account(callee_bytecode.hash),
+                        caller_id,
+                        depth: 2,
+                        is_static: true,
+                        ..Default::default()
+                    },
+                ],
+                steps,
+                ..Default::default()
+            }],
+            rws,
+            bytecodes: vec![caller_bytecode, callee_bytecode],
+            ..Default::default()
+        };
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn error_write_protection_simple() {
+        test_ok(OpcodeId::SSTORE);
+        test_ok(OpcodeId::CREATE2);
+        test_ok(OpcodeId::SELFDESTRUCT);
+        test_ok(OpcodeId::LOG2);
+    }
+
+    #[test]
+    fn error_write_protection_call_with_value() {
+        test_ok(OpcodeId::CALL);
+    }
+}
@@ -0,0 +1,303 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{
+            N_BYTES_ACCOUNT_ADDRESS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE, N_BYTES_U64,
+        },
+        step::ExecutionState,
+        table::{AccountFieldTag, CallContextFieldTag},
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            from_bytes,
+            math_gadget::MinMaxGadget,
+            memory_gadget::{MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget},
+            Cell, MemoryAddress, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::circuit_input_builder::CopyDataType;
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian};
+use halo2_proofs::{circuit::Region, plonk::Error};
+use std::convert::TryInto;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ExtcodecopyGadget<F> {
+    same_context: SameContextGadget<F>,
+    address: Word<F>,
+    memory_address: MemoryAddressGadget<F>,
+    code_offset: MemoryAddress<F>,
+    tx_id: Cell<F>,
+    rw_counter_end_of_reversion: Cell<F>,
+    is_persistent: Cell<F>,
+    is_warm: Cell<F>,
+    code_hash: Cell<F>,
+    code_size: Cell<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_copier_gas: MemoryCopierGasGadget<F>,
+    src_addr: MinMaxGadget<F, N_BYTES_U64>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ExtcodecopyGadget<F> {
+    const NAME: &'static str = "EXTCODECOPY";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::EXTCODECOPY;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let address = cb.query_word();
+        let memory_offset = cb.query_cell();
+        let code_offset = cb.query_rlc();
+        let length = cb.query_rlc();
+
+        // Pop address, memory_offset, code_offset, length from stack
+        cb.stack_pop(address.expr());
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(code_offset.expr());
+        cb.stack_pop(length.expr());
+
+        // Only the lower 20 bytes of the address are used
+        let address_expr = from_bytes::expr(&address.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+        let memory_address = MemoryAddressGadget::construct(cb, memory_offset, length);
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let rw_counter_end_of_reversion =
+            cb.call_context(None, CallContextFieldTag::RwCounterEndOfReversion);
+        let is_persistent = cb.call_context(None, CallContextFieldTag::IsPersistent);
+
+        // Add the account into the access list
+        let is_warm = cb.query_bool();
+        cb.account_access_list_write_with_reversion(
+            tx_id.expr(),
+            address_expr.clone(),
+            1.expr(),
+            is_warm.expr(),
+            is_persistent.expr(),
+            rw_counter_end_of_reversion.expr(),
+        );
+
+        // Read the code hash of the account to lookup its code size
+        let code_hash = cb.query_cell();
+        cb.account_read(address_expr, AccountFieldTag::CodeHash, code_hash.expr());
+        let code_size = cb.query_cell();
+        cb.bytecode_length(code_hash.expr(), code_size.expr());
+
+        // Calculate the next memory size and the gas cost for this memory
+        // access
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [memory_address.address()],
+        );
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            memory_address.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        // Copy the bytecode, which is read as 0 past its end, with a lookup
+        // to the copy table
+        let src_addr =
+            MinMaxGadget::construct(cb, from_bytes::expr(&code_offset.cells), code_size.expr());
+        cb.condition(memory_address.has_length(), |cb| {
+            cb.copy_table_lookup(
+                code_hash.expr(),
+                CopyDataType::Bytecode.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                src_addr.min(),
+                code_size.expr(),
+                memory_address.offset(),
+                memory_address.length(),
+                memory_address.length(),
+                0.expr(),
+            );
+        });
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            // 4 stack pop + 3 call context reads + 1 access list write + 1
+            // account read + the memory writes of the copy
+            rw_counter: Delta(cb.rw_counter_offset()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(4.expr()),
+            memory_word_size: To(memory_expansion.next_memory_word_size()),
+            state_write_counter: Delta(1.expr()),
+            ..Default::default()
+        };
+        let access_gas_cost = (1.expr() - is_warm.expr())
+            * (GasCost::COLD_ACCOUNT_ACCESS_COST.expr() - GasCost::WARM_STORAGE_READ_COST.expr());
+        let same_context = SameContextGadget::construct(
+            cb,
+            opcode,
+            step_state_transition,
+            Some(access_gas_cost + memory_copier_gas.gas_cost()),
+        );
+
+        Self {
+            same_context,
+            address,
+            memory_address,
+            code_offset,
+            tx_id,
+            rw_counter_end_of_reversion,
+            is_persistent,
+            is_warm,
+            code_hash,
+            code_size,
+            memory_expansion,
+            memory_copier_gas,
+            src_addr,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [address, memory_offset, code_offset, length] = [
+            step.rw_indices[0],
+            step.rw_indices[1],
+            step.rw_indices[2],
+            step.rw_indices[3],
+        ]
+        .map(|idx| block.rws[idx].stack_value());
+        self.address
+            .assign(region, offset, Some(address.to_le_bytes()))?;
+        let memory_address =
+            self.memory_address
+                .assign(region, offset, memory_offset, length, block.randomness)?;
+        self.code_offset.assign(
+            region,
+            offset,
+            Some(
+                code_offset.to_le_bytes()[..N_BYTES_MEMORY_ADDRESS]
+                    .try_into()
+                    .unwrap(),
+            ),
+        )?;
+
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx.id as u64)))?;
+        self.rw_counter_end_of_reversion.assign(
+            region,
+            offset,
+            Some(F::from(call.rw_counter_end_of_reversion as u64)),
+        )?;
+        self.is_persistent
+            .assign(region, offset, Some(F::from(call.is_persistent as u64)))?;
+
+        let (_, is_warm) = block.rws[step.rw_indices[7]].tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Some(F::from(is_warm as u64)))?;
+
+        let (code_hash, _) = block.rws[step.rw_indices[8]].account_value_pair();
+        self.code_hash.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                code_hash.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+        let code_size = block
+            .bytecodes
+            .iter()
+            .find(|bytecode| bytecode.hash == code_hash)
+            .expect("bytecode of any account must be in the block")
+            .bytes
+            .len();
+        self.code_size
+            .assign(region, offset, Some(F::from(code_size as u64)))?;
+
+        // Memory expansion
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+
+        self.memory_copier_gas.assign(
+            region,
+            offset,
+            length.as_u64(),
+            memory_expansion_gas_cost as u64,
+        )?;
+
+        self.src_addr.assign(
+            region,
+            offset,
+            F::from(code_offset.low_u64()),
+            F::from(code_size as u64),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::{bytecode, Word};
+
+    fn test_ok(address: Word, memory_offset: Word, code_offset: Word, length: Word) {
+        let bytecode = bytecode! {
+            PUSH32(length)
+            PUSH32(code_offset)
+            PUSH32(memory_offset)
+            PUSH32(address)
+            #[start]
+            EXTCODECOPY
+            STOP
+        };
+        assert_eq!(run_test_circuits(bytecode), Ok(()));
+    }
+
+    #[test]
+    fn extcodecopy_gadget_warm_account() {
+        // The callee of the mock tx is always in the access list
+        test_ok(
+            Word::zero(),
+            Word::from(0x40),
+            Word::from(0x00),
+            Word::from(0x20),
+        );
+        test_ok(
+            Word::zero(),
+            Word::from(0x00),
+            Word::from(0x10),
+            Word::from(0x70),
+        );
+    }
+
+    #[test]
+    fn extcodecopy_gadget_cold_account() {
+        // The account has no code, so zeros are copied
+        test_ok(
+            Word::from(0x123),
+            Word::from(0x40),
+            Word::from(0x00),
+            Word::from(0x20),
+        );
+        test_ok(
+            Word::from(0x123),
+            Word::from(0x40),
+            Word::from(0x00),
+            Word::from(0x00),
+        );
+    }
+}
@@ -123,14 +123,15 @@ mod test {
         builder
             .handle_tx(&block_trace.eth_tx, &block_trace.geth_trace)
             .expect("could not handle block tx");
+        builder.set_value_ops_call_context_rwc_eor();
         let mut block = block_convert(&builder.block, &builder.code_db);
 
-        // The above block has 2 steps (GAS and STOP). We forcefully assign a
-        // wrong `gas_left` value for the second step, to assert that
-        // the circuit verification fails for this scenario.
+        // The above block has 4 steps (BeginTx, GAS, STOP and EndTx). We
+        // forcefully assign a wrong `gas_left` value for the STOP step, to
+        // assert that the circuit verification fails for this scenario.
         assert_eq!(block.txs.len(), 1);
-        assert_eq!(block.txs[0].steps.len(), 2);
-        block.txs[0].steps[1].gas_left -= 1;
+        assert_eq!(block.txs[0].steps.len(), 4);
+        block.txs[0].steps[2].gas_left -= 1;
 
         assert!(run_test_circuit(block, config.evm_circuit_lookup_tags).is_err());
     }
@@ -0,0 +1,98 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_U64,
+        step::ExecutionState,
+        table::BlockContextFieldTag,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
+            from_bytes, RandomLinearCombination,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::Field;
+use halo2_proofs::{circuit::Region, plonk::Error};
+use std::convert::TryFrom;
+
+#[derive(Clone, Debug)]
+pub(crate) struct GasLimitGadget<F> {
+    same_context: SameContextGadget<F>,
+    gas_limit: RandomLinearCombination<F, N_BYTES_U64>,
+}
+
+impl<F: Field> ExecutionGadget<F> for GasLimitGadget<F> {
+    const NAME: &'static str = "GASLIMIT";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::GASLIMIT;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let gas_limit = cb.query_rlc();
+        cb.stack_push(gas_limit.expr());
+
+        // Lookup block table with gas limit
+        cb.block_lookup(
+            BlockContextFieldTag::GasLimit.expr(),
+            None,
+            from_bytes::expr(&gas_limit.cells),
+        );
+
+        // State transition
+        let opcode = cb.query_cell();
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(1.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta((-1).expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition, None);
+
+        Self {
+            same_context,
+            gas_limit,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let gas_limit = block.rws[step.rw_indices[0]].stack_value();
+
+        self.gas_limit.assign(
+            region,
+            offset,
+            Some(u64::try_from(gas_limit).unwrap().to_le_bytes()),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::bytecode;
+
+    fn test_ok() {
+        let bytecode = bytecode! {
+            #[start]
+            GASLIMIT
+            STOP
+        };
+        assert_eq!(run_test_circuits(bytecode), Ok(()));
+    }
+    #[test]
+    fn gaslimit_gadget_test() {
+        test_ok();
+    }
+}
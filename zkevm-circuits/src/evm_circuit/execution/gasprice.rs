@@ -0,0 +1,107 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        table::{CallContextFieldTag, TxContextFieldTag},
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
+            Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{Field, ToLittleEndian};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct GasPriceGadget<F> {
+    same_context: SameContextGadget<F>,
+    tx_id: Cell<F>,
+    gas_price: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for GasPriceGadget<F> {
+    const NAME: &'static str = "GASPRICE";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::GASPRICE;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let gas_price = cb.query_cell();
+
+        // Lookup tx_table with the gas price of the current tx
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        cb.tx_context_lookup(
+            tx_id.expr(),
+            TxContextFieldTag::GasPrice,
+            None,
+            gas_price.expr(),
+        );
+
+        // Push the value to the stack
+        cb.stack_push(gas_price.expr());
+
+        // State transition
+        let opcode = cb.query_cell();
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(2.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta((-1).expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition, None);
+
+        Self {
+            same_context,
+            tx_id,
+            gas_price,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx.id as u64)))?;
+
+        let gas_price = block.rws[step.rw_indices[1]].stack_value();
+        self.gas_price.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                gas_price.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::bytecode;
+
+    fn test_ok() {
+        let bytecode = bytecode! {
+            #[start]
+            GASPRICE
+            STOP
+        };
+        assert_eq!(run_test_circuits(bytecode), Ok(()));
+    }
+    #[test]
+    fn gasprice_gadget_test() {
+        test_ok();
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        table::BlockContextFieldTag,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition::Delta},
+            Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{Field, ToLittleEndian};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct NumberGadget<F> {
+    same_context: SameContextGadget<F>,
+    number: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for NumberGadget<F> {
+    const NAME: &'static str = "NUMBER";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::NUMBER;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let number = cb.query_cell();
+        cb.stack_push(number.expr());
+
+        // Lookup block table with block number
+        cb.block_lookup(BlockContextFieldTag::Number.expr(), None, number.expr());
+
+        // State transition
+        let opcode = cb.query_cell();
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(1.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta((-1).expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition, None);
+
+        Self {
+            same_context,
+            number,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let number = block.rws[step.rw_indices[0]].stack_value();
+
+        self.number.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                number.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::run_test_circuits;
+    use eth_types::bytecode;
+
+    fn test_ok() {
+        let bytecode = bytecode! {
+            #[start]
+            NUMBER
+            STOP
+        };
+        assert_eq!(run_test_circuits(bytecode), Ok(()));
+    }
+    #[test]
+    fn number_gadget_test() {
+        test_ok();
+    }
+}
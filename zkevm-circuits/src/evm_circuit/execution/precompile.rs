@@ -0,0 +1,1228 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS},
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::ConstraintBuilder,
+            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget, MinMaxGadget},
+            select, sum, Cell,
+        },
+        witness::{Block, Call, ExecStep, StepAuxiliaryData, Transaction},
+    },
+    util::Expr,
+};
+use array_init::array_init;
+use bus_mapping::{circuit_input_builder::CopyDataType, precompile::PrecompileCalls};
+use eth_types::{Field, ToAddress, ToScalar};
+use halo2_proofs::{
+    circuit::Region,
+    plonk::{Error, Expression},
+};
+
+mod blake2f;
+mod bn128;
+mod ec_recover;
+mod identity;
+mod modexp;
+mod ripemd160;
+mod sha256;
+
+use self::blake2f::Blake2fGadget;
+use self::ripemd160::Ripemd160Gadget;
+use self::sha256::Sha256Gadget;
+use bn128::{Bn128AddGadget, Bn128MulGadget, Bn128PairingGadget};
+use ec_recover::EcRecoverGadget;
+use identity::IdentityGadget;
+use modexp::ModexpGadget;
+
+/// The call context of the call to a precompiled contract, which is shared
+/// with the gadget of every precompiled contract.
+#[derive(Clone, Debug)]
+pub(crate) struct PrecompileContext<F> {
+    // The id of the caller, whose memory holds the input, unless the call is
+    // a root call, whose input is the calldata of the transaction
+    pub(crate) caller_id: Cell<F>,
+    pub(crate) tx_id: Cell<F>,
+    pub(crate) call_data_offset: Cell<F>,
+    pub(crate) call_data_length: Cell<F>,
+    pub(crate) is_success: Cell<F>,
+    // Whether the gas passed to the call is less than the gas cost of the
+    // precompiled contract
+    pub(crate) insufficient_gas: Cell<F>,
+}
+
+impl<F: Field> PrecompileContext<F> {
+    /// The id and the type of the source of the input in the copy table,
+    /// which is the memory of the caller, or the calldata of the transaction
+    /// when `is_root`.
+    pub(crate) fn input_source(&self, is_root: Expression<F>) -> (Expression<F>, Expression<F>) {
+        (
+            select::expr(is_root.clone(), self.tx_id.expr(), self.caller_id.expr()),
+            select::expr(
+                is_root,
+                CopyDataType::TxCalldata.expr(),
+                CopyDataType::Memory.expr(),
+            ),
+        )
+    }
+}
+
+/// Gadget proving the execution of a precompiled contract, whose constraints
+/// are only enabled when `is_selected` is 1.  It must be satisfiable with any
+/// input when it's not selected.
+pub(crate) trait PrecompileCallGadget<F: Field> {
+    fn configure(
+        cb: &mut ConstraintBuilder<F>,
+        ctx: &PrecompileContext<F>,
+        is_selected: Expression<F>,
+    ) -> Self
+    where
+        Self: Sized;
+
+    /// Gas cost of the call to the precompiled contract.
+    fn gas_cost(&self) -> Expression<F>;
+
+    /// Length of the output of the call to the precompiled contract when it
+    /// succeeds.
+    fn output_length(&self, ctx: &PrecompileContext<F>) -> Expression<F>;
+
+    /// Length of the input read from the call data into [`Self::input_rlc`],
+    /// which is right padded with zeros past its end.
+    fn input_length(&self, ctx: &PrecompileContext<F>) -> Expression<F>;
+
+    /// RLC of the input, where the first byte has the highest power of the
+    /// randomness.
+    fn input_rlc(&self) -> Expression<F>;
+
+    /// RLC of the output when the call succeeds, where the first byte has the
+    /// highest power of the randomness.
+    fn output_rlc(&self) -> Expression<F>;
+
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        step: &ExecStep,
+        call_data_length: u64,
+        is_selected: bool,
+    ) -> Result<(), Error>;
+}
+
+/// Gadget for the step of a call to a precompiled contract, which dispatches
+/// to the gadget of the callee and checks that the call fails when it runs
+/// out of gas.  The input is copied from the call data into its RLC, and the
+/// output is copied from its RLC into the memory of the callee, except for
+/// the identity precompile, which copies its input there itself.  Then the
+/// call returns to the caller, into whose memory the output is copied as
+/// return data, with the gas left.
+#[derive(Clone, Debug)]
+pub(crate) struct PrecompileGadget<F> {
+    callee_address: Cell<F>,
+    ctx: PrecompileContext<F>,
+    return_data_offset: Cell<F>,
+    return_data_length: Cell<F>,
+    // `is_precompile[i]` is 1 when the callee is the precompiled contract at
+    // address `i + 1`
+    is_precompile: [IsEqualGadget<F>; 9],
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    ec_recover: EcRecoverGadget<F>,
+    sha256: Sha256Gadget<F>,
+    ripemd160: Ripemd160Gadget<F>,
+    identity: IdentityGadget<F>,
+    modexp: ModexpGadget<F>,
+    bn128_add: Bn128AddGadget<F>,
+    bn128_mul: Bn128MulGadget<F>,
+    bn128_pairing: Bn128PairingGadget<F>,
+    blake2f: Blake2fGadget<F>,
+    // Length and RLC of the input read by the callee, and the number of its
+    // bytes read before the end of the call data
+    input_length: Cell<F>,
+    input_length_is_zero: IsZeroGadget<F>,
+    input_bytes_read: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    input_rlc: Cell<F>,
+    // Length of the output, which is 0 when the call fails
+    output_length: Cell<F>,
+    output_length_is_zero: IsZeroGadget<F>,
+    output_rlc: Cell<F>,
+    // The length of the return data copied into the memory of the caller is
+    // the minimum of the output length and the length requested by the
+    // caller
+    copy_length: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    copy_length_is_zero: IsZeroGadget<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for PrecompileGadget<F> {
+    const NAME: &'static str = "Precompile";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::Precompile;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let [callee_address, caller_id, tx_id, call_data_offset, call_data_length, return_data_offset, return_data_length, is_success] =
+            [
+                CallContextFieldTag::CalleeAddress,
+                CallContextFieldTag::CallerId,
+                CallContextFieldTag::TxId,
+                CallContextFieldTag::CallDataOffset,
+                CallContextFieldTag::CallDataLength,
+                CallContextFieldTag::ReturnDataOffset,
+                CallContextFieldTag::ReturnDataLength,
+                CallContextFieldTag::IsSuccess,
+            ]
+            .map(|field_tag| cb.call_context(None, field_tag));
+        let ctx = PrecompileContext {
+            caller_id,
+            tx_id,
+            call_data_offset,
+            call_data_length,
+            is_success,
+            insufficient_gas: cb.query_bool(),
+        };
+
+        // The callee must be one of the precompiled contracts
+        let is_precompile: [IsEqualGadget<F>; 9] = array_init(|idx| {
+            IsEqualGadget::construct(cb, callee_address.expr(), (idx as u64 + 1).expr())
+        });
+        cb.require_equal(
+            "Callee is a precompiled contract",
+            sum::expr(
+                is_precompile
+                    .iter()
+                    .map(|is_precompile| is_precompile.expr()),
+            ),
+            1.expr(),
+        );
+        let is_selected =
+            |precompile: PrecompileCalls| is_precompile[precompile as usize - 1].expr();
+        // The precompiles whose execution isn't proved are never selected,
+        // as rejected by the bus-mapping
+        for precompile in PrecompileCalls::iter().filter(|precompile| !precompile.is_supported()) {
+            cb.require_zero("Precompile is supported", is_selected(precompile));
+        }
+
+        let ec_recover =
+            EcRecoverGadget::configure(cb, &ctx, is_selected(PrecompileCalls::ECRecover));
+        let sha256 = Sha256Gadget::configure(cb, &ctx, is_selected(PrecompileCalls::Sha256));
+        let ripemd160 =
+            Ripemd160Gadget::configure(cb, &ctx, is_selected(PrecompileCalls::Ripemd160));
+        let identity = IdentityGadget::configure(cb, &ctx, is_selected(PrecompileCalls::Identity));
+        let modexp = ModexpGadget::configure(cb, &ctx, is_selected(PrecompileCalls::Modexp));
+        let bn128_add = Bn128AddGadget::configure(cb, &ctx, is_selected(PrecompileCalls::Bn128Add));
+        let bn128_mul = Bn128MulGadget::configure(cb, &ctx, is_selected(PrecompileCalls::Bn128Mul));
+        let bn128_pairing =
+            Bn128PairingGadget::configure(cb, &ctx, is_selected(PrecompileCalls::Bn128Pairing));
+        let blake2f = Blake2fGadget::configure(cb, &ctx, is_selected(PrecompileCalls::Blake2F));
+
+        // Check whether the gas passed to the call is sufficient, the call
+        // fails otherwise
+        let gas_cost = sum::expr([
+            is_selected(PrecompileCalls::ECRecover) * ec_recover.gas_cost(),
+            is_selected(PrecompileCalls::Sha256) * sha256.gas_cost(),
+            is_selected(PrecompileCalls::Ripemd160) * ripemd160.gas_cost(),
+            is_selected(PrecompileCalls::Identity) * identity.gas_cost(),
+            is_selected(PrecompileCalls::Modexp) * modexp.gas_cost(),
+            is_selected(PrecompileCalls::Bn128Add) * bn128_add.gas_cost(),
+            is_selected(PrecompileCalls::Bn128Mul) * bn128_mul.gas_cost(),
+            is_selected(PrecompileCalls::Bn128Pairing) * bn128_pairing.gas_cost(),
+            is_selected(PrecompileCalls::Blake2F) * blake2f.gas_cost(),
+        ]);
+        let insufficient_gas =
+            LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost.clone());
+        cb.require_equal(
+            "insufficient_gas == gas_left < gas_cost",
+            ctx.insufficient_gas.expr(),
+            insufficient_gas.expr(),
+        );
+        cb.require_zero(
+            "Call fails when out of gas",
+            ctx.insufficient_gas.expr() * ctx.is_success.expr(),
+        );
+
+        // The output of a failed call is empty
+        let output_length = cb.query_cell();
+        cb.require_equal(
+            "output_length == is_success ⋅ output length of the callee",
+            output_length.expr(),
+            ctx.is_success.expr()
+                * sum::expr([
+                    is_selected(PrecompileCalls::ECRecover) * ec_recover.output_length(&ctx),
+                    is_selected(PrecompileCalls::Sha256) * sha256.output_length(&ctx),
+                    is_selected(PrecompileCalls::Ripemd160) * ripemd160.output_length(&ctx),
+                    is_selected(PrecompileCalls::Identity) * identity.output_length(&ctx),
+                    is_selected(PrecompileCalls::Modexp) * modexp.output_length(&ctx),
+                    is_selected(PrecompileCalls::Bn128Add) * bn128_add.output_length(&ctx),
+                    is_selected(PrecompileCalls::Bn128Mul) * bn128_mul.output_length(&ctx),
+                    is_selected(PrecompileCalls::Bn128Pairing) * bn128_pairing.output_length(&ctx),
+                    is_selected(PrecompileCalls::Blake2F) * blake2f.output_length(&ctx),
+                ]),
+        );
+        let is_identity = is_selected(PrecompileCalls::Identity);
+
+        // Copy the input from the memory of the caller, or from the calldata
+        // of the transaction for a root call, into its RLC, which is read even
+        // when the call runs out of gas since the gas cost or the validity of
+        // the input may depend on it
+        let input_length = cb.query_cell();
+        cb.require_equal(
+            "input_length == input length of the callee",
+            input_length.expr(),
+            sum::expr([
+                is_selected(PrecompileCalls::ECRecover) * ec_recover.input_length(&ctx),
+                is_selected(PrecompileCalls::Sha256) * sha256.input_length(&ctx),
+                is_selected(PrecompileCalls::Ripemd160) * ripemd160.input_length(&ctx),
+                is_identity.clone() * identity.input_length(&ctx),
+                is_selected(PrecompileCalls::Modexp) * modexp.input_length(&ctx),
+                is_selected(PrecompileCalls::Bn128Add) * bn128_add.input_length(&ctx),
+                is_selected(PrecompileCalls::Bn128Mul) * bn128_mul.input_length(&ctx),
+                is_selected(PrecompileCalls::Bn128Pairing) * bn128_pairing.input_length(&ctx),
+                is_selected(PrecompileCalls::Blake2F) * blake2f.input_length(&ctx),
+            ]),
+        );
+        let input_rlc = cb.query_cell();
+        cb.require_equal(
+            "input_rlc == input RLC of the callee",
+            input_rlc.expr(),
+            sum::expr([
+                is_selected(PrecompileCalls::ECRecover) * ec_recover.input_rlc(),
+                is_selected(PrecompileCalls::Sha256) * sha256.input_rlc(),
+                is_selected(PrecompileCalls::Ripemd160) * ripemd160.input_rlc(),
+                is_identity.clone() * identity.input_rlc(),
+                is_selected(PrecompileCalls::Modexp) * modexp.input_rlc(),
+                is_selected(PrecompileCalls::Bn128Add) * bn128_add.input_rlc(),
+                is_selected(PrecompileCalls::Bn128Mul) * bn128_mul.input_rlc(),
+                is_selected(PrecompileCalls::Bn128Pairing) * bn128_pairing.input_rlc(),
+                is_selected(PrecompileCalls::Blake2F) * blake2f.input_rlc(),
+            ]),
+        );
+        let input_length_is_zero = IsZeroGadget::construct(cb, input_length.expr());
+        let input_bytes_read =
+            MinMaxGadget::construct(cb, input_length.expr(), ctx.call_data_length.expr());
+        let is_root = cb.curr.state.is_root.expr();
+        let (src_id, src_type) = ctx.input_source(is_root.clone());
+        cb.condition(1.expr() - input_length_is_zero.expr(), |cb| {
+            cb.copy_table_lookup(
+                src_id,
+                src_type,
+                0.expr(),
+                CopyDataType::RlcAcc.expr(),
+                ctx.call_data_offset.expr(),
+                ctx.call_data_offset.expr() + ctx.call_data_length.expr(),
+                0.expr(),
+                input_length.expr(),
+                (1.expr() - is_root) * input_bytes_read.min(),
+                input_rlc.expr(),
+            );
+        });
+
+        // Copy the output from its RLC into the memory of the callee, where
+        // the output of the identity precompile is already copied from its
+        // input
+        let output_length_is_zero = IsZeroGadget::construct(cb, output_length.expr());
+        let output_rlc = cb.query_cell();
+        cb.require_equal(
+            "output_rlc == output RLC of the callee",
+            output_rlc.expr(),
+            sum::expr([
+                is_selected(PrecompileCalls::ECRecover) * ec_recover.output_rlc(),
+                is_selected(PrecompileCalls::Sha256) * sha256.output_rlc(),
+                is_selected(PrecompileCalls::Ripemd160) * ripemd160.output_rlc(),
+                is_identity.clone() * identity.output_rlc(),
+                is_selected(PrecompileCalls::Modexp) * modexp.output_rlc(),
+                is_selected(PrecompileCalls::Bn128Add) * bn128_add.output_rlc(),
+                is_selected(PrecompileCalls::Bn128Mul) * bn128_mul.output_rlc(),
+                is_selected(PrecompileCalls::Bn128Pairing) * bn128_pairing.output_rlc(),
+                is_selected(PrecompileCalls::Blake2F) * blake2f.output_rlc(),
+            ]),
+        );
+        cb.condition(
+            (1.expr() - is_identity) * (1.expr() - output_length_is_zero.expr()),
+            |cb| {
+                cb.copy_table_lookup(
+                    0.expr(),
+                    CopyDataType::RlcAcc.expr(),
+                    cb.curr.state.call_id.expr(),
+                    CopyDataType::Memory.expr(),
+                    0.expr(),
+                    output_length.expr(),
+                    0.expr(),
+                    output_length.expr(),
+                    output_length.expr(),
+                    output_rlc.expr(),
+                );
+            },
+        );
+
+        // Copy the return data from the memory of the callee into the memory
+        // of the caller, where every byte is read and written
+        let copy_length =
+            MinMaxGadget::construct(cb, return_data_length.expr(), output_length.expr());
+        let copy_length_is_zero = IsZeroGadget::construct(cb, copy_length.min());
+        cb.condition(1.expr() - copy_length_is_zero.expr(), |cb| {
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                ctx.caller_id.expr(),
+                CopyDataType::Memory.expr(),
+                0.expr(),
+                output_length.expr(),
+                return_data_offset.expr(),
+                copy_length.min(),
+                2.expr() * copy_length.min(),
+                0.expr(),
+            );
+        });
+
+        // Return to the caller with the output as return data, and with the
+        // gas left after the precompile, unless it fails, which consumes all
+        // the gas passed to it
+        let restore_context = RestoreContextGadget::construct(
+            cb,
+            ctx.is_success.expr(),
+            0.expr(),
+            output_length.expr(),
+            ctx.is_success.expr() * (cb.curr.state.gas_left.expr() - gas_cost),
+        );
+
+        Self {
+            callee_address,
+            ctx,
+            return_data_offset,
+            return_data_length,
+            is_precompile,
+            insufficient_gas,
+            ec_recover,
+            sha256,
+            ripemd160,
+            identity,
+            modexp,
+            bn128_add,
+            bn128_mul,
+            bn128_pairing,
+            blake2f,
+            input_length,
+            input_length_is_zero,
+            input_bytes_read,
+            input_rlc,
+            output_length,
+            output_length_is_zero,
+            output_rlc,
+            copy_length,
+            copy_length_is_zero,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let [callee_address, caller_id, tx_id, call_data_offset, call_data_length, return_data_offset, return_data_length, is_success] =
+            [0, 1, 2, 3, 4, 5, 6, 7].map(|idx| block.rws[step.rw_indices[idx]].call_context_value());
+        self.callee_address
+            .assign(region, offset, callee_address.to_scalar())?;
+        for (cell, value) in [
+            (&self.ctx.caller_id, caller_id),
+            (&self.ctx.tx_id, tx_id),
+            (&self.ctx.call_data_offset, call_data_offset),
+            (&self.ctx.call_data_length, call_data_length),
+            (&self.return_data_offset, return_data_offset),
+            (&self.return_data_length, return_data_length),
+            (&self.ctx.is_success, is_success),
+        ] {
+            cell.assign(region, offset, value.to_scalar())?;
+        }
+
+        let callee_address = callee_address.to_address();
+        for (is_precompile, precompile) in self.is_precompile.iter().zip(PrecompileCalls::iter()) {
+            is_precompile.assign(
+                region,
+                offset,
+                callee_address.to_scalar().unwrap(),
+                F::from(precompile as u64),
+            )?;
+        }
+
+        let (insufficient_gas, _) = self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left),
+            F::from(step.gas_cost),
+        )?;
+        self.ctx
+            .insufficient_gas
+            .assign(region, offset, Some(insufficient_gas))?;
+
+        let precompile = PrecompileCalls::from_address(&callee_address);
+        let call_data_length = call_data_length.as_u64();
+        let gadgets: [(&dyn PrecompileCallGadget<F>, PrecompileCalls); 9] = [
+            (&self.ec_recover, PrecompileCalls::ECRecover),
+            (&self.sha256, PrecompileCalls::Sha256),
+            (&self.ripemd160, PrecompileCalls::Ripemd160),
+            (&self.identity, PrecompileCalls::Identity),
+            (&self.modexp, PrecompileCalls::Modexp),
+            (&self.bn128_add, PrecompileCalls::Bn128Add),
+            (&self.bn128_mul, PrecompileCalls::Bn128Mul),
+            (&self.bn128_pairing, PrecompileCalls::Bn128Pairing),
+            (&self.blake2f, PrecompileCalls::Blake2F),
+        ];
+        for (gadget, callee) in gadgets.iter() {
+            gadget.assign(
+                region,
+                offset,
+                block,
+                step,
+                call_data_length,
+                precompile == Some(*callee),
+            )?;
+        }
+
+        let (input, output) = match step.aux_data.as_ref() {
+            Some(StepAuxiliaryData::Precompile { input, output }) => {
+                (input.as_slice(), output.as_slice())
+            }
+            _ => (&[][..], &[][..]),
+        };
+        // The identity precompile copies its input without its RLC
+        let is_identity = precompile == Some(PrecompileCalls::Identity);
+        let input_length = match precompile {
+            Some(precompile) if !is_identity => precompile.input_length(input) as u64,
+            _ => 0,
+        };
+        // The input is right padded with zeros past the end of the call data
+        let input = (0..input_length as usize)
+            .map(|idx| input.get(idx).copied().unwrap_or_default())
+            .collect::<Vec<_>>();
+        let rlc = |bytes: &[u8]| {
+            bytes.iter().fold(F::zero(), |acc, byte| {
+                acc * block.randomness + F::from(*byte as u64)
+            })
+        };
+        let input_rlc = rlc(&input);
+        let output_rlc = if is_identity { F::zero() } else { rlc(output) };
+        self.input_length
+            .assign(region, offset, Some(F::from(input_length)))?;
+        self.input_length_is_zero
+            .assign(region, offset, F::from(input_length))?;
+        self.input_bytes_read.assign(
+            region,
+            offset,
+            F::from(input_length),
+            F::from(call_data_length),
+        )?;
+        self.input_rlc.assign(region, offset, Some(input_rlc))?;
+
+        let output_length = output.len() as u64;
+        self.output_length
+            .assign(region, offset, Some(F::from(output_length)))?;
+        self.output_length_is_zero
+            .assign(region, offset, F::from(output_length))?;
+        self.output_rlc.assign(region, offset, Some(output_rlc))?;
+        let (copy_length, _) = self.copy_length.assign(
+            region,
+            offset,
+            F::from(return_data_length.low_u64()),
+            F::from(output_length),
+        )?;
+        self.copy_length_is_zero
+            .assign(region, offset, copy_length)?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::{
+        step::ExecutionState,
+        table::{CallContextFieldTag, RwTableTag},
+        test::{make_copy_event, rand_bytes, run_test_circuit_incomplete_fixed_table},
+        witness::{
+            Block, Bytecode, Call, CodeSource, EcRecover, EccCall, ExecStep, ModexpCall, Rw, RwMap,
+            StepAuxiliaryData, Transaction,
+        },
+    };
+    use blake2f::plain::Blake2fInput;
+    use bus_mapping::{
+        circuit_input_builder::{CopyDataType, NumberOrHash},
+        precompile::PrecompileCalls,
+    };
+    use eth_types::{evm_types::OpcodeId, word, Word};
+    use halo2_proofs::arithmetic::BaseExt;
+    use pairing::bn256::Fr as Fp;
+
+    /// Run the call to `precompile` with `input` at `call_data_offset` in the
+    /// memory of the caller, or with `input` as the calldata of the tx when
+    /// `is_root`.  The output is returned at 0x80 in the memory of the caller,
+    /// which resumes at a STOP step.  Return whether the circuit is
+    /// satisfied.
+    fn run_test(
+        precompile: PrecompileCalls,
+        is_root: bool,
+        call_data_offset: u64,
+        input: Vec<u8>,
+        gas_left: u64,
+    ) -> bool {
+        let randomness = Fp::rand();
+        let caller_bytecode = Bytecode::new(vec![OpcodeId::STOP.as_u8()]);
+        let precompile_bytecode = Bytecode::new(Vec::new());
+        let tx_id = 1;
+        // A root call has no caller to return data to
+        let (caller_id, call_id) = if is_root { (0, 1) } else { (1, 2) };
+        let (return_data_offset, return_data_length) = if is_root { (0, 0) } else { (0x80, 0x20) };
+        let caller_gas_left = 100;
+        let caller_memory_word_size = 5;
+        let gas_cost = precompile.gas_cost(&input);
+        // The ECC precompiles also fail when their input is invalid
+        let ecc_calls = match precompile {
+            PrecompileCalls::Bn128Add
+            | PrecompileCalls::Bn128Mul
+            | PrecompileCalls::Bn128Pairing => {
+                vec![EccCall::new(precompile, &input)]
+            }
+            _ => Vec::new(),
+        };
+        let blake2f_input = Blake2fInput::from_bytes(&input);
+        let is_valid = ecc_calls.iter().all(|ecc_call| ecc_call.is_valid())
+            && (precompile != PrecompileCalls::Blake2F || blake2f_input.is_some());
+        let is_success = gas_left >= gas_cost && is_valid;
+        let output = if is_success {
+            precompile.output(&input).unwrap()
+        } else {
+            Vec::new()
+        };
+        let gas_returned = if is_success { gas_left - gas_cost } else { 0 };
+
+        let mut rws = RwMap(
+            [(
+                RwTableTag::CallContext,
+                [
+                    (CallContextFieldTag::CalleeAddress, precompile as u64),
+                    (CallContextFieldTag::CallerId, caller_id as u64),
+                    (CallContextFieldTag::TxId, tx_id as u64),
+                    (CallContextFieldTag::CallDataOffset, call_data_offset),
+                    (CallContextFieldTag::CallDataLength, input.len() as u64),
+                    (CallContextFieldTag::ReturnDataOffset, return_data_offset),
+                    (CallContextFieldTag::ReturnDataLength, return_data_length),
+                    (CallContextFieldTag::IsSuccess, is_success as u64),
+                ]
+                .iter()
+                .enumerate()
+                .map(|(idx, (field_tag, value))| Rw::CallContext {
+                    rw_counter: idx + 1,
+                    is_write: false,
+                    call_id,
+                    field_tag: *field_tag,
+                    value: Word::from(*value),
+                })
+                .collect(),
+            )]
+            .into(),
+        );
+        let mut rw_counter = 9;
+        let mut rw_indices = (0..8)
+            .map(|idx| (RwTableTag::CallContext, idx))
+            .collect::<Vec<_>>();
+
+        // The input of the identity precompile is copied into the memory of
+        // the callee, which holds the output, the input of the other ones is
+        // copied into its RLC, and their output from its RLC into the memory
+        // of the callee
+        let (src_type, src_id) = if is_root {
+            (CopyDataType::TxCalldata, NumberOrHash::Number(tx_id))
+        } else {
+            (CopyDataType::Memory, NumberOrHash::Number(caller_id))
+        };
+        let mut copy_events = Vec::new();
+        if precompile == PrecompileCalls::Identity {
+            if is_success && !input.is_empty() {
+                copy_events.push(make_copy_event(
+                    src_type,
+                    src_id,
+                    &input,
+                    call_data_offset,
+                    call_data_offset,
+                    CopyDataType::Memory,
+                    call_id,
+                    0,
+                    input.len(),
+                    &mut rw_counter,
+                    &mut rws,
+                    &mut rw_indices,
+                ));
+            }
+        } else {
+            let input_length = precompile.input_length(&input);
+            if input_length > 0 {
+                copy_events.push(make_copy_event(
+                    src_type,
+                    src_id,
+                    &input,
+                    call_data_offset,
+                    call_data_offset,
+                    CopyDataType::RlcAcc,
+                    0,
+                    0,
+                    input_length,
+                    &mut rw_counter,
+                    &mut rws,
+                    &mut rw_indices,
+                ));
+            }
+            if !output.is_empty() {
+                copy_events.push(make_copy_event(
+                    CopyDataType::RlcAcc,
+                    NumberOrHash::Number(0),
+                    &output,
+                    0,
+                    0,
+                    CopyDataType::Memory,
+                    call_id,
+                    0,
+                    output.len(),
+                    &mut rw_counter,
+                    &mut rws,
+                    &mut rw_indices,
+                ));
+            }
+        }
+
+        // The output is copied into the memory of the caller as return data,
+        // up to the length requested by the caller
+        let copy_length = output.len().min(return_data_length as usize);
+        if copy_length > 0 {
+            copy_events.push(make_copy_event(
+                CopyDataType::Memory,
+                NumberOrHash::Number(call_id),
+                &output,
+                0,
+                0,
+                CopyDataType::Memory,
+                caller_id,
+                return_data_offset,
+                copy_length,
+                &mut rw_counter,
+                &mut rws,
+                &mut rw_indices,
+            ));
+        }
+
+        // Then the call context of the caller is restored, which resumes with
+        // the gas returned by the precompile
+        if !is_root {
+            let call_context_rws = rws.0.get_mut(&RwTableTag::CallContext).unwrap();
+            for (is_write, id, field_tag, value) in [
+                (
+                    false,
+                    call_id,
+                    CallContextFieldTag::CallerId,
+                    Word::from(caller_id),
+                ),
+                (false, caller_id, CallContextFieldTag::IsRoot, Word::one()),
+                (
+                    false,
+                    caller_id,
+                    CallContextFieldTag::IsCreate,
+                    Word::zero(),
+                ),
+                (
+                    false,
+                    caller_id,
+                    CallContextFieldTag::CodeSource,
+                    caller_bytecode.hash,
+                ),
+                (
+                    false,
+                    caller_id,
+                    CallContextFieldTag::ProgramCounter,
+                    Word::zero(),
+                ),
+                (
+                    false,
+                    caller_id,
+                    CallContextFieldTag::StackPointer,
+                    Word::from(1023),
+                ),
+                (
+                    false,
+                    caller_id,
+                    CallContextFieldTag::GasLeft,
+                    Word::from(caller_gas_left),
+                ),
+                (
+                    false,
+                    caller_id,
+                    CallContextFieldTag::MemorySize,
+                    Word::from(caller_memory_word_size),
+                ),
+                (
+                    false,
+                    caller_id,
+                    CallContextFieldTag::StateWriteCounter,
+                    Word::zero(),
+                ),
+                (
+                    true,
+                    caller_id,
+                    CallContextFieldTag::LastCalleeId,
+                    Word::from(call_id),
+                ),
+                (
+                    true,
+                    caller_id,
+                    CallContextFieldTag::LastCalleeReturnDataOffset,
+                    Word::zero(),
+                ),
+                (
+                    true,
+                    caller_id,
+                    CallContextFieldTag::LastCalleeReturnDataLength,
+                    Word::from(output.len()),
+                ),
+            ] {
+                rw_indices.push((RwTableTag::CallContext, call_context_rws.len()));
+                call_context_rws.push(Rw::CallContext {
+                    rw_counter,
+                    is_write,
+                    call_id: id,
+                    field_tag,
+                    value,
+                });
+                rw_counter += 1;
+            }
+        }
+
+        let mut steps = vec![ExecStep {
+            call_index: if is_root { 0 } else { 1 },
+            rw_indices,
+            execution_state: ExecutionState::Precompile,
+            rw_counter: 1,
+            program_counter: 0,
+            stack_pointer: 1024,
+            gas_left,
+            gas_cost,
+            aux_data: Some(StepAuxiliaryData::Precompile {
+                input: input.clone(),
+                output: output.clone(),
+            }),
+            ..Default::default()
+        }];
+        if !is_root {
+            steps.push(ExecStep {
+                call_index: 0,
+                execution_state: ExecutionState::STOP,
+                rw_counter,
+                program_counter: 0,
+                stack_pointer: 1023,
+                gas_left: caller_gas_left + gas_returned,
+                memory_size: caller_memory_word_size * 32,
+                opcode: Some(OpcodeId::STOP),
+                ..Default::default()
+            });
+        }
+
+        let callee = Call {
+            id: call_id,
+            is_root,
+            is_create: false,
+            caller_id,
+            code_source: CodeSource::Account(precompile_bytecode.hash),
+            ..Default::default()
+        };
+        let calls = if is_root {
+            vec![callee]
+        } else {
+            vec![
+                Call {
+                    id: caller_id,
+                    is_root: true,
+                    is_create: false,
+                    code_source: CodeSource::Account(caller_bytecode.hash),
+                    ..Default::default()
+                },
+                callee,
+            ]
+        };
+        let (call_data, call_data_length) = if is_root {
+            (input.clone(), input.len())
+        } else {
+            (Vec::new(), 0)
+        };
+
+        let ecrecovers = if precompile == PrecompileCalls::ECRecover {
+            vec![EcRecover::new(&input)]
+        } else {
+            Vec::new()
+        };
+        let inputs_of = |callee: PrecompileCalls| {
+            if precompile == callee {
+                vec![input.clone()]
+            } else {
+                Vec::new()
+            }
+        };
+
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                id: tx_id,
+                call_data,
+                call_data_length,
+                calls,
+                steps,
+                ..Default::default()
+            }],
+            rws,
+            bytecodes: vec![caller_bytecode],
+            ecrecovers,
+            sha256_inputs: inputs_of(PrecompileCalls::Sha256),
+            ripemd160_inputs: inputs_of(PrecompileCalls::Ripemd160),
+            ecc_calls,
+            modexp_calls: if precompile == PrecompileCalls::Modexp {
+                ModexpCall::new(&input)
+                    .into_iter()
+                    .filter(|call| !call.modulus.is_zero())
+                    .collect()
+            } else {
+                Vec::new()
+            },
+            blake2f_inputs: if precompile == PrecompileCalls::Blake2F && is_success {
+                blake2f_input.into_iter().collect()
+            } else {
+                Vec::new()
+            },
+            copy_events,
+            ..Default::default()
+        };
+        run_test_circuit_incomplete_fixed_table(block).is_ok()
+    }
+
+    fn test_ok(precompile: PrecompileCalls, call_data_offset: u64, input: Vec<u8>, gas_left: u64) {
+        assert!(run_test(
+            precompile,
+            false,
+            call_data_offset,
+            input,
+            gas_left
+        ));
+    }
+
+    fn test_not_supported(
+        precompile: PrecompileCalls,
+        call_data_offset: u64,
+        input: Vec<u8>,
+        gas_left: u64,
+    ) {
+        assert!(!run_test(
+            precompile,
+            false,
+            call_data_offset,
+            input,
+            gas_left
+        ));
+    }
+
+    fn test_root_ok(precompile: PrecompileCalls, input: Vec<u8>, gas_left: u64) {
+        assert!(run_test(precompile, true, 0, input, gas_left));
+    }
+
+    fn ecrecover_input(words: [Word; 4]) -> Vec<u8> {
+        words
+            .iter()
+            .flat_map(|word| {
+                let mut bytes = [0u8; 32];
+                word.to_big_endian(&mut bytes);
+                bytes.to_vec()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn precompile_identity_simple() {
+        test_ok(PrecompileCalls::Identity, 0x20, rand_bytes(0x20), 0xffff);
+        test_ok(PrecompileCalls::Identity, 0x40, rand_bytes(5), 18);
+    }
+
+    #[test]
+    fn precompile_identity_multi_step() {
+        test_ok(PrecompileCalls::Identity, 0x00, rand_bytes(100), 0xffff);
+    }
+
+    #[test]
+    fn precompile_identity_empty_input() {
+        test_ok(PrecompileCalls::Identity, 0x00, vec![], 15);
+    }
+
+    #[test]
+    fn precompile_identity_root() {
+        test_root_ok(PrecompileCalls::Identity, rand_bytes(0x40), 0xffff);
+        test_root_ok(PrecompileCalls::Identity, rand_bytes(0x40), 20);
+    }
+
+    #[test]
+    fn precompile_identity_out_of_gas() {
+        test_ok(PrecompileCalls::Identity, 0x20, rand_bytes(0x20), 17);
+        test_ok(PrecompileCalls::Identity, 0x00, vec![], 14);
+    }
+
+    // ECRecover isn't supported until the ECDSA circuit proves the
+    // recovery, so even a valid witness of a call to it is rejected
+    #[test]
+    fn precompile_ec_recover() {
+        let input = ecrecover_input([
+            word!("18c547e4f7b0f325ad1e56f57e26c745b09a3e503d86e00e5255ff7f715d3d1c"),
+            word!("1c"),
+            word!("73b1693892219d736caba55bdb67216e485557ea6b6af75f37096c9aa6a5a75f"),
+            word!("eeb940b1d03b21e36b0e47e79769f095fe2ab855bd91e3a38756b7d75a9c4549"),
+        ]);
+        test_not_supported(PrecompileCalls::ECRecover, 0x00, input.clone(), 3000);
+        // Short input, which is right padded with zeros
+        test_not_supported(
+            PrecompileCalls::ECRecover,
+            0x20,
+            input[..100].to_vec(),
+            0xffff,
+        );
+        // Out of gas
+        test_not_supported(PrecompileCalls::ECRecover, 0x00, input, 2999);
+    }
+
+    #[test]
+    fn precompile_ec_recover_invalid_signature() {
+        let input = ecrecover_input([Word::one(), Word::from(27), Word::zero(), Word::one()]);
+        test_not_supported(PrecompileCalls::ECRecover, 0x00, input, 3000);
+    }
+
+    #[test]
+    fn precompile_sha256() {
+        test_ok(PrecompileCalls::Sha256, 0x00, b"abc".to_vec(), 0xffff);
+        test_ok(PrecompileCalls::Sha256, 0x20, vec![], 60);
+        test_ok(PrecompileCalls::Sha256, 0x20, rand_bytes(100), 108);
+        // Out of gas
+        test_ok(PrecompileCalls::Sha256, 0x00, rand_bytes(33), 83);
+    }
+
+    #[test]
+    fn precompile_sha256_root() {
+        test_root_ok(PrecompileCalls::Sha256, b"abc".to_vec(), 0xffff);
+    }
+
+    #[test]
+    fn precompile_ripemd160() {
+        test_ok(PrecompileCalls::Ripemd160, 0x00, b"abc".to_vec(), 0xffff);
+        test_ok(PrecompileCalls::Ripemd160, 0x20, vec![], 600);
+        test_ok(PrecompileCalls::Ripemd160, 0x20, rand_bytes(100), 1080);
+        // Out of gas
+        test_ok(PrecompileCalls::Ripemd160, 0x00, rand_bytes(33), 839);
+    }
+
+    fn words_to_bytes(words: &[Word]) -> Vec<u8> {
+        words
+            .iter()
+            .flat_map(|word| {
+                let mut bytes = [0u8; 32];
+                word.to_big_endian(&mut bytes);
+                bytes.to_vec()
+            })
+            .collect()
+    }
+
+    fn bn128_neg_g1() -> [Word; 2] {
+        [
+            Word::one(),
+            word!("30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45"),
+        ]
+    }
+
+    fn bn128_pairing_input(g1s: &[[Word; 2]]) -> Vec<u8> {
+        let g2 = [
+            word!("198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2"),
+            word!("1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed"),
+            word!("090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b"),
+            word!("12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa"),
+        ];
+        words_to_bytes(
+            &g1s.iter()
+                .flat_map(|g1| [g1.to_vec(), g2.to_vec()].concat())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    // The BN254 precompiles aren't supported until the ECC circuit proves
+    // the curve arithmetic, so even a valid witness of a call is rejected
+    #[test]
+    fn precompile_bn128_add() {
+        let input = words_to_bytes(&[Word::one(), Word::from(2), Word::one(), Word::from(2)]);
+        test_not_supported(PrecompileCalls::Bn128Add, 0x00, input.clone(), 150);
+        // Short input, which is right padded with zeros
+        test_not_supported(
+            PrecompileCalls::Bn128Add,
+            0x20,
+            input[..64].to_vec(),
+            0xffff,
+        );
+        // Out of gas
+        test_not_supported(PrecompileCalls::Bn128Add, 0x00, input, 149);
+        // Point not on the curve
+        test_not_supported(
+            PrecompileCalls::Bn128Add,
+            0x00,
+            words_to_bytes(&[Word::one(), Word::from(3)]),
+            0xffff,
+        );
+    }
+
+    #[test]
+    fn precompile_bn128_mul() {
+        let input = words_to_bytes(&[Word::one(), Word::from(2), Word::MAX]);
+        test_not_supported(PrecompileCalls::Bn128Mul, 0x00, input.clone(), 6000);
+        // Out of gas
+        test_not_supported(PrecompileCalls::Bn128Mul, 0x00, input, 5999);
+        // Coordinate not less than the modulus of the field
+        test_not_supported(
+            PrecompileCalls::Bn128Mul,
+            0x00,
+            words_to_bytes(&[Word::MAX, Word::from(2), Word::one()]),
+            0xffff,
+        );
+    }
+
+    #[test]
+    fn precompile_bn128_pairing() {
+        let g1 = [Word::one(), Word::from(2)];
+        test_not_supported(PrecompileCalls::Bn128Pairing, 0x00, vec![], 45000);
+        test_not_supported(
+            PrecompileCalls::Bn128Pairing,
+            0x00,
+            bn128_pairing_input(&[g1]),
+            0xfffff,
+        );
+        test_not_supported(
+            PrecompileCalls::Bn128Pairing,
+            0x20,
+            bn128_pairing_input(&[g1, bn128_neg_g1()]),
+            113000,
+        );
+        // Out of gas
+        test_not_supported(
+            PrecompileCalls::Bn128Pairing,
+            0x00,
+            bn128_pairing_input(&[g1, bn128_neg_g1()]),
+            112999,
+        );
+    }
+
+    #[test]
+    fn precompile_bn128_pairing_invalid_input() {
+        // Length not a multiple of 192
+        test_not_supported(
+            PrecompileCalls::Bn128Pairing,
+            0x00,
+            rand_bytes(200),
+            0xfffff,
+        );
+        // Point not on the curve
+        test_not_supported(
+            PrecompileCalls::Bn128Pairing,
+            0x00,
+            bn128_pairing_input(&[[Word::one(), Word::from(3)]]),
+            0xfffff,
+        );
+    }
+
+    fn modexp_input(base: &[u8], exp: &[u8], modulus: &[u8]) -> Vec<u8> {
+        let lengths = [base, exp, modulus].map(|bytes| Word::from(bytes.len()));
+        [
+            words_to_bytes(&lengths),
+            base.to_vec(),
+            exp.to_vec(),
+            modulus.to_vec(),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn precompile_modexp() {
+        let input = modexp_input(&[2], &[10], &[0x03, 0xe8]);
+        test_ok(PrecompileCalls::Modexp, 0x00, input.clone(), 200);
+        // Out of gas
+        test_ok(PrecompileCalls::Modexp, 0x00, input, 199);
+        // Fermat's little theorem on the modulus of secp256k1 (EIP-198)
+        let mut modulus = [0xff; 32];
+        modulus[27..].copy_from_slice(&[0xfe, 0xff, 0xff, 0xfc, 0x2f]);
+        let mut exp = modulus;
+        exp[31] -= 1;
+        let input = modexp_input(&[3], &exp, &modulus);
+        test_ok(PrecompileCalls::Modexp, 0x20, input.clone(), 1360);
+        test_ok(PrecompileCalls::Modexp, 0x20, input, 1359);
+    }
+
+    #[test]
+    fn precompile_modexp_edge_cases() {
+        // Zero modulus
+        test_ok(
+            PrecompileCalls::Modexp,
+            0x00,
+            modexp_input(&[2], &[3], &[]),
+            0xffff,
+        );
+        // Zero exponent
+        test_ok(
+            PrecompileCalls::Modexp,
+            0x00,
+            modexp_input(&rand_bytes(32), &[], &rand_bytes(32)),
+            0xffff,
+        );
+        // Short input, which is right padded with zeros
+        test_ok(PrecompileCalls::Modexp, 0x00, vec![], 0xffff);
+    }
+
+    #[test]
+    fn precompile_modexp_zero_modulus() {
+        // The result takes as many zero bytes as the modulus, and isn't
+        // looked up in the MODEXP table
+        let input = modexp_input(&[2], &[3], &[0, 0]);
+        test_ok(PrecompileCalls::Modexp, 0x00, input.clone(), 0xffff);
+        test_root_ok(PrecompileCalls::Modexp, input, 0xffff);
+        test_ok(
+            PrecompileCalls::Modexp,
+            0x00,
+            modexp_input(&rand_bytes(32), &rand_bytes(32), &[0; 32]),
+            0xffff,
+        );
+    }
+
+    fn blake2f_input(rounds: u32, f: u8) -> Vec<u8> {
+        let mut input = rand_bytes(213);
+        input[..4].copy_from_slice(&rounds.to_be_bytes());
+        input[212] = f;
+        input
+    }
+
+    #[test]
+    fn precompile_blake2f() {
+        test_ok(PrecompileCalls::Blake2F, 0x00, blake2f_input(12, 1), 12);
+        test_ok(PrecompileCalls::Blake2F, 0x20, blake2f_input(0, 0), 0);
+        // Out of gas
+        test_ok(PrecompileCalls::Blake2F, 0x00, blake2f_input(12, 0), 11);
+    }
+
+    #[test]
+    fn precompile_blake2f_invalid_input() {
+        // Invalid length
+        test_ok(PrecompileCalls::Blake2F, 0x00, rand_bytes(212), 0xffff);
+        // Invalid final block indicator flag
+        test_ok(PrecompileCalls::Blake2F, 0x00, blake2f_input(12, 2), 0xffff);
+    }
+}
@@ -0,0 +1,247 @@
+use super::{PrecompileCallGadget, PrecompileContext};
+use crate::{
+    evm_circuit::{
+        util::{
+            constraint_builder::ConstraintBuilder,
+            from_bytes,
+            math_gadget::{IsEqualGadget, LtGadget, RangeCheckGadget},
+            Cell,
+        },
+        witness::{Block, ExecStep, StepAuxiliaryData},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::GasCost, Field};
+use halo2_proofs::{
+    circuit::Region,
+    plonk::{Error, Expression},
+};
+use std::convert::TryInto;
+
+/// The length of the input of the BLAKE2F precompile, which fails with any
+/// other length.
+const INPUT_LENGTH: u64 = 213;
+
+/// The length of the output of the BLAKE2F precompile, which is the state
+/// vector of 8 words in little-endian.
+const OUTPUT_LENGTH: usize = 64;
+
+/// Returns the RLC of at most 32 `values`, where the first value has the
+/// highest power of the randomness.
+fn rlc<F: Field>(values: &[Expression<F>], power_of_randomness: &[Expression<F>]) -> Expression<F> {
+    debug_assert!(values.len() <= power_of_randomness.len() + 1);
+    values
+        .iter()
+        .rev()
+        .zip(std::iter::once(1.expr()).chain(power_of_randomness.iter().cloned()))
+        .fold(0.expr(), |acc, (value, power)| acc + value.clone() * power)
+}
+
+/// Gadget for the BLAKE2F precompile, which looks up the output of the
+/// compression in the BLAKE2F table built by the BLAKE2F circuit.  The input
+/// is the number of rounds as 4 bytes in big-endian, followed by the words
+/// `h`, `m` and `t` in little-endian and the flag `f` as a byte, and the
+/// table holds the RLCs of these words rather than of the bytes.
+#[derive(Clone, Debug)]
+pub(crate) struct Blake2fGadget<F> {
+    is_valid_length: IsEqualGadget<F>,
+    rounds: Cell<F>,
+    rounds_range_check: RangeCheckGadget<F, 4>,
+    // The final block indicator flag, which must be 0 or 1
+    f: Cell<F>,
+    f_lt: LtGadget<F, 1>,
+    input: [Cell<F>; INPUT_LENGTH as usize],
+    // `input_rlc_chunks[k]` is the RLC of the first `32 ⋅ (k + 1)` bytes of
+    // the input
+    input_rlc_chunks: [Cell<F>; 6],
+    output: [Cell<F>; OUTPUT_LENGTH],
+    input_rlc: Expression<F>,
+    output_rlc: Expression<F>,
+}
+
+impl<F: Field> PrecompileCallGadget<F> for Blake2fGadget<F> {
+    fn configure(
+        cb: &mut ConstraintBuilder<F>,
+        ctx: &PrecompileContext<F>,
+        is_selected: Expression<F>,
+    ) -> Self {
+        let power_of_randomness = cb.power_of_randomness().to_vec();
+        let is_valid_length =
+            IsEqualGadget::construct(cb, ctx.call_data_length.expr(), INPUT_LENGTH.expr());
+        let rounds = cb.query_cell();
+        let rounds_range_check = RangeCheckGadget::construct(cb, rounds.expr());
+        let f = cb.query_byte();
+        let f_lt = LtGadget::construct(cb, f.expr(), 2.expr());
+        let input: [Cell<F>; INPUT_LENGTH as usize] = cb.query_bytes();
+        let input_rlc_chunks = [(); 6].map(|_| cb.query_cell());
+        let output: [Cell<F>; OUTPUT_LENGTH] = cb.query_bytes();
+
+        cb.condition(is_selected.clone(), |cb| {
+            // An input of invalid length costs nothing
+            cb.require_zero(
+                "rounds == 0 when the length is invalid",
+                (1.expr() - is_valid_length.expr()) * rounds.expr(),
+            );
+            // Unlike most precompiles, an invalid input makes the call fail
+            cb.require_equal(
+                "is_success == is_valid_length ⋅ (f < 2) ⋅ (1 - insufficient_gas)",
+                ctx.is_success.expr(),
+                is_valid_length.expr() * f_lt.expr() * (1.expr() - ctx.insufficient_gas.expr()),
+            );
+        });
+
+        // The rounds and the flag are read from the input when its length is
+        // valid, otherwise it isn't read
+        let mut rounds_bytes = input[..4]
+            .iter()
+            .map(|byte| byte.expr())
+            .collect::<Vec<_>>();
+        rounds_bytes.reverse();
+        cb.condition(is_valid_length.expr(), |cb| {
+            cb.require_equal(
+                "rounds == first 4 bytes of the input in big-endian",
+                rounds.expr(),
+                from_bytes::expr(&rounds_bytes),
+            );
+            cb.require_equal(
+                "f == last byte of the input",
+                f.expr(),
+                input[INPUT_LENGTH as usize - 1].expr(),
+            );
+        });
+
+        // The RLCs of the input and the output are accumulated by chunks of
+        // 32 bytes, whose powers of the randomness are available, and the
+        // last chunk of the input takes the remaining 21 bytes
+        let r_32 = power_of_randomness[30].clone() * power_of_randomness[0].clone();
+        let chunk_rlc = |chunk: &[Cell<F>]| {
+            rlc(
+                &chunk.iter().map(|byte| byte.expr()).collect::<Vec<_>>(),
+                &power_of_randomness,
+            )
+        };
+        let mut input_rlc = 0.expr();
+        for (chunk, cell) in input.chunks(32).zip(input_rlc_chunks.iter()) {
+            cb.require_equal(
+                "input_rlc_chunks[k] == input_rlc_chunks[k - 1] ⋅ r^32 + RLC of chunk k",
+                cell.expr(),
+                input_rlc * r_32.clone() + chunk_rlc(chunk),
+            );
+            input_rlc = cell.expr();
+        }
+        let input_rlc = input_rlc * power_of_randomness[20].clone() + chunk_rlc(&input[192..]);
+        let output_rlc = chunk_rlc(&output[..32]) * r_32 + chunk_rlc(&output[32..]);
+
+        // The table holds the RLCs of the words of the input and the output
+        let words = |bytes: &[Cell<F>]| {
+            bytes
+                .chunks(8)
+                .map(|word| from_bytes::expr(word))
+                .collect::<Vec<_>>()
+        };
+        let input_words = std::iter::once(rounds.expr())
+            .chain(words(&input[4..INPUT_LENGTH as usize - 1]))
+            .chain(std::iter::once(f.expr()))
+            .collect::<Vec<_>>();
+        cb.condition(is_selected * ctx.is_success.expr(), |cb| {
+            cb.blake2f_lookup(
+                rounds.expr(),
+                rlc(&input_words, &power_of_randomness),
+                rlc(&words(&output), &power_of_randomness),
+            );
+        });
+
+        Self {
+            is_valid_length,
+            rounds,
+            rounds_range_check,
+            f,
+            f_lt,
+            input,
+            input_rlc_chunks,
+            output,
+            input_rlc,
+            output_rlc,
+        }
+    }
+
+    fn gas_cost(&self) -> Expression<F> {
+        GasCost::PRECOMPILE_BLAKE2F_PER_ROUND.expr() * self.rounds.expr()
+    }
+
+    fn output_length(&self, _: &PrecompileContext<F>) -> Expression<F> {
+        OUTPUT_LENGTH.expr()
+    }
+
+    fn input_length(&self, _: &PrecompileContext<F>) -> Expression<F> {
+        self.is_valid_length.expr() * INPUT_LENGTH.expr()
+    }
+
+    fn input_rlc(&self) -> Expression<F> {
+        self.input_rlc.clone()
+    }
+
+    fn output_rlc(&self) -> Expression<F> {
+        self.output_rlc.clone()
+    }
+
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        step: &ExecStep,
+        call_data_length: u64,
+        is_selected: bool,
+    ) -> Result<(), Error> {
+        self.is_valid_length.assign(
+            region,
+            offset,
+            F::from(call_data_length),
+            F::from(INPUT_LENGTH),
+        )?;
+
+        let (input, output): (&[u8], &[u8]) = match (is_selected, step.aux_data.as_ref()) {
+            (true, Some(StepAuxiliaryData::Precompile { input, output }))
+                if input.len() as u64 == INPUT_LENGTH =>
+            {
+                (input, output)
+            }
+            _ => (&[], &[]),
+        };
+        // The rounds and the flag are read even when the flag is invalid
+        let (rounds, f) = if input.is_empty() {
+            (0, 0)
+        } else {
+            (
+                u32::from_be_bytes(input[..4].try_into().unwrap()),
+                input[INPUT_LENGTH as usize - 1],
+            )
+        };
+        self.rounds
+            .assign(region, offset, Some(F::from(rounds as u64)))?;
+        self.rounds_range_check
+            .assign(region, offset, F::from(rounds as u64))?;
+        self.f.assign(region, offset, Some(F::from(f as u64)))?;
+        self.f_lt
+            .assign(region, offset, F::from(f as u64), F::from(2))?;
+
+        // The output is empty when the call fails
+        for (cells, bytes) in [(&self.input[..], input), (&self.output[..], output)] {
+            for (idx, cell) in cells.iter().enumerate() {
+                let byte = bytes.get(idx).copied().unwrap_or_default();
+                cell.assign(region, offset, Some(F::from(byte as u64)))?;
+            }
+        }
+        let mut input_rlc = F::zero();
+        for (idx, cell) in self.input_rlc_chunks.iter().enumerate() {
+            for byte_idx in 32 * idx..32 * (idx + 1) {
+                let byte = input.get(byte_idx).copied().unwrap_or_default();
+                input_rlc = input_rlc * block.randomness + F::from(byte as u64);
+            }
+            cell.assign(region, offset, Some(input_rlc))?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,294 @@
+use super::{PrecompileCallGadget, PrecompileContext};
+use crate::{
+    evm_circuit::{
+        param::N_BYTES_MEMORY_WORD_SIZE,
+        util::{
+            constraint_builder::ConstraintBuilder,
+            math_gadget::{LtGadget, RangeCheckGadget},
+            Cell,
+        },
+        witness::{Block, EccCall, ExecStep, StepAuxiliaryData},
+    },
+    util::Expr,
+};
+use bus_mapping::precompile::PrecompileCalls;
+use eth_types::{evm_types::GasCost, Field};
+use halo2_proofs::{
+    circuit::Region,
+    plonk::{Error, Expression},
+};
+
+/// Lookup of a call to the ecAdd, ecMul or ecPairing precompile in the ECC
+/// table, which is shared by their gadgets.  The ECC circuit only checks the
+/// shape of the rows of the table, and doesn't prove the curve arithmetic
+/// or the validity of the points yet, so their gadgets are never selected by
+/// [`super::PrecompileGadget`].
+#[derive(Clone, Debug)]
+struct EccLookupGadget<F> {
+    precompile: PrecompileCalls,
+    input_rlc: Cell<F>,
+    is_valid: Cell<F>,
+    output_rlc: Cell<F>,
+}
+
+impl<F: Field> EccLookupGadget<F> {
+    fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        ctx: &PrecompileContext<F>,
+        is_selected: Expression<F>,
+        precompile: PrecompileCalls,
+    ) -> Self {
+        let [input_rlc, output_rlc] = [(); 2].map(|_| cb.query_cell());
+        let is_valid = cb.query_bool();
+
+        // Unlike the other precompiles, an invalid input makes the call fail
+        cb.condition(is_selected.clone(), |cb| {
+            cb.require_equal(
+                "is_success == is_valid ⋅ (1 - insufficient_gas)",
+                ctx.is_success.expr(),
+                is_valid.expr() * (1.expr() - ctx.insufficient_gas.expr()),
+            );
+        });
+
+        cb.condition(
+            is_selected * (1.expr() - ctx.insufficient_gas.expr()),
+            |cb| {
+                cb.ecc_lookup(
+                    (precompile as u64).expr(),
+                    input_rlc.expr(),
+                    ctx.call_data_length.expr(),
+                    is_valid.expr(),
+                    output_rlc.expr(),
+                );
+            },
+        );
+
+        Self {
+            precompile,
+            input_rlc,
+            is_valid,
+            output_rlc,
+        }
+    }
+
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        step: &ExecStep,
+        is_selected: bool,
+    ) -> Result<(), Error> {
+        let [_, input_rlc, _, is_valid, output_rlc] = match (is_selected, step.aux_data.as_ref()) {
+            (true, Some(StepAuxiliaryData::Precompile { input, .. })) => {
+                EccCall::new(self.precompile, input).table_assignment(block.randomness)
+            }
+            _ => [F::zero(); 5],
+        };
+        self.input_rlc.assign(region, offset, Some(input_rlc))?;
+        self.is_valid.assign(region, offset, Some(is_valid))?;
+        self.output_rlc.assign(region, offset, Some(output_rlc))?;
+
+        Ok(())
+    }
+}
+
+/// Gadget for the ecAdd precompile, which adds two points of BN254.
+#[derive(Clone, Debug)]
+pub(crate) struct Bn128AddGadget<F> {
+    ecc_lookup: EccLookupGadget<F>,
+}
+
+impl<F: Field> PrecompileCallGadget<F> for Bn128AddGadget<F> {
+    fn configure(
+        cb: &mut ConstraintBuilder<F>,
+        ctx: &PrecompileContext<F>,
+        is_selected: Expression<F>,
+    ) -> Self {
+        Self {
+            ecc_lookup: EccLookupGadget::construct(cb, ctx, is_selected, PrecompileCalls::Bn128Add),
+        }
+    }
+
+    fn gas_cost(&self) -> Expression<F> {
+        GasCost::PRECOMPILE_BN256_ADD.expr()
+    }
+
+    fn output_length(&self, _: &PrecompileContext<F>) -> Expression<F> {
+        64.expr()
+    }
+
+    fn input_length(&self, ctx: &PrecompileContext<F>) -> Expression<F> {
+        ctx.call_data_length.expr()
+    }
+
+    fn input_rlc(&self) -> Expression<F> {
+        self.ecc_lookup.input_rlc.expr()
+    }
+
+    fn output_rlc(&self) -> Expression<F> {
+        self.ecc_lookup.output_rlc.expr()
+    }
+
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        step: &ExecStep,
+        _: u64,
+        is_selected: bool,
+    ) -> Result<(), Error> {
+        self.ecc_lookup
+            .assign(region, offset, block, step, is_selected)
+    }
+}
+
+/// Gadget for the ecMul precompile, which multiplies a point of BN254 by a
+/// scalar.
+#[derive(Clone, Debug)]
+pub(crate) struct Bn128MulGadget<F> {
+    ecc_lookup: EccLookupGadget<F>,
+}
+
+impl<F: Field> PrecompileCallGadget<F> for Bn128MulGadget<F> {
+    fn configure(
+        cb: &mut ConstraintBuilder<F>,
+        ctx: &PrecompileContext<F>,
+        is_selected: Expression<F>,
+    ) -> Self {
+        Self {
+            ecc_lookup: EccLookupGadget::construct(cb, ctx, is_selected, PrecompileCalls::Bn128Mul),
+        }
+    }
+
+    fn gas_cost(&self) -> Expression<F> {
+        GasCost::PRECOMPILE_BN256_MUL.expr()
+    }
+
+    fn output_length(&self, _: &PrecompileContext<F>) -> Expression<F> {
+        64.expr()
+    }
+
+    fn input_length(&self, ctx: &PrecompileContext<F>) -> Expression<F> {
+        ctx.call_data_length.expr()
+    }
+
+    fn input_rlc(&self) -> Expression<F> {
+        self.ecc_lookup.input_rlc.expr()
+    }
+
+    fn output_rlc(&self) -> Expression<F> {
+        self.ecc_lookup.output_rlc.expr()
+    }
+
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        step: &ExecStep,
+        _: u64,
+        is_selected: bool,
+    ) -> Result<(), Error> {
+        self.ecc_lookup
+            .assign(region, offset, block, step, is_selected)
+    }
+}
+
+/// Gadget for the ecPairing precompile, which checks whether the product of
+/// the pairings of pairs of points of BN254 is 1.
+#[derive(Clone, Debug)]
+pub(crate) struct Bn128PairingGadget<F> {
+    ecc_lookup: EccLookupGadget<F>,
+    // `call_data_length == 192 ⋅ num_pairs + remainder`
+    num_pairs: Cell<F>,
+    remainder: Cell<F>,
+    num_pairs_range_check: RangeCheckGadget<F, N_BYTES_MEMORY_WORD_SIZE>,
+    remainder_lt: LtGadget<F, 1>,
+}
+
+impl<F: Field> PrecompileCallGadget<F> for Bn128PairingGadget<F> {
+    fn configure(
+        cb: &mut ConstraintBuilder<F>,
+        ctx: &PrecompileContext<F>,
+        is_selected: Expression<F>,
+    ) -> Self {
+        let ecc_lookup =
+            EccLookupGadget::construct(cb, ctx, is_selected.clone(), PrecompileCalls::Bn128Pairing);
+
+        // The fixed table has no range of 192, so the remainder is checked to
+        // be a byte less than 192
+        let num_pairs = cb.query_cell();
+        let remainder = cb.query_byte();
+        let num_pairs_range_check = RangeCheckGadget::construct(cb, num_pairs.expr());
+        let remainder_lt = LtGadget::construct(cb, remainder.expr(), 192.expr());
+        cb.require_equal("remainder < 192", remainder_lt.expr(), 1.expr());
+        cb.require_equal(
+            "call_data_length == 192 ⋅ num_pairs + remainder",
+            ctx.call_data_length.expr(),
+            num_pairs.expr() * 192.expr() + remainder.expr(),
+        );
+
+        // The input is a list of pairs of points taking 192 bytes each
+        cb.condition(is_selected, |cb| {
+            cb.require_zero(
+                "is_valid == 0 when call_data_length isn't a multiple of 192",
+                ecc_lookup.is_valid.expr() * remainder.expr(),
+            );
+        });
+
+        Self {
+            ecc_lookup,
+            num_pairs,
+            remainder,
+            num_pairs_range_check,
+            remainder_lt,
+        }
+    }
+
+    fn gas_cost(&self) -> Expression<F> {
+        GasCost::PRECOMPILE_BN256_PAIRING_BASE.expr()
+            + GasCost::PRECOMPILE_BN256_PAIRING_PER_PAIR.expr() * self.num_pairs.expr()
+    }
+
+    fn output_length(&self, _: &PrecompileContext<F>) -> Expression<F> {
+        32.expr()
+    }
+
+    fn input_length(&self, ctx: &PrecompileContext<F>) -> Expression<F> {
+        ctx.call_data_length.expr()
+    }
+
+    fn input_rlc(&self) -> Expression<F> {
+        self.ecc_lookup.input_rlc.expr()
+    }
+
+    fn output_rlc(&self) -> Expression<F> {
+        self.ecc_lookup.output_rlc.expr()
+    }
+
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        step: &ExecStep,
+        call_data_length: u64,
+        is_selected: bool,
+    ) -> Result<(), Error> {
+        self.ecc_lookup
+            .assign(region, offset, block, step, is_selected)?;
+
+        let num_pairs = F::from(call_data_length / 192);
+        let remainder = F::from(call_data_length % 192);
+        self.num_pairs.assign(region, offset, Some(num_pairs))?;
+        self.remainder.assign(region, offset, Some(remainder))?;
+        self.num_pairs_range_check
+            .assign(region, offset, num_pairs)?;
+        self.remainder_lt
+            .assign(region, offset, remainder, F::from(192))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use super::{PrecompileCallGadget, PrecompileContext};
+use crate::{
+    evm_circuit::{
+        util::{constraint_builder::ConstraintBuilder, from_bytes, Cell, RandomLinearCombination},
+        witness::{Block, EcRecover, ExecStep, StepAuxiliaryData},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::GasCost, Field};
+use halo2_proofs::{
+    circuit::Region,
+    plonk::{Error, Expression},
+};
+
+/// Gadget for the ecRecover precompile, which looks up the signer recovered
+/// from the signature in the ecRecover table.  The ECDSA circuit only checks
+/// the shape of the rows of the table, and doesn't prove the recovery yet, so
+/// the gadget is never selected by [`super::PrecompileGadget`].  The
+/// input is the hash, `v`, `r` and `s` as 32 bytes words, and the output is
+/// the recovered address left padded to 32 bytes when the signature is
+/// valid, or empty otherwise.
+#[derive(Clone, Debug)]
+pub(crate) struct EcRecoverGadget<F> {
+    msg_hash: Cell<F>,
+    v: Cell<F>,
+    r: Cell<F>,
+    s: Cell<F>,
+    input_rlc: Cell<F>,
+    is_valid: Cell<F>,
+    // The bytes of the recovered address in little-endian, which are 0 when
+    // the signature is invalid
+    recovered_address: RandomLinearCombination<F, 20>,
+}
+
+impl<F: Field> PrecompileCallGadget<F> for EcRecoverGadget<F> {
+    fn configure(
+        cb: &mut ConstraintBuilder<F>,
+        ctx: &PrecompileContext<F>,
+        is_selected: Expression<F>,
+    ) -> Self {
+        let [msg_hash, v, r, s, input_rlc] = [(); 5].map(|_| cb.query_cell());
+        let is_valid = cb.query_bool();
+        let recovered_address = cb.query_rlc();
+
+        // The RLC of a word is the one of its 32 bytes in big-endian, so the
+        // words are chained with the 32nd power of the randomness
+        let r_32 = cb.power_of_randomness()[30].clone() * cb.power_of_randomness()[0].clone();
+        cb.require_equal(
+            "input_rlc == RLC of msg_hash, v, r and s",
+            input_rlc.expr(),
+            [&v, &r, &s].iter().fold(msg_hash.expr(), |acc, word| {
+                acc * r_32.clone() + word.expr()
+            }),
+        );
+
+        // An invalid signature doesn't make the call fail, it only returns no
+        // data
+        cb.condition(is_selected.clone(), |cb| {
+            cb.require_equal(
+                "is_success == 1 - insufficient_gas",
+                ctx.is_success.expr(),
+                1.expr() - ctx.insufficient_gas.expr(),
+            );
+        });
+
+        cb.condition(is_selected * ctx.is_success.expr(), |cb| {
+            cb.ecrecover_lookup(
+                msg_hash.expr(),
+                v.expr(),
+                r.expr(),
+                s.expr(),
+                is_valid.expr(),
+                from_bytes::expr(&recovered_address.cells),
+            );
+        });
+
+        Self {
+            msg_hash,
+            v,
+            r,
+            s,
+            input_rlc,
+            is_valid,
+            recovered_address,
+        }
+    }
+
+    fn gas_cost(&self) -> Expression<F> {
+        GasCost::PRECOMPILE_EC_RECOVER.expr()
+    }
+
+    fn output_length(&self, _: &PrecompileContext<F>) -> Expression<F> {
+        self.is_valid.expr() * 32.expr()
+    }
+
+    fn input_length(&self, _: &PrecompileContext<F>) -> Expression<F> {
+        128.expr()
+    }
+
+    fn input_rlc(&self) -> Expression<F> {
+        self.input_rlc.expr()
+    }
+
+    fn output_rlc(&self) -> Expression<F> {
+        // The zeros left padding the address don't change its RLC
+        self.recovered_address.expr()
+    }
+
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        step: &ExecStep,
+        _: u64,
+        is_selected: bool,
+    ) -> Result<(), Error> {
+        let ecrecover = match (is_selected, step.aux_data.as_ref()) {
+            (true, Some(StepAuxiliaryData::Precompile { input, .. })) => EcRecover::new(input),
+            _ => EcRecover::default(),
+        };
+
+        let [msg_hash, v, r, s, is_valid, _] = ecrecover.table_assignment(block.randomness);
+        for (cell, value) in [
+            (&self.msg_hash, msg_hash),
+            (&self.v, v),
+            (&self.r, r),
+            (&self.s, s),
+            (&self.is_valid, is_valid),
+        ] {
+            cell.assign(region, offset, Some(value))?;
+        }
+        let r_32 = block.randomness.pow(&[32, 0, 0, 0]);
+        self.input_rlc.assign(
+            region,
+            offset,
+            Some(
+                [v, r, s]
+                    .iter()
+                    .fold(msg_hash, |acc, word| acc * r_32 + word),
+            ),
+        )?;
+
+        let mut recovered_address = ecrecover.recovered_address.unwrap_or_default().0;
+        recovered_address.reverse();
+        self.recovered_address
+            .assign(region, offset, Some(recovered_address))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use super::{PrecompileCallGadget, PrecompileContext};
+use crate::{
+    evm_circuit::{
+        param::N_BYTES_MEMORY_WORD_SIZE,
+        util::{
+            constraint_builder::ConstraintBuilder, math_gadget::IsZeroGadget,
+            memory_gadget::MemoryWordSizeGadget,
+        },
+        witness::{Block, ExecStep},
+    },
+    util::Expr,
+};
+use bus_mapping::circuit_input_builder::CopyDataType;
+use eth_types::{evm_types::GasCost, Field};
+use halo2_proofs::{
+    circuit::Region,
+    plonk::{Error, Expression},
+};
+
+/// Gadget for the identity precompile, which copies its input from the memory
+/// of the caller, or from the calldata of the transaction for a root call,
+/// into the memory of the callee with a lookup to the copy table, and returns
+/// it from there.
+#[derive(Clone, Debug)]
+pub(crate) struct IdentityGadget<F> {
+    input_word_size: MemoryWordSizeGadget<F, N_BYTES_MEMORY_WORD_SIZE>,
+    input_length_is_zero: IsZeroGadget<F>,
+}
+
+impl<F: Field> PrecompileCallGadget<F> for IdentityGadget<F> {
+    fn configure(
+        cb: &mut ConstraintBuilder<F>,
+        ctx: &PrecompileContext<F>,
+        is_selected: Expression<F>,
+    ) -> Self {
+        let input_word_size = MemoryWordSizeGadget::construct(cb, ctx.call_data_length.expr());
+        let input_length_is_zero = IsZeroGadget::construct(cb, ctx.call_data_length.expr());
+
+        // The identity precompile only fails when it runs out of gas
+        cb.condition(is_selected.clone(), |cb| {
+            cb.require_equal(
+                "is_success == 1 - insufficient_gas",
+                ctx.is_success.expr(),
+                1.expr() - ctx.insufficient_gas.expr(),
+            );
+        });
+
+        // Copy the input into the memory of the callee, where every byte is
+        // written, after being read when it's in the memory of the caller
+        let is_root = cb.curr.state.is_root.expr();
+        let (src_id, src_type) = ctx.input_source(is_root.clone());
+        cb.condition(
+            is_selected * ctx.is_success.expr() * (1.expr() - input_length_is_zero.expr()),
+            |cb| {
+                cb.copy_table_lookup(
+                    src_id,
+                    src_type,
+                    cb.curr.state.call_id.expr(),
+                    CopyDataType::Memory.expr(),
+                    ctx.call_data_offset.expr(),
+                    ctx.call_data_offset.expr() + ctx.call_data_length.expr(),
+                    0.expr(),
+                    ctx.call_data_length.expr(),
+                    (2.expr() - is_root) * ctx.call_data_length.expr(),
+                    0.expr(),
+                );
+            },
+        );
+
+        Self {
+            input_word_size,
+            input_length_is_zero,
+        }
+    }
+
+    fn gas_cost(&self) -> Expression<F> {
+        GasCost::PRECOMPILE_IDENTITY_BASE.expr()
+            + GasCost::PRECOMPILE_IDENTITY_PER_WORD.expr() * self.input_word_size.expr()
+    }
+
+    fn output_length(&self, ctx: &PrecompileContext<F>) -> Expression<F> {
+        ctx.call_data_length.expr()
+    }
+
+    fn input_length(&self, _: &PrecompileContext<F>) -> Expression<F> {
+        // The input is copied into the memory of the callee without its RLC
+        0.expr()
+    }
+
+    fn input_rlc(&self) -> Expression<F> {
+        0.expr()
+    }
+
+    fn output_rlc(&self) -> Expression<F> {
+        0.expr()
+    }
+
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        _: &Block<F>,
+        _: &ExecStep,
+        call_data_length: u64,
+        _: bool,
+    ) -> Result<(), Error> {
+        self.input_word_size
+            .assign(region, offset, call_data_length)?;
+        self.input_length_is_zero
+            .assign(region, offset, F::from(call_data_length))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,476 @@
+use super::{PrecompileCallGadget, PrecompileContext};
+use crate::{
+    evm_circuit::{
+        param::N_BYTES_WORD,
+        util::{
+            constraint_builder::ConstraintBuilder,
+            math_gadget::{IsZeroGadget, LtGadget, MinMaxGadget, RangeCheckGadget},
+            sum, Cell, Word,
+        },
+        witness::{Block, ExecStep, ModexpCall, StepAuxiliaryData},
+    },
+    util::Expr,
+};
+use array_init::array_init;
+use bus_mapping::precompile::{ModexpInput, MODEXP_MAX_INPUT_SIZE};
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian};
+use halo2_proofs::{
+    circuit::Region,
+    plonk::{Error, Expression},
+};
+
+/// Gadget for the power of the randomness to a length of at most 32 bytes,
+/// which is the product of its powers to the set bits of the length.
+#[derive(Clone, Debug)]
+struct RandomnessPowerGadget<F> {
+    bits: [Cell<F>; 6],
+    // `products[k]` is the power of the randomness to the bits `0..=k` of the
+    // length
+    products: [Cell<F>; 6],
+}
+
+impl<F: Field> RandomnessPowerGadget<F> {
+    fn construct(cb: &mut ConstraintBuilder<F>, length: Expression<F>) -> Self {
+        let bits = [(); 6].map(|_| cb.query_bool());
+        let products = [(); 6].map(|_| cb.query_cell());
+        cb.require_equal(
+            "length == sum of its bits",
+            length,
+            sum::expr(
+                bits.iter()
+                    .enumerate()
+                    .map(|(idx, bit)| bit.expr() * (1u64 << idx).expr()),
+            ),
+        );
+
+        // The powers of the randomness to 1, 2, 4, 8, 16 and 32
+        let power_of_randomness = cb.power_of_randomness();
+        let powers = [0, 1, 3, 7, 15]
+            .iter()
+            .map(|idx| power_of_randomness[*idx].clone())
+            .chain(std::iter::once(
+                power_of_randomness[30].clone() * power_of_randomness[0].clone(),
+            ))
+            .collect::<Vec<_>>();
+        let mut product = 1.expr();
+        for ((bit, power), cell) in bits.iter().zip(powers).zip(products.iter()) {
+            cb.require_equal(
+                "products[k] == products[k - 1] ⋅ (bit_k ? r^(2^k) : 1)",
+                cell.expr(),
+                product * (1.expr() + bit.expr() * (power - 1.expr())),
+            );
+            product = cell.expr();
+        }
+
+        Self { bits, products }
+    }
+
+    fn expr(&self) -> Expression<F> {
+        self.products[5].expr()
+    }
+
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        length: u64,
+        randomness: F,
+    ) -> Result<(), Error> {
+        let mut product = F::one();
+        for (idx, (bit, cell)) in self.bits.iter().zip(self.products.iter()).enumerate() {
+            let is_set = (length >> idx) & 1;
+            bit.assign(region, offset, Some(F::from(is_set)))?;
+            if is_set == 1 {
+                product *= randomness.pow(&[1 << idx, 0, 0, 0]);
+            }
+            cell.assign(region, offset, Some(product))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Gadget for the MODEXP precompile, which looks up the result of the
+/// exponentiation in the MODEXP table built by the MODEXP circuit.  Only the
+/// calls whose base, exponent and modulus are at most
+/// [`MODEXP_MAX_INPUT_SIZE`] bytes long are supported, so the exponent fits
+/// in a word and the number of iterations of the gas cost (EIP-2565) is the
+/// index of its most significant set bit.  The calls whose modulus is 0 are
+/// not looked up, since their result is 0.
+#[derive(Clone, Debug)]
+pub(crate) struct ModexpGadget<F> {
+    // Lengths in bytes of the base, the exponent and the modulus
+    lengths: [Cell<F>; 3],
+    lengths_supported: [LtGadget<F, 1>; 3],
+    // The powers of the randomness to the lengths, which chain the RLCs of
+    // the sections of the input
+    length_powers: [RandomnessPowerGadget<F>; 3],
+    max_length: MinMaxGadget<F, 1>,
+    // `8 ⋅ words == max(base_len, mod_len) + padding`
+    words: Cell<F>,
+    padding: Cell<F>,
+    padding_lt: LtGadget<F, 1>,
+    base_rlc: Cell<F>,
+    exp: Word<F>,
+    modulus_rlc: Cell<F>,
+    modulus_is_zero: IsZeroGadget<F>,
+    // The RLCs of the input up to the end of the base, of the exponent and
+    // of the modulus
+    base_end_rlc: Cell<F>,
+    exp_end_rlc: Cell<F>,
+    input_rlc: Cell<F>,
+    result_rlc: Cell<F>,
+    // `is_msb_byte[i]` is 1 when the byte `i` is the most significant
+    // non-zero byte of the exponent, and `is_msb_bit[j]` is 1 when the bit
+    // `j` is the most significant set bit of that byte
+    is_msb_byte: [Cell<F>; N_BYTES_WORD],
+    is_msb_bit: [Cell<F>; 8],
+    // `msb_byte == 2^j + msb_byte_rest`, where `msb_byte_rest < 2^j`
+    msb_byte_rest: Cell<F>,
+    msb_byte_rest_lt: LtGadget<F, 1>,
+    msb_index_is_zero: IsZeroGadget<F>,
+    // `words² ⋅ iteration_count == 3 ⋅ quotient + remainder`
+    quotient: Cell<F>,
+    quotient_range_check: RangeCheckGadget<F, 2>,
+    remainder: Cell<F>,
+    gas_cost: MinMaxGadget<F, 2>,
+}
+
+impl<F: Field> PrecompileCallGadget<F> for ModexpGadget<F> {
+    fn configure(
+        cb: &mut ConstraintBuilder<F>,
+        ctx: &PrecompileContext<F>,
+        is_selected: Expression<F>,
+    ) -> Self {
+        let lengths = [(); 3].map(|_| cb.query_byte());
+        let lengths_supported: [LtGadget<F, 1>; 3] = array_init(|idx| {
+            LtGadget::construct(
+                cb,
+                lengths[idx].expr(),
+                (MODEXP_MAX_INPUT_SIZE as u64 + 1).expr(),
+            )
+        });
+        cb.condition(is_selected.clone(), |cb| {
+            for lt in lengths_supported.iter() {
+                cb.require_equal(
+                    "Length is at most MODEXP_MAX_INPUT_SIZE",
+                    lt.expr(),
+                    1.expr(),
+                );
+            }
+        });
+        let [base_len, _, mod_len] = lengths.clone();
+
+        // The multiplication complexity is the square of the number of words
+        // of the base or the modulus, whichever is longer
+        let max_length = MinMaxGadget::construct(cb, base_len.expr(), mod_len.expr());
+        let words = cb.query_cell();
+        let padding = cb.query_byte();
+        let padding_lt = LtGadget::construct(cb, padding.expr(), 8.expr());
+        cb.require_equal("padding < 8", padding_lt.expr(), 1.expr());
+        cb.require_equal(
+            "8 ⋅ words == max(base_len, mod_len) + padding",
+            8.expr() * words.expr(),
+            max_length.max() + padding.expr(),
+        );
+
+        // The most significant set bit of the exponent is the bit `j` of its
+        // non-zero byte `i` whose bytes above are all zero
+        let exp = cb.query_word();
+        let is_msb_byte = [(); N_BYTES_WORD].map(|_| cb.query_bool());
+        let is_msb_bit = [(); 8].map(|_| cb.query_bool());
+        let is_exp_non_zero = sum::expr(&is_msb_byte);
+        cb.require_boolean("At most one most significant byte", is_exp_non_zero.clone());
+        cb.require_equal(
+            "One most significant bit when the exponent is not zero",
+            sum::expr(&is_msb_bit),
+            is_exp_non_zero.clone(),
+        );
+        for idx in 0..N_BYTES_WORD {
+            cb.require_zero(
+                "Bytes above the most significant byte are zero",
+                exp.cells[idx].expr() * (1.expr() - sum::expr(&is_msb_byte[idx..])),
+            );
+        }
+        let msb_byte = sum::expr(
+            is_msb_byte
+                .iter()
+                .zip(exp.cells.iter())
+                .map(|(is_msb_byte, byte)| is_msb_byte.expr() * byte.expr()),
+        );
+        let msb_bit_value = sum::expr(
+            is_msb_bit
+                .iter()
+                .enumerate()
+                .map(|(idx, is_msb_bit)| is_msb_bit.expr() * (1u64 << idx).expr()),
+        );
+        let msb_byte_rest = cb.query_byte();
+        let msb_byte_rest_lt = LtGadget::construct(cb, msb_byte_rest.expr(), msb_bit_value.clone());
+        cb.require_equal(
+            "msb_byte == 2^j + msb_byte_rest",
+            msb_byte,
+            msb_bit_value + msb_byte_rest.expr(),
+        );
+        cb.require_equal(
+            "msb_byte_rest < 2^j when the exponent is not zero",
+            msb_byte_rest_lt.expr(),
+            is_exp_non_zero,
+        );
+
+        // The number of iterations is the index of the most significant set
+        // bit of the exponent, and at least 1
+        let msb_index = sum::expr(
+            is_msb_byte
+                .iter()
+                .enumerate()
+                .map(|(idx, is_msb_byte)| is_msb_byte.expr() * (8 * idx as u64).expr())
+                .chain(
+                    is_msb_bit
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, is_msb_bit)| is_msb_bit.expr() * (idx as u64).expr()),
+                ),
+        );
+        let msb_index_is_zero = IsZeroGadget::construct(cb, msb_index.clone());
+        let iteration_count = msb_index + msb_index_is_zero.expr();
+
+        let quotient = cb.query_cell();
+        let quotient_range_check = RangeCheckGadget::construct(cb, quotient.expr());
+        let remainder = cb.query_cell();
+        cb.require_in_set(
+            "remainder < 3",
+            remainder.expr(),
+            vec![0.expr(), 1.expr(), 2.expr()],
+        );
+        cb.require_equal(
+            "words² ⋅ iteration_count == 3 ⋅ quotient + remainder",
+            words.expr() * words.expr() * iteration_count,
+            3.expr() * quotient.expr() + remainder.expr(),
+        );
+        let gas_cost =
+            MinMaxGadget::construct(cb, quotient.expr(), GasCost::PRECOMPILE_MODEXP_MIN.expr());
+
+        // The MODEXP precompile only fails when it runs out of gas
+        let [base_rlc, modulus_rlc, result_rlc] = [(); 3].map(|_| cb.query_cell());
+        cb.condition(is_selected.clone(), |cb| {
+            cb.require_equal(
+                "is_success == 1 - insufficient_gas",
+                ctx.is_success.expr(),
+                1.expr() - ctx.insufficient_gas.expr(),
+            );
+        });
+
+        // The result is 0 when the modulus is 0, which isn't looked up
+        let modulus_is_zero = IsZeroGadget::construct(cb, modulus_rlc.expr());
+        cb.condition(is_selected.clone() * modulus_is_zero.expr(), |cb| {
+            cb.require_zero("result_rlc == 0 when the modulus is 0", result_rlc.expr());
+        });
+        cb.condition(
+            is_selected * ctx.is_success.expr() * (1.expr() - modulus_is_zero.expr()),
+            |cb| {
+                cb.modexp_lookup(
+                    base_rlc.expr(),
+                    exp.expr(),
+                    modulus_rlc.expr(),
+                    result_rlc.expr(),
+                );
+            },
+        );
+
+        // The input is the three lengths as 32 bytes words, whose RLC is their
+        // value, followed by the base, the exponent and the modulus in
+        // big-endian in as many bytes as their lengths, whose RLC is the one
+        // of their value as a word
+        let length_powers: [RandomnessPowerGadget<F>; 3] =
+            array_init(|idx| RandomnessPowerGadget::construct(cb, lengths[idx].expr()));
+        let r_32 = cb.power_of_randomness()[30].clone() * cb.power_of_randomness()[0].clone();
+        let [base_end_rlc, exp_end_rlc, input_rlc] = [(); 3].map(|_| cb.query_cell());
+        cb.require_equal(
+            "base_end_rlc == RLC of the lengths and the base",
+            base_end_rlc.expr(),
+            lengths[1..].iter().fold(lengths[0].expr(), |acc, length| {
+                acc * r_32.clone() + length.expr()
+            }) * length_powers[0].expr()
+                + base_rlc.expr(),
+        );
+        cb.require_equal(
+            "exp_end_rlc == base_end_rlc ⋅ r^exp_len + RLC of the exponent",
+            exp_end_rlc.expr(),
+            base_end_rlc.expr() * length_powers[1].expr() + exp.expr(),
+        );
+        cb.require_equal(
+            "input_rlc == exp_end_rlc ⋅ r^mod_len + RLC of the modulus",
+            input_rlc.expr(),
+            exp_end_rlc.expr() * length_powers[2].expr() + modulus_rlc.expr(),
+        );
+
+        Self {
+            lengths,
+            lengths_supported,
+            length_powers,
+            max_length,
+            words,
+            padding,
+            padding_lt,
+            base_rlc,
+            exp,
+            modulus_rlc,
+            modulus_is_zero,
+            base_end_rlc,
+            exp_end_rlc,
+            input_rlc,
+            result_rlc,
+            is_msb_byte,
+            is_msb_bit,
+            msb_byte_rest,
+            msb_byte_rest_lt,
+            msb_index_is_zero,
+            quotient,
+            quotient_range_check,
+            remainder,
+            gas_cost,
+        }
+    }
+
+    fn gas_cost(&self) -> Expression<F> {
+        self.gas_cost.max()
+    }
+
+    fn output_length(&self, _: &PrecompileContext<F>) -> Expression<F> {
+        // The result takes as many bytes as the modulus
+        self.lengths[2].expr()
+    }
+
+    fn input_length(&self, _: &PrecompileContext<F>) -> Expression<F> {
+        96.expr() + sum::expr(&self.lengths)
+    }
+
+    fn input_rlc(&self) -> Expression<F> {
+        self.input_rlc.expr()
+    }
+
+    fn output_rlc(&self) -> Expression<F> {
+        // The result is less than the modulus, so it fits in as many bytes
+        self.result_rlc.expr()
+    }
+
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        step: &ExecStep,
+        _: u64,
+        is_selected: bool,
+    ) -> Result<(), Error> {
+        let input = match (is_selected, step.aux_data.as_ref()) {
+            (true, Some(StepAuxiliaryData::Precompile { input, .. })) => {
+                ModexpInput::new(input).ok_or(Error::Synthesis)?
+            }
+            _ => ModexpInput::default(),
+        };
+
+        let lengths = [input.base_len, input.exp_len, input.mod_len].map(|len| len as u64);
+        for (((cell, lt), power), length) in self
+            .lengths
+            .iter()
+            .zip(self.lengths_supported.iter())
+            .zip(self.length_powers.iter())
+            .zip(lengths)
+        {
+            cell.assign(region, offset, Some(F::from(length)))?;
+            lt.assign(
+                region,
+                offset,
+                F::from(length),
+                F::from(MODEXP_MAX_INPUT_SIZE as u64 + 1),
+            )?;
+            power.assign(region, offset, length, block.randomness)?;
+        }
+        let max_length = lengths[0].max(lengths[2]);
+        self.max_length
+            .assign(region, offset, F::from(lengths[0]), F::from(lengths[2]))?;
+        let words = (max_length + 7) / 8;
+        let padding = 8 * words - max_length;
+        self.words.assign(region, offset, Some(F::from(words)))?;
+        self.padding
+            .assign(region, offset, Some(F::from(padding)))?;
+        self.padding_lt
+            .assign(region, offset, F::from(padding), F::from(8))?;
+
+        let call = ModexpCall {
+            base: input.base,
+            exp: input.exp,
+            modulus: input.modulus,
+            result: input.modexp(),
+        };
+        let [base_rlc, exp_rlc, modulus_rlc, result_rlc] = call.table_assignment(block.randomness);
+        self.base_rlc.assign(region, offset, Some(base_rlc))?;
+        self.exp
+            .assign(region, offset, Some(input.exp.to_le_bytes()))?;
+        self.modulus_rlc.assign(region, offset, Some(modulus_rlc))?;
+        self.modulus_is_zero.assign(region, offset, modulus_rlc)?;
+        self.result_rlc.assign(region, offset, Some(result_rlc))?;
+
+        let r_power = |length: u64| block.randomness.pow(&[length, 0, 0, 0]);
+        let r_32 = r_power(32);
+        let lengths_rlc =
+            (F::from(lengths[0]) * r_32 + F::from(lengths[1])) * r_32 + F::from(lengths[2]);
+        let base_end_rlc = lengths_rlc * r_power(lengths[0]) + base_rlc;
+        let exp_end_rlc = base_end_rlc * r_power(lengths[1]) + exp_rlc;
+        let input_rlc = exp_end_rlc * r_power(lengths[2]) + modulus_rlc;
+        for (cell, value) in [
+            (&self.base_end_rlc, base_end_rlc),
+            (&self.exp_end_rlc, exp_end_rlc),
+            (&self.input_rlc, input_rlc),
+        ] {
+            cell.assign(region, offset, Some(value))?;
+        }
+
+        // The index of the most significant set bit, if any
+        let msb_index = (input.exp.bits() as u64).checked_sub(1);
+        let exp_bytes = input.exp.to_le_bytes();
+        for (idx, cell) in self.is_msb_byte.iter().enumerate() {
+            let is_msb_byte = msb_index.map_or(false, |msb_index| msb_index / 8 == idx as u64);
+            cell.assign(region, offset, Some(F::from(is_msb_byte as u64)))?;
+        }
+        for (idx, cell) in self.is_msb_bit.iter().enumerate() {
+            let is_msb_bit = msb_index.map_or(false, |msb_index| msb_index % 8 == idx as u64);
+            cell.assign(region, offset, Some(F::from(is_msb_bit as u64)))?;
+        }
+        let (msb_bit_value, msb_byte_rest) = msb_index.map_or((0, 0), |msb_index| {
+            let msb_bit_value = 1u64 << (msb_index % 8);
+            let msb_byte = exp_bytes[msb_index as usize / 8] as u64;
+            (msb_bit_value, msb_byte - msb_bit_value)
+        });
+        self.msb_byte_rest
+            .assign(region, offset, Some(F::from(msb_byte_rest)))?;
+        self.msb_byte_rest_lt.assign(
+            region,
+            offset,
+            F::from(msb_byte_rest),
+            F::from(msb_bit_value),
+        )?;
+        let msb_index = msb_index.unwrap_or(0);
+        self.msb_index_is_zero
+            .assign(region, offset, F::from(msb_index))?;
+
+        let iteration_count = msb_index.max(1);
+        let quotient = words * words * iteration_count / 3;
+        let remainder = words * words * iteration_count % 3;
+        self.quotient
+            .assign(region, offset, Some(F::from(quotient)))?;
+        self.quotient_range_check
+            .assign(region, offset, F::from(quotient))?;
+        self.remainder
+            .assign(region, offset, Some(F::from(remainder)))?;
+        self.gas_cost.assign(
+            region,
+            offset,
+            F::from(quotient),
+            F::from(GasCost::PRECOMPILE_MODEXP_MIN.as_u64()),
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use super::{PrecompileCallGadget, PrecompileContext};
+use crate::{
+    evm_circuit::{
+        param::N_BYTES_MEMORY_WORD_SIZE,
+        util::{constraint_builder::ConstraintBuilder, memory_gadget::MemoryWordSizeGadget, Cell},
+        witness::{Block, ExecStep, StepAuxiliaryData},
+    },
+    util::Expr,
+};
+use eth_types::{evm_types::GasCost, Field};
+use halo2_proofs::{
+    circuit::Region,
+    plonk::{Error, Expression},
+};
+use sha256::circuit::{hash_table_row, HASH_TABLE_WIDTH};
+
+/// Gadget for the SHA256 precompile, which looks up the digest of the input
+/// in the SHA-256 table built by the SHA-256 circuit.
+#[derive(Clone, Debug)]
+pub(crate) struct Sha256Gadget<F> {
+    input_word_size: MemoryWordSizeGadget<F, N_BYTES_MEMORY_WORD_SIZE>,
+    input_rlc: Cell<F>,
+    digest_rlc: Cell<F>,
+}
+
+impl<F: Field> PrecompileCallGadget<F> for Sha256Gadget<F> {
+    fn configure(
+        cb: &mut ConstraintBuilder<F>,
+        ctx: &PrecompileContext<F>,
+        is_selected: Expression<F>,
+    ) -> Self {
+        let input_word_size = MemoryWordSizeGadget::construct(cb, ctx.call_data_length.expr());
+        let [input_rlc, digest_rlc] = [(); 2].map(|_| cb.query_cell());
+
+        // The SHA256 precompile only fails when it runs out of gas
+        cb.condition(is_selected.clone(), |cb| {
+            cb.require_equal(
+                "is_success == 1 - insufficient_gas",
+                ctx.is_success.expr(),
+                1.expr() - ctx.insufficient_gas.expr(),
+            );
+        });
+
+        cb.condition(is_selected * ctx.is_success.expr(), |cb| {
+            cb.sha256_lookup(
+                input_rlc.expr(),
+                ctx.call_data_length.expr(),
+                digest_rlc.expr(),
+            );
+        });
+
+        Self {
+            input_word_size,
+            input_rlc,
+            digest_rlc,
+        }
+    }
+
+    fn gas_cost(&self) -> Expression<F> {
+        GasCost::PRECOMPILE_SHA256_BASE.expr()
+            + GasCost::PRECOMPILE_SHA256_PER_WORD.expr() * self.input_word_size.expr()
+    }
+
+    fn output_length(&self, _: &PrecompileContext<F>) -> Expression<F> {
+        32.expr()
+    }
+
+    fn input_length(&self, ctx: &PrecompileContext<F>) -> Expression<F> {
+        ctx.call_data_length.expr()
+    }
+
+    fn input_rlc(&self) -> Expression<F> {
+        self.input_rlc.expr()
+    }
+
+    fn output_rlc(&self) -> Expression<F> {
+        self.digest_rlc.expr()
+    }
+
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        step: &ExecStep,
+        call_data_length: u64,
+        is_selected: bool,
+    ) -> Result<(), Error> {
+        self.input_word_size
+            .assign(region, offset, call_data_length)?;
+
+        let [input_rlc, _, digest_rlc] = match (is_selected, step.aux_data.as_ref()) {
+            (true, Some(StepAuxiliaryData::Precompile { input, .. })) => {
+                hash_table_row(input, block.randomness)
+            }
+            _ => [F::zero(); HASH_TABLE_WIDTH],
+        };
+        self.input_rlc.assign(region, offset, Some(input_rlc))?;
+        self.digest_rlc.assign(region, offset, Some(digest_rlc))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,426 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE, N_BYTES_U64},
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            from_bytes,
+            math_gadget::RangeCheckGadget,
+            memory_gadget::{MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget},
+            Cell, MemoryAddress,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::circuit_input_builder::CopyDataType;
+use eth_types::Field;
+use eth_types::ToLittleEndian;
+use halo2_proofs::{circuit::Region, plonk::Error};
+use std::convert::TryInto;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ReturnDataCopyGadget<F> {
+    same_context: SameContextGadget<F>,
+    memory_address: MemoryAddressGadget<F>,
+    data_offset: MemoryAddress<F>,
+    last_callee_id: Cell<F>,
+    return_data_offset: Cell<F>,
+    return_data_length: Cell<F>,
+    in_bound: RangeCheckGadget<F, N_BYTES_U64>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_copier_gas: MemoryCopierGasGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ReturnDataCopyGadget<F> {
+    const NAME: &'static str = "RETURNDATACOPY";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::RETURNDATACOPY;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let memory_offset = cb.query_cell();
+        let data_offset = cb.query_rlc();
+        let length = cb.query_rlc();
+
+        // Pop memory_offset, data_offset, length from stack
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(data_offset.expr());
+        cb.stack_pop(length.expr());
+
+        let memory_address = MemoryAddressGadget::construct(cb, memory_offset, length);
+
+        // Lookup the last callee and the location of its return data in its
+        // memory
+        let last_callee_id = cb.call_context(None, CallContextFieldTag::LastCalleeId);
+        let return_data_offset =
+            cb.call_context(None, CallContextFieldTag::LastCalleeReturnDataOffset);
+        let return_data_length =
+            cb.call_context(None, CallContextFieldTag::LastCalleeReturnDataLength);
+
+        // Copying out of the bound of the return data is handled by
+        // ErrorReturnDataOutOfBound
+        let in_bound = RangeCheckGadget::construct(
+            cb,
+            return_data_length.expr()
+                - from_bytes::expr(&data_offset.cells)
+                - memory_address.length(),
+        );
+
+        // Calculate the next memory size and the gas cost for this memory
+        // access
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [memory_address.address()],
+        );
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            memory_address.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        // Copy the return data from the memory of the last callee with a
+        // lookup to the copy table, where every byte is read and written
+        cb.condition(memory_address.has_length(), |cb| {
+            cb.copy_table_lookup(
+                last_callee_id.expr(),
+                CopyDataType::Memory.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                return_data_offset.expr() + from_bytes::expr(&data_offset.cells),
+                return_data_offset.expr() + return_data_length.expr(),
+                memory_address.offset(),
+                memory_address.length(),
+                2.expr() * memory_address.length(),
+                0.expr(),
+            );
+        });
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            // 3 stack pop + 3 call context lookups + the memory accesses of
+            // the copy
+            rw_counter: Delta(cb.rw_counter_offset()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(3.expr()),
+            memory_word_size: To(memory_expansion.next_memory_word_size()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(
+            cb,
+            opcode,
+            step_state_transition,
+            Some(memory_copier_gas.gas_cost()),
+        );
+
+        Self {
+            same_context,
+            memory_address,
+            data_offset,
+            last_callee_id,
+            return_data_offset,
+            return_data_length,
+            in_bound,
+            memory_expansion,
+            memory_copier_gas,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [memory_offset, data_offset, length] =
+            [step.rw_indices[0], step.rw_indices[1], step.rw_indices[2]]
+                .map(|idx| block.rws[idx].stack_value());
+        let memory_address =
+            self.memory_address
+                .assign(region, offset, memory_offset, length, block.randomness)?;
+        self.data_offset.assign(
+            region,
+            offset,
+            Some(
+                data_offset.to_le_bytes()[..N_BYTES_MEMORY_ADDRESS]
+                    .try_into()
+                    .unwrap(),
+            ),
+        )?;
+
+        let [last_callee_id, return_data_offset, return_data_length] =
+            [step.rw_indices[3], step.rw_indices[4], step.rw_indices[5]]
+                .map(|idx| block.rws[idx].call_context_value().as_u64());
+        for (cell, value) in [
+            (&self.last_callee_id, last_callee_id),
+            (&self.return_data_offset, return_data_offset),
+            (&self.return_data_length, return_data_length),
+        ] {
+            cell.assign(region, offset, Some(F::from(value)))?;
+        }
+
+        self.in_bound.assign(
+            region,
+            offset,
+            F::from(return_data_length - data_offset.as_u64() - length.as_u64()),
+        )?;
+
+        // Memory expansion
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+
+        self.memory_copier_gas.assign(
+            region,
+            offset,
+            length.as_u64(),
+            memory_expansion_gas_cost as u64,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::{
+        step::ExecutionState,
+        table::{CallContextFieldTag, RwTableTag},
+        test::{
+            calc_memory_copier_gas_cost, make_copy_event, rand_bytes,
+            run_test_circuit_incomplete_fixed_table,
+        },
+        witness::{Block, Bytecode, Call, CodeSource, ExecStep, Rw, RwMap, Transaction},
+    };
+    use bus_mapping::circuit_input_builder::{CopyDataType, NumberOrHash};
+    use eth_types::{
+        evm_types::{GasCost, OpcodeId},
+        ToBigEndian, Word,
+    };
+    use halo2_proofs::arithmetic::BaseExt;
+    use pairing::bn256::Fr as Fp;
+
+    fn test_ok(
+        return_data_offset: u64,
+        return_data_length: u64,
+        memory_offset: Word,
+        data_offset: Word,
+        length: Word,
+    ) {
+        let randomness = Fp::rand();
+        let bytecode = Bytecode::new(
+            [
+                vec![OpcodeId::PUSH32.as_u8()],
+                length.to_be_bytes().to_vec(),
+                vec![OpcodeId::PUSH32.as_u8()],
+                data_offset.to_be_bytes().to_vec(),
+                vec![OpcodeId::PUSH32.as_u8()],
+                memory_offset.to_be_bytes().to_vec(),
+                vec![OpcodeId::RETURNDATACOPY.as_u8(), OpcodeId::STOP.as_u8()],
+            ]
+            .concat(),
+        );
+        let call_id = 1;
+        let callee_id = 2;
+        let return_data = rand_bytes(return_data_length as usize);
+
+        let mut rws = RwMap(
+            [
+                (
+                    RwTableTag::Stack,
+                    vec![
+                        Rw::Stack {
+                            rw_counter: 1,
+                            is_write: false,
+                            call_id,
+                            stack_pointer: 1021,
+                            value: memory_offset,
+                        },
+                        Rw::Stack {
+                            rw_counter: 2,
+                            is_write: false,
+                            call_id,
+                            stack_pointer: 1022,
+                            value: data_offset,
+                        },
+                        Rw::Stack {
+                            rw_counter: 3,
+                            is_write: false,
+                            call_id,
+                            stack_pointer: 1023,
+                            value: length,
+                        },
+                    ],
+                ),
+                (
+                    RwTableTag::CallContext,
+                    vec![
+                        Rw::CallContext {
+                            rw_counter: 4,
+                            is_write: false,
+                            call_id,
+                            field_tag: CallContextFieldTag::LastCalleeId,
+                            value: Word::from(callee_id),
+                        },
+                        Rw::CallContext {
+                            rw_counter: 5,
+                            is_write: false,
+                            call_id,
+                            field_tag: CallContextFieldTag::LastCalleeReturnDataOffset,
+                            value: Word::from(return_data_offset),
+                        },
+                        Rw::CallContext {
+                            rw_counter: 6,
+                            is_write: false,
+                            call_id,
+                            field_tag: CallContextFieldTag::LastCalleeReturnDataLength,
+                            value: Word::from(return_data_length),
+                        },
+                    ],
+                ),
+            ]
+            .into(),
+        );
+        let mut rw_counter = 7;
+
+        let next_memory_word_size = if length.is_zero() {
+            0
+        } else {
+            (memory_offset.as_u64() + length.as_u64() + 31) / 32
+        };
+        let gas_cost = GasCost::FASTEST.as_u64()
+            + calc_memory_copier_gas_cost(0, next_memory_word_size, length.as_u64());
+
+        let mut steps = vec![ExecStep {
+            rw_indices: vec![
+                (RwTableTag::Stack, 0),
+                (RwTableTag::Stack, 1),
+                (RwTableTag::Stack, 2),
+                (RwTableTag::CallContext, 0),
+                (RwTableTag::CallContext, 1),
+                (RwTableTag::CallContext, 2),
+            ],
+            execution_state: ExecutionState::RETURNDATACOPY,
+            rw_counter: 1,
+            program_counter: 99,
+            stack_pointer: 1021,
+            gas_left: gas_cost,
+            gas_cost,
+            memory_size: 0,
+            opcode: Some(OpcodeId::RETURNDATACOPY),
+            ..Default::default()
+        }];
+
+        let mut copy_events = Vec::new();
+        if !length.is_zero() {
+            copy_events.push(make_copy_event(
+                CopyDataType::Memory,
+                NumberOrHash::Number(callee_id),
+                &return_data,
+                return_data_offset,
+                return_data_offset + data_offset.as_u64(),
+                CopyDataType::Memory,
+                call_id,
+                memory_offset.as_u64(),
+                length.as_usize(),
+                &mut rw_counter,
+                &mut rws,
+                &mut steps[0].rw_indices,
+            ));
+        }
+
+        steps.push(ExecStep {
+            execution_state: ExecutionState::STOP,
+            rw_counter,
+            program_counter: 100,
+            stack_pointer: 1024,
+            opcode: Some(OpcodeId::STOP),
+            memory_size: next_memory_word_size * 32,
+            ..Default::default()
+        });
+
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                id: 1,
+                calls: vec![Call {
+                    id: call_id,
+                    is_root: true,
+                    is_create: false,
+                    code_source: CodeSource::Account(bytecode.hash),
+                    ..Default::default()
+                }],
+                steps,
+                ..Default::default()
+            }],
+            rws,
+            bytecodes: vec![bytecode],
+            copy_events,
+            ..Default::default()
+        };
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn returndatacopy_gadget_simple() {
+        test_ok(
+            0x00,
+            0x20,
+            Word::from(0x40),
+            Word::from(0x00),
+            Word::from(0x20),
+        );
+        test_ok(
+            0x40,
+            0x40,
+            Word::from(0xA0),
+            Word::from(0x10),
+            Word::from(0x10),
+        );
+    }
+
+    #[test]
+    fn returndatacopy_gadget_multi_step() {
+        test_ok(
+            0x40,
+            0x80,
+            Word::from(0x100),
+            Word::from(0x08),
+            Word::from(0x70),
+        );
+    }
+
+    #[test]
+    fn returndatacopy_gadget_zero_length() {
+        test_ok(
+            0x00,
+            0x00,
+            Word::from(0x40),
+            Word::from(0x00),
+            Word::from(0x00),
+        );
+        test_ok(
+            0x40,
+            0x20,
+            Word::from(0x40),
+            Word::from(0x20),
+            Word::from(0x00),
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use eth_types::{Field, ToLittleEndian};
+use halo2_proofs::{circuit::Region, plonk::Error};
+use std::convert::TryInto;
+
+use crate::{
+    evm_circuit::{
+        param::N_BYTES_U64,
+        step::ExecutionState,
+        table::CallContextFieldTag,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{ConstraintBuilder, StepStateTransition, Transition},
+            from_bytes, RandomLinearCombination,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+
+use super::ExecutionGadget;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ReturnDataSizeGadget<F> {
+    same_context: SameContextGadget<F>,
+    return_data_size: RandomLinearCombination<F, N_BYTES_U64>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ReturnDataSizeGadget<F> {
+    const NAME: &'static str = "RETURNDATASIZE";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::RETURNDATASIZE;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        // Add lookup constraint in the call context for the length of the last
+        // callee's return data.
+        let return_data_size = cb.query_rlc();
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::LastCalleeReturnDataLength,
+            from_bytes::expr(&return_data_size.cells),
+        );
+
+        // The returndatasize should be pushed to the top of the stack.
+        cb.stack_push(return_data_size.expr());
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Transition::Delta(2.expr()),
+            program_counter: Transition::Delta(1.expr()),
+            stack_pointer: Transition::Delta((-1).expr()),
+            ..Default::default()
+        };
+
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition, None);
+
+        Self {
+            same_context,
+            return_data_size,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        _call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let return_data_size = block.rws[step.rw_indices[1]].stack_value();
+
+        self.return_data_size.assign(
+            region,
+            offset,
+            Some(
+                return_data_size.to_le_bytes()[..N_BYTES_U64]
+                    .try_into()
+                    .unwrap(),
+            ),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use bus_mapping::evm::OpcodeId;
+    use eth_types::{bytecode, Word};
+    use halo2_proofs::arithmetic::BaseExt;
+    use pairing::bn256::Fr;
+
+    use crate::evm_circuit::{
+        step::ExecutionState,
+        table::{CallContextFieldTag, RwTableTag},
+        test::run_test_circuit_incomplete_fixed_table,
+        witness::{Block, Bytecode, Call, CodeSource, ExecStep, Rw, RwMap, Transaction},
+    };
+
+    fn test_ok(return_data_size: u64) {
+        let randomness = Fr::rand();
+        let bytecode = bytecode! {
+            #[start]
+            RETURNDATASIZE
+            STOP
+        };
+        let bytecode = Bytecode::new(bytecode.to_vec());
+        let call_id = 1;
+
+        let mut rw_map = HashMap::new();
+        rw_map.insert(
+            RwTableTag::CallContext,
+            vec![Rw::CallContext {
+                rw_counter: 9,
+                is_write: false,
+                call_id,
+                field_tag: CallContextFieldTag::LastCalleeReturnDataLength,
+                value: Word::from(return_data_size),
+            }],
+        );
+        rw_map.insert(
+            RwTableTag::Stack,
+            vec![Rw::Stack {
+                rw_counter: 10,
+                is_write: true,
+                call_id,
+                stack_pointer: 1023,
+                value: Word::from(return_data_size),
+            }],
+        );
+
+        let steps = vec![
+            ExecStep {
+                execution_state: ExecutionState::RETURNDATASIZE,
+                rw_indices: vec![(RwTableTag::CallContext, 0), (RwTableTag::Stack, 0)],
+                rw_counter: 9,
+                program_counter: 0,
+                stack_pointer: 1024,
+                gas_left: OpcodeId::RETURNDATASIZE.constant_gas_cost().as_u64(),
+                gas_cost: OpcodeId::RETURNDATASIZE.constant_gas_cost().as_u64(),
+                opcode: Some(OpcodeId::RETURNDATASIZE),
+                ..Default::default()
+            },
+            ExecStep {
+                execution_state: ExecutionState::STOP,
+                rw_counter: 11,
+                program_counter: 1,
+                stack_pointer: 1023,
+                gas_left: 0,
+                opcode: Some(OpcodeId::STOP),
+                ..Default::default()
+            },
+        ];
+
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                id: 1,
+                steps,
+                calls: vec![Call {
+                    id: call_id,
+                    is_root: true,
+                    is_create: false,
+                    code_source: CodeSource::Account(bytecode.hash),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            rws: RwMap(rw_map),
+            bytecodes: vec![bytecode],
+            ..Default::default()
+        };
+
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn returndatasize_gadget_simple() {
+        test_ok(0);
+        test_ok(32);
+        test_ok(1024);
+    }
+}
@@ -0,0 +1,863 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{MAX_CODE_SIZE, N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        table::{AccountFieldTag, CallContextFieldTag},
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::ConstraintBuilder,
+            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget, MinMaxGadget, RangeCheckGadget},
+            memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget},
+            Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::circuit_input_builder::CopyDataType;
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian, ToScalar,
+};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+/// Gadget for RETURN and REVERT.  RETURN deploys the returned bytes as the
+/// code of the created contract in a CREATE* or creation transaction, or
+/// copies them into the memory of the caller otherwise.  REVERT copies them
+/// into the memory of the caller as well, except in a creation, and fails the
+/// current call, whose state writes are reverted.  A root call doesn't return
+/// to any caller, so its next step isn't constrained, like STOP.
+#[derive(Clone, Debug)]
+pub(crate) struct ReturnGadget<F> {
+    opcode: Cell<F>,
+    is_revert: IsEqualGadget<F>,
+    memory_address: MemoryAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    // Only used in a creation
+    callee_address: Cell<F>,
+    rw_counter_end_of_reversion: Cell<F>,
+    is_persistent: Cell<F>,
+    is_length_gt_max: LtGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    first_byte: Cell<F>,
+    is_first_byte_ef: IsEqualGadget<F>,
+    code_rlc: Cell<F>,
+    code_hash: Word<F>,
+    code_hash_prev: Cell<F>,
+    // Only used in the return of an internal call
+    caller_id: Cell<F>,
+    return_data_offset: Cell<F>,
+    return_data_length: Cell<F>,
+    copy_length: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    copy_length_is_zero: IsZeroGadget<F>,
+    sufficient_gas_left: RangeCheckGadget<F, N_BYTES_GAS>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ReturnGadget<F> {
+    const NAME: &'static str = "RETURN";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::RETURN;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        let is_revert = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::REVERT.expr());
+
+        // Pop memory_offset and length from stack
+        let memory_offset = cb.query_cell();
+        let length = cb.query_rlc();
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(length.expr());
+        let memory_address = MemoryAddressGadget::construct(cb, memory_offset, length);
+
+        let is_create = cb.curr.state.is_create.expr();
+        let is_root = cb.curr.state.is_root.expr();
+        let is_deploy = is_create.clone() * (1.expr() - is_revert.expr());
+
+        // A creation which returns deploys the returned bytes, which are read
+        // from memory into their RLC with a lookup to the copy table, and
+        // whose hash is written as the code hash of the created contract.  The
+        // write is reverted if the creation or any of its callers fails later.
+        let [callee_address, rw_counter_end_of_reversion, is_persistent] =
+            cb.condition(is_deploy.clone(), |cb| {
+                [
+                    CallContextFieldTag::CalleeAddress,
+                    CallContextFieldTag::RwCounterEndOfReversion,
+                    CallContextFieldTag::IsPersistent,
+                ]
+                .map(|field_tag| cb.call_context(None, field_tag))
+            });
+
+        // The deployed code is at most MAX_CODE_SIZE bytes (EIP-170), and
+        // doesn't start with 0xEF (EIP-3541), otherwise the creation fails in
+        // ErrorMaxCodeSizeExceeded or ErrorInvalidCreationCode instead
+        let is_length_gt_max =
+            LtGadget::construct(cb, MAX_CODE_SIZE.expr(), memory_address.length());
+        cb.require_zero(
+            "length <= MAX_CODE_SIZE in a deployment",
+            is_deploy.clone() * is_length_gt_max.expr(),
+        );
+        let first_byte = cb.query_cell();
+        cb.condition(is_deploy.clone() * memory_address.has_length(), |cb| {
+            cb.memory_lookup(0.expr(), memory_address.offset(), first_byte.expr(), None);
+        });
+        let is_first_byte_ef = IsEqualGadget::construct(cb, first_byte.expr(), 0xefu8.expr());
+        cb.require_zero(
+            "First byte != 0xEF in a deployment",
+            is_deploy.clone() * memory_address.has_length() * is_first_byte_ef.expr(),
+        );
+
+        let code_rlc = cb.query_cell();
+        cb.condition(is_deploy.clone() * memory_address.has_length(), |cb| {
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                0.expr(),
+                CopyDataType::RlcAcc.expr(),
+                memory_address.offset(),
+                memory_address.address(),
+                0.expr(),
+                memory_address.length(),
+                memory_address.length(),
+                code_rlc.expr(),
+            );
+        });
+        cb.require_zero(
+            "code_rlc == 0 when length == 0",
+            (1.expr() - memory_address.has_length()) * code_rlc.expr(),
+        );
+        let code_hash = cb.query_word();
+        let code_hash_prev = cb.query_cell();
+        cb.condition(is_deploy.clone(), |cb| {
+            cb.keccak_lookup(code_rlc.expr(), memory_address.length(), code_hash.expr());
+            cb.account_write_with_reversion(
+                callee_address.expr(),
+                AccountFieldTag::CodeHash,
+                code_hash.expr(),
+                code_hash_prev.expr(),
+                is_persistent.expr(),
+                rw_counter_end_of_reversion.expr(),
+            );
+        });
+
+        // An internal call which isn't a creation copies the returned bytes
+        // into the memory of its caller, up to the length it expects
+        let is_call_return = (1.expr() - is_create) * (1.expr() - is_root);
+        let [caller_id, return_data_offset, return_data_length] =
+            cb.condition(is_call_return.clone(), |cb| {
+                [
+                    CallContextFieldTag::CallerId,
+                    CallContextFieldTag::ReturnDataOffset,
+                    CallContextFieldTag::ReturnDataLength,
+                ]
+                .map(|field_tag| cb.call_context(None, field_tag))
+            });
+        let copy_length =
+            MinMaxGadget::construct(cb, memory_address.length(), return_data_length.expr());
+        let copy_length_is_zero = IsZeroGadget::construct(cb, copy_length.min());
+        cb.condition(
+            is_call_return * (1.expr() - copy_length_is_zero.expr()),
+            |cb| {
+                cb.copy_table_lookup(
+                    cb.curr.state.call_id.expr(),
+                    CopyDataType::Memory.expr(),
+                    caller_id.expr(),
+                    CopyDataType::Memory.expr(),
+                    memory_address.offset(),
+                    memory_address.address(),
+                    return_data_offset.expr(),
+                    copy_length.min(),
+                    2.expr() * copy_length.min(),
+                    0.expr(),
+                );
+            },
+        );
+
+        // The gas cost is the memory expansion cost, and the cost of every
+        // deployed byte in a creation
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [memory_address.address()],
+        );
+        let gas_cost = memory_expansion.gas_cost()
+            + is_deploy.clone() * GasCost::CODE_DEPOSIT_BYTE_COST.expr() * memory_address.length();
+        let gas_left = cb.curr.state.gas_left.expr() - gas_cost;
+        let sufficient_gas_left = RangeCheckGadget::construct(cb, gas_left.clone());
+
+        // REVERT fails the current call, whose state writes are reverted
+        cb.condition(is_revert.expr(), |cb| cb.require_call_failure());
+
+        // Return to the caller with the gas left and the returned bytes as
+        // return data, except for a creation which deploys them
+        let restore_context = RestoreContextGadget::construct(
+            cb,
+            1.expr() - is_revert.expr(),
+            (1.expr() - is_deploy.clone()) * memory_address.offset(),
+            (1.expr() - is_deploy) * memory_address.length(),
+            gas_left,
+        );
+
+        Self {
+            opcode,
+            is_revert,
+            memory_address,
+            memory_expansion,
+            callee_address,
+            rw_counter_end_of_reversion,
+            is_persistent,
+            is_length_gt_max,
+            first_byte,
+            is_first_byte_ef,
+            code_rlc,
+            code_hash,
+            code_hash_prev,
+            caller_id,
+            return_data_offset,
+            return_data_length,
+            copy_length,
+            copy_length_is_zero,
+            sufficient_gas_left,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Some(F::from(opcode.as_u64())))?;
+        self.is_revert.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::REVERT.as_u64()),
+        )?;
+        let is_deploy = call.is_create && opcode == OpcodeId::RETURN;
+
+        let [memory_offset, length] =
+            [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].stack_value());
+        let memory_address =
+            self.memory_address
+                .assign(region, offset, memory_offset, length, block.randomness)?;
+        let length = if length.is_zero() { 0 } else { length.as_u64() };
+
+        // The first byte of the deployed code is read after the 2 stack pops
+        // and the 3 call context lookups, then the deployed code, which is
+        // followed by the write of its hash
+        let (first_byte, code_rlc, code_hash, code_hash_prev) = if is_deploy {
+            let (first_byte, code_offset) = if length == 0 {
+                (0, 5)
+            } else {
+                (block.rws[step.rw_indices[5]].memory_value(), 6)
+            };
+            let code_rlc = step.rw_indices[code_offset..code_offset + length as usize]
+                .iter()
+                .fold(F::zero(), |acc, idx| {
+                    acc * block.randomness + F::from(block.rws[*idx].memory_value() as u64)
+                });
+            let (code_hash, code_hash_prev) =
+                block.rws[step.rw_indices[code_offset + length as usize]].account_value_pair();
+            (
+                first_byte,
+                code_rlc,
+                code_hash,
+                Word::random_linear_combine(code_hash_prev.to_le_bytes(), block.randomness),
+            )
+        } else {
+            Default::default()
+        };
+        self.callee_address.assign(
+            region,
+            offset,
+            if is_deploy {
+                call.callee_address.to_scalar()
+            } else {
+                Some(F::zero())
+            },
+        )?;
+        self.rw_counter_end_of_reversion.assign(
+            region,
+            offset,
+            Some(F::from(call.rw_counter_end_of_reversion as u64)),
+        )?;
+        self.is_persistent
+            .assign(region, offset, Some(F::from(call.is_persistent as u64)))?;
+        self.is_length_gt_max
+            .assign(region, offset, F::from(MAX_CODE_SIZE), F::from(length))?;
+        self.first_byte
+            .assign(region, offset, Some(F::from(first_byte as u64)))?;
+        self.is_first_byte_ef
+            .assign(region, offset, F::from(first_byte as u64), F::from(0xef))?;
+        self.code_rlc.assign(region, offset, Some(code_rlc))?;
+        self.code_hash
+            .assign(region, offset, Some(code_hash.to_le_bytes()))?;
+        self.code_hash_prev
+            .assign(region, offset, Some(code_hash_prev))?;
+
+        // The caller expects no return data in a creation or a root call
+        let (caller_id, return_data_offset, return_data_length) = if call.is_create || call.is_root
+        {
+            (0, 0, 0)
+        } else {
+            (
+                call.caller_id as u64,
+                call.return_data_offset,
+                call.return_data_length,
+            )
+        };
+        for (cell, value) in [
+            (&self.caller_id, caller_id),
+            (&self.return_data_offset, return_data_offset),
+            (&self.return_data_length, return_data_length),
+        ] {
+            cell.assign(region, offset, Some(F::from(value)))?;
+        }
+        let (copy_length, _) = self.copy_length.assign(
+            region,
+            offset,
+            F::from(length),
+            F::from(return_data_length),
+        )?;
+        self.copy_length_is_zero
+            .assign(region, offset, copy_length)?;
+
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+        let code_deposit_cost = if is_deploy {
+            GasCost::CODE_DEPOSIT_BYTE_COST.as_u64() * length
+        } else {
+            0
+        };
+        self.sufficient_gas_left.assign(
+            region,
+            offset,
+            F::from(step.gas_left - memory_expansion_gas_cost - code_deposit_cost),
+        )?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        evm_circuit::{
+            step::ExecutionState,
+            table::{AccountFieldTag, CallContextFieldTag, RwTableTag},
+            test::{make_copy_event, rand_bytes, run_test_circuit_incomplete_fixed_table},
+            witness::{Block, Bytecode, Call, CodeSource, ExecStep, Rw, RwMap, Transaction},
+        },
+        test_util::{run_test_circuits, test_circuits_using_geth_data, BytecodeTestConfig},
+    };
+    use bus_mapping::circuit_input_builder::{CopyDataType, NumberOrHash};
+    use eth_types::{
+        bytecode,
+        evm_types::{GasCost, OpcodeId},
+        Address, ToWord, Word,
+    };
+    use halo2_proofs::{arithmetic::BaseExt, dev::VerifyFailure};
+    use pairing::bn256::Fr as Fp;
+    use sha3::{Digest, Keccak256};
+
+    /// Run the RETURN, or the REVERT when `is_revert`, of `length` bytes at
+    /// `memory_offset` in the random memory of the callee.  RETURN deploys
+    /// them when `is_create`, and otherwise they are returned into the memory
+    /// of the caller at 0x80, up to `return_data_length` bytes.  A caller which
+    /// isn't root resumes at a STOP step.
+    fn run_test(
+        is_revert: bool,
+        is_root: bool,
+        is_create: bool,
+        memory_size: u64,
+        memory_offset: u64,
+        length: u64,
+        return_data_length: u64,
+    ) {
+        // A deployed code starting with 0xef is invalid
+        let mut memory = rand_bytes(memory_size as usize);
+        if let Some(byte) = memory.get_mut(memory_offset as usize) {
+            if *byte == 0xef {
+                *byte = 0;
+            }
+        }
+        assert_eq!(
+            run_test_with_memory(
+                is_revert,
+                is_root,
+                is_create,
+                memory,
+                memory_offset,
+                length,
+                return_data_length,
+            ),
+            Ok(())
+        );
+    }
+
+    fn run_test_with_memory(
+        is_revert: bool,
+        is_root: bool,
+        is_create: bool,
+        memory: Vec<u8>,
+        memory_offset: u64,
+        length: u64,
+        return_data_length: u64,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        let randomness = Fp::rand();
+        let memory_size = memory.len() as u64;
+        let caller_bytecode = Bytecode::new(vec![OpcodeId::STOP.as_u8()]);
+        let opcode = if is_revert {
+            OpcodeId::REVERT
+        } else {
+            OpcodeId::RETURN
+        };
+        let callee_bytecode = Bytecode::new(vec![opcode.as_u8()]);
+        let (caller_id, callee_id) = if is_root { (0, 1) } else { (1, 2) };
+        let callee_address = Address::repeat_byte(0xfe);
+        let return_data_offset = 0x80;
+        let caller_gas_left = 100;
+
+        let mut rws = RwMap(
+            [(
+                RwTableTag::Stack,
+                [memory_offset, length]
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, value)| Rw::Stack {
+                        rw_counter: idx + 1,
+                        is_write: false,
+                        call_id: callee_id,
+                        stack_pointer: 1022 + idx,
+                        value: Word::from(*value),
+                    })
+                    .collect(),
+            )]
+            .into(),
+        );
+        let mut rw_counter = 3;
+        let mut rw_indices = vec![(RwTableTag::Stack, 0), (RwTableTag::Stack, 1)];
+        let push_call_context = |rws: &mut RwMap,
+                                 rw_counter: &mut usize,
+                                 rw_indices: &mut Vec<_>,
+                                 is_write,
+                                 call_id,
+                                 field_tag,
+                                 value| {
+            let call_context_rws = rws
+                .0
+                .entry(RwTableTag::CallContext)
+                .or_insert_with(Vec::new);
+            rw_indices.push((RwTableTag::CallContext, call_context_rws.len()));
+            call_context_rws.push(Rw::CallContext {
+                rw_counter: *rw_counter,
+                is_write,
+                call_id,
+                field_tag,
+                value,
+            });
+            *rw_counter += 1;
+        };
+
+        // The bytes past the end of the memory are read as 0
+        let buffer = (0..(memory_offset + length) as usize)
+            .map(|addr| memory.get(addr).copied().unwrap_or(0))
+            .collect::<Vec<_>>();
+        let mut copy_events = Vec::new();
+        let mut keccak_inputs = Vec::new();
+        let is_deploy = is_create && !is_revert;
+        if is_deploy {
+            // The returned bytes are deployed as the code of the callee
+            for (field_tag, value) in [
+                (CallContextFieldTag::CalleeAddress, callee_address.to_word()),
+                (CallContextFieldTag::RwCounterEndOfReversion, Word::zero()),
+                (CallContextFieldTag::IsPersistent, Word::one()),
+            ] {
+                push_call_context(
+                    &mut rws,
+                    &mut rw_counter,
+                    &mut rw_indices,
+                    false,
+                    callee_id,
+                    field_tag,
+                    value,
+                );
+            }
+            let mut code = Vec::new();
+            if length != 0 {
+                let memory_rws = rws.0.entry(RwTableTag::Memory).or_insert_with(Vec::new);
+                rw_indices.push((RwTableTag::Memory, memory_rws.len()));
+                memory_rws.push(Rw::Memory {
+                    rw_counter,
+                    is_write: false,
+                    call_id: callee_id,
+                    memory_address: memory_offset,
+                    byte: buffer[memory_offset as usize],
+                });
+                rw_counter += 1;
+                let event = make_copy_event(
+                    CopyDataType::Memory,
+                    NumberOrHash::Number(callee_id),
+                    &buffer,
+                    0,
+                    memory_offset,
+                    CopyDataType::RlcAcc,
+                    0,
+                    0,
+                    length as usize,
+                    &mut rw_counter,
+                    &mut rws,
+                    &mut rw_indices,
+                );
+                code = event.bytes.clone();
+                copy_events.push(event);
+            }
+            let account_rws = rws.0.entry(RwTableTag::Account).or_insert_with(Vec::new);
+            rw_indices.push((RwTableTag::Account, account_rws.len()));
+            account_rws.push(Rw::Account {
+                rw_counter,
+                is_write: true,
+                account_address: callee_address,
+                field_tag: AccountFieldTag::CodeHash,
+                value: Word::from_big_endian(Keccak256::digest(&code).as_slice()),
+                value_prev: Word::zero(),
+            });
+            rw_counter += 1;
+            keccak_inputs.push(code);
+        } else if !is_create && !is_root {
+            // The returned bytes are copied into the memory of the caller
+            for (field_tag, value) in [
+                (CallContextFieldTag::CallerId, Word::from(caller_id)),
+                (
+                    CallContextFieldTag::ReturnDataOffset,
+                    Word::from(return_data_offset),
+                ),
+                (
+                    CallContextFieldTag::ReturnDataLength,
+                    Word::from(return_data_length),
+                ),
+            ] {
+                push_call_context(
+                    &mut rws,
+                    &mut rw_counter,
+                    &mut rw_indices,
+                    false,
+                    callee_id,
+                    field_tag,
+                    value,
+                );
+            }
+            let copy_length = length.min(return_data_length);
+            if copy_length != 0 {
+                copy_events.push(make_copy_event(
+                    CopyDataType::Memory,
+                    NumberOrHash::Number(callee_id),
+                    &buffer,
+                    0,
+                    memory_offset,
+                    CopyDataType::Memory,
+                    caller_id,
+                    return_data_offset,
+                    copy_length as usize,
+                    &mut rw_counter,
+                    &mut rws,
+                    &mut rw_indices,
+                ));
+            }
+        }
+
+        let curr_memory_word_size = (memory_size + 31) / 32;
+        let next_memory_word_size = if length == 0 {
+            curr_memory_word_size
+        } else {
+            curr_memory_word_size.max((memory_offset + length + 31) / 32)
+        };
+        let memory_cost = |word_size: u64| {
+            GasCost::MEMORY_EXPANSION_LINEAR_COEFF.as_u64() * word_size
+                + word_size * word_size / GasCost::MEMORY_EXPANSION_QUAD_DENOMINATOR.as_u64()
+        };
+        let gas_cost = memory_cost(next_memory_word_size) - memory_cost(curr_memory_word_size);
+        let code_deposit_cost = if is_deploy {
+            GasCost::CODE_DEPOSIT_BYTE_COST.as_u64() * length
+        } else {
+            0
+        };
+        let gas_returned = 10;
+        let gas_left = gas_cost + code_deposit_cost + gas_returned;
+
+        // REVERT fails the callee, which has no state write to revert
+        if is_revert {
+            for (field_tag, value) in [
+                (CallContextFieldTag::IsSuccess, Word::zero()),
+                (
+                    CallContextFieldTag::RwCounterEndOfReversion,
+                    Word::from(rw_counter + 1),
+                ),
+            ] {
+                push_call_context(
+                    &mut rws,
+                    &mut rw_counter,
+                    &mut rw_indices,
+                    false,
+                    callee_id,
+                    field_tag,
+                    value,
+                );
+            }
+        }
+
+        // Then the call context of the caller is restored, which resumes with
+        // the gas left, and the returned bytes as return data of a call
+        if !is_root {
+            let (last_callee_return_data_offset, last_callee_return_data_length) =
+                if is_deploy || length == 0 {
+                    (0, 0)
+                } else {
+                    (memory_offset, length)
+                };
+            for (is_write, call_id, field_tag, value) in [
+                (
+                    false,
+                    callee_id,
+                    CallContextFieldTag::CallerId,
+                    Word::from(caller_id),
+                ),
+                (false, caller_id, CallContextFieldTag::IsRoot, Word::one()),
+                (
+                    false,
+                    caller_id,
+                    CallContextFieldTag::IsCreate,
+                    Word::zero(),
+                ),
+                (
+                    false,
+                    caller_id,
+                    CallContextFieldTag::CodeSource,
+                    caller_bytecode.hash,
+                ),
+                (
+                    false,
+                    caller_id,
+                    CallContextFieldTag::ProgramCounter,
+                    Word::zero(),
+                ),
+                (
+                    false,
+                    caller_id,
+                    CallContextFieldTag::StackPointer,
+                    Word::from(1023),
+                ),
+                (
+                    false,
+                    caller_id,
+                    CallContextFieldTag::GasLeft,
+                    Word::from(caller_gas_left),
+                ),
+                (
+                    false,
+                    caller_id,
+                    CallContextFieldTag::MemorySize,
+                    Word::from(8),
+                ),
+                (
+                    false,
+                    caller_id,
+                    CallContextFieldTag::StateWriteCounter,
+                    Word::zero(),
+                ),
+                (
+                    true,
+                    caller_id,
+                    CallContextFieldTag::LastCalleeId,
+                    Word::from(callee_id),
+                ),
+                (
+                    true,
+                    caller_id,
+                    CallContextFieldTag::LastCalleeReturnDataOffset,
+                    Word::from(last_callee_return_data_offset),
+                ),
+                (
+                    true,
+                    caller_id,
+                    CallContextFieldTag::LastCalleeReturnDataLength,
+                    Word::from(last_callee_return_data_length),
+                ),
+            ] {
+                push_call_context(
+                    &mut rws,
+                    &mut rw_counter,
+                    &mut rw_indices,
+                    is_write,
+                    call_id,
+                    field_tag,
+                    value,
+                );
+            }
+        }
+
+        let mut steps = vec![ExecStep {
+            call_index: if is_root { 0 } else { 1 },
+            rw_indices,
+            execution_state: ExecutionState::RETURN,
+            rw_counter: 1,
+            program_counter: 0,
+            stack_pointer: 1022,
+            gas_left,
+            gas_cost,
+            memory_size: curr_memory_word_size * 32,
+            opcode: Some(opcode),
+            ..Default::default()
+        }];
+        if !is_root {
+            steps.push(ExecStep {
+                call_index: 0,
+                execution_state: ExecutionState::STOP,
+                rw_counter,
+                program_counter: 0,
+                stack_pointer: 1023,
+                gas_left: caller_gas_left + gas_returned,
+                memory_size: 8 * 32,
+                // The code hash written by a creation is kept by the caller
+                state_write_counter: is_deploy as usize,
+                opcode: Some(OpcodeId::STOP),
+                ..Default::default()
+            });
+        }
+
+        let callee = Call {
+            id: callee_id,
+            is_root,
+            is_create,
+            is_success: !is_revert,
+            is_persistent: !is_revert,
+            callee_address,
+            caller_id,
+            return_data_offset,
+            return_data_length,
+            code_source: CodeSource::Account(callee_bytecode.hash),
+            ..Default::default()
+        };
+        let calls = if is_root {
+            vec![callee]
+        } else {
+            vec![
+                Call {
+                    id: caller_id,
+                    is_root: true,
+                    is_create: false,
+                    code_source: CodeSource::Account(caller_bytecode.hash),
+                    ..Default::default()
+                },
+                callee,
+            ]
+        };
+
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                id: 1,
+                calls,
+                steps,
+                ..Default::default()
+            }],
+            rws,
+            bytecodes: vec![caller_bytecode, callee_bytecode],
+            keccak_inputs,
+            copy_events,
+            ..Default::default()
+        };
+        run_test_circuit_incomplete_fixed_table(block)
+    }
+
+    #[test]
+    fn return_gadget_call() {
+        run_test(false, false, false, 0x40, 0x10, 0x20, 0x20);
+        // Only the bytes expected by the caller are copied
+        run_test(false, false, false, 0x40, 0x00, 0x40, 0x10);
+        run_test(false, false, false, 0x40, 0x00, 0x10, 0x00);
+        run_test(false, false, false, 0x20, 0x10, 0x45, 0x60);
+        run_test(false, false, false, 0x20, 0x1234, 0x00, 0x20);
+    }
+
+    #[test]
+    fn return_gadget_create() {
+        run_test(false, false, true, 0x40, 0x10, 0x20, 0x00);
+        run_test(false, false, true, 0x20, 0x10, 0x45, 0x00);
+        run_test(false, false, true, 0x20, 0x1234, 0x00, 0x00);
+    }
+
+    #[test]
+    fn return_gadget_create_invalid_code() {
+        // A deployed code starting with 0xef fails in ErrorInvalidCreationCode
+        let mut memory = rand_bytes(0x40);
+        memory[0x10] = 0xef;
+        assert!(run_test_with_memory(false, false, true, memory, 0x10, 0x20, 0x00).is_err());
+    }
+
+    #[test]
+    fn return_gadget_root() {
+        run_test(false, true, true, 0x40, 0x00, 0x40, 0x00);
+        run_test(false, true, false, 0x40, 0x10, 0x20, 0x00);
+    }
+
+    #[test]
+    fn revert_gadget_call() {
+        run_test(true, false, false, 0x40, 0x10, 0x20, 0x20);
+        run_test(true, false, false, 0x40, 0x00, 0x40, 0x10);
+        run_test(true, false, false, 0x20, 0x1234, 0x00, 0x20);
+    }
+
+    #[test]
+    fn revert_gadget_create() {
+        // The reverted creation deploys nothing, and returns the bytes as
+        // return data instead
+        run_test(true, false, true, 0x40, 0x10, 0x20, 0x00);
+        run_test(true, false, true, 0x20, 0x1234, 0x00, 0x00);
+    }
+
+    fn revert_bytecode(memory_offset: u64, length: u64) -> eth_types::Bytecode {
+        bytecode! {
+            PUSH32(Word::from_big_endian(&rand_bytes(32)))
+            PUSH1(0x00)
+            MSTORE
+            PUSH32(length)
+            PUSH32(memory_offset)
+            REVERT
+        }
+    }
+
+    #[test]
+    fn revert_gadget_root() {
+        for (memory_offset, length) in [(0x00, 0x20), (0x10, 0x45), (0x1234, 0x00)] {
+            assert_eq!(
+                run_test_circuits(revert_bytecode(memory_offset, length)),
+                Ok(())
+            );
+        }
+    }
+
+    #[test]
+    fn revert_gadget_creation_tx() {
+        let geth_data = mock::new_single_tx_trace_create(&revert_bytecode(0x10, 0x20)).unwrap();
+        assert_eq!(
+            test_circuits_using_geth_data(geth_data, BytecodeTestConfig::default()),
+            Ok(())
+        );
+    }
+}
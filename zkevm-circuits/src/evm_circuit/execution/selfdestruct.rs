@@ -0,0 +1,596 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{EMPTY_CODE_HASH_LE, N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS},
+        step::ExecutionState,
+        table::{AccountFieldTag, CallContextFieldTag},
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::ConstraintBuilder,
+            from_bytes,
+            math_gadget::{AddWordsGadget, IsEqualGadget, IsZeroGadget, RangeCheckGadget},
+            Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian, ToScalar,
+};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct SelfdestructGadget<F> {
+    beneficiary: Word<F>,
+    tx_id: Cell<F>,
+    callee_address: Cell<F>,
+    rw_counter_end_of_reversion: Cell<F>,
+    is_persistent: Cell<F>,
+    is_warm: Cell<F>,
+    nonce: Cell<F>,
+    code_hash: Cell<F>,
+    nonce_is_zero: IsZeroGadget<F>,
+    code_hash_is_empty: IsEqualGadget<F>,
+    value: Word<F>,
+    value_is_zero: IsZeroGadget<F>,
+    beneficiary_balance_prev: Word<F>,
+    beneficiary_balance_prev_is_zero: IsZeroGadget<F>,
+    add_beneficiary_balance: AddWordsGadget<F, 2>,
+    callee_balance_prev: Cell<F>,
+    is_destructed_prev: Cell<F>,
+    sufficient_gas_left: RangeCheckGadget<F, N_BYTES_GAS>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for SelfdestructGadget<F> {
+    const NAME: &'static str = "SELFDESTRUCT";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::SELFDESTRUCT;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        cb.opcode_lookup(OpcodeId::SELFDESTRUCT.expr(), 1.expr());
+
+        // Pop the beneficiary from stack, of which only the lower 20 bytes are
+        // used
+        let beneficiary = cb.query_word();
+        cb.stack_pop(beneficiary.expr());
+        let beneficiary_expr = from_bytes::expr(&beneficiary.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let callee_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+        let rw_counter_end_of_reversion =
+            cb.call_context(None, CallContextFieldTag::RwCounterEndOfReversion);
+        let is_persistent = cb.call_context(None, CallContextFieldTag::IsPersistent);
+
+        // Add the beneficiary into the access list
+        let is_warm = cb.query_bool();
+        cb.account_access_list_write_with_reversion(
+            tx_id.expr(),
+            beneficiary_expr.clone(),
+            1.expr(),
+            is_warm.expr(),
+            is_persistent.expr(),
+            rw_counter_end_of_reversion.expr(),
+        );
+
+        // Read nonce and code hash of the beneficiary to check whether it's
+        // empty, where a non-existing account has the empty code hash, as read
+        // by EXTCODEHASH as well
+        let nonce = cb.query_cell();
+        cb.account_read(
+            beneficiary_expr.clone(),
+            AccountFieldTag::Nonce,
+            nonce.expr(),
+        );
+        let code_hash = cb.query_cell();
+        cb.account_read(
+            beneficiary_expr.clone(),
+            AccountFieldTag::CodeHash,
+            code_hash.expr(),
+        );
+        let nonce_is_zero = IsZeroGadget::construct(cb, nonce.expr());
+        let empty_code_hash = Word::random_linear_combine_expr(
+            EMPTY_CODE_HASH_LE.map(|byte| byte.expr()),
+            cb.power_of_randomness(),
+        );
+        let code_hash_is_empty = IsEqualGadget::construct(cb, code_hash.expr(), empty_code_hash);
+
+        // Read the balance of the callee as the value to transfer
+        let value = cb.query_word();
+        cb.account_read(
+            callee_address.expr(),
+            AccountFieldTag::Balance,
+            value.expr(),
+        );
+        let value_is_zero = IsZeroGadget::construct(cb, value.expr());
+
+        // Add the value to the beneficiary first, and then clear the balance
+        // of the callee, so the value is burnt if the beneficiary is the
+        // callee itself
+        let beneficiary_balance_prev = cb.query_word();
+        let beneficiary_balance_prev_is_zero =
+            IsZeroGadget::construct(cb, beneficiary_balance_prev.expr());
+        let add_beneficiary_balance =
+            AddWordsGadget::construct(cb, [beneficiary_balance_prev.clone(), value.clone()]);
+        cb.require_zero(
+            "Beneficiary has too much balance",
+            add_beneficiary_balance.carry().expr(),
+        );
+        cb.account_write_with_reversion(
+            beneficiary_expr,
+            AccountFieldTag::Balance,
+            add_beneficiary_balance.sum().expr(),
+            beneficiary_balance_prev.expr(),
+            is_persistent.expr(),
+            rw_counter_end_of_reversion.expr(),
+        );
+        let callee_balance_prev = cb.query_cell();
+        cb.account_write_with_reversion(
+            callee_address.expr(),
+            AccountFieldTag::Balance,
+            0.expr(),
+            callee_balance_prev.expr(),
+            is_persistent.expr(),
+            rw_counter_end_of_reversion.expr(),
+        );
+
+        // Mark the callee as destructed in the current tx
+        let is_destructed_prev = cb.query_bool();
+        cb.account_destructed_write_with_reversion(
+            tx_id.expr(),
+            callee_address.expr(),
+            1.expr(),
+            is_destructed_prev.expr(),
+            is_persistent.expr(),
+            rw_counter_end_of_reversion.expr(),
+        );
+
+        // Calculate the gas cost, which includes the cold account access cost
+        // (EIP-2929) and the cost of sending value to an empty account
+        let is_empty = nonce_is_zero.expr()
+            * beneficiary_balance_prev_is_zero.expr()
+            * code_hash_is_empty.expr();
+        let gas_cost = GasCost::SELFDESTRUCT.expr()
+            + (1.expr() - is_warm.expr()) * GasCost::COLD_ACCOUNT_ACCESS_COST.expr()
+            + (1.expr() - value_is_zero.expr()) * is_empty * GasCost::NEW_ACCOUNT.expr();
+        let gas_left = cb.curr.state.gas_left.expr() - gas_cost;
+        let sufficient_gas_left = RangeCheckGadget::construct(cb, gas_left.clone());
+
+        // Return to the caller with the gas left and without return data, like
+        // STOP
+        let restore_context =
+            RestoreContextGadget::construct(cb, 1.expr(), 0.expr(), 0.expr(), gas_left);
+
+        Self {
+            beneficiary,
+            tx_id,
+            callee_address,
+            rw_counter_end_of_reversion,
+            is_persistent,
+            is_warm,
+            nonce,
+            code_hash,
+            nonce_is_zero,
+            code_hash_is_empty,
+            value,
+            value_is_zero,
+            beneficiary_balance_prev,
+            beneficiary_balance_prev_is_zero,
+            add_beneficiary_balance,
+            callee_balance_prev,
+            is_destructed_prev,
+            sufficient_gas_left,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let beneficiary = block.rws[step.rw_indices[0]].stack_value();
+        self.beneficiary
+            .assign(region, offset, Some(beneficiary.to_le_bytes()))?;
+
+        self.tx_id
+            .assign(region, offset, Some(F::from(tx.id as u64)))?;
+        let callee_address = block.rws[step.rw_indices[2]].call_context_value();
+        self.callee_address
+            .assign(region, offset, callee_address.to_scalar())?;
+        self.rw_counter_end_of_reversion.assign(
+            region,
+            offset,
+            Some(F::from(call.rw_counter_end_of_reversion as u64)),
+        )?;
+        self.is_persistent
+            .assign(region, offset, Some(F::from(call.is_persistent as u64)))?;
+
+        let (_, is_warm) = block.rws[step.rw_indices[5]].tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Some(F::from(is_warm as u64)))?;
+
+        let [nonce, code_hash] =
+            [6, 7].map(|idx| block.rws[step.rw_indices[idx]].account_value_pair().0);
+        let nonce = nonce.to_scalar().unwrap();
+        let code_hash = Word::random_linear_combine(code_hash.to_le_bytes(), block.randomness);
+        self.nonce.assign(region, offset, Some(nonce))?;
+        self.code_hash.assign(region, offset, Some(code_hash))?;
+        self.nonce_is_zero.assign(region, offset, nonce)?;
+        self.code_hash_is_empty.assign(
+            region,
+            offset,
+            code_hash,
+            Word::random_linear_combine(EMPTY_CODE_HASH_LE, block.randomness),
+        )?;
+
+        let (value, _) = block.rws[step.rw_indices[8]].account_value_pair();
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.value_is_zero.assign(
+            region,
+            offset,
+            Word::random_linear_combine(value.to_le_bytes(), block.randomness),
+        )?;
+
+        let (beneficiary_balance, beneficiary_balance_prev) =
+            block.rws[step.rw_indices[9]].account_value_pair();
+        self.beneficiary_balance_prev.assign(
+            region,
+            offset,
+            Some(beneficiary_balance_prev.to_le_bytes()),
+        )?;
+        self.beneficiary_balance_prev_is_zero.assign(
+            region,
+            offset,
+            Word::random_linear_combine(beneficiary_balance_prev.to_le_bytes(), block.randomness),
+        )?;
+        self.add_beneficiary_balance.assign(
+            region,
+            offset,
+            [beneficiary_balance_prev, value],
+            beneficiary_balance,
+        )?;
+
+        let (_, callee_balance_prev) = block.rws[step.rw_indices[10]].account_value_pair();
+        self.callee_balance_prev.assign(
+            region,
+            offset,
+            Some(Word::random_linear_combine(
+                callee_balance_prev.to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+
+        let (_, is_destructed_prev) =
+            block.rws[step.rw_indices[11]].account_destructed_value_pair();
+        self.is_destructed_prev
+            .assign(region, offset, Some(F::from(is_destructed_prev as u64)))?;
+
+        self.sufficient_gas_left
+            .assign(region, offset, F::from(step.gas_left - step.gas_cost))?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        evm_circuit::{
+            step::ExecutionState,
+            table::{AccountFieldTag, CallContextFieldTag, RwTableTag},
+            test::run_test_circuit_incomplete_fixed_table,
+            witness::{Block, Bytecode, Call, CodeSource, ExecStep, Rw, RwMap, Transaction},
+        },
+        test_util::run_test_circuits,
+    };
+    use eth_types::{
+        bytecode,
+        evm_types::{GasCost, OpcodeId},
+        Address, ToWord, Word,
+    };
+    use halo2_proofs::arithmetic::BaseExt;
+    use pairing::bn256::Fr as Fp;
+    use sha3::{Digest, Keccak256};
+
+    fn test_ok(beneficiary: Word) {
+        let bytecode = bytecode! {
+            PUSH32(beneficiary)
+            #[start]
+            SELFDESTRUCT
+        };
+        assert_eq!(run_test_circuits(bytecode), Ok(()));
+    }
+
+    #[test]
+    fn selfdestruct_gadget_to_self() {
+        // The callee of the mock tx is always in the access list
+        test_ok(Word::zero());
+    }
+
+    #[test]
+    fn selfdestruct_gadget_to_empty_account() {
+        test_ok(Word::from(0x123));
+    }
+
+    #[test]
+    fn selfdestruct_gadget_internal_call() {
+        // The callee sends its balance to a cold and empty beneficiary, and
+        // then the caller resumes at a STOP step with the gas left by the
+        // callee, keeping its 4 state writes
+        let randomness = Fp::rand();
+        let caller_bytecode = Bytecode::new(vec![OpcodeId::STOP.as_u8()]);
+        let callee_bytecode = Bytecode::new(vec![OpcodeId::SELFDESTRUCT.as_u8()]);
+        let (caller_id, callee_id) = (1, 2);
+        let callee_address = Address::repeat_byte(0xfe);
+        let beneficiary = Address::from_low_u64_be(0x123);
+        let value = Word::from(100);
+        let empty_code_hash = Word::from_big_endian(Keccak256::digest(&[]).as_slice());
+        let caller_gas_left = 100;
+        let gas_returned = 10;
+        let gas_cost = GasCost::SELFDESTRUCT.as_u64()
+            + GasCost::COLD_ACCOUNT_ACCESS_COST.as_u64()
+            + GasCost::NEW_ACCOUNT.as_u64();
+
+        let mut rw_counter = 0;
+        let mut next_rw_counter = || {
+            rw_counter += 1;
+            rw_counter
+        };
+        let stack_rws = vec![Rw::Stack {
+            rw_counter: next_rw_counter(),
+            is_write: false,
+            call_id: callee_id,
+            stack_pointer: 1023,
+            value: beneficiary.to_word(),
+        }];
+        let mut call_context_rws = vec![
+            (CallContextFieldTag::TxId, Word::one()),
+            (CallContextFieldTag::CalleeAddress, callee_address.to_word()),
+            (CallContextFieldTag::RwCounterEndOfReversion, Word::zero()),
+            (CallContextFieldTag::IsPersistent, Word::one()),
+        ]
+        .into_iter()
+        .map(|(field_tag, value)| Rw::CallContext {
+            rw_counter: next_rw_counter(),
+            is_write: false,
+            call_id: callee_id,
+            field_tag,
+            value,
+        })
+        .collect::<Vec<_>>();
+        let access_list_rws = vec![Rw::TxAccessListAccount {
+            rw_counter: next_rw_counter(),
+            is_write: true,
+            tx_id: 1,
+            account_address: beneficiary,
+            value: true,
+            value_prev: false,
+        }];
+        let account_rws = vec![
+            (
+                false,
+                beneficiary,
+                AccountFieldTag::Nonce,
+                Word::zero(),
+                Word::zero(),
+            ),
+            (
+                false,
+                beneficiary,
+                AccountFieldTag::CodeHash,
+                empty_code_hash,
+                empty_code_hash,
+            ),
+            (
+                false,
+                callee_address,
+                AccountFieldTag::Balance,
+                value,
+                value,
+            ),
+            (
+                true,
+                beneficiary,
+                AccountFieldTag::Balance,
+                value,
+                Word::zero(),
+            ),
+            (
+                true,
+                callee_address,
+                AccountFieldTag::Balance,
+                Word::zero(),
+                value,
+            ),
+        ]
+        .into_iter()
+        .map(
+            |(is_write, account_address, field_tag, value, value_prev)| Rw::Account {
+                rw_counter: next_rw_counter(),
+                is_write,
+                account_address,
+                field_tag,
+                value,
+                value_prev,
+            },
+        )
+        .collect::<Vec<_>>();
+        let account_destructed_rws = vec![Rw::AccountDestructed {
+            rw_counter: next_rw_counter(),
+            is_write: true,
+            tx_id: 1,
+            account_address: callee_address,
+            value: true,
+            value_prev: false,
+        }];
+        // Then the call context of the caller is restored, and the callee
+        // becomes its last callee without return data
+        let restore_context_rws = vec![
+            (
+                false,
+                callee_id,
+                CallContextFieldTag::CallerId,
+                Word::from(caller_id),
+            ),
+            (false, caller_id, CallContextFieldTag::IsRoot, Word::one()),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::IsCreate,
+                Word::zero(),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::CodeSource,
+                caller_bytecode.hash,
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::ProgramCounter,
+                Word::zero(),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::StackPointer,
+                Word::from(1023),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::GasLeft,
+                Word::from(caller_gas_left),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::MemorySize,
+                Word::zero(),
+            ),
+            (
+                false,
+                caller_id,
+                CallContextFieldTag::StateWriteCounter,
+                Word::zero(),
+            ),
+            (
+                true,
+                caller_id,
+                CallContextFieldTag::LastCalleeId,
+                Word::from(callee_id),
+            ),
+            (
+                true,
+                caller_id,
+                CallContextFieldTag::LastCalleeReturnDataOffset,
+                Word::zero(),
+            ),
+            (
+                true,
+                caller_id,
+                CallContextFieldTag::LastCalleeReturnDataLength,
+                Word::zero(),
+            ),
+        ]
+        .into_iter()
+        .map(|(is_write, call_id, field_tag, value)| Rw::CallContext {
+            rw_counter: next_rw_counter(),
+            is_write,
+            call_id,
+            field_tag,
+            value,
+        });
+        call_context_rws.extend(restore_context_rws);
+        let next_step_rw_counter = next_rw_counter();
+
+        let rw_indices = std::iter::once((RwTableTag::Stack, 0))
+            .chain((0..4).map(|idx| (RwTableTag::CallContext, idx)))
+            .chain([(RwTableTag::TxAccessListAccount, 0)])
+            .chain((0..5).map(|idx| (RwTableTag::Account, idx)))
+            .chain([(RwTableTag::AccountDestructed, 0)])
+            .chain((4..16).map(|idx| (RwTableTag::CallContext, idx)))
+            .collect();
+        let rws = RwMap(
+            [
+                (RwTableTag::Stack, stack_rws),
+                (RwTableTag::CallContext, call_context_rws),
+                (RwTableTag::TxAccessListAccount, access_list_rws),
+                (RwTableTag::Account, account_rws),
+                (RwTableTag::AccountDestructed, account_destructed_rws),
+            ]
+            .into(),
+        );
+
+        let steps = vec![
+            ExecStep {
+                call_index: 1,
+                rw_indices,
+                execution_state: ExecutionState::SELFDESTRUCT,
+                rw_counter: 1,
+                program_counter: 0,
+                stack_pointer: 1023,
+                gas_left: gas_cost + gas_returned,
+                gas_cost,
+                opcode: Some(OpcodeId::SELFDESTRUCT),
+                ..Default::default()
+            },
+            ExecStep {
+                call_index: 0,
+                execution_state: ExecutionState::STOP,
+                rw_counter: next_step_rw_counter,
+                program_counter: 0,
+                stack_pointer: 1023,
+                gas_left: caller_gas_left + gas_returned,
+                state_write_counter: 4,
+                opcode: Some(OpcodeId::STOP),
+                ..Default::default()
+            },
+        ];
+
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                id: 1,
+                calls: vec![
+                    Call {
+                        id: caller_id,
+                        is_root: true,
+                        is_create: false,
+                        code_source: CodeSource::Account(caller_bytecode.hash),
+                        ..Default::default()
+                    },
+                    Call {
+                        id: callee_id,
+                        is_root: false,
+                        is_create: false,
+                        code_source: CodeSource::Account(callee_bytecode.hash),
+                        caller_id,
+                        callee_address,
+                        is_success: true,
+                        is_persistent: true,
+                        ..Default::default()
+                    },
+                ],
+                steps,
+                ..Default::default()
+            }],
+            rws,
+            bytecodes: vec![caller_bytecode, callee_bytecode],
+            ..Default::default()
+        };
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+}
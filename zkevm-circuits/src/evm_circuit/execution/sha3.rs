@@ -0,0 +1,306 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_MEMORY_WORD_SIZE,
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstraintBuilder, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget, MemoryWordSizeGadget},
+            Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::circuit_input_builder::CopyDataType;
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian};
+use halo2_proofs::{circuit::Region, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct Sha3Gadget<F> {
+    same_context: SameContextGadget<F>,
+    memory_address: MemoryAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    hash_word_size: MemoryWordSizeGadget<F, N_BYTES_MEMORY_WORD_SIZE>,
+    input_rlc: Cell<F>,
+    hash: Word<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for Sha3Gadget<F> {
+    const NAME: &'static str = "SHA3";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::SHA3;
+
+    fn configure(cb: &mut ConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let memory_offset = cb.query_cell();
+        let length = cb.query_rlc();
+        let hash = cb.query_word();
+
+        // Pop memory_offset and length from stack
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(length.expr());
+
+        let memory_address = MemoryAddressGadget::construct(cb, memory_offset, length);
+
+        // Read the hashed bytes from memory into their RLC with a lookup to
+        // the copy table, which is 0 when there's no byte
+        let input_rlc = cb.query_cell();
+        cb.condition(memory_address.has_length(), |cb| {
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                0.expr(),
+                CopyDataType::RlcAcc.expr(),
+                memory_address.offset(),
+                memory_address.address(),
+                0.expr(),
+                memory_address.length(),
+                memory_address.length(),
+                input_rlc.expr(),
+            );
+        });
+        cb.condition(1.expr() - memory_address.has_length(), |cb| {
+            cb.require_zero("input_rlc == 0 when length == 0", input_rlc.expr());
+        });
+
+        // Push the hash of the bytes, which is looked up in the Keccak table
+        cb.keccak_lookup(input_rlc.expr(), memory_address.length(), hash.expr());
+        cb.stack_push(hash.expr());
+
+        // Calculate the next memory size and the gas cost, which includes the
+        // hashing cost of every word
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [memory_address.address()],
+        );
+        let hash_word_size = MemoryWordSizeGadget::construct(cb, memory_address.length());
+        let gas_cost =
+            GasCost::COPY_SHA3.expr() * hash_word_size.expr() + memory_expansion.gas_cost();
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            // 2 stack pops + the memory reads of the copy + 1 stack push
+            rw_counter: Delta(cb.rw_counter_offset()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(1.expr()),
+            memory_word_size: To(memory_expansion.next_memory_word_size()),
+            ..Default::default()
+        };
+        let same_context =
+            SameContextGadget::construct(cb, opcode, step_state_transition, Some(gas_cost));
+
+        Self {
+            same_context,
+            memory_address,
+            memory_expansion,
+            hash_word_size,
+            input_rlc,
+            hash,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [memory_offset, length] =
+            [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].stack_value());
+        let hash = block.rws[*step.rw_indices.last().unwrap()].stack_value();
+        let memory_address =
+            self.memory_address
+                .assign(region, offset, memory_offset, length, block.randomness)?;
+
+        // The hashed bytes are read after the 2 stack pops
+        let input_rlc = step.rw_indices[2..step.rw_indices.len() - 1]
+            .iter()
+            .fold(F::zero(), |acc, idx| {
+                acc * block.randomness + F::from(block.rws[*idx].memory_value() as u64)
+            });
+        self.input_rlc.assign(region, offset, Some(input_rlc))?;
+        self.hash.assign(region, offset, Some(hash.to_le_bytes()))?;
+
+        self.memory_expansion
+            .assign(region, offset, step.memory_word_size(), [memory_address])?;
+        self.hash_word_size
+            .assign(region, offset, length.as_u64())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evm_circuit::{
+        step::ExecutionState,
+        table::RwTableTag,
+        test::{make_copy_event, rand_bytes, run_test_circuit_incomplete_fixed_table},
+        witness::{Block, Bytecode, Call, CodeSource, ExecStep, Rw, RwMap, Transaction},
+    };
+    use bus_mapping::circuit_input_builder::{CopyDataType, NumberOrHash};
+    use eth_types::{
+        evm_types::{GasCost, OpcodeId},
+        Word,
+    };
+    use halo2_proofs::arithmetic::BaseExt;
+    use pairing::bn256::Fr as Fp;
+    use sha3::{Digest, Keccak256};
+
+    fn test_ok(memory_size: u64, memory_offset: u64, length: u64) {
+        let randomness = Fp::rand();
+        let bytecode = Bytecode::new(vec![OpcodeId::SHA3.as_u8(), OpcodeId::STOP.as_u8()]);
+        let call_id = 1;
+        let memory = rand_bytes(memory_size as usize);
+
+        let mut rws = RwMap(
+            [(
+                RwTableTag::Stack,
+                vec![
+                    Rw::Stack {
+                        rw_counter: 1,
+                        is_write: false,
+                        call_id,
+                        stack_pointer: 1022,
+                        value: Word::from(memory_offset),
+                    },
+                    Rw::Stack {
+                        rw_counter: 2,
+                        is_write: false,
+                        call_id,
+                        stack_pointer: 1023,
+                        value: Word::from(length),
+                    },
+                ],
+            )]
+            .into(),
+        );
+        let mut rw_counter = 3;
+        let mut rw_indices = vec![(RwTableTag::Stack, 0), (RwTableTag::Stack, 1)];
+
+        // The bytes past the end of the memory are read as 0
+        let mut copy_events = Vec::new();
+        let mut input = Vec::new();
+        if length != 0 {
+            let buffer = (0..(memory_offset + length) as usize)
+                .map(|addr| memory.get(addr).copied().unwrap_or(0))
+                .collect::<Vec<_>>();
+            let event = make_copy_event(
+                CopyDataType::Memory,
+                NumberOrHash::Number(call_id),
+                &buffer,
+                0,
+                memory_offset,
+                CopyDataType::RlcAcc,
+                0,
+                0,
+                length as usize,
+                &mut rw_counter,
+                &mut rws,
+                &mut rw_indices,
+            );
+            input = event.bytes.clone();
+            copy_events.push(event);
+        }
+        let hash = Word::from_big_endian(Keccak256::digest(&input).as_slice());
+        rws.0.get_mut(&RwTableTag::Stack).unwrap().push(Rw::Stack {
+            rw_counter,
+            is_write: true,
+            call_id,
+            stack_pointer: 1023,
+            value: hash,
+        });
+        rw_indices.push((RwTableTag::Stack, 2));
+        rw_counter += 1;
+
+        let curr_memory_word_size = (memory_size + 31) / 32;
+        let next_memory_word_size = if length == 0 {
+            curr_memory_word_size
+        } else {
+            curr_memory_word_size.max((memory_offset + length + 31) / 32)
+        };
+        let memory_cost = |word_size: u64| {
+            GasCost::MEMORY_EXPANSION_LINEAR_COEFF.as_u64() * word_size
+                + word_size * word_size / GasCost::MEMORY_EXPANSION_QUAD_DENOMINATOR.as_u64()
+        };
+        let gas_cost = OpcodeId::SHA3.constant_gas_cost().as_u64()
+            + GasCost::COPY_SHA3.as_u64() * ((length + 31) / 32)
+            + memory_cost(next_memory_word_size)
+            - memory_cost(curr_memory_word_size);
+
+        let steps = vec![
+            ExecStep {
+                rw_indices,
+                execution_state: ExecutionState::SHA3,
+                rw_counter: 1,
+                program_counter: 0,
+                stack_pointer: 1022,
+                gas_left: gas_cost,
+                gas_cost,
+                memory_size: curr_memory_word_size * 32,
+                opcode: Some(OpcodeId::SHA3),
+                ..Default::default()
+            },
+            ExecStep {
+                execution_state: ExecutionState::STOP,
+                rw_counter,
+                program_counter: 1,
+                stack_pointer: 1023,
+                memory_size: next_memory_word_size * 32,
+                opcode: Some(OpcodeId::STOP),
+                ..Default::default()
+            },
+        ];
+
+        let block = Block {
+            randomness,
+            txs: vec![Transaction {
+                id: 1,
+                calls: vec![Call {
+                    id: call_id,
+                    is_root: true,
+                    is_create: false,
+                    code_source: CodeSource::Account(bytecode.hash),
+                    ..Default::default()
+                }],
+                steps,
+                ..Default::default()
+            }],
+            rws,
+            bytecodes: vec![bytecode],
+            keccak_inputs: vec![input],
+            copy_events,
+            ..Default::default()
+        };
+        assert_eq!(run_test_circuit_incomplete_fixed_table(block), Ok(()));
+    }
+
+    #[test]
+    fn sha3_gadget_simple() {
+        test_ok(0x40, 0x10, 0x20);
+        test_ok(0x40, 0x00, 0x40);
+    }
+
+    #[test]
+    fn sha3_gadget_memory_expansion() {
+        test_ok(0x20, 0x10, 0x45);
+        test_ok(0, 0x40, 0x20);
+    }
+
+    #[test]
+    fn sha3_gadget_zero_length() {
+        test_ok(0x20, 0x1234, 0);
+    }
+}
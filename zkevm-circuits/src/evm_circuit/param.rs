@@ -1,7 +1,15 @@
 // Step dimension
 pub(crate) const STEP_WIDTH: usize = 32;
-/// Step height
-pub const STEP_HEIGHT: usize = 14;
+/// Step height, which must fit the cells of the widest execution gadget,
+/// below the 4 rows of the 100 cells of the step state (90 execution states
+/// and 10 state cells).  BeginTx queries 629 byte cells and 113 other cells,
+/// in separate rows, so it takes 4 + 20 + 4 = 28 rows: the byte cells are the
+/// 496 of the fee and balance checks, the 125 of the contract address of a
+/// creation tx and the 8 of the gas check, and 54 of the other cells are the
+/// entries of the access list.  The 4 rows left are headroom for the
+/// Precompile gadget, which lays out the gadgets of all the precompiled
+/// contracts side by side.
+pub const STEP_HEIGHT: usize = 32;
 pub(crate) const N_CELLS_STEP_STATE: usize = 10;
 
 /// Maximum number of bytes that an integer can fit in field without wrapping
@@ -16,6 +24,20 @@ pub(crate) const N_BYTES_U64: usize = 8;
 
 pub(crate) const N_BYTES_ACCOUNT_ADDRESS: usize = 20;
 
+// Little-endian bytes of the keccak256 hash of empty bytes, which is the code
+// hash of accounts without code.
+pub(crate) const EMPTY_CODE_HASH_LE: [u8; 32] = [
+    0x70, 0xa4, 0x85, 0x5d, 0x04, 0xd8, 0xfa, 0x7b, 0x3b, 0x27, 0x82, 0xca, 0x53, 0xb6, 0x00, 0xe5,
+    0xc0, 0x03, 0xc7, 0xdc, 0xb2, 0x7d, 0x7e, 0x92, 0x3c, 0x23, 0xf7, 0x86, 0x01, 0x46, 0xd2, 0xc5,
+];
+
+// Maximum number of addresses and of storage keys in the access list of a
+// transaction, which are pre-warmed by BeginTx (EIP-2930).  The builder fails
+// with a transaction exceeding them.
+pub(crate) use bus_mapping::circuit_input_builder::{
+    MAX_ACCESS_LIST_ADDRESSES, MAX_ACCESS_LIST_STORAGE_KEYS,
+};
+
 // Number of bytes that will be used of the memory address and size.
 // If any of the other more signficant bytes are used it will always result in
 // an out-of-gas error.
@@ -24,6 +46,13 @@ pub(crate) const N_BYTES_MEMORY_WORD_SIZE: usize = 4;
 
 pub(crate) const STACK_CAPACITY: usize = 1024;
 
+// Maximum size of the code of a contract (EIP-170).
+pub(crate) const MAX_CODE_SIZE: u64 = 0x6000;
+
+// Number of bytes that will be used of stack pointer, which is at most the
+// stack capacity.
+pub(crate) const N_BYTES_STACK_POINTER: usize = 2;
+
 // Number of bytes that will be used of prorgam counter. Although the maximum
 // size of execution bytecode could be at most 128kB due to the size limit of a
 // transaction, which could be covered by 3 bytes, we still support program
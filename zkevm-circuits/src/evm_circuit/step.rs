@@ -19,7 +19,8 @@ use std::collections::VecDeque;
 pub enum ExecutionState {
     // Internal state
     BeginTx,
-    CopyToMemory,
+    EndTx,
+    Precompile,
     // Opcode successful cases
     STOP,
     ADD, // ADD, SUB
@@ -84,11 +85,10 @@ pub enum ExecutionState {
     CREATE,
     CALL,
     CALLCODE,
-    RETURN,
+    RETURN, // RETURN, REVERT
     DELEGATECALL,
     CREATE2,
     STATICCALL,
-    REVERT,
     SELFDESTRUCT,
     // Error cases
     ErrorInvalidOpcode,
@@ -100,23 +100,16 @@ pub enum ExecutionState {
     ErrorContractAddressCollision,
     ErrorMaxCodeSizeExceeded,
     ErrorInvalidCreationCode,
-    ErrorReverted,
     ErrorInvalidJump,
     ErrorReturnDataOutOfBound,
     ErrorOutOfGasConstant,
     ErrorOutOfGasPureMemory,
     ErrorOutOfGasCodeStore,
     ErrorOutOfGasSHA3,
-    ErrorOutOfGasCALLDATACOPY,
-    ErrorOutOfGasCODECOPY,
-    ErrorOutOfGasEXTCODECOPY,
-    ErrorOutOfGasRETURNDATACOPY,
+    ErrorOutOfGasMemoryCopy,
     ErrorOutOfGasLOG,
-    ErrorOutOfGasCALL,
-    ErrorOutOfGasCALLCODE,
-    ErrorOutOfGasDELEGATECALL,
+    ErrorOutOfGasCall,
     ErrorOutOfGasCREATE2,
-    ErrorOutOfGasSTATICCALL,
 }
 
 impl Default for ExecutionState {
@@ -133,7 +126,8 @@ impl ExecutionState {
     pub(crate) fn iterator() -> impl Iterator<Item = Self> {
         [
             Self::BeginTx,
-            Self::CopyToMemory,
+            Self::EndTx,
+            Self::Precompile,
             Self::STOP,
             Self::ADD,
             Self::MUL,
@@ -201,7 +195,6 @@ impl ExecutionState {
             Self::DELEGATECALL,
             Self::CREATE2,
             Self::STATICCALL,
-            Self::REVERT,
             Self::SELFDESTRUCT,
             Self::ErrorInvalidOpcode,
             Self::ErrorStackOverflow,
@@ -212,23 +205,16 @@ impl ExecutionState {
             Self::ErrorContractAddressCollision,
             Self::ErrorMaxCodeSizeExceeded,
             Self::ErrorInvalidCreationCode,
-            Self::ErrorReverted,
             Self::ErrorInvalidJump,
             Self::ErrorReturnDataOutOfBound,
             Self::ErrorOutOfGasConstant,
             Self::ErrorOutOfGasPureMemory,
             Self::ErrorOutOfGasCodeStore,
             Self::ErrorOutOfGasSHA3,
-            Self::ErrorOutOfGasCALLDATACOPY,
-            Self::ErrorOutOfGasCODECOPY,
-            Self::ErrorOutOfGasEXTCODECOPY,
-            Self::ErrorOutOfGasRETURNDATACOPY,
+            Self::ErrorOutOfGasMemoryCopy,
             Self::ErrorOutOfGasLOG,
-            Self::ErrorOutOfGasCALL,
-            Self::ErrorOutOfGasCALLCODE,
-            Self::ErrorOutOfGasDELEGATECALL,
+            Self::ErrorOutOfGasCall,
             Self::ErrorOutOfGasCREATE2,
-            Self::ErrorOutOfGasSTATICCALL,
         ]
         .iter()
         .copied()
@@ -378,11 +364,10 @@ impl ExecutionState {
             Self::CREATE => vec![OpcodeId::CREATE],
             Self::CALL => vec![OpcodeId::CALL],
             Self::CALLCODE => vec![OpcodeId::CALLCODE],
-            Self::RETURN => vec![OpcodeId::RETURN],
+            Self::RETURN => vec![OpcodeId::RETURN, OpcodeId::REVERT],
             Self::DELEGATECALL => vec![OpcodeId::DELEGATECALL],
             Self::CREATE2 => vec![OpcodeId::CREATE2],
             Self::STATICCALL => vec![OpcodeId::STATICCALL],
-            Self::REVERT => vec![OpcodeId::REVERT],
             Self::SELFDESTRUCT => vec![OpcodeId::SELFDESTRUCT],
             _ => vec![],
         }
@@ -1,4 +1,6 @@
-use crate::{evm_circuit::step::ExecutionState, impl_expr};
+use crate::{evm_circuit::step::ExecutionState, impl_expr, util::Expr};
+use bus_mapping::circuit_input_builder::CopyDataType;
+use eth_types::evm_types::OpcodeId;
 use halo2_proofs::{
     arithmetic::FieldExt,
     plonk::{Advice, Column, Expression, Fixed, VirtualCells},
@@ -27,11 +29,15 @@ pub enum FixedTableTag {
     Range32,
     Range256,
     Range512,
+    Range5,
     SignByte,
     BitwiseAnd,
     BitwiseOr,
     BitwiseXor,
     ResponsibleOpcode,
+    StackPointerRange,
+    InvalidOpcode,
+    ConstantGasCost,
 }
 
 impl FixedTableTag {
@@ -41,11 +47,15 @@ impl FixedTableTag {
             Self::Range32,
             Self::Range256,
             Self::Range512,
+            Self::Range5,
             Self::SignByte,
             Self::BitwiseAnd,
             Self::BitwiseOr,
             Self::BitwiseXor,
             Self::ResponsibleOpcode,
+            Self::StackPointerRange,
+            Self::InvalidOpcode,
+            Self::ConstantGasCost,
         ]
         .iter()
         .copied()
@@ -66,6 +76,9 @@ impl FixedTableTag {
             Self::Range512 => {
                 Box::new((0..512).map(move |value| [tag, F::from(value), F::zero(), F::zero()]))
             }
+            Self::Range5 => {
+                Box::new((0..5).map(move |value| [tag, F::from(value), F::zero(), F::zero()]))
+            }
             Self::SignByte => Box::new((0..256).map(move |value| {
                 [
                     tag,
@@ -98,6 +111,34 @@ impl FixedTableTag {
                         })
                 }))
             }
+            Self::StackPointerRange => Box::new((0..256).map(move |byte| {
+                let (min_stack_pointer, max_stack_pointer) =
+                    OpcodeId::from(byte as u8).valid_stack_ptr_range();
+                [
+                    tag,
+                    F::from(byte),
+                    F::from(min_stack_pointer as u64),
+                    F::from(max_stack_pointer as u64),
+                ]
+            })),
+            Self::InvalidOpcode => Box::new(
+                (0..256)
+                    .filter(|byte| matches!(OpcodeId::from(*byte as u8), OpcodeId::INVALID(_)))
+                    .map(move |byte| [tag, F::from(byte), F::zero(), F::zero()]),
+            ),
+            Self::ConstantGasCost => Box::new(
+                (0..256)
+                    .map(|byte| OpcodeId::from(byte as u8))
+                    .filter(|opcode| !matches!(opcode, OpcodeId::INVALID(_)))
+                    .map(move |opcode| {
+                        [
+                            tag,
+                            F::from(opcode.as_u64()),
+                            F::from(opcode.constant_gas_cost().as_u64()),
+                            F::zero(),
+                        ]
+                    }),
+            ),
         }
     }
 }
@@ -107,13 +148,27 @@ pub enum TxContextFieldTag {
     Nonce = 1,
     Gas,
     GasPrice,
+    GasFeeCap,
+    GasTipCap,
     CallerAddress,
     CalleeAddress,
     IsCreate,
     Value,
     CallDataLength,
     CallDataGasCost,
+    CallDataRlc,
+    AccessListAddressesLen,
+    AccessListStorageKeysLen,
     CallData,
+    AccessListAddress,
+    AccessListStorageKeyAddress,
+    AccessListStorageKey,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum BytecodeFieldTag {
+    Length = 1,
+    Byte,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -125,6 +180,7 @@ pub enum BlockContextFieldTag {
     Difficulty,
     BaseFee,
     BlockHash,
+    ChainId,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -161,6 +217,17 @@ pub enum AccountFieldTag {
     CodeHash,
 }
 
+/// The field of an update in the MPT table, whose account fields are numbered
+/// as in [`AccountFieldTag`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MptFieldTag {
+    Nonce = 1,
+    Balance,
+    CodeHash,
+    Storage,
+    StorageRoot,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum CallContextFieldTag {
     RwCounterEndOfReversion = 1,
@@ -194,10 +261,13 @@ pub enum CallContextFieldTag {
 
 impl_expr!(FixedTableTag);
 impl_expr!(TxContextFieldTag);
+impl_expr!(BytecodeFieldTag);
 impl_expr!(RwTableTag);
 impl_expr!(AccountFieldTag);
+impl_expr!(MptFieldTag);
 impl_expr!(CallContextFieldTag);
 impl_expr!(BlockContextFieldTag);
+impl_expr!(CopyDataType);
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub(crate) enum Table {
@@ -206,6 +276,14 @@ pub(crate) enum Table {
     Rw,
     Bytecode,
     Block,
+    Keccak,
+    EcRecover,
+    Sha256,
+    Ripemd160,
+    Ecc,
+    Modexp,
+    Blake2f,
+    Copy,
 }
 
 #[derive(Clone, Debug)]
@@ -249,12 +327,18 @@ pub(crate) enum Lookup<F> {
     Bytecode {
         /// Hash to specify which code to read.
         hash: Expression<F>,
-        /// Index to specify which byte of bytecode.
+        /// Tag to specify whether to read the length of the bytecode or one of
+        /// its bytes.
+        field_tag: Expression<F>,
+        /// Index to specify which byte of bytecode, which is only used when
+        /// field_tag is Byte, otherwise should be set to 0.
         index: Expression<F>,
-        /// Value of the index.
+        /// Value of the index, or the length of the bytecode when field_tag is
+        /// Length.
         value: Expression<F>,
         /// A boolean value to specify if the value is executable opcode or the
-        /// data portion of PUSH* operations.
+        /// data portion of PUSH* operations, which is set to 0 when field_tag
+        /// is Length.
         is_code: Expression<F>,
     },
     /// Lookup to block table, which contains constants of this block.
@@ -267,6 +351,121 @@ pub(crate) enum Lookup<F> {
         /// Value of the field.
         value: Expression<F>,
     },
+    /// Lookup to Keccak table, which contains the digests of the inputs
+    /// hashed by KECCAK256, such as the preimages of contract addresses,
+    /// proved by the Keccak circuit.
+    Keccak {
+        /// Input of the hash, in RLC encoding.
+        input_rlc: Expression<F>,
+        /// Length of the input in bytes.
+        length: Expression<F>,
+        /// Digest of the input as a word, in RLC encoding.
+        digest_rlc: Expression<F>,
+    },
+    /// Lookup to ecRecover table, which contains the signers recovered from
+    /// secp256k1 signatures, whose recovery isn't proved yet.
+    EcRecover {
+        /// Hash of the signed message, in RLC encoding.
+        msg_hash: Expression<F>,
+        /// Recovery id of the signature, in RLC encoding.
+        v: Expression<F>,
+        /// `r` of the signature, in RLC encoding.
+        r: Expression<F>,
+        /// `s` of the signature, in RLC encoding.
+        s: Expression<F>,
+        /// A boolean value to specify if the signature is valid.
+        is_valid: Expression<F>,
+        /// Address of the signer, which is 0 when the signature is invalid.
+        recovered_address: Expression<F>,
+    },
+    /// Lookup to SHA-256 table, which contains the digests of the inputs of
+    /// the SHA256 precompile proved by the SHA-256 circuit.
+    Sha256 {
+        /// Input of the hash, in RLC encoding.
+        input_rlc: Expression<F>,
+        /// Length of the input in bytes.
+        length: Expression<F>,
+        /// Digest of the input, in RLC encoding.
+        digest_rlc: Expression<F>,
+    },
+    /// Lookup to RIPEMD-160 table, which contains the digests of the inputs
+    /// of the RIPEMD160 precompile proved by the RIPEMD-160 circuit.
+    Ripemd160 {
+        /// Input of the hash, in RLC encoding.
+        input_rlc: Expression<F>,
+        /// Length of the input in bytes.
+        length: Expression<F>,
+        /// Digest of the input, in RLC encoding.
+        digest_rlc: Expression<F>,
+    },
+    /// Lookup to ECC table, which contains the calls to the ecAdd, ecMul and
+    /// ecPairing precompiles, whose curve arithmetic isn't proved yet.
+    Ecc {
+        /// Address of the precompile.
+        precompile: Expression<F>,
+        /// Input of the call, in RLC encoding.
+        input_rlc: Expression<F>,
+        /// Length of the input in bytes.
+        length: Expression<F>,
+        /// A boolean value to specify if the input is valid, otherwise the
+        /// call fails.
+        is_valid: Expression<F>,
+        /// Output of the call, in RLC encoding, which is 0 when the input is
+        /// invalid.
+        output_rlc: Expression<F>,
+    },
+    /// Lookup to MODEXP table, which contains the calls to the MODEXP
+    /// precompile proved by the MODEXP circuit.
+    Modexp {
+        /// Base, in RLC encoding.
+        base_rlc: Expression<F>,
+        /// Exponent, in RLC encoding.
+        exp_rlc: Expression<F>,
+        /// Modulus, in RLC encoding.
+        modulus_rlc: Expression<F>,
+        /// Result `base ^ exp % modulus`, in RLC encoding, which is 0 when
+        /// the modulus is 0.
+        result_rlc: Expression<F>,
+    },
+    /// Lookup to BLAKE2F table, which contains the inputs of the BLAKE2F
+    /// precompile compressed by the BLAKE2F circuit.
+    Blake2f {
+        /// Number of rounds of the compression.
+        rounds: Expression<F>,
+        /// Words of the input, in RLC encoding.
+        input_rlc: Expression<F>,
+        /// Words of the output state vector, in RLC encoding.
+        output_rlc: Expression<F>,
+    },
+    /// Lookup to copy table, which contains the copies of bytes between
+    /// buffers proved by the copy circuit, on the row of their first byte.
+    CopyTable {
+        /// Id of the source, which is a call id for memory, a transaction id
+        /// for calldata, or a code hash in RLC encoding for bytecode.
+        src_id: Expression<F>,
+        /// Type of the source, as a `CopyDataType`.
+        src_type: Expression<F>,
+        /// Id of the destination.
+        dst_id: Expression<F>,
+        /// Type of the destination, as a `CopyDataType`.
+        dst_type: Expression<F>,
+        /// Address of the first byte in the source, which is at most
+        /// src_addr_end.
+        src_addr: Expression<F>,
+        /// End of the source buffer, past which the bytes are 0.
+        src_addr_end: Expression<F>,
+        /// Address of the first byte in the destination.
+        dst_addr: Expression<F>,
+        /// Number of copied bytes.
+        length: Expression<F>,
+        /// Read/Write counter of the first memory access of the copy.
+        rw_counter: Expression<F>,
+        /// Number of memory accesses of the copy.
+        rwc_inc: Expression<F>,
+        /// RLC of the copied bytes when the source or the destination is
+        /// `CopyDataType::RlcAcc`, or 0 otherwise.
+        rlc_acc: Expression<F>,
+    },
     /// Conditional lookup enabled by the first element.
     Conditional(Expression<F>, Box<Lookup<F>>),
 }
@@ -283,6 +482,14 @@ impl<F: FieldExt> Lookup<F> {
             Self::Rw { .. } => Table::Rw,
             Self::Bytecode { .. } => Table::Bytecode,
             Self::Block { .. } => Table::Block,
+            Self::Keccak { .. } => Table::Keccak,
+            Self::EcRecover { .. } => Table::EcRecover,
+            Self::Sha256 { .. } => Table::Sha256,
+            Self::Ripemd160 { .. } => Table::Ripemd160,
+            Self::Ecc { .. } => Table::Ecc,
+            Self::Modexp { .. } => Table::Modexp,
+            Self::Blake2f { .. } => Table::Blake2f,
+            Self::CopyTable { .. } => Table::Copy,
             Self::Conditional(_, lookup) => lookup.table(),
         }
     }
@@ -308,11 +515,18 @@ impl<F: FieldExt> Lookup<F> {
             .concat(),
             Self::Bytecode {
                 hash,
+                field_tag,
                 index,
                 value,
                 is_code,
             } => {
-                vec![hash.clone(), index.clone(), value.clone(), is_code.clone()]
+                vec![
+                    hash.clone(),
+                    field_tag.clone(),
+                    index.clone(),
+                    value.clone(),
+                    is_code.clone(),
+                ]
             }
             Self::Block {
                 field_tag,
@@ -321,6 +535,91 @@ impl<F: FieldExt> Lookup<F> {
             } => {
                 vec![field_tag.clone(), number.clone(), value.clone()]
             }
+            Self::EcRecover {
+                msg_hash,
+                v,
+                r,
+                s,
+                is_valid,
+                recovered_address,
+            } => vec![
+                msg_hash.clone(),
+                v.clone(),
+                r.clone(),
+                s.clone(),
+                is_valid.clone(),
+                recovered_address.clone(),
+            ],
+            Self::Keccak {
+                input_rlc,
+                length,
+                digest_rlc,
+            }
+            | Self::Sha256 {
+                input_rlc,
+                length,
+                digest_rlc,
+            }
+            | Self::Ripemd160 {
+                input_rlc,
+                length,
+                digest_rlc,
+            } => vec![input_rlc.clone(), length.clone(), digest_rlc.clone()],
+            Self::Ecc {
+                precompile,
+                input_rlc,
+                length,
+                is_valid,
+                output_rlc,
+            } => vec![
+                precompile.clone(),
+                input_rlc.clone(),
+                length.clone(),
+                is_valid.clone(),
+                output_rlc.clone(),
+            ],
+            Self::Modexp {
+                base_rlc,
+                exp_rlc,
+                modulus_rlc,
+                result_rlc,
+            } => vec![
+                base_rlc.clone(),
+                exp_rlc.clone(),
+                modulus_rlc.clone(),
+                result_rlc.clone(),
+            ],
+            Self::Blake2f {
+                rounds,
+                input_rlc,
+                output_rlc,
+            } => vec![rounds.clone(), input_rlc.clone(), output_rlc.clone()],
+            Self::CopyTable {
+                src_id,
+                src_type,
+                dst_id,
+                dst_type,
+                src_addr,
+                src_addr_end,
+                dst_addr,
+                length,
+                rw_counter,
+                rwc_inc,
+                rlc_acc,
+            } => vec![
+                1.expr(), // is_first
+                src_id.clone(),
+                src_type.clone(),
+                dst_id.clone(),
+                dst_type.clone(),
+                src_addr.clone(),
+                src_addr_end.clone(),
+                dst_addr.clone(),
+                length.clone(),
+                rw_counter.clone(),
+                rwc_inc.clone(),
+                rlc_acc.clone(),
+            ],
             Self::Conditional(condition, lookup) => lookup
                 .input_exprs()
                 .into_iter()
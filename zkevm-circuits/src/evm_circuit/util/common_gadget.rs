@@ -1,21 +1,36 @@
 use crate::{
     evm_circuit::{
-        param::N_BYTES_GAS,
-        table::{AccountFieldTag, FixedTableTag, Lookup},
+        param::{
+            EMPTY_CODE_HASH_LE, N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE,
+            N_BYTES_U64,
+        },
+        table::{AccountFieldTag, CallContextFieldTag, FixedTableTag, Lookup},
         util::{
             constraint_builder::{ConstraintBuilder, StepStateTransition, Transition},
-            math_gadget::{AddWordsGadget, RangeCheckGadget},
-            Cell, Word,
+            from_bytes,
+            math_gadget::{
+                AddWordsGadget, ConstantDivisionGadget, IsEqualGadget, IsZeroGadget, LtGadget,
+                RangeCheckGadget,
+            },
+            memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget, MemoryWordSizeGadget},
+            select, sum, Cell, RandomLinearCombination, Word,
         },
-        witness::ExecStep,
+        witness::{Block, Call, ExecStep},
     },
     util::Expr,
 };
-use eth_types::{Field, U256};
+use bus_mapping::circuit_input_builder::{
+    get_create2_address_preimage, get_create_address_preimage,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Address, Field, ToLittleEndian, ToScalar, U256,
+};
 use halo2_proofs::{
     circuit::Region,
     plonk::{Error, Expression},
 };
+use sha3::{Digest, Keccak256};
 
 /// Construction of execution state that stays in the same call context, which
 /// lookups the opcode and verifies the execution state is responsible for it,
@@ -99,9 +114,13 @@ impl<F: Field> SameContextGadget<F> {
     }
 }
 
+/// Construction of the purchase of the gas of a transaction by the sender,
+/// which is kept even when the transaction fails, followed by the transfer of
+/// its value to the receiver, which is reverted when it fails.
 #[derive(Clone, Debug)]
 pub(crate) struct TransferWithGasFeeGadget<F> {
-    sub_sender_balance: AddWordsGadget<F, 3>,
+    sub_sender_value: AddWordsGadget<F, 2>,
+    sub_sender_gas_fee: AddWordsGadget<F, 2>,
     add_receiver_balance: AddWordsGadget<F, 2>,
 }
 
@@ -118,12 +137,19 @@ impl<F: Field> TransferWithGasFeeGadget<F> {
         let sender_balance = cb.query_word();
         let receiver_balance_prev = cb.query_word();
 
-        // Subtract sender balance by value and gas_fee
-        let sub_sender_balance =
-            AddWordsGadget::construct(cb, [sender_balance.clone(), value.clone(), gas_fee]);
+        // Subtract sender balance by value and then by gas_fee, so the balance
+        // after buying the gas is the sum of the first subtraction
+        let sub_sender_value =
+            AddWordsGadget::construct(cb, [sender_balance.clone(), value.clone()]);
+        let sub_sender_gas_fee =
+            AddWordsGadget::construct(cb, [sub_sender_value.sum().clone(), gas_fee]);
+        cb.require_zero(
+            "Sender has sufficient balance for the value",
+            sub_sender_value.carry().expr(),
+        );
         cb.require_zero(
-            "Sender has sufficient balance",
-            sub_sender_balance.carry().expr(),
+            "Sender has sufficient balance for the gas fee",
+            sub_sender_gas_fee.carry().expr(),
         );
 
         // Add receiver balance by value
@@ -134,13 +160,22 @@ impl<F: Field> TransferWithGasFeeGadget<F> {
             add_receiver_balance.carry().expr(),
         );
 
-        let sender_balance_prev = sub_sender_balance.sum();
-        let receiver_balance = add_receiver_balance.sum();
+        // Buy the gas
+        cb.account_write(
+            sender_address.clone(),
+            AccountFieldTag::Balance,
+            sub_sender_value.sum().expr(),
+            sub_sender_gas_fee.sum().expr(),
+        );
 
-        // Write with possible reversion
+        // Transfer the value with possible reversion
         for (address, balance, balance_prev) in [
-            (sender_address, &sender_balance, sender_balance_prev),
-            (receiver_address, receiver_balance, &receiver_balance_prev),
+            (sender_address, &sender_balance, sub_sender_value.sum()),
+            (
+                receiver_address,
+                add_receiver_balance.sum(),
+                &receiver_balance_prev,
+            ),
         ] {
             cb.account_write_with_reversion(
                 address,
@@ -153,7 +188,8 @@ impl<F: Field> TransferWithGasFeeGadget<F> {
         }
 
         Self {
-            sub_sender_balance,
+            sub_sender_value,
+            sub_sender_gas_fee,
             add_receiver_balance,
         }
     }
@@ -167,10 +203,16 @@ impl<F: Field> TransferWithGasFeeGadget<F> {
         value: U256,
         gas_fee: U256,
     ) -> Result<(), Error> {
-        self.sub_sender_balance.assign(
+        self.sub_sender_value.assign(
             region,
             offset,
-            [sender_balance, value, gas_fee],
+            [sender_balance, value],
+            sender_balance + value,
+        )?;
+        self.sub_sender_gas_fee.assign(
+            region,
+            offset,
+            [sender_balance + value, gas_fee],
             sender_balance_prev,
         )?;
         self.add_receiver_balance.assign(
@@ -182,3 +224,892 @@ impl<F: Field> TransferWithGasFeeGadget<F> {
         Ok(())
     }
 }
+
+/// Construction of the return of the current call to its caller, which
+/// restores the call context of the caller after the reversion of the current
+/// call if it fails.  The caller resumes with the step state saved in its
+/// call context and the gas returned by the current call, which becomes its
+/// last callee with the given return data.  This must be constructed after
+/// all the other lookups of the step.  A root call has no caller to return
+/// to, since its return ends the transaction.
+#[derive(Clone, Debug)]
+pub(crate) struct RestoreContextGadget<F> {
+    caller_id: Cell<F>,
+    caller_is_root: Cell<F>,
+    caller_is_create: Cell<F>,
+    caller_code_source: Cell<F>,
+    caller_program_counter: Cell<F>,
+    caller_stack_pointer: Cell<F>,
+    caller_gas_left: Cell<F>,
+    caller_memory_word_size: Cell<F>,
+    caller_state_write_counter: Cell<F>,
+}
+
+impl<F: Field> RestoreContextGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        is_success: Expression<F>,
+        return_data_offset: Expression<F>,
+        return_data_length: Expression<F>,
+        gas_returned: Expression<F>,
+    ) -> Self {
+        let is_root = cb.curr.state.is_root.expr();
+        cb.condition(1.expr() - is_root, |cb| {
+            // The rw counters right after the step are taken by the
+            // reversion of the current call if it fails
+            cb.skip_reversion(is_success.clone());
+
+            let caller_id = cb.call_context(None, CallContextFieldTag::CallerId);
+            let mut caller_context = |field_tag| cb.call_context(Some(caller_id.expr()), field_tag);
+            let caller_is_root = caller_context(CallContextFieldTag::IsRoot);
+            let caller_is_create = caller_context(CallContextFieldTag::IsCreate);
+            let caller_code_source = caller_context(CallContextFieldTag::CodeSource);
+            let caller_program_counter = caller_context(CallContextFieldTag::ProgramCounter);
+            let caller_stack_pointer = caller_context(CallContextFieldTag::StackPointer);
+            let caller_gas_left = caller_context(CallContextFieldTag::GasLeft);
+            let caller_memory_word_size = caller_context(CallContextFieldTag::MemorySize);
+            let caller_state_write_counter = caller_context(CallContextFieldTag::StateWriteCounter);
+
+            // The current call becomes the last callee of the caller
+            for (field_tag, value) in [
+                (
+                    CallContextFieldTag::LastCalleeId,
+                    cb.curr.state.call_id.expr(),
+                ),
+                (
+                    CallContextFieldTag::LastCalleeReturnDataOffset,
+                    return_data_offset,
+                ),
+                (
+                    CallContextFieldTag::LastCalleeReturnDataLength,
+                    return_data_length,
+                ),
+            ] {
+                cb.call_context_lookup(true.expr(), Some(caller_id.expr()), field_tag, value);
+            }
+
+            // The state writes of the current call are only kept by the
+            // caller when it succeeds
+            let state_write_counter =
+                cb.curr.state.state_write_counter.expr() + cb.state_write_counter_offset();
+            cb.require_step_state_transition(StepStateTransition {
+                rw_counter: Transition::Delta(cb.rw_counter_offset()),
+                call_id: Transition::To(caller_id.expr()),
+                is_root: Transition::To(caller_is_root.expr()),
+                is_create: Transition::To(caller_is_create.expr()),
+                code_source: Transition::To(caller_code_source.expr()),
+                program_counter: Transition::To(caller_program_counter.expr()),
+                stack_pointer: Transition::To(caller_stack_pointer.expr()),
+                gas_left: Transition::To(caller_gas_left.expr() + gas_returned),
+                memory_word_size: Transition::To(caller_memory_word_size.expr()),
+                state_write_counter: Transition::To(
+                    caller_state_write_counter.expr() + is_success * state_write_counter,
+                ),
+            });
+
+            Self {
+                caller_id,
+                caller_is_root,
+                caller_is_create,
+                caller_code_source,
+                caller_program_counter,
+                caller_stack_pointer,
+                caller_gas_left,
+                caller_memory_word_size,
+                caller_state_write_counter,
+            }
+        })
+    }
+
+    /// Assign the call context of the caller, which is read by the last rw
+    /// lookups of the step.
+    pub(crate) fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let value = |idx: usize| {
+            if call.is_root {
+                U256::zero()
+            } else {
+                let rw_offset = step.rw_indices.len() - 12;
+                block.rws[step.rw_indices[rw_offset + idx]].call_context_value()
+            }
+        };
+
+        for (idx, cell) in [
+            (0, &self.caller_id),
+            (1, &self.caller_is_root),
+            (2, &self.caller_is_create),
+            (4, &self.caller_program_counter),
+            (5, &self.caller_stack_pointer),
+            (6, &self.caller_gas_left),
+            (7, &self.caller_memory_word_size),
+            (8, &self.caller_state_write_counter),
+        ] {
+            cell.assign(region, offset, Some(F::from(value(idx).low_u64())))?;
+        }
+        self.caller_code_source.assign(
+            region,
+            offset,
+            Some(RandomLinearCombination::random_linear_combine(
+                value(3).to_le_bytes(),
+                block.randomness,
+            )),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Construction of a *CALL*/CREATE* step which doesn't create its sub-call,
+/// because a precheck of the sub-call fails.  The operands of the step are
+/// popped, 0 is pushed as its result, and the current call continues without
+/// last callee, since there's no return data.  A *CALL* adds the callee into
+/// the access list, and reads it when CALL transfers value to check whether
+/// it's empty.  The memory is expanded to the input and output of the
+/// sub-call, and the gas left is `gas_left - gas_cost + callee_gas_left`,
+/// where the gas cost includes the gas passed to the sub-call, which is all
+/// left to the current call, with the stipend of a value transfer, unless
+/// `is_callee_gas_consumed`.  This must be constructed after all the other
+/// lookups of the step.
+#[derive(Clone, Debug)]
+pub(crate) struct CallPrecheckFailureGadget<F> {
+    is_call: IsEqualGadget<F>,
+    is_callcode: IsEqualGadget<F>,
+    is_create: IsEqualGadget<F>,
+    is_create2: IsEqualGadget<F>,
+    operands: [Cell<F>; 7],
+    value_is_zero: IsZeroGadget<F>,
+    // The input is the call data of a *CALL* or the init code of a CREATE*,
+    // and the output is the return data of a *CALL*
+    input_address: MemoryAddressGadget<F>,
+    output_address: MemoryAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 2, N_BYTES_MEMORY_WORD_SIZE>,
+    // Only used in CREATE2
+    hash_word_size: MemoryWordSizeGadget<F, N_BYTES_MEMORY_WORD_SIZE>,
+    // Only used in *CALL*
+    callee_address: Word<F>,
+    tx_id: Cell<F>,
+    rw_counter_end_of_reversion: Cell<F>,
+    is_persistent: Cell<F>,
+    is_warm: Cell<F>,
+    // Only used in CALL with non-zero value
+    callee_nonce: Cell<F>,
+    callee_balance: Cell<F>,
+    callee_code_hash: Cell<F>,
+    callee_nonce_is_zero: IsZeroGadget<F>,
+    callee_balance_is_zero: IsZeroGadget<F>,
+    callee_code_hash_is_zero: IsZeroGadget<F>,
+    callee_code_hash_is_empty: IsEqualGadget<F>,
+    // One 64th of the gas left after the gas cost, which is kept by the
+    // current call when the gas passed to a creation is consumed
+    gas_left_reserved: ConstantDivisionGadget<F, N_BYTES_GAS>,
+}
+
+impl<F: Field> CallPrecheckFailureGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        opcode: Expression<F>,
+        is_callee_gas_consumed: Expression<F>,
+    ) -> Self {
+        let [is_call, is_callcode, is_create, is_create2] = [
+            OpcodeId::CALL,
+            OpcodeId::CALLCODE,
+            OpcodeId::CREATE,
+            OpcodeId::CREATE2,
+        ]
+        .map(|opcode_id| IsEqualGadget::construct(cb, opcode.clone(), opcode_id.expr()));
+
+        // CALL and CALLCODE have 7 operands, DELEGATECALL and STATICCALL have
+        // 6 operands without value, CREATE has 3 operands and CREATE2 has 4
+        // operands with salt
+        let has_value_operand = is_call.expr() + is_callcode.expr();
+        let is_creation = is_create.expr() + is_create2.expr();
+        let operand_conditions = [
+            1.expr(),
+            1.expr(),
+            1.expr(),
+            1.expr() - is_create.expr(),
+            1.expr() - is_creation.clone(),
+            1.expr() - is_creation.clone(),
+            has_value_operand.clone(),
+        ];
+        let operands = [(); 7].map(|_| cb.query_cell());
+        for (idx, (operand, condition)) in
+            operands.iter().zip(operand_conditions.iter()).enumerate()
+        {
+            cb.condition(condition.clone(), |cb| {
+                cb.stack_lookup(false.expr(), idx.expr(), operand.expr());
+            });
+        }
+        let operand_count = operand_conditions
+            .iter()
+            .fold(0.expr(), |acc, condition| acc + condition.clone());
+
+        // Push 0 as the result of the sub-call which is not created
+        cb.stack_lookup(true.expr(), operand_count.clone() - 1.expr(), 0.expr());
+
+        let value = select::expr(
+            has_value_operand.clone(),
+            operands[2].expr(),
+            is_creation.clone() * operands[0].expr(),
+        );
+        let value_is_zero = IsZeroGadget::construct(cb, value);
+        let has_value = has_value_operand.clone() * (1.expr() - value_is_zero.expr());
+
+        // The input is at the 2nd and 3rd operands of CREATE*, and follows
+        // the callee address, and the value if any, in *CALL*, where it's
+        // followed by the output
+        let operand = |idx: usize| {
+            select::expr(
+                has_value_operand.clone(),
+                operands[idx + 1].expr(),
+                operands[idx].expr(),
+            )
+        };
+        let [input_offset, output_offset] = [(); 2].map(|_| cb.query_cell());
+        let [input_length, output_length] = [(); 2].map(|_| cb.query_rlc());
+        for (name, lhs, rhs) in [
+            (
+                "Input offset",
+                input_offset.expr(),
+                select::expr(is_creation.clone(), operands[1].expr(), operand(2)),
+            ),
+            (
+                "Input length",
+                input_length.expr(),
+                select::expr(is_creation.clone(), operands[2].expr(), operand(3)),
+            ),
+            (
+                "Output offset",
+                output_offset.expr(),
+                (1.expr() - is_creation.clone()) * operand(4),
+            ),
+            (
+                "Output length",
+                output_length.expr(),
+                (1.expr() - is_creation.clone()) * operand(5),
+            ),
+        ] {
+            cb.require_equal(name, lhs, rhs);
+        }
+        let input_address = MemoryAddressGadget::construct(cb, input_offset, input_length);
+        let output_address = MemoryAddressGadget::construct(cb, output_offset, output_length);
+
+        // A *CALL* adds the callee into the access list, and CALL with
+        // non-zero value reads its nonce, balance and code hash to check
+        // whether it's empty, where the code hash of a non-existing account
+        // is 0
+        let callee_address = cb.query_word();
+        let callee_address_expr =
+            from_bytes::expr(&callee_address.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+        let is_warm = cb.query_bool();
+        let [tx_id, rw_counter_end_of_reversion, is_persistent] =
+            cb.condition(1.expr() - is_creation.clone(), |cb| {
+                cb.require_equal(
+                    "Callee address is the 2nd operand",
+                    callee_address.expr(),
+                    operands[1].expr(),
+                );
+                let [tx_id, rw_counter_end_of_reversion, is_persistent] = [
+                    CallContextFieldTag::TxId,
+                    CallContextFieldTag::RwCounterEndOfReversion,
+                    CallContextFieldTag::IsPersistent,
+                ]
+                .map(|field_tag| cb.call_context(None, field_tag));
+                cb.account_access_list_write_with_reversion(
+                    tx_id.expr(),
+                    callee_address_expr.clone(),
+                    1.expr(),
+                    is_warm.expr(),
+                    is_persistent.expr(),
+                    rw_counter_end_of_reversion.expr(),
+                );
+                [tx_id, rw_counter_end_of_reversion, is_persistent]
+            });
+        let callee_nonce = cb.query_cell();
+        let callee_balance = cb.query_cell();
+        let callee_code_hash = cb.query_cell();
+        cb.condition(is_call.expr() * (1.expr() - value_is_zero.expr()), |cb| {
+            for (field_tag, value) in [
+                (AccountFieldTag::Nonce, callee_nonce.expr()),
+                (AccountFieldTag::Balance, callee_balance.expr()),
+                (AccountFieldTag::CodeHash, callee_code_hash.expr()),
+            ] {
+                cb.account_read(callee_address_expr.clone(), field_tag, value);
+            }
+        });
+        let callee_nonce_is_zero = IsZeroGadget::construct(cb, callee_nonce.expr());
+        let callee_balance_is_zero = IsZeroGadget::construct(cb, callee_balance.expr());
+        let callee_code_hash_is_zero = IsZeroGadget::construct(cb, callee_code_hash.expr());
+        let empty_code_hash = Word::random_linear_combine_expr(
+            EMPTY_CODE_HASH_LE.map(|byte| byte.expr()),
+            cb.power_of_randomness(),
+        );
+        let callee_code_hash_is_empty =
+            IsEqualGadget::construct(cb, callee_code_hash.expr(), empty_code_hash);
+        let is_empty = callee_nonce_is_zero.expr()
+            * callee_balance_is_zero.expr()
+            * (callee_code_hash_is_zero.expr() + callee_code_hash_is_empty.expr());
+
+        // The current call has no last callee anymore
+        for field_tag in [
+            CallContextFieldTag::LastCalleeId,
+            CallContextFieldTag::LastCalleeReturnDataOffset,
+            CallContextFieldTag::LastCalleeReturnDataLength,
+        ] {
+            cb.call_context_lookup(true.expr(), None, field_tag, 0.expr());
+        }
+
+        // The gas cost of a *CALL* includes the cold account access cost
+        // (EIP-2929), the cost of transferring value and of creating a new
+        // account, and the gas cost of a CREATE* includes the hashing cost of
+        // every word of the init code of CREATE2, while both include the
+        // memory expansion cost
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr(),
+            [input_address.address(), output_address.address()],
+        );
+        let hash_word_size = MemoryWordSizeGadget::construct(cb, input_address.length());
+        let gas_cost = memory_expansion.gas_cost()
+            + select::expr(
+                is_creation,
+                OpcodeId::CREATE.constant_gas_cost().expr()
+                    + is_create2.expr() * GasCost::COPY_SHA3.expr() * hash_word_size.expr(),
+                GasCost::WARM_STORAGE_READ_COST.expr()
+                    + (1.expr() - is_warm.expr())
+                        * (GasCost::COLD_ACCOUNT_ACCESS_COST.expr()
+                            - GasCost::WARM_STORAGE_READ_COST.expr())
+                    + has_value.clone() * GasCost::CALL_WITH_VALUE.expr()
+                    + is_call.expr()
+                        * (1.expr() - value_is_zero.expr())
+                        * is_empty
+                        * GasCost::NEW_ACCOUNT.expr(),
+            );
+
+        // The gas passed to the sub-call, which is part of the gas cost, is
+        // all left to the current call with the stipend of a value transfer,
+        // unless it's consumed, where a creation passes all but one 64th of
+        // the gas left after the rest of its gas cost
+        let gas_left = cb.curr.state.gas_left.expr() - gas_cost;
+        let gas_left_reserved = ConstantDivisionGadget::construct(cb, gas_left.clone(), 64);
+        cb.require_step_state_transition(StepStateTransition {
+            rw_counter: Transition::Delta(cb.rw_counter_offset()),
+            program_counter: Transition::Delta(1.expr()),
+            stack_pointer: Transition::Delta(operand_count - 1.expr()),
+            gas_left: Transition::To(select::expr(
+                is_callee_gas_consumed,
+                gas_left_reserved.quotient(),
+                gas_left + has_value * GasCost::CALL_STIPEND.expr(),
+            )),
+            memory_word_size: Transition::To(memory_expansion.next_memory_word_size()),
+            state_write_counter: Transition::Delta(cb.state_write_counter_offset()),
+            ..StepStateTransition::default()
+        });
+
+        Self {
+            is_call,
+            is_callcode,
+            is_create,
+            is_create2,
+            operands,
+            value_is_zero,
+            input_address,
+            output_address,
+            memory_expansion,
+            hash_word_size,
+            callee_address,
+            tx_id,
+            rw_counter_end_of_reversion,
+            is_persistent,
+            is_warm,
+            callee_nonce,
+            callee_balance,
+            callee_code_hash,
+            callee_nonce_is_zero,
+            callee_balance_is_zero,
+            callee_code_hash_is_zero,
+            callee_code_hash_is_empty,
+            gas_left_reserved,
+        }
+    }
+
+    /// Return the `idx`-th operand of the step in RLC encoding.
+    pub(crate) fn operand(&self, idx: usize) -> Expression<F> {
+        self.operands[idx].expr()
+    }
+
+    /// Return the value transferred to the sub-call, which is an operand of
+    /// CALL, CALLCODE, CREATE and CREATE2.
+    pub(crate) fn value(&self) -> Expression<F> {
+        select::expr(
+            self.is_call.expr() + self.is_callcode.expr(),
+            self.operands[2].expr(),
+            select::expr(
+                self.is_create.expr() + self.is_create2.expr(),
+                self.operands[0].expr(),
+                0.expr(),
+            ),
+        )
+    }
+
+    /// Assign the operands of the step, which are read by the rw lookups of
+    /// the step starting at `rw_offset`, and the rest of the lookups that
+    /// follow them.
+    pub(crate) fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        step: &ExecStep,
+        rw_offset: usize,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        for (gadget, opcode_id) in [
+            (&self.is_call, OpcodeId::CALL),
+            (&self.is_callcode, OpcodeId::CALLCODE),
+            (&self.is_create, OpcodeId::CREATE),
+            (&self.is_create2, OpcodeId::CREATE2),
+        ] {
+            gadget.assign(
+                region,
+                offset,
+                F::from(opcode.as_u64()),
+                F::from(opcode_id.as_u64()),
+            )?;
+        }
+
+        let (operand_count, _) = opcode.stack_pop_push();
+        let operand_count = operand_count as usize;
+        let operands = (0..operand_count)
+            .map(|idx| block.rws[step.rw_indices[rw_offset + idx]].stack_value())
+            .collect::<Vec<_>>();
+        for (idx, cell) in self.operands.iter().enumerate() {
+            let value = operands.get(idx).copied().unwrap_or_default();
+            cell.assign(
+                region,
+                offset,
+                Some(Word::random_linear_combine(
+                    value.to_le_bytes(),
+                    block.randomness,
+                )),
+            )?;
+        }
+
+        let is_call = opcode == OpcodeId::CALL;
+        let is_creation = matches!(opcode, OpcodeId::CREATE | OpcodeId::CREATE2);
+        let has_value_operand = is_call || opcode == OpcodeId::CALLCODE;
+        let value = if has_value_operand {
+            operands[2]
+        } else if is_creation {
+            operands[0]
+        } else {
+            U256::zero()
+        };
+        self.value_is_zero.assign(
+            region,
+            offset,
+            Word::random_linear_combine(value.to_le_bytes(), block.randomness),
+        )?;
+        let has_value = has_value_operand && !value.is_zero();
+
+        let [input_offset, input_length, output_offset, output_length] = if is_creation {
+            [operands[1], operands[2], U256::zero(), U256::zero()]
+        } else {
+            let idx = 2 + has_value_operand as usize;
+            [
+                operands[idx],
+                operands[idx + 1],
+                operands[idx + 2],
+                operands[idx + 3],
+            ]
+        };
+        let input_address = self.input_address.assign(
+            region,
+            offset,
+            input_offset,
+            input_length,
+            block.randomness,
+        )?;
+        let output_address = self.output_address.assign(
+            region,
+            offset,
+            output_offset,
+            output_length,
+            block.randomness,
+        )?;
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [input_address, output_address],
+        )?;
+        let hash_word_size = self
+            .hash_word_size
+            .assign(region, offset, input_length.low_u64())?;
+
+        // The lookups of a *CALL* follow the operands and the result
+        let rw_offset = rw_offset + operand_count + 1;
+        let callee_address = if is_creation {
+            U256::zero()
+        } else {
+            operands[1]
+        };
+        self.callee_address
+            .assign(region, offset, Some(callee_address.to_le_bytes()))?;
+        let [tx_id, rw_counter_end_of_reversion, is_persistent] = if is_creation {
+            [U256::zero(); 3]
+        } else {
+            [0, 1, 2].map(|idx| block.rws[step.rw_indices[rw_offset + idx]].call_context_value())
+        };
+        for (cell, value) in [
+            (&self.tx_id, tx_id),
+            (
+                &self.rw_counter_end_of_reversion,
+                rw_counter_end_of_reversion,
+            ),
+            (&self.is_persistent, is_persistent),
+        ] {
+            cell.assign(region, offset, value.to_scalar())?;
+        }
+        let is_warm = !is_creation
+            && block.rws[step.rw_indices[rw_offset + 3]]
+                .tx_access_list_value_pair()
+                .1;
+        self.is_warm
+            .assign(region, offset, Some(F::from(is_warm as u64)))?;
+
+        let is_call_with_value = is_call && !value.is_zero();
+        let [nonce, balance, code_hash] = if is_call_with_value {
+            [4, 5, 6].map(|idx| {
+                block.rws[step.rw_indices[rw_offset + idx]]
+                    .account_value_pair()
+                    .0
+            })
+        } else {
+            [U256::zero(); 3]
+        };
+        let nonce = nonce.to_scalar().unwrap();
+        let balance = Word::random_linear_combine(balance.to_le_bytes(), block.randomness);
+        let code_hash = Word::random_linear_combine(code_hash.to_le_bytes(), block.randomness);
+        self.callee_nonce.assign(region, offset, Some(nonce))?;
+        self.callee_balance.assign(region, offset, Some(balance))?;
+        self.callee_code_hash
+            .assign(region, offset, Some(code_hash))?;
+        let nonce_is_zero = self.callee_nonce_is_zero.assign(region, offset, nonce)?;
+        let balance_is_zero = self
+            .callee_balance_is_zero
+            .assign(region, offset, balance)?;
+        let code_hash_is_zero = self
+            .callee_code_hash_is_zero
+            .assign(region, offset, code_hash)?;
+        let code_hash_is_empty = self.callee_code_hash_is_empty.assign(
+            region,
+            offset,
+            code_hash,
+            Word::random_linear_combine(EMPTY_CODE_HASH_LE, block.randomness),
+        )?;
+        let is_empty = nonce_is_zero == F::one()
+            && balance_is_zero == F::one()
+            && (code_hash_is_zero == F::one() || code_hash_is_empty == F::one());
+
+        let mut gas_cost = memory_expansion_gas_cost;
+        if is_creation {
+            gas_cost += OpcodeId::CREATE.constant_gas_cost().as_u64();
+            if opcode == OpcodeId::CREATE2 {
+                gas_cost += GasCost::COPY_SHA3.as_u64() * hash_word_size;
+            }
+        } else {
+            gas_cost += if is_warm {
+                GasCost::WARM_STORAGE_READ_COST.as_u64()
+            } else {
+                GasCost::COLD_ACCOUNT_ACCESS_COST.as_u64()
+            };
+            if has_value {
+                gas_cost += GasCost::CALL_WITH_VALUE.as_u64();
+            }
+            if is_call_with_value && is_empty {
+                gas_cost += GasCost::NEW_ACCOUNT.as_u64();
+            }
+        }
+        self.gas_left_reserved
+            .assign(region, offset, (step.gas_left - gas_cost) as u128)?;
+
+        Ok(())
+    }
+}
+
+/// Derivation of the address of a contract created by a CREATE or CREATE2
+/// step, which is the last 20 bytes of `keccak256(rlp([creator, nonce]))` in
+/// CREATE, or of `keccak256(0xff ++ creator ++ salt ++ keccak256(init_code))`
+/// in CREATE2, where the hashes are looked up in the Keccak table.
+#[derive(Clone, Debug)]
+pub(crate) struct ContractAddressGadget<F> {
+    creator_address: RandomLinearCombination<F, N_BYTES_ACCOUNT_ADDRESS>,
+    creator_nonce: RandomLinearCombination<F, N_BYTES_U64>,
+    // Whether each byte of the nonce is at or below its most significant
+    // non-zero byte, which are the bytes encoded in RLP
+    nonce_byte_is_encoded: [Cell<F>; N_BYTES_U64],
+    nonce_msb_is_zero: IsZeroGadget<F>,
+    nonce_lt_0x80: LtGadget<F, 1>,
+    // Whether the nonce is encoded in RLP as a single byte without prefix
+    nonce_is_single_byte: Cell<F>,
+    // `r^n` where `n` is the number of encoded bytes of the nonce
+    nonce_length_power: Cell<F>,
+    salt: Word<F>,
+    init_code_rlc: Cell<F>,
+    init_code_length: Cell<F>,
+    init_code_hash: Word<F>,
+    digest: Word<F>,
+}
+
+impl<F: Field> ContractAddressGadget<F> {
+    pub(crate) fn construct(cb: &mut ConstraintBuilder<F>, is_create2: Expression<F>) -> Self {
+        let creator_address = cb.query_rlc();
+        let creator_nonce = cb.query_rlc();
+        let nonce_byte_is_encoded = [(); N_BYTES_U64].map(|_| cb.query_bool());
+        let nonce_is_single_byte = cb.query_cell();
+        let nonce_length_power = cb.query_cell();
+        let salt = cb.query_word();
+        let init_code_rlc = cb.query_cell();
+        let init_code_length = cb.query_cell();
+        let init_code_hash = cb.query_word();
+        let digest = cb.query_word();
+
+        // The encoded bytes of the nonce are its lowest bytes up to its most
+        // significant non-zero byte
+        let mut nonce_msb = 0.expr();
+        for idx in 0..N_BYTES_U64 {
+            let is_encoded = nonce_byte_is_encoded[idx].expr();
+            let is_next_encoded = nonce_byte_is_encoded
+                .get(idx + 1)
+                .map_or(0.expr(), |cell| cell.expr());
+            cb.require_zero(
+                "Nonce byte above an encoded byte is not encoded",
+                is_next_encoded.clone() * (1.expr() - is_encoded.clone()),
+            );
+            cb.require_zero(
+                "Nonce byte which is not encoded is zero",
+                (1.expr() - is_encoded.clone()) * creator_nonce.cells[idx].expr(),
+            );
+            nonce_msb =
+                nonce_msb + (is_encoded - is_next_encoded) * creator_nonce.cells[idx].expr();
+        }
+        let nonce_msb_is_zero = IsZeroGadget::construct(cb, nonce_msb);
+        cb.require_equal(
+            "The most significant encoded byte of the nonce is not zero",
+            nonce_msb_is_zero.expr(),
+            1.expr() - nonce_byte_is_encoded[0].expr(),
+        );
+        let nonce_length = sum::expr(&nonce_byte_is_encoded);
+
+        // The nonce is encoded as a single byte if it's in [1, 0x80), or as
+        // `0x80 + n` followed by its `n` encoded bytes otherwise
+        let nonce_lt_0x80 = LtGadget::construct(cb, creator_nonce.cells[0].expr(), 0x80.expr());
+        cb.require_equal(
+            "Nonce is encoded as a single byte",
+            nonce_is_single_byte.expr(),
+            nonce_byte_is_encoded[0].expr()
+                * (1.expr() - nonce_byte_is_encoded[1].expr())
+                * nonce_lt_0x80.expr(),
+        );
+        let power_of_randomness = cb.power_of_randomness().to_vec();
+        cb.require_equal(
+            "nonce_length_power == r^nonce_length",
+            nonce_length_power.expr(),
+            (0..=N_BYTES_U64).fold(0.expr(), |acc, length| {
+                let is_encoded = |idx: usize| {
+                    nonce_byte_is_encoded
+                        .get(idx)
+                        .map_or(0.expr(), |cell| cell.expr())
+                };
+                let has_length = if length == 0 {
+                    1.expr() - is_encoded(0)
+                } else {
+                    is_encoded(length - 1) - is_encoded(length)
+                };
+                let power = if length == 0 {
+                    1.expr()
+                } else {
+                    power_of_randomness[length - 1].clone()
+                };
+                acc + has_length * power
+            }),
+        );
+        let nonce_rlp_length =
+            1.expr() + (1.expr() - nonce_is_single_byte.expr()) * nonce_length.clone();
+        let nonce_rlp_rlc = creator_nonce.expr()
+            + (1.expr() - nonce_is_single_byte.expr())
+                * (0x80.expr() + nonce_length)
+                * nonce_length_power.expr();
+        let nonce_rlp_length_power = power_of_randomness[0].clone()
+            * (nonce_is_single_byte.expr()
+                + (1.expr() - nonce_is_single_byte.expr()) * nonce_length_power.expr());
+
+        // The preimage of CREATE is the RLP encoding of the list of the
+        // creator address, which is 20 bytes prefixed by 0x94, and the nonce
+        let list_length = 1.expr() + N_BYTES_ACCOUNT_ADDRESS.expr() + nonce_rlp_length.clone();
+        let create_preimage_rlc =
+            (((0xc0.expr() + list_length.clone()) * power_of_randomness[0].clone() + 0x94.expr())
+                * power_of_randomness[N_BYTES_ACCOUNT_ADDRESS - 1].clone()
+                + creator_address.expr())
+                * nonce_rlp_length_power
+                + nonce_rlp_rlc;
+        let create_preimage_length = 1.expr() + list_length;
+
+        // The preimage of CREATE2 is 0xff, the creator address, the salt and
+        // the hash of the init code
+        let power_of_randomness_32 =
+            power_of_randomness[30].clone() * power_of_randomness[0].clone();
+        let create2_preimage_rlc = ((0xff.expr()
+            * power_of_randomness[N_BYTES_ACCOUNT_ADDRESS - 1].clone()
+            + creator_address.expr())
+            * power_of_randomness_32.clone()
+            + salt.expr())
+            * power_of_randomness_32
+            + init_code_hash.expr();
+        let create2_preimage_length = (1 + N_BYTES_ACCOUNT_ADDRESS + 32 + 32).expr();
+        cb.add_lookup(
+            "Init code hash lookup",
+            Lookup::Keccak {
+                input_rlc: init_code_rlc.expr(),
+                length: init_code_length.expr(),
+                digest_rlc: init_code_hash.expr(),
+            }
+            .conditional(is_create2.clone()),
+        );
+
+        cb.keccak_lookup(
+            select::expr(
+                is_create2.clone(),
+                create2_preimage_rlc,
+                create_preimage_rlc,
+            ),
+            select::expr(is_create2, create2_preimage_length, create_preimage_length),
+            digest.expr(),
+        );
+
+        Self {
+            creator_address,
+            creator_nonce,
+            nonce_byte_is_encoded,
+            nonce_msb_is_zero,
+            nonce_lt_0x80,
+            nonce_is_single_byte,
+            nonce_length_power,
+            salt,
+            init_code_rlc,
+            init_code_length,
+            init_code_hash,
+            digest,
+        }
+    }
+
+    pub(crate) fn creator_address(&self) -> Expression<F> {
+        from_bytes::expr(&self.creator_address.cells)
+    }
+
+    pub(crate) fn creator_nonce(&self) -> Expression<F> {
+        from_bytes::expr(&self.creator_nonce.cells)
+    }
+
+    /// Return the salt of CREATE2 in RLC encoding.
+    pub(crate) fn salt(&self) -> Expression<F> {
+        self.salt.expr()
+    }
+
+    /// Return the RLC of the init code of CREATE2, where the first byte has
+    /// the highest power of the randomness.
+    pub(crate) fn init_code_rlc(&self) -> Expression<F> {
+        self.init_code_rlc.expr()
+    }
+
+    /// Return the length of the init code of CREATE2.
+    pub(crate) fn init_code_length(&self) -> Expression<F> {
+        self.init_code_length.expr()
+    }
+
+    pub(crate) fn contract_address(&self) -> Expression<F> {
+        from_bytes::expr(&self.digest.cells[..N_BYTES_ACCOUNT_ADDRESS])
+    }
+
+    /// Assign the derivation of the contract address, where `salt` and
+    /// `init_code` are only used in CREATE2.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        is_create2: bool,
+        creator_address: Address,
+        creator_nonce: u64,
+        salt: U256,
+        init_code: &[u8],
+        randomness: F,
+    ) -> Result<(), Error> {
+        let mut creator_address_bytes = creator_address.to_fixed_bytes();
+        creator_address_bytes.reverse();
+        self.creator_address
+            .assign(region, offset, Some(creator_address_bytes))?;
+        let nonce_bytes = creator_nonce.to_le_bytes();
+        self.creator_nonce
+            .assign(region, offset, Some(nonce_bytes))?;
+
+        let nonce_length = (64 - creator_nonce.leading_zeros() as usize + 7) / 8;
+        for (idx, cell) in self.nonce_byte_is_encoded.iter().enumerate() {
+            cell.assign(region, offset, Some(F::from((idx < nonce_length) as u64)))?;
+        }
+        let nonce_msb = if nonce_length == 0 {
+            0
+        } else {
+            nonce_bytes[nonce_length - 1]
+        };
+        self.nonce_msb_is_zero
+            .assign(region, offset, F::from(nonce_msb as u64))?;
+        self.nonce_lt_0x80.assign(
+            region,
+            offset,
+            F::from(nonce_bytes[0] as u64),
+            F::from(0x80),
+        )?;
+        self.nonce_is_single_byte.assign(
+            region,
+            offset,
+            Some(F::from((nonce_length == 1 && nonce_bytes[0] < 0x80) as u64)),
+        )?;
+        self.nonce_length_power.assign(
+            region,
+            offset,
+            Some(randomness.pow(&[nonce_length as u64, 0, 0, 0])),
+        )?;
+
+        self.salt.assign(region, offset, Some(salt.to_le_bytes()))?;
+        self.init_code_rlc.assign(
+            region,
+            offset,
+            Some(init_code.iter().fold(F::zero(), |acc, byte| {
+                acc * randomness + F::from(*byte as u64)
+            })),
+        )?;
+        self.init_code_length
+            .assign(region, offset, Some(F::from(init_code.len() as u64)))?;
+        let keccak = |input: &[u8]| U256::from_big_endian(Keccak256::digest(input).as_slice());
+        self.init_code_hash
+            .assign(region, offset, Some(keccak(init_code).to_le_bytes()))?;
+
+        let preimage = if is_create2 {
+            get_create2_address_preimage(creator_address, salt, init_code)
+        } else {
+            get_create_address_preimage(creator_address, creator_nonce.into())
+        };
+        self.digest
+            .assign(region, offset, Some(keccak(&preimage).to_le_bytes()))?;
+
+        Ok(())
+    }
+}
@@ -2,8 +2,8 @@ use crate::{
     evm_circuit::{
         step::{ExecutionState, Preset, Step},
         table::{
-            AccountFieldTag, CallContextFieldTag, FixedTableTag, Lookup, RwTableTag,
-            TxContextFieldTag,
+            AccountFieldTag, BytecodeFieldTag, CallContextFieldTag, FixedTableTag, Lookup,
+            RwTableTag, TxContextFieldTag,
         },
         util::{Cell, RandomLinearCombination, Word},
     },
@@ -31,6 +31,7 @@ pub(crate) enum Transition<T> {
     Same,
     Delta(T),
     To(T),
+    Any,
 }
 
 impl<F> Default for Transition<F> {
@@ -62,6 +63,21 @@ impl<F: FieldExt> StepStateTransition<F> {
             ..Self::default()
         }
     }
+
+    pub(crate) fn any() -> Self {
+        Self {
+            rw_counter: Transition::Any,
+            call_id: Transition::Any,
+            is_root: Transition::Any,
+            is_create: Transition::Any,
+            code_source: Transition::Any,
+            program_counter: Transition::Any,
+            stack_pointer: Transition::Any,
+            gas_left: Transition::Any,
+            memory_word_size: Transition::Any,
+            state_write_counter: Transition::Any,
+        }
+    }
 }
 
 #[derive(Default)]
@@ -174,7 +190,7 @@ pub(crate) struct ConstraintBuilder<'a, F> {
     rw_counter_offset: Expression<F>,
     program_counter_offset: usize,
     stack_pointer_offset: i32,
-    state_write_counter_offset: usize,
+    state_write_counter_offset: Expression<F>,
     in_next_step: bool,
 }
 
@@ -198,7 +214,7 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
             rw_counter_offset: 0.expr(),
             program_counter_offset: 0,
             stack_pointer_offset: 0,
-            state_write_counter_offset: 0,
+            state_write_counter_offset: 0.expr(),
             in_next_step: false,
         }
     }
@@ -457,6 +473,7 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
                     self.require_equal(name, next.expr(), curr.expr() + delta)
                 }
                 Transition::To(to) => self.require_equal(name, next.expr(), to),
+                Transition::Any => {}
             }
         }
     }
@@ -465,6 +482,7 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
 
     pub(crate) fn range_lookup(&mut self, value: Expression<F>, range: u64) {
         let (name, tag) = match range {
+            5 => ("Range5", FixedTableTag::Range5),
             16 => ("Range16", FixedTableTag::Range16),
             32 => ("Range32", FixedTableTag::Range32),
             256 => ("Range256", FixedTableTag::Range256),
@@ -480,6 +498,16 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         );
     }
 
+    pub(crate) fn constant_gas_lookup(&mut self, opcode: Expression<F>, gas: Expression<F>) {
+        self.add_lookup(
+            "Constant gas cost lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::ConstantGasCost.expr(),
+                values: [opcode, gas, 0.expr()],
+            },
+        );
+    }
+
     // Opcode
 
     pub(crate) fn opcode_lookup(&mut self, opcode: Expression<F>, is_code: Expression<F>) {
@@ -506,6 +534,7 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
             "Opcode lookup",
             Lookup::Bytecode {
                 hash: self.curr.state.code_source.expr(),
+                field_tag: BytecodeFieldTag::Byte.expr(),
                 index,
                 value: opcode,
                 is_code,
@@ -514,6 +543,40 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         );
     }
 
+    // Bytecode table
+
+    pub(crate) fn bytecode_lookup(
+        &mut self,
+        code_hash: Expression<F>,
+        index: Expression<F>,
+        value: Expression<F>,
+        is_code: Expression<F>,
+    ) {
+        self.add_lookup(
+            "Bytecode (byte) lookup",
+            Lookup::Bytecode {
+                hash: code_hash,
+                field_tag: BytecodeFieldTag::Byte.expr(),
+                index,
+                value,
+                is_code,
+            },
+        )
+    }
+
+    pub(crate) fn bytecode_length(&mut self, code_hash: Expression<F>, value: Expression<F>) {
+        self.add_lookup(
+            "Bytecode (length) lookup",
+            Lookup::Bytecode {
+                hash: code_hash,
+                field_tag: BytecodeFieldTag::Length.expr(),
+                index: 0.expr(),
+                value,
+                is_code: 0.expr(),
+            },
+        )
+    }
+
     // Tx context
 
     pub(crate) fn tx_context(
@@ -573,6 +636,177 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         );
     }
 
+    // Keccak
+    pub(crate) fn keccak_lookup(
+        &mut self,
+        input_rlc: Expression<F>,
+        length: Expression<F>,
+        digest_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "Keccak lookup",
+            Lookup::Keccak {
+                input_rlc,
+                length,
+                digest_rlc,
+            },
+        );
+    }
+
+    // EcRecover
+    pub(crate) fn ecrecover_lookup(
+        &mut self,
+        msg_hash: Expression<F>,
+        v: Expression<F>,
+        r: Expression<F>,
+        s: Expression<F>,
+        is_valid: Expression<F>,
+        recovered_address: Expression<F>,
+    ) {
+        self.add_lookup(
+            "EcRecover lookup",
+            Lookup::EcRecover {
+                msg_hash,
+                v,
+                r,
+                s,
+                is_valid,
+                recovered_address,
+            },
+        );
+    }
+
+    // Sha256
+    pub(crate) fn sha256_lookup(
+        &mut self,
+        input_rlc: Expression<F>,
+        length: Expression<F>,
+        digest_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "Sha256 lookup",
+            Lookup::Sha256 {
+                input_rlc,
+                length,
+                digest_rlc,
+            },
+        );
+    }
+
+    // Ripemd160
+    pub(crate) fn ripemd160_lookup(
+        &mut self,
+        input_rlc: Expression<F>,
+        length: Expression<F>,
+        digest_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "Ripemd160 lookup",
+            Lookup::Ripemd160 {
+                input_rlc,
+                length,
+                digest_rlc,
+            },
+        );
+    }
+
+    // Ecc
+    pub(crate) fn ecc_lookup(
+        &mut self,
+        precompile: Expression<F>,
+        input_rlc: Expression<F>,
+        length: Expression<F>,
+        is_valid: Expression<F>,
+        output_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "Ecc lookup",
+            Lookup::Ecc {
+                precompile,
+                input_rlc,
+                length,
+                is_valid,
+                output_rlc,
+            },
+        );
+    }
+
+    // Modexp
+    pub(crate) fn modexp_lookup(
+        &mut self,
+        base_rlc: Expression<F>,
+        exp_rlc: Expression<F>,
+        modulus_rlc: Expression<F>,
+        result_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "Modexp lookup",
+            Lookup::Modexp {
+                base_rlc,
+                exp_rlc,
+                modulus_rlc,
+                result_rlc,
+            },
+        );
+    }
+
+    // Blake2f
+    pub(crate) fn blake2f_lookup(
+        &mut self,
+        rounds: Expression<F>,
+        input_rlc: Expression<F>,
+        output_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "Blake2f lookup",
+            Lookup::Blake2f {
+                rounds,
+                input_rlc,
+                output_rlc,
+            },
+        );
+    }
+
+    // Copy Table
+
+    /// Look up a copy of `length` bytes in the copy table, whose memory
+    /// accesses take the next `rwc_inc` rw counters, and increase the
+    /// rw_counter_offset by them.  `rlc_acc` is the RLC of the bytes when the
+    /// source or the destination is an RLC, and 0 otherwise.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn copy_table_lookup(
+        &mut self,
+        src_id: Expression<F>,
+        src_type: Expression<F>,
+        dst_id: Expression<F>,
+        dst_type: Expression<F>,
+        src_addr: Expression<F>,
+        src_addr_end: Expression<F>,
+        dst_addr: Expression<F>,
+        length: Expression<F>,
+        rwc_inc: Expression<F>,
+        rlc_acc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "copy lookup",
+            Lookup::CopyTable {
+                src_id,
+                src_type,
+                dst_id,
+                dst_type,
+                src_addr,
+                src_addr_end,
+                dst_addr,
+                length,
+                rw_counter: self.curr.state.rw_counter.expr() + self.rw_counter_offset.clone(),
+                rwc_inc: rwc_inc.clone(),
+                rlc_acc,
+            },
+        );
+        self.rw_counter_offset = self.rw_counter_offset.clone()
+            + self.cb.condition.clone().unwrap_or_else(|| 1.expr()) * rwc_inc;
+    }
+
     // Rw
 
     /// Add a Lookup::Rw without increasing the rw_counter_offset, which is
@@ -626,11 +860,15 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
     ) {
         self.rw_lookup(name, true.expr(), tag, values.clone());
 
-        // Revert if is_persistent is 0
-        self.condition(1.expr() - is_persistent, |cb| {
+        // Revert if is_persistent is 0, where the write itself may be under a
+        // condition, which the reversion is under as well
+        let condition = self.cb.condition.take();
+        let is_reverted =
+            condition.clone().unwrap_or_else(|| 1.expr()) * (1.expr() - is_persistent);
+        self.condition(is_reverted, |cb| {
             // Calculate state_write_counter so far
             let state_write_counter =
-                cb.curr.state.state_write_counter.expr() + cb.state_write_counter_offset.expr();
+                cb.curr.state.state_write_counter.expr() + cb.state_write_counter_offset.clone();
 
             // Swap value and value_prev respect to tag
             if tag.is_reversible() {
@@ -645,8 +883,10 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
                 values,
             )
         });
+        self.cb.condition = condition;
 
-        self.state_write_counter_offset += 1;
+        self.state_write_counter_offset = self.state_write_counter_offset.clone()
+            + self.cb.condition.clone().unwrap_or_else(|| 1.expr());
     }
 
     // Access list
@@ -676,6 +916,97 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         value - value_prev
     }
 
+    pub(crate) fn account_access_list_read(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        value: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "TxAccessListAccount read",
+            false.expr(),
+            RwTableTag::TxAccessListAccount,
+            [
+                tx_id,
+                account_address,
+                0.expr(),
+                value.clone(),
+                value,
+                0.expr(),
+                0.expr(),
+            ],
+        );
+    }
+
+    pub(crate) fn account_access_list_write_with_reversion(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        value: Expression<F>,
+        value_prev: Expression<F>,
+        is_persistent: Expression<F>,
+        rw_counter_end_of_reversion: Expression<F>,
+    ) {
+        self.state_write_with_reversion(
+            "TxAccessListAccount write with reversion",
+            RwTableTag::TxAccessListAccount,
+            [
+                tx_id,
+                account_address,
+                0.expr(),
+                value,
+                value_prev,
+                0.expr(),
+                0.expr(),
+            ],
+            is_persistent,
+            rw_counter_end_of_reversion,
+        );
+    }
+
+    pub(crate) fn account_storage_access_list_write(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        storage_key: Expression<F>,
+        value: Expression<F>,
+        value_prev: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "TxAccessListAccountStorage write",
+            true.expr(),
+            RwTableTag::TxAccessListAccountStorage,
+            [
+                tx_id,
+                account_address,
+                storage_key,
+                value,
+                value_prev,
+                0.expr(),
+                0.expr(),
+            ],
+        );
+    }
+
+    // Tx refund
+
+    pub(crate) fn tx_refund_read(&mut self, tx_id: Expression<F>, value: Expression<F>) {
+        self.rw_lookup(
+            "TxRefund read",
+            false.expr(),
+            RwTableTag::TxRefund,
+            [
+                tx_id,
+                0.expr(),
+                0.expr(),
+                value.clone(),
+                value,
+                0.expr(),
+                0.expr(),
+            ],
+        );
+    }
+
     // Account
 
     pub(crate) fn account_read(
@@ -749,6 +1080,34 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         );
     }
 
+    // Account destructed
+
+    pub(crate) fn account_destructed_write_with_reversion(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        value: Expression<F>,
+        value_prev: Expression<F>,
+        is_persistent: Expression<F>,
+        rw_counter_end_of_reversion: Expression<F>,
+    ) {
+        self.state_write_with_reversion(
+            "AccountDestructed write with reversion",
+            RwTableTag::AccountDestructed,
+            [
+                tx_id,
+                account_address,
+                0.expr(),
+                value,
+                value_prev,
+                0.expr(),
+                0.expr(),
+            ],
+            is_persistent,
+            rw_counter_end_of_reversion,
+        );
+    }
+
     // Call context
 
     pub(crate) fn call_context(
@@ -784,6 +1143,42 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         );
     }
 
+    /// Check the current call fails, and that its reversible state writes are
+    /// reverted right after the current step, which is the last step of the
+    /// call.
+    pub(crate) fn require_call_failure(&mut self) {
+        self.call_context_lookup(false.expr(), None, CallContextFieldTag::IsSuccess, 0.expr());
+
+        // The reversions take the rw counters following the last lookup of
+        // the current step, one for each state write so far
+        let rw_counter_end_of_step =
+            self.curr.state.rw_counter.expr() + self.rw_counter_offset.clone();
+        let state_write_counter =
+            self.curr.state.state_write_counter.expr() + self.state_write_counter_offset.clone();
+        self.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::RwCounterEndOfReversion,
+            rw_counter_end_of_step + state_write_counter,
+        );
+    }
+
+    /// Increase the rw_counter_offset by the rw counters taken by the
+    /// reversion of the current call when it fails, which follows the last
+    /// lookup done by [`Self::require_call_failure`].
+    pub(crate) fn skip_reversion(&mut self, is_success: Expression<F>) {
+        let state_write_counter =
+            self.curr.state.state_write_counter.expr() + self.state_write_counter_offset.clone();
+        self.rw_counter_offset = self.rw_counter_offset.clone()
+            + self.cb.condition.clone().unwrap_or_else(|| 1.expr())
+                * (1.expr() - is_success)
+                * state_write_counter;
+    }
+
+    pub(crate) fn state_write_counter_offset(&self) -> Expression<F> {
+        self.state_write_counter_offset.clone()
+    }
+
     // Stack
 
     pub(crate) fn stack_pop(&mut self, value: Expression<F>) {
@@ -825,13 +1220,14 @@ impl<'a, F: FieldExt> ConstraintBuilder<'a, F> {
         is_write: Expression<F>,
         memory_address: Expression<F>,
         byte: Expression<F>,
+        call_id: Option<Expression<F>>,
     ) {
         self.rw_lookup(
             "Memory lookup",
             is_write,
             RwTableTag::Memory,
             [
-                self.curr.state.call_id.expr(),
+                call_id.unwrap_or_else(|| self.curr.state.call_id.expr()),
                 memory_address,
                 0.expr(),
                 byte,
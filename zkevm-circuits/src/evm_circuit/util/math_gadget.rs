@@ -612,6 +612,68 @@ impl<F: Field, const N_BYTES: usize> ComparisonGadget<F, N_BYTES> {
     }
 }
 
+/// Returns `1` when `lhs < rhs`, and returns `0` otherwise, where lhs and rhs
+/// are 256-bit words. The high 128 bits are compared first, and the low 128
+/// bits decide only when the high ones are equal.
+#[derive(Clone, Debug)]
+pub(crate) struct LtWordGadget<F> {
+    comparison_hi: ComparisonGadget<F, 16>,
+    lt_lo: LtGadget<F, 16>,
+}
+
+impl<F: Field> LtWordGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut ConstraintBuilder<F>,
+        lhs: &util::Word<F>,
+        rhs: &util::Word<F>,
+    ) -> Self {
+        let comparison_hi = ComparisonGadget::construct(
+            cb,
+            from_bytes::expr(&lhs.cells[16..]),
+            from_bytes::expr(&rhs.cells[16..]),
+        );
+        let lt_lo = LtGadget::construct(
+            cb,
+            from_bytes::expr(&lhs.cells[..16]),
+            from_bytes::expr(&rhs.cells[..16]),
+        );
+
+        Self {
+            comparison_hi,
+            lt_lo,
+        }
+    }
+
+    pub(crate) fn expr(&self) -> Expression<F> {
+        let (lt_hi, eq_hi) = self.comparison_hi.expr();
+        select::expr(lt_hi, 1.expr(), eq_hi * self.lt_lo.expr())
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Word,
+        rhs: Word,
+    ) -> Result<(), Error> {
+        let (lhs, rhs) = (lhs.to_le_bytes(), rhs.to_le_bytes());
+        self.comparison_hi.assign(
+            region,
+            offset,
+            from_bytes::value(&lhs[16..]),
+            from_bytes::value(&rhs[16..]),
+        )?;
+        self.lt_lo.assign(
+            region,
+            offset,
+            from_bytes::value(&lhs[..16]),
+            from_bytes::value(&rhs[..16]),
+        )?;
+
+        Ok(())
+    }
+}
+
 /// Returns (is_a, is_b):
 /// - `is_a` is `1` when `value == a`, else `0`
 /// - `is_b` is `1` when `value == b`, else `0`
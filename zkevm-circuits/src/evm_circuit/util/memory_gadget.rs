@@ -154,15 +154,105 @@ impl<F: FieldExt> MemoryAddressGadget<F> {
     }
 }
 
+/// Decodes the dynamic memory offset and length as full words, which is useful
+/// for error cases where the offset and length are not guaranteed to fit in
+/// [`N_BYTES_MEMORY_ADDRESS`] bytes. When they don't fit, the memory access
+/// always runs out of gas.
+#[derive(Clone, Debug)]
+pub(crate) struct MemoryExpandedAddressGadget<F> {
+    memory_offset: Word<F>,
+    memory_length: Word<F>,
+    memory_length_is_zero: IsZeroGadget<F>,
+    address_in_range: IsZeroGadget<F>,
+}
+
+impl<F: FieldExt> MemoryExpandedAddressGadget<F> {
+    pub(crate) fn construct(cb: &mut ConstraintBuilder<F>) -> Self {
+        let memory_offset = cb.query_word();
+        let memory_length = cb.query_word();
+
+        let memory_length_is_zero = IsZeroGadget::construct(cb, sum::expr(&memory_length.cells));
+        // The offset is ignored when the length is zero
+        let address_in_range = IsZeroGadget::construct(
+            cb,
+            address_high::expr(&memory_length)
+                + (1.expr() - memory_length_is_zero.expr()) * address_high::expr(&memory_offset),
+        );
+
+        Self {
+            memory_offset,
+            memory_length,
+            memory_length_is_zero,
+            address_in_range,
+        }
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        memory_offset: U256,
+        memory_length: U256,
+    ) -> Result<u64, Error> {
+        let memory_offset_bytes = memory_offset.to_le_bytes();
+        let memory_length_bytes = memory_length.to_le_bytes();
+        let memory_length_is_zero = memory_length.is_zero();
+        self.memory_offset
+            .assign(region, offset, Some(memory_offset_bytes))?;
+        self.memory_length
+            .assign(region, offset, Some(memory_length_bytes))?;
+        self.memory_length_is_zero
+            .assign(region, offset, sum::value(&memory_length_bytes))?;
+        self.address_in_range.assign(
+            region,
+            offset,
+            address_high::value::<F>(memory_length_bytes)
+                + if memory_length_is_zero {
+                    F::zero()
+                } else {
+                    address_high::value::<F>(memory_offset_bytes)
+                },
+        )?;
+        Ok(if memory_length_is_zero {
+            0
+        } else {
+            address_low::value(memory_offset_bytes) + address_low::value(memory_length_bytes)
+        })
+    }
+
+    pub(crate) fn offset_rlc(&self) -> Expression<F> {
+        self.memory_offset.expr()
+    }
+
+    pub(crate) fn length_rlc(&self) -> Expression<F> {
+        self.memory_length.expr()
+    }
+
+    pub(crate) fn address_in_range(&self) -> Expression<F> {
+        self.address_in_range.expr()
+    }
+
+    pub(crate) fn length(&self) -> Expression<F> {
+        address_low::expr(&self.memory_length)
+    }
+
+    pub(crate) fn address(&self) -> Expression<F> {
+        (1.expr() - self.memory_length_is_zero.expr())
+            * (address_low::expr(&self.memory_offset) + address_low::expr(&self.memory_length))
+    }
+}
+
 /// Calculates the memory size in words required for a memory access at the
 /// specified address.
 /// `memory_word_size = ceil(address/32) = floor((address + 31) / 32)`
 #[derive(Clone, Debug)]
-pub(crate) struct MemoryWordSizeGadget<F> {
+pub(crate) struct MemoryWordSizeGadget<F, const N_BYTES_MEMORY_WORD_SIZE: usize> {
     memory_word_size: ConstantDivisionGadget<F, N_BYTES_MEMORY_WORD_SIZE>,
 }
 
-impl<F: Field> MemoryWordSizeGadget<F> {
+impl<F: Field, const N_BYTES_MEMORY_WORD_SIZE: usize>
+    MemoryWordSizeGadget<F, N_BYTES_MEMORY_WORD_SIZE>
+{
     pub(crate) fn construct(cb: &mut ConstraintBuilder<F>, address: Expression<F>) -> Self {
         let memory_word_size = ConstantDivisionGadget::construct(cb, address + 31.expr(), 32);
 
@@ -193,7 +283,7 @@ impl<F: Field> MemoryWordSizeGadget<F> {
 /// memory_word_size / 512)`
 #[derive(Clone, Debug)]
 pub(crate) struct MemoryExpansionGadget<F, const N: usize, const N_BYTES_MEMORY_WORD_SIZE: usize> {
-    memory_word_sizes: [MemoryWordSizeGadget<F>; N],
+    memory_word_sizes: [MemoryWordSizeGadget<F, N_BYTES_MEMORY_WORD_SIZE>; N],
     max_memory_word_sizes: [MinMaxGadget<F, N_BYTES_MEMORY_WORD_SIZE>; N],
     curr_quad_memory_cost: ConstantDivisionGadget<F, N_BYTES_GAS>,
     next_quad_memory_cost: ConstantDivisionGadget<F, N_BYTES_GAS>,
@@ -336,7 +426,7 @@ impl<F: Field, const N: usize, const N_BYTES_MEMORY_WORD_SIZE: usize>
 /// `memory_cost = Gmem * memory_size + floor(memory_size * memory_size / 512)`
 #[derive(Clone, Debug)]
 pub(crate) struct MemoryCopierGasGadget<F> {
-    word_size: MemoryWordSizeGadget<F>,
+    word_size: MemoryWordSizeGadget<F, N_BYTES_MEMORY_WORD_SIZE>,
     gas_cost: Expression<F>,
     gas_cost_range_check: RangeCheckGadget<F, N_BYTES_GAS>,
 }
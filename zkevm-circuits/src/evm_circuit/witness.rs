@@ -3,12 +3,18 @@ use crate::evm_circuit::{
     param::{N_BYTES_WORD, STACK_CAPACITY},
     step::ExecutionState,
     table::{
-        AccountFieldTag, BlockContextFieldTag, CallContextFieldTag, RwTableTag, TxContextFieldTag,
+        AccountFieldTag, BlockContextFieldTag, BytecodeFieldTag, CallContextFieldTag, MptFieldTag,
+        RwTableTag, TxContextFieldTag,
     },
     util::RandomLinearCombination,
 };
-use bus_mapping::circuit_input_builder::{self, ExecError, OogError};
+use blake2f::plain::Blake2fInput;
+use bus_mapping::circuit_input_builder::{
+    self, CopyDataType, ExecError, ExecState, NumberOrHash, OogError,
+};
+use bus_mapping::mpt::{self, MptKey, MptProofNode};
 use bus_mapping::operation::{self, AccountField, CallContextField};
+use bus_mapping::precompile::{EcRecoverInput, ModexpInput, PrecompileCalls, BLAKE2F_MAX_ROUNDS};
 use eth_types::evm_types::OpcodeId;
 use eth_types::{Address, Field, ToLittleEndian, ToScalar, ToWord, Word};
 use halo2_proofs::arithmetic::{BaseExt, FieldExt};
@@ -28,6 +34,34 @@ pub struct Block<F> {
     pub bytecodes: Vec<Bytecode>,
     /// The block context
     pub context: BlockContext,
+    /// Inputs hashed by KECCAK256, such as the preimages of contract
+    /// addresses, proved by the Keccak circuit
+    pub keccak_inputs: Vec<Vec<u8>>,
+    /// Calls to the ecRecover precompile, whose table is built by the ECDSA
+    /// circuit
+    pub ecrecovers: Vec<EcRecover>,
+    /// Inputs of the calls to the SHA256 precompile, proved by the SHA-256
+    /// circuit
+    pub sha256_inputs: Vec<Vec<u8>>,
+    /// Inputs of the calls to the RIPEMD160 precompile, proved by the
+    /// RIPEMD-160 circuit
+    pub ripemd160_inputs: Vec<Vec<u8>>,
+    /// Calls to the ecAdd, ecMul and ecPairing precompiles, whose table is
+    /// built by the ECC circuit
+    pub ecc_calls: Vec<EccCall>,
+    /// Calls to the MODEXP precompile, proved by the MODEXP circuit
+    pub modexp_calls: Vec<ModexpCall>,
+    /// Inputs of the calls to the BLAKE2F precompile, proved by the BLAKE2F
+    /// circuit
+    pub blake2f_inputs: Vec<Blake2fInput>,
+    /// Copies of bytes between buffers, proved by the copy circuit
+    pub copy_events: Vec<CopyEvent>,
+    /// The state root before the block
+    pub prev_state_root: Word,
+    /// The state root after the block
+    pub state_root: Word,
+    /// Updates of the state trie, proved by the MPT circuit
+    pub mpt_updates: Vec<MptUpdate>,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -46,6 +80,8 @@ pub struct BlockContext {
     pub base_fee: Word,
     /// The hash of previous blocks
     pub history_hashes: Vec<Word>,
+    /// The chain id
+    pub chain_id: Word,
 }
 
 impl BlockContext {
@@ -91,6 +127,14 @@ impl BlockContext {
                         randomness,
                     ),
                 ],
+                [
+                    F::from(BlockContextFieldTag::ChainId as u64),
+                    F::zero(),
+                    RandomLinearCombination::random_linear_combine(
+                        self.chain_id.to_le_bytes(),
+                        randomness,
+                    ),
+                ],
             ],
             self.history_hashes
                 .iter()
@@ -98,7 +142,9 @@ impl BlockContext {
                 .map(|(idx, hash)| {
                     [
                         F::from(BlockContextFieldTag::BlockHash as u64),
-                        (self.number - idx - 1).to_scalar().unwrap(),
+                        (self.number - self.history_hashes.len() + idx)
+                            .to_scalar()
+                            .unwrap(),
                         RandomLinearCombination::random_linear_combine(
                             hash.to_le_bytes(),
                             randomness,
@@ -121,6 +167,10 @@ pub struct Transaction {
     pub gas: u64,
     /// The gas price
     pub gas_price: Word,
+    /// The gas fee cap
+    pub gas_fee_cap: Word,
+    /// The gas tip cap
+    pub gas_tip_cap: Word,
     /// The caller address
     pub caller_address: Address,
     /// The callee address
@@ -135,6 +185,10 @@ pub struct Transaction {
     pub call_data_length: usize,
     /// The gas cost for transaction call data
     pub call_data_gas_cost: u64,
+    /// The addresses in the access list
+    pub access_list_addresses: Vec<Address>,
+    /// The storage keys in the access list, along with their addresses
+    pub access_list_storage_keys: Vec<(Address, Word)>,
     /// The calls made in the transaction
     pub calls: Vec<Call>,
     /// The steps executioned in the transaction
@@ -142,6 +196,14 @@ pub struct Transaction {
 }
 
 impl Transaction {
+    /// Return the RLC of the calldata, in the order of the keccak table, which
+    /// is the init code of a creation transaction.
+    pub fn call_data_rlc<F: Field>(&self, randomness: F) -> F {
+        self.call_data.iter().fold(F::zero(), |acc, byte| {
+            acc * randomness + F::from(*byte as u64)
+        })
+    }
+
     pub fn table_assignments<F: Field>(&self, randomness: F) -> Vec<[F; 4]> {
         [
             vec![
@@ -166,6 +228,24 @@ impl Transaction {
                         randomness,
                     ),
                 ],
+                [
+                    F::from(self.id as u64),
+                    F::from(TxContextFieldTag::GasFeeCap as u64),
+                    F::zero(),
+                    RandomLinearCombination::random_linear_combine(
+                        self.gas_fee_cap.to_le_bytes(),
+                        randomness,
+                    ),
+                ],
+                [
+                    F::from(self.id as u64),
+                    F::from(TxContextFieldTag::GasTipCap as u64),
+                    F::zero(),
+                    RandomLinearCombination::random_linear_combine(
+                        self.gas_tip_cap.to_le_bytes(),
+                        randomness,
+                    ),
+                ],
                 [
                     F::from(self.id as u64),
                     F::from(TxContextFieldTag::CallerAddress as u64),
@@ -205,6 +285,24 @@ impl Transaction {
                     F::zero(),
                     F::from(self.call_data_gas_cost),
                 ],
+                [
+                    F::from(self.id as u64),
+                    F::from(TxContextFieldTag::CallDataRlc as u64),
+                    F::zero(),
+                    self.call_data_rlc(randomness),
+                ],
+                [
+                    F::from(self.id as u64),
+                    F::from(TxContextFieldTag::AccessListAddressesLen as u64),
+                    F::zero(),
+                    F::from(self.access_list_addresses.len() as u64),
+                ],
+                [
+                    F::from(self.id as u64),
+                    F::from(TxContextFieldTag::AccessListStorageKeysLen as u64),
+                    F::zero(),
+                    F::from(self.access_list_storage_keys.len() as u64),
+                ],
             ],
             self.call_data
                 .iter()
@@ -218,6 +316,41 @@ impl Transaction {
                     ]
                 })
                 .collect(),
+            self.access_list_addresses
+                .iter()
+                .enumerate()
+                .map(|(idx, address)| {
+                    [
+                        F::from(self.id as u64),
+                        F::from(TxContextFieldTag::AccessListAddress as u64),
+                        F::from(idx as u64),
+                        address.to_scalar().unwrap(),
+                    ]
+                })
+                .collect(),
+            self.access_list_storage_keys
+                .iter()
+                .enumerate()
+                .flat_map(|(idx, (address, key))| {
+                    [
+                        [
+                            F::from(self.id as u64),
+                            F::from(TxContextFieldTag::AccessListStorageKeyAddress as u64),
+                            F::from(idx as u64),
+                            address.to_scalar().unwrap(),
+                        ],
+                        [
+                            F::from(self.id as u64),
+                            F::from(TxContextFieldTag::AccessListStorageKey as u64),
+                            F::from(idx as u64),
+                            RandomLinearCombination::random_linear_combine(
+                                key.to_le_bytes(),
+                                randomness,
+                            ),
+                        ],
+                    ]
+                })
+                .collect(),
         ]
         .concat()
     }
@@ -276,14 +409,24 @@ pub struct Call {
 
 #[derive(Clone, Debug)]
 pub enum StepAuxiliaryData {
-    CopyToMemory {
-        src_addr: u64,
-        dst_addr: u64,
-        bytes_left: u64,
-        src_addr_end: u64,
-        from_tx: bool,
-        selectors: Vec<u8>,
-    },
+    Precompile { input: Vec<u8>, output: Vec<u8> },
+    Create { init_code: Vec<u8> },
+}
+
+impl From<&circuit_input_builder::StepAuxiliaryData> for StepAuxiliaryData {
+    fn from(aux_data: &circuit_input_builder::StepAuxiliaryData) -> Self {
+        match aux_data {
+            circuit_input_builder::StepAuxiliaryData::Precompile { input, output } => {
+                Self::Precompile {
+                    input: input.clone(),
+                    output: output.clone(),
+                }
+            }
+            circuit_input_builder::StepAuxiliaryData::Create { init_code } => Self::Create {
+                init_code: init_code.clone(),
+            },
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -339,7 +482,7 @@ impl Bytecode {
     pub fn table_assignments<'a, F: FieldExt>(
         &'a self,
         randomness: F,
-    ) -> impl Iterator<Item = [F; 4]> + '_ {
+    ) -> impl Iterator<Item = [F; 5]> + '_ {
         struct BytecodeIterator<'a, F> {
             idx: usize,
             push_data_left: usize,
@@ -348,7 +491,7 @@ impl Bytecode {
         }
 
         impl<'a, F: FieldExt> Iterator for BytecodeIterator<'a, F> {
-            type Item = [F; 4];
+            type Item = [F; 5];
 
             fn next(&mut self) -> Option<Self::Item> {
                 if self.idx == self.bytes.len() {
@@ -370,6 +513,7 @@ impl Bytecode {
 
                 Some([
                     self.hash,
+                    F::from(BytecodeFieldTag::Byte as u64),
                     F::from(idx as u64),
                     F::from(byte as u64),
                     F::from(is_code as u64),
@@ -377,18 +521,349 @@ impl Bytecode {
             }
         }
 
-        BytecodeIterator {
+        let hash =
+            RandomLinearCombination::random_linear_combine(self.hash.to_le_bytes(), randomness);
+
+        // The first row of each bytecode contains its length
+        std::iter::once([
+            hash,
+            F::from(BytecodeFieldTag::Length as u64),
+            F::zero(),
+            F::from(self.bytes.len() as u64),
+            F::zero(),
+        ])
+        .chain(BytecodeIterator {
             idx: 0,
             push_data_left: 0,
-            hash: RandomLinearCombination::random_linear_combine(
-                self.hash.to_le_bytes(),
+            hash,
+            bytes: &self.bytes,
+        })
+    }
+}
+
+/// Return the row of the Keccak table of `input`, where the input is encoded
+/// with its first byte having the highest power of `randomness`, and the
+/// digest is encoded as a word.
+pub fn keccak_table_row<F: Field>(input: &[u8], randomness: F) -> [F; 3] {
+    let digest = Word::from_big_endian(Keccak256::digest(input).as_slice());
+    [
+        input.iter().fold(F::zero(), |acc, byte| {
+            acc * randomness + F::from(*byte as u64)
+        }),
+        F::from(input.len() as u64),
+        RandomLinearCombination::random_linear_combine(digest.to_le_bytes(), randomness),
+    ]
+}
+
+/// A call to the ecRecover precompile, whose signer is recovered from a
+/// secp256k1 signature.
+#[derive(Debug, Default, Clone)]
+pub struct EcRecover {
+    pub msg_hash: Word,
+    pub v: Word,
+    pub r: Word,
+    pub s: Word,
+    /// The address of the signer, or `None` when the signature is invalid
+    pub recovered_address: Option<Address>,
+}
+
+impl EcRecover {
+    /// Parse the call data `input` of the ecRecover precompile and recover
+    /// the signer.
+    pub fn new(input: &[u8]) -> Self {
+        let input = EcRecoverInput::new(input);
+        let recovered_address = input.recover();
+        Self {
+            msg_hash: input.msg_hash,
+            v: input.v,
+            r: input.r,
+            s: input.s,
+            recovered_address,
+        }
+    }
+
+    pub fn table_assignment<F: Field>(&self, randomness: F) -> [F; 6] {
+        let [msg_hash, v, r, s] = [self.msg_hash, self.v, self.r, self.s].map(|word| {
+            RandomLinearCombination::random_linear_combine(word.to_le_bytes(), randomness)
+        });
+        [
+            msg_hash,
+            v,
+            r,
+            s,
+            F::from(self.recovered_address.is_some() as u64),
+            self.recovered_address
+                .map(|address| address.to_scalar().unwrap())
+                .unwrap_or_else(F::zero),
+        ]
+    }
+}
+
+/// A call to the ecAdd, ecMul or ecPairing precompile, which operate on the
+/// BN254 curve.
+#[derive(Debug, Clone)]
+pub struct EccCall {
+    pub precompile: PrecompileCalls,
+    pub input: Vec<u8>,
+    /// The output of the call, which is empty when the input is invalid and
+    /// makes the call fail
+    pub output: Vec<u8>,
+}
+
+impl EccCall {
+    /// Compute the output of the call to `precompile` with call data `input`.
+    pub fn new(precompile: PrecompileCalls, input: &[u8]) -> Self {
+        assert!(
+            [
+                PrecompileCalls::Bn128Add,
+                PrecompileCalls::Bn128Mul,
+                PrecompileCalls::Bn128Pairing
+            ]
+            .contains(&precompile),
+            "{:?} is not an ECC precompile",
+            precompile
+        );
+        Self {
+            precompile,
+            input: input.to_vec(),
+            output: precompile.output(input).unwrap(),
+        }
+    }
+
+    /// Return whether the input is valid, in which case the output is never
+    /// empty.
+    pub fn is_valid(&self) -> bool {
+        !self.output.is_empty()
+    }
+
+    pub fn table_assignment<F: Field>(&self, randomness: F) -> [F; 5] {
+        // The first byte has the highest power of the randomness, so that the
+        // RLC of a small output is its value
+        let rlc = |bytes: &[u8]| {
+            bytes.iter().fold(F::zero(), |acc, byte| {
+                acc * randomness + F::from(*byte as u64)
+            })
+        };
+        [
+            F::from(self.precompile as u64),
+            rlc(&self.input),
+            F::from(self.input.len() as u64),
+            F::from(self.is_valid() as u64),
+            rlc(&self.output),
+        ]
+    }
+}
+
+/// A call to the MODEXP precompile whose base, exponent and modulus are at
+/// most `MODEXP_MAX_INPUT_SIZE` bytes long, so that they fit in a word.
+#[derive(Debug, Default, Clone)]
+pub struct ModexpCall {
+    pub base: Word,
+    pub exp: Word,
+    pub modulus: Word,
+    /// The result `base ^ exp % modulus`, which is 0 when the modulus is 0
+    pub result: Word,
+}
+
+impl ModexpCall {
+    /// Parse the call data `input`, or return `None` if any of the lengths
+    /// is greater than `MODEXP_MAX_INPUT_SIZE`.
+    pub fn new(input: &[u8]) -> Option<Self> {
+        let input = ModexpInput::new(input)?;
+        Some(Self {
+            base: input.base,
+            exp: input.exp,
+            modulus: input.modulus,
+            result: input.modexp(),
+        })
+    }
+
+    pub fn table_assignment<F: Field>(&self, randomness: F) -> [F; 4] {
+        [self.base, self.exp, self.modulus, self.result].map(|word| {
+            RandomLinearCombination::random_linear_combine(word.to_le_bytes(), randomness)
+        })
+    }
+}
+
+/// A copy of bytes from a source buffer into a destination one, proved by
+/// the copy circuit with a row per byte.  Either of them can be the RLC of
+/// the bytes instead of a buffer.
+#[derive(Debug, Clone)]
+pub struct CopyEvent {
+    pub src_type: CopyDataType,
+    pub src_id: NumberOrHash,
+    /// The address of the first byte in the source, which is at most
+    /// `src_addr_end`
+    pub src_addr: u64,
+    /// The end of the source buffer, past which the bytes are 0
+    pub src_addr_end: u64,
+    pub dst_type: CopyDataType,
+    pub dst_id: NumberOrHash,
+    pub dst_addr: u64,
+    /// The Read/Write counter of the first access of the copy
+    pub rw_counter_start: usize,
+    pub bytes: Vec<u8>,
+}
+
+impl From<&circuit_input_builder::CopyEvent> for CopyEvent {
+    fn from(event: &circuit_input_builder::CopyEvent) -> Self {
+        Self {
+            src_type: event.src_type,
+            src_id: event.src_id,
+            src_addr: event.src_addr,
+            src_addr_end: event.src_addr_end,
+            dst_type: event.dst_type,
+            dst_id: event.dst_id,
+            dst_addr: event.dst_addr,
+            rw_counter_start: event.rw_counter_start.0,
+            bytes: event.bytes.clone(),
+        }
+    }
+}
+
+impl CopyEvent {
+    /// The number of bytes read from the source before its end
+    pub fn src_bytes(&self) -> u64 {
+        (self.src_addr_end - self.src_addr).min(self.bytes.len() as u64)
+    }
+
+    /// The number of Read/Write accesses of the copy, which are the reads of
+    /// a memory source and the writes of a memory destination
+    pub fn rw_counter_increase(&self) -> u64 {
+        let reads = if self.src_type == CopyDataType::Memory {
+            self.src_bytes()
+        } else {
+            0
+        };
+        let writes = if self.dst_type == CopyDataType::Memory {
+            self.bytes.len() as u64
+        } else {
+            0
+        };
+        reads + writes
+    }
+
+    /// The RLC of the bytes when the source or the destination is an RLC,
+    /// where the first byte has the highest power of the randomness, or 0
+    /// otherwise
+    pub fn rlc_acc<F: Field>(&self, randomness: F) -> F {
+        if [self.src_type, self.dst_type].contains(&CopyDataType::RlcAcc) {
+            self.bytes.iter().fold(F::zero(), |acc, byte| {
+                acc * randomness + F::from(*byte as u64)
+            })
+        } else {
+            F::zero()
+        }
+    }
+
+    /// The rows of the copy table, one per byte
+    pub fn table_assignments<F: Field>(&self, randomness: F) -> Vec<[F; 12]> {
+        let id = |id: &NumberOrHash| match id {
+            NumberOrHash::Number(number) => F::from(*number as u64),
+            NumberOrHash::Hash(hash) => RandomLinearCombination::random_linear_combine(
+                hash.to_word().to_le_bytes(),
                 randomness,
             ),
-            bytes: &self.bytes,
+        };
+        let [src_id, dst_id] = [id(&self.src_id), id(&self.dst_id)];
+        let rwc_inc = self.rw_counter_increase();
+        let src_bytes = self.src_bytes();
+        let rlc_acc = self.rlc_acc(randomness);
+
+        let mut rwc_offset = 0;
+        (0..self.bytes.len() as u64)
+            .map(|idx| {
+                let row = [
+                    F::from((idx == 0) as u64),
+                    src_id,
+                    F::from(self.src_type as u64),
+                    dst_id,
+                    F::from(self.dst_type as u64),
+                    F::from((self.src_addr + idx).min(self.src_addr_end)),
+                    F::from(self.src_addr_end),
+                    F::from(self.dst_addr + idx),
+                    F::from(self.bytes.len() as u64 - idx),
+                    F::from(self.rw_counter_start as u64 + rwc_offset),
+                    F::from(rwc_inc - rwc_offset),
+                    rlc_acc,
+                ];
+                if self.src_type == CopyDataType::Memory && idx < src_bytes {
+                    rwc_offset += 1;
+                }
+                if self.dst_type == CopyDataType::Memory {
+                    rwc_offset += 1;
+                }
+                row
+            })
+            .collect()
+    }
+}
+
+/// An update of an account field or a storage slot, which moves the state
+/// root from `old_root` to `new_root`.  The paths are the nodes of the proofs
+/// of the key before and after the update.
+#[derive(Debug, Clone)]
+pub struct MptUpdate {
+    pub address: Address,
+    pub key: MptKey,
+    pub old_value: Word,
+    pub new_value: Word,
+    pub old_root: Word,
+    pub new_root: Word,
+    pub old_path: Vec<MptProofNode>,
+    pub new_path: Vec<MptProofNode>,
+}
+
+impl From<&mpt::MptUpdate> for MptUpdate {
+    fn from(update: &mpt::MptUpdate) -> Self {
+        Self {
+            address: update.address,
+            key: update.key.clone(),
+            old_value: update.old_value,
+            new_value: update.new_value,
+            old_root: update.old_root.to_word(),
+            new_root: update.new_root.to_word(),
+            old_path: update.old_path.clone(),
+            new_path: update.new_path.clone(),
         }
     }
 }
 
+impl MptUpdate {
+    pub fn field_tag(&self) -> MptFieldTag {
+        match &self.key {
+            MptKey::Account(AccountField::Nonce) => MptFieldTag::Nonce,
+            MptKey::Account(AccountField::Balance) => MptFieldTag::Balance,
+            MptKey::Account(AccountField::CodeHash) => MptFieldTag::CodeHash,
+            MptKey::Storage(_) => MptFieldTag::Storage,
+            MptKey::StorageRoot => MptFieldTag::StorageRoot,
+        }
+    }
+
+    /// The row of the MPT table, which is `address`, `storage_key`,
+    /// `field_tag`, `old_root`, `new_root`, `old_value` and `new_value`.  All
+    /// the values are random linear combinations of their bytes, even the
+    /// nonces, as the MPT circuit reads them from the trie nodes.
+    pub fn table_assignment<F: Field>(&self, randomness: F) -> [F; 7] {
+        let rlc = |word: Word| {
+            RandomLinearCombination::random_linear_combine(word.to_le_bytes(), randomness)
+        };
+        let storage_key = match &self.key {
+            MptKey::Storage(key) => rlc(*key),
+            MptKey::Account(_) | MptKey::StorageRoot => F::zero(),
+        };
+        [
+            self.address.to_scalar().unwrap(),
+            storage_key,
+            F::from(self.field_tag() as u64),
+            rlc(self.old_root),
+            rlc(self.new_root),
+            rlc(self.old_value),
+            rlc(self.new_value),
+        ]
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct RwMap(pub HashMap<RwTableTag, Vec<Rw>>);
 
@@ -558,6 +1033,31 @@ impl Rw {
         }
     }
 
+    pub fn tx_refund_value(&self) -> Word {
+        match self {
+            Self::TxRefund { value, .. } => *value,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn account_destructed_value_pair(&self) -> (bool, bool) {
+        match self {
+            Self::AccountDestructed {
+                value, value_prev, ..
+            } => (*value, *value_prev),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn account_address(&self) -> Address {
+        match self {
+            Self::Account {
+                account_address, ..
+            } => *account_address,
+            _ => unreachable!(),
+        }
+    }
+
     pub fn account_value_pair(&self) -> (Word, Word) {
         match self {
             Self::Account {
@@ -621,7 +1121,7 @@ impl Rw {
             } => [
                 F::from(*rw_counter as u64),
                 F::from(*is_write as u64),
-                F::from(RwTableTag::TxAccessListAccount as u64),
+                F::from(RwTableTag::TxAccessListAccountStorage as u64),
                 F::from(*tx_id as u64),
                 account_address.to_scalar().unwrap(),
                 RandomLinearCombination::random_linear_combine(
@@ -634,6 +1134,25 @@ impl Rw {
                 F::zero(),
             ]
             .into(),
+            Self::TxRefund {
+                rw_counter,
+                is_write,
+                tx_id,
+                value,
+                value_prev,
+            } => [
+                F::from(*rw_counter as u64),
+                F::from(*is_write as u64),
+                F::from(RwTableTag::TxRefund as u64),
+                F::from(*tx_id as u64),
+                F::zero(),
+                F::zero(),
+                F::from(value.low_u64()),
+                F::from(value_prev.low_u64()),
+                F::zero(),
+                F::zero(),
+            ]
+            .into(),
             Self::Account {
                 rw_counter,
                 is_write,
@@ -683,7 +1202,9 @@ impl Rw {
                             randomness,
                         )
                     }
-                    CallContextFieldTag::IsSuccess => value.to_scalar().unwrap(),
+                    CallContextFieldTag::CallerAddress
+                    | CallContextFieldTag::CalleeAddress
+                    | CallContextFieldTag::IsSuccess => value.to_scalar().unwrap(),
                     _ => F::from(value.low_u64()),
                 },
                 F::zero(),
@@ -755,7 +1276,26 @@ impl Rw {
                 F::zero(), // TODO: committed_value
             ]
             .into(),
-            _ => unimplemented!(),
+            Self::AccountDestructed {
+                rw_counter,
+                is_write,
+                tx_id,
+                account_address,
+                value,
+                value_prev,
+            } => [
+                F::from(*rw_counter as u64),
+                F::from(*is_write as u64),
+                F::from(RwTableTag::AccountDestructed as u64),
+                F::from(*tx_id as u64),
+                account_address.to_scalar().unwrap(),
+                F::zero(),
+                F::from(*value as u64),
+                F::from(*value_prev as u64),
+                F::zero(),
+                F::zero(),
+            ]
+            .into(),
         }
     }
 }
@@ -770,6 +1310,7 @@ impl From<&circuit_input_builder::Block> for BlockContext {
             difficulty: block.difficulty,
             base_fee: block.base_fee,
             history_hashes: block.history_hashes.clone(),
+            chain_id: block.chain_id,
         }
     }
 }
@@ -958,7 +1499,7 @@ impl From<&operation::OperationContainer> for RwMap {
 impl From<&ExecError> for ExecutionState {
     fn from(error: &ExecError) -> Self {
         match error {
-            ExecError::Reverted => ExecutionState::ErrorReverted,
+            ExecError::Reverted => ExecutionState::RETURN,
             ExecError::InvalidOpcode => ExecutionState::ErrorInvalidOpcode,
             ExecError::StackOverflow => ExecutionState::ErrorStackOverflow,
             ExecError::StackUnderflow => ExecutionState::ErrorStackUnderflow,
@@ -975,16 +1516,16 @@ impl From<&ExecError> for ExecutionState {
                 OogError::Constant => ExecutionState::ErrorOutOfGasConstant,
                 OogError::PureMemory => ExecutionState::ErrorOutOfGasPureMemory,
                 OogError::Sha3 => ExecutionState::ErrorOutOfGasSHA3,
-                OogError::CallDataCopy => ExecutionState::ErrorOutOfGasCALLDATACOPY,
-                OogError::CodeCopy => ExecutionState::ErrorOutOfGasCODECOPY,
-                OogError::ExtCodeCopy => ExecutionState::ErrorOutOfGasEXTCODECOPY,
-                OogError::ReturnDataCopy => ExecutionState::ErrorOutOfGasRETURNDATACOPY,
+                OogError::CallDataCopy
+                | OogError::CodeCopy
+                | OogError::ExtCodeCopy
+                | OogError::ReturnDataCopy => ExecutionState::ErrorOutOfGasMemoryCopy,
                 OogError::Log => ExecutionState::ErrorOutOfGasLOG,
-                OogError::Call => ExecutionState::ErrorOutOfGasCALL,
-                OogError::CallCode => ExecutionState::ErrorOutOfGasCALLCODE,
-                OogError::DelegateCall => ExecutionState::ErrorOutOfGasDELEGATECALL,
+                OogError::Call
+                | OogError::CallCode
+                | OogError::DelegateCall
+                | OogError::StaticCall => ExecutionState::ErrorOutOfGasCall,
                 OogError::Create2 => ExecutionState::ErrorOutOfGasCREATE2,
-                OogError::StaticCall => ExecutionState::ErrorOutOfGasSTATICCALL,
             },
         }
     }
@@ -995,6 +1536,12 @@ impl From<&bus_mapping::circuit_input_builder::ExecStep> for ExecutionState {
         if let Some(error) = step.error.as_ref() {
             return error.into();
         }
+        match step.exec_state {
+            ExecState::BeginTx => return ExecutionState::BeginTx,
+            ExecState::Precompile(_) => return ExecutionState::Precompile,
+            ExecState::EndTx => return ExecutionState::EndTx,
+            ExecState::Op(_) => {}
+        }
         if step.op.is_dup() {
             return ExecutionState::DUP;
         }
@@ -1026,12 +1573,35 @@ impl From<&bus_mapping::circuit_input_builder::ExecStep> for ExecutionState {
             OpcodeId::JUMPI => ExecutionState::JUMPI,
             OpcodeId::PC => ExecutionState::PC,
             OpcodeId::MSIZE => ExecutionState::MSIZE,
+            OpcodeId::ADDRESS => ExecutionState::ADDRESS,
+            OpcodeId::ORIGIN => ExecutionState::ORIGIN,
             OpcodeId::CALLER => ExecutionState::CALLER,
+            OpcodeId::GASPRICE => ExecutionState::GASPRICE,
             OpcodeId::CALLVALUE => ExecutionState::CALLVALUE,
             OpcodeId::COINBASE => ExecutionState::COINBASE,
             OpcodeId::TIMESTAMP => ExecutionState::TIMESTAMP,
             OpcodeId::GAS => ExecutionState::GAS,
             OpcodeId::SELFBALANCE => ExecutionState::SELFBALANCE,
+            OpcodeId::CALLDATALOAD => ExecutionState::CALLDATALOAD,
+            OpcodeId::CALLDATASIZE => ExecutionState::CALLDATASIZE,
+            OpcodeId::RETURNDATASIZE => ExecutionState::RETURNDATASIZE,
+            OpcodeId::RETURNDATACOPY => ExecutionState::RETURNDATACOPY,
+            OpcodeId::CALLDATACOPY => ExecutionState::CALLDATACOPY,
+            OpcodeId::CODESIZE => ExecutionState::CODESIZE,
+            OpcodeId::CODECOPY => ExecutionState::CODECOPY,
+            OpcodeId::EXTCODESIZE => ExecutionState::EXTCODESIZE,
+            OpcodeId::EXTCODECOPY => ExecutionState::EXTCODECOPY,
+            OpcodeId::BALANCE => ExecutionState::BALANCE,
+            OpcodeId::EXTCODEHASH => ExecutionState::EXTCODEHASH,
+            OpcodeId::BLOCKHASH => ExecutionState::BLOCKHASH,
+            OpcodeId::NUMBER => ExecutionState::NUMBER,
+            OpcodeId::DIFFICULTY => ExecutionState::DIFFICULTY,
+            OpcodeId::GASLIMIT => ExecutionState::GASLIMIT,
+            OpcodeId::CHAINID => ExecutionState::CHAINID,
+            OpcodeId::BASEFEE => ExecutionState::BASEFEE,
+            OpcodeId::SELFDESTRUCT => ExecutionState::SELFDESTRUCT,
+            OpcodeId::SHA3 => ExecutionState::SHA3,
+            OpcodeId::RETURN => ExecutionState::RETURN,
             _ => unimplemented!("unimplemented opcode {:?}", step.op),
         }
     }
@@ -1072,19 +1642,24 @@ fn step_convert(step: &circuit_input_builder::ExecStep) -> ExecStep {
         stack_pointer: STACK_CAPACITY - step.stack_size,
         gas_left: step.gas_left.0,
         gas_cost: step.gas_cost.as_u64(),
-        opcode: Some(step.op),
+        opcode: match step.exec_state {
+            ExecState::BeginTx | ExecState::EndTx => None,
+            _ => Some(step.op),
+        },
         memory_size: step.memory_size as u64,
         state_write_counter: step.swc,
-        aux_data: Default::default(),
+        aux_data: step.aux_data.as_ref().map(Into::into),
     }
 }
 
-fn tx_convert(tx: &circuit_input_builder::Transaction) -> Transaction {
+fn tx_convert(tx: &circuit_input_builder::Transaction, id: usize) -> Transaction {
     Transaction {
-        id: 1,
+        id,
         nonce: tx.nonce,
         gas: tx.gas,
         gas_price: tx.gas_price,
+        gas_fee_cap: tx.gas_fee_cap,
+        gas_tip_cap: tx.gas_tip_cap,
         caller_address: tx.from,
         callee_address: tx.to,
         is_create: tx.is_create(),
@@ -1095,6 +1670,17 @@ fn tx_convert(tx: &circuit_input_builder::Transaction) -> Transaction {
             .input
             .iter()
             .fold(0, |acc, byte| acc + if *byte == 0 { 4 } else { 16 }),
+        access_list_addresses: tx.access_list.0.iter().map(|item| item.address).collect(),
+        access_list_storage_keys: tx
+            .access_list
+            .0
+            .iter()
+            .flat_map(|item| {
+                item.storage_keys
+                    .iter()
+                    .map(move |key| (item.address, key.to_word()))
+            })
+            .collect(),
         calls: tx
             .calls()
             .iter()
@@ -1103,10 +1689,14 @@ fn tx_convert(tx: &circuit_input_builder::Transaction) -> Transaction {
                 is_root: call.is_root,
                 is_create: call.is_create(),
                 code_source: match call.code_source {
-                    circuit_input_builder::CodeSource::Address(_) => {
+                    // The init code of a creation transaction or of a
+                    // CREATE* is in the bytecode table as well, indexed by
+                    // its hash
+                    circuit_input_builder::CodeSource::Address(_)
+                    | circuit_input_builder::CodeSource::Tx
+                    | circuit_input_builder::CodeSource::Memory => {
                         CodeSource::Account(call.code_hash.to_word())
                     }
-                    _ => unimplemented!(),
                 },
                 rw_counter_end_of_reversion: call.rw_counter_end_of_reversion,
                 caller_id: call.caller_id,
@@ -1134,15 +1724,89 @@ pub fn block_convert(
         randomness: Fp::rand(),
         context: block.into(),
         rws: RwMap::from(&block.container),
-        txs: block.txs().iter().map(tx_convert).collect(),
-        bytecodes: block
+        txs: block
             .txs()
             .iter()
-            .flat_map(|tx| {
-                tx.calls()
-                    .iter()
-                    .map(|call| Bytecode::new(code_db.0.get(&call.code_hash).unwrap().to_vec()))
+            .enumerate()
+            .map(|(idx, tx)| tx_convert(tx, idx + 1))
+            .collect(),
+        // All the known bytecodes are included, since besides the executed
+        // ones, EXTCODESIZE and EXTCODECOPY can read any of them.  The empty
+        // bytecode is always included for the accounts without code.
+        bytecodes: code_db
+            .0
+            .values()
+            .filter(|code| !code.is_empty())
+            .map(|code| Bytecode::new(code.to_vec()))
+            .chain(std::iter::once(Bytecode::new(vec![])))
+            .collect(),
+        keccak_inputs: block.sha3_inputs.clone(),
+        ecrecovers: block
+            .txs()
+            .iter()
+            .flat_map(|tx| tx.steps())
+            .filter_map(|step| match (&step.exec_state, &step.aux_data) {
+                (
+                    ExecState::Precompile(PrecompileCalls::ECRecover),
+                    Some(circuit_input_builder::StepAuxiliaryData::Precompile { input, .. }),
+                ) => Some(EcRecover::new(input)),
+                _ => None,
             })
             .collect(),
+        sha256_inputs: precompile_inputs(block, PrecompileCalls::Sha256),
+        ripemd160_inputs: precompile_inputs(block, PrecompileCalls::Ripemd160),
+        ecc_calls: block
+            .txs()
+            .iter()
+            .flat_map(|tx| tx.steps())
+            .filter_map(|step| match (&step.exec_state, &step.aux_data) {
+                (
+                    ExecState::Precompile(
+                        precompile @ (PrecompileCalls::Bn128Add
+                        | PrecompileCalls::Bn128Mul
+                        | PrecompileCalls::Bn128Pairing),
+                    ),
+                    Some(circuit_input_builder::StepAuxiliaryData::Precompile { input, .. }),
+                ) => Some(EccCall::new(*precompile, input)),
+                _ => None,
+            })
+            .collect(),
+        // The inputs larger than supported are rejected by the bus-mapping,
+        // and the result of a zero modulus is checked without a lookup
+        modexp_calls: precompile_inputs(block, PrecompileCalls::Modexp)
+            .iter()
+            .filter_map(|input| ModexpCall::new(input))
+            .filter(|call| !call.modulus.is_zero())
+            .collect(),
+        // Only the calls which don't fail because of their input or of the
+        // number of rounds are proved by the BLAKE2F circuit
+        blake2f_inputs: precompile_inputs(block, PrecompileCalls::Blake2F)
+            .iter()
+            .filter_map(|input| Blake2fInput::from_bytes(input))
+            .filter(|input| input.rounds <= BLAKE2F_MAX_ROUNDS)
+            .collect(),
+        copy_events: block.copy_events.iter().map(CopyEvent::from).collect(),
+        prev_state_root: block.prev_state_root.to_word(),
+        state_root: block.state_root.to_word(),
+        mpt_updates: block.mpt_updates.iter().map(MptUpdate::from).collect(),
     }
 }
+
+/// Return the inputs of the calls to `precompile` in `block`.
+fn precompile_inputs(
+    block: &circuit_input_builder::Block,
+    precompile: PrecompileCalls,
+) -> Vec<Vec<u8>> {
+    block
+        .txs()
+        .iter()
+        .flat_map(|tx| tx.steps())
+        .filter_map(|step| match (&step.exec_state, &step.aux_data) {
+            (
+                ExecState::Precompile(callee),
+                Some(circuit_input_builder::StepAuxiliaryData::Precompile { input, .. }),
+            ) if *callee == precompile => Some(input.clone()),
+            _ => None,
+        })
+        .collect()
+}
@@ -11,8 +11,13 @@
 #![deny(unsafe_code)]
 
 pub mod bytecode_circuit;
+pub mod copy_circuit;
+pub mod ecc_circuit;
+pub mod ecdsa_circuit;
 pub mod evm_circuit;
 pub mod gadget;
+pub mod modexp_circuit;
+pub mod mpt_circuit;
 pub mod rw_table;
 pub mod state_circuit;
 #[cfg(test)]
@@ -0,0 +1,6 @@
+//! The MODEXP circuit implementation, which proves the results of the calls
+//! to the MODEXP precompile by square-and-multiply, with a modular
+//! multiplication per row.
+
+pub(crate) mod modexp;
+pub(crate) mod param;
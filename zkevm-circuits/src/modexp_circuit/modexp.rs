@@ -0,0 +1,843 @@
+use super::param::{MODEXP_TABLE_WIDTH, NUM_BYTES, ROWS_PER_CALL};
+use crate::{
+    evm_circuit::{
+        util::{constraint_builder::BaseConstraintBuilder, not},
+        witness::ModexpCall,
+    },
+    util::Expr,
+};
+use eth_types::{Field, ToLittleEndian};
+use halo2_proofs::{
+    circuit::{Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, VirtualCells},
+    poly::Rotation,
+};
+use num::{BigUint, Zero};
+
+/// The number of carries of the byte-wise multiplications, which have
+/// `2 * NUM_BYTES - 1` byte positions, the last of which has no carry.
+const NUM_CARRIES: usize = 2 * NUM_BYTES - 2;
+
+/// The offset added to the carries, which can be negative, so that they're
+/// encoded in 2 bytes.
+const CARRY_OFFSET: u64 = 1 << 15;
+
+/// The number of rows before the first call, which are queried by its first
+/// rows as the rows of a previous call.
+const NUM_LEADING_ROWS: usize = 2;
+
+/// The modular multiplication `lhs * rhs = quotient * modulus + remainder`
+/// of a row, where the numbers are encoded in little-endian bytes.
+#[derive(Clone, Debug, Default)]
+struct MulModWitness {
+    lhs: [u8; NUM_BYTES],
+    rhs: [u8; NUM_BYTES],
+    quotient: [u8; NUM_BYTES],
+    remainder: [u8; NUM_BYTES],
+    /// The bit of the exponent squared and multiplied by the row and the
+    /// other row of its pair
+    bit: bool,
+}
+
+impl MulModWitness {
+    /// Returns the carries of the byte positions of the multiplications, for
+    /// which `lhs * rhs - quotient * modulus - remainder` adds up to 0.
+    fn carries(&self, modulus: &[u8; NUM_BYTES]) -> Vec<i64> {
+        let mut carry = 0;
+        (0..NUM_CARRIES)
+            .map(|pos| {
+                let mut value = carry;
+                for idx in pos.saturating_sub(NUM_BYTES - 1)..=pos.min(NUM_BYTES - 1) {
+                    value += self.lhs[idx] as i64 * self.rhs[pos - idx] as i64
+                        - self.quotient[idx] as i64 * modulus[pos - idx] as i64;
+                }
+                if pos < NUM_BYTES {
+                    value -= self.remainder[pos] as i64;
+                }
+                carry = value >> 8;
+                carry
+            })
+            .collect()
+    }
+
+    /// Returns the bytes and the borrows of `modulus - remainder - 1`.
+    fn diff(&self, modulus: &[u8; NUM_BYTES]) -> ([u8; NUM_BYTES], [bool; NUM_BYTES]) {
+        let mut diff = [0; NUM_BYTES];
+        let mut borrows = [false; NUM_BYTES];
+        let mut borrow = true;
+        for idx in 0..NUM_BYTES {
+            let value = modulus[idx] as i64 - self.remainder[idx] as i64 - borrow as i64;
+            borrow = value < 0;
+            diff[idx] = (value + 256 * borrow as i64) as u8;
+            borrows[idx] = borrow;
+        }
+        (diff, borrows)
+    }
+}
+
+/// The rows of a call, where each row multiplies the remainder of the
+/// previous row, and the remainder of the last row is the result.
+#[derive(Clone, Debug)]
+struct CallWitness {
+    call: ModexpCall,
+    rows: Vec<MulModWitness>,
+}
+
+impl CallWitness {
+    fn new(call: &ModexpCall) -> Self {
+        let base = call.base.to_le_bytes();
+        let modulus = BigUint::from_bytes_le(&call.modulus.to_le_bytes());
+        let one = to_bytes(&BigUint::from(1u64));
+
+        let mut rows: Vec<MulModWitness> = Vec::with_capacity(ROWS_PER_CALL);
+        for idx in 0..ROWS_PER_CALL {
+            // The bits of the exponent are squared and multiplied from the
+            // most significant one
+            let bit = call.exp.bit(8 * NUM_BYTES - 1 - idx / 2);
+            let lhs = rows.last().map_or(one, |row| row.remainder);
+            let rhs = if idx % 2 == 0 {
+                lhs
+            } else if bit {
+                base
+            } else {
+                one
+            };
+            let (quotient, remainder) = if modulus.is_zero() {
+                (BigUint::zero(), BigUint::zero())
+            } else {
+                let product = BigUint::from_bytes_le(&lhs) * BigUint::from_bytes_le(&rhs);
+                (&product / &modulus, &product % &modulus)
+            };
+            rows.push(MulModWitness {
+                lhs,
+                rhs,
+                quotient: to_bytes(&quotient),
+                remainder: to_bytes(&remainder),
+                bit,
+            });
+        }
+
+        Self {
+            call: call.clone(),
+            rows,
+        }
+    }
+}
+
+fn to_bytes(value: &BigUint) -> [u8; NUM_BYTES] {
+    let mut bytes = [0; NUM_BYTES];
+    let value = value.to_bytes_le();
+    bytes[..value.len()].copy_from_slice(&value);
+    bytes
+}
+
+/// The calls take [`ROWS_PER_CALL`] rows each, where the row `2 * i` squares
+/// the result of the previous row and the row `2 * i + 1` multiplies it by
+/// the base if the bit `255 - i` of the exponent is set, starting from 1.
+/// Every row proves a modular multiplication with a byte-wise multiplication
+/// and a carry per byte position, and the bytes of the exponent are
+/// accumulated from its bits.
+#[derive(Clone, Debug)]
+pub struct Config<F> {
+    r: F,
+    minimum_rows: usize,
+    /// Enabled on all the usable rows, including the ones out of the calls
+    q_enable: Selector,
+    /// Enabled on all the rows of the calls
+    q_step: Selector,
+    /// Enabled on the first row of every call
+    q_first: Selector,
+    /// Enabled on the rows which square the previous result
+    q_square: Selector,
+    /// Enabled on the rows which multiply the previous result by the base or
+    /// by 1
+    q_multiply: Selector,
+    /// Enabled on the multiplication rows of the most significant bits of the
+    /// bytes of the exponent
+    q_byte_start: Selector,
+    /// Enabled on the multiplication rows of the least significant bits of
+    /// the bytes of the exponent
+    q_byte_end: Selector,
+    /// Enabled on the last row of every call
+    q_last: Selector,
+    u8_table: Column<Fixed>,
+    base: [Column<Advice>; NUM_BYTES],
+    modulus: [Column<Advice>; NUM_BYTES],
+    is_zero_modulus: Column<Advice>,
+    /// The inverse of the sum of the bytes of the modulus, or 0 if the
+    /// modulus is 0
+    modulus_sum_inv: Column<Advice>,
+    bit: Column<Advice>,
+    /// The bits of the current byte of the exponent accumulated so far
+    exp_byte: Column<Advice>,
+    /// The RLC of the bytes of the exponent accumulated so far, from the most
+    /// significant one
+    exp_rlc: Column<Advice>,
+    lhs: [Column<Advice>; NUM_BYTES],
+    rhs: [Column<Advice>; NUM_BYTES],
+    quotient: [Column<Advice>; NUM_BYTES],
+    remainder: [Column<Advice>; NUM_BYTES],
+    /// The carries of the byte positions of the multiplications, encoded in
+    /// 2 bytes after adding [`CARRY_OFFSET`]
+    carries: [[Column<Advice>; 2]; NUM_CARRIES],
+    /// The bytes of `modulus - remainder - 1`, which prove with their borrows
+    /// that the remainder is less than the modulus
+    diff: [Column<Advice>; NUM_BYTES],
+    borrows: [Column<Advice>; NUM_BYTES],
+    /// The MODEXP table looked up by the EVM circuit, whose columns are the
+    /// RLCs of the base, the exponent, the modulus and the result.  Only the
+    /// last row of a call isn't all zero.
+    modexp_table: [Column<Advice>; MODEXP_TABLE_WIDTH],
+}
+
+impl<F: Field> Config<F> {
+    pub(crate) fn configure(meta: &mut ConstraintSystem<F>, r: F) -> Self {
+        // The selectors are combined with each other in the gates, which
+        // requires complex selectors
+        let q_enable = meta.complex_selector();
+        let q_step = meta.complex_selector();
+        let q_first = meta.complex_selector();
+        let q_square = meta.complex_selector();
+        let q_multiply = meta.complex_selector();
+        let q_byte_start = meta.complex_selector();
+        let q_byte_end = meta.complex_selector();
+        let q_last = meta.complex_selector();
+        let u8_table = meta.fixed_column();
+        let base = array_init::array_init(|_| meta.advice_column());
+        let modulus = array_init::array_init(|_| meta.advice_column());
+        let is_zero_modulus = meta.advice_column();
+        let modulus_sum_inv = meta.advice_column();
+        let bit = meta.advice_column();
+        let exp_byte = meta.advice_column();
+        let exp_rlc = meta.advice_column();
+        let lhs = array_init::array_init(|_| meta.advice_column());
+        let rhs = array_init::array_init(|_| meta.advice_column());
+        let quotient = array_init::array_init(|_| meta.advice_column());
+        let remainder = array_init::array_init(|_| meta.advice_column());
+        let carries = array_init::array_init(|_| [meta.advice_column(), meta.advice_column()]);
+        let diff = array_init::array_init(|_| meta.advice_column());
+        let borrows = array_init::array_init(|_| meta.advice_column());
+        let modexp_table = array_init::array_init(|_| meta.advice_column());
+
+        meta.create_gate("modexp table", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let q_last = meta.query_selector(q_last);
+            for column in modexp_table.iter() {
+                cb.require_zero(
+                    "table value = 0 when not in the last row of a call",
+                    not::expr(q_last.clone()) * meta.query_advice(*column, Rotation::cur()),
+                );
+            }
+            cb.gate(meta.query_selector(q_enable))
+        });
+
+        meta.create_gate("modexp call constants", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            for column in base
+                .iter()
+                .chain(modulus.iter())
+                .chain([is_zero_modulus, modulus_sum_inv].iter())
+            {
+                cb.require_equal(
+                    "value is the same in all the rows of a call",
+                    meta.query_advice(*column, Rotation::cur()),
+                    meta.query_advice(*column, Rotation::prev()),
+                );
+            }
+            cb.gate(meta.query_selector(q_step) - meta.query_selector(q_first))
+        });
+
+        meta.create_gate("modexp zero modulus", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_zero_modulus = meta.query_advice(is_zero_modulus, Rotation::cur());
+            let modulus_sum_inv = meta.query_advice(modulus_sum_inv, Rotation::cur());
+            // Since the bytes are in range, their sum is 0 only if they're
+            // all 0
+            let modulus_sum = modulus.iter().fold(0.expr(), |acc, column| {
+                acc + meta.query_advice(*column, Rotation::cur())
+            });
+            cb.require_equal(
+                "is_zero_modulus = 1 - modulus_sum * modulus_sum_inv",
+                is_zero_modulus.clone(),
+                1.expr() - modulus_sum.clone() * modulus_sum_inv,
+            );
+            cb.require_zero(
+                "modulus_sum = 0 when is_zero_modulus",
+                is_zero_modulus * modulus_sum,
+            );
+            cb.gate(meta.query_selector(q_first))
+        });
+
+        meta.create_gate("modexp operands", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let q_first = meta.query_selector(q_first);
+            let q_not_first = meta.query_selector(q_step) - q_first.clone();
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let base = query(meta, &base, 0);
+            let lhs = query(meta, &lhs, 0);
+            let rhs = query(meta, &rhs, 0);
+            let remainder_prev = query(meta, &remainder, -1);
+
+            cb.condition(q_first, |cb| {
+                for (idx, lhs) in lhs.iter().enumerate() {
+                    cb.require_equal("lhs = 1 in the first row", lhs.clone(), (idx == 0).expr());
+                }
+            });
+            cb.condition(q_not_first, |cb| {
+                for (lhs, remainder_prev) in lhs.iter().zip(remainder_prev.iter()) {
+                    cb.require_equal(
+                        "lhs = previous remainder in the other rows",
+                        lhs.clone(),
+                        remainder_prev.clone(),
+                    );
+                }
+            });
+            cb.condition(meta.query_selector(q_square), |cb| {
+                for (rhs, lhs) in rhs.iter().zip(lhs.iter()) {
+                    cb.require_equal("rhs = lhs when squaring", rhs.clone(), lhs.clone());
+                }
+            });
+            cb.condition(meta.query_selector(q_multiply), |cb| {
+                cb.require_boolean("bit is boolean", bit.clone());
+                for (idx, (rhs, base)) in rhs.iter().zip(base.iter()).enumerate() {
+                    cb.require_equal(
+                        "rhs = bit ? base : 1 when multiplying",
+                        rhs.clone(),
+                        bit.clone() * base.clone() + not::expr(bit.clone()) * (idx == 0).expr(),
+                    );
+                }
+            });
+
+            cb.gate(meta.query_selector(q_step))
+        });
+
+        meta.create_gate("modexp multiplication", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_zero_modulus = meta.query_advice(is_zero_modulus, Rotation::cur());
+            let modulus = query(meta, &modulus, 0);
+            let lhs = query(meta, &lhs, 0);
+            let rhs = query(meta, &rhs, 0);
+            let quotient = query(meta, &quotient, 0);
+            let remainder = query(meta, &remainder, 0);
+            let diff = query(meta, &diff, 0);
+            let borrows = query(meta, &borrows, 0);
+            let carries = carries
+                .iter()
+                .map(|[low, high]| {
+                    meta.query_advice(*low, Rotation::cur())
+                        + meta.query_advice(*high, Rotation::cur()) * 256.expr()
+                        - CARRY_OFFSET.expr()
+                })
+                .collect::<Vec<_>>();
+
+            // The remainder is unconstrained when the modulus is 0, since the
+            // result is then 0
+            cb.condition(not::expr(is_zero_modulus), |cb| {
+                for pos in 0..2 * NUM_BYTES - 1 {
+                    let mut value = if pos == 0 {
+                        0.expr()
+                    } else {
+                        carries[pos - 1].clone()
+                    };
+                    for idx in pos.saturating_sub(NUM_BYTES - 1)..=pos.min(NUM_BYTES - 1) {
+                        value = value + lhs[idx].clone() * rhs[pos - idx].clone()
+                            - quotient[idx].clone() * modulus[pos - idx].clone();
+                    }
+                    if pos < NUM_BYTES {
+                        value = value - remainder[pos].clone();
+                    }
+                    let carry = if pos < NUM_CARRIES {
+                        carries[pos].clone()
+                    } else {
+                        0.expr()
+                    };
+                    cb.require_equal(
+                        "lhs * rhs = quotient * modulus + remainder at every byte position",
+                        value,
+                        carry * 256.expr(),
+                    );
+                }
+
+                for idx in 0..NUM_BYTES {
+                    let borrow_prev = if idx == 0 {
+                        1.expr()
+                    } else {
+                        borrows[idx - 1].clone()
+                    };
+                    cb.require_boolean("borrow is boolean", borrows[idx].clone());
+                    cb.require_equal(
+                        "diff = modulus - remainder - 1 byte by byte",
+                        diff[idx].clone(),
+                        modulus[idx].clone() - remainder[idx].clone() - borrow_prev
+                            + borrows[idx].clone() * 256.expr(),
+                    );
+                }
+                cb.require_zero("remainder < modulus", borrows[NUM_BYTES - 1].clone());
+            });
+
+            cb.gate(meta.query_selector(q_step))
+        });
+
+        meta.create_gate("modexp exponent", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let q_first = meta.query_selector(q_first);
+            let q_byte_start = meta.query_selector(q_byte_start);
+            let q_byte_end = meta.query_selector(q_byte_end);
+            let bit = meta.query_advice(bit, Rotation::cur());
+            // The previous multiplication row is 2 rows before
+            let exp_byte_prev = meta.query_advice(exp_byte, Rotation(-2));
+            let exp_byte = meta.query_advice(exp_byte, Rotation::cur());
+            let exp_rlc_prev = meta.query_advice(exp_rlc, Rotation::prev());
+            let exp_rlc = meta.query_advice(exp_rlc, Rotation::cur());
+
+            cb.condition(q_byte_start.clone(), |cb| {
+                cb.require_equal(
+                    "exp_byte = bit at the start of a byte",
+                    exp_byte.clone(),
+                    bit.clone(),
+                );
+            });
+            cb.condition(meta.query_selector(q_multiply) - q_byte_start, |cb| {
+                cb.require_equal(
+                    "exp_byte = 2 * previous exp_byte + bit in the rest of a byte",
+                    exp_byte.clone(),
+                    exp_byte_prev * 2.expr() + bit,
+                );
+            });
+            cb.condition(q_first.clone(), |cb| {
+                cb.require_zero("exp_rlc = 0 in the first row", exp_rlc.clone());
+            });
+            cb.condition(
+                meta.query_selector(q_step) - q_first - q_byte_end.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "exp_rlc = previous exp_rlc within a byte",
+                        exp_rlc.clone(),
+                        exp_rlc_prev.clone(),
+                    );
+                },
+            );
+            cb.condition(q_byte_end, |cb| {
+                cb.require_equal(
+                    "exp_rlc = previous exp_rlc * r + exp_byte at the end of a byte",
+                    exp_rlc,
+                    exp_rlc_prev * Expression::Constant(r) + exp_byte,
+                );
+            });
+
+            cb.gate(meta.query_selector(q_step))
+        });
+
+        meta.create_gate("modexp output", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_zero_modulus = meta.query_advice(is_zero_modulus, Rotation::cur());
+            let exp_rlc = meta.query_advice(exp_rlc, Rotation::cur());
+            // The RLC of the little-endian bytes of a word
+            let rlc = |bytes: Vec<Expression<F>>| {
+                bytes.iter().rev().fold(0.expr(), |acc, byte| {
+                    acc * Expression::Constant(r) + byte.clone()
+                })
+            };
+            let values = [
+                rlc(query(meta, &base, 0)),
+                exp_rlc,
+                rlc(query(meta, &modulus, 0)),
+                not::expr(is_zero_modulus) * rlc(query(meta, &remainder, 0)),
+            ];
+            for (column, value) in modexp_table.iter().zip(values.iter()) {
+                cb.require_equal(
+                    "table value = [base, exp, modulus, result] in the last row",
+                    meta.query_advice(*column, Rotation::cur()),
+                    value.clone(),
+                );
+            }
+            cb.gate(meta.query_selector(q_last))
+        });
+
+        // The multiplication operands are equal to bytes in range, so only
+        // the other numbers are looked up
+        for column in base
+            .iter()
+            .chain(modulus.iter())
+            .chain(quotient.iter())
+            .chain(remainder.iter())
+            .chain(diff.iter())
+            .chain(carries.iter().flatten())
+        {
+            meta.lookup_any("modexp byte in range", |meta| {
+                vec![(
+                    meta.query_advice(*column, Rotation::cur()),
+                    meta.query_fixed(u8_table, Rotation::cur()),
+                )]
+            });
+        }
+
+        Config {
+            r,
+            minimum_rows: meta.minimum_rows(),
+            q_enable,
+            q_step,
+            q_first,
+            q_square,
+            q_multiply,
+            q_byte_start,
+            q_byte_end,
+            q_last,
+            u8_table,
+            base,
+            modulus,
+            is_zero_modulus,
+            modulus_sum_inv,
+            bit,
+            exp_byte,
+            exp_rlc,
+            lhs,
+            rhs,
+            quotient,
+            remainder,
+            carries,
+            diff,
+            borrows,
+            modexp_table,
+        }
+    }
+
+    /// Assign `modexp_calls` into a circuit of `size` rows, where the calls
+    /// left are filled with padding calls of modulus 0, whose rows in the
+    /// table are all zero.
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        size: usize,
+        modexp_calls: &[ModexpCall],
+    ) -> Result<(), Error> {
+        self.assign_calls(
+            layouter,
+            size,
+            modexp_calls.iter().map(CallWitness::new).collect(),
+        )
+    }
+
+    fn assign_calls(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        size: usize,
+        mut calls: Vec<CallWitness>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "modexp u8 table",
+            |mut region| {
+                for idx in 0..256 {
+                    region.assign_fixed(
+                        || "modexp u8 table",
+                        self.u8_table,
+                        idx,
+                        || Ok(F::from(idx as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )?;
+
+        // Subtract the unusable rows from the size
+        let num_rows = size - self.minimum_rows + 1;
+        let num_calls = (num_rows - NUM_LEADING_ROWS) / ROWS_PER_CALL;
+        if calls.len() > num_calls {
+            return Err(Error::Synthesis);
+        }
+        calls.resize(num_calls, CallWitness::new(&ModexpCall::default()));
+
+        layouter.assign_region(
+            || "modexp",
+            |mut region| {
+                for offset in 0..NUM_LEADING_ROWS {
+                    self.assign_empty_row(&mut region, offset)?;
+                }
+
+                let mut offset = NUM_LEADING_ROWS;
+                for call in calls.iter() {
+                    for idx in 0..ROWS_PER_CALL {
+                        self.assign_row(&mut region, offset, call, idx)?;
+                        offset += 1;
+                    }
+                }
+
+                for offset in offset..num_rows {
+                    self.assign_empty_row(&mut region, offset)?;
+                }
+
+                Ok(())
+            },
+        )
+    }
+
+    fn assign_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        call: &CallWitness,
+        idx: usize,
+    ) -> Result<(), Error> {
+        for (selector, is_enabled) in [
+            (self.q_enable, true),
+            (self.q_step, true),
+            (self.q_first, idx == 0),
+            (self.q_square, idx % 2 == 0),
+            (self.q_multiply, idx % 2 == 1),
+            (self.q_byte_start, idx % 16 == 1),
+            (self.q_byte_end, idx % 16 == 15),
+            (self.q_last, idx == ROWS_PER_CALL - 1),
+        ] {
+            if is_enabled {
+                selector.enable(region, offset)?;
+            }
+        }
+
+        let row = &call.rows[idx];
+        let base = call.call.base.to_le_bytes();
+        let modulus = call.call.modulus.to_le_bytes();
+        let exp = call.call.exp.to_le_bytes();
+        let modulus_sum = modulus.iter().map(|byte| *byte as u64).sum::<u64>();
+        // The bits of the current byte of the exponent, up to the bit of the
+        // row, and the bytes before it
+        let exp_byte = exp[NUM_BYTES - 1 - idx / 16] >> (7 - idx / 2 % 8);
+        let exp_rlc = exp
+            .iter()
+            .rev()
+            .take((idx + 1) / 16)
+            .fold(F::zero(), |acc, byte| acc * self.r + F::from(*byte as u64));
+
+        let mut values = vec![
+            (self.is_zero_modulus, F::from((modulus_sum == 0) as u64)),
+            (
+                self.modulus_sum_inv,
+                F::from(modulus_sum).invert().unwrap_or(F::zero()),
+            ),
+            (self.bit, F::from(row.bit as u64)),
+            (self.exp_byte, F::from(exp_byte as u64)),
+            (self.exp_rlc, exp_rlc),
+        ];
+        for (columns, bytes) in [
+            (&self.base, &base),
+            (&self.modulus, &modulus),
+            (&self.lhs, &row.lhs),
+            (&self.rhs, &row.rhs),
+            (&self.quotient, &row.quotient),
+            (&self.remainder, &row.remainder),
+        ] {
+            for (column, byte) in columns.iter().zip(bytes.iter()) {
+                values.push((*column, F::from(*byte as u64)));
+            }
+        }
+        for ([low, high], carry) in self.carries.iter().zip(row.carries(&modulus)) {
+            let carry = (carry + CARRY_OFFSET as i64) as u64;
+            values.push((*low, F::from(carry & 0xff)));
+            values.push((*high, F::from((carry >> 8) & 0xff)));
+        }
+        let (diff, borrows) = row.diff(&modulus);
+        for ((diff_column, borrow_column), (diff, borrow)) in self
+            .diff
+            .iter()
+            .zip(self.borrows.iter())
+            .zip(diff.iter().zip(borrows.iter()))
+        {
+            values.push((*diff_column, F::from(*diff as u64)));
+            values.push((*borrow_column, F::from(*borrow as u64)));
+        }
+        let table_row = if idx == ROWS_PER_CALL - 1 {
+            call.call.table_assignment(self.r)
+        } else {
+            [F::zero(); MODEXP_TABLE_WIDTH]
+        };
+        values.extend(self.modexp_table.iter().copied().zip(table_row));
+
+        for (column, value) in values {
+            region.assign_advice(
+                || format!("assign modexp {}", offset),
+                column,
+                offset,
+                || Ok(value),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Assign a row out of the calls, where only the constraints of the
+    /// table are enabled so that its values are all zero.
+    fn assign_empty_row(&self, region: &mut Region<'_, F>, offset: usize) -> Result<(), Error> {
+        self.q_enable.enable(region, offset)?;
+        let columns = [
+            self.is_zero_modulus,
+            self.modulus_sum_inv,
+            self.bit,
+            self.exp_byte,
+            self.exp_rlc,
+        ];
+        for column in columns
+            .iter()
+            .chain(self.base.iter())
+            .chain(self.modulus.iter())
+            .chain(self.lhs.iter())
+            .chain(self.rhs.iter())
+            .chain(self.quotient.iter())
+            .chain(self.remainder.iter())
+            .chain(self.carries.iter().flatten())
+            .chain(self.diff.iter())
+            .chain(self.borrows.iter())
+            .chain(self.modexp_table.iter())
+        {
+            region.assign_advice(
+                || format!("assign modexp empty row {}", offset),
+                *column,
+                offset,
+                || Ok(F::zero()),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn query<F: Field>(
+    meta: &mut VirtualCells<'_, F>,
+    columns: &[Column<Advice>],
+    rotation: i32,
+) -> Vec<Expression<F>> {
+    columns
+        .iter()
+        .map(|column| meta.query_advice(*column, Rotation(rotation)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{word, ToBigEndian, Word};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use pairing::bn256::Fr;
+
+    #[derive(Default)]
+    struct MyCircuit<F> {
+        calls: Vec<CallWitness>,
+        size: usize,
+        _marker: std::marker::PhantomData<F>,
+    }
+
+    impl<F: Field> MyCircuit<F> {
+        fn r() -> F {
+            F::from(123456)
+        }
+    }
+
+    impl<F: Field> Circuit<F> for MyCircuit<F> {
+        type Config = Config<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            Config::configure(meta, MyCircuit::r())
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.assign_calls(&mut layouter, self.size, self.calls.clone())
+        }
+    }
+
+    fn verify<F: Field>(calls: Vec<CallWitness>, success: bool) {
+        let k = 12;
+        let circuit = MyCircuit::<F> {
+            calls,
+            size: 2usize.pow(k),
+            _marker: std::marker::PhantomData,
+        };
+
+        let prover = MockProver::<F>::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify().is_ok(), success);
+    }
+
+    fn modexp_call(base: Word, exp: Word, modulus: Word) -> ModexpCall {
+        let input = [Word::from(32); 3]
+            .iter()
+            .chain([base, exp, modulus].iter())
+            .flat_map(|word| word.to_be_bytes())
+            .collect::<Vec<_>>();
+        ModexpCall::new(&input).unwrap()
+    }
+
+    #[test]
+    fn modexp_valid() {
+        let calls = vec![
+            modexp_call(Word::from(2), Word::from(10), Word::from(1000)),
+            modexp_call(
+                word!("d8f9c2dcbbcad11f5a7fc3c3f7fbed9ea1b06d1e1b2f4d0a0cf6e2b18b2c4b2f"),
+                word!("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"),
+                word!("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f"),
+            ),
+            modexp_call(Word::from(3), Word::zero(), Word::from(5)),
+            modexp_call(Word::from(3), Word::from(5), Word::one()),
+            modexp_call(Word::MAX, Word::from(3), Word::MAX),
+        ];
+        assert_eq!(calls[0].result, Word::from(24));
+        assert_eq!(calls[2].result, Word::one());
+        assert_eq!(calls[3].result, Word::zero());
+
+        verify::<Fr>(calls.iter().map(CallWitness::new).collect(), true);
+    }
+
+    #[test]
+    fn modexp_zero_modulus() {
+        let call = modexp_call(Word::from(3), Word::from(5), Word::zero());
+        assert_eq!(call.result, Word::zero());
+        verify::<Fr>(vec![CallWitness::new(&call)], true);
+    }
+
+    #[test]
+    fn modexp_empty() {
+        verify::<Fr>(vec![], true);
+    }
+
+    #[test]
+    fn modexp_invalid_result() {
+        let call = modexp_call(Word::from(2), Word::from(10), Word::from(1000));
+        let call = ModexpCall {
+            result: Word::from(25),
+            ..call
+        };
+        verify::<Fr>(vec![CallWitness::new(&call)], false);
+    }
+
+    #[test]
+    fn modexp_invalid_exp() {
+        let call = modexp_call(Word::from(2), Word::from(10), Word::from(1000));
+        let mut call = CallWitness::new(&call);
+        call.call.exp = Word::from(11);
+        verify::<Fr>(vec![call], false);
+    }
+
+    #[test]
+    fn modexp_invalid_remainder() {
+        // 3^5 % 7 = 5 is computed by 4 * 3 = 1 * 7 + 5 in the last row, which
+        // is replaced by 4 * 3 = 0 * 7 + 12
+        let call = modexp_call(Word::from(3), Word::from(5), Word::from(7));
+        assert_eq!(call.result, Word::from(5));
+        let mut call = CallWitness::new(&call);
+        call.call.result = Word::from(12);
+        let last = call.rows.last_mut().unwrap();
+        assert_eq!((last.lhs[0], last.rhs[0], last.quotient[0]), (4, 3, 1));
+        last.quotient[0] = 0;
+        last.remainder[0] = 12;
+        verify::<Fr>(vec![call], false);
+    }
+}
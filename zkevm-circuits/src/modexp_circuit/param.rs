@@ -0,0 +1,11 @@
+use bus_mapping::precompile::MODEXP_MAX_INPUT_SIZE;
+
+pub const MODEXP_TABLE_WIDTH: usize = 4;
+
+/// The number of bytes of the base, the exponent, the modulus and the
+/// intermediate results.
+pub const NUM_BYTES: usize = MODEXP_MAX_INPUT_SIZE;
+
+/// The number of rows of a call, which squares and then multiplies for every
+/// bit of the exponent.
+pub const ROWS_PER_CALL: usize = 2 * 8 * NUM_BYTES;
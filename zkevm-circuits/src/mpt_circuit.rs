@@ -0,0 +1,7 @@
+//! The MPT circuit implementation, which proves the updates of the state
+//! trie from the state root before the block to the one after it, by
+//! checking the proofs of the accessed accounts and storage slots before and
+//! after each update.
+
+pub(crate) mod mpt;
+pub(crate) mod param;
@@ -0,0 +1,3158 @@
+use super::param::{
+    ADDRESS_LEN, GRAMMAR_TABLE_WIDTH, HASH_LEN, KECCAK_TABLE_WIDTH, KEY_NIBBLES, MPT_TABLE_WIDTH,
+};
+use crate::{
+    evm_circuit::{
+        table::{LookupTable, MptFieldTag},
+        util::{
+            constraint_builder::BaseConstraintBuilder,
+            math_gadget::generate_lagrange_base_polynomial, not, select, sum,
+            RandomLinearCombination,
+        },
+        witness::{Block, MptUpdate},
+    },
+    gadget::evm_word::encode,
+    util::Expr,
+};
+use bus_mapping::mpt::{MptKey, MptProofNode};
+use eth_types::{Field, ToBigEndian, ToLittleEndian, Word};
+use halo2_proofs::{
+    circuit::{Layouter, Region},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector,
+        VirtualCells,
+    },
+    poly::Rotation,
+};
+use sha3::{Digest, Keccak256};
+
+// The indices of the columns of the MPT table
+const ADDRESS: usize = 0;
+const STORAGE_KEY: usize = 1;
+const FIELD_TAG: usize = 2;
+const OLD_ROOT: usize = 3;
+const NEW_ROOT: usize = 4;
+const OLD_VALUE: usize = 5;
+const NEW_VALUE: usize = 6;
+
+/// The RLP encoding of an empty node, which is the root of an empty trie.
+const EMPTY_NODE: [u8; 1] = [0x80];
+
+/// The kind of a node, which gives the grammar of its RLP items in the
+/// grammar table.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum NodeKind {
+    Branch = 1,
+    Extension,
+    AccountLeaf,
+    StorageLeaf,
+    Empty,
+}
+
+impl NodeKind {
+    /// Return the kind of the node encoded in `bytes`, which is a leaf of a
+    /// storage trie when `is_storage`.
+    fn of(bytes: &[u8], is_storage: bool) -> Self {
+        if bytes == EMPTY_NODE {
+            return NodeKind::Empty;
+        }
+        let mut items = Vec::new();
+        let mut offset = rlp_header_len(bytes);
+        while offset < bytes.len() {
+            items.push(offset);
+            offset += rlp_header_len(&bytes[offset..]) + rlp_payload_len(&bytes[offset..]);
+        }
+        if items.len() == 17 {
+            return NodeKind::Branch;
+        }
+
+        // The first nibble of the hex-prefix encoded path tells a leaf
+        let path = items.first().map(|offset| &bytes[*offset..]).unwrap_or(&[]);
+        let flag = path.get(rlp_header_len(path)).copied().unwrap_or(0) >> 4;
+        match (flag & 2 == 2, is_storage) {
+            (false, _) => NodeKind::Extension,
+            (true, false) => NodeKind::AccountLeaf,
+            (true, true) => NodeKind::StorageLeaf,
+        }
+    }
+}
+
+/// The form of an RLP item of a node.  The headers of the lists, and of the
+/// strings holding RLP encoded values, are items of their own, followed by
+/// the items of their payloads.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ItemForm {
+    /// A byte below 0x80, which is its own encoding
+    SingleByte = 1,
+    /// A string of up to 55 bytes after its header `0x80 + len`
+    ShortString,
+    /// The header `0x80 + len` or `0xc0 + len` of a string or a list of up to
+    /// 55 bytes
+    ShortHeader,
+    /// The header `0xb8` or `0xf8` of a string or a list, followed by its
+    /// length in a byte
+    LongHeader,
+    /// The header `0xf9` of a list, followed by its length in two bytes
+    LongHeader2,
+}
+
+impl ItemForm {
+    /// Return the form of the item `item` of a node of kind `kind`, which
+    /// starts with `byte`.
+    fn of(kind: NodeKind, item: u64, byte: u8) -> Self {
+        match (kind, item, byte) {
+            (NodeKind::Empty, _, _) => ItemForm::ShortString,
+            (_, 0, 0xf8) => ItemForm::LongHeader,
+            (_, 0, 0xf9) => ItemForm::LongHeader2,
+            (_, 0, _) => ItemForm::ShortHeader,
+            // The account is RLP encoded in a string
+            (NodeKind::AccountLeaf, 2 | 3, _) => ItemForm::LongHeader,
+            // So is the value of a storage slot, unless it's a single byte
+            (NodeKind::StorageLeaf, 2, 0x80..=0xff) => ItemForm::ShortHeader,
+            (_, _, 0..=0x7f) => ItemForm::SingleByte,
+            _ => ItemForm::ShortString,
+        }
+    }
+
+    /// Return the length of an item of this form starting with `byte`.
+    fn len(&self, byte: u8) -> u64 {
+        match self {
+            ItemForm::SingleByte | ItemForm::ShortHeader => 1,
+            ItemForm::ShortString => 1 + byte.saturating_sub(0x80) as u64,
+            ItemForm::LongHeader => 2,
+            ItemForm::LongHeader2 => 3,
+        }
+    }
+}
+
+/// Return the length of the header of the RLP item at the start of `bytes`.
+fn rlp_header_len(bytes: &[u8]) -> usize {
+    match bytes.first().copied().unwrap_or(0) {
+        0..=0x7f => 0,
+        0x80..=0xb7 | 0xc0..=0xf7 => 1,
+        byte @ 0xb8..=0xbf => 1 + (byte - 0xb7) as usize,
+        byte => 1 + (byte - 0xf7) as usize,
+    }
+}
+
+/// Return the length of the payload of the RLP item at the start of `bytes`.
+fn rlp_payload_len(bytes: &[u8]) -> usize {
+    match bytes.first().copied().unwrap_or(0) {
+        0..=0x7f => 1,
+        byte @ 0x80..=0xb7 => (byte - 0x80) as usize,
+        byte @ 0xc0..=0xf7 => (byte - 0xc0) as usize,
+        _ => bytes
+            .iter()
+            .take(rlp_header_len(bytes))
+            .skip(1)
+            .fold(0, |len, byte| len * 256 + *byte as usize),
+    }
+}
+
+/// Return the item of an account leaf holding the field of `field_tag`,
+/// after the header of the leaf, its path, and the headers of the string and
+/// the list of the account.
+fn account_leaf_item(field_tag: MptFieldTag) -> u64 {
+    match field_tag {
+        MptFieldTag::Nonce => 4,
+        MptFieldTag::Balance => 5,
+        // The storage root is the reference to the storage trie
+        MptFieldTag::Storage | MptFieldTag::StorageRoot => 6,
+        MptFieldTag::CodeHash => 7,
+    }
+}
+
+/// Return the rows of the grammar table, which are the kind of a node, the
+/// index of an item in it, the form of the item, its first byte and whether
+/// it's the last item of the node.  The nodes embedded in their parents,
+/// which are shorter than a hash, and the values of the branches aren't in
+/// the grammar, as they don't occur in the tries of the state short of
+/// billions of keys.
+fn grammar_rows() -> Vec<[u64; GRAMMAR_TABLE_WIDTH]> {
+    use ItemForm::*;
+    use NodeKind::*;
+
+    let mut rows = Vec::new();
+    let mut push = |kind: NodeKind, item: u64, form: ItemForm, first: u64, last: u64| {
+        rows.extend((first..=last).map(|byte| {
+            let is_last_item = match kind {
+                Branch => item == 17,
+                Extension => item == 2,
+                AccountLeaf => item == 7,
+                StorageLeaf => item == 3 || (item == 2 && form == SingleByte),
+                Empty => true,
+            };
+            [kind as u64, item, form as u64, byte, is_last_item as u64]
+        }))
+    };
+
+    push(Branch, 0, LongHeader, 0xf8, 0xf8);
+    push(Branch, 0, LongHeader2, 0xf9, 0xf9);
+    for item in 1..=16 {
+        push(Branch, item, ShortString, 0x80, 0x80);
+        push(Branch, item, ShortString, 0xa0, 0xa0);
+    }
+    push(Branch, 17, ShortString, 0x80, 0x80);
+
+    push(Extension, 0, ShortHeader, 0xc0, 0xf7);
+    push(Extension, 0, LongHeader, 0xf8, 0xf8);
+    push(Extension, 1, SingleByte, 0x10, 0x1f);
+    push(Extension, 1, ShortString, 0x81, 0xa1);
+    push(Extension, 2, ShortString, 0xa0, 0xa0);
+
+    push(AccountLeaf, 0, LongHeader, 0xf8, 0xf8);
+    push(AccountLeaf, 1, SingleByte, 0x20, 0x3f);
+    push(AccountLeaf, 1, ShortString, 0x81, 0xa1);
+    push(AccountLeaf, 2, LongHeader, 0xb8, 0xb8);
+    push(AccountLeaf, 3, LongHeader, 0xf8, 0xf8);
+    for item in [4, 5] {
+        push(AccountLeaf, item, SingleByte, 0x01, 0x7f);
+        push(AccountLeaf, item, ShortString, 0x80, 0xa0);
+    }
+    push(AccountLeaf, 6, ShortString, 0xa0, 0xa0);
+    push(AccountLeaf, 7, ShortString, 0xa0, 0xa0);
+
+    push(StorageLeaf, 0, ShortHeader, 0xc0, 0xf7);
+    push(StorageLeaf, 0, LongHeader, 0xf8, 0xf8);
+    push(StorageLeaf, 1, SingleByte, 0x20, 0x3f);
+    push(StorageLeaf, 1, ShortString, 0x81, 0xa1);
+    push(StorageLeaf, 2, SingleByte, 0x01, 0x7f);
+    push(StorageLeaf, 2, ShortHeader, 0x82, 0xa1);
+    push(StorageLeaf, 3, ShortString, 0x81, 0xa0);
+
+    push(Empty, 0, ShortString, 0x80, 0x80);
+    rows
+}
+
+/// The row of a byte of a node of a path, which is the proof of the key of
+/// an update before or after it.
+#[derive(Clone, Debug)]
+struct NodeRow<F> {
+    /// The `address`, `storage_key`, `field_tag`, `root`, `value`, `is_new`
+    /// and `update_idx` of the path, which are the same on all its rows
+    path: [F; 7],
+    path_len: u64,
+    ends_with_branch: bool,
+    ends_with_empty: bool,
+    compared_depth: u64,
+    /// The length of the path minus the one of the path before the update,
+    /// on the first row of the path after it
+    len_diff: F,
+    is_new_shorter: bool,
+    is_path_start: bool,
+    is_first_byte: bool,
+    byte: u8,
+    bytes_left: u64,
+    node_len: u64,
+    node_rlc: F,
+    node_hash: F,
+    kind: NodeKind,
+    depth: u64,
+    is_storage: bool,
+    item: u64,
+    item_left: u64,
+    is_item_start: bool,
+    form: ItemForm,
+    is_last_item: bool,
+    target_item: u64,
+    is_hp_flag: bool,
+    is_hp_byte: bool,
+    is_hp_odd: bool,
+    path_pos: u64,
+    path_acc: F,
+    key_prefix: F,
+    is_match: bool,
+    has_child: bool,
+    is_compared: bool,
+    is_compared_byte: bool,
+    is_child: bool,
+    child_len: u64,
+    child_rlc: F,
+    child_hash: F,
+    is_value: bool,
+    value_rlc: F,
+}
+
+/// The row of a nibble of a key of the tries of an update, which is the hash
+/// of its address or of its storage key.
+#[derive(Clone, Debug)]
+struct KeyRow<F> {
+    update_idx: u64,
+    is_storage: bool,
+    /// The address, or the random linear combination of the storage key
+    input: F,
+    pos: u64,
+    byte: u8,
+    is_preimage: bool,
+    input_value: F,
+    input_rlc: F,
+    nibble: u8,
+    acc: F,
+}
+
+/// The updates of the state trie, in the MPT table looked up by the state
+/// circuit, which chain the state roots from the one before the block to the
+/// one after it.  Each update is proved by the paths of its key in the tries
+/// before and after it, with a row per byte of their nodes.  The first node
+/// of a path hashes to the root, the hash of every other node is referenced
+/// in the previous one, and the last one holds the value, which is 0 when
+/// the key isn't in the trie.
+///
+/// The bytes of a node are split into the RLP items of the grammar of its
+/// kind, in the grammar table.  The path walks the nibbles of the hash of the
+/// address, then of the storage key, which are in rows of their own: a branch
+/// takes the child at the next nibble, and the hex-prefix encoded path of an
+/// extension or a leaf is either a prefix of the key left, or diverges from
+/// it when the key isn't in the trie.  The references and the values are the
+/// items at these nibbles, or at the field of the update in an account leaf.
+/// The nodes of the paths before and after an update are the same down to
+/// where the update changes the structure of the trie, except for the items
+/// on the path.
+///
+/// TODO: Check the nodes of the paths below that depth, which split or merge
+/// a leaf or an extension with the one of another key when the update adds
+/// or removes its key, and the other fields of an account it creates or
+/// removes.  Until then, only their key and value are checked.
+#[derive(Clone, Debug)]
+pub struct Config<F> {
+    r: F,
+    minimum_rows: usize,
+    /// The bytes, from 0 to 255
+    byte_table: Column<Fixed>,
+    /// The nibbles, from 0 to 15
+    nibble_table: Column<Fixed>,
+    /// The grammar of the nodes, from [`grammar_rows`]
+    grammar_table: [Column<Fixed>; GRAMMAR_TABLE_WIDTH],
+    /// Enabled on all the usable rows of the MPT table
+    q_table: Selector,
+    /// Enabled on the first row of the MPT table
+    q_table_first: Selector,
+    /// Enabled on the last usable row of the MPT table
+    q_table_last: Selector,
+    /// The MPT table looked up by the state circuit, with a row per update
+    mpt_table: [Column<Advice>; MPT_TABLE_WIDTH],
+    /// The index of the update of the row of the MPT table
+    table_update_idx: Column<Fixed>,
+    /// Whether the row of the MPT table is out of the updates, which keeps
+    /// the root
+    table_is_padding: Column<Advice>,
+    /// The state root after the block, which is the same on all the rows of
+    /// the MPT table
+    final_root: Column<Advice>,
+    /// The state roots before and after the block
+    state_roots: Column<Instance>,
+    /// Enabled on all the usable rows of the nodes
+    q_node: Selector,
+    /// Enabled on the first row of the nodes
+    q_node_first: Selector,
+    /// Enabled on the last usable row of the nodes
+    q_node_last: Selector,
+    address: Column<Advice>,
+    storage_key: Column<Advice>,
+    field_tag: Column<Advice>,
+    /// The root of the trie of the path
+    root: Column<Advice>,
+    /// The value at the key in the trie of the path
+    value: Column<Advice>,
+    /// Whether the path is the one after the update
+    is_new: Column<Advice>,
+    /// The index of the update of the path, whose paths before and after it
+    /// follow each other
+    update_idx: Column<Advice>,
+    /// The number of nodes of the path
+    path_len: Column<Advice>,
+    ends_with_branch: Column<Advice>,
+    ends_with_empty: Column<Advice>,
+    /// The depth down to which the nodes of the paths of the update are the
+    /// same except for the items on the path
+    compared_depth: Column<Advice>,
+    /// Whether the path after the update is shorter than the one before it,
+    /// on the first row of the path after it
+    is_new_shorter: Column<Advice>,
+    len_diff_inv: Column<Advice>,
+    /// Whether the row is the first one of a path
+    is_path_start: Column<Advice>,
+    is_first_byte: Column<Advice>,
+    byte: Column<Advice>,
+    byte_hi: Column<Advice>,
+    byte_lo: Column<Advice>,
+    /// The number of bytes of the node from the byte of the row
+    bytes_left: Column<Advice>,
+    bytes_left_inv: Column<Advice>,
+    /// Whether the byte is the last one of the node, when `bytes_left == 1`
+    is_last_byte: Column<Advice>,
+    node_len: Column<Advice>,
+    /// The random linear combination of the bytes of the node up to the one
+    /// of the row
+    node_rlc: Column<Advice>,
+    node_hash: Column<Advice>,
+    is_branch: Column<Advice>,
+    is_extension: Column<Advice>,
+    is_leaf: Column<Advice>,
+    is_empty: Column<Advice>,
+    /// The index of the node in the path
+    depth: Column<Advice>,
+    /// Whether the node is in a storage trie, after an account leaf
+    is_storage: Column<Advice>,
+    /// The index of the RLP item of the byte in the node
+    item: Column<Advice>,
+    /// The number of bytes of the item after the one of the row
+    item_left: Column<Advice>,
+    item_left_inv: Column<Advice>,
+    is_item_start: Column<Advice>,
+    is_single_byte: Column<Advice>,
+    is_short_string: Column<Advice>,
+    is_short_header: Column<Advice>,
+    is_long_header: Column<Advice>,
+    is_long_header_2: Column<Advice>,
+    is_last_item: Column<Advice>,
+    /// The item of the node on the path of the key, which is the child of a
+    /// branch at the next nibble, the child of an extension, or the value or
+    /// the storage root of a leaf
+    target_item: Column<Advice>,
+    target_inv: Column<Advice>,
+    /// The inverse of `item - 1`, as the path of an extension or a leaf is
+    /// its item 1
+    path_item_inv: Column<Advice>,
+    /// Whether the byte is the first one of the hex-prefix encoded path of an
+    /// extension or a leaf, whose first nibble is its flag
+    is_hp_flag: Column<Advice>,
+    /// Whether the byte is one of the next ones, with two nibbles of the path
+    is_hp_byte: Column<Advice>,
+    /// Whether the hex-prefix encoded path has an odd number of nibbles, the
+    /// first of which is in the flag byte
+    is_hp_odd: Column<Advice>,
+    /// The number of nibbles of the key walked by the path up to the byte of
+    /// the row, from the root of its trie
+    path_pos: Column<Advice>,
+    path_is_odd: Column<Advice>,
+    /// The random linear combination of the walked nibbles, with the nibbles
+    /// at even positions multiplied by 16 so that pairs of them make bytes
+    path_acc: Column<Advice>,
+    /// The random linear combination of the nibbles of the key up to
+    /// `path_pos`, on the last byte of a node
+    key_prefix: Column<Advice>,
+    /// Whether the nibbles walked by the node are the ones of the key
+    is_match: Column<Advice>,
+    match_inv: Column<Advice>,
+    /// Whether the node references the next node of the path at its target
+    has_child: Column<Advice>,
+    /// Whether the depth of the node is below `compared_depth`
+    is_compared: Column<Advice>,
+    /// Whether the byte is compared to the path of the other side of the
+    /// update, which are the bytes of the items of a compared node, except
+    /// the target and the headers of the lists and the RLP encoded values
+    is_compared_byte: Column<Advice>,
+    /// Whether the byte is in the hash of the next node
+    is_child: Column<Advice>,
+    child_len: Column<Advice>,
+    child_rlc: Column<Advice>,
+    /// The hash of the next node, or 0 in the last node of the path
+    child_hash: Column<Advice>,
+    /// Whether the byte is in the value, in the last node of the path
+    is_value: Column<Advice>,
+    value_rlc: Column<Advice>,
+    /// Whether the row is out of the paths
+    is_padding: Column<Advice>,
+    /// Enabled on all the usable rows of the keys
+    q_key: Selector,
+    /// Enabled on the first row of the keys
+    q_key_first: Selector,
+    /// Enabled on the last usable row of the keys
+    q_key_last: Selector,
+    key_update_idx: Column<Advice>,
+    /// Whether the key is the one of the storage slot, or of the account
+    key_is_storage: Column<Advice>,
+    /// The address, or the random linear combination of the storage key,
+    /// which is hashed into the key
+    key_input: Column<Advice>,
+    /// The position of the nibble of the row in the key, up to
+    /// `KEY_NIBBLES` on the last row of a key
+    key_pos: Column<Advice>,
+    key_pos_inv: Column<Advice>,
+    /// A byte of the address, big-endian
+    key_byte: Column<Advice>,
+    key_is_preimage: Column<Advice>,
+    /// The value of the bytes of the address up to the one of the row
+    key_input_value: Column<Advice>,
+    /// The random linear combination of the bytes of the address up to the
+    /// one of the row
+    key_input_rlc: Column<Advice>,
+    key_nibble: Column<Advice>,
+    key_is_odd: Column<Advice>,
+    /// The random linear combination of the nibbles of the key before the
+    /// one of the row, as `path_acc`
+    key_acc: Column<Advice>,
+    /// Whether the row is out of the keys
+    key_is_padding: Column<Advice>,
+}
+
+impl<F: Field> Config<F> {
+    /// Configure the circuit, which looks up the hashes of the nodes and of
+    /// the keys in `keccak_table`, with the random linear combination of the
+    /// input, its length and the random linear combination of its hash as a
+    /// word.
+    pub(crate) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        r: F,
+        keccak_table: impl LookupTable<F, KECCAK_TABLE_WIDTH>,
+    ) -> Self {
+        let byte_table = meta.fixed_column();
+        let nibble_table = meta.fixed_column();
+        let grammar_table = [(); GRAMMAR_TABLE_WIDTH].map(|_| meta.fixed_column());
+        // The selectors are combined with each other in the gates and used in
+        // the lookups, which requires complex selectors
+        let q_table = meta.complex_selector();
+        let q_table_first = meta.complex_selector();
+        let q_table_last = meta.complex_selector();
+        let mpt_table = [(); MPT_TABLE_WIDTH].map(|_| meta.advice_column());
+        let table_update_idx = meta.fixed_column();
+        let table_is_padding = meta.advice_column();
+        let final_root = meta.advice_column();
+        let state_roots = meta.instance_column();
+        let q_node = meta.complex_selector();
+        let q_node_first = meta.complex_selector();
+        let q_node_last = meta.complex_selector();
+        let address = meta.advice_column();
+        let storage_key = meta.advice_column();
+        let field_tag = meta.advice_column();
+        let root = meta.advice_column();
+        let value = meta.advice_column();
+        let is_new = meta.advice_column();
+        let update_idx = meta.advice_column();
+        let path_len = meta.advice_column();
+        let ends_with_branch = meta.advice_column();
+        let ends_with_empty = meta.advice_column();
+        let compared_depth = meta.advice_column();
+        let is_new_shorter = meta.advice_column();
+        let len_diff_inv = meta.advice_column();
+        let is_path_start = meta.advice_column();
+        let is_first_byte = meta.advice_column();
+        let byte = meta.advice_column();
+        let byte_hi = meta.advice_column();
+        let byte_lo = meta.advice_column();
+        let bytes_left = meta.advice_column();
+        let bytes_left_inv = meta.advice_column();
+        let is_last_byte = meta.advice_column();
+        let node_len = meta.advice_column();
+        let node_rlc = meta.advice_column();
+        let node_hash = meta.advice_column();
+        let is_branch = meta.advice_column();
+        let is_extension = meta.advice_column();
+        let is_leaf = meta.advice_column();
+        let is_empty = meta.advice_column();
+        let depth = meta.advice_column();
+        let is_storage = meta.advice_column();
+        let item = meta.advice_column();
+        let item_left = meta.advice_column();
+        let item_left_inv = meta.advice_column();
+        let is_item_start = meta.advice_column();
+        let is_single_byte = meta.advice_column();
+        let is_short_string = meta.advice_column();
+        let is_short_header = meta.advice_column();
+        let is_long_header = meta.advice_column();
+        let is_long_header_2 = meta.advice_column();
+        let is_last_item = meta.advice_column();
+        let target_item = meta.advice_column();
+        let target_inv = meta.advice_column();
+        let path_item_inv = meta.advice_column();
+        let is_hp_flag = meta.advice_column();
+        let is_hp_byte = meta.advice_column();
+        let is_hp_odd = meta.advice_column();
+        let path_pos = meta.advice_column();
+        let path_is_odd = meta.advice_column();
+        let path_acc = meta.advice_column();
+        let key_prefix = meta.advice_column();
+        let is_match = meta.advice_column();
+        let match_inv = meta.advice_column();
+        let has_child = meta.advice_column();
+        let is_compared = meta.advice_column();
+        let is_compared_byte = meta.advice_column();
+        let is_child = meta.advice_column();
+        let child_len = meta.advice_column();
+        let child_rlc = meta.advice_column();
+        let child_hash = meta.advice_column();
+        let is_value = meta.advice_column();
+        let value_rlc = meta.advice_column();
+        let is_padding = meta.advice_column();
+        let q_key = meta.complex_selector();
+        let q_key_first = meta.complex_selector();
+        let q_key_last = meta.complex_selector();
+        let key_update_idx = meta.advice_column();
+        let key_is_storage = meta.advice_column();
+        let key_input = meta.advice_column();
+        let key_pos = meta.advice_column();
+        let key_pos_inv = meta.advice_column();
+        let key_byte = meta.advice_column();
+        let key_is_preimage = meta.advice_column();
+        let key_input_value = meta.advice_column();
+        let key_input_rlc = meta.advice_column();
+        let key_nibble = meta.advice_column();
+        let key_is_odd = meta.advice_column();
+        let key_acc = meta.advice_column();
+        let key_is_padding = meta.advice_column();
+
+        // The roots at both ends of the chain of updates are copied from the
+        // public inputs
+        meta.enable_equality(mpt_table[OLD_ROOT]);
+        meta.enable_equality(final_root);
+        meta.enable_equality(state_roots);
+
+        let field_tags = || {
+            [
+                MptFieldTag::Nonce,
+                MptFieldTag::Balance,
+                MptFieldTag::CodeHash,
+                MptFieldTag::Storage,
+                MptFieldTag::StorageRoot,
+            ]
+        };
+        let is_storage_tag = |field_tag: Expression<F>| {
+            generate_lagrange_base_polynomial(
+                field_tag,
+                MptFieldTag::Storage as usize,
+                MptFieldTag::Nonce as usize..=MptFieldTag::StorageRoot as usize,
+            )
+        };
+        let forms = |meta: &mut VirtualCells<F>| {
+            [
+                is_single_byte,
+                is_short_string,
+                is_short_header,
+                is_long_header,
+                is_long_header_2,
+            ]
+            .map(|column| meta.query_advice(column, Rotation::cur()))
+        };
+        // The kind of the node, as in the grammar table
+        let node_kind = |meta: &mut VirtualCells<F>| {
+            meta.query_advice(is_branch, Rotation::cur()) * (NodeKind::Branch as u64).expr()
+                + meta.query_advice(is_extension, Rotation::cur())
+                    * (NodeKind::Extension as u64).expr()
+                + meta.query_advice(is_leaf, Rotation::cur())
+                    * ((NodeKind::AccountLeaf as u64).expr()
+                        + meta.query_advice(is_storage, Rotation::cur()))
+                + meta.query_advice(is_empty, Rotation::cur()) * (NodeKind::Empty as u64).expr()
+        };
+        // Whether the byte is in the payload of its item, rather than in a
+        // header
+        let is_payload = |meta: &mut VirtualCells<F>| {
+            meta.query_advice(is_single_byte, Rotation::cur())
+                + meta.query_advice(is_short_string, Rotation::cur())
+                    * not::expr(meta.query_advice(is_item_start, Rotation::cur()))
+        };
+        let item_diff = |meta: &mut VirtualCells<F>, other: Expression<F>, inv: Column<Advice>| {
+            let diff = meta.query_advice(item, Rotation::cur()) - other;
+            let is_zero = 1.expr() - diff.clone() * meta.query_advice(inv, Rotation::cur());
+            (diff, is_zero)
+        };
+        // Whether the item of the row is the target one, and the item 1
+        let is_target = |meta: &mut VirtualCells<F>| {
+            let target_item = meta.query_advice(target_item, Rotation::cur());
+            item_diff(meta, target_item, target_inv)
+        };
+        let is_path_item = |meta: &mut VirtualCells<F>| item_diff(meta, 1.expr(), path_item_inv);
+
+        meta.create_gate("mpt table row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let table = query(meta, &mpt_table, Rotation::cur());
+            let is_padding = meta.query_advice(table_is_padding, Rotation::cur());
+
+            cb.require_boolean("is_padding is boolean", is_padding.clone());
+            cb.condition(not::expr(is_padding.clone()), |cb| {
+                cb.require_in_set(
+                    "field_tag is valid",
+                    table[FIELD_TAG].clone(),
+                    field_tags().map(|tag| tag.expr()).to_vec(),
+                );
+                cb.require_zero(
+                    "storage_key = 0 for the account fields",
+                    (table[FIELD_TAG].clone() - MptFieldTag::Storage.expr())
+                        * table[STORAGE_KEY].clone(),
+                );
+            });
+            cb.condition(is_padding, |cb| {
+                for idx in [ADDRESS, STORAGE_KEY, FIELD_TAG, OLD_VALUE, NEW_VALUE] {
+                    cb.require_zero("value is 0 on padding rows", table[idx].clone());
+                }
+                cb.require_equal(
+                    "padding rows keep the root",
+                    table[NEW_ROOT].clone(),
+                    table[OLD_ROOT].clone(),
+                );
+            });
+
+            cb.gate(meta.query_selector(q_table))
+        });
+
+        meta.create_gate("mpt table transition", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_padding = meta.query_advice(table_is_padding, Rotation::cur());
+            let is_padding_prev = meta.query_advice(table_is_padding, Rotation::prev());
+
+            cb.require_zero(
+                "padding rows are only followed by padding rows",
+                is_padding_prev * not::expr(is_padding),
+            );
+            cb.require_equal(
+                "an update starts from the root of the previous one",
+                meta.query_advice(mpt_table[OLD_ROOT], Rotation::cur()),
+                meta.query_advice(mpt_table[NEW_ROOT], Rotation::prev()),
+            );
+            cb.require_equal(
+                "final_root is the same on all the rows",
+                meta.query_advice(final_root, Rotation::cur()),
+                meta.query_advice(final_root, Rotation::prev()),
+            );
+
+            cb.gate(meta.query_selector(q_table) - meta.query_selector(q_table_first))
+        });
+
+        meta.create_gate("mpt table last row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            cb.require_equal(
+                "the last update ends at the final root",
+                meta.query_advice(mpt_table[NEW_ROOT], Rotation::cur()),
+                meta.query_advice(final_root, Rotation::cur()),
+            );
+            cb.gate(meta.query_selector(q_table_last))
+        });
+
+        meta.create_gate("mpt node row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [is_path_start, is_first_byte, is_child, is_value, is_padding, is_new] = [
+                is_path_start,
+                is_first_byte,
+                is_child,
+                is_value,
+                is_padding,
+                is_new,
+            ]
+            .map(|column| meta.query_advice(column, Rotation::cur()));
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let bytes_left = meta.query_advice(bytes_left, Rotation::cur());
+            let field_tag = meta.query_advice(field_tag, Rotation::cur());
+
+            for (name, flag) in [
+                ("is_path_start is boolean", is_path_start.clone()),
+                ("is_first_byte is boolean", is_first_byte.clone()),
+                ("is_child is boolean", is_child.clone()),
+                ("is_value is boolean", is_value.clone()),
+                ("is_padding is boolean", is_padding.clone()),
+                ("is_new is boolean", is_new),
+            ] {
+                cb.require_boolean(name, flag);
+            }
+            for column in [
+                ends_with_branch,
+                ends_with_empty,
+                is_new_shorter,
+                is_compared,
+                is_compared_byte,
+            ] {
+                cb.require_boolean(
+                    "path flag is boolean",
+                    meta.query_advice(column, Rotation::cur()),
+                );
+            }
+
+            let is_last_byte = meta.query_advice(is_last_byte, Rotation::cur());
+            let bytes_left_inv = meta.query_advice(bytes_left_inv, Rotation::cur());
+            let bytes_left_minus_one = bytes_left.clone() - 1.expr();
+            cb.require_equal(
+                "is_last_byte = bytes_left == 1",
+                is_last_byte.clone(),
+                1.expr() - bytes_left_minus_one.clone() * bytes_left_inv,
+            );
+            cb.require_zero(
+                "is_last_byte = bytes_left == 1",
+                is_last_byte * bytes_left_minus_one,
+            );
+
+            cb.condition(is_first_byte.clone(), |cb| {
+                cb.require_equal(
+                    "bytes_left = node_len on the first byte",
+                    bytes_left,
+                    meta.query_advice(node_len, Rotation::cur()),
+                );
+                cb.require_equal(
+                    "node_rlc starts with the first byte",
+                    meta.query_advice(node_rlc, Rotation::cur()),
+                    byte.clone(),
+                );
+                cb.require_equal(
+                    "child_rlc starts with the first byte",
+                    meta.query_advice(child_rlc, Rotation::cur()),
+                    is_child.clone() * byte.clone(),
+                );
+                cb.require_equal(
+                    "child_len starts with the first byte",
+                    meta.query_advice(child_len, Rotation::cur()),
+                    is_child.clone(),
+                );
+                cb.require_equal(
+                    "value_rlc starts with the first byte",
+                    meta.query_advice(value_rlc, Rotation::cur()),
+                    is_value.clone() * byte,
+                );
+            });
+            cb.condition(is_path_start.clone(), |cb| {
+                cb.require_equal(
+                    "a path starts with the first byte of a node",
+                    is_first_byte,
+                    1.expr(),
+                );
+                cb.require_equal(
+                    "the first node of a path hashes to the root",
+                    meta.query_advice(node_hash, Rotation::cur()),
+                    meta.query_advice(root, Rotation::cur()),
+                );
+            });
+            cb.condition(is_padding.clone(), |cb| {
+                cb.require_zero("is_path_start = 0 on padding rows", is_path_start);
+                cb.require_zero("is_child = 0 on padding rows", is_child);
+                cb.require_zero("is_value = 0 on padding rows", is_value);
+            });
+            cb.condition(not::expr(is_padding), |cb| {
+                cb.require_in_set(
+                    "field_tag is valid",
+                    field_tag.clone(),
+                    field_tags().map(|tag| tag.expr()).to_vec(),
+                );
+                cb.require_zero(
+                    "storage_key = 0 for the account fields",
+                    (field_tag - MptFieldTag::Storage.expr())
+                        * meta.query_advice(storage_key, Rotation::cur()),
+                );
+            });
+
+            cb.gate(meta.query_selector(q_node))
+        });
+
+        meta.create_gate("mpt node first row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_path_start = meta.query_advice(is_path_start, Rotation::cur());
+            cb.require_zero(
+                "the first row starts a path or is padding",
+                not::expr(is_path_start.clone())
+                    * not::expr(meta.query_advice(is_padding, Rotation::cur())),
+            );
+            cb.condition(is_path_start, |cb| {
+                cb.require_zero(
+                    "the first path is the one before the first update",
+                    meta.query_advice(is_new, Rotation::cur()),
+                );
+                cb.require_zero(
+                    "the first path is the one of the first update",
+                    meta.query_advice(update_idx, Rotation::cur()),
+                );
+            });
+            // These flags are given by the previous row on the next rows
+            for column in [is_hp_flag, is_hp_byte] {
+                cb.require_zero(
+                    "the first row is out of the paths of the nodes",
+                    meta.query_advice(column, Rotation::cur()),
+                );
+            }
+            cb.gate(meta.query_selector(q_node_first))
+        });
+
+        meta.create_gate("mpt node last row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            // So that the end of the last path is checked on the next row
+            cb.require_equal(
+                "the last row is padding",
+                meta.query_advice(is_padding, Rotation::cur()),
+                1.expr(),
+            );
+            cb.gate(meta.query_selector(q_node_last))
+        });
+
+        meta.create_gate("mpt node transition", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_padding_prev = meta.query_advice(is_padding, Rotation::prev());
+            let [is_path_start, is_first_byte, is_child, is_value, is_padding] =
+                [is_path_start, is_first_byte, is_child, is_value, is_padding]
+                    .map(|column| meta.query_advice(column, Rotation::cur()));
+            let is_last_byte_prev = meta.query_advice(is_last_byte, Rotation::prev());
+            let [is_new_prev, is_new_cur] = [Rotation::prev(), Rotation::cur()]
+                .map(|rotation| meta.query_advice(is_new, rotation));
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let r = Expression::Constant(r);
+
+            cb.require_zero(
+                "padding rows are only followed by padding rows",
+                is_padding_prev.clone() * not::expr(is_padding.clone()),
+            );
+            cb.require_zero(
+                "a node starts after the last byte of the previous one",
+                not::expr(is_padding.clone()) * (is_first_byte.clone() - is_last_byte_prev.clone()),
+            );
+
+            // The previous row ends a path when this one starts the next path
+            // or the padding
+            let padding_start = is_padding.clone() * not::expr(is_padding_prev);
+            let path_end = is_path_start.clone() + padding_start.clone();
+            cb.condition(path_end, |cb| {
+                cb.require_equal(
+                    "a path ends with the last byte of a node",
+                    is_last_byte_prev,
+                    1.expr(),
+                );
+                cb.require_equal(
+                    "the last node of a path holds the value",
+                    meta.query_advice(value_rlc, Rotation::prev()),
+                    meta.query_advice(value, Rotation::prev()),
+                );
+                cb.require_zero(
+                    "the last node of a path references no child",
+                    meta.query_advice(child_len, Rotation::prev()),
+                );
+                cb.require_equal(
+                    "path_len is the number of nodes of the path",
+                    meta.query_advice(path_len, Rotation::prev()),
+                    meta.query_advice(depth, Rotation::prev()) + 1.expr(),
+                );
+                for (flag, kind) in [(ends_with_branch, is_branch), (ends_with_empty, is_empty)] {
+                    cb.require_equal(
+                        "the path ends with the kind of its last node",
+                        meta.query_advice(flag, Rotation::prev()),
+                        meta.query_advice(kind, Rotation::prev()),
+                    );
+                }
+            });
+
+            // The paths before and after an update follow each other, in the
+            // order of the updates
+            cb.condition(is_path_start.clone(), |cb| {
+                cb.require_equal(
+                    "the paths before and after the updates alternate",
+                    is_new_cur.clone() + is_new_prev.clone(),
+                    1.expr(),
+                );
+                cb.require_equal(
+                    "the path before an update starts the next update",
+                    meta.query_advice(update_idx, Rotation::cur()),
+                    meta.query_advice(update_idx, Rotation::prev()) + not::expr(is_new_cur.clone()),
+                );
+            });
+            cb.condition(padding_start, |cb| {
+                cb.require_equal(
+                    "the last path is the one after the last update",
+                    is_new_prev,
+                    1.expr(),
+                );
+            });
+
+            // The nodes of both paths of an update are compared down to the
+            // depth where the update changes the structure of the trie.  When
+            // the paths have the same length, it changes at most the last
+            // node, from or into an empty trie.  Otherwise, it adds or removes
+            // a leaf, which is in the longer path below the last node of the
+            // shorter one that's a branch, and splits or merges the last node
+            // of the shorter one otherwise.
+            cb.condition(is_path_start.clone() * is_new_cur, |cb| {
+                let path_len_old = meta.query_advice(path_len, Rotation::prev());
+                let path_len_diff =
+                    meta.query_advice(path_len, Rotation::cur()) - path_len_old.clone();
+                let is_same_len = 1.expr()
+                    - path_len_diff.clone() * meta.query_advice(len_diff_inv, Rotation::cur());
+                cb.require_zero(
+                    "is_same_len = both paths have the same length",
+                    path_len_diff.clone() * is_same_len.clone(),
+                );
+
+                let is_new_shorter = meta.query_advice(is_new_shorter, Rotation::cur());
+                let min_len = path_len_old + is_new_shorter.clone() * path_len_diff;
+                let [ends_with_empty_old, ends_with_empty_new] =
+                    [Rotation::prev(), Rotation::cur()]
+                        .map(|rotation| meta.query_advice(ends_with_empty, rotation));
+                let shorter_ends_with_branch = select::expr(
+                    is_new_shorter,
+                    meta.query_advice(ends_with_branch, Rotation::cur()),
+                    meta.query_advice(ends_with_branch, Rotation::prev()),
+                );
+                let compared_depth_cur = meta.query_advice(compared_depth, Rotation::cur());
+                cb.require_equal(
+                    "compared_depth is the depth where the update changes the trie",
+                    compared_depth_cur.clone(),
+                    select::expr(
+                        is_same_len,
+                        min_len.clone()
+                            - (ends_with_empty_old.clone() + ends_with_empty_new.clone()
+                                - ends_with_empty_old * ends_with_empty_new),
+                        min_len - 1.expr() + shorter_ends_with_branch,
+                    ),
+                );
+                cb.require_equal(
+                    "compared_depth is the same in both paths",
+                    compared_depth_cur,
+                    meta.query_advice(compared_depth, Rotation::prev()),
+                );
+            });
+
+            cb.condition(
+                not::expr(is_padding.clone()) * not::expr(is_path_start.clone()),
+                |cb| {
+                    for column in [
+                        address,
+                        storage_key,
+                        field_tag,
+                        root,
+                        value,
+                        is_new,
+                        update_idx,
+                        path_len,
+                        ends_with_branch,
+                        ends_with_empty,
+                        compared_depth,
+                    ] {
+                        cb.require_equal(
+                            "value is the same in all the rows of a path",
+                            meta.query_advice(column, Rotation::cur()),
+                            meta.query_advice(column, Rotation::prev()),
+                        );
+                    }
+                },
+            );
+
+            cb.condition(
+                not::expr(is_padding.clone()) * not::expr(is_first_byte.clone()),
+                |cb| {
+                    for column in [node_len, node_hash, child_hash] {
+                        cb.require_equal(
+                            "value is the same in all the rows of a node",
+                            meta.query_advice(column, Rotation::cur()),
+                            meta.query_advice(column, Rotation::prev()),
+                        );
+                    }
+                    cb.require_equal(
+                        "bytes_left decreases by 1",
+                        meta.query_advice(bytes_left, Rotation::cur()),
+                        meta.query_advice(bytes_left, Rotation::prev()) - 1.expr(),
+                    );
+                    cb.require_equal(
+                        "node_rlc accumulates the byte",
+                        meta.query_advice(node_rlc, Rotation::cur()),
+                        meta.query_advice(node_rlc, Rotation::prev()) * r.clone() + byte.clone(),
+                    );
+                    cb.require_equal(
+                        "child_rlc accumulates the byte when in the child",
+                        meta.query_advice(child_rlc, Rotation::cur()),
+                        meta.query_advice(child_rlc, Rotation::prev())
+                            * (1.expr() + is_child.clone() * (r.clone() - 1.expr()))
+                            + is_child.clone() * byte.clone(),
+                    );
+                    cb.require_equal(
+                        "child_len counts the bytes of the child",
+                        meta.query_advice(child_len, Rotation::cur()),
+                        meta.query_advice(child_len, Rotation::prev()) + is_child,
+                    );
+                    cb.require_equal(
+                        "value_rlc accumulates the byte when in the value",
+                        meta.query_advice(value_rlc, Rotation::cur()),
+                        meta.query_advice(value_rlc, Rotation::prev())
+                            * (1.expr() + is_value.clone() * (r - 1.expr()))
+                            + is_value * byte,
+                    );
+                },
+            );
+
+            // The nodes of a path after the first one are referenced by their
+            // hashes in the previous node
+            cb.condition(
+                not::expr(is_padding) * is_first_byte * not::expr(is_path_start),
+                |cb| {
+                    let child_hash_prev = meta.query_advice(child_hash, Rotation::prev());
+                    cb.require_equal(
+                        "the node hashes to the child of the previous node",
+                        meta.query_advice(node_hash, Rotation::cur()),
+                        child_hash_prev.clone(),
+                    );
+                    cb.require_equal(
+                        "the child of the previous node is a hash",
+                        meta.query_advice(child_len, Rotation::prev()),
+                        HASH_LEN.expr(),
+                    );
+                    cb.require_equal(
+                        "the child of the previous node is its hash",
+                        meta.query_advice(child_rlc, Rotation::prev()),
+                        child_hash_prev,
+                    );
+                },
+            );
+
+            cb.gate(meta.query_selector(q_node) - meta.query_selector(q_node_first))
+        });
+
+        // The bytes of a node are split into RLP items, whose forms and first
+        // bytes follow the grammar of the kind of the node
+        meta.create_gate("mpt node item row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [is_padding, is_first_byte, is_last_byte, is_item_start_cur, is_last_item_cur] = [
+                is_padding,
+                is_first_byte,
+                is_last_byte,
+                is_item_start,
+                is_last_item,
+            ]
+            .map(|column| meta.query_advice(column, Rotation::cur()));
+            let [is_branch_cur, is_extension_cur, is_leaf_cur, is_empty_cur] =
+                [is_branch, is_extension, is_leaf, is_empty]
+                    .map(|column| meta.query_advice(column, Rotation::cur()));
+            let [is_single_byte_cur, is_short_string_cur, is_short_header_cur, _, _] = forms(meta);
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let bytes_left = meta.query_advice(bytes_left, Rotation::cur());
+            let item_left_cur = meta.query_advice(item_left, Rotation::cur());
+
+            for column in [
+                is_branch,
+                is_extension,
+                is_leaf,
+                is_empty,
+                is_storage,
+                is_item_start,
+                is_last_item,
+                is_hp_flag,
+                is_hp_byte,
+                is_hp_odd,
+                is_match,
+                is_single_byte,
+                is_short_string,
+                is_short_header,
+                is_long_header,
+                is_long_header_2,
+            ] {
+                cb.require_boolean(
+                    "node flag is boolean",
+                    meta.query_advice(column, Rotation::cur()),
+                );
+            }
+            cb.require_equal(
+                "a node has one kind, unless padding",
+                is_branch_cur.clone()
+                    + is_extension_cur.clone()
+                    + is_leaf_cur.clone()
+                    + is_empty_cur,
+                not::expr(is_padding.clone()),
+            );
+            cb.require_equal(
+                "byte_hi and byte_lo are the nibbles of the byte",
+                byte.clone(),
+                meta.query_advice(byte_hi, Rotation::cur()) * 16.expr()
+                    + meta.query_advice(byte_lo, Rotation::cur()),
+            );
+            cb.require_zero(
+                "item_left_inv is the inverse of item_left when not 0",
+                item_left_cur.clone()
+                    * (1.expr()
+                        - item_left_cur.clone()
+                            * meta.query_advice(item_left_inv, Rotation::cur())),
+            );
+
+            cb.condition(is_first_byte.clone(), |cb| {
+                cb.require_equal(
+                    "a node starts with an item",
+                    is_item_start_cur.clone(),
+                    1.expr(),
+                );
+                cb.require_zero(
+                    "the first item of a node is 0",
+                    meta.query_advice(item, Rotation::cur()),
+                );
+            });
+            cb.condition(is_item_start_cur.clone() * not::expr(is_padding), |cb| {
+                cb.require_equal("an item has one form", sum::expr(forms(meta)), 1.expr());
+                cb.require_equal(
+                    "item_left is the length of the item after its first byte",
+                    item_left_cur.clone(),
+                    is_short_string_cur.clone() * (byte.clone() - 0x80.expr())
+                        + meta.query_advice(is_long_header, Rotation::cur())
+                        + meta.query_advice(is_long_header_2, Rotation::cur()) * 2.expr(),
+                );
+                cb.require_zero(
+                    "the last item ends with the node",
+                    is_last_item_cur.clone()
+                        * (item_left_cur.clone() + 1.expr() - bytes_left.clone()),
+                );
+                // The header of the list of the node is based at 0xc0, and the
+                // one of the RLP encoded value of a storage slot at 0x80
+                cb.require_zero(
+                    "a short header is followed by the rest of the node",
+                    is_short_header_cur
+                        * (byte.clone() - 0x80.expr() - is_first_byte.clone() * 0x40.expr()
+                            + 1.expr()
+                            - bytes_left),
+                );
+            });
+            cb.condition(is_last_byte, |cb| {
+                cb.require_equal("a node ends with its last item", is_last_item_cur, 1.expr());
+                cb.require_zero("a node ends with the end of an item", item_left_cur);
+            });
+
+            // The target is the item of the child or the value, which is the
+            // storage root of an account leaf for a storage slot
+            let (target_diff, is_target_cur) = is_target(meta);
+            cb.require_zero(
+                "is_target = item == target_item",
+                target_diff * is_target_cur.clone(),
+            );
+            let (path_item_diff, is_path_item_cur) = is_path_item(meta);
+            cb.require_zero(
+                "is_path_item = item == 1",
+                path_item_diff * is_path_item_cur,
+            );
+            let is_match_cur = meta.query_advice(is_match, Rotation::cur());
+            let has_child_cur = meta.query_advice(has_child, Rotation::cur());
+            let is_account_child = is_leaf_cur.clone()
+                * not::expr(meta.query_advice(is_storage, Rotation::cur()))
+                * is_storage_tag(meta.query_advice(field_tag, Rotation::cur()));
+            cb.require_equal(
+                "has_child = the node matches the key and references the next node",
+                has_child_cur.clone(),
+                is_match_cur.clone() * (is_branch_cur + is_extension_cur + is_account_child),
+            );
+            let is_target_payload = is_target_cur.clone() * is_payload(meta);
+            cb.require_equal(
+                "is_child = the byte is in the payload of the target referencing the next node",
+                meta.query_advice(is_child, Rotation::cur()),
+                is_target_payload.clone() * has_child_cur.clone(),
+            );
+            cb.require_equal(
+                "is_value = the byte is in the payload of the target of a leaf matching the key",
+                meta.query_advice(is_value, Rotation::cur()),
+                is_target_payload * is_leaf_cur * (is_match_cur - has_child_cur),
+            );
+            cb.require_equal(
+                "is_compared_byte = the byte is in an item off the path of a compared node",
+                meta.query_advice(is_compared_byte, Rotation::cur()),
+                meta.query_advice(is_compared, Rotation::cur())
+                    * not::expr(is_target_cur)
+                    * (is_single_byte_cur + is_short_string_cur),
+            );
+
+            cb.gate(meta.query_selector(q_node))
+        });
+
+        meta.create_gate("mpt node item transition", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [is_padding, is_first_byte, is_item_start_cur] =
+                [is_padding, is_first_byte, is_item_start]
+                    .map(|column| meta.query_advice(column, Rotation::cur()));
+            let is_item_start_prev = meta.query_advice(is_item_start, Rotation::prev());
+            let item_left_prev = meta.query_advice(item_left, Rotation::prev());
+            let byte_prev = meta.query_advice(byte, Rotation::prev());
+            let byte_cur = meta.query_advice(byte, Rotation::cur());
+            let bytes_left = meta.query_advice(bytes_left, Rotation::cur());
+            let item_left_cur = meta.query_advice(item_left, Rotation::cur());
+            let is_item_end = 1.expr()
+                - item_left_cur.clone() * meta.query_advice(item_left_inv, Rotation::cur());
+
+            cb.condition(
+                not::expr(is_padding.clone()) * not::expr(is_first_byte),
+                |cb| {
+                    for column in [
+                        is_branch,
+                        is_extension,
+                        is_leaf,
+                        is_empty,
+                        is_storage,
+                        depth,
+                        target_item,
+                        is_match,
+                        is_hp_odd,
+                        is_compared,
+                    ] {
+                        cb.require_equal(
+                            "value is the same in all the rows of a node",
+                            meta.query_advice(column, Rotation::cur()),
+                            meta.query_advice(column, Rotation::prev()),
+                        );
+                    }
+                    cb.require_equal(
+                        "an item starts after the last byte of the previous one",
+                        is_item_start_cur.clone(),
+                        1.expr()
+                            - item_left_prev.clone()
+                                * meta.query_advice(item_left_inv, Rotation::prev()),
+                    );
+                    cb.require_equal(
+                        "the items of a node follow each other",
+                        meta.query_advice(item, Rotation::cur()),
+                        meta.query_advice(item, Rotation::prev()) + is_item_start_cur.clone(),
+                    );
+                },
+            );
+            cb.condition(
+                not::expr(is_padding) * not::expr(is_item_start_cur.clone()),
+                |cb| {
+                    for column in [
+                        is_single_byte,
+                        is_short_string,
+                        is_short_header,
+                        is_long_header,
+                        is_long_header_2,
+                        is_last_item,
+                    ] {
+                        cb.require_equal(
+                            "value is the same in all the rows of an item",
+                            meta.query_advice(column, Rotation::cur()),
+                            meta.query_advice(column, Rotation::prev()),
+                        );
+                    }
+                    cb.require_equal(
+                        "item_left decreases by 1",
+                        item_left_cur,
+                        item_left_prev - 1.expr(),
+                    );
+                    // The lengths of the long headers are checked on their last
+                    // bytes
+                    cb.require_zero(
+                        "a long header is followed by the length of the rest of the node",
+                        meta.query_advice(is_long_header, Rotation::cur())
+                            * (byte_cur.clone() + 1.expr() - bytes_left.clone()),
+                    );
+                    cb.require_zero(
+                        "a long header is followed by the length of the rest of the node",
+                        meta.query_advice(is_long_header_2, Rotation::cur())
+                            * is_item_end
+                            * (byte_prev * 256.expr() + byte_cur + 1.expr() - bytes_left),
+                    );
+                },
+            );
+
+            // The hex-prefix encoded path of an extension or a leaf is its item
+            // 1, whose first payload byte holds the flag
+            let (_, is_path_item_cur) = is_path_item(meta);
+            let is_hp_item = (meta.query_advice(is_extension, Rotation::cur())
+                + meta.query_advice(is_leaf, Rotation::cur()))
+                * is_path_item_cur;
+            let is_hp_flag_cur = meta.query_advice(is_hp_flag, Rotation::cur());
+            cb.require_equal(
+                "is_hp_flag = the byte is the first one of the path of an extension or a leaf",
+                is_hp_flag_cur.clone(),
+                is_hp_item.clone()
+                    * (meta.query_advice(is_single_byte, Rotation::cur())
+                        + meta.query_advice(is_short_string, Rotation::cur())
+                            * not::expr(is_item_start_cur)
+                            * is_item_start_prev),
+            );
+            cb.require_equal(
+                "is_hp_byte = the byte is one of the next ones of the path",
+                meta.query_advice(is_hp_byte, Rotation::cur()),
+                is_hp_item * is_payload(meta) - is_hp_flag_cur,
+            );
+
+            cb.gate(meta.query_selector(q_node) - meta.query_selector(q_node_first))
+        });
+
+        // A path walks the nibbles of the key from the root of the account
+        // trie, then of the storage trie, and its last node either matches
+        // them or diverges from them
+        meta.create_gate("mpt node key row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [is_path_start, is_first_byte, is_last_byte, is_leaf_cur, is_storage_cur, is_empty_cur, is_match_cur] = [
+                is_path_start,
+                is_first_byte,
+                is_last_byte,
+                is_leaf,
+                is_storage,
+                is_empty,
+                is_match,
+            ]
+            .map(|column| meta.query_advice(column, Rotation::cur()));
+            let target_item_cur = meta.query_advice(target_item, Rotation::cur());
+            let is_hp_odd_cur = meta.query_advice(is_hp_odd, Rotation::cur());
+            let path_acc_cur = meta.query_advice(path_acc, Rotation::cur());
+            let key_prefix_cur = meta.query_advice(key_prefix, Rotation::cur());
+
+            cb.require_boolean(
+                "path_is_odd is boolean",
+                meta.query_advice(path_is_odd, Rotation::cur()),
+            );
+            cb.condition(is_path_start, |cb| {
+                for column in [depth, is_storage, path_pos, path_is_odd, path_acc] {
+                    cb.require_zero(
+                        "a path starts at the root of the account trie",
+                        meta.query_advice(column, Rotation::cur()),
+                    );
+                }
+            });
+            cb.condition(meta.query_advice(is_hp_flag, Rotation::cur()), |cb| {
+                cb.require_equal(
+                    "the flag tells a leaf and an odd number of nibbles",
+                    meta.query_advice(byte_hi, Rotation::cur()),
+                    is_leaf_cur.clone() * 2.expr() + is_hp_odd_cur.clone(),
+                );
+                cb.require_zero(
+                    "the flag of an even number of nibbles is followed by 0",
+                    not::expr(is_hp_odd_cur) * meta.query_advice(byte_lo, Rotation::cur()),
+                );
+            });
+
+            cb.condition(meta.query_advice(is_extension, Rotation::cur()), |cb| {
+                cb.require_equal(
+                    "the target of an extension is its child",
+                    target_item_cur.clone(),
+                    2.expr(),
+                );
+            });
+            // The field tags are below 8
+            cb.condition(
+                is_first_byte * is_leaf_cur.clone() * not::expr(is_storage_cur.clone()),
+                |cb| {
+                    cb.require_in_set(
+                        "the target of an account leaf is the field of the update",
+                        meta.query_advice(field_tag, Rotation::cur()) * 8.expr()
+                            + target_item_cur.clone(),
+                        field_tags()
+                            .map(|tag| (tag as u64 * 8 + account_leaf_item(tag)).expr())
+                            .to_vec(),
+                    );
+                },
+            );
+            cb.condition(
+                is_last_byte.clone() * is_leaf_cur.clone() * is_storage_cur,
+                |cb| {
+                    cb.require_equal(
+                        "the target of a storage leaf is its value, in its last item",
+                        meta.query_advice(item, Rotation::cur()),
+                        target_item_cur,
+                    );
+                },
+            );
+            cb.condition(is_empty_cur.clone(), |cb| {
+                cb.require_zero("an empty node matches no key", is_match_cur.clone());
+            });
+            cb.condition(is_last_byte * not::expr(is_empty_cur), |cb| {
+                cb.require_zero(
+                    "a node matching the key walks its nibbles",
+                    is_match_cur.clone() * (path_acc_cur.clone() - key_prefix_cur.clone()),
+                );
+                cb.require_zero(
+                    "a node not matching the key diverges from its nibbles",
+                    not::expr(is_match_cur.clone())
+                        * ((path_acc_cur - key_prefix_cur)
+                            * meta.query_advice(match_inv, Rotation::cur())
+                            - 1.expr()),
+                );
+                cb.require_zero(
+                    "a branch matches the key",
+                    meta.query_advice(is_branch, Rotation::cur()) * not::expr(is_match_cur),
+                );
+                cb.require_equal(
+                    "a leaf walks the key to its end",
+                    is_leaf_cur.clone() * meta.query_advice(path_pos, Rotation::cur()),
+                    is_leaf_cur * KEY_NIBBLES.expr(),
+                );
+            });
+
+            cb.gate(meta.query_selector(q_node))
+        });
+
+        meta.create_gate("mpt node key transition", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [is_padding, is_path_start, is_first_byte, is_item_start] =
+                [is_padding, is_path_start, is_first_byte, is_item_start]
+                    .map(|column| meta.query_advice(column, Rotation::cur()));
+            let is_leaf_prev = meta.query_advice(is_leaf, Rotation::prev());
+            let [path_pos_prev, path_is_odd_prev, path_acc_prev] =
+                [path_pos, path_is_odd, path_acc]
+                    .map(|column| meta.query_advice(column, Rotation::prev()));
+            let r = Expression::Constant(r);
+
+            // The key of the storage trie is walked after the account leaf
+            cb.condition(is_first_byte.clone() * not::expr(is_path_start), |cb| {
+                cb.require_equal(
+                    "depth increases by 1",
+                    meta.query_advice(depth, Rotation::cur()),
+                    meta.query_advice(depth, Rotation::prev()) + 1.expr(),
+                );
+                cb.require_equal(
+                    "the nodes after an account leaf are in its storage trie",
+                    meta.query_advice(is_storage, Rotation::cur()),
+                    meta.query_advice(is_storage, Rotation::prev()) + is_leaf_prev.clone(),
+                );
+                for (column, prev) in [
+                    (path_pos, path_pos_prev.clone()),
+                    (path_is_odd, path_is_odd_prev.clone()),
+                    (path_acc, path_acc_prev.clone()),
+                ] {
+                    cb.require_equal(
+                        "the walk of the key goes on in the next node",
+                        meta.query_advice(column, Rotation::cur()),
+                        prev * not::expr(is_leaf_prev.clone()),
+                    );
+                }
+            });
+
+            // A branch walks the nibble of its target, and an extension or a
+            // leaf the nibbles of its path, the first of which is in the flag
+            // byte when their number is odd
+            cb.condition(not::expr(is_padding) * not::expr(is_first_byte), |cb| {
+                let (_, is_target_cur) = is_target(meta);
+                let is_branch_nibble =
+                    meta.query_advice(is_branch, Rotation::cur()) * is_target_cur * is_item_start;
+                let is_flag_nibble = meta.query_advice(is_hp_flag, Rotation::cur())
+                    * meta.query_advice(is_hp_odd, Rotation::cur());
+                let is_hp_byte_cur = meta.query_advice(is_hp_byte, Rotation::cur());
+                let [byte_cur, byte_hi_cur, byte_lo_cur] = [byte, byte_hi, byte_lo]
+                    .map(|column| meta.query_advice(column, Rotation::cur()));
+                let acc_nibble = |nibble: Expression<F>| {
+                    select::expr(
+                        path_is_odd_prev.clone(),
+                        nibble.clone(),
+                        path_acc_prev.clone() * (r.clone() - 1.expr()) + nibble * 16.expr(),
+                    )
+                };
+                let acc_byte = path_acc_prev.clone() * (r.clone() - 1.expr())
+                    + select::expr(
+                        path_is_odd_prev.clone(),
+                        byte_hi_cur * r.clone() + byte_lo_cur.clone() * 16.expr(),
+                        byte_cur,
+                    );
+
+                cb.require_equal(
+                    "path_pos counts the walked nibbles",
+                    meta.query_advice(path_pos, Rotation::cur()),
+                    path_pos_prev.clone()
+                        + is_branch_nibble.clone()
+                        + is_flag_nibble.clone()
+                        + is_hp_byte_cur.clone() * 2.expr(),
+                );
+                cb.require_equal(
+                    "path_is_odd is the parity of path_pos",
+                    meta.query_advice(path_is_odd, Rotation::cur()),
+                    path_is_odd_prev.clone()
+                        + (is_branch_nibble.clone() + is_flag_nibble.clone())
+                            * (1.expr() - path_is_odd_prev.clone() * 2.expr()),
+                );
+                cb.require_equal(
+                    "path_acc accumulates the walked nibbles",
+                    meta.query_advice(path_acc, Rotation::cur()),
+                    path_acc_prev.clone()
+                        + is_branch_nibble
+                            * acc_nibble(meta.query_advice(item, Rotation::cur()) - 1.expr())
+                        + is_flag_nibble * acc_nibble(byte_lo_cur)
+                        + is_hp_byte_cur * acc_byte,
+                );
+            });
+
+            cb.gate(meta.query_selector(q_node) - meta.query_selector(q_node_first))
+        });
+
+        // The keys of the updates are hashed from their addresses and storage
+        // keys, with a row per nibble
+        meta.create_gate("mpt key row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_storage = meta.query_advice(key_is_storage, Rotation::cur());
+            let is_padding = meta.query_advice(key_is_padding, Rotation::cur());
+            let pos_diff = meta.query_advice(key_pos, Rotation::cur()) - KEY_NIBBLES.expr();
+            let is_key_end =
+                1.expr() - pos_diff.clone() * meta.query_advice(key_pos_inv, Rotation::cur());
+
+            for column in [key_is_storage, key_is_preimage, key_is_odd, key_is_padding] {
+                cb.require_boolean(
+                    "key flag is boolean",
+                    meta.query_advice(column, Rotation::cur()),
+                );
+            }
+            cb.require_zero(
+                "is_key_end = key_pos == KEY_NIBBLES",
+                pos_diff * is_key_end.clone(),
+            );
+            cb.condition(
+                is_key_end * not::expr(is_storage) * not::expr(is_padding),
+                |cb| {
+                    cb.require_equal(
+                        "the bytes of the address make the address",
+                        meta.query_advice(key_input_value, Rotation::cur()),
+                        meta.query_advice(key_input, Rotation::cur()),
+                    );
+                },
+            );
+
+            cb.gate(meta.query_selector(q_key))
+        });
+
+        meta.create_gate("mpt key first row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            cb.condition(
+                not::expr(meta.query_advice(key_is_padding, Rotation::cur())),
+                |cb| {
+                    for column in [key_update_idx, key_is_storage] {
+                        cb.require_zero(
+                            "the first key is the one of the account of the first update",
+                            meta.query_advice(column, Rotation::cur()),
+                        );
+                    }
+                },
+            );
+            cb.gate(meta.query_selector(q_key_first))
+        });
+
+        meta.create_gate("mpt key last row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            // So that the last key ends before it
+            cb.require_equal(
+                "the last row is padding",
+                meta.query_advice(key_is_padding, Rotation::cur()),
+                1.expr(),
+            );
+            cb.gate(meta.query_selector(q_key_last))
+        });
+
+        // A key starts on the first row, and after the end of the previous one
+        meta.create_gate("mpt key start", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let q_key_first = meta.query_selector(q_key_first);
+            let pos_diff_prev = meta.query_advice(key_pos, Rotation::prev()) - KEY_NIBBLES.expr();
+            let is_key_end_prev =
+                1.expr() - pos_diff_prev * meta.query_advice(key_pos_inv, Rotation::prev());
+            let is_key_start = q_key_first.clone()
+                + (meta.query_selector(q_key) - q_key_first.clone()) * is_key_end_prev;
+            let is_storage = meta.query_advice(key_is_storage, Rotation::cur());
+            let is_preimage = meta.query_advice(key_is_preimage, Rotation::cur());
+            let byte = meta.query_advice(key_byte, Rotation::cur());
+
+            cb.condition(
+                not::expr(meta.query_advice(key_is_padding, Rotation::cur())),
+                |cb| {
+                    for column in [key_pos, key_is_odd, key_acc] {
+                        cb.require_zero(
+                            "a key starts at its first nibble",
+                            meta.query_advice(column, Rotation::cur()),
+                        );
+                    }
+                    cb.require_equal(
+                        "the bytes of an address start with the key",
+                        is_preimage.clone(),
+                        not::expr(is_storage.clone()),
+                    );
+                    for column in [key_input_value, key_input_rlc] {
+                        cb.require_equal(
+                            "the bytes of an address start with the key",
+                            meta.query_advice(column, Rotation::cur()),
+                            is_preimage.clone() * byte.clone(),
+                        );
+                    }
+                    // The key of an account starts every update, and is
+                    // followed by the one of the storage slot of the update
+                    let update_idx_diff = meta.query_advice(key_update_idx, Rotation::cur())
+                        - meta.query_advice(key_update_idx, Rotation::prev());
+                    cb.require_equal(
+                        "the key of an account starts the next update",
+                        (1.expr() - q_key_first.clone()) * update_idx_diff.clone(),
+                        (1.expr() - q_key_first.clone()) * not::expr(is_storage.clone()),
+                    );
+                    cb.require_zero(
+                        "the key of a storage slot follows the one of its account",
+                        (1.expr() - q_key_first)
+                            * is_storage
+                            * meta.query_advice(key_is_storage, Rotation::prev()),
+                    );
+                },
+            );
+
+            cb.gate(is_key_start)
+        });
+
+        meta.create_gate("mpt key transition", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [is_padding_prev, is_odd_prev, acc_prev, nibble_prev, is_preimage_prev] = [
+                key_is_padding,
+                key_is_odd,
+                key_acc,
+                key_nibble,
+                key_is_preimage,
+            ]
+            .map(|column| meta.query_advice(column, Rotation::prev()));
+            let is_padding = meta.query_advice(key_is_padding, Rotation::cur());
+            let pos_diff_prev = meta.query_advice(key_pos, Rotation::prev()) - KEY_NIBBLES.expr();
+            let is_key_end_prev =
+                1.expr() - pos_diff_prev * meta.query_advice(key_pos_inv, Rotation::prev());
+            let r = Expression::Constant(r);
+
+            cb.require_zero(
+                "padding rows are only followed by padding rows",
+                is_padding_prev.clone() * not::expr(is_padding.clone()),
+            );
+            cb.require_zero(
+                "the padding starts after the end of a key",
+                is_padding.clone()
+                    * not::expr(is_padding_prev)
+                    * not::expr(is_key_end_prev.clone()),
+            );
+            cb.condition(not::expr(is_key_end_prev) * not::expr(is_padding), |cb| {
+                for column in [key_update_idx, key_is_storage, key_input] {
+                    cb.require_equal(
+                        "value is the same in all the rows of a key",
+                        meta.query_advice(column, Rotation::cur()),
+                        meta.query_advice(column, Rotation::prev()),
+                    );
+                }
+                cb.require_equal(
+                    "key_pos increases by 1",
+                    meta.query_advice(key_pos, Rotation::cur()),
+                    meta.query_advice(key_pos, Rotation::prev()) + 1.expr(),
+                );
+                cb.require_equal(
+                    "key_is_odd alternates",
+                    meta.query_advice(key_is_odd, Rotation::cur()),
+                    not::expr(is_odd_prev.clone()),
+                );
+                cb.require_equal(
+                    "key_acc accumulates the nibbles",
+                    meta.query_advice(key_acc, Rotation::cur()),
+                    select::expr(
+                        is_odd_prev,
+                        acc_prev.clone() + nibble_prev.clone(),
+                        acc_prev * r.clone() + nibble_prev * 16.expr(),
+                    ),
+                );
+
+                // The bytes of the address are on the first rows of its key
+                let is_preimage = meta.query_advice(key_is_preimage, Rotation::cur());
+                let byte = meta.query_advice(key_byte, Rotation::cur());
+                cb.require_zero(
+                    "the bytes of an address are on the first rows of its key",
+                    is_preimage.clone() * not::expr(is_preimage_prev),
+                );
+                cb.require_equal(
+                    "key_input_value accumulates the bytes of the address",
+                    meta.query_advice(key_input_value, Rotation::cur()),
+                    meta.query_advice(key_input_value, Rotation::prev())
+                        * (1.expr() + is_preimage.clone() * 255.expr())
+                        + is_preimage.clone() * byte.clone(),
+                );
+                cb.require_equal(
+                    "key_input_rlc accumulates the bytes of the address",
+                    meta.query_advice(key_input_rlc, Rotation::cur()),
+                    meta.query_advice(key_input_rlc, Rotation::prev())
+                        * (1.expr() + is_preimage.clone() * (r - 1.expr()))
+                        + is_preimage * byte,
+                );
+            });
+
+            cb.gate(meta.query_selector(q_key) - meta.query_selector(q_key_first))
+        });
+
+        meta.lookup_any("mpt node hash", |meta| {
+            let condition = meta.query_selector(q_node)
+                * meta.query_advice(is_last_byte, Rotation::cur())
+                * not::expr(meta.query_advice(is_padding, Rotation::cur()));
+            let input_exprs = [node_rlc, node_len, node_hash]
+                .map(|column| meta.query_advice(column, Rotation::cur()));
+            IntoIterator::into_iter(input_exprs)
+                .map(|expr| condition.clone() * expr)
+                .zip(keccak_table.table_exprs(meta))
+                .collect()
+        });
+
+        // Every update is looked up in the paths before and after it
+        for (name, root_idx, value_idx, is_new_path) in [
+            ("mpt old path", OLD_ROOT, OLD_VALUE, false),
+            ("mpt new path", NEW_ROOT, NEW_VALUE, true),
+        ] {
+            meta.lookup_any(name, |meta| {
+                let condition = meta.query_selector(q_table)
+                    * not::expr(meta.query_advice(table_is_padding, Rotation::cur()));
+                let table = query(meta, &mpt_table, Rotation::cur());
+                let input_exprs = [
+                    table[ADDRESS].clone(),
+                    table[STORAGE_KEY].clone(),
+                    table[FIELD_TAG].clone(),
+                    table[root_idx].clone(),
+                    table[value_idx].clone(),
+                    is_new_path.expr(),
+                    meta.query_fixed(table_update_idx, Rotation::cur()),
+                ];
+
+                let path_start =
+                    meta.query_selector(q_node) * meta.query_advice(is_path_start, Rotation::cur());
+                let table_exprs = [
+                    address,
+                    storage_key,
+                    field_tag,
+                    root,
+                    value,
+                    is_new,
+                    update_idx,
+                ]
+                .map(|column| path_start.clone() * meta.query_advice(column, Rotation::cur()));
+
+                IntoIterator::into_iter(input_exprs)
+                    .map(|expr| condition.clone() * expr)
+                    .zip(table_exprs)
+                    .collect()
+            });
+        }
+
+        for (name, column) in [("mpt node byte hi", byte_hi), ("mpt node byte lo", byte_lo)] {
+            meta.lookup_any(name, |meta| {
+                vec![(
+                    meta.query_selector(q_node) * meta.query_advice(column, Rotation::cur()),
+                    meta.query_fixed(nibble_table, Rotation::cur()),
+                )]
+            });
+        }
+
+        meta.lookup_any("mpt node grammar", |meta| {
+            let condition = meta.query_selector(q_node)
+                * meta.query_advice(is_item_start, Rotation::cur())
+                * not::expr(meta.query_advice(is_padding, Rotation::cur()));
+            let form = IntoIterator::into_iter(forms(meta))
+                .zip([
+                    ItemForm::SingleByte,
+                    ItemForm::ShortString,
+                    ItemForm::ShortHeader,
+                    ItemForm::LongHeader,
+                    ItemForm::LongHeader2,
+                ])
+                .fold(0.expr(), |acc, (flag, form)| {
+                    acc + flag * (form as u64).expr()
+                });
+            let input_exprs = [
+                node_kind(meta),
+                meta.query_advice(item, Rotation::cur()),
+                form,
+                meta.query_advice(byte, Rotation::cur()),
+                meta.query_advice(is_last_item, Rotation::cur()),
+            ];
+            IntoIterator::into_iter(input_exprs)
+                .map(|expr| condition.clone() * expr)
+                .zip(grammar_table.map(|column| meta.query_fixed(column, Rotation::cur())))
+                .collect()
+        });
+
+        // The target of a branch is one of its 16 children
+        meta.lookup_any("mpt node branch target", |meta| {
+            vec![(
+                meta.query_selector(q_node)
+                    * meta.query_advice(is_branch, Rotation::cur())
+                    * (meta.query_advice(target_item, Rotation::cur()) - 1.expr()),
+                meta.query_fixed(nibble_table, Rotation::cur()),
+            )]
+        });
+
+        meta.lookup_any("mpt node key", |meta| {
+            let condition = meta.query_selector(q_node)
+                * meta.query_advice(is_last_byte, Rotation::cur())
+                * not::expr(meta.query_advice(is_empty, Rotation::cur()))
+                * not::expr(meta.query_advice(is_padding, Rotation::cur()));
+            let input_exprs = [update_idx, is_storage, path_pos, key_prefix]
+                .map(|column| condition.clone() * meta.query_advice(column, Rotation::cur()));
+
+            let is_key = meta.query_selector(q_key)
+                * not::expr(meta.query_advice(key_is_padding, Rotation::cur()));
+            let table_exprs = [key_update_idx, key_is_storage, key_pos, key_acc]
+                .map(|column| is_key.clone() * meta.query_advice(column, Rotation::cur()));
+
+            IntoIterator::into_iter(input_exprs)
+                .zip(table_exprs)
+                .collect()
+        });
+
+        // The paths after the updates are the ones before them when on the
+        // other side of the update, as 0 <= path_len_old - path_len_new - 1
+        // when it's shorter
+        meta.lookup_any("mpt path length order", |meta| {
+            let condition = meta.query_selector(q_node)
+                * meta.query_advice(is_path_start, Rotation::cur())
+                * meta.query_advice(is_new, Rotation::cur());
+            let path_len_diff = meta.query_advice(path_len, Rotation::cur())
+                - meta.query_advice(path_len, Rotation::prev());
+            let is_new_shorter = meta.query_advice(is_new_shorter, Rotation::cur());
+            vec![(
+                condition
+                    * select::expr(
+                        is_new_shorter,
+                        0.expr() - path_len_diff.clone() - 1.expr(),
+                        path_len_diff,
+                    ),
+                meta.query_fixed(byte_table, Rotation::cur()),
+            )]
+        });
+
+        meta.lookup_any("mpt compared depth", |meta| {
+            let condition = meta.query_selector(q_node)
+                * meta.query_advice(is_first_byte, Rotation::cur())
+                * not::expr(meta.query_advice(is_padding, Rotation::cur()));
+            let depth_diff = meta.query_advice(compared_depth, Rotation::cur())
+                - meta.query_advice(depth, Rotation::cur());
+            let is_compared = meta.query_advice(is_compared, Rotation::cur());
+            vec![(
+                condition
+                    * select::expr(
+                        is_compared,
+                        depth_diff.clone() - 1.expr(),
+                        0.expr() - depth_diff,
+                    ),
+                meta.query_fixed(byte_table, Rotation::cur()),
+            )]
+        });
+
+        // The bytes of the compared nodes off the path are the same in the
+        // paths before and after an update, at the same positions
+        meta.lookup_any("mpt path comparison", |meta| {
+            let is_compared_byte =
+                meta.query_selector(q_node) * meta.query_advice(is_compared_byte, Rotation::cur());
+            let [update_idx, depth, item, item_left, byte, is_new] =
+                [update_idx, depth, item, item_left, byte, is_new]
+                    .map(|column| meta.query_advice(column, Rotation::cur()));
+            let node_kind = node_kind(meta);
+            let values = [update_idx, depth, node_kind, item, item_left, byte];
+
+            let input_exprs = values
+                .iter()
+                .cloned()
+                .chain([not::expr(is_new.clone())])
+                .map(|expr| is_compared_byte.clone() * expr);
+            let table_exprs = values
+                .iter()
+                .cloned()
+                .chain([is_new])
+                .map(|expr| is_compared_byte.clone() * expr);
+            input_exprs.zip(table_exprs).collect()
+        });
+
+        meta.lookup_any("mpt key nibble", |meta| {
+            vec![(
+                meta.query_selector(q_key) * meta.query_advice(key_nibble, Rotation::cur()),
+                meta.query_fixed(nibble_table, Rotation::cur()),
+            )]
+        });
+
+        meta.lookup_any("mpt key byte", |meta| {
+            vec![(
+                meta.query_selector(q_key)
+                    * meta.query_advice(key_is_preimage, Rotation::cur())
+                    * meta.query_advice(key_byte, Rotation::cur()),
+                meta.query_fixed(byte_table, Rotation::cur()),
+            )]
+        });
+
+        // The keys are the hashes of the big-endian bytes of the addresses and
+        // of the storage keys
+        meta.lookup_any("mpt key hash", |meta| {
+            let pos_diff = meta.query_advice(key_pos, Rotation::cur()) - KEY_NIBBLES.expr();
+            let is_key_end = 1.expr() - pos_diff * meta.query_advice(key_pos_inv, Rotation::cur());
+            let condition = meta.query_selector(q_key)
+                * is_key_end
+                * not::expr(meta.query_advice(key_is_padding, Rotation::cur()));
+            let is_storage = meta.query_advice(key_is_storage, Rotation::cur());
+            let input_exprs = [
+                select::expr(
+                    is_storage.clone(),
+                    meta.query_advice(key_input, Rotation::cur()),
+                    meta.query_advice(key_input_rlc, Rotation::cur()),
+                ),
+                ADDRESS_LEN.expr() + is_storage * (HASH_LEN - ADDRESS_LEN).expr(),
+                meta.query_advice(key_acc, Rotation::cur()),
+            ];
+            IntoIterator::into_iter(input_exprs)
+                .map(|expr| condition.clone() * expr)
+                .zip(keccak_table.table_exprs(meta))
+                .collect()
+        });
+
+        // Every update is looked up in the keys of its account and storage
+        // slot
+        for (name, is_storage_key) in [("mpt address key", false), ("mpt storage key", true)] {
+            meta.lookup_any(name, |meta| {
+                let table = query(meta, &mpt_table, Rotation::cur());
+                let mut condition = meta.query_selector(q_table)
+                    * not::expr(meta.query_advice(table_is_padding, Rotation::cur()));
+                if is_storage_key {
+                    condition = condition * is_storage_tag(table[FIELD_TAG].clone());
+                }
+                let input_exprs = [
+                    meta.query_fixed(table_update_idx, Rotation::cur()),
+                    is_storage_key.expr(),
+                    table[if is_storage_key { STORAGE_KEY } else { ADDRESS }].clone(),
+                ];
+
+                let is_key = meta.query_selector(q_key)
+                    * not::expr(meta.query_advice(key_is_padding, Rotation::cur()));
+                let table_exprs = [key_update_idx, key_is_storage, key_input]
+                    .map(|column| is_key.clone() * meta.query_advice(column, Rotation::cur()));
+
+                IntoIterator::into_iter(input_exprs)
+                    .map(|expr| condition.clone() * expr)
+                    .zip(table_exprs)
+                    .collect()
+            });
+        }
+
+        Config {
+            r,
+            minimum_rows: meta.minimum_rows(),
+            byte_table,
+            nibble_table,
+            grammar_table,
+            q_table,
+            q_table_first,
+            q_table_last,
+            mpt_table,
+            table_update_idx,
+            table_is_padding,
+            final_root,
+            state_roots,
+            q_node,
+            q_node_first,
+            q_node_last,
+            address,
+            storage_key,
+            field_tag,
+            root,
+            value,
+            is_new,
+            update_idx,
+            path_len,
+            ends_with_branch,
+            ends_with_empty,
+            compared_depth,
+            is_new_shorter,
+            len_diff_inv,
+            is_path_start,
+            is_first_byte,
+            byte,
+            byte_hi,
+            byte_lo,
+            bytes_left,
+            bytes_left_inv,
+            is_last_byte,
+            node_len,
+            node_rlc,
+            node_hash,
+            is_branch,
+            is_extension,
+            is_leaf,
+            is_empty,
+            depth,
+            is_storage,
+            item,
+            item_left,
+            item_left_inv,
+            is_item_start,
+            is_single_byte,
+            is_short_string,
+            is_short_header,
+            is_long_header,
+            is_long_header_2,
+            is_last_item,
+            target_item,
+            target_inv,
+            path_item_inv,
+            is_hp_flag,
+            is_hp_byte,
+            is_hp_odd,
+            path_pos,
+            path_is_odd,
+            path_acc,
+            key_prefix,
+            is_match,
+            match_inv,
+            has_child,
+            is_compared,
+            is_compared_byte,
+            is_child,
+            child_len,
+            child_rlc,
+            child_hash,
+            is_value,
+            value_rlc,
+            is_padding,
+            q_key,
+            q_key_first,
+            q_key_last,
+            key_update_idx,
+            key_is_storage,
+            key_input,
+            key_pos,
+            key_pos_inv,
+            key_byte,
+            key_is_preimage,
+            key_input_value,
+            key_input_rlc,
+            key_nibble,
+            key_is_odd,
+            key_acc,
+            key_is_padding,
+        }
+    }
+
+    /// Assign the updates of `block` into a circuit of `size` rows, where the
+    /// rows left are padding.  The public inputs are the state roots before
+    /// and after the block, in this order.
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        size: usize,
+        block: &Block<F>,
+    ) -> Result<(), Error> {
+        let table_rows = block
+            .mpt_updates
+            .iter()
+            .map(|update| update.table_assignment(self.r))
+            .collect::<Vec<_>>();
+        let node_rows = block
+            .mpt_updates
+            .iter()
+            .enumerate()
+            .flat_map(|(update_idx, update)| node_rows(update, update_idx, self.r))
+            .collect::<Vec<_>>();
+        let key_rows = block
+            .mpt_updates
+            .iter()
+            .enumerate()
+            .flat_map(|(update_idx, update)| key_rows(update, update_idx, self.r))
+            .collect::<Vec<_>>();
+        self.assign_fixed_tables(layouter)?;
+        self.assign_rows(layouter, size, &table_rows, &node_rows, &key_rows)
+    }
+
+    fn assign_fixed_tables(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_region(
+            || "mpt fixed tables",
+            |mut region| {
+                let mut values = Vec::new();
+                values.extend((0..=255).map(|byte| (self.byte_table, byte, byte)));
+                values.extend((0..16).map(|nibble| (self.nibble_table, nibble, nibble)));
+                for (offset, row) in grammar_rows().iter().enumerate() {
+                    values.extend(
+                        self.grammar_table
+                            .iter()
+                            .zip(row.iter())
+                            .map(|(column, value)| (*column, offset, *value as usize)),
+                    );
+                }
+
+                for (column, offset, value) in values {
+                    region.assign_fixed(
+                        || format!("assign mpt fixed table {}", offset),
+                        column,
+                        offset,
+                        || Ok(F::from(value as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    fn assign_rows(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        size: usize,
+        table_rows: &[[F; MPT_TABLE_WIDTH]],
+        node_rows: &[NodeRow<F>],
+        key_rows: &[KeyRow<F>],
+    ) -> Result<(), Error> {
+        // Subtract the unusable rows from the size
+        let num_rows = size - self.minimum_rows + 1;
+        // The last rows of the nodes and of the keys are padding
+        if table_rows.len() > num_rows || node_rows.len() >= num_rows || key_rows.len() >= num_rows
+        {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || "mpt table",
+            |mut region| {
+                self.q_table_first.enable(&mut region, 0)?;
+                self.q_table_last.enable(&mut region, num_rows - 1)?;
+                // The roots of the first row are copied from the public inputs
+                let prev_state_root = region.assign_advice_from_instance(
+                    || "mpt prev state root",
+                    self.state_roots,
+                    0,
+                    self.mpt_table[OLD_ROOT],
+                    0,
+                )?;
+                let state_root = region.assign_advice_from_instance(
+                    || "mpt state root",
+                    self.state_roots,
+                    1,
+                    self.final_root,
+                    0,
+                )?;
+                let state_root = state_root.value().copied().unwrap_or(F::zero());
+
+                let mut root = prev_state_root.value().copied().unwrap_or(F::zero());
+                for offset in 0..num_rows {
+                    self.q_table.enable(&mut region, offset)?;
+                    region.assign_fixed(
+                        || format!("assign mpt table update_idx {}", offset),
+                        self.table_update_idx,
+                        offset,
+                        || Ok(F::from(offset as u64)),
+                    )?;
+                    let (row, is_padding) = match table_rows.get(offset) {
+                        Some(row) => (*row, false),
+                        None => {
+                            // The padding rows keep the root of the last update
+                            let mut row = [F::zero(); MPT_TABLE_WIDTH];
+                            row[OLD_ROOT] = root;
+                            row[NEW_ROOT] = root;
+                            (row, true)
+                        }
+                    };
+                    root = row[NEW_ROOT];
+
+                    let mut values = vec![(self.table_is_padding, F::from(is_padding as u64))];
+                    if offset > 0 {
+                        values.push((self.final_root, state_root));
+                    }
+                    values.extend(
+                        self.mpt_table
+                            .iter()
+                            .copied()
+                            .zip(row)
+                            .filter(|(column, _)| {
+                                offset > 0 || *column != self.mpt_table[OLD_ROOT]
+                            }),
+                    );
+                    for (column, value) in values {
+                        region.assign_advice(
+                            || format!("assign mpt table {}", offset),
+                            column,
+                            offset,
+                            || Ok(value),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )?;
+
+        layouter.assign_region(
+            || "mpt nodes",
+            |mut region| {
+                self.q_node_first.enable(&mut region, 0)?;
+                self.q_node_last.enable(&mut region, num_rows - 1)?;
+                for offset in 0..num_rows {
+                    self.q_node.enable(&mut region, offset)?;
+                    match node_rows.get(offset) {
+                        Some(row) => self.assign_node_row(&mut region, offset, row)?,
+                        None => self.assign_padding_node_row(&mut region, offset)?,
+                    }
+                }
+                Ok(())
+            },
+        )?;
+
+        layouter.assign_region(
+            || "mpt keys",
+            |mut region| {
+                self.q_key_first.enable(&mut region, 0)?;
+                self.q_key_last.enable(&mut region, num_rows - 1)?;
+                for offset in 0..num_rows {
+                    self.q_key.enable(&mut region, offset)?;
+                    match key_rows.get(offset) {
+                        Some(row) => self.assign_key_row(&mut region, offset, row)?,
+                        None => self.assign_padding_key_row(&mut region, offset)?,
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    fn assign_node_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: &NodeRow<F>,
+    ) -> Result<(), Error> {
+        let inv = |value: F| value.invert().unwrap_or(F::zero());
+        let flag = |flag: bool| F::from(flag as u64);
+        let path_columns = [
+            self.address,
+            self.storage_key,
+            self.field_tag,
+            self.root,
+            self.value,
+            self.is_new,
+            self.update_idx,
+        ];
+        let form_columns = [
+            (self.is_single_byte, ItemForm::SingleByte),
+            (self.is_short_string, ItemForm::ShortString),
+            (self.is_short_header, ItemForm::ShortHeader),
+            (self.is_long_header, ItemForm::LongHeader),
+            (self.is_long_header_2, ItemForm::LongHeader2),
+        ];
+        let kind_columns = [
+            (self.is_branch, row.kind == NodeKind::Branch),
+            (self.is_extension, row.kind == NodeKind::Extension),
+            (
+                self.is_leaf,
+                row.kind == NodeKind::AccountLeaf || row.kind == NodeKind::StorageLeaf,
+            ),
+            (self.is_empty, row.kind == NodeKind::Empty),
+        ];
+        let item = F::from(row.item);
+
+        let mut values = vec![
+            (self.path_len, F::from(row.path_len)),
+            (self.ends_with_branch, flag(row.ends_with_branch)),
+            (self.ends_with_empty, flag(row.ends_with_empty)),
+            (self.compared_depth, F::from(row.compared_depth)),
+            (self.is_new_shorter, flag(row.is_new_shorter)),
+            (self.len_diff_inv, inv(row.len_diff)),
+            (self.is_path_start, flag(row.is_path_start)),
+            (self.is_first_byte, flag(row.is_first_byte)),
+            (self.byte, F::from(row.byte as u64)),
+            (self.byte_hi, F::from((row.byte >> 4) as u64)),
+            (self.byte_lo, F::from((row.byte & 0xf) as u64)),
+            (self.bytes_left, F::from(row.bytes_left)),
+            (self.bytes_left_inv, inv(F::from(row.bytes_left) - F::one())),
+            (self.is_last_byte, flag(row.bytes_left == 1)),
+            (self.node_len, F::from(row.node_len)),
+            (self.node_rlc, row.node_rlc),
+            (self.node_hash, row.node_hash),
+            (self.depth, F::from(row.depth)),
+            (self.is_storage, flag(row.is_storage)),
+            (self.item, item),
+            (self.item_left, F::from(row.item_left)),
+            (self.item_left_inv, inv(F::from(row.item_left))),
+            (self.is_item_start, flag(row.is_item_start)),
+            (self.is_last_item, flag(row.is_last_item)),
+            (self.target_item, F::from(row.target_item)),
+            (self.target_inv, inv(item - F::from(row.target_item))),
+            (self.path_item_inv, inv(item - F::one())),
+            (self.is_hp_flag, flag(row.is_hp_flag)),
+            (self.is_hp_byte, flag(row.is_hp_byte)),
+            (self.is_hp_odd, flag(row.is_hp_odd)),
+            (self.path_pos, F::from(row.path_pos)),
+            (self.path_is_odd, F::from(row.path_pos % 2)),
+            (self.path_acc, row.path_acc),
+            (self.key_prefix, row.key_prefix),
+            (self.is_match, flag(row.is_match)),
+            (self.match_inv, inv(row.path_acc - row.key_prefix)),
+            (self.has_child, flag(row.has_child)),
+            (self.is_compared, flag(row.is_compared)),
+            (self.is_compared_byte, flag(row.is_compared_byte)),
+            (self.is_child, flag(row.is_child)),
+            (self.child_len, F::from(row.child_len)),
+            (self.child_rlc, row.child_rlc),
+            (self.child_hash, row.child_hash),
+            (self.is_value, flag(row.is_value)),
+            (self.value_rlc, row.value_rlc),
+            (self.is_padding, F::zero()),
+        ];
+        values.extend(path_columns.iter().copied().zip(row.path));
+        values.extend(
+            form_columns
+                .iter()
+                .map(|(column, form)| (*column, flag(row.form == *form))),
+        );
+        values.extend(
+            kind_columns
+                .iter()
+                .map(|(column, is_kind)| (*column, flag(*is_kind))),
+        );
+
+        for (column, value) in values {
+            region.assign_advice(
+                || format!("assign mpt node {}", offset),
+                column,
+                offset,
+                || Ok(value),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Assign a row out of the paths, whose values are all 0.
+    fn assign_padding_node_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+    ) -> Result<(), Error> {
+        let columns = [
+            self.address,
+            self.storage_key,
+            self.field_tag,
+            self.root,
+            self.value,
+            self.is_new,
+            self.update_idx,
+            self.path_len,
+            self.ends_with_branch,
+            self.ends_with_empty,
+            self.compared_depth,
+            self.is_new_shorter,
+            self.len_diff_inv,
+            self.is_path_start,
+            self.is_first_byte,
+            self.byte,
+            self.byte_hi,
+            self.byte_lo,
+            self.bytes_left,
+            self.is_last_byte,
+            self.node_len,
+            self.node_rlc,
+            self.node_hash,
+            self.is_branch,
+            self.is_extension,
+            self.is_leaf,
+            self.is_empty,
+            self.depth,
+            self.is_storage,
+            self.item,
+            self.item_left,
+            self.item_left_inv,
+            self.is_item_start,
+            self.is_single_byte,
+            self.is_short_string,
+            self.is_short_header,
+            self.is_long_header,
+            self.is_long_header_2,
+            self.is_last_item,
+            self.target_item,
+            self.target_inv,
+            self.is_hp_flag,
+            self.is_hp_byte,
+            self.is_hp_odd,
+            self.path_pos,
+            self.path_is_odd,
+            self.path_acc,
+            self.key_prefix,
+            self.is_match,
+            self.match_inv,
+            self.has_child,
+            self.is_compared,
+            self.is_compared_byte,
+            self.is_child,
+            self.child_len,
+            self.child_rlc,
+            self.child_hash,
+            self.is_value,
+            self.value_rlc,
+        ];
+        for (column, value) in columns
+            .iter()
+            .map(|column| (*column, F::zero()))
+            // The inverses of bytes_left - 1 = -1 and item - 1 = -1
+            .chain([
+                (self.bytes_left_inv, -F::one()),
+                (self.path_item_inv, -F::one()),
+                (self.is_padding, F::one()),
+            ])
+        {
+            region.assign_advice(
+                || format!("assign mpt node padding row {}", offset),
+                column,
+                offset,
+                || Ok(value),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn assign_key_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: &KeyRow<F>,
+    ) -> Result<(), Error> {
+        let pos_diff = F::from(row.pos) - F::from(KEY_NIBBLES as u64);
+        let values = [
+            (self.key_update_idx, F::from(row.update_idx)),
+            (self.key_is_storage, F::from(row.is_storage as u64)),
+            (self.key_input, row.input),
+            (self.key_pos, F::from(row.pos)),
+            (self.key_pos_inv, pos_diff.invert().unwrap_or(F::zero())),
+            (self.key_byte, F::from(row.byte as u64)),
+            (self.key_is_preimage, F::from(row.is_preimage as u64)),
+            (self.key_input_value, row.input_value),
+            (self.key_input_rlc, row.input_rlc),
+            (self.key_nibble, F::from(row.nibble as u64)),
+            (self.key_is_odd, F::from(row.pos % 2)),
+            (self.key_acc, row.acc),
+            (self.key_is_padding, F::zero()),
+        ];
+        for (column, value) in values {
+            region.assign_advice(
+                || format!("assign mpt key {}", offset),
+                column,
+                offset,
+                || Ok(value),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Assign a row out of the keys, whose values are all 0.
+    fn assign_padding_key_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+    ) -> Result<(), Error> {
+        let columns = [
+            self.key_update_idx,
+            self.key_is_storage,
+            self.key_input,
+            self.key_pos,
+            self.key_byte,
+            self.key_is_preimage,
+            self.key_input_value,
+            self.key_input_rlc,
+            self.key_nibble,
+            self.key_is_odd,
+            self.key_acc,
+        ];
+        for (column, value) in columns
+            .iter()
+            .map(|column| (*column, F::zero()))
+            // The inverse of key_pos - KEY_NIBBLES
+            .chain([
+                (
+                    self.key_pos_inv,
+                    (-F::from(KEY_NIBBLES as u64)).invert().unwrap(),
+                ),
+                (self.key_is_padding, F::one()),
+            ])
+        {
+            region.assign_advice(
+                || format!("assign mpt key padding row {}", offset),
+                column,
+                offset,
+                || Ok(value),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<F: Field> LookupTable<F, MPT_TABLE_WIDTH> for Config<F> {
+    fn table_exprs(&self, meta: &mut VirtualCells<F>) -> [Expression<F>; MPT_TABLE_WIDTH] {
+        query(meta, &self.mpt_table, Rotation::cur())
+    }
+}
+
+/// Return the rows of the keccak table for the nodes of the paths of the
+/// updates of `block`, and for their keys.
+pub(crate) fn keccak_table_assignments<F: Field>(block: &Block<F>) -> Vec<[F; KECCAK_TABLE_WIDTH]> {
+    block
+        .mpt_updates
+        .iter()
+        .flat_map(|update| {
+            update
+                .old_path
+                .iter()
+                .chain(update.new_path.iter())
+                .map(|node| node.bytes.clone())
+                .chain(key_preimages(update))
+        })
+        .map(|bytes| {
+            [
+                encode(bytes.iter().copied(), block.randomness),
+                F::from(bytes.len() as u64),
+                hash_rlc(&bytes, block.randomness),
+            ]
+        })
+        .collect()
+}
+
+/// Return the random linear combination of the hash of `bytes`, as a word.
+fn hash_rlc<F: Field>(bytes: &[u8], randomness: F) -> F {
+    let hash = Word::from_big_endian(Keccak256::digest(bytes).as_slice());
+    RandomLinearCombination::random_linear_combine(hash.to_le_bytes(), randomness)
+}
+
+/// Return the nibbles of `bytes`.
+fn nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0xf])
+        .collect()
+}
+
+/// Return `acc` with the nibble at `pos` of a key, where the nibbles at even
+/// positions are multiplied by 16 so that `acc` is the random linear
+/// combination of the bytes of the key.
+fn acc_nibble<F: Field>(acc: F, pos: u64, nibble: u8, randomness: F) -> F {
+    if pos % 2 == 0 {
+        acc * randomness + F::from(16 * nibble as u64)
+    } else {
+        acc + F::from(nibble as u64)
+    }
+}
+
+/// Return the random linear combination of the nibbles of `key`.
+fn acc_nibbles<F: Field>(key: &[u8], randomness: F) -> F {
+    key.iter()
+        .enumerate()
+        .fold(F::zero(), |acc, (pos, nibble)| {
+            acc_nibble(acc, pos as u64, *nibble, randomness)
+        })
+}
+
+/// Return the preimages of the keys of `update`, which are its address and
+/// its storage key, if any.
+fn key_preimages(update: &MptUpdate) -> Vec<Vec<u8>> {
+    let mut preimages = vec![update.address.as_bytes().to_vec()];
+    if let MptKey::Storage(key) = update.key {
+        preimages.push(key.to_be_bytes().to_vec());
+    }
+    preimages
+}
+
+/// Return the rows of the keys of `update`, the `update_idx`-th one of the
+/// block.
+fn key_rows<F: Field>(update: &MptUpdate, update_idx: usize, randomness: F) -> Vec<KeyRow<F>> {
+    let table = update.table_assignment(randomness);
+
+    let mut rows = Vec::new();
+    for (idx, preimage) in key_preimages(update).iter().enumerate() {
+        let is_storage = idx == 1;
+        let input = table[if is_storage { STORAGE_KEY } else { ADDRESS }];
+        let key = nibbles(Keccak256::digest(preimage).as_slice());
+
+        let (mut input_value, mut input_rlc, mut acc) = (F::zero(), F::zero(), F::zero());
+        // The last row of a key is after its last nibble
+        for (pos, nibble) in key.iter().copied().chain([0]).enumerate() {
+            // The bytes of the storage key are in its random linear combination
+            let is_preimage = !is_storage && pos < ADDRESS_LEN;
+            let byte = if is_preimage { preimage[pos] } else { 0 };
+            if is_preimage {
+                input_value = input_value * F::from(256) + F::from(byte as u64);
+                input_rlc = input_rlc * randomness + F::from(byte as u64);
+            }
+
+            rows.push(KeyRow {
+                update_idx: update_idx as u64,
+                is_storage,
+                input,
+                pos: pos as u64,
+                byte,
+                is_preimage,
+                input_value,
+                input_rlc,
+                nibble,
+                acc,
+            });
+            acc = acc_nibble(acc, pos as u64, nibble, randomness);
+        }
+    }
+    rows
+}
+
+/// Return the kinds of the nodes of `path`, which are in a storage trie after
+/// an account leaf.
+fn node_kinds(path: &[MptProofNode]) -> Vec<NodeKind> {
+    let mut is_storage = false;
+    path.iter()
+        .map(|node| {
+            let kind = NodeKind::of(&node.bytes, is_storage);
+            is_storage |= kind == NodeKind::AccountLeaf;
+            kind
+        })
+        .collect()
+}
+
+/// Return the item, the number of bytes left in the item, and the form of
+/// the item of each byte of a node of kind `kind`.
+fn node_items(kind: NodeKind, bytes: &[u8]) -> Vec<(u64, u64, ItemForm)> {
+    let mut items = Vec::new();
+    let mut item = 0;
+    while let Some(byte) = bytes.get(items.len()) {
+        let form = ItemForm::of(kind, item, *byte);
+        items.extend(
+            (0..form.len(*byte))
+                .rev()
+                .map(|item_left| (item, item_left, form)),
+        );
+        item += 1;
+    }
+    items.truncate(bytes.len());
+    items
+}
+
+/// Return the rows of the paths of `update`, the `update_idx`-th one of the
+/// block, before and after it.
+fn node_rows<F: Field>(update: &MptUpdate, update_idx: usize, randomness: F) -> Vec<NodeRow<F>> {
+    let rlc =
+        |word: Word| RandomLinearCombination::random_linear_combine(word.to_le_bytes(), randomness);
+    let table = update.table_assignment(randomness);
+    let field_tag = update.field_tag();
+    let keys = key_preimages(update)
+        .iter()
+        .map(|preimage| nibbles(Keccak256::digest(preimage).as_slice()))
+        .collect::<Vec<_>>();
+
+    // The nodes of both paths are compared down to the depth where the update
+    // changes the structure of the trie
+    let [old_kinds, new_kinds] =
+        [update.old_path.as_slice(), update.new_path.as_slice()].map(node_kinds);
+    let ends_with = |kinds: &[NodeKind], kind: NodeKind| kinds.last() == Some(&kind);
+    let (old_len, new_len) = (old_kinds.len() as u64, new_kinds.len() as u64);
+    let compared_depth = if old_len == new_len {
+        let ends_with_empty =
+            ends_with(&old_kinds, NodeKind::Empty) || ends_with(&new_kinds, NodeKind::Empty);
+        old_len - ends_with_empty as u64
+    } else {
+        let shorter = if new_len < old_len {
+            &new_kinds
+        } else {
+            &old_kinds
+        };
+        old_len.min(new_len) - 1 + ends_with(shorter, NodeKind::Branch) as u64
+    };
+
+    let mut rows = Vec::new();
+    for (is_new, root, value, path, kinds) in [
+        (
+            false,
+            update.old_root,
+            update.old_value,
+            &update.old_path,
+            &old_kinds,
+        ),
+        (
+            true,
+            update.new_root,
+            update.new_value,
+            &update.new_path,
+            &new_kinds,
+        ),
+    ] {
+        let path_values = [
+            table[ADDRESS],
+            table[STORAGE_KEY],
+            table[FIELD_TAG],
+            rlc(root),
+            rlc(value),
+            F::from(is_new as u64),
+            F::from(update_idx as u64),
+        ];
+        let hashes = path
+            .iter()
+            .map(|node| hash_rlc(&node.bytes, randomness))
+            .collect::<Vec<_>>();
+
+        // The nibbles of the key walked by the path in the trie of the node
+        let mut walked = Vec::new();
+        let mut is_storage = false;
+        for (depth, (node, kind)) in path.iter().zip(kinds.iter()).enumerate() {
+            let kind = *kind;
+            if depth > 0 && kinds[depth - 1] == NodeKind::AccountLeaf {
+                walked.clear();
+                is_storage = true;
+            }
+            let key = keys
+                .get(is_storage as usize)
+                .map(Vec::as_slice)
+                .unwrap_or(&[]);
+            let is_leaf = kind == NodeKind::AccountLeaf || kind == NodeKind::StorageLeaf;
+            let is_hp_node = kind == NodeKind::Extension || is_leaf;
+
+            let items = node_items(kind, &node.bytes);
+            let is_item_start = (0..items.len())
+                .map(|offset| offset == 0 || items[offset - 1].1 == 0)
+                .collect::<Vec<_>>();
+            let last_item = items.last().map(|(item, _, _)| *item).unwrap_or(0);
+            let target_item = match kind {
+                NodeKind::Branch => key
+                    .get(walked.len())
+                    .map(|nibble| *nibble as u64 + 1)
+                    .unwrap_or(0),
+                NodeKind::Extension => 2,
+                NodeKind::AccountLeaf => account_leaf_item(field_tag),
+                NodeKind::StorageLeaf => last_item,
+                NodeKind::Empty => 0,
+            };
+
+            // The flag of the hex-prefix encoded path is in the first payload
+            // byte of the item 1
+            let is_hp_flag = items
+                .iter()
+                .enumerate()
+                .map(|(offset, (item, _, form))| {
+                    is_hp_node
+                        && *item == 1
+                        && (*form == ItemForm::SingleByte
+                            || (*form == ItemForm::ShortString
+                                && !is_item_start[offset]
+                                && is_item_start[offset - 1]))
+                })
+                .collect::<Vec<_>>();
+            let is_hp_odd = is_hp_flag
+                .iter()
+                .zip(node.bytes.iter())
+                .any(|(is_hp_flag, byte)| *is_hp_flag && (byte >> 4) & 1 == 1);
+            let is_payload = items
+                .iter()
+                .zip(is_item_start.iter())
+                .map(|((_, _, form), is_item_start)| {
+                    *form == ItemForm::SingleByte
+                        || (*form == ItemForm::ShortString && !is_item_start)
+                })
+                .collect::<Vec<_>>();
+
+            // Walk the nibbles of the node, as on the rows of its bytes
+            let mut walks = Vec::new();
+            let mut path_acc = acc_nibbles(&walked, randomness);
+            for (offset, (byte, (item, _, _))) in node.bytes.iter().zip(items.iter()).enumerate() {
+                let is_hp_byte =
+                    is_hp_node && *item == 1 && is_payload[offset] && !is_hp_flag[offset];
+                let mut nibbles = Vec::new();
+                if offset > 0 {
+                    if kind == NodeKind::Branch && *item == target_item && is_item_start[offset] {
+                        nibbles.push(*item as u8 - 1);
+                    }
+                    if is_hp_flag[offset] && is_hp_odd {
+                        nibbles.push(byte & 0xf);
+                    }
+                    if is_hp_byte {
+                        nibbles.extend([byte >> 4, byte & 0xf]);
+                    }
+                }
+                for nibble in nibbles {
+                    path_acc = acc_nibble(path_acc, walked.len() as u64, nibble, randomness);
+                    walked.push(nibble);
+                }
+                walks.push((is_hp_byte, walked.len() as u64, path_acc));
+            }
+
+            let is_match = kind != NodeKind::Empty
+                && key.len() >= walked.len()
+                && key[..walked.len()] == walked[..];
+            let key_prefix = acc_nibbles(&key[..walked.len().min(key.len())], randomness);
+            let has_child = is_match
+                && (kind == NodeKind::Branch
+                    || kind == NodeKind::Extension
+                    || (kind == NodeKind::AccountLeaf && field_tag == MptFieldTag::Storage));
+            let is_compared = (depth as u64) < compared_depth;
+            let child_hash = hashes.get(depth + 1).copied().unwrap_or(F::zero());
+
+            let (mut node_rlc, mut child_rlc, mut value_rlc) = (F::zero(), F::zero(), F::zero());
+            let mut child_len = 0;
+            for (offset, byte) in node.bytes.iter().enumerate() {
+                let (item, item_left, form) = items[offset];
+                let (is_hp_byte, path_pos, path_acc) = walks[offset];
+                let is_target = item == target_item;
+                let is_child = is_target && is_payload[offset] && has_child;
+                let is_value = is_target && is_payload[offset] && is_leaf && is_match && !has_child;
+                let byte_value = F::from(*byte as u64);
+                node_rlc = node_rlc * randomness + byte_value;
+                if is_child {
+                    child_rlc = child_rlc * randomness + byte_value;
+                    child_len += 1;
+                }
+                if is_value {
+                    value_rlc = value_rlc * randomness + byte_value;
+                }
+
+                rows.push(NodeRow {
+                    path: path_values,
+                    path_len: path.len() as u64,
+                    ends_with_branch: ends_with(kinds, NodeKind::Branch),
+                    ends_with_empty: ends_with(kinds, NodeKind::Empty),
+                    compared_depth,
+                    len_diff: F::from(new_len) - F::from(old_len),
+                    is_new_shorter: new_len < old_len,
+                    is_path_start: depth == 0 && offset == 0,
+                    is_first_byte: offset == 0,
+                    byte: *byte,
+                    bytes_left: (node.bytes.len() - offset) as u64,
+                    node_len: node.bytes.len() as u64,
+                    node_rlc,
+                    node_hash: hashes[depth],
+                    kind,
+                    depth: depth as u64,
+                    is_storage,
+                    item,
+                    item_left,
+                    is_item_start: is_item_start[offset],
+                    form,
+                    is_last_item: item == last_item,
+                    target_item,
+                    is_hp_flag: is_hp_flag[offset],
+                    is_hp_byte,
+                    is_hp_odd,
+                    path_pos,
+                    path_acc,
+                    key_prefix,
+                    is_match,
+                    has_child,
+                    is_compared,
+                    is_compared_byte: is_compared
+                        && !is_target
+                        && (form == ItemForm::SingleByte || form == ItemForm::ShortString),
+                    is_child,
+                    child_len,
+                    child_rlc,
+                    child_hash,
+                    is_value,
+                    value_rlc,
+                });
+            }
+        }
+    }
+    rows
+}
+
+fn query<F: Field>(
+    meta: &mut VirtualCells<F>,
+    columns: &[Column<Advice>; MPT_TABLE_WIDTH],
+    rotation: Rotation,
+) -> [Expression<F>; MPT_TABLE_WIDTH] {
+    columns.map(|column| meta.query_advice(column, rotation))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bus_mapping::{
+        mpt::{MptKey, StateTrie},
+        operation::AccountField,
+        state_db::{Account, StateDB},
+    };
+    use eth_types::{address, Address, ToWord};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use pairing::bn256::Fr;
+
+    #[derive(Clone)]
+    struct MyConfig<F> {
+        keccak_table: [Column<Advice>; KECCAK_TABLE_WIDTH],
+        mpt: Config<F>,
+    }
+
+    #[derive(Default)]
+    struct MyCircuit<F> {
+        block: Block<F>,
+        size: usize,
+    }
+
+    impl<F: Field> MyCircuit<F> {
+        fn r() -> F {
+            F::from(123456)
+        }
+    }
+
+    impl<F: Field> Circuit<F> for MyCircuit<F> {
+        type Config = MyConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let keccak_table = [(); KECCAK_TABLE_WIDTH].map(|_| meta.advice_column());
+            MyConfig {
+                keccak_table,
+                mpt: Config::configure(meta, MyCircuit::r(), keccak_table),
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "keccak table",
+                |mut region| {
+                    let rows = std::iter::once([F::zero(); KECCAK_TABLE_WIDTH])
+                        .chain(keccak_table_assignments(&self.block));
+                    for (offset, row) in rows.enumerate() {
+                        for (column, value) in config.keccak_table.iter().zip(row) {
+                            region.assign_advice(
+                                || format!("keccak table row {}", offset),
+                                *column,
+                                offset,
+                                || Ok(value),
+                            )?;
+                        }
+                    }
+                    Ok(())
+                },
+            )?;
+            config.mpt.assign(&mut layouter, self.size, &self.block)
+        }
+    }
+
+    /// Verify the updates of `block`, against the state roots of `block`
+    /// unless they're given by `state_roots`.
+    fn verify<F: Field>(block: Block<F>, state_roots: Option<[Word; 2]>, success: bool) {
+        let k = 13;
+        let [prev_state_root, state_root] =
+            state_roots.unwrap_or([block.prev_state_root, block.state_root]);
+        let instance = [prev_state_root, state_root]
+            .iter()
+            .map(|root| {
+                RandomLinearCombination::random_linear_combine(root.to_le_bytes(), block.randomness)
+            })
+            .collect();
+        let circuit = MyCircuit::<F> {
+            block,
+            size: 2usize.pow(k),
+        };
+
+        let prover = MockProver::<F>::run(k, &circuit, vec![instance]).unwrap();
+        assert_eq!(prover.verify().is_ok(), success);
+    }
+
+    /// A block whose updates write and read the storage of an account, move
+    /// balance to a new account, and remove an account left empty.
+    fn block<F: Field>() -> Block<F> {
+        let [contract, sender, receiver, empty] = [
+            address!("0x00000000000000000000000000000000000000aa"),
+            address!("0x00000000000000000000000000000000000000bb"),
+            address!("0x00000000000000000000000000000000000000cc"),
+            address!("0x00000000000000000000000000000000000000dd"),
+        ];
+        let mut sdb = StateDB::new();
+        sdb.set_account(
+            &contract,
+            Account {
+                nonce: Word::one(),
+                storage: (1..4)
+                    .map(|key| (Word::from(key), Word::from(key)))
+                    .collect(),
+                ..Account::zero()
+            },
+        );
+        sdb.set_account(
+            &sender,
+            Account {
+                nonce: Word::from(5),
+                balance: Word::from(1000),
+                ..Account::zero()
+            },
+        );
+        sdb.set_account(
+            &empty,
+            Account {
+                balance: Word::from(1),
+                ..Account::zero()
+            },
+        );
+        let mut state_trie = StateTrie::from_state_db(&sdb);
+        let prev_state_root = state_trie.root();
+
+        let updates: [(Address, MptKey, u64); 7] = [
+            (contract, MptKey::Storage(Word::from(1)), 1),
+            (contract, MptKey::Storage(Word::from(2)), 0x1234),
+            (contract, MptKey::Storage(Word::from(3)), 0),
+            (contract, MptKey::Storage(Word::from(4)), 4),
+            (sender, MptKey::Account(AccountField::Balance), 900),
+            (receiver, MptKey::Account(AccountField::Balance), 100),
+            (empty, MptKey::Account(AccountField::Balance), 0),
+        ];
+        let mpt_updates = updates
+            .iter()
+            .map(|(address, key, value)| {
+                let update = state_trie
+                    .update(*address, key.clone(), Word::from(*value))
+                    .unwrap();
+                MptUpdate::from(&update)
+            })
+            .collect();
+
+        Block {
+            randomness: MyCircuit::r(),
+            prev_state_root: prev_state_root.to_word(),
+            state_root: state_trie.root().to_word(),
+            mpt_updates,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn mpt_valid() {
+        verify::<Fr>(block(), None, true);
+    }
+
+    #[test]
+    fn mpt_empty() {
+        verify::<Fr>(Block::default(), None, true);
+    }
+
+    #[test]
+    fn mpt_invalid_prev_state_root() {
+        let block = block::<Fr>();
+        let state_roots = [block.prev_state_root + Word::one(), block.state_root];
+        verify(block, Some(state_roots), false);
+    }
+
+    #[test]
+    fn mpt_invalid_state_root() {
+        let block = block::<Fr>();
+        let state_roots = [block.prev_state_root, block.state_root + Word::one()];
+        verify(block, Some(state_roots), false);
+    }
+
+    #[test]
+    fn mpt_invalid_new_value() {
+        let mut block = block::<Fr>();
+        block.mpt_updates[1].new_value = Word::from(0x1235);
+        verify(block, None, false);
+    }
+
+    #[test]
+    fn mpt_invalid_old_value() {
+        let mut block = block::<Fr>();
+        block.mpt_updates[4].old_value = Word::from(901);
+        verify(block, None, false);
+    }
+
+    #[test]
+    fn mpt_invalid_node() {
+        let mut block = block::<Fr>();
+        let node = block.mpt_updates[0].old_path.last_mut().unwrap();
+        *node.bytes.last_mut().unwrap() ^= 1;
+        verify(block, None, false);
+    }
+
+    #[test]
+    fn mpt_invalid_sibling() {
+        let mut block = block::<Fr>();
+        block.mpt_updates.truncate(1);
+        let update = &mut block.mpt_updates[0];
+        let root = &mut update.new_path[0];
+        let child = root.child.clone().unwrap();
+        // Change the hash of a child of the root branch off the path, which is
+        // the same before the update
+        let sibling = node_items(NodeKind::Branch, &root.bytes)
+            .iter()
+            .enumerate()
+            .position(|(offset, (item, item_left, _))| {
+                (1..=16).contains(item)
+                    && *item_left == HASH_LEN as u64
+                    && offset + 1 != child.start
+            })
+            .unwrap();
+        root.bytes[sibling + HASH_LEN] ^= 1;
+        let new_root = Word::from_big_endian(Keccak256::digest(&root.bytes).as_slice());
+        update.new_root = new_root;
+        block.state_root = new_root;
+        verify(block, None, false);
+    }
+
+    #[test]
+    fn mpt_invalid_key() {
+        let mut block = block::<Fr>();
+        block.mpt_updates[0].key = MptKey::Storage(Word::from(5));
+        verify(block, None, false);
+    }
+
+    #[test]
+    fn mpt_missing_update() {
+        let mut block = block::<Fr>();
+        block.mpt_updates.remove(2);
+        verify(block, None, false);
+    }
+}
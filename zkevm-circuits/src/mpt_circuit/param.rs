@@ -0,0 +1,26 @@
+/// The number of columns of the MPT table, which are `address`,
+/// `storage_key`, `field_tag`, `old_root`, `new_root`, `old_value` and
+/// `new_value`.
+pub const MPT_TABLE_WIDTH: usize = 7;
+
+/// The number of columns of the keccak table looked up for the hashes of the
+/// nodes, which are the random linear combination of the input, its length
+/// and the random linear combination of the hash as a word.
+pub const KECCAK_TABLE_WIDTH: usize = 3;
+
+/// The number of bytes of the hash of a node, which references it in its
+/// parent.
+pub const HASH_LEN: usize = 32;
+
+/// The number of nibbles of the keys of the tries, which are the hashes of
+/// the addresses and of the storage keys.
+pub const KEY_NIBBLES: usize = 64;
+
+/// The number of bytes of an address, which is hashed into the key of its
+/// account.
+pub const ADDRESS_LEN: usize = 20;
+
+/// The number of columns of the grammar table of the nodes, which are the
+/// kind of a node, the index of an item in it, the form of the item, its
+/// first byte and whether it's the last item of the node.
+pub const GRAMMAR_TABLE_WIDTH: usize = 5;
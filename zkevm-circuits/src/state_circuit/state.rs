@@ -1,12 +1,21 @@
 use crate::{
-    evm_circuit::{util::math_gadget::generate_lagrange_base_polynomial, witness::RwMap},
+    evm_circuit::{
+        table::{LookupTable, MptFieldTag},
+        util::math_gadget::generate_lagrange_base_polynomial,
+        witness::{MptUpdate, RwMap},
+    },
     gadget::{
         is_zero::{IsZeroChip, IsZeroConfig, IsZeroInstruction},
         monotone::{MonotoneChip, MonotoneConfig},
         Variable,
     },
+    mpt_circuit::param::MPT_TABLE_WIDTH,
+    util::Expr,
+};
+use bus_mapping::{
+    mpt,
+    operation::{MemoryOp, Operation, OperationContainer, StackOp, StorageOp},
 };
-use bus_mapping::operation::{MemoryOp, Operation, OperationContainer, StackOp, StorageOp};
 use eth_types::Field;
 use halo2_proofs::{
     circuit::{Layouter, Region, SimpleFloorPlanner},
@@ -115,6 +124,11 @@ pub struct Config<
     storage_key: Column<Advice>,
     storage_key_diff_inv: Column<Advice>,
     value_prev: Column<Advice>,
+    // Whether the row is the first or the last access to its storage slot in
+    // the block, which is looked up in the MPT table for the old and new
+    // values of the slot.
+    is_first_access: Column<Advice>,
+    is_last_access: Column<Advice>,
     rw_counter_table: Column<Fixed>,
     memory_address_table_zero: Column<Fixed>,
     stack_address_table_zero: Column<Fixed>,
@@ -147,7 +161,10 @@ impl<
     >
 {
     /// Set up custom gates and lookup arguments for this configuration.
-    pub(crate) fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+    pub(crate) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        mpt_table: impl LookupTable<F, MPT_TABLE_WIDTH>,
+    ) -> Self {
         let q_target = meta.fixed_column();
         let address = meta.advice_column();
         let address_diff_inv = meta.advice_column();
@@ -158,6 +175,8 @@ impl<
         let storage_key = meta.advice_column();
         let storage_key_diff_inv = meta.advice_column();
         let value_prev = meta.advice_column();
+        let is_first_access = meta.advice_column();
+        let is_last_access = meta.advice_column();
         let rw_counter_table = meta.fixed_column();
         let memory_address_table_zero = meta.fixed_column();
         let stack_address_table_zero = meta.fixed_column();
@@ -512,6 +531,95 @@ impl<
             )]
         });
 
+        meta.create_gate("Storage first and last access", |meta| {
+            let q_storage_first = q_storage_first(meta);
+            let q_storage_not_first = q_storage_not_first(meta);
+            // The row after the last storage row, where the storage rows end.
+            let q_storage_end = {
+                let q_target_cur = meta.query_fixed(q_target, Rotation::cur());
+                let q_target_prev = meta.query_fixed(q_target, Rotation::prev());
+                generate_lagrange_base_polynomial(q_target_cur, EMPTY_TAG, EMPTY_TAG..=STORAGE_TAG)
+                    * generate_lagrange_base_polynomial(
+                        q_target_prev,
+                        STORAGE_TAG,
+                        EMPTY_TAG..=STORAGE_TAG,
+                    )
+            };
+
+            let padding = meta.query_advice(padding, Rotation::cur());
+            let is_not_padding = one.clone() - padding;
+            let is_first_access = meta.query_advice(is_first_access, Rotation::cur());
+            let is_last_access_prev = meta.query_advice(is_last_access, Rotation::prev());
+            let is_same_slot = address_diff_is_zero.clone().is_zero_expression
+                * storage_key_diff_is_zero.clone().is_zero_expression;
+
+            vec![
+                // the first storage op is the first access to its slot
+                q_storage_first * is_not_padding.clone() * (one.clone() - is_first_access.clone()),
+                // an op is the first access to its slot iff the slot changes
+                q_storage_not_first.clone()
+                    * is_not_padding.clone()
+                    * (is_first_access - one.clone() + is_same_slot.clone()),
+                // the previous op is the last access to its slot iff the slot
+                // changes or the storage rows are padded from here
+                q_storage_not_first
+                    * (is_last_access_prev.clone() - one.clone() + is_not_padding * is_same_slot),
+                // the last storage row is the last access to its slot
+                q_storage_end * (is_last_access_prev - one.clone()),
+            ]
+        });
+
+        // The value before the first access to a slot and the value after the
+        // last access are the old and new values of its update in the MPT
+        // table, which proves the state root after the block.
+        for (name, is_last) in [
+            ("Storage first access in MPT table", false),
+            ("Storage last access in MPT table", true),
+        ] {
+            meta.lookup_any(name, |meta| {
+                // The accesses are only constrained on the storage rows, but
+                // setting them elsewhere only adds lookups to be satisfied.
+                let padding = meta.query_advice(padding, Rotation::cur());
+                let is_not_padding = one.clone() - padding;
+                let [
+                    address_table,
+                    storage_key_table,
+                    field_tag_table,
+                    _,
+                    _,
+                    old_value_table,
+                    new_value_table,
+                ] = mpt_table.table_exprs(meta);
+                let (is_access, value, value_table) = if is_last {
+                    (
+                        meta.query_advice(is_last_access, Rotation::cur()),
+                        meta.query_advice(value, Rotation::cur()),
+                        new_value_table,
+                    )
+                } else {
+                    (
+                        meta.query_advice(is_first_access, Rotation::cur()),
+                        meta.query_advice(value_prev, Rotation::cur()),
+                        old_value_table,
+                    )
+                };
+                let condition = is_not_padding * is_access;
+
+                vec![
+                    (
+                        condition.clone() * meta.query_advice(address, Rotation::cur()),
+                        address_table,
+                    ),
+                    (
+                        condition.clone() * meta.query_advice(storage_key, Rotation::cur()),
+                        storage_key_table,
+                    ),
+                    (condition.clone() * MptFieldTag::Storage.expr(), field_tag_table),
+                    (condition * value, value_table),
+                ]
+            });
+        }
+
         // TODO: monotone address for storage
 
         Config {
@@ -525,6 +633,8 @@ impl<
             storage_key,
             storage_key_diff_inv,
             value_prev,
+            is_first_access,
+            is_last_access,
             rw_counter_table,
             memory_address_table_zero,
             stack_address_table_zero,
@@ -770,6 +880,12 @@ impl<
             } else {
                 (F::zero(), F::zero())
             };
+            let is_first_access =
+                index == 0 || address != address_prev || storage_key != storage_key_prev;
+            let is_last_access = ops.get(index + 1).map_or(true, |next| {
+                let next_row = next.table_assignment(randomness);
+                address != next_row.key2 || storage_key != next_row.key3
+            });
 
             let bus_mapping = self.assign_op(
                 region,
@@ -792,6 +908,19 @@ impl<
                 Some(storage_key - storage_key_prev),
             )?;
 
+            region.assign_advice(
+                || "is first access",
+                self.is_first_access,
+                offset,
+                || Ok(F::from(is_first_access as u64)),
+            )?;
+            region.assign_advice(
+                || "is last access",
+                self.is_last_access,
+                offset,
+                || Ok(F::from(is_last_access as u64)),
+            )?;
+
             offset += 1;
         }
 
@@ -819,14 +948,29 @@ impl<
         // the first unused row and some checks would be triggered.
 
         for i in start_offset..end_offset {
-            let target = if need_pad_start_row && i == start_offset {
+            let q_target = if need_pad_start_row && i == start_offset {
                 START_TAG
             } else {
                 target
             };
-            region.assign_fixed(|| "target", self.q_target, i, || Ok(F::from(target as u64)))?;
+            region.assign_fixed(
+                || "target",
+                self.q_target,
+                i,
+                || Ok(F::from(q_target as u64)),
+            )?;
             region.assign_advice(|| "padding", self.padding, i, || Ok(F::one()))?;
             region.assign_advice(|| "memory", self.flag, i, || Ok(F::one()))?;
+            // Each storage padding row is constrained by the next one as the
+            // last access to its slot.
+            if target == STORAGE_TAG {
+                region.assign_advice(
+                    || "is last access",
+                    self.is_last_access,
+                    i,
+                    || Ok(F::one()),
+                )?;
+            }
         }
 
         Ok(())
@@ -1001,6 +1145,9 @@ pub struct StateCircuit<
     pub stack_ops: Vec<Rw>,
     /// Storage Operations
     pub storage_ops: Vec<Rw>,
+    /// MPT updates of the block, whose old and new values are the values of
+    /// the storage slots before and after the storage operations
+    pub mpt_updates: Vec<MptUpdate>,
 }
 
 impl<
@@ -1024,22 +1171,25 @@ impl<
         STORAGE_ROWS_MAX,
     >
 {
-    /// Use rw_map to build a StateCircuit instance
-    pub fn new_from_rw_map(randomness: F, rw_map: &RwMap) -> Self {
+    /// Use rw_map and mpt_updates to build a StateCircuit instance
+    pub fn new_from_rw_map(randomness: F, rw_map: &RwMap, mpt_updates: &[MptUpdate]) -> Self {
         Self {
             randomness,
             memory_ops: rw_map.sorted_memory_rw(),
             stack_ops: rw_map.sorted_stack_rw(),
             storage_ops: rw_map.sorted_storage_rw(),
+            mpt_updates: mpt_updates.to_vec(),
         }
     }
-    /// Use memory_ops, stack_ops, storage_ops to build a StateCircuit instance.
-    /// This method should be replaced with `new_from_rw_map` later.
+    /// Use memory_ops, stack_ops, storage_ops and mpt_updates to build a
+    /// StateCircuit instance.  This method should be replaced with
+    /// `new_from_rw_map` later.
     pub fn new(
         randomness: F,
         memory_ops: Vec<Operation<MemoryOp>>,
         stack_ops: Vec<Operation<StackOp>>,
         storage_ops: Vec<Operation<StorageOp>>,
+        mpt_updates: &[mpt::MptUpdate],
     ) -> Self {
         let rw_map = RwMap::from(&OperationContainer {
             memory: memory_ops,
@@ -1047,7 +1197,8 @@ impl<
             storage: storage_ops,
             ..Default::default()
         });
-        Self::new_from_rw_map(randomness, &rw_map)
+        let mpt_updates: Vec<_> = mpt_updates.iter().map(MptUpdate::from).collect();
+        Self::new_from_rw_map(randomness, &rw_map, &mpt_updates)
     }
 }
 
@@ -1072,16 +1223,19 @@ impl<
         STORAGE_ROWS_MAX,
     >
 {
-    type Config = Config<
-        F,
-        SANITY_CHECK,
-        RW_COUNTER_MAX,
-        MEMORY_ROWS_MAX,
-        MEMORY_ADDRESS_MAX,
-        STACK_ROWS_MAX,
-        STACK_ADDRESS_MAX,
-        STORAGE_ROWS_MAX,
-    >;
+    type Config = (
+        Config<
+            F,
+            SANITY_CHECK,
+            RW_COUNTER_MAX,
+            MEMORY_ROWS_MAX,
+            MEMORY_ADDRESS_MAX,
+            STACK_ROWS_MAX,
+            STACK_ADDRESS_MAX,
+            STORAGE_ROWS_MAX,
+        >,
+        [Column<Advice>; MPT_TABLE_WIDTH],
+    );
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
@@ -1089,15 +1243,33 @@ impl<
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        Config::configure(meta)
+        let mpt_table = [(); MPT_TABLE_WIDTH].map(|_| meta.advice_column());
+        (Config::configure(meta, mpt_table), mpt_table)
     }
 
     fn synthesize(
         &self,
-        config: Self::Config,
+        (config, mpt_table): Self::Config,
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
         config.load(&mut layouter)?;
+        layouter.assign_region(
+            || "mpt table",
+            |mut region| {
+                // The zero row is looked up by the rows without storage accesses
+                let rows = std::iter::once([F::zero(); MPT_TABLE_WIDTH]).chain(
+                    self.mpt_updates
+                        .iter()
+                        .map(|update| update.table_assignment(self.randomness)),
+                );
+                for (offset, row) in rows.enumerate() {
+                    for (column, value) in mpt_table.iter().zip(row) {
+                        region.assign_advice(|| "mpt table", *column, offset, || Ok(value))?;
+                    }
+                }
+                Ok(())
+            },
+        )?;
         config.assign(
             layouter,
             self.randomness,
@@ -1112,15 +1284,48 @@ impl<
 #[cfg(test)]
 mod tests {
     use super::*;
+    use bus_mapping::mpt::MptKey;
     use bus_mapping::operation::{MemoryOp, Operation, RWCounter, StackOp, StorageOp, RW};
     use eth_types::evm_types::{MemoryAddress, StackAddress};
-    use eth_types::{address, bytecode, Word};
+    use eth_types::{address, bytecode, Address, Hash, Word};
     use halo2_proofs::arithmetic::BaseExt;
     use halo2_proofs::dev::MockProver;
     use pairing::bn256::Fr;
+    use std::collections::BTreeMap;
+
+    // The MPT updates with the values of the storage slots before and after
+    // `storage_ops`.  The roots and paths are left empty, as they are only
+    // checked by the MPT circuit.
+    fn mpt_updates(storage_ops: &[Operation<StorageOp>]) -> Vec<mpt::MptUpdate> {
+        let mut storage_ops: Vec<_> = storage_ops.iter().collect();
+        storage_ops.sort_by_key(|op| op.rwc());
+        let mut values = BTreeMap::<(Address, Word), (Word, Word)>::new();
+        for op in storage_ops {
+            let op = op.op();
+            values
+                .entry((op.address, op.key))
+                .or_insert((op.value_prev, op.value))
+                .1 = op.value;
+        }
+        values
+            .into_iter()
+            .map(|((address, key), (old_value, new_value))| mpt::MptUpdate {
+                address,
+                key: MptKey::Storage(key),
+                old_value,
+                new_value,
+                old_root: Hash::zero(),
+                new_root: Hash::zero(),
+                old_path: vec![],
+                new_path: vec![],
+            })
+            .collect()
+    }
 
     macro_rules! test_state_circuit_ok {
         ($k:expr, $rw_counter_max:expr, $memory_rows_max:expr, $memory_address_max:expr, $stack_rows_max:expr, $stack_address_max:expr, $storage_rows_max:expr, $memory_ops:expr, $stack_ops:expr, $storage_ops:expr, $result:expr) => {{
+            let storage_ops: Vec<Operation<StorageOp>> = $storage_ops;
+            let mpt_updates = mpt_updates(&storage_ops);
             let circuit = StateCircuit::<
                 Fr,
                 true,
@@ -1130,7 +1335,13 @@ mod tests {
                 $stack_rows_max,
                 $stack_address_max,
                 $storage_rows_max,
-            >::new(Fr::rand(), $memory_ops, $stack_ops, $storage_ops);
+            >::new(
+                Fr::rand(),
+                $memory_ops,
+                $stack_ops,
+                storage_ops,
+                &mpt_updates,
+            );
 
             let prover = MockProver::<Fr>::run($k, &circuit, vec![]).unwrap();
             let verify_result = prover.verify();
@@ -1140,6 +1351,8 @@ mod tests {
 
     macro_rules! test_state_circuit_error {
         ($k:expr, $rw_counter_max:expr, $memory_rows_max:expr, $memory_address_max:expr, $stack_rows_max:expr, $stack_address_max:expr, $storage_rows_max:expr, $memory_ops:expr, $stack_ops:expr, $storage_ops:expr) => {{
+            let storage_ops: Vec<Operation<StorageOp>> = $storage_ops;
+            let mpt_updates = mpt_updates(&storage_ops);
             let circuit = StateCircuit::<
                 Fr,
                 false,
@@ -1149,7 +1362,13 @@ mod tests {
                 $stack_rows_max,
                 $stack_address_max,
                 $storage_rows_max,
-            >::new(Fr::rand(), $memory_ops, $stack_ops, $storage_ops);
+            >::new(
+                Fr::rand(),
+                $memory_ops,
+                $stack_ops,
+                storage_ops,
+                &mpt_updates,
+            );
 
             let prover = MockProver::<Fr>::run($k, &circuit, vec![]).unwrap();
             assert!(prover.verify().is_err());
@@ -1775,6 +1994,49 @@ mod tests {
         );
     }
 
+    #[test]
+    fn storage_mpt_update() {
+        let storage_op_0 = Operation::new(
+            RWCounter::from(18),
+            RW::WRITE,
+            StorageOp::new(
+                address!("0x0000000000000000000000000000000000000001"),
+                Word::from(0x40),
+                Word::from(32),
+                Word::from(0),
+            ),
+        );
+        let storage_op_1 = Operation::new(
+            RWCounter::from(19),
+            RW::WRITE,
+            StorageOp::new(
+                address!("0x0000000000000000000000000000000000000001"),
+                Word::from(0x40),
+                Word::from(33),
+                Word::from(32),
+            ),
+        );
+        let storage_ops = vec![storage_op_0, storage_op_1];
+
+        for (old_value, new_value) in [(1, 33), (0, 32)] {
+            let mut updates = mpt_updates(&storage_ops);
+            // The update has to be from the value before the first op to the
+            // value after the last op.
+            updates[0].old_value = Word::from(old_value);
+            updates[0].new_value = Word::from(new_value);
+
+            let circuit = StateCircuit::<Fr, false, 2000, 2, 1000, 2, 1023, 1000>::new(
+                Fr::rand(),
+                vec![],
+                vec![],
+                storage_ops.clone(),
+                &updates,
+            );
+            let prover = MockProver::<Fr>::run(14, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
     #[test]
     fn trace() {
         let bytecode = bytecode! {
@@ -19,8 +19,12 @@ pub fn get_fixed_table(conf: FixedTableConfig) -> Vec<FixedTableTag> {
                 FixedTableTag::Range32,
                 FixedTableTag::Range256,
                 FixedTableTag::Range512,
+                FixedTableTag::Range5,
                 FixedTableTag::SignByte,
                 FixedTableTag::ResponsibleOpcode,
+                FixedTableTag::StackPointerRange,
+                FixedTableTag::InvalidOpcode,
+                FixedTableTag::ConstantGasCost,
             ]
         }
         FixedTableConfig::Complete => FixedTableTag::iterator().collect(),
@@ -49,18 +53,38 @@ pub fn run_test_circuits(bytecode: eth_types::Bytecode) -> Result<(), Vec<Verify
     test_circuits_using_bytecode(bytecode, BytecodeTestConfig::default())
 }
 
+/// Run the test circuits on a tx which executes `caller`, with `callee`
+/// deployed at address 0x123, so that `caller` can call it.
+pub fn run_test_circuits_with_callee(
+    caller: eth_types::Bytecode,
+    callee: eth_types::Bytecode,
+) -> Result<(), Vec<VerifyFailure>> {
+    test_circuits_using_geth_data(
+        mock::new_single_tx_trace_code_2(&caller, &callee).unwrap(),
+        BytecodeTestConfig::default(),
+    )
+}
+
 pub fn test_circuits_using_bytecode(
     bytecode: eth_types::Bytecode,
     config: BytecodeTestConfig,
 ) -> Result<(), Vec<VerifyFailure>> {
     // execute the bytecode and get trace
-    let block_trace = bus_mapping::mock::BlockData::new_from_geth_data(
-        mock::new_single_tx_trace_code_gas(&bytecode, Gas(config.gas_limit)).unwrap(),
-    );
+    let geth_data = mock::new_single_tx_trace_code_gas(&bytecode, Gas(config.gas_limit)).unwrap();
+    test_circuits_using_geth_data(geth_data, config)
+}
+
+pub fn test_circuits_using_geth_data(
+    geth_data: eth_types::geth_types::GethData,
+    config: BytecodeTestConfig,
+) -> Result<(), Vec<VerifyFailure>> {
+    let block_trace = bus_mapping::mock::BlockData::new_from_geth_data(geth_data);
     let mut builder = block_trace.new_circuit_input_builder();
     builder
         .handle_tx(&block_trace.eth_tx, &block_trace.geth_trace)
         .unwrap();
+    builder.set_value_ops_call_context_rwc_eor();
+    builder.gen_mpt_updates().unwrap();
 
     // build a witness block from trace result
     let block = crate::evm_circuit::witness::block_convert(&builder.block, &builder.code_db);
@@ -88,6 +112,7 @@ pub fn test_circuits_using_witness_block(
             StateCircuit::<Fr, true, 2000, 100, 100, 100, 1023, 100>::new_from_rw_map(
                 block.randomness,
                 &block.rws,
+                &block.mpt_updates,
             );
         let prover = MockProver::<Fr>::run(12, &state_circuit, vec![]).unwrap();
         prover.verify()?;